@@ -0,0 +1,106 @@
+//! Trades advice columns for rows: range checks of [`NUM_VALUES`] 64-bit
+//! values with [`BitsConfig`] over 1 to 8 columns.
+//!
+//! Each extra column divides the rows of a decomposition, so the circuit fits
+//! a smaller `k`, which cuts keygen and proving time, until the extra column
+//! commitments and the wider `bit` gate outweigh the smaller domain. The
+//! byte columns of `WordConfig` and the groups of `BitwiseChip` trade the
+//! same way, but the `k >= 18` of the bitwise table hides it there.
+//!
+//! Criterion only reports timings, so the rows used and `k` of each layout
+//! are printed before its group runs.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use halo2_circuit_examples::{
+    circuits::gadgets::bits::BitsConfig,
+    dev::rows::{estimate_k, rows_used},
+    prover::{keygen, prove, ParamsStore},
+};
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    halo2curves::bn256::Fr,
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+};
+
+/// Values range checked by [`RangeCheckCircuit`].
+const NUM_VALUES: usize = 64;
+
+/// Bits of each value.
+const NUM_BITS: usize = 64;
+
+/// Range checks [`NUM_VALUES`] values with `NUM_COLUMNS` running sum columns.
+#[derive(Clone, Debug)]
+struct RangeCheckCircuit<const NUM_COLUMNS: usize> {
+    values: Value<[u64; NUM_VALUES]>,
+}
+
+impl<const NUM_COLUMNS: usize> Circuit<Fr> for RangeCheckCircuit<NUM_COLUMNS> {
+    type Config = (BitsConfig<NUM_COLUMNS>, Column<Advice>);
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            values: Value::unknown(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let value = meta.advice_column();
+        let acc = [(); NUM_COLUMNS].map(|_| meta.advice_column());
+        meta.enable_equality(value);
+        (BitsConfig::configure_columns(meta, acc), value)
+    }
+
+    fn synthesize(&self, (bits, value): Self::Config, mut layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        let values = layouter.assign_region(
+            || "values",
+            |mut region| {
+                (0..NUM_VALUES)
+                    .map(|i| {
+                        let value_i = self.values.map(|values| Fr::from(values[i]));
+                        region.assign_advice(|| "value", value, i, || value_i)
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            },
+        )?;
+        bits.running_sums(layouter.namespace(|| "range checks"), &values, NUM_BITS)?;
+        Ok(())
+    }
+}
+
+fn bench_layout<const NUM_COLUMNS: usize>(c: &mut Criterion, store: &ParamsStore) {
+    let circuit = RangeCheckCircuit::<NUM_COLUMNS> {
+        values: Value::known(std::array::from_fn(|i| u64::MAX - i as u64)),
+    };
+    let k = estimate_k(&circuit).unwrap();
+    println!(
+        "{NUM_COLUMNS} columns: {} rows used, k = {k}",
+        rows_used(&circuit).unwrap()
+    );
+
+    let params = store.get(k).unwrap();
+    let pk = keygen(&params, &circuit).unwrap();
+
+    let mut group = c.benchmark_group(format!("{NUM_COLUMNS} columns"));
+    group.sample_size(10);
+    group.bench_function(BenchmarkId::new("keygen", k), |b| {
+        b.iter(|| keygen(&params, &circuit).unwrap())
+    });
+    group.bench_function(BenchmarkId::new("prove", k), |b| {
+        b.iter(|| prove(&params, &pk, circuit.clone(), &[]).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_columns(c: &mut Criterion) {
+    let store = ParamsStore::default();
+    bench_layout::<1>(c, &store);
+    bench_layout::<2>(c, &store);
+    bench_layout::<4>(c, &store);
+    bench_layout::<8>(c, &store);
+}
+
+criterion_group!(benches, bench_columns);
+criterion_main!(benches);
@@ -0,0 +1,48 @@
+//! Keygen, witness generation, proving and verification of every registered
+//! example circuit at its smallest `k` and a few above.
+//!
+//! Witness generation is timed as a run of the mock prover, which synthesizes
+//! the circuit into its own assignment without committing to it. Criterion
+//! only reports timings, so the rows used by each circuit are printed before
+//! its group runs.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use halo2_circuit_examples::{
+    prover::{verify, ParamsStore},
+    registry::iter_circuits,
+};
+
+/// Number of `k` benchmarked per circuit, starting at its smallest `k`.
+const NUM_K: u32 = 3;
+
+fn bench_examples(c: &mut Criterion) {
+    let store = ParamsStore::default();
+
+    for entry in iter_circuits() {
+        let circuit = (entry.build)(&(entry.sample_input)()).unwrap();
+        let instances = circuit.instances();
+        println!("{}: {} rows used", entry.name, circuit.rows_used().unwrap());
+
+        let min_k = entry.k().unwrap();
+        let mut group = c.benchmark_group(entry.name);
+        group.sample_size(10);
+        for k in min_k..min_k + NUM_K {
+            let params = store.get(k).unwrap();
+            let pk = circuit.keygen(&params).unwrap();
+            let proof = circuit.prove(&params, &pk).unwrap();
+
+            group.bench_function(BenchmarkId::new("keygen", k), |b| b.iter(|| circuit.keygen(&params).unwrap()));
+            group.bench_function(BenchmarkId::new("witness", k), |b| b.iter(|| circuit.mock_prover(k).unwrap()));
+            group.bench_function(BenchmarkId::new("prove", k), |b| {
+                b.iter(|| circuit.prove(&params, &pk).unwrap())
+            });
+            group.bench_function(BenchmarkId::new("verify", k), |b| {
+                b.iter(|| verify(&params, pk.get_vk(), &proof, &instances).unwrap())
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_examples);
+criterion_main!(benches);
@@ -0,0 +1,123 @@
+//! Compares Poseidon2 against classic Poseidon at width 3: the native
+//! permutation, and keygen and proving of a circuit hashing [`NUM_INPUTS`]
+//! elements with either chip.
+//!
+//! Criterion only reports timings, so the rows used by each circuit are
+//! printed before the groups run.
+
+use std::marker::PhantomData;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use halo2_circuit_examples::{
+    circuits::gadgets::{
+        hash::{HashInstructions, HashSpec},
+        poseidon::{self, PoseidonChip},
+        poseidon2::{self, Poseidon2Chip},
+    },
+    dev::rows::{estimate_k, rows_used},
+    prover::{keygen, prove},
+};
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    halo2curves::bn256::{Bn256, Fr},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+};
+use rand::rngs::OsRng;
+
+/// Inputs hashed by [`HashCircuit`], absorbed in four permutations.
+const NUM_INPUTS: usize = 8;
+
+/// Exposes the hash of `inputs` computed with `H`.
+#[derive(Clone, Debug)]
+struct HashCircuit<H> {
+    inputs: Value<[Fr; NUM_INPUTS]>,
+    _marker: PhantomData<H>,
+}
+
+impl<H> HashCircuit<H> {
+    fn new(inputs: Value<[Fr; NUM_INPUTS]>) -> Self {
+        Self {
+            inputs,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<H: HashInstructions<Fr>> Circuit<Fr> for HashCircuit<H> {
+    type Config = (H::Config, Column<Advice>, Column<Instance>);
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(Value::unknown())
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let [input, s0, s1, s2, i0, i1] = [(); 6].map(|_| meta.advice_column());
+        let constant = meta.fixed_column();
+        let hash = H::configure(meta, [s0, s1, s2], [i0, i1], constant);
+        let instance = meta.instance_column();
+        meta.enable_equality(input);
+        meta.enable_equality(instance);
+        (hash, input, instance)
+    }
+
+    fn synthesize(
+        &self,
+        (config, input, instance): Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let inputs = layouter.assign_region(
+            || "inputs",
+            |mut region| {
+                (0..NUM_INPUTS)
+                    .map(|i| region.assign_advice(|| "input", input, i, || self.inputs.map(|inputs| inputs[i])))
+                    .collect::<Result<Vec<_>, _>>()
+            },
+        )?;
+
+        let hash = H::construct(config).hash(layouter.namespace(|| "hash"), &inputs)?;
+        layouter.constrain_instance(hash.cell(), instance, 0)
+    }
+}
+
+fn bench_hash<H: HashInstructions<Fr>>(c: &mut Criterion, name: &str, params: &ParamsKZG<Bn256>) {
+    let inputs: [Fr; NUM_INPUTS] = std::array::from_fn(|i| Fr::from(i as u64));
+    let instances = vec![vec![H::Spec::default().hash(&inputs)]];
+    let circuit = HashCircuit::<H>::new(Value::known(inputs));
+    println!("{name}: {} rows used", rows_used(&circuit).unwrap());
+    let pk = keygen(params, &circuit).unwrap();
+
+    let mut group = c.benchmark_group(name);
+    group.sample_size(10);
+    group.bench_function(BenchmarkId::new("keygen", params.k()), |b| {
+        b.iter(|| keygen(params, &circuit).unwrap())
+    });
+    group.bench_function(BenchmarkId::new("prove", params.k()), |b| {
+        b.iter(|| prove(params, &pk, circuit.clone(), &instances).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_poseidon2(c: &mut Criterion) {
+    let poseidon = poseidon::Spec::<Fr>::new();
+    let poseidon2 = poseidon2::Spec::<Fr>::new();
+    let state = [1, 2, 3].map(Fr::from);
+    let mut group = c.benchmark_group("permutation");
+    group.bench_function("poseidon", |b| b.iter(|| poseidon.permute(&mut state.clone())));
+    group.bench_function("poseidon2", |b| b.iter(|| poseidon2.permute(&mut state.clone())));
+    group.finish();
+
+    // Both circuits at the same `k`, the one of the larger.
+    let k = estimate_k(&HashCircuit::<PoseidonChip<Fr>>::new(Value::unknown()))
+        .unwrap()
+        .max(estimate_k(&HashCircuit::<Poseidon2Chip<Fr>>::new(Value::unknown())).unwrap());
+    let params = ParamsKZG::<Bn256>::setup(k, OsRng);
+    bench_hash::<PoseidonChip<Fr>>(c, "poseidon", &params);
+    bench_hash::<Poseidon2Chip<Fr>>(c, "poseidon2", &params);
+}
+
+criterion_group!(benches, bench_poseidon2);
+criterion_main!(benches);
@@ -0,0 +1,109 @@
+//! Compares verifying proofs one by one against `verify_batch`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use halo2_circuit_examples::{
+    dev::rows::estimate_k,
+    prover::{keygen, prove, verify, verify_batch},
+};
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    halo2curves::bn256::{Bn256, Fr},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance, Selector},
+    poly::{kzg::commitment::ParamsKZG, Rotation},
+};
+use rand::rngs::OsRng;
+
+#[derive(Clone, Debug)]
+struct MulConfig {
+    advice: [Column<Advice>; 3],
+    instance: Column<Instance>,
+    s_mul: Selector,
+}
+
+#[derive(Default)]
+struct MulCircuit {
+    a: Value<Fr>,
+    b: Value<Fr>,
+}
+
+impl Circuit<Fr> for MulCircuit {
+    type Config = MulConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let advice = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+        let instance = meta.instance_column();
+        let s_mul = meta.selector();
+
+        meta.enable_equality(advice[2]);
+        meta.enable_equality(instance);
+
+        meta.create_gate("mul", |meta| {
+            let s_mul = meta.query_selector(s_mul);
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            let b = meta.query_advice(advice[1], Rotation::cur());
+            let out = meta.query_advice(advice[2], Rotation::cur());
+            vec![s_mul * (a * b - out)]
+        });
+
+        MulConfig {
+            advice,
+            instance,
+            s_mul,
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        let out = layouter.assign_region(
+            || "mul",
+            |mut region| {
+                config.s_mul.enable(&mut region, 0)?;
+                region.assign_advice(|| "a", config.advice[0], 0, || self.a)?;
+                region.assign_advice(|| "b", config.advice[1], 0, || self.b)?;
+                region.assign_advice(|| "out", config.advice[2], 0, || self.a * self.b)
+            },
+        )?;
+
+        layouter.constrain_instance(out.cell(), config.instance, 0)
+    }
+}
+
+fn bench_verify_batch(c: &mut Criterion) {
+    let params = ParamsKZG::<Bn256>::setup(estimate_k(&MulCircuit::default()).unwrap(), OsRng);
+    let pk = keygen(&params, &MulCircuit::default()).unwrap();
+
+    let mut group = c.benchmark_group("verify");
+    for n in [1u64, 4, 16] {
+        let proofs: Vec<_> = (0..n)
+            .map(|i| {
+                let circuit = MulCircuit {
+                    a: Value::known(Fr::from(i)),
+                    b: Value::known(Fr::from(i + 1)),
+                };
+                let instances = vec![vec![Fr::from(i * (i + 1))]];
+                (prove(&params, &pk, circuit, &instances).unwrap(), instances)
+            })
+            .collect();
+
+        group.bench_with_input(BenchmarkId::new("individual", n), &proofs, |b, proofs| {
+            b.iter(|| {
+                for (proof, instances) in proofs {
+                    verify(&params, pk.get_vk(), proof, instances).unwrap();
+                }
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("batch", n), &proofs, |b, proofs| {
+            b.iter(|| verify_batch(&params, pk.get_vk(), proofs).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_verify_batch);
+criterion_main!(benches);
@@ -0,0 +1,67 @@
+//! Witness generation of the range check running sums, row by row as the
+//! gadgets used to compute them and in parallel as they do now.
+//!
+//! A single decomposition of a full field element is split over a few tasks
+//! only, the speedup shows with batches like the words of a RIPEMD-160 block
+//! or the amounts of a large commitment.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use halo2_circuit_examples::circuits::gadgets::bits::running_sum_values;
+use halo2_proofs::halo2curves::{
+    bn256::Fr,
+    group::ff::{Field, PrimeField},
+};
+use rand::rngs::OsRng;
+use rayon::prelude::*;
+
+/// Values decomposed by the batch benchmarks.
+const BATCH: usize = 1024;
+
+/// The running sum of `value` over `num_bits` bits, halving it row by row.
+fn sequential_running_sum(value: Fr, num_bits: usize) -> Vec<Fr> {
+    let mut accs = vec![value];
+    for _ in 0..num_bits {
+        let acc = accs[accs.len() - 1];
+        let bit = Fr::from((acc.to_repr()[0] & 1) as u64);
+        accs.push((acc - bit) * Fr::from(2).invert().unwrap());
+    }
+    accs
+}
+
+fn bench_running_sum(c: &mut Criterion) {
+    let mut group = c.benchmark_group("running sum");
+    for num_bits in [32, 64, Fr::NUM_BITS as usize] {
+        let value = Fr::random(OsRng);
+        group.bench_function(BenchmarkId::new("sequential", num_bits), |b| {
+            b.iter(|| sequential_running_sum(value, num_bits))
+        });
+        group.bench_function(BenchmarkId::new("parallel", num_bits), |b| {
+            b.iter(|| running_sum_values(&value, 1, num_bits))
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("running sum batch");
+    group.sample_size(10);
+    for num_bits in [32, 64] {
+        let values: Vec<_> = (0..BATCH).map(|_| Fr::from(rand::random::<u64>())).collect();
+        group.bench_function(BenchmarkId::new("sequential", num_bits), |b| {
+            b.iter(|| {
+                (values.iter())
+                    .map(|value| sequential_running_sum(*value, num_bits))
+                    .collect::<Vec<_>>()
+            })
+        });
+        group.bench_function(BenchmarkId::new("parallel", num_bits), |b| {
+            b.iter(|| {
+                (values.par_iter())
+                    .map(|value| running_sum_values(value, 1, num_bits))
+                    .collect::<Vec<_>>()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_running_sum);
+criterion_main!(benches);
@@ -0,0 +1,316 @@
+//! Command line interface to the example circuits of the registry.
+//!
+//! ```text
+//! halo2-examples list
+//! halo2-examples keygen <circuit>
+//! halo2-examples prove <circuit> --input inputs.json
+//! halo2-examples verify <circuit> --proof proof.bin --instances instances.json
+//! halo2-examples stats [circuit] [--json] [--regions|--planners]
+//! halo2-examples dump-gates <circuit> [--json]
+//! halo2-examples dump-witness <circuit> --input inputs.json [--out witness.csv]
+//! halo2-examples vk-hash [circuit] [--bless]
+//! halo2-examples render [circuit] [--out-dir dir] [--svg]   (dev-graph feature)
+//! ```
+//!
+//! With the `trace` feature, `--timings` prints the time spent in each phase,
+//! see [`halo2_circuit_examples::trace`]. With the `circuit-params` feature,
+//! `--param range=32` or `--param depth=10` sizes the circuits taking params,
+//! see [`CircuitEntry::with_params`]. Their keys are cached apart from the
+//! ones of the default sizes.
+//!
+//! See [`halo2_circuit_examples::registry`] for the JSON formats.
+
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+use clap::{Parser, Subcommand};
+use halo2_circuit_examples::{
+    dev::{
+        expr::{gates_text, GateDump},
+        golden,
+        planner::planner_table,
+        stats::{region_table, table, CircuitStats},
+        vk::{entry_vk_hash, golden_file},
+        witness::witness_csv,
+    },
+    prover::{verify, KeyCache, ParamsStore},
+    registry::{find_circuit, instances_from_json, instances_to_json, iter_circuits, CircuitEntry},
+};
+use serde_json::Value as Json;
+
+#[derive(Parser)]
+#[command(name = "halo2-examples", about = "Key generation, proving and verification of the example circuits")]
+struct Cli {
+    /// Directory of the cached proving and verifying keys.
+    #[arg(long, env = "HALO2_KEY_CACHE", default_value = "target/halo2-keys")]
+    keys: PathBuf,
+
+    /// Directory of the cached KZG params.
+    #[arg(long, env = "HALO2_PARAMS_DIR", default_value = "target/halo2-params")]
+    params: PathBuf,
+
+    /// Size of the circuit, such as `range=32`, repeated for each param.
+    #[arg(long = "param", value_name = "NAME=VALUE", global = true)]
+    circuit_params: Vec<String>,
+
+    /// Print the time spent in each phase to stderr.
+    #[cfg(feature = "trace")]
+    #[arg(long, global = true)]
+    timings: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List the available circuits.
+    List,
+    /// Generate the keys of a circuit, or load them from the cache.
+    Keygen {
+        circuit: String,
+    },
+    /// Prove a circuit for the inputs of a JSON file.
+    Prove {
+        circuit: String,
+        /// JSON file with the circuit inputs.
+        #[arg(long)]
+        input: PathBuf,
+        /// Where to write the proof.
+        #[arg(long, default_value = "proof.bin")]
+        proof: PathBuf,
+        /// Where to write the instances.
+        #[arg(long, default_value = "instances.json")]
+        instances: PathBuf,
+    },
+    /// Verify a proof against its instances.
+    Verify {
+        circuit: String,
+        /// File with the proof.
+        #[arg(long)]
+        proof: PathBuf,
+        /// JSON file with the instances.
+        #[arg(long)]
+        instances: PathBuf,
+    },
+    /// Print column, gate and proof size statistics, of all circuits by default.
+    Stats {
+        circuit: Option<String>,
+        /// Print JSON instead of a table.
+        #[arg(long)]
+        json: bool,
+        /// Print the rows and cells used per region instead, largest first.
+        #[arg(long, conflicts_with = "json")]
+        regions: bool,
+        /// Print the rows used under each floor planner instead.
+        #[arg(long, conflicts_with_all = ["json", "regions"])]
+        planners: bool,
+    },
+    /// Print the gates of a circuit as polynomials.
+    DumpGates {
+        circuit: String,
+        /// Print JSON instead of text.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Dump the advice and fixed values assigned for the inputs of a JSON file as CSV.
+    DumpWitness {
+        circuit: String,
+        /// JSON file with the circuit inputs.
+        #[arg(long)]
+        input: PathBuf,
+        /// Where to write the CSV, instead of the standard output.
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Print the hashes of the verifying keys pinned by the tests, of all circuits by default.
+    VkHash {
+        circuit: Option<String>,
+        /// Rewrite the golden files with the current hashes.
+        #[arg(long)]
+        bless: bool,
+    },
+    /// Render the layout of the circuits, of all circuits by default.
+    #[cfg(feature = "dev-graph")]
+    Render {
+        circuit: Option<String>,
+        /// Directory of the rendered diagrams.
+        #[arg(long, default_value = halo2_circuit_examples::dev::layout::LAYOUT_DIR)]
+        out_dir: PathBuf,
+        /// Render SVG instead of PNG.
+        #[arg(long)]
+        svg: bool,
+    },
+}
+
+fn main() {
+    let cli = Cli::parse();
+    #[cfg(feature = "trace")]
+    if cli.timings {
+        halo2_circuit_examples::trace::init_timings();
+    }
+
+    if let Err(err) = run(&cli) {
+        eprintln!("error: {err}");
+        process::exit(1);
+    }
+}
+
+/// The named circuit, sized by `sizes`.
+fn entry(name: &str, sizes: &Json) -> Result<CircuitEntry, String> {
+    let entry = find_circuit(name).ok_or_else(|| format!("unknown circuit `{name}`, see `list`"))?;
+    entry.with_params(sizes)
+}
+
+/// The named circuit, or all of them, sized by `sizes`.
+fn entries(name: Option<&str>, sizes: &Json) -> Result<Vec<CircuitEntry>, String> {
+    match name {
+        Some(name) => Ok(vec![entry(name, sizes)?]),
+        None => iter_circuits().map(|entry| entry.with_params(sizes)).collect(),
+    }
+}
+
+/// Parses `NAME=VALUE` params into a JSON object of sizes.
+fn parse_sizes(params: &[String]) -> Result<Json, String> {
+    (params.iter())
+        .map(|param| {
+            let (name, value) = param
+                .split_once('=')
+                .ok_or_else(|| format!("expected NAME=VALUE, got `{param}`"))?;
+            let value: usize = value
+                .parse()
+                .map_err(|_| format!("param `{name}` must be an integer, got `{value}`"))?;
+            Ok((name.to_string(), Json::from(value)))
+        })
+        .collect()
+}
+
+fn run(cli: &Cli) -> Result<(), Box<dyn Error>> {
+    let params =
+        |entry: &CircuitEntry| -> Result<_, Box<dyn Error>> { Ok(ParamsStore::new(&cli.params).get(entry.k()?)?) };
+    let keys = KeyCache::new(&cli.keys);
+    let sizes = parse_sizes(&cli.circuit_params)?;
+
+    match &cli.command {
+        Command::List => {
+            for entry in iter_circuits() {
+                println!("{:<16} k = {:<3} {}", entry.name, entry.k()?, entry.description);
+            }
+        }
+        Command::Keygen { circuit } => {
+            let entry = entry(circuit, &sizes)?;
+            (entry.without_witnesses)().pk(&keys, &entry.key_name(), &params(&entry)?)?;
+            println!("keys of `{}` are in {}", entry.name, keys.dir().display());
+        }
+        Command::Prove {
+            circuit,
+            input,
+            proof,
+            instances,
+        } => {
+            let entry = entry(circuit, &sizes)?;
+            let params = params(&entry)?;
+            let pk = (entry.without_witnesses)().pk(&keys, &entry.key_name(), &params)?;
+            let circuit = (entry.build)(&read_json(input)?)?;
+            fs::write(proof, circuit.prove(&params, &pk)?)?;
+            fs::write(instances, instances_to_json(&circuit.instances()).to_string())?;
+            println!("wrote {} and {}", proof.display(), instances.display());
+        }
+        Command::Verify {
+            circuit,
+            proof,
+            instances,
+        } => {
+            let entry = entry(circuit, &sizes)?;
+            let params = params(&entry)?;
+            let vk = (entry.without_witnesses)().vk(&keys, &entry.key_name(), &params)?;
+            let instances = instances_from_json(&read_json(instances)?)?;
+            verify(&params, &vk, &fs::read(proof)?, &instances)?;
+            println!("proof is valid");
+        }
+        Command::Stats {
+            circuit,
+            json,
+            regions,
+            planners,
+        } => {
+            if *planners {
+                let rows = entries(circuit.as_deref(), &sizes)?
+                    .iter()
+                    .map(|entry| Ok((entry.name, (entry.without_witnesses)().planner_rows()?)))
+                    .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+                print!("{}", planner_table(&rows));
+                return Ok(());
+            }
+            if *regions {
+                for entry in entries(circuit.as_deref(), &sizes)? {
+                    let regions = (entry.without_witnesses)().region_stats()?;
+                    println!("{}:\n{}", entry.name, region_table(&regions));
+                }
+                return Ok(());
+            }
+
+            let stats = entries(circuit.as_deref(), &sizes)?
+                .iter()
+                .map(|entry| (entry.without_witnesses)().stats(entry.name, entry.k()?))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if *json {
+                let stats: Vec<_> = stats.iter().map(CircuitStats::to_json).collect();
+                println!("{}", serde_json::to_string_pretty(&stats)?);
+            } else {
+                print!("{}", table(&stats));
+            }
+        }
+        Command::DumpGates { circuit, json } => {
+            let gates = (entry(circuit, &sizes)?.without_witnesses)().gates();
+            if *json {
+                let gates: Vec<_> = gates.iter().map(GateDump::to_json).collect();
+                println!("{}", serde_json::to_string_pretty(&gates)?);
+            } else {
+                print!("{}", gates_text(&gates));
+            }
+        }
+        Command::DumpWitness { circuit, input, out } => {
+            let circuit = (entry(circuit, &sizes)?.build)(&read_json(input)?)?;
+            let csv = witness_csv(&circuit.witness()?);
+            match out {
+                Some(out) => {
+                    fs::write(out, csv)?;
+                    println!("wrote {}", out.display());
+                }
+                None => print!("{csv}"),
+            }
+        }
+        Command::VkHash { circuit, bless } => {
+            if *bless && !cli.circuit_params.is_empty() {
+                return Err("only the keys of the default params are pinned".into());
+            }
+            for entry in entries(circuit.as_deref(), &sizes)? {
+                let hash = entry_vk_hash(&entry)?;
+                if *bless {
+                    golden::bless(&golden_file(entry.name), &format!("{hash}\n"))?;
+                }
+                println!("{:<16} {hash}", entry.name);
+            }
+        }
+        #[cfg(feature = "dev-graph")]
+        Command::Render { circuit, out_dir, svg } => {
+            for entry in entries(circuit.as_deref(), &sizes)? {
+                let path = out_dir.join(format!("{}.{}", entry.key_name(), if *svg { "svg" } else { "png" }));
+                (entry.without_witnesses)().render(&path, entry.name, entry.k()?)?;
+                println!("wrote {}", path.display());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn read_json(path: &Path) -> Result<Json, Box<dyn Error>> {
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
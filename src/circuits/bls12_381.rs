@@ -0,0 +1,239 @@
+//! Arithmetic over the base field of BLS12-381 in a bn256 circuit, the
+//! foundation of BLS signature verification.
+//!
+//! An element of the 381-bit [`Fq`] doesn't fit in a native cell. The integer
+//! chip of halo2wrong, which the ECC chip of [`crate::circuits::verifier`] is
+//! built on, splits it into `LIMBS` limbs of `BITS` bits and checks each
+//! operation twice: limb by limb modulo `2^(LIMBS * BITS)`, and modulo the
+//! native modulus. By the CRT, the operation then holds over the integers as
+//! long as the product of two elements stays below both moduli multiplied,
+//! so `LIMBS * BITS` must exceed `2 * 381 - 254` bits. The `4 * 68` limbs of
+//! the verifier are too short; the defaults below keep its `68` bit limbs,
+//! and its range table, with twice as many.
+//!
+//! halo2curves has no BLS12-381, so [`Fq`] is derived with `ff`.
+
+use std::rc::Rc;
+
+use ff::PrimeField;
+use halo2_proofs::{
+    circuit::{Layouter, Value},
+    halo2curves::bn256::Fr,
+    plonk::{self, ConstraintSystem},
+};
+use snark_verifier::loader::halo2::halo2_wrong_ecc::{
+    integer::{
+        self, rns::Rns, AssignedInteger, Integer, IntegerConfig, IntegerInstructions, Range, NUMBER_OF_LOOKUP_LIMBS,
+    },
+    maingate::{MainGate, MainGateConfig, RangeChip, RangeConfig, RangeInstructions, RegionCtx},
+};
+
+/// The base field of BLS12-381.
+#[derive(PrimeField)]
+#[PrimeFieldModulus = "4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559787"]
+#[PrimeFieldGenerator = "2"]
+#[PrimeFieldReprEndianness = "little"]
+pub struct Fq([u64; 6]);
+
+/// Default number of limbs an [`Fq`] element is split into.
+pub const LIMBS: usize = 8;
+/// Default bit size of each limb.
+pub const BITS: usize = 68;
+
+/// An [`Fq`] element assigned as `LIMBS` limbs of `BITS` bits.
+pub type AssignedFq<const LIMBS: usize = 8, const BITS: usize = 68> = AssignedInteger<Fq, Fr, LIMBS, BITS>;
+
+type IntegerChip<const LIMBS: usize, const BITS: usize> = integer::IntegerChip<Fq, Fr, LIMBS, BITS>;
+
+/// Config of [`FqChip`]: a main gate and the range chip of the limbs.
+#[derive(Clone, Debug)]
+pub struct FqConfig<const LIMBS: usize = 8, const BITS: usize = 68> {
+    main_gate_config: MainGateConfig,
+    range_config: RangeConfig,
+}
+
+impl<const LIMBS: usize, const BITS: usize> FqConfig<LIMBS, BITS> {
+    /// Configures the main gate and the range chip for [`Fq`] elements split
+    /// into `LIMBS` limbs of `BITS` bits.
+    pub fn configure(meta: &mut ConstraintSystem<Fr>) -> Self {
+        let main_gate_config = MainGate::<Fr>::configure(meta);
+        let range_config = RangeChip::<Fr>::configure(
+            meta,
+            &main_gate_config,
+            vec![BITS / NUMBER_OF_LOOKUP_LIMBS],
+            Rns::<Fq, Fr, LIMBS, BITS>::construct().overflow_lengths(),
+        );
+
+        Self {
+            main_gate_config,
+            range_config,
+        }
+    }
+
+    /// Fills the range table, once per circuit.
+    pub fn load(&self, layouter: &mut impl Layouter<Fr>) -> Result<(), plonk::Error> {
+        RangeChip::new(self.range_config.clone()).load_table(layouter)
+    }
+}
+
+/// Adds, multiplies and inverts [`Fq`] elements.
+///
+/// Results are reduced lazily: a sum may exceed the modulus, and is only
+/// reduced when it is multiplied or compared.
+#[derive(Clone)]
+pub struct FqChip<const LIMBS: usize = 8, const BITS: usize = 68> {
+    integer_chip: IntegerChip<LIMBS, BITS>,
+}
+
+impl<const LIMBS: usize, const BITS: usize> FqChip<LIMBS, BITS> {
+    /// The chip of `config`.
+    pub fn new(config: &FqConfig<LIMBS, BITS>) -> Self {
+        let integer_config = IntegerConfig::new(config.range_config.clone(), config.main_gate_config.clone());
+
+        Self {
+            integer_chip: IntegerChip::new(integer_config, Rc::new(Rns::construct())),
+        }
+    }
+
+    /// Assigns `value`, with its limbs range checked.
+    pub fn assign(
+        &self,
+        ctx: &mut RegionCtx<'_, Fr>,
+        value: Value<Fq>,
+    ) -> Result<AssignedFq<LIMBS, BITS>, plonk::Error> {
+        let rns = self.integer_chip.rns();
+        let value = value.map(|value| Integer::from_fe(value, rns.clone()));
+        self.integer_chip.assign_integer(ctx, value.into(), Range::Remainder)
+    }
+
+    /// Assigns the constant `value`.
+    pub fn assign_constant(
+        &self,
+        ctx: &mut RegionCtx<'_, Fr>,
+        value: Fq,
+    ) -> Result<AssignedFq<LIMBS, BITS>, plonk::Error> {
+        self.integer_chip.assign_constant(ctx, value)
+    }
+
+    /// `a + b`.
+    pub fn add(
+        &self,
+        ctx: &mut RegionCtx<'_, Fr>,
+        a: &AssignedFq<LIMBS, BITS>,
+        b: &AssignedFq<LIMBS, BITS>,
+    ) -> Result<AssignedFq<LIMBS, BITS>, plonk::Error> {
+        self.integer_chip.add(ctx, a, b)
+    }
+
+    /// `a * b`, reduced.
+    pub fn mul(
+        &self,
+        ctx: &mut RegionCtx<'_, Fr>,
+        a: &AssignedFq<LIMBS, BITS>,
+        b: &AssignedFq<LIMBS, BITS>,
+    ) -> Result<AssignedFq<LIMBS, BITS>, plonk::Error> {
+        self.integer_chip.mul(ctx, a, b)
+    }
+
+    /// `a^-1`. The circuit is not satisfied if `a` is zero.
+    pub fn invert(
+        &self,
+        ctx: &mut RegionCtx<'_, Fr>,
+        a: &AssignedFq<LIMBS, BITS>,
+    ) -> Result<AssignedFq<LIMBS, BITS>, plonk::Error> {
+        self.integer_chip.invert_incomplete(ctx, a)
+    }
+
+    /// Constrains `a` and `b` to be equal modulo the [`Fq`] modulus.
+    pub fn assert_equal(
+        &self,
+        ctx: &mut RegionCtx<'_, Fr>,
+        a: &AssignedFq<LIMBS, BITS>,
+        b: &AssignedFq<LIMBS, BITS>,
+    ) -> Result<(), plonk::Error> {
+        self.integer_chip.assert_equal(ctx, a, b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::{Field, PrimeField};
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        halo2curves::bn256::Fr,
+        plonk::{self, Circuit, ConstraintSystem},
+    };
+    use snark_verifier::loader::halo2::halo2_wrong_ecc::maingate::RegionCtx;
+
+    use super::{Fq, FqChip, FqConfig};
+    use crate::dev::mock::{expect_satisfied, is_satisfied};
+
+    /// Checks `(a + b) * b^-1 == c`, over `LIMBS` limbs of `BITS` bits.
+    #[derive(Default)]
+    struct FqCircuit<const LIMBS: usize, const BITS: usize> {
+        a: Value<Fq>,
+        b: Value<Fq>,
+        c: Value<Fq>,
+    }
+
+    impl<const LIMBS: usize, const BITS: usize> FqCircuit<LIMBS, BITS> {
+        fn new(a: Fq, b: Fq, c: Fq) -> Self {
+            Self {
+                a: Value::known(a),
+                b: Value::known(b),
+                c: Value::known(c),
+            }
+        }
+    }
+
+    impl<const LIMBS: usize, const BITS: usize> Circuit<Fr> for FqCircuit<LIMBS, BITS> {
+        type Config = FqConfig<LIMBS, BITS>;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            FqConfig::configure(meta)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fr>) -> Result<(), plonk::Error> {
+            config.load(&mut layouter)?;
+            let chip = FqChip::new(&config);
+
+            layouter.assign_region(
+                || "fq",
+                |region| {
+                    let ctx = &mut RegionCtx::new(region, 0);
+                    let a = chip.assign(ctx, self.a)?;
+                    let b = chip.assign(ctx, self.b)?;
+                    let c = chip.assign(ctx, self.c)?;
+
+                    let sum = chip.add(ctx, &a, &b)?;
+                    let b_inv = chip.invert(ctx, &b)?;
+                    let quotient = chip.mul(ctx, &sum, &b_inv)?;
+                    chip.assert_equal(ctx, &quotient, &c)
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn fq_arithmetic() {
+        // -1 fills the limbs up to the modulus, and the sum wraps around it.
+        let (a, b) = (-Fq::ONE, Fq::from(u64::MAX).pow_vartime([6]));
+        let c = (a + b) * b.invert().unwrap();
+        expect_satisfied(&FqCircuit::<8, 68>::new(a, b, c), vec![]);
+        assert!(!is_satisfied(&FqCircuit::<8, 68>::new(a, b, c + Fq::ONE), vec![]));
+    }
+
+    #[test]
+    fn fq_modulus() {
+        // The generator must not be a square for `sqrt`.
+        assert_eq!(Fq::NUM_BITS, 381);
+        assert!(bool::from(Fq::MULTIPLICATIVE_GENERATOR.sqrt().is_none()));
+    }
+}
@@ -0,0 +1,439 @@
+//! AES-128 encryption of a public plaintext block under a private key, with
+//! the key schedule computed in-circuit.
+//!
+//! The bytes of the state only ever go through three operations, each looked
+//! up in a fixed table of `(op, a, b, op(a, b))` tuples: XOR, the S-box and
+//! `xtime`, the doubling in `GF(2^8)` of MixColumns. ShiftRows only permutes
+//! cells, and a column of MixColumns is
+//!
+//! ```text
+//! t = a[0] ^ a[1] ^ a[2] ^ a[3]
+//! b[i] = a[i] ^ t ^ xtime(a[i] ^ a[i + 1])
+//! ```
+//!
+//! Each step of the cipher is a region of lookups, one per row, with `b = 0`
+//! for the S-box and `xtime`:
+//!
+//! | op (fixed) | a      | b      | out          | q_lookup |
+//! | XOR        | s[0]   | k[0]   | s[0] ^ k[0]  | 1        |
+//! | ...        | ...    | ...    | ...          | 1        |
+//! | SBOX       | s[0]   | 0      | sbox(s[0])   | 1        |
+//!
+//! The XORs of the first round range check the key to bytes.
+//!
+//! | instance                               |
+//! | plaintext[0..16], ciphertext[0..16]    |
+//!
+//! The table has `2^16 + 2 ⋅ 2^8` rows, so the circuit needs `k >= 17`.
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use crate::{
+    circuits::instance::{InstanceColumns, InstanceLayout},
+    field::Field,
+};
+
+/// Rounds of AES-128.
+pub const ROUNDS: usize = 10;
+/// The round constants of the key schedule.
+pub const RCON: [u8; ROUNDS] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36];
+
+/// Doubling in `GF(2^8)` modulo `x^8 + x^4 + x^3 + x + 1`.
+pub fn xtime(x: u8) -> u8 {
+    (x << 1) ^ if x & 0x80 != 0 { 0x1b } else { 0 }
+}
+
+/// Multiplication in `GF(2^8)`.
+fn mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        a = xtime(a);
+        b >>= 1;
+    }
+    product
+}
+
+/// The S-box: the affine map of the inverse of `x` in `GF(2^8)`, with `0`
+/// mapped to itself.
+pub fn sbox(x: u8) -> u8 {
+    let inverse = if x == 0 {
+        0
+    } else {
+        (0..254).fold(1, |acc, _| mul(acc, x))
+    };
+    (1..5).fold(inverse ^ 0x63, |acc, i| acc ^ inverse.rotate_left(i))
+}
+
+/// The round keys of `key`, the first being `key` itself.
+pub fn expand_key(key: &[u8; 16]) -> [[u8; 16]; ROUNDS + 1] {
+    let mut keys = [*key; ROUNDS + 1];
+    for round in 1..=ROUNDS {
+        let prev = keys[round - 1];
+        let mut temp = [prev[13], prev[14], prev[15], prev[12]].map(sbox);
+        temp[0] ^= RCON[round - 1];
+        for i in 0..16 {
+            temp[i % 4] ^= prev[i];
+            keys[round][i] = temp[i % 4];
+        }
+    }
+    keys
+}
+
+/// Rotates row `r` of the column-major state left by `r`.
+pub fn shift_rows<T: Clone>(state: &[T; 16]) -> [T; 16] {
+    std::array::from_fn(|i| state[(i + 4 * (i % 4)) % 16].clone())
+}
+
+/// Mixes a column of the state.
+pub fn mix_column(column: [u8; 4]) -> [u8; 4] {
+    let total = column.iter().fold(0, |acc, x| acc ^ x);
+    std::array::from_fn(|i| column[i] ^ total ^ xtime(column[i] ^ column[(i + 1) % 4]))
+}
+
+/// The encryption of `plaintext` under `key`.
+pub fn encrypt(key: &[u8; 16], plaintext: &[u8; 16]) -> [u8; 16] {
+    let keys = expand_key(key);
+    let mut state: [u8; 16] = std::array::from_fn(|i| plaintext[i] ^ keys[0][i]);
+    for (round, key) in keys.iter().enumerate().skip(1) {
+        state = shift_rows(&state.map(sbox));
+        if round < ROUNDS {
+            for column in state.chunks_mut(4) {
+                let mixed = mix_column(column.try_into().unwrap());
+                column.copy_from_slice(&mixed);
+            }
+        }
+        state = std::array::from_fn(|i| state[i] ^ key[i]);
+    }
+    state
+}
+
+/// A byte operation, encoded in the table by its discriminant.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByteOp {
+    Xor = 0,
+    SBox = 1,
+    Xtime = 2,
+}
+
+impl ByteOp {
+    pub const ALL: [ByteOp; 3] = [ByteOp::Xor, ByteOp::SBox, ByteOp::Xtime];
+
+    /// Applies the operation, ignoring `b` for the S-box and `xtime`.
+    pub fn apply(self, a: u8, b: u8) -> u8 {
+        match self {
+            ByteOp::Xor => a ^ b,
+            ByteOp::SBox => sbox(a),
+            ByteOp::Xtime => xtime(a),
+        }
+    }
+
+    /// The `b` inputs of the table, every byte for XOR and only `0` otherwise.
+    fn operands(self) -> std::ops::RangeInclusive<u8> {
+        match self {
+            ByteOp::Xor => 0..=255,
+            ByteOp::SBox | ByteOp::Xtime => 0..=0,
+        }
+    }
+}
+
+type Byte<F> = AssignedCell<F, F>;
+
+/// Config for [`AesCircuit`].
+#[derive(Clone, Debug)]
+pub struct AesConfig {
+    q_lookup: Selector,
+    op: Column<Fixed>,
+    a: Column<Advice>,
+    b: Column<Advice>,
+    out: Column<Advice>,
+    table: [TableColumn; 4],
+    input: Column<Advice>,
+    instance: InstanceColumns,
+}
+
+impl AesConfig {
+    /// Fills the `(op, a, b, op(a, b))` table, once per circuit.
+    fn load_table<F: Field>(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        let table = self.table;
+        layouter.assign_table(
+            || "aes table",
+            |mut t| {
+                let mut offset = 0;
+                for op in ByteOp::ALL {
+                    for a in 0..=255u8 {
+                        for b in op.operands() {
+                            let row = [op as u64, a as u64, b as u64, op.apply(a, b) as u64];
+                            for (column, value) in table.iter().zip(row) {
+                                t.assign_cell(|| "aes table", *column, offset, || Value::known(F::from(value)))?;
+                            }
+                            offset += 1;
+                        }
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Applies `op` to each of `inputs`, `b` being `0` when absent.
+    fn apply<F: Field>(
+        &self,
+        mut layouter: impl Layouter<F>,
+        op: ByteOp,
+        inputs: &[(&Byte<F>, Option<&Byte<F>>)],
+    ) -> Result<Vec<Byte<F>>, Error> {
+        layouter.assign_region(
+            || format!("aes {op:?}"),
+            |mut region| {
+                let mut outputs = Vec::with_capacity(inputs.len());
+                for (i, (a, b)) in inputs.iter().enumerate() {
+                    self.q_lookup.enable(&mut region, i)?;
+                    region.assign_fixed(|| "op", self.op, i, || Value::known(F::from(op as u64)))?;
+
+                    let a = a.copy_advice(|| "a", &mut region, self.a, i)?;
+                    let b = match b {
+                        Some(b) => b.copy_advice(|| "b", &mut region, self.b, i)?,
+                        None => region.assign_advice(|| "b", self.b, i, || Value::known(F::ZERO))?,
+                    };
+                    let out = a.value().zip(b.value()).map(|(a, b)| {
+                        let out = op.apply(to_u8(a), to_u8(b));
+                        F::from(out as u64)
+                    });
+                    outputs.push(region.assign_advice(|| "out", self.out, i, || out)?);
+                }
+                Ok(outputs)
+            },
+        )
+    }
+
+    /// XORs `a` and `b` byte by byte.
+    fn xor<F: Field>(&self, layouter: impl Layouter<F>, a: &[Byte<F>], b: &[Byte<F>]) -> Result<Vec<Byte<F>>, Error> {
+        let inputs = (a.iter().zip(b)).map(|(a, b)| (a, Some(b))).collect::<Vec<_>>();
+        self.apply(layouter, ByteOp::Xor, &inputs)
+    }
+
+    /// Applies the S-box or `xtime` to each of `bytes`.
+    fn map<F: Field>(&self, layouter: impl Layouter<F>, op: ByteOp, bytes: &[Byte<F>]) -> Result<Vec<Byte<F>>, Error> {
+        let inputs = bytes.iter().map(|a| (a, None)).collect::<Vec<_>>();
+        self.apply(layouter, op, &inputs)
+    }
+}
+
+/// The low byte of `value`.
+fn to_u8<F: Field>(value: &F) -> u8 {
+    value.to_repr()[0]
+}
+
+/// Circuit proving knowledge of the key encrypting a plaintext block to a
+/// ciphertext block.
+#[derive(Clone, Debug, Default)]
+pub struct AesCircuit {
+    key: Value<[u8; 16]>,
+    plaintext: Value<[u8; 16]>,
+}
+
+impl AesCircuit {
+    pub fn new(key: [u8; 16], plaintext: [u8; 16]) -> Self {
+        Self {
+            key: Value::known(key),
+            plaintext: Value::known(plaintext),
+        }
+    }
+
+    /// The plaintext and its encryption.
+    pub fn instances<F: Field>(&self) -> Vec<Vec<F>> {
+        let mut instances = vec![];
+        self.key.zip(self.plaintext).map(|(key, plaintext)| {
+            let bytes = plaintext.into_iter().chain(encrypt(&key, &plaintext));
+            instances.extend(bytes.map(|byte| F::from(byte as u64)));
+        });
+        vec![instances]
+    }
+
+    /// A single instance column, see the [module documentation](self).
+    pub fn instance_layout() -> InstanceLayout {
+        let plaintext = (0..16).map(|i| format!("plaintext {i}"));
+        let ciphertext = (0..16).map(|i| format!("ciphertext {i}"));
+        InstanceLayout::new().column(plaintext.chain(ciphertext))
+    }
+}
+
+impl<F: Field> Circuit<F> for AesCircuit {
+    type Config = AesConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let q_lookup = meta.complex_selector();
+        let op = meta.fixed_column();
+        let constant = meta.fixed_column();
+        let [input, a, b, out] = [(); 4].map(|_| meta.advice_column());
+        let table = [(); 4].map(|_| meta.lookup_table_column());
+
+        for column in [input, a, b, out] {
+            meta.enable_equality(column);
+        }
+        meta.enable_constant(constant);
+
+        meta.lookup("aes byte", |meta| {
+            let q_lookup = meta.query_selector(q_lookup);
+            let inputs = [
+                meta.query_fixed(op, Rotation::cur()),
+                meta.query_advice(a, Rotation::cur()),
+                meta.query_advice(b, Rotation::cur()),
+                meta.query_advice(out, Rotation::cur()),
+            ];
+
+            (inputs.into_iter().zip(table))
+                .map(|(input, column)| (q_lookup.clone() * input, column))
+                .collect()
+        });
+
+        AesConfig {
+            q_lookup,
+            op,
+            a,
+            b,
+            out,
+            table,
+            input,
+            instance: Self::instance_layout().configure(meta),
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        config.load_table(&mut layouter)?;
+
+        let (key, plaintext, rcon) = layouter.assign_region(
+            || "inputs",
+            |mut region| {
+                let mut assign = |name: &str, offset: usize, byte: Value<u8>| {
+                    region.assign_advice(|| name, config.input, offset, || byte.map(|byte| F::from(byte as u64)))
+                };
+                let key = (0..16)
+                    .map(|i| assign("key", i, self.key.map(|key| key[i])))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let plaintext = (0..16)
+                    .map(|i| assign("plaintext", 16 + i, self.plaintext.map(|plaintext| plaintext[i])))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let rcon = (RCON.iter().enumerate())
+                    .map(|(i, rcon)| {
+                        region.assign_advice_from_constant(|| "rcon", config.input, 32 + i, F::from(*rcon as u64))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok((key, plaintext, rcon))
+            },
+        )?;
+
+        // The key schedule, as `expand_key`.
+        let mut keys = vec![key];
+        for (round, rcon) in rcon.iter().enumerate() {
+            let mut layouter = layouter.namespace(|| format!("round key {}", round + 1));
+            let prev = &keys[round];
+            let rotated = [13, 14, 15, 12].map(|i| prev[i].clone());
+            let mut temp = config.map(layouter.namespace(|| "sub word"), ByteOp::SBox, &rotated)?;
+            temp[0] = config
+                .xor(layouter.namespace(|| "rcon"), &temp[..1], &[rcon.clone()])?
+                .remove(0);
+
+            let mut key = Vec::with_capacity(16);
+            for (j, word) in prev.chunks(4).enumerate() {
+                temp = config.xor(layouter.namespace(|| format!("word {j}")), &temp, word)?;
+                key.extend(temp.iter().cloned());
+            }
+            keys.push(key);
+        }
+
+        let mut state = config.xor(layouter.namespace(|| "add round key 0"), &plaintext, &keys[0])?;
+        for (round, key) in keys.iter().enumerate().skip(1) {
+            let mut layouter = layouter.namespace(|| format!("round {round}"));
+            let substituted = config.map(layouter.namespace(|| "sub bytes"), ByteOp::SBox, &state)?;
+            state = shift_rows(&<[_; 16]>::try_from(substituted).unwrap()).to_vec();
+            if round < ROUNDS {
+                state = mix_columns(&config, layouter.namespace(|| "mix columns"), &state)?;
+            }
+            state = config.xor(layouter.namespace(|| "add round key"), &state, key)?;
+        }
+
+        for (i, byte) in plaintext.iter().chain(&state).enumerate() {
+            config.instance.expose_public(&mut layouter, byte, i)?;
+        }
+        Ok(())
+    }
+}
+
+/// Mixes the four columns of `state`, see the [module documentation](self).
+fn mix_columns<F: Field>(
+    config: &AesConfig,
+    mut layouter: impl Layouter<F>,
+    state: &[Byte<F>],
+) -> Result<Vec<Byte<F>>, Error> {
+    let next = (0..16)
+        .map(|i| state[i - i % 4 + (i + 1) % 4].clone())
+        .collect::<Vec<_>>();
+    let pairs = config.xor(layouter.namespace(|| "a[i] ^ a[i + 1]"), state, &next)?;
+
+    // `t = (a[0] ^ a[1]) ^ (a[2] ^ a[3])`, repeated over its column.
+    let (first, second): (Vec<_>, Vec<_>) = (pairs.chunks(4))
+        .map(|pairs| (pairs[0].clone(), pairs[2].clone()))
+        .unzip();
+    let totals = config.xor(layouter.namespace(|| "t"), &first, &second)?;
+    let totals = (0..16).map(|i| totals[i / 4].clone()).collect::<Vec<_>>();
+
+    let doubled = config.map(layouter.namespace(|| "xtime"), ByteOp::Xtime, &pairs)?;
+    let sums = config.xor(layouter.namespace(|| "a[i] ^ t"), state, &totals)?;
+    config.xor(layouter.namespace(|| "b[i]"), &sums, &doubled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encrypt, expand_key, sbox, AesCircuit};
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::TestField as Fp,
+    };
+
+    #[test]
+    fn aes() {
+        // FIPS-197, appendices A.1 and C.1.
+        assert_eq!([0x00, 0x01, 0x53].map(sbox), [0x63, 0x7c, 0xed]);
+        let key = [
+            0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c,
+        ];
+        assert_eq!(hex::encode(expand_key(&key)[10]), "d014f9a8c9ee2589e13f0cc8b6630ca6");
+        let key = std::array::from_fn(|i| i as u8);
+        let plaintext = std::array::from_fn(|i| 0x11 * i as u8);
+        assert_eq!(
+            hex::encode(encrypt(&key, &plaintext)),
+            "69c4e0d86a7b0430d8cdb78070b4c55a"
+        );
+
+        let circuit = AesCircuit::new(key, plaintext);
+        expect_satisfied::<Fp, _>(&circuit, circuit.instances());
+
+        // The ciphertext under another key.
+        let mut other_key = key;
+        other_key[0] ^= 1;
+        let mut instances = circuit.instances::<Fp>();
+        instances[0][16..].copy_from_slice(&AesCircuit::new(other_key, plaintext).instances::<Fp>()[0][16..]);
+        expect_failure(
+            &circuit,
+            instances,
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 16 },
+            },
+        );
+    }
+}
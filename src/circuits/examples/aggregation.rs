@@ -0,0 +1,171 @@
+//! Aggregation circuit: verifies several inner proofs in-circuit and outputs a
+//! single KZG accumulator.
+//!
+//! Each inner proof is run through the *succinct* Plonk verifier, which does
+//! everything but the final pairing check and returns a KZG accumulator
+//! `(lhs, rhs)` with `e(lhs, [1]) == e(rhs, [tau])`. The accumulators are then
+//! folded into one with a random linear combination, and its coordinates are
+//! exposed as `4 * LIMBS` instances. Whoever verifies the outer proof must also
+//! run that one pairing check on the instances, which is what the EVM verifier
+//! of snark-verifier does when given `accumulator_indices`.
+//!
+//! The instances of the inner proofs follow the accumulator limbs, so that the
+//! outer proof says what the inner proofs proved.
+//!
+//! The in-circuit verifier lives in [`crate::circuits::verifier`]. The inner
+//! proofs must be created with [`gen_snark`].
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    halo2curves::bn256::{Bn256, Fr},
+    plonk::{self, Circuit, ConstraintSystem},
+    poly::{commitment::ParamsProver, kzg::commitment::ParamsKZG},
+};
+use itertools::Itertools;
+
+pub use crate::circuits::verifier::{gen_snark, PoseidonTranscript, Snark, BITS, LIMBS};
+use crate::circuits::verifier::{accumulate, accumulator_limbs, SnarkWitness, Svk, VerifierConfig};
+
+/// Circuit verifying the accumulators of several inner proofs.
+#[derive(Clone)]
+pub struct AggregationCircuit {
+    svk: Svk,
+    snarks: Vec<SnarkWitness>,
+    instances: Vec<Fr>,
+    as_proof: Value<Vec<u8>>,
+}
+
+impl AggregationCircuit {
+    /// Verifies `snarks` natively to compute the expected accumulator and the
+    /// accumulation proof witnessed by the circuit.
+    pub fn new(params: &ParamsKZG<Bn256>, snarks: impl IntoIterator<Item = Snark>) -> Self {
+        let svk = params.get_g()[0].into();
+        let snarks = snarks.into_iter().collect_vec();
+        let (accumulator, as_proof) = accumulate(&svk, &snarks);
+        let inner_instances = snarks.iter().flat_map(|snark| snark.instances().concat()).collect_vec();
+
+        Self {
+            svk,
+            snarks: snarks.into_iter().map_into().collect(),
+            instances: [accumulator_limbs(&accumulator), inner_instances].concat(),
+            as_proof: Value::known(as_proof),
+        }
+    }
+
+    /// Positions of the accumulator limbs in the instances.
+    pub fn accumulator_indices() -> Vec<(usize, usize)> {
+        (0..4 * LIMBS).map(|idx| (0, idx)).collect()
+    }
+
+    /// Number of instances per instance column.
+    pub fn num_instance(&self) -> Vec<usize> {
+        vec![self.instances.len()]
+    }
+
+    /// The accumulator limbs exposed by the circuit, followed by the
+    /// instances of the inner proofs.
+    pub fn instances(&self) -> Vec<Vec<Fr>> {
+        vec![self.instances.clone()]
+    }
+
+    fn as_proof(&self) -> Value<&[u8]> {
+        self.as_proof.as_ref().map(Vec::as_slice)
+    }
+}
+
+impl Circuit<Fr> for AggregationCircuit {
+    type Config = VerifierConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            svk: self.svk,
+            snarks: self.snarks.iter().map(SnarkWitness::without_witnesses).collect(),
+            instances: Vec::new(),
+            as_proof: Value::unknown(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        VerifierConfig::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fr>) -> Result<(), plonk::Error> {
+        config.load(&mut layouter)?;
+
+        let verified = config.verify(layouter.namespace(|| "verify"), &self.svk, &self.snarks, self.as_proof())?;
+        for (row, cell) in verified.accumulator.into_iter().chain(verified.instances).enumerate() {
+            config.expose_public(layouter.namespace(|| "instance"), cell, row)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::Value,
+        dev::MockProver,
+        halo2curves::bn256::{Bn256, Fr},
+        poly::kzg::commitment::ParamsKZG,
+    };
+    use rand::rngs::OsRng;
+
+    use super::{gen_snark, AggregationCircuit, LIMBS};
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        prover::{keygen, tests::TestCircuit},
+    };
+
+    #[test]
+    fn aggregate_one_proof() {
+        let params = ParamsKZG::<Bn256>::setup(4, OsRng);
+        let pk = keygen(&params, &TestCircuit::default()).unwrap();
+        let circuit = TestCircuit {
+            a: Value::known(Fr::from(3)),
+            b: Value::known(Fr::from(5)),
+        };
+        let snark = gen_snark(&params, &pk, circuit, vec![vec![Fr::from(15)]]).unwrap();
+
+        let circuit = AggregationCircuit::new(&params, [snark]);
+        let instances = circuit.instances();
+        assert_eq!(circuit.num_instance(), vec![4 * LIMBS + 1]);
+        assert_eq!(instances[0][4 * LIMBS..], [Fr::from(15)]);
+        expect_satisfied(&circuit, instances.clone());
+
+        // The inner proof is of 15: exposing another instance after the
+        // accumulator breaks the copy of the verified one.
+        let mut instances = instances;
+        instances[0][4 * LIMBS] = Fr::from(16);
+        expect_failure(
+            &circuit,
+            instances,
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 4 * LIMBS },
+            },
+        );
+    }
+
+    #[test]
+    #[ignore = "needs k = 21 and several minutes"]
+    fn aggregate_two_proofs() {
+        let params = ParamsKZG::<Bn256>::setup(4, OsRng);
+        let pk = keygen(&params, &TestCircuit::default()).unwrap();
+
+        let snarks = [(3, 5), (7, 11)].map(|(a, b)| {
+            let circuit = TestCircuit {
+                a: Value::known(Fr::from(a)),
+                b: Value::known(Fr::from(b)),
+            };
+            gen_snark(&params, &pk, circuit, vec![vec![Fr::from(a * b)]]).unwrap()
+        });
+
+        let circuit = AggregationCircuit::new(&params, snarks);
+        let instances = circuit.instances();
+        let prover = MockProver::run(21, &circuit, instances).unwrap();
+        prover.assert_satisfied();
+    }
+}
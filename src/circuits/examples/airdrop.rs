@@ -0,0 +1,255 @@
+//! Merkle airdrop claims: an `(address, amount)` leaf is in a public tree,
+//! without revealing which one, with a nullifier against double claims.
+//!
+//! An eligible address is `H(secret)` for a secret known to its owner, and
+//! the airdrop publishes the root of a tree of `H(address, amount)` leaves. A
+//! claim proves, with [`MerkleChip`], that the leaf of the claimer's address
+//! and the public amount is in the tree, and exposes the nullifier
+//! `H(address, secret)`, which the contract records:
+//!
+//! | instance                           |
+//! | root, nullifier, amount, recipient |
+//!
+//! The nullifier is the same for every claim of a leaf, yet only the owner of
+//! the secret can compute it, so it links claims to each other but not to
+//! leaves. As in the [Tornado example](super::tornado), the recipient is only
+//! bound to the proof through the instances, and the circuit is generic over
+//! the [`HashInstructions`] chip, Poseidon by default. The depth of the tree
+//! is the [`MerkleParams`] of the circuit, set from the length of the path.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+};
+
+use crate::{
+    circuits::{
+        gadgets::{
+            hash::{HashInstructions, HashSpec},
+            merkle::{MerkleChip, MerkleConfig, MerkleParams},
+            poseidon::{PoseidonChip, Spec},
+            select::SelectChip,
+        },
+        instance::{InstanceColumns, InstanceLayout},
+    },
+    field::Field,
+};
+
+/// An eligible claimer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Claimer<F> {
+    pub secret: F,
+    pub amount: u64,
+}
+
+impl<F: Field> Claimer<F> {
+    /// `H(secret)`.
+    pub fn address(&self) -> F {
+        self.address_with(&Spec::new())
+    }
+
+    /// `H(address, amount)`, the leaf of the claimer.
+    pub fn leaf(&self) -> F {
+        self.leaf_with(&Spec::new())
+    }
+
+    /// `H(address, secret)`, revealed on claim.
+    pub fn nullifier(&self) -> F {
+        self.nullifier_with(&Spec::new())
+    }
+
+    /// [`Claimer::address`] with the hash of `spec`.
+    pub fn address_with(&self, spec: &impl HashSpec<F>) -> F {
+        spec.hash(&[self.secret])
+    }
+
+    /// [`Claimer::leaf`] with the hash of `spec`.
+    pub fn leaf_with(&self, spec: &impl HashSpec<F>) -> F {
+        spec.hash(&[self.address_with(spec), F::from(self.amount)])
+    }
+
+    /// [`Claimer::nullifier`] with the hash of `spec`.
+    pub fn nullifier_with(&self, spec: &impl HashSpec<F>) -> F {
+        spec.hash(&[self.address_with(spec), self.secret])
+    }
+}
+
+/// Config for [`AirdropCircuit`].
+#[derive(Clone, Debug)]
+pub struct AirdropConfig<F: Field, H: HashInstructions<F>> {
+    merkle: MerkleConfig<F, H>,
+    input: Column<Advice>,
+    instance: InstanceColumns,
+}
+
+/// Circuit claiming from an airdrop tree.
+#[derive(Clone, Debug)]
+pub struct AirdropCircuit<F: Field, H: HashInstructions<F> = PoseidonChip<F>> {
+    claimer: Value<Claimer<F>>,
+    path: Value<Vec<(F, bool)>>,
+    root: Value<F>,
+    recipient: Value<F>,
+    params: MerkleParams,
+    _marker: PhantomData<H>,
+}
+
+impl<F: Field, H: HashInstructions<F>> Default for AirdropCircuit<F, H> {
+    fn default() -> Self {
+        Self::with_params(MerkleParams::default())
+    }
+}
+
+impl<F: Field, H: HashInstructions<F>> AirdropCircuit<F, H> {
+    /// Creates the circuit claiming the leaf of `claimer`, in the tree of
+    /// `root` at the end of `path`, for `recipient`.
+    pub fn new(claimer: Claimer<F>, path: Vec<(F, bool)>, root: F, recipient: F) -> Self {
+        Self {
+            claimer: Value::known(claimer),
+            params: MerkleParams { depth: path.len() },
+            path: Value::known(path),
+            root: Value::known(root),
+            recipient: Value::known(recipient),
+            _marker: PhantomData,
+        }
+    }
+
+    /// The circuit for a tree of `params`, without witnesses.
+    pub fn with_params(params: MerkleParams) -> Self {
+        Self {
+            claimer: Value::unknown(),
+            path: Value::unknown(),
+            root: Value::unknown(),
+            recipient: Value::unknown(),
+            params,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The root, the nullifier, the amount and the recipient.
+    pub fn instances(&self) -> Vec<Vec<F>> {
+        let mut instances = vec![];
+        self.claimer.zip(self.root).zip(self.recipient).map(|((claimer, root), recipient)| {
+            let nullifier = claimer.nullifier_with(&H::Spec::default());
+            instances.extend([root, nullifier, F::from(claimer.amount), recipient]);
+        });
+        vec![instances]
+    }
+
+    /// A single instance column, see the [module documentation](self).
+    pub fn instance_layout() -> InstanceLayout {
+        InstanceLayout::new().column(["root", "nullifier", "amount", "recipient"])
+    }
+}
+
+impl<F: Field, H: HashInstructions<F>> Circuit<F> for AirdropCircuit<F, H> {
+    type Config = AirdropConfig<F, H>;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = MerkleParams;
+
+    fn without_witnesses(&self) -> Self {
+        Self::with_params(self.params)
+    }
+
+    #[cfg(feature = "circuit-params")]
+    fn params(&self) -> Self::Params {
+        self.params
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let [input, s0, s1, s2, i0, i1] = [(); 6].map(|_| meta.advice_column());
+        let constant = meta.fixed_column();
+        meta.enable_equality(input);
+
+        AirdropConfig {
+            merkle: MerkleConfig {
+                hash: H::configure(meta, [s0, s1, s2], [i0, i1], constant),
+                select: SelectChip::configure(meta),
+            },
+            input,
+            instance: Self::instance_layout().configure(meta),
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let mut inputs = vec![
+            self.claimer.map(|claimer| claimer.secret),
+            self.claimer.map(|claimer| F::from(claimer.amount)),
+            self.recipient,
+        ];
+        for level in 0..self.params.depth {
+            let node = self.path.as_ref().map(|path| path[level]);
+            inputs.extend([node.map(|(sibling, _)| sibling), node.map(|(_, is_right)| F::from(is_right as u64))]);
+        }
+        let inputs = layouter.assign_region(
+            || "inputs",
+            |mut region| {
+                (inputs.iter().enumerate())
+                    .map(|(i, value)| region.assign_advice(|| "input", config.input, i, || *value))
+                    .collect::<Result<Vec<_>, _>>()
+            },
+        )?;
+        let [secret, amount, recipient] = [0, 1, 2].map(|i| inputs[i].clone());
+        let path: Vec<_> = inputs[3..].chunks(2).map(|node| (node[0].clone(), node[1].clone())).collect();
+
+        let hash = H::construct(config.merkle.hash.clone());
+        let address = hash.hash(layouter.namespace(|| "address"), &[secret.clone()])?;
+        let leaf = hash.hash2(layouter.namespace(|| "leaf"), &address, &amount)?;
+        let nullifier = hash.hash2(layouter.namespace(|| "nullifier"), &address, &secret)?;
+        let root = MerkleChip::construct(config.merkle).root(layouter.namespace(|| "merkle"), &leaf, &path)?;
+
+        for (i, cell) in [&root, &nullifier, &amount, &recipient].into_iter().enumerate() {
+            config.instance.expose_public(&mut layouter, cell, i)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AirdropCircuit, Claimer};
+    use crate::{
+        circuits::gadgets::merkle::MerkleTree,
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::TestField as Fp,
+    };
+
+    #[test]
+    fn airdrop() {
+        let claimers: Vec<_> = (0..5)
+            .map(|i| Claimer {
+                secret: Fp::from(1000 + i),
+                amount: 100 * (i + 1),
+            })
+            .collect();
+        let tree = MerkleTree::new(3, claimers.iter().map(Claimer::leaf).collect());
+        let recipient = Fp::from(0xcafe);
+
+        let path = tree.path(3);
+        let circuit = AirdropCircuit::<_>::new(claimers[3], path.clone(), tree.root(), recipient);
+        assert_eq!(circuit.instances()[0][2], Fp::from(400));
+        expect_satisfied(&circuit, circuit.instances());
+
+        // Claiming more than the leaf's amount.
+        let greedy = Claimer {
+            amount: 1000,
+            ..claimers[3]
+        };
+        let circuit = AirdropCircuit::<_>::new(greedy, path.clone(), tree.root(), recipient);
+        expect_failure(
+            &circuit,
+            circuit.instances(),
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+
+        // A second claim of the same leaf has the same nullifier, whatever
+        // the recipient.
+        let again = AirdropCircuit::<_>::new(claimers[3], path, tree.root(), Fp::from(0xbeef));
+        assert_eq!(again.instances()[0][1], claimers[3].nullifier());
+        expect_satisfied(&again, again.instances());
+    }
+}
@@ -0,0 +1,238 @@
+//! Sealed-bid auction: the winner had the highest committed bid, without
+//! revealing the losing ones.
+//!
+//! Each of the `K` bidders publishes `H(bid, salt)` and opens it to the
+//! auctioneer only. The auctioneer opens every commitment in the circuit with
+//! [`PoseidonChip`], range checks the bids to [`BID_BYTES`] bytes with
+//! [`PackConfig`], and folds them into a running maximum with an [`LtChip`]
+//! comparison and two [`SelectChip`] selections per bidder:
+//!
+//! ```text
+//! higher = max < bid[k]
+//! max    = higher ? bid[k] : max
+//! winner = higher ? k : winner
+//! ```
+//!
+//! Ties go to the first bidder. The commitments, the winner and the winning
+//! bid are public:
+//!
+//! | instance                                               |
+//! | commitments[0], ..., commitments[K - 1], winner, price |
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+};
+
+use crate::{
+    circuits::{
+        gadgets::{
+            lt::{LtChip, LtConfig},
+            poseidon::{PoseidonChip, PoseidonConfig, Spec},
+            select::{SelectChip, SelectConfig},
+        },
+        instance::{InstanceColumns, InstanceLayout},
+        pack::PackConfig,
+        table::U8Table,
+    },
+    field::Field,
+};
+
+/// Bytes of a bid.
+pub const BID_BYTES: usize = 8;
+
+/// A sealed bid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bid<F> {
+    pub amount: u64,
+    pub salt: F,
+}
+
+impl<F: Field> Bid<F> {
+    /// `H(amount, salt)`.
+    pub fn commitment(&self) -> F {
+        Spec::new().hash(&[F::from(self.amount), self.salt])
+    }
+}
+
+/// Config for [`AuctionCircuit`].
+#[derive(Clone, Debug)]
+pub struct AuctionConfig<F: Field> {
+    input: Column<Advice>,
+    poseidon: PoseidonConfig<F>,
+    lt: LtConfig<F, BID_BYTES>,
+    select: SelectConfig,
+    pack: PackConfig,
+    u8_table: U8Table,
+    instance: InstanceColumns,
+}
+
+/// Circuit proving the winner of `K` sealed bids.
+#[derive(Clone, Debug)]
+pub struct AuctionCircuit<F: Field, const K: usize> {
+    bids: Value<[Bid<F>; K]>,
+}
+
+impl<F: Field, const K: usize> Default for AuctionCircuit<F, K> {
+    fn default() -> Self {
+        Self { bids: Value::unknown() }
+    }
+}
+
+impl<F: Field, const K: usize> AuctionCircuit<F, K> {
+    pub fn new(bids: [Bid<F>; K]) -> Self {
+        assert!(K > 0);
+        Self {
+            bids: Value::known(bids),
+        }
+    }
+
+    /// The commitments, the winner and the winning bid.
+    pub fn instances(&self) -> Vec<Vec<F>> {
+        let mut instances = vec![];
+        self.bids.map(|bids| {
+            // `max_by_key` keeps the last maximum, ties go to the first bid.
+            let (winner, bid) = (bids.iter().enumerate().rev()).max_by_key(|(_, bid)| bid.amount).unwrap();
+            instances.extend(bids.iter().map(Bid::commitment));
+            instances.extend([F::from(winner as u64), F::from(bid.amount)]);
+        });
+        vec![instances]
+    }
+
+    /// A single instance column, see the [module documentation](self).
+    pub fn instance_layout() -> InstanceLayout {
+        let commitments = (0..K).map(|k| format!("commitments[{k}]"));
+        InstanceLayout::new().column(commitments.chain(["winner".to_string(), "price".to_string()]))
+    }
+}
+
+impl<F: Field, const K: usize> Circuit<F> for AuctionCircuit<F, K> {
+    type Config = AuctionConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let [input, byte, acc, s0, s1, s2, i0, i1] = [(); 8].map(|_| meta.advice_column());
+        let constant = meta.fixed_column();
+        let u8_table = U8Table::configure(meta);
+        let pack = PackConfig::configure(meta, byte, acc, u8_table);
+        meta.enable_equality(input);
+
+        AuctionConfig {
+            input,
+            poseidon: PoseidonChip::configure(meta, [s0, s1, s2], [i0, i1], constant),
+            lt: LtChip::configure(meta, pack.clone()),
+            select: SelectChip::configure(meta),
+            pack,
+            u8_table,
+            instance: Self::instance_layout().configure(meta),
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        config.u8_table.load(&mut layouter)?;
+
+        let (bids, indices) = layouter.assign_region(
+            || "inputs",
+            |mut region| {
+                let mut bids = vec![];
+                let mut indices = vec![];
+                for k in 0..K {
+                    let bid = self.bids.map(|bids| bids[k]);
+                    let amount = bid.map(|bid| F::from(bid.amount));
+                    let salt = bid.map(|bid| bid.salt);
+                    bids.push([
+                        region.assign_advice(|| format!("amount {k}"), config.input, 3 * k, || amount)?,
+                        region.assign_advice(|| format!("salt {k}"), config.input, 3 * k + 1, || salt)?,
+                    ]);
+                    let index = F::from(k as u64);
+                    indices.push(region.assign_advice_from_constant(|| "index", config.input, 3 * k + 2, index)?);
+                }
+                Ok((bids, indices))
+            },
+        )?;
+
+        let poseidon = PoseidonChip::construct(config.poseidon);
+        let commitments = (bids.iter())
+            .map(|bid| poseidon.hash(layouter.namespace(|| "commitment"), bid))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let lt = LtChip::construct(config.lt);
+        let select = SelectChip::construct(config.select);
+        let (mut price, mut winner) = (bids[0][0].clone(), indices[0].clone());
+        for (k, [amount, _]) in bids.iter().enumerate() {
+            let mut layouter = layouter.namespace(|| format!("bid {k}"));
+            config.pack.unpack(layouter.namespace(|| "range"), amount, BID_BYTES)?;
+            if k == 0 {
+                continue;
+            }
+            let higher = lt.assign(layouter.namespace(|| "higher"), &price, amount)?.lt;
+            price = select.select(layouter.namespace(|| "price"), &higher, amount, &price)?;
+            winner = select.select(layouter.namespace(|| "winner"), &higher, &indices[k], &winner)?;
+        }
+
+        for (i, cell) in commitments.iter().chain([&winner, &price]).enumerate() {
+            config.instance.expose_public(&mut layouter, cell, i)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AuctionCircuit, Bid};
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::TestField as Fp,
+    };
+
+    fn bids(amounts: [u64; 4]) -> [Bid<Fp>; 4] {
+        let mut salt = 0;
+        amounts.map(|amount| {
+            salt += 1;
+            Bid {
+                amount,
+                salt: Fp::from(1000 + salt),
+            }
+        })
+    }
+
+    #[test]
+    fn auction() {
+        let circuit = AuctionCircuit::new(bids([300, 500, 200, 500]));
+        assert_eq!(circuit.instances()[0][4..], [Fp::from(1), Fp::from(500)]);
+        expect_satisfied(&circuit, circuit.instances());
+
+        let circuit = AuctionCircuit::new(bids([0, 0, u64::MAX, 7]));
+        assert_eq!(circuit.instances()[0][4..], [Fp::from(2), Fp::from(u64::MAX)]);
+        expect_satisfied(&circuit, circuit.instances());
+
+        // Announcing the second of two equal highest bids.
+        let circuit = AuctionCircuit::new(bids([300, 500, 200, 500]));
+        let mut wrong = circuit.instances();
+        wrong[0][4] = Fp::from(3);
+        expect_failure(
+            &circuit,
+            wrong,
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 4 },
+            },
+        );
+
+        // Announcing a lower price than the winning bid.
+        let mut wrong = circuit.instances();
+        wrong[0][5] = Fp::from(400);
+        expect_failure(
+            &circuit,
+            wrong,
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 5 },
+            },
+        );
+    }
+}
@@ -0,0 +1,260 @@
+//! Battleship: answers to shots at a hidden board, consistent with a
+//! commitment to it.
+//!
+//! The board has [`CELLS`] cells, each 1 if a ship occupies it. The prover
+//! commits to it as `H(salt, cells[0], ..., cells[CELLS - 1])` with
+//! [`PoseidonChip`], and answers each of `S` shots by selecting the shot cell
+//! with [`IndexChip`]. The cells are laid out with a running count of the
+//! occupied ones:
+//!
+//! | cell     | count                | q_cell |
+//! |          | 0                    | 0      |
+//! | cells[0] | cells[0]             | 1      |
+//! | cells[1] | cells[0] + cells[1]  | 1      |
+//! | ...      | ...                  | 1      |
+//!
+//! - every cell is boolean,
+//! - `count = count_prev + cell`.
+//!
+//! The commitment, the final count, which the verifier compares to the
+//! number of ship cells of the rules, and the shots and hits are public:
+//!
+//! | instance                                                     |
+//! | commitment, ships, shot[0], hit[0], ..., shot[S-1], hit[S-1] |
+//!
+//! The shape of the ships is not checked, only how many cells they occupy.
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::{
+    circuits::{
+        gadgets::{
+            index::{IndexChip, IndexConfig},
+            poseidon::{PoseidonChip, PoseidonConfig, Spec},
+        },
+        instance::{InstanceColumns, InstanceLayout},
+    },
+    field::Field,
+};
+
+/// Side of the square board.
+pub const SIZE: usize = 4;
+
+/// Cells of the board, row by row.
+pub const CELLS: usize = SIZE * SIZE;
+
+/// Config for [`BattleshipCircuit`].
+#[derive(Clone, Debug)]
+pub struct BattleshipConfig<F: Field> {
+    q_cell: Selector,
+    cell: Column<Advice>,
+    count: Column<Advice>,
+    input: Column<Advice>,
+    poseidon: PoseidonConfig<F>,
+    index: IndexConfig<F, CELLS>,
+    instance: InstanceColumns,
+}
+
+/// Circuit answering `S` shots at a committed board.
+#[derive(Clone, Debug)]
+pub struct BattleshipCircuit<F: Field, const S: usize> {
+    board: Value<[bool; CELLS]>,
+    salt: Value<F>,
+    shots: Value<[usize; S]>,
+}
+
+impl<F: Field, const S: usize> Default for BattleshipCircuit<F, S> {
+    fn default() -> Self {
+        Self {
+            board: Value::unknown(),
+            salt: Value::unknown(),
+            shots: Value::unknown(),
+        }
+    }
+}
+
+impl<F: Field, const S: usize> BattleshipCircuit<F, S> {
+    /// Creates the circuit for the private `board` and `salt`, answering the
+    /// `shots`, each a cell index.
+    pub fn new(board: [bool; CELLS], salt: F, shots: [usize; S]) -> Self {
+        assert!(shots.iter().all(|shot| *shot < CELLS));
+        Self {
+            board: Value::known(board),
+            salt: Value::known(salt),
+            shots: Value::known(shots),
+        }
+    }
+
+    /// The commitment, the number of ship cells, and the shots and hits.
+    pub fn instances(&self) -> Vec<Vec<F>> {
+        let mut instances = vec![];
+        self.board.zip(self.salt).zip(self.shots).map(|((board, salt), shots)| {
+            let cells = board.map(|cell| F::from(cell as u64));
+            let preimage: Vec<F> = [salt].into_iter().chain(cells).collect();
+            instances.push(Spec::new().hash(&preimage));
+            instances.push(F::from(board.iter().filter(|cell| **cell).count() as u64));
+            for shot in shots {
+                instances.extend([F::from(shot as u64), cells[shot]]);
+            }
+        });
+        vec![instances]
+    }
+
+    /// A single instance column, see the [module documentation](self).
+    pub fn instance_layout() -> InstanceLayout {
+        let shots = (0..S).flat_map(|i| [format!("shot[{i}]"), format!("hit[{i}]")]);
+        InstanceLayout::new().column(["commitment".to_string(), "ships".to_string()].into_iter().chain(shots))
+    }
+}
+
+impl<F: Field, const S: usize> Circuit<F> for BattleshipCircuit<F, S> {
+    type Config = BattleshipConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let q_cell = meta.selector();
+        let [cell, count, input, s0, s1, s2, i0, i1] = [(); 8].map(|_| meta.advice_column());
+        let constant = meta.fixed_column();
+        let poseidon = PoseidonChip::configure(meta, [s0, s1, s2], [i0, i1], constant);
+        let index = IndexChip::configure(meta);
+        let instance = Self::instance_layout().configure(meta);
+
+        for column in [cell, count, input] {
+            meta.enable_equality(column);
+        }
+
+        meta.create_gate("board cell", |meta| {
+            let q_cell = meta.query_selector(q_cell);
+            let cell = meta.query_advice(cell, Rotation::cur());
+            let count_prev = meta.query_advice(count, Rotation::prev());
+            let count = meta.query_advice(count, Rotation::cur());
+
+            vec![
+                q_cell.clone() * cell.clone() * (Expression::Constant(F::ONE) - cell.clone()),
+                q_cell * (count - count_prev - cell),
+            ]
+        });
+
+        BattleshipConfig {
+            q_cell,
+            cell,
+            count,
+            input,
+            poseidon,
+            index,
+            instance,
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let (cells, ships) = layouter.assign_region(
+            || "board",
+            |mut region| {
+                let mut count = region.assign_advice_from_constant(|| "count", config.count, 0, F::ZERO)?;
+                let mut cells = vec![];
+                for i in 0..CELLS {
+                    config.q_cell.enable(&mut region, i + 1)?;
+                    let cell = self.board.map(|board| F::from(board[i] as u64));
+                    cells.push(region.assign_advice(|| format!("cells[{i}]"), config.cell, i + 1, || cell)?);
+                    let next = count.value().copied() + cell;
+                    count = region.assign_advice(|| "count", config.count, i + 1, || next)?;
+                }
+                Ok((cells, count))
+            },
+        )?;
+
+        let (salt, shots) = layouter.assign_region(
+            || "inputs",
+            |mut region| {
+                let salt = region.assign_advice(|| "salt", config.input, 0, || self.salt)?;
+                let shots = (0..S)
+                    .map(|i| {
+                        let shot = self.shots.map(|shots| F::from(shots[i] as u64));
+                        region.assign_advice(|| format!("shot[{i}]"), config.input, i + 1, || shot)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok((salt, shots))
+            },
+        )?;
+
+        let poseidon = PoseidonChip::construct(config.poseidon);
+        let preimage: Vec<AssignedCell<F, F>> = [salt].into_iter().chain(cells.iter().cloned()).collect();
+        let commitment = poseidon.hash(layouter.namespace(|| "commitment"), &preimage)?;
+
+        let index = IndexChip::construct(config.index);
+        let board = cells.try_into().unwrap();
+        let hits = (shots.iter())
+            .map(|shot| index.select(layouter.namespace(|| "shot"), &board, shot))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let shots = shots.iter().zip(&hits).flat_map(|(shot, hit)| [shot, hit]);
+        for (i, cell) in [&commitment, &ships].into_iter().chain(shots).enumerate() {
+            config.instance.expose_public(&mut layouter, cell, i)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BattleshipCircuit, CELLS};
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::TestField as Fp,
+    };
+
+    /// A ship of 3 cells on the first row, and one of 2 cells on the last
+    /// column.
+    fn board() -> [bool; CELLS] {
+        let mut board = [false; CELLS];
+        for i in [0, 1, 2, 11, 15] {
+            board[i] = true;
+        }
+        board
+    }
+
+    #[test]
+    fn battleship() {
+        let circuit = BattleshipCircuit::new(board(), Fp::from(1234), [1, 5, 15]);
+        let instances = circuit.instances();
+        assert_eq!(instances[0][1], Fp::from(5));
+        assert_eq!(instances[0][2..], [1u64, 1, 5, 0, 15, 1].map(Fp::from));
+        expect_satisfied(&circuit, instances.clone());
+
+        // Answering a miss at 15.
+        let mut wrong = instances.clone();
+        wrong[0][7] = Fp::from(0);
+        expect_failure(
+            &circuit,
+            wrong,
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 7 },
+            },
+        );
+
+        // Answering from a board with one ship cell moved, under the
+        // original commitment.
+        let mut moved = board();
+        moved.swap(15, 14);
+        let circuit = BattleshipCircuit::new(moved, Fp::from(1234), [1, 5, 15]);
+        let mut wrong = circuit.instances();
+        wrong[0][0] = instances[0][0];
+        expect_failure(
+            &circuit,
+            wrong,
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+    }
+}
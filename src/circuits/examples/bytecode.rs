@@ -0,0 +1,345 @@
+//! A program ROM: a fixed bytecode table, and an execution trace that must
+//! follow it.
+//!
+//! The bytecode of a tiny accumulator machine is loaded into a lookup table
+//! of `(pc, opcode, imm)` rows. Every row of the trace fetches the instruction
+//! at its `pc` by looking up its `(pc, opcode, imm)` cells in that table, and
+//! decodes the opcode into [`OneHotChip`] bits, which select the transition
+//! to the next row:
+//!
+//! | pc | bits[0..5] | imm | acc | q_row | q_step | q_first | q_last |
+//! | 0  | PUSH       | 3   | 0   | 1     | 1      | 1       | 0      |
+//! | 1  | ADD        | 4   | 3   | 1     | 1      | 0       | 0      |
+//! | .. | ..         | ..  | ..  | 1     | ..     | 0       | ..     |
+//! | 5  | STOP       | 0   | 35  | 1     | 0      | 0       | 1      |
+//!
+//! The trace starts at `pc = acc = 0` and must end on a `STOP`, after which
+//! it stays put; the final `acc` is public. The table has a leading tag
+//! column, 1 on instructions and 0 on a padding row, so that the all-zero
+//! input of rows with `q_row` off can't be confused with an instruction at
+//! `pc = 0`.
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use crate::{
+    circuits::{
+        gadgets::one_hot::{OneHotChip, OneHotConfig},
+        instance::{InstanceColumns, InstanceLayout},
+        util::constraint_builder::ConstraintBuilder,
+    },
+    field::Field,
+};
+
+/// Number of opcodes.
+pub const NUM_OPCODES: usize = 5;
+
+/// An opcode, encoded in the table and the one-hot bits by its discriminant.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Opcode {
+    /// Halts: `pc` and `acc` no longer change.
+    Stop = 0,
+    /// `acc = imm`.
+    Push = 1,
+    /// `acc = acc + imm`.
+    Add = 2,
+    /// `acc = acc ⋅ imm`.
+    Mul = 3,
+    /// `pc = imm`.
+    Jump = 4,
+}
+
+/// An instruction of the bytecode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Instruction {
+    pub opcode: Opcode,
+    pub imm: u64,
+}
+
+impl Instruction {
+    pub fn new(opcode: Opcode, imm: u64) -> Self {
+        Self { opcode, imm }
+    }
+}
+
+/// A row of the trace: the state of the machine and the instruction fetched
+/// at its `pc`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Step<F> {
+    pub pc: u64,
+    pub instruction: Instruction,
+    pub acc: F,
+}
+
+/// Runs `bytecode` for `rows` rows from `pc = acc = 0`.
+///
+/// # Panics
+///
+/// If `pc` leaves the bytecode.
+pub fn execute<F: Field>(bytecode: &[Instruction], rows: usize) -> Vec<Step<F>> {
+    let (mut pc, mut acc) = (0, F::ZERO);
+    let mut trace = Vec::with_capacity(rows);
+    for _ in 0..rows {
+        let instruction = bytecode[pc as usize];
+        trace.push(Step { pc, instruction, acc });
+        let imm = F::from(instruction.imm);
+        (pc, acc) = match instruction.opcode {
+            Opcode::Stop => (pc, acc),
+            Opcode::Push => (pc + 1, imm),
+            Opcode::Add => (pc + 1, acc + imm),
+            Opcode::Mul => (pc + 1, acc * imm),
+            Opcode::Jump => (instruction.imm, acc),
+        };
+    }
+    trace
+}
+
+/// Config for [`BytecodeCircuit`].
+#[derive(Clone, Debug)]
+pub struct BytecodeConfig<F: Field> {
+    q_row: Selector,
+    q_step: Selector,
+    q_first: Selector,
+    q_last: Selector,
+    pc: Column<Advice>,
+    imm: Column<Advice>,
+    acc: Column<Advice>,
+    opcode: OneHotConfig<F, NUM_OPCODES>,
+    table: [TableColumn; 4],
+    instance: InstanceColumns,
+}
+
+/// Circuit executing a fixed `bytecode` for `N` rows.
+#[derive(Clone, Debug)]
+pub struct BytecodeCircuit<F: Field, const N: usize> {
+    bytecode: Vec<Instruction>,
+    trace: Value<Vec<Step<F>>>,
+}
+
+impl<F: Field, const N: usize> BytecodeCircuit<F, N> {
+    /// Creates the circuit for `bytecode`, which must reach a `STOP` within
+    /// `N` rows.
+    pub fn new(bytecode: Vec<Instruction>) -> Self {
+        let trace = execute(&bytecode, N);
+        Self {
+            bytecode,
+            trace: Value::known(trace),
+        }
+    }
+
+    /// The final `acc`.
+    pub fn instances(&self) -> Vec<Vec<F>> {
+        let mut instances = vec![];
+        self.trace.as_ref().map(|trace| instances.push(trace[N - 1].acc));
+        vec![instances]
+    }
+
+    /// A single instance column holding the final `acc`.
+    pub fn instance_layout() -> InstanceLayout {
+        InstanceLayout::new().column(["acc"])
+    }
+}
+
+impl<F: Field, const N: usize> Circuit<F> for BytecodeCircuit<F, N> {
+    type Config = BytecodeConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            bytecode: self.bytecode.clone(),
+            trace: Value::unknown(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let q_row = meta.complex_selector();
+        let q_step = meta.selector();
+        let q_first = meta.selector();
+        let q_last = meta.selector();
+        let [pc, imm, acc] = [(); 3].map(|_| meta.advice_column());
+        let bits = [(); NUM_OPCODES].map(|_| meta.advice_column());
+        let table = [(); 4].map(|_| meta.lookup_table_column());
+        let instance = Self::instance_layout().configure(meta);
+
+        meta.enable_equality(acc);
+
+        let opcode = OneHotChip::configure(meta, |meta| meta.query_selector(q_row), bits);
+
+        meta.lookup("bytecode", |meta| {
+            let q_row = meta.query_selector(q_row);
+            let pc = meta.query_advice(pc, Rotation::cur());
+            let imm = meta.query_advice(imm, Rotation::cur());
+
+            [q_row.clone(), q_row.clone() * pc, q_row.clone() * opcode.index(), q_row * imm]
+                .into_iter()
+                .zip(table)
+                .collect()
+        });
+
+        meta.create_gate("step", |meta| {
+            let [pc_next, acc_next] = [pc, acc].map(|column| meta.query_advice(column, Rotation::next()));
+            let [pc, imm, acc] = [pc, imm, acc].map(|column| meta.query_advice(column, Rotation::cur()));
+            let [stop, push, add, mul, jump] = opcode.bits().clone();
+            let one = Expression::Constant(F::ONE);
+
+            let mut cb = ConstraintBuilder::default();
+            cb.condition(stop, |cb| {
+                cb.require_equal("stop keeps pc", pc_next.clone(), pc.clone());
+                cb.require_equal("stop keeps acc", acc_next.clone(), acc.clone());
+            });
+            cb.condition(push.clone() + add.clone() + mul.clone(), |cb| {
+                cb.require_equal("next pc", pc_next.clone(), pc.clone() + one);
+            });
+            cb.condition(push, |cb| cb.require_equal("push", acc_next.clone(), imm.clone()));
+            cb.condition(add, |cb| cb.require_equal("add", acc_next.clone(), acc.clone() + imm.clone()));
+            cb.condition(mul, |cb| cb.require_equal("mul", acc_next.clone(), acc.clone() * imm.clone()));
+            cb.condition(jump, |cb| {
+                cb.require_equal("jump pc", pc_next.clone(), imm.clone());
+                cb.require_equal("jump keeps acc", acc_next.clone(), acc.clone());
+            });
+            cb.gate(meta.query_selector(q_step))
+        });
+
+        meta.create_gate("start", |meta| {
+            let mut cb = ConstraintBuilder::default();
+            cb.require_zero("pc starts at 0", meta.query_advice(pc, Rotation::cur()));
+            cb.require_zero("acc starts at 0", meta.query_advice(acc, Rotation::cur()));
+            cb.gate(meta.query_selector(q_first))
+        });
+
+        meta.create_gate("halt", |meta| {
+            let mut cb = ConstraintBuilder::default();
+            let stop = opcode.bits()[Opcode::Stop as usize].clone();
+            cb.require_equal("ends on stop", stop, Expression::Constant(F::ONE));
+            cb.gate(meta.query_selector(q_last))
+        });
+
+        BytecodeConfig {
+            q_row,
+            q_step,
+            q_first,
+            q_last,
+            pc,
+            imm,
+            acc,
+            opcode,
+            table,
+            instance,
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "bytecode",
+            |mut table| {
+                let padding = [0; 4];
+                let rows = (self.bytecode.iter().enumerate())
+                    .map(|(pc, instruction)| [1, pc as u64, instruction.opcode as u64, instruction.imm]);
+                for (offset, row) in [padding].into_iter().chain(rows).enumerate() {
+                    for (column, value) in config.table.iter().zip(row) {
+                        table.assign_cell(|| "bytecode", *column, offset, || Value::known(F::from(value)))?;
+                    }
+                }
+                Ok(())
+            },
+        )?;
+
+        let chip = OneHotChip::construct(config.opcode.clone());
+        let acc = layouter.assign_region(
+            || "trace",
+            |mut region| {
+                config.q_first.enable(&mut region, 0)?;
+                config.q_last.enable(&mut region, N - 1)?;
+
+                let mut acc = None;
+                for i in 0..N {
+                    config.q_row.enable(&mut region, i)?;
+                    if i < N - 1 {
+                        config.q_step.enable(&mut region, i)?;
+                    }
+
+                    let step = self.trace.as_ref().map(|trace| trace[i]);
+                    let opcode = step.map(|step| F::from(step.instruction.opcode as u64));
+                    let imm = step.map(|step| F::from(step.instruction.imm));
+
+                    region.assign_advice(|| "pc", config.pc, i, || step.map(|step| F::from(step.pc)))?;
+                    region.assign_advice(|| "imm", config.imm, i, || imm)?;
+                    acc = Some(region.assign_advice(|| "acc", config.acc, i, || step.map(|step| step.acc))?);
+                    chip.assign(&mut region, i, opcode)?;
+                }
+                Ok(acc.unwrap())
+            },
+        )?;
+
+        config.instance.expose_public(&mut layouter, &acc, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::circuit::Value;
+
+    use super::{execute, BytecodeCircuit, Instruction, Opcode};
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::TestField as Fp,
+    };
+
+    /// `acc = (3 + 4) ⋅ 5`, jumping over a `MUL 100`.
+    fn program() -> Vec<Instruction> {
+        vec![
+            Instruction::new(Opcode::Push, 3),
+            Instruction::new(Opcode::Add, 4),
+            Instruction::new(Opcode::Jump, 4),
+            Instruction::new(Opcode::Mul, 100),
+            Instruction::new(Opcode::Mul, 5),
+            Instruction::new(Opcode::Stop, 0),
+        ]
+    }
+
+    #[test]
+    fn bytecode() {
+        let circuit = BytecodeCircuit::<Fp, 8>::new(program());
+        assert_eq!(circuit.instances(), vec![vec![Fp::from(35)]]);
+        expect_satisfied(&circuit, circuit.instances());
+
+        // A trace of a program that adds 100 instead of jumping: its
+        // instruction at pc 2 is not in the table.
+        let mut patched = program();
+        patched[2] = Instruction::new(Opcode::Add, 100);
+        let circuit = BytecodeCircuit::<Fp, 8> {
+            bytecode: program(),
+            trace: Value::known(execute(&patched, 8)),
+        };
+        expect_failure(
+            &circuit,
+            circuit.instances(),
+            FailureMatcher::Lookup {
+                name: "bytecode",
+                location: Location::InRegion {
+                    region: "trace",
+                    offset: 2,
+                },
+            },
+        );
+
+        // Too few rows to reach the STOP.
+        let circuit = BytecodeCircuit::<Fp, 4>::new(program());
+        expect_failure(
+            &circuit,
+            circuit.instances(),
+            FailureMatcher::Constraint {
+                gate: "halt",
+                location: Location::InRegion {
+                    region: "trace",
+                    offset: 3,
+                },
+            },
+        );
+    }
+}
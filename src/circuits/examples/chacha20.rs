@@ -0,0 +1,311 @@
+//! ChaCha20 block function: a keystream block of a private key, and
+//! optionally the encryption of a private plaintext with it.
+//!
+//! The state is the four constants, the 8 key words, the block counter and
+//! the 3 nonce words, and 20 rounds of quarter rounds mix it with the
+//! additions, XORs and rotations of [`Word32Chip`]:
+//!
+//! ```text
+//! a += b; d ^= a; d <<<= 16;
+//! c += d; b ^= c; b <<<= 12;
+//! a += b; d ^= a; d <<<= 8;
+//! c += d; b ^= c; b <<<= 7;
+//! ```
+//!
+//! The keystream block is the mixed state plus the initial one. The XORs
+//! range check their inputs, so only the key words, which are first added,
+//! are decomposed.
+//!
+//! | instance                                   |
+//! | counter, nonce[0..3], output[0..16]        |
+//!
+//! The output is the keystream block, or the ciphertext `plaintext ^ block`
+//! when the circuit has a plaintext. Words are little-endian, as in RFC 8439.
+//!
+//! Every addition and rotated XOR decomposes a word in the bits column, some
+//! 22k rows in all, so the circuit needs `k >= 15`.
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+};
+
+use crate::{
+    circuits::{
+        gadgets::{
+            bits::BitsConfig,
+            word32::{Word32Chip, Word32Config},
+        },
+        instance::{InstanceColumns, InstanceLayout},
+    },
+    field::Field,
+};
+
+/// The first row of the state, `"expand 32-byte k"`.
+pub const CONSTANTS: [u32; 4] = [0x61707865, 0x3320646e, 0x79622d32, 0x6b206574];
+/// Rounds of the block function, half on columns and half on diagonals.
+pub const ROUNDS: usize = 20;
+/// The `(a, b, c, d)` of the quarter rounds of a column round, then of a
+/// diagonal round.
+pub const QUARTER_ROUNDS: [[usize; 4]; 8] = [
+    [0, 4, 8, 12],
+    [1, 5, 9, 13],
+    [2, 6, 10, 14],
+    [3, 7, 11, 15],
+    [0, 5, 10, 15],
+    [1, 6, 11, 12],
+    [2, 7, 8, 13],
+    [3, 4, 9, 14],
+];
+
+/// The keystream block of `key` at `counter` and `nonce`.
+pub fn block(key: &[u32; 8], counter: u32, nonce: &[u32; 3]) -> [u32; 16] {
+    let initial = initial_state(key, counter, nonce);
+    let mut state = initial;
+    for _ in 0..ROUNDS / 2 {
+        for [a, b, c, d] in QUARTER_ROUNDS {
+            state[a] = state[a].wrapping_add(state[b]);
+            state[d] = (state[d] ^ state[a]).rotate_left(16);
+            state[c] = state[c].wrapping_add(state[d]);
+            state[b] = (state[b] ^ state[c]).rotate_left(12);
+            state[a] = state[a].wrapping_add(state[b]);
+            state[d] = (state[d] ^ state[a]).rotate_left(8);
+            state[c] = state[c].wrapping_add(state[d]);
+            state[b] = (state[b] ^ state[c]).rotate_left(7);
+        }
+    }
+    std::array::from_fn(|i| state[i].wrapping_add(initial[i]))
+}
+
+fn initial_state(key: &[u32; 8], counter: u32, nonce: &[u32; 3]) -> [u32; 16] {
+    let mut state = [0; 16];
+    state[..4].copy_from_slice(&CONSTANTS);
+    state[4..12].copy_from_slice(key);
+    state[12] = counter;
+    state[13..].copy_from_slice(nonce);
+    state
+}
+
+/// Config for [`ChaCha20Circuit`].
+#[derive(Clone, Debug)]
+pub struct ChaCha20Config {
+    word: Word32Config,
+    input: Column<Advice>,
+    instance: InstanceColumns,
+}
+
+/// Circuit proving a ChaCha20 keystream block or ciphertext.
+#[derive(Clone, Debug)]
+pub struct ChaCha20Circuit {
+    key: Value<[u32; 8]>,
+    counter: Value<u32>,
+    nonce: Value<[u32; 3]>,
+    plaintext: Option<Value<[u32; 16]>>,
+}
+
+impl ChaCha20Circuit {
+    /// Creates the circuit exposing the keystream block of `key`.
+    pub fn keystream(key: [u32; 8], counter: u32, nonce: [u32; 3]) -> Self {
+        Self {
+            key: Value::known(key),
+            counter: Value::known(counter),
+            nonce: Value::known(nonce),
+            plaintext: None,
+        }
+    }
+
+    /// Creates the circuit exposing the encryption of `plaintext` with the
+    /// keystream block of `key`.
+    pub fn encryption(key: [u32; 8], counter: u32, nonce: [u32; 3], plaintext: [u32; 16]) -> Self {
+        Self {
+            plaintext: Some(Value::known(plaintext)),
+            ..Self::keystream(key, counter, nonce)
+        }
+    }
+
+    /// The counter, the nonce and the output.
+    pub fn instances<F: Field>(&self) -> Vec<Vec<F>> {
+        let mut instances = vec![];
+        let plaintext = self.plaintext.unwrap_or(Value::known([0; 16]));
+        (self.key.zip(self.counter).zip(self.nonce).zip(plaintext)).map(|(((key, counter), nonce), plaintext)| {
+            let output = (block(&key, counter, &nonce).iter().zip(plaintext)).map(|(word, plaintext)| word ^ plaintext);
+            let words = [counter].into_iter().chain(nonce).chain(output);
+            instances.extend(words.map(|word| F::from(word as u64)));
+        });
+        vec![instances]
+    }
+
+    /// A single instance column, see the [module documentation](self).
+    pub fn instance_layout() -> InstanceLayout {
+        let nonce = (0..3).map(|i| format!("nonce {i}"));
+        let output = (0..16).map(|i| format!("output {i}"));
+        InstanceLayout::new().column(["counter".to_string()].into_iter().chain(nonce).chain(output))
+    }
+}
+
+impl<F: Field> Circuit<F> for ChaCha20Circuit {
+    type Config = ChaCha20Config;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            key: Value::unknown(),
+            counter: Value::unknown(),
+            nonce: Value::unknown(),
+            plaintext: self.plaintext.map(|_| Value::unknown()),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let [input, acc, t0, t1, t2, sum, carry] = [(); 7].map(|_| meta.advice_column());
+        let constant = meta.fixed_column();
+        meta.enable_equality(input);
+
+        let bits = BitsConfig::configure(meta, acc);
+        ChaCha20Config {
+            word: Word32Chip::configure(meta, [t0, t1, t2, sum, carry], bits, constant),
+            input,
+            instance: Self::instance_layout().configure(meta),
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let chip = Word32Chip::construct(config.word);
+        chip.load_table(&mut layouter)?;
+
+        let (key, counter, nonce, plaintext) = layouter.assign_region(
+            || "inputs",
+            |mut region| {
+                let mut assign = |name: &str, offset: usize, word: Value<u32>| {
+                    region.assign_advice(|| name, config.input, offset, || word.map(|word| F::from(word as u64)))
+                };
+                let key = (0..8)
+                    .map(|i| assign("key", i, self.key.map(|key| key[i])))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let counter = assign("counter", 8, self.counter)?;
+                let nonce = (0..3)
+                    .map(|i| assign("nonce", 9 + i, self.nonce.map(|nonce| nonce[i])))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let plaintext = match self.plaintext {
+                    Some(plaintext) => Some(
+                        (0..16)
+                            .map(|i| assign("plaintext", 12 + i, plaintext.map(|plaintext| plaintext[i])))
+                            .collect::<Result<Vec<_>, _>>()?,
+                    ),
+                    None => None,
+                };
+                Ok((key, counter, nonce, plaintext))
+            },
+        )?;
+
+        for (i, word) in key.iter().enumerate() {
+            chip.decompose(layouter.namespace(|| format!("key {i}")), word)?;
+        }
+
+        let mut initial = chip.constants(layouter.namespace(|| "constants"), &CONSTANTS)?;
+        initial.extend(key);
+        initial.push(counter.clone());
+        initial.extend(nonce.iter().cloned());
+
+        let mut state = initial.clone();
+        for round in 0..ROUNDS / 2 {
+            let mut layouter = layouter.namespace(|| format!("double round {round}"));
+            for indices in QUARTER_ROUNDS {
+                let words = indices.map(|i| state[i].clone());
+                let mixed = quarter_round(&chip, layouter.namespace(|| format!("{indices:?}")), words)?;
+                for (i, word) in indices.into_iter().zip(mixed) {
+                    state[i] = word;
+                }
+            }
+        }
+
+        let mut output = Vec::with_capacity(16);
+        for (i, (word, initial)) in state.iter().zip(&initial).enumerate() {
+            let mut layouter = layouter.namespace(|| format!("output {i}"));
+            let word = chip
+                .add(layouter.namespace(|| "add"), &[word, initial], 0)?
+                .cell()
+                .clone();
+            output.push(match &plaintext {
+                Some(plaintext) => chip.xor(layouter.namespace(|| "encrypt"), &plaintext[i], &word)?,
+                None => word,
+            });
+        }
+
+        config.instance.expose_public(&mut layouter, &counter, 0)?;
+        for (i, cell) in nonce.iter().chain(&output).enumerate() {
+            config.instance.expose_public(&mut layouter, cell, 1 + i)?;
+        }
+        Ok(())
+    }
+}
+
+/// Mixes `[a, b, c, d]`, see the [module documentation](self).
+fn quarter_round<F: Field>(
+    chip: &Word32Chip<F>,
+    mut layouter: impl Layouter<F>,
+    [mut a, mut b, mut c, mut d]: [AssignedCell<F, F>; 4],
+) -> Result<[AssignedCell<F, F>; 4], Error> {
+    for (i, (rotate_d, rotate_b)) in [(16, 12), (8, 7)].into_iter().enumerate() {
+        let mut layouter = layouter.namespace(|| format!("half {i}"));
+        a = chip.add(layouter.namespace(|| "a + b"), &[&a, &b], 0)?.cell().clone();
+        let xor = chip.xor(layouter.namespace(|| "d ^ a"), &d, &a)?;
+        let xor = chip.decompose(layouter.namespace(|| "d ^ a"), &xor)?;
+        d = chip.rotate_left(layouter.namespace(|| "rotate d"), &xor, rotate_d)?;
+
+        c = chip.add(layouter.namespace(|| "c + d"), &[&c, &d], 0)?.cell().clone();
+        let xor = chip.xor(layouter.namespace(|| "b ^ c"), &b, &c)?;
+        let xor = chip.decompose(layouter.namespace(|| "b ^ c"), &xor)?;
+        b = chip.rotate_left(layouter.namespace(|| "rotate b"), &xor, rotate_b)?;
+    }
+    Ok([a, b, c, d])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{block, ChaCha20Circuit};
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::TestField as Fp,
+    };
+
+    /// The key, nonce and counter of RFC 8439, section 2.3.2.
+    fn rfc_8439() -> ([u32; 8], u32, [u32; 3]) {
+        let key = std::array::from_fn(|i| u32::from_le_bytes(std::array::from_fn(|j| (4 * i + j) as u8)));
+        (key, 1, [0x09000000, 0x4a000000, 0])
+    }
+
+    #[test]
+    fn chacha20() {
+        let (key, counter, nonce) = rfc_8439();
+        assert_eq!(
+            block(&key, counter, &nonce),
+            [
+                0xe4e7f110, 0x15593bd1, 0x1fdd0f50, 0xc47120a3, 0xc7f4d1c7, 0x0368c033, 0x9aaa2204, 0x4e6cd4c3, //
+                0x466482d2, 0x09aa9f07, 0x05d7c214, 0xa2028bd9, 0xd19c12b5, 0xb94e16de, 0xe883d0cb, 0x4e3c50a2,
+            ]
+        );
+
+        let circuit = ChaCha20Circuit::keystream(key, counter, nonce);
+        expect_satisfied::<Fp, _>(&circuit, circuit.instances());
+
+        let plaintext = std::array::from_fn(|i| 0x01010101 * i as u32);
+        let circuit = ChaCha20Circuit::encryption(key, counter, nonce, plaintext);
+        expect_satisfied::<Fp, _>(&circuit, circuit.instances());
+
+        // The keystream of the next block.
+        let circuit = ChaCha20Circuit::keystream(key, counter, nonce);
+        let next = ChaCha20Circuit::keystream(key, counter + 1, nonce);
+        let mut instances = next.instances::<Fp>();
+        instances[0][0] = Fp::from(counter as u64);
+        expect_failure(
+            &circuit,
+            instances,
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 4 },
+            },
+        );
+    }
+}
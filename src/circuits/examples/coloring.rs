@@ -0,0 +1,202 @@
+//! Graph 3-coloring: a private coloring of a public graph is proper.
+//!
+//! The `E` edges between the `V` vertices are part of the circuit: each edge
+//! row copies the colors of its endpoints, so the edge list is fixed by the
+//! copy constraints of the verifying key. Every color is checked to be in
+//! `{0, 1, 2}`, and [`is_zero_2`](crate::circuits::gadgets::is_zero_2) on the
+//! difference of the endpoints must be 0:
+//!
+//! | color | q_color | a   | b   | diff_inv  | is_zero | q_edge |
+//! | c_v   | 1       | c_u | c_w | 1/(a - b) | 0       | 1      |
+//!
+//! - `c ⋅ (c - 1) ⋅ (c - 2) = 0`,
+//! - `is_zero(a - b) = 0`.
+//!
+//! There are no instances: the statement is that the graph of the circuit is
+//! 3-colorable, and the proof reveals nothing about the coloring.
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::{
+    circuits::{
+        gadgets::is_zero_2::{IsZeroChip, IsZeroConfig, IsZeroInstruction},
+        util::constraint_builder::ConstraintBuilder,
+    },
+    field::Field,
+};
+
+/// Config for [`ColoringCircuit`].
+#[derive(Clone, Debug)]
+pub struct ColoringConfig<F: Field> {
+    q_color: Selector,
+    q_edge: Selector,
+    color: Column<Advice>,
+    a: Column<Advice>,
+    b: Column<Advice>,
+    is_zero: IsZeroConfig<F>,
+}
+
+/// Circuit proving a 3-coloring of a graph of `V` vertices and `E` edges.
+#[derive(Clone, Debug)]
+pub struct ColoringCircuit<const V: usize, const E: usize> {
+    edges: [(usize, usize); E],
+    colors: Value<[u8; V]>,
+}
+
+impl<const V: usize, const E: usize> ColoringCircuit<V, E> {
+    /// Creates the circuit for the graph of `edges` and the private `colors`.
+    pub fn new(edges: [(usize, usize); E], colors: [u8; V]) -> Self {
+        assert!(edges.iter().all(|(u, w)| *u < V && *w < V));
+        Self {
+            edges,
+            colors: Value::known(colors),
+        }
+    }
+}
+
+impl<F: Field, const V: usize, const E: usize> Circuit<F> for ColoringCircuit<V, E> {
+    type Config = ColoringConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            edges: self.edges,
+            colors: Value::unknown(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let q_color = meta.selector();
+        let q_edge = meta.selector();
+        let [color, a, b, diff_inv, is_zero] = [(); 5].map(|_| meta.advice_column());
+
+        for column in [color, a, b] {
+            meta.enable_equality(column);
+        }
+
+        meta.create_gate("color", |meta| {
+            let c = meta.query_advice(color, Rotation::cur());
+            let one = Expression::Constant(F::ONE);
+            let two = Expression::Constant(F::from(2));
+
+            let mut cb = ConstraintBuilder::default();
+            cb.require_zero("color in {0, 1, 2}", c.clone() * (c.clone() - one) * (c - two));
+            cb.gate(meta.query_selector(q_color))
+        });
+
+        let is_zero = IsZeroChip::configure(
+            meta,
+            |meta| meta.query_selector(q_edge),
+            |meta| meta.query_advice(a, Rotation::cur()) - meta.query_advice(b, Rotation::cur()),
+            diff_inv,
+            is_zero,
+        );
+
+        meta.create_gate("proper edge", |meta| {
+            let mut cb = ConstraintBuilder::default();
+            cb.require_zero("endpoints differ", is_zero.expr());
+            cb.gate(meta.query_selector(q_edge))
+        });
+
+        ColoringConfig {
+            q_color,
+            q_edge,
+            color,
+            a,
+            b,
+            is_zero,
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let colors = layouter.assign_region(
+            || "colors",
+            |mut region| {
+                (0..V)
+                    .map(|v| {
+                        config.q_color.enable(&mut region, v)?;
+                        let color = self.colors.map(|colors| F::from(colors[v] as u64));
+                        region.assign_advice(|| format!("color {v}"), config.color, v, || color)
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            },
+        )?;
+
+        let is_zero = IsZeroChip::construct(config.is_zero.clone());
+        layouter.assign_region(
+            || "edges",
+            |mut region| {
+                for (offset, (u, w)) in self.edges.iter().enumerate() {
+                    config.q_edge.enable(&mut region, offset)?;
+                    let a = colors[*u].copy_advice(|| "a", &mut region, config.a, offset)?;
+                    let b = colors[*w].copy_advice(|| "b", &mut region, config.b, offset)?;
+                    is_zero.assign(&mut region, offset, a.value().copied() - b.value())?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ColoringCircuit;
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::TestField as Fp,
+    };
+
+    /// The Petersen graph: an outer 5-cycle, spokes, and an inner pentagram.
+    fn petersen() -> [(usize, usize); 15] {
+        std::array::from_fn(|k| {
+            let i = k % 5;
+            match k / 5 {
+                0 => (i, (i + 1) % 5),
+                1 => (i, 5 + i),
+                _ => (5 + i, 5 + (i + 2) % 5),
+            }
+        })
+    }
+
+    #[test]
+    fn coloring() {
+        let colors = [0, 1, 0, 1, 2, 1, 0, 2, 2, 1];
+        expect_satisfied::<Fp, _>(&ColoringCircuit::new(petersen(), colors), vec![]);
+
+        // A fourth color.
+        let mut wrong = colors;
+        wrong[4] = 3;
+        expect_failure::<Fp, _>(
+            &ColoringCircuit::new(petersen(), wrong),
+            vec![],
+            FailureMatcher::Constraint {
+                gate: "color",
+                location: Location::InRegion {
+                    region: "colors",
+                    offset: 4,
+                },
+            },
+        );
+
+        // Vertices 4 and 0, joined by edge 4, with the same color.
+        let mut wrong = colors;
+        wrong[0] = 2;
+        expect_failure::<Fp, _>(
+            &ColoringCircuit::new(petersen(), wrong),
+            vec![],
+            FailureMatcher::Constraint {
+                gate: "proper edge",
+                location: Location::InRegion {
+                    region: "edges",
+                    offset: 4,
+                },
+            },
+        );
+    }
+}
@@ -0,0 +1,364 @@
+//! Regex-lite matching: a private string is accepted by one of a fixed set of
+//! DFAs, whose pattern ID is public.
+//!
+//! The transitions of every [`Dfa`] are loaded into a lookup table of
+//! `(id, state, byte, next)` rows, and their accepting states into a table of
+//! `(id, state)` rows. The trace runs the DFA over the `N` bytes of the
+//! string from state 0, one byte per row:
+//!
+//! | id | state   | byte    | q_first | q_step | q_last |
+//! | id | 0       | s[0]    | 1       | 1      | 0      |
+//! | id | state_1 | s[1]    | 0       | 1      | 0      |
+//! | .. | ..      | ..      | 0       | 1      | 0      |
+//! | id | state_N |         | 0       | 0      | 1      |
+//!
+//! - `state` starts at 0,
+//! - `(id, state, byte, state_next)` is a transition of the DFA `id`,
+//! - `id` is the same on every row, and public,
+//! - `(id, state_N)` is an accepting state.
+//!
+//! Every accepting state loops on the NUL byte, so strings shorter than `N`
+//! are padded with zeros. Both tables have a leading tag column, 1 on real
+//! rows and 0 on a padding row, so that disabled rows can't be confused with
+//! a transition from state 0.
+
+use std::{collections::BTreeSet, marker::PhantomData};
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use crate::{
+    circuits::instance::{InstanceColumns, InstanceLayout},
+    field::Field,
+};
+
+/// A deterministic finite automaton over bytes, starting in state 0.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Dfa {
+    id: u64,
+    transitions: BTreeSet<(u64, u8, u64)>,
+    accepting: BTreeSet<u64>,
+}
+
+impl Dfa {
+    /// Creates a DFA without transitions, identified by the non-zero `id`.
+    pub fn new(id: u64) -> Self {
+        assert_ne!(id, 0);
+        Self {
+            id,
+            ..Self::default()
+        }
+    }
+
+    /// Adds transitions from `from` to `to` on each of `bytes`.
+    ///
+    /// # Panics
+    ///
+    /// If `from` already has a transition on one of `bytes`, or on NUL.
+    pub fn transition(mut self, from: u64, bytes: impl IntoIterator<Item = u8>, to: u64) -> Self {
+        for byte in bytes {
+            assert!(byte != 0 && self.next(from, byte).is_none(), "non-deterministic transition");
+            self.transitions.insert((from, byte, to));
+        }
+        self
+    }
+
+    /// Makes `state` accepting.
+    pub fn accept(mut self, state: u64) -> Self {
+        self.accepting.insert(state);
+        self
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// The state after `state` on `byte`, with the NUL loops of the accepting
+    /// states.
+    pub fn next(&self, state: u64, byte: u8) -> Option<u64> {
+        if byte == 0 && self.accepting.contains(&state) {
+            return Some(state);
+        }
+        let mut transitions = self.transitions.range((state, byte, 0)..=(state, byte, u64::MAX));
+        transitions.next().map(|(_, _, next)| *next)
+    }
+
+    /// Whether the DFA accepts `input`.
+    pub fn accepts(&self, input: &[u8]) -> bool {
+        let state = input.iter().try_fold(0, |state, byte| self.next(state, *byte));
+        state.map_or(false, |state| self.accepting.contains(&state))
+    }
+
+    /// The `(state, byte, next)` rows of the transition table.
+    fn rows(&self) -> impl Iterator<Item = (u64, u8, u64)> + '_ {
+        let loops = self.accepting.iter().map(|state| (*state, 0, *state));
+        self.transitions.iter().copied().chain(loops)
+    }
+}
+
+/// Config for [`DfaCircuit`].
+#[derive(Clone, Debug)]
+pub struct DfaConfig {
+    q_first: Selector,
+    q_step: Selector,
+    q_last: Selector,
+    id: Column<Advice>,
+    state: Column<Advice>,
+    byte: Column<Advice>,
+    transitions: [TableColumn; 5],
+    accepting: [TableColumn; 3],
+    instance: InstanceColumns,
+}
+
+/// Circuit matching strings of up to `N` bytes against fixed `dfas`.
+#[derive(Clone, Debug)]
+pub struct DfaCircuit<F: Field, const N: usize> {
+    dfas: Vec<Dfa>,
+    id: Value<u64>,
+    input: Value<Vec<u8>>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field, const N: usize> DfaCircuit<F, N> {
+    /// Creates the circuit proving that the DFA `id` of `dfas` accepts
+    /// `input`.
+    pub fn new(dfas: Vec<Dfa>, id: u64, input: &[u8]) -> Self {
+        assert!(input.len() <= N);
+        let mut input = input.to_vec();
+        input.resize(N, 0);
+        Self {
+            dfas,
+            id: Value::known(id),
+            input: Value::known(input),
+            _marker: PhantomData,
+        }
+    }
+
+    /// The pattern ID.
+    pub fn instances(&self) -> Vec<Vec<F>> {
+        let mut instances = vec![];
+        self.id.map(|id| instances.push(F::from(id)));
+        vec![instances]
+    }
+
+    /// A single instance column holding the pattern ID.
+    pub fn instance_layout() -> InstanceLayout {
+        InstanceLayout::new().column(["pattern_id"])
+    }
+}
+
+/// Assigns `rows`, after an all-zero padding row, to the `columns` of a table.
+fn assign_table<F: Field, const W: usize>(
+    layouter: &mut impl Layouter<F>,
+    name: &'static str,
+    columns: [TableColumn; W],
+    rows: impl Iterator<Item = [u64; W]>,
+) -> Result<(), Error> {
+    layouter.assign_table(
+        || name,
+        |mut table| {
+            for (offset, row) in [[0; W]].into_iter().chain(rows).enumerate() {
+                for (column, value) in columns.iter().zip(row) {
+                    table.assign_cell(|| name, *column, offset, || Value::known(F::from(value)))?;
+                }
+            }
+            Ok(())
+        },
+    )
+}
+
+impl<F: Field, const N: usize> Circuit<F> for DfaCircuit<F, N> {
+    type Config = DfaConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            dfas: self.dfas.clone(),
+            id: Value::unknown(),
+            input: Value::unknown(),
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let q_first = meta.selector();
+        let q_step = meta.complex_selector();
+        let q_last = meta.complex_selector();
+        let [id, state, byte] = [(); 3].map(|_| meta.advice_column());
+        let transitions = [(); 5].map(|_| meta.lookup_table_column());
+        let accepting = [(); 3].map(|_| meta.lookup_table_column());
+        let instance = Self::instance_layout().configure(meta);
+
+        meta.enable_equality(id);
+
+        meta.lookup("dfa transition", |meta| {
+            let q_step = meta.query_selector(q_step);
+            let next = meta.query_advice(state, Rotation::next());
+            let [id, state, byte] = [id, state, byte].map(|column| meta.query_advice(column, Rotation::cur()));
+
+            [q_step.clone(), q_step.clone() * id, q_step.clone() * state, q_step.clone() * byte, q_step * next]
+                .into_iter()
+                .zip(transitions)
+                .collect()
+        });
+
+        meta.lookup("dfa accept", |meta| {
+            let q_last = meta.query_selector(q_last);
+            let [id, state] = [id, state].map(|column| meta.query_advice(column, Rotation::cur()));
+
+            [q_last.clone(), q_last.clone() * id, q_last * state]
+                .into_iter()
+                .zip(accepting)
+                .collect()
+        });
+
+        meta.create_gate("dfa start", |meta| {
+            let q_first = meta.query_selector(q_first);
+            vec![q_first * meta.query_advice(state, Rotation::cur())]
+        });
+
+        meta.create_gate("dfa id", |meta| {
+            let q_step = meta.query_selector(q_step);
+            let id_next = meta.query_advice(id, Rotation::next());
+            let id = meta.query_advice(id, Rotation::cur());
+            vec![q_step * (id_next - id)]
+        });
+
+        DfaConfig {
+            q_first,
+            q_step,
+            q_last,
+            id,
+            state,
+            byte,
+            transitions,
+            accepting,
+            instance,
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let transitions = (self.dfas.iter())
+            .flat_map(|dfa| dfa.rows().map(|(state, byte, next)| [1, dfa.id, state, byte as u64, next]));
+        assign_table(&mut layouter, "dfa transitions", config.transitions, transitions)?;
+        let accepting = (self.dfas.iter()).flat_map(|dfa| dfa.accepting.iter().map(|state| [1, dfa.id, *state]));
+        assign_table(&mut layouter, "dfa accepting states", config.accepting, accepting)?;
+
+        // States of the run, continuing from state 0 after a missing
+        // transition, which fails the lookup.
+        let states = self.id.zip(self.input.as_ref()).map(|(id, input)| {
+            let dfa = self.dfas.iter().find(|dfa| dfa.id == id);
+            let mut states = vec![0];
+            for byte in input {
+                let state = *states.last().unwrap();
+                states.push(dfa.and_then(|dfa| dfa.next(state, *byte)).unwrap_or(0));
+            }
+            states
+        });
+
+        let id = layouter.assign_region(
+            || "dfa",
+            |mut region| {
+                config.q_first.enable(&mut region, 0)?;
+                config.q_last.enable(&mut region, N)?;
+
+                let mut id = None;
+                for i in 0..=N {
+                    let state = states.as_ref().map(|states| F::from(states[i]));
+                    region.assign_advice(|| "state", config.state, i, || state)?;
+                    id = Some(region.assign_advice(|| "id", config.id, i, || self.id.map(F::from))?);
+                    if i < N {
+                        config.q_step.enable(&mut region, i)?;
+                        let byte = self.input.as_ref().map(|input| F::from(input[i] as u64));
+                        region.assign_advice(|| "byte", config.byte, i, || byte)?;
+                    }
+                }
+                Ok(id.unwrap())
+            },
+        )?;
+
+        config.instance.expose_public(&mut layouter, &id, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Dfa, DfaCircuit};
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::TestField as Fp,
+    };
+
+    /// `[a-z]+@[a-z]+\.com` and `[0-9]+`.
+    fn dfas() -> Vec<Dfa> {
+        let email = Dfa::new(1)
+            .transition(0, b'a'..=b'z', 1)
+            .transition(1, b'a'..=b'z', 1)
+            .transition(1, [b'@'], 2)
+            .transition(2, b'a'..=b'z', 3)
+            .transition(3, b'a'..=b'z', 3)
+            .transition(3, [b'.'], 4)
+            .transition(4, [b'c'], 5)
+            .transition(5, [b'o'], 6)
+            .transition(6, [b'm'], 7)
+            .accept(7);
+        let number = Dfa::new(2)
+            .transition(0, b'0'..=b'9', 1)
+            .transition(1, b'0'..=b'9', 1)
+            .accept(1);
+        vec![email, number]
+    }
+
+    #[test]
+    fn dfa() {
+        let [email, number] = <[Dfa; 2]>::try_from(dfas()).unwrap();
+        assert!(email.accepts(b"alice@halo.com"));
+        assert!(!email.accepts(b"alice@halo.co"));
+        assert!(number.accepts(b"2023\0\0"));
+        assert!(!number.accepts(b"20a3"));
+
+        let cases: [(u64, &[u8]); 3] = [(1, b"alice@halo.com"), (1, b"bob@zk.com"), (2, b"2023")];
+        for (id, input) in cases {
+            let circuit = DfaCircuit::<Fp, 16>::new(dfas(), id, input);
+            expect_satisfied(&circuit, circuit.instances());
+        }
+
+        // `.` is not a transition of state 1 of the number DFA.
+        let circuit = DfaCircuit::<Fp, 16>::new(dfas(), 2, b"3.14");
+        expect_failure(
+            &circuit,
+            circuit.instances(),
+            FailureMatcher::Lookup {
+                name: "dfa transition",
+                location: Location::InRegion { region: "dfa", offset: 1 },
+            },
+        );
+
+        // A string of the email DFA claimed as a number.
+        let circuit = DfaCircuit::<Fp, 16>::new(dfas(), 2, b"alice@halo.com");
+        expect_failure(
+            &circuit,
+            circuit.instances(),
+            FailureMatcher::Lookup {
+                name: "dfa transition",
+                location: Location::InRegion { region: "dfa", offset: 0 },
+            },
+        );
+
+        // Every transition exists, but the run stops before `.com`.
+        let circuit = DfaCircuit::<Fp, 10>::new(dfas(), 1, b"alice@halo");
+        expect_failure(
+            &circuit,
+            circuit.instances(),
+            FailureMatcher::Lookup {
+                name: "dfa accept",
+                location: Location::InRegion { region: "dfa", offset: 10 },
+            },
+        );
+    }
+}
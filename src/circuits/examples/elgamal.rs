@@ -0,0 +1,169 @@
+//! ElGamal encryption correctness, the building block of private voting and
+//! sealed-bid auctions.
+//!
+//! A plaintext `m` is encrypted under a public key `pk = sk⋅G` of bn256 G1,
+//! with randomness `r`, as
+//!
+//! ```text
+//! c1 = r⋅G
+//! c2 = m⋅G + r⋅pk
+//! ```
+//!
+//! The circuit witnesses `m` and `r`, computes both points on the non-native
+//! ECC chip of [`crate::circuits::verifier`], and exposes the limbs of `pk`,
+//! `c1` and `c2`, see [`point_limbs`]. The proof shows that the ciphertext
+//! encrypts some `m` with the randomness used for `c1`, without revealing
+//! either.
+//!
+//! The plaintext is in the exponent: ciphertexts add up to an encryption of
+//! the sum, as when tallying votes, and `c2 - sk⋅c1 = m⋅G` only decrypts small
+//! values. The chip's scalar multiplication and addition are incomplete, so
+//! `m` and `r` must not be zero, and `m⋅G` must not be `±r⋅pk`, which happens
+//! with negligible probability for a random `r`.
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    halo2curves::{
+        bn256::{Fr, G1Affine, G1},
+        group::{Curve, Group},
+    },
+    plonk::{self, Circuit, ConstraintSystem},
+};
+use rand::rngs::OsRng;
+use snark_verifier::{
+    loader::halo2::halo2_wrong_ecc::maingate::{MainGateInstructions, RegionCtx},
+    pcs::kzg::LimbsEncodingInstructions,
+};
+
+pub use crate::circuits::verifier::{point_limbs, LIMBS};
+use crate::circuits::verifier::VerifierConfig;
+
+/// Window size of the scalar multiplications.
+const WINDOW_SIZE: usize = 4;
+
+/// `(r⋅G, m⋅G + r⋅pk)`.
+pub fn encrypt(pk: G1Affine, m: Fr, r: Fr) -> (G1Affine, G1Affine) {
+    let g = G1::generator();
+    ((g * r).to_affine(), (g * m + pk * r).to_affine())
+}
+
+/// Circuit proving that `(c1, c2)` is the ElGamal encryption of a private
+/// plaintext under `pk`.
+#[derive(Clone, Debug, Default)]
+pub struct ElGamalCircuit {
+    pk: Value<G1Affine>,
+    m: Value<Fr>,
+    r: Value<Fr>,
+    aux_generator: Value<G1Affine>,
+    instances: Vec<Fr>,
+}
+
+impl ElGamalCircuit {
+    /// Encrypts `m` under `pk` with the randomness `r`.
+    pub fn new(pk: G1Affine, m: Fr, r: Fr) -> Self {
+        let (c1, c2) = encrypt(pk, m, r);
+
+        Self {
+            pk: Value::known(pk),
+            m: Value::known(m),
+            r: Value::known(r),
+            aux_generator: Value::known(G1::random(OsRng).to_affine()),
+            instances: [pk, c1, c2].iter().flat_map(point_limbs).collect(),
+        }
+    }
+
+    /// The limbs of `pk`, `c1` and `c2`.
+    pub fn instances(&self) -> Vec<Vec<Fr>> {
+        vec![self.instances.clone()]
+    }
+}
+
+impl Circuit<Fr> for ElGamalCircuit {
+    type Config = VerifierConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        VerifierConfig::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fr>) -> Result<(), plonk::Error> {
+        config.load(&mut layouter)?;
+        let main_gate = config.main_gate();
+        let mut ecc_chip = config.ecc_chip();
+
+        let limbs = layouter.assign_region(
+            || "elgamal",
+            |region| {
+                let ctx = &mut RegionCtx::new(region, 0);
+                ecc_chip.assign_aux_generator(ctx, self.aux_generator)?;
+                ecc_chip.assign_aux(ctx, WINDOW_SIZE, 1)?;
+
+                let g = ecc_chip.assign_constant(ctx, G1::generator().to_affine())?;
+                let pk = ecc_chip.assign_point(ctx, self.pk)?;
+                let m = main_gate.assign_value(ctx, self.m)?;
+                let r = main_gate.assign_value(ctx, self.r)?;
+
+                let c1 = ecc_chip.mul(ctx, &g, &r, WINDOW_SIZE)?;
+                let m_g = ecc_chip.mul(ctx, &g, &m, WINDOW_SIZE)?;
+                let r_pk = ecc_chip.mul(ctx, &pk, &r, WINDOW_SIZE)?;
+                let c2 = ecc_chip.add(ctx, &m_g, &r_pk)?;
+
+                let limbs = [pk, c1, c2]
+                    .iter()
+                    .map(|point| ecc_chip.assign_ec_point_to_limbs(ctx, point))
+                    .collect::<Result<Vec<_>, plonk::Error>>()?;
+                Ok(limbs.into_iter().flatten().collect::<Vec<_>>())
+            },
+        )?;
+
+        for (row, limb) in limbs.into_iter().enumerate() {
+            config.expose_public(layouter.namespace(|| "ciphertext limb"), limb, row)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::halo2curves::{
+        bn256::{Fr, G1},
+        group::{Curve, Group},
+    };
+
+    use super::{encrypt, point_limbs, ElGamalCircuit, LIMBS};
+    use crate::dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location};
+
+    #[test]
+    fn elgamal() {
+        let sk = Fr::from(0x5ec7e7);
+        let pk = (G1::generator() * sk).to_affine();
+        let (m, r) = (Fr::from(42), Fr::from(0xdecaf) * Fr::from(u64::MAX));
+
+        let circuit = ElGamalCircuit::new(pk, m, r);
+        expect_satisfied(&circuit, circuit.instances());
+
+        // The secret key decrypts `m⋅G`.
+        let (c1, c2) = encrypt(pk, m, r);
+        assert_eq!(G1::from(c2) - c1 * sk, G1::generator() * m);
+
+        // `c2 + G` is a valid ciphertext of 43, but not the one computed from
+        // the witnessed plaintext: its first limb, after those of `pk` and
+        // `c1`, is not copied.
+        let mut instances = circuit.instances();
+        instances[0][4 * LIMBS..].copy_from_slice(&point_limbs(&(G1::from(c2) + G1::generator()).to_affine()));
+        expect_failure(
+            &circuit,
+            instances,
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 4 * LIMBS },
+            },
+        );
+    }
+}
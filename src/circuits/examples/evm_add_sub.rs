@@ -0,0 +1,250 @@
+//! The EVM `ADD` and `SUB` opcodes over 256-bit words, built from
+//! [`WordAddChip`] and [`SelectChip`].
+//!
+//! A step pops `a` (the top of the stack) and `b`, and pushes
+//! `c = a + b mod 2^256` for `ADD` or `c = a - b mod 2^256` for `SUB`. The
+//! stack is modeled as advice cells holding the `lo`/`hi` halves of the
+//! words, each range checked to 128 bits:
+//!
+//! | stack | opcode | is_sub | q_opcode |
+//! | a.lo  | op     | s      | 1        |
+//! | a.hi  |        |        |          |
+//! | b.lo  |        |        |          |
+//! | b.hi  |        |        |          |
+//!
+//! where `op = ADD + (SUB - ADD) ⋅ s` with `s` boolean, which only admits the
+//! two opcodes. As in a zkEVM, the opcode is a witness rather than part of
+//! the circuit: both `a + b` and `a - b` are computed, and `is_sub` selects
+//! the pushed word. Wrap-around is the EVM semantics, so the overflow flags
+//! of [`WordAddChip`] are ignored.
+//!
+//! The opcode, the popped words and the pushed word are public:
+//!
+//! | instance                                   |
+//! | opcode, a.lo, a.hi, b.lo, b.hi, c.lo, c.hi |
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::{
+    circuits::{
+        gadgets::{
+            select::{SelectChip, SelectConfig},
+            word_add::{WordAddChip, WordAddConfig},
+        },
+        instance::{InstanceColumns, InstanceLayout},
+        pack::PackConfig,
+        table::U8Table,
+        word::Word,
+    },
+    field::Field,
+};
+
+/// An arithmetic opcode, encoded by its EVM byte.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Opcode {
+    #[default]
+    Add = 0x01,
+    Sub = 0x03,
+}
+
+impl Opcode {
+    /// Applies the opcode to the `lo`/`hi` halves of `a` and `b`.
+    pub fn apply(self, [a_lo, a_hi]: [u128; 2], [b_lo, b_hi]: [u128; 2]) -> [u128; 2] {
+        match self {
+            Opcode::Add => {
+                let (lo, carry) = a_lo.overflowing_add(b_lo);
+                [lo, a_hi.wrapping_add(b_hi).wrapping_add(carry as u128)]
+            }
+            Opcode::Sub => {
+                let (lo, borrow) = a_lo.overflowing_sub(b_lo);
+                [lo, a_hi.wrapping_sub(b_hi).wrapping_sub(borrow as u128)]
+            }
+        }
+    }
+}
+
+/// Config for [`AddSubCircuit`].
+#[derive(Clone, Debug)]
+pub struct AddSubConfig {
+    q_opcode: Selector,
+    stack: Column<Advice>,
+    opcode: Column<Advice>,
+    is_sub: Column<Advice>,
+    word_add: WordAddConfig,
+    select: SelectConfig,
+    pack: PackConfig,
+    u8_table: U8Table,
+    instance: InstanceColumns,
+}
+
+/// Circuit executing one `ADD` or `SUB` on a stack of two words.
+#[derive(Clone, Debug, Default)]
+pub struct AddSubCircuit<F: Field> {
+    opcode: Value<Opcode>,
+    stack: Value<[[u128; 2]; 2]>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> AddSubCircuit<F> {
+    /// Creates the circuit popping `a`, then `b`, given as `lo`/`hi` halves.
+    pub fn new(opcode: Opcode, a: [u128; 2], b: [u128; 2]) -> Self {
+        Self {
+            opcode: Value::known(opcode),
+            stack: Value::known([a, b]),
+            _marker: PhantomData,
+        }
+    }
+
+    /// The opcode, the popped words and the pushed word.
+    pub fn instances(&self) -> Vec<Vec<F>> {
+        let mut instances = vec![];
+        self.opcode.zip(self.stack).map(|(opcode, [a, b])| {
+            let c = opcode.apply(a, b);
+            instances.push(F::from(opcode as u64));
+            instances.extend([a, b, c].into_iter().flatten().map(F::from_u128));
+        });
+        vec![instances]
+    }
+
+    /// A single instance column, see the [module documentation](self).
+    pub fn instance_layout() -> InstanceLayout {
+        InstanceLayout::new().column(["opcode", "a.lo", "a.hi", "b.lo", "b.hi", "c.lo", "c.hi"])
+    }
+}
+
+impl<F: Field> Circuit<F> for AddSubCircuit<F> {
+    type Config = AddSubConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let q_opcode = meta.selector();
+        let [stack, opcode, is_sub, byte, acc] = [(); 5].map(|_| meta.advice_column());
+        let u8_table = U8Table::configure(meta);
+        let pack = PackConfig::configure(meta, byte, acc, u8_table);
+        let word_add = WordAddChip::configure(meta, pack.clone());
+        let select = SelectChip::configure(meta);
+        let instance = Self::instance_layout().configure(meta);
+
+        for column in [stack, opcode, is_sub] {
+            meta.enable_equality(column);
+        }
+
+        meta.create_gate("add/sub opcode", |meta| {
+            let q_opcode = meta.query_selector(q_opcode);
+            let opcode = meta.query_advice(opcode, Rotation::cur());
+            let is_sub = meta.query_advice(is_sub, Rotation::cur());
+
+            let add = Expression::Constant(F::from(Opcode::Add as u64));
+            let sub = Expression::Constant(F::from(Opcode::Sub as u64));
+            vec![q_opcode * (opcode - add.clone() - (sub - add) * is_sub)]
+        });
+
+        AddSubConfig {
+            q_opcode,
+            stack,
+            opcode,
+            is_sub,
+            word_add,
+            select,
+            pack,
+            u8_table,
+            instance,
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let word_add = WordAddChip::construct(config.word_add.clone());
+        let select = SelectChip::construct(config.select.clone());
+        config.u8_table.load(&mut layouter)?;
+
+        let (opcode, is_sub, halves) = layouter.assign_region(
+            || "pop",
+            |mut region| {
+                config.q_opcode.enable(&mut region, 0)?;
+                let opcode = self.opcode.map(|opcode| F::from(opcode as u64));
+                let is_sub = self.opcode.map(|opcode| F::from((opcode == Opcode::Sub) as u64));
+                let opcode = region.assign_advice(|| "opcode", config.opcode, 0, || opcode)?;
+                let is_sub = region.assign_advice(|| "is_sub", config.is_sub, 0, || is_sub)?;
+
+                let halves = (0..4)
+                    .map(|i| {
+                        let half = self.stack.map(|stack| F::from_u128(stack[i / 2][i % 2]));
+                        region.assign_advice(|| "stack", config.stack, i, || half)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok((opcode, is_sub, halves))
+            },
+        )?;
+        for half in &halves {
+            config.pack.unpack(layouter.namespace(|| "stack half"), half, 16)?;
+        }
+        let a = Word::new([halves[0].clone(), halves[1].clone()]);
+        let b = Word::new([halves[2].clone(), halves[3].clone()]);
+
+        let (sum, _) = word_add.add(layouter.namespace(|| "a + b"), &a, &b)?;
+        let (diff, _) = word_add.sub(layouter.namespace(|| "a - b"), &a, &b)?;
+        let c: Vec<AssignedCell<F, F>> = [(diff.lo(), sum.lo()), (diff.hi(), sum.hi())]
+            .iter()
+            .map(|(diff, sum)| select.select(layouter.namespace(|| "push"), &is_sub, diff, sum))
+            .collect::<Result<_, _>>()?;
+
+        let public = [&opcode].into_iter().chain(&halves).chain(&c);
+        for (i, cell) in public.enumerate() {
+            config.instance.expose_public(&mut layouter, cell, i)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AddSubCircuit, Opcode};
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::TestField as Fp,
+    };
+
+    #[test]
+    fn add_sub() {
+        let max = u128::MAX;
+        let cases = [
+            (Opcode::Add, [5, 7], [3, 2], [8, 9]),
+            (Opcode::Add, [max, 0], [1, 0], [0, 1]),
+            // 2^256 - 1 + 1 wraps around to 0.
+            (Opcode::Add, [max, max], [1, 0], [0, 0]),
+            (Opcode::Sub, [5, 7], [3, 2], [2, 5]),
+            (Opcode::Sub, [0, 1], [1, 0], [max, 0]),
+            // 0 - 1 wraps around to 2^256 - 1.
+            (Opcode::Sub, [0, 0], [1, 0], [max, max]),
+        ];
+        for (opcode, a, b, c) in cases {
+            assert_eq!(opcode.apply(a, b), c);
+            let circuit = AddSubCircuit::<Fp>::new(opcode, a, b);
+            expect_satisfied(&circuit, circuit.instances());
+        }
+
+        // Pushing the difference on an `ADD`.
+        let circuit = AddSubCircuit::<Fp>::new(Opcode::Add, [5, 7], [3, 2]);
+        let mut wrong = circuit.instances();
+        wrong[0][5] = Fp::from(2);
+        expect_failure(
+            &circuit,
+            wrong,
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 5 },
+            },
+        );
+    }
+}
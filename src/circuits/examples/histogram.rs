@@ -0,0 +1,366 @@
+//! A public histogram of a committed private dataset.
+//!
+//! The `N` values are bytes, sorted into `B` buckets by increasing lower
+//! bounds `edges`, the first of which is 0: bucket `b` holds the values in
+//! `[edges[b], edges[b + 1])`, and the last one the values up to 255.
+//!
+//! The bucket of every byte is witnessed in advice columns, and looked up
+//! with [`ConstraintSystem::lookup_any`], with the selectors telling table
+//! rows from padding as in [`super::memory`]. The table counts the values up
+//! from 0 and its buckets up from 0, by steps of 0 or 1:
+//!
+//! | t_value | t_bucket  | q_table | q_step |
+//! | 0       | 0         | 1       | 0      |
+//! | v       | bucket(v) | 1       | 1      |
+//! | 255     | B - 1     | 1       | 1      |
+//!
+//! Each public edge `edges[b]`, for `b` in `[1, B)`, is looked up as the
+//! `(value, bucket, step)` row `(edges[b], b, 1)` of the table, which pins the
+//! bucket of every value to the edges:
+//!
+//! | edge     | edge_bucket | q_edge |
+//! | edges[b] | b           | 1      |
+//!
+//! Each value picks its bucket with one-hot bits, and the `(value, index)`
+//! pair is looked up. A running count per bucket adds the bits up:
+//!
+//! | value | bits[0..B]    | counts[0..B]             | q_value |
+//! |       |               | 0 ... 0                  | 0       |
+//! | v_1   | one-hot       | counts[prev] + bits      | 1       |
+//! | ...   | ...           | ...                      | 1       |
+//!
+//! The prover commits to the values as `H(salt, values[0], ...,
+//! values[N - 1])` with [`PoseidonChip`], and the commitment, the final
+//! counts and the edges are public:
+//!
+//! | instance                                                           |
+//! | commitment, counts[0], ..., counts[B - 1], edges[1], ..., edges[B - 1] |
+//!
+//! As the table is witnessed, every set of `B` edges shares a verifying key.
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+use crate::{
+    circuits::{
+        gadgets::{
+            one_hot::{OneHotChip, OneHotConfig},
+            poseidon::{PoseidonChip, PoseidonConfig, Spec},
+        },
+        instance::{InstanceColumns, InstanceLayout},
+    },
+    field::Field,
+};
+
+/// The bucket of `value`, given the increasing lower bounds `edges`.
+pub fn bucket<const B: usize>(edges: &[u8; B], value: u8) -> usize {
+    edges.iter().rposition(|edge| *edge <= value).unwrap()
+}
+
+/// Config for [`HistogramCircuit`].
+#[derive(Clone, Debug)]
+pub struct HistogramConfig<F: Field, const B: usize> {
+    q_value: Selector,
+    value: Column<Advice>,
+    bits: OneHotConfig<F, B>,
+    counts: [Column<Advice>; B],
+    q_table: Selector,
+    q_step: Selector,
+    t_value: Column<Advice>,
+    t_bucket: Column<Advice>,
+    q_edge: Selector,
+    edge: Column<Advice>,
+    edge_bucket: Column<Fixed>,
+    input: Column<Advice>,
+    poseidon: PoseidonConfig<F>,
+    instance: InstanceColumns,
+}
+
+/// Circuit exposing the histogram of `N` committed bytes over `B` buckets.
+#[derive(Clone, Debug)]
+pub struct HistogramCircuit<F: Field, const N: usize, const B: usize> {
+    edges: Value<[u8; B]>,
+    values: Value<[u8; N]>,
+    salt: Value<F>,
+}
+
+impl<F: Field, const N: usize, const B: usize> HistogramCircuit<F, N, B> {
+    /// Creates the circuit for the buckets starting at `edges`, and the
+    /// private `values` and `salt`.
+    pub fn new(edges: [u8; B], values: [u8; N], salt: F) -> Self {
+        assert!(edges[0] == 0 && edges.windows(2).all(|pair| pair[0] < pair[1]));
+        Self {
+            edges: Value::known(edges),
+            values: Value::known(values),
+            salt: Value::known(salt),
+        }
+    }
+
+    /// The commitment, the counts and the edges but the first.
+    pub fn instances(&self) -> Vec<Vec<F>> {
+        let mut instances = vec![];
+        self.values.zip(self.salt).zip(self.edges).map(|((values, salt), edges)| {
+            let preimage: Vec<F> = [salt].into_iter().chain(values.map(|value| F::from(value as u64))).collect();
+            let mut counts = [0u64; B];
+            for value in values {
+                counts[bucket(&edges, value)] += 1;
+            }
+            instances.push(Spec::new().hash(&preimage));
+            instances.extend(counts.map(F::from));
+            instances.extend(edges[1..].iter().map(|edge| F::from(*edge as u64)));
+        });
+        vec![instances]
+    }
+
+    /// A single instance column, see the [module documentation](self).
+    pub fn instance_layout() -> InstanceLayout {
+        let counts = (0..B).map(|b| format!("counts[{b}]"));
+        let edges = (1..B).map(|b| format!("edges[{b}]"));
+        InstanceLayout::new().column(["commitment".to_string()].into_iter().chain(counts).chain(edges))
+    }
+}
+
+impl<F: Field, const N: usize, const B: usize> Circuit<F> for HistogramCircuit<F, N, B> {
+    type Config = HistogramConfig<F, B>;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            edges: Value::unknown(),
+            values: Value::unknown(),
+            salt: Value::unknown(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let [q_value, q_table, q_step, q_edge] = [(); 4].map(|_| meta.complex_selector());
+        let [value, input, t_value, t_bucket, edge, s0, s1, s2, i0, i1] = [(); 10].map(|_| meta.advice_column());
+        let bits = [(); B].map(|_| meta.advice_column());
+        let counts = [(); B].map(|_| meta.advice_column());
+        let [edge_bucket, constant] = [(); 2].map(|_| meta.fixed_column());
+        let poseidon = PoseidonChip::configure(meta, [s0, s1, s2], [i0, i1], constant);
+        let instance = Self::instance_layout().configure(meta);
+
+        for column in [value, input, t_value, t_bucket, edge].into_iter().chain(counts) {
+            meta.enable_equality(column);
+        }
+
+        let bits = OneHotChip::configure(meta, |meta| meta.query_selector(q_value), bits);
+
+        meta.create_gate("histogram table", |meta| {
+            let q_step = meta.query_selector(q_step);
+            let [value, value_prev, bucket, bucket_prev] = [
+                (t_value, Rotation::cur()),
+                (t_value, Rotation::prev()),
+                (t_bucket, Rotation::cur()),
+                (t_bucket, Rotation::prev()),
+            ]
+            .map(|(column, rotation)| meta.query_advice(column, rotation));
+            let step = bucket - bucket_prev;
+
+            vec![
+                q_step.clone() * (value - value_prev - Expression::Constant(F::ONE)),
+                q_step * step.clone() * (Expression::Constant(F::ONE) - step),
+            ]
+        });
+
+        meta.lookup_any("histogram bucket", |meta| {
+            let q_value = meta.query_selector(q_value);
+            let q_table = meta.query_selector(q_table);
+            let value = meta.query_advice(value, Rotation::cur());
+            let [t_value, t_bucket] = [t_value, t_bucket].map(|column| meta.query_advice(column, Rotation::cur()));
+
+            vec![
+                (q_value.clone(), q_table.clone()),
+                (q_value.clone() * value, q_table.clone() * t_value),
+                (q_value * bits.index(), q_table * t_bucket),
+            ]
+        });
+
+        meta.lookup_any("histogram edge", |meta| {
+            let q_edge = meta.query_selector(q_edge);
+            let q_step = meta.query_selector(q_step);
+            let edge = meta.query_advice(edge, Rotation::cur());
+            let edge_bucket = meta.query_fixed(edge_bucket, Rotation::cur());
+            let t_value = meta.query_advice(t_value, Rotation::cur());
+            let t_bucket = meta.query_advice(t_bucket, Rotation::cur());
+            let step = t_bucket.clone() - meta.query_advice(t_bucket, Rotation::prev());
+
+            vec![
+                (q_edge.clone(), q_step.clone()),
+                (q_edge.clone() * edge, q_step.clone() * t_value),
+                (q_edge.clone() * edge_bucket, q_step.clone() * t_bucket),
+                (q_edge, q_step * step),
+            ]
+        });
+
+        meta.create_gate("histogram count", |meta| {
+            let q_value = meta.query_selector(q_value);
+            (counts.iter().zip(bits.bits()))
+                .map(|(column, bit)| {
+                    let count = meta.query_advice(*column, Rotation::cur());
+                    let count_prev = meta.query_advice(*column, Rotation::prev());
+                    q_value.clone() * (count - count_prev - bit.clone())
+                })
+                .collect::<Vec<_>>()
+        });
+
+        HistogramConfig {
+            q_value,
+            value,
+            bits,
+            counts,
+            q_table,
+            q_step,
+            t_value,
+            t_bucket,
+            q_edge,
+            edge,
+            edge_bucket,
+            input,
+            poseidon,
+            instance,
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_region(
+            || "histogram buckets",
+            |mut region| {
+                region.assign_advice_from_constant(|| "value", config.t_value, 0, F::ZERO)?;
+                region.assign_advice_from_constant(|| "bucket", config.t_bucket, 0, F::ZERO)?;
+                config.q_table.enable(&mut region, 0)?;
+
+                for value in 1..=255u8 {
+                    let offset = value as usize;
+                    config.q_table.enable(&mut region, offset)?;
+                    config.q_step.enable(&mut region, offset)?;
+                    let t_bucket = self.edges.map(|edges| F::from(bucket(&edges, value) as u64));
+                    region.assign_advice(|| "value", config.t_value, offset, || Value::known(F::from(value as u64)))?;
+                    region.assign_advice(|| "bucket", config.t_bucket, offset, || t_bucket)?;
+                }
+                Ok(())
+            },
+        )?;
+
+        let edges = layouter.assign_region(
+            || "histogram edges",
+            |mut region| {
+                (1..B)
+                    .map(|b| {
+                        config.q_edge.enable(&mut region, b - 1)?;
+                        let bucket = Value::known(F::from(b as u64));
+                        region.assign_fixed(|| "bucket", config.edge_bucket, b - 1, || bucket)?;
+                        let edge = self.edges.map(|edges| F::from(edges[b] as u64));
+                        region.assign_advice(|| format!("edges[{b}]"), config.edge, b - 1, || edge)
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            },
+        )?;
+
+        let (salt, values) = layouter.assign_region(
+            || "inputs",
+            |mut region| {
+                let salt = region.assign_advice(|| "salt", config.input, 0, || self.salt)?;
+                let values = (0..N)
+                    .map(|i| {
+                        let value = self.values.map(|values| F::from(values[i] as u64));
+                        region.assign_advice(|| format!("values[{i}]"), config.input, i + 1, || value)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok((salt, values))
+            },
+        )?;
+
+        let chip = OneHotChip::construct(config.bits.clone());
+        let counts = layouter.assign_region(
+            || "histogram",
+            |mut region| {
+                let mut counts: Vec<AssignedCell<F, F>> = (config.counts.iter())
+                    .map(|column| region.assign_advice_from_constant(|| "count", *column, 0, F::ZERO))
+                    .collect::<Result<_, _>>()?;
+
+                for (i, value) in values.iter().enumerate() {
+                    config.q_value.enable(&mut region, i + 1)?;
+                    value.copy_advice(|| "value", &mut region, config.value, i + 1)?;
+
+                    let index = (value.value().zip(self.edges))
+                        .map(|(value, edges)| F::from(bucket(&edges, value.to_repr()[0]) as u64));
+                    let bits = chip.assign(&mut region, i + 1, index)?;
+                    for (count, (bit, column)) in counts.iter_mut().zip(bits.iter().zip(config.counts)) {
+                        let next = count.value().copied() + bit.value().copied();
+                        *count = region.assign_advice(|| "count", column, i + 1, || next)?;
+                    }
+                }
+                Ok(counts)
+            },
+        )?;
+
+        let poseidon = PoseidonChip::construct(config.poseidon);
+        let preimage: Vec<_> = [salt].into_iter().chain(values).collect();
+        let commitment = poseidon.hash(layouter.namespace(|| "commitment"), &preimage)?;
+
+        for (i, cell) in [&commitment].into_iter().chain(&counts).chain(&edges).enumerate() {
+            config.instance.expose_public(&mut layouter, cell, i)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bucket, HistogramCircuit};
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::TestField as Fp,
+    };
+
+    const EDGES: [u8; 4] = [0, 18, 40, 65];
+
+    #[test]
+    fn histogram() {
+        assert_eq!([0, 17, 18, 64, 65, 255].map(|value| bucket(&EDGES, value)), [0, 0, 1, 2, 3, 3]);
+
+        let ages = [34, 17, 65, 40, 22, 90, 0, 39];
+        let circuit = HistogramCircuit::new(EDGES, ages, Fp::from(5));
+        assert_eq!(circuit.instances()[0][1..], [2u64, 3, 1, 2].map(Fp::from));
+        expect_satisfied(&circuit, circuit.instances());
+
+        // Moving a value to the next bucket.
+        let mut wrong = circuit.instances();
+        wrong[0][2] = Fp::from(2);
+        wrong[0][3] = Fp::from(2);
+        expect_failure(
+            &circuit,
+            wrong,
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 2 },
+            },
+        );
+
+        // Claiming the counts under another first edge.
+        let mut wrong = circuit.instances();
+        wrong[0][5] = Fp::from(17);
+        expect_failure(
+            &circuit,
+            wrong,
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 5 },
+            },
+        );
+    }
+
+    #[test]
+    fn histogram_edges() {
+        // The same circuit shape serves other edges, as its buckets are witnessed.
+        let ages = [34, 17, 65, 40, 22, 90, 0, 39];
+        let circuit = HistogramCircuit::new([0, 30, 60, 200], ages, Fp::from(5));
+        assert_eq!(circuit.instances()[0][1..], [3u64, 3, 2, 0, 30, 60, 200].map(Fp::from));
+        expect_satisfied(&circuit, circuit.instances());
+    }
+}
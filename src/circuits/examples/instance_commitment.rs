@@ -0,0 +1,180 @@
+//! Committed public inputs: the circuit exposes a single hash of its inputs
+//! instead of the inputs themselves.
+//!
+//! An EVM verifier pays calldata for every instance, so a circuit with many
+//! public inputs is cheaper to verify when it takes them as private cells,
+//! checks them as usual and exposes only
+//!
+//! ```text
+//! commitment = H(inputs[0], ..., inputs[N - 1])
+//! ```
+//!
+//! The verifier, which knows the inputs, recomputes the commitment with
+//! [`commit`] instead of passing them. The encoding is [`HashSpec::hash`] of
+//! the inputs in order. The number of inputs is fixed by the circuit, so it
+//! isn't hashed, and a contract must hash with the same Poseidon parameters
+//! as [`Spec`].
+//!
+//! Here the inputs are `N` amounts, each range checked to 64 bits.
+//!
+//! | instance   |
+//! | commitment |
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+};
+
+use crate::{
+    circuits::{
+        gadgets::{
+            bits::BitsConfig,
+            hash::{HashInstructions, HashSpec},
+            poseidon::{PoseidonChip, Spec},
+        },
+        instance::{InstanceColumns, InstanceLayout},
+    },
+    field::Field,
+};
+
+/// Bits of each amount.
+pub const AMOUNT_BITS: usize = 64;
+
+/// The Poseidon commitment to `inputs`, the only instance of the circuit.
+pub fn commit<F: Field>(inputs: &[F]) -> F {
+    commit_with(&Spec::new(), inputs)
+}
+
+/// [`commit`] with the hash of `spec`.
+pub fn commit_with<F: Field>(spec: &impl HashSpec<F>, inputs: &[F]) -> F {
+    spec.hash(inputs)
+}
+
+/// Config for [`InstanceCommitmentCircuit`].
+#[derive(Clone, Debug)]
+pub struct InstanceCommitmentConfig<F: Field, H: HashInstructions<F>> {
+    hash: H::Config,
+    bits: BitsConfig,
+    input: Column<Advice>,
+    instance: InstanceColumns,
+}
+
+/// Circuit range checking `N` amounts and exposing their commitment.
+#[derive(Clone, Debug)]
+pub struct InstanceCommitmentCircuit<F: Field, const N: usize, H: HashInstructions<F> = PoseidonChip<F>> {
+    amounts: Value<[u64; N]>,
+    _marker: PhantomData<(F, H)>,
+}
+
+impl<F: Field, const N: usize, H: HashInstructions<F>> Default for InstanceCommitmentCircuit<F, N, H> {
+    fn default() -> Self {
+        Self {
+            amounts: Value::unknown(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: Field, const N: usize, H: HashInstructions<F>> InstanceCommitmentCircuit<F, N, H> {
+    pub fn new(amounts: [u64; N]) -> Self {
+        Self {
+            amounts: Value::known(amounts),
+            _marker: PhantomData,
+        }
+    }
+
+    /// The commitment to the amounts.
+    pub fn instances(&self) -> Vec<Vec<F>> {
+        let mut instances = vec![];
+        self.amounts.map(|amounts| {
+            instances.push(commit_with(&H::Spec::default(), &amounts.map(F::from)));
+        });
+        vec![instances]
+    }
+
+    /// A single instance column, see the [module documentation](self).
+    pub fn instance_layout() -> InstanceLayout {
+        InstanceLayout::new().column(["commitment"])
+    }
+}
+
+impl<F: Field, const N: usize, H: HashInstructions<F>> Circuit<F> for InstanceCommitmentCircuit<F, N, H> {
+    type Config = InstanceCommitmentConfig<F, H>;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let [input, acc, s0, s1, s2, i0, i1] = [(); 7].map(|_| meta.advice_column());
+        let constant = meta.fixed_column();
+        meta.enable_equality(input);
+
+        InstanceCommitmentConfig {
+            hash: H::configure(meta, [s0, s1, s2], [i0, i1], constant),
+            bits: BitsConfig::configure(meta, acc),
+            input,
+            instance: Self::instance_layout().configure(meta),
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let amounts = layouter.assign_region(
+            || "inputs",
+            |mut region| {
+                (0..N)
+                    .map(|i| {
+                        let amount = self.amounts.map(|amounts| F::from(amounts[i]));
+                        region.assign_advice(|| "amount", config.input, i, || amount)
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            },
+        )?;
+
+        for (i, amount) in amounts.iter().enumerate() {
+            config
+                .bits
+                .decompose(layouter.namespace(|| format!("amount {i}")), amount, AMOUNT_BITS)?;
+        }
+
+        let commitment = H::construct(config.hash).hash(layouter.namespace(|| "commitment"), &amounts)?;
+        config.instance.expose_public(&mut layouter, &commitment, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{commit, InstanceCommitmentCircuit};
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::TestField as Fp,
+    };
+
+    #[test]
+    fn instance_commitment() {
+        let amounts = [100, 250, 0, u64::MAX, 42, 7, 1 << 40, 3];
+        let circuit = InstanceCommitmentCircuit::<Fp, 8>::new(amounts);
+        assert_eq!(
+            InstanceCommitmentCircuit::<Fp, 8>::instance_layout().num_instance(),
+            vec![1]
+        );
+        assert_eq!(circuit.instances(), vec![vec![commit(&amounts.map(Fp::from))]]);
+        expect_satisfied(&circuit, circuit.instances());
+
+        // The verifier's inputs differ from the prover's.
+        let mut other = amounts;
+        other[4] = 43;
+        expect_failure(
+            &circuit,
+            vec![vec![commit(&other.map(Fp::from))]],
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+    }
+}
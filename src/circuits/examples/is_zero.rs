@@ -0,0 +1,177 @@
+//! Exposes whether a private value is zero, using [`IsZeroChip`].
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
+    poly::Rotation,
+};
+
+use crate::circuits::gadgets::is_zero::{IsZeroChip, IsZeroConfig, IsZeroInstruction};
+use crate::circuits::instance::{InstanceColumns, InstanceLayout};
+use crate::circuits::sub_circuit::{SharedColumns, SubCircuit};
+use crate::field::Field;
+
+/// Config for [`IsZeroCircuit`].
+#[derive(Clone, Debug)]
+pub struct IsZeroCircuitConfig<F> {
+    q_enable: Selector,
+    value: Column<Advice>,
+    is_zero: IsZeroConfig<F>,
+}
+
+/// Circuit exposing `1` if the private `value` is zero and `0` otherwise.
+#[derive(Clone, Debug, Default)]
+pub struct IsZeroCircuit<F: Field> {
+    value: Value<F>,
+    is_zero: bool,
+}
+
+impl<F: Field> IsZeroCircuit<F> {
+    /// Creates the circuit for the private `value`.
+    pub fn new(value: F) -> Self {
+        Self {
+            value: Value::known(value),
+            is_zero: value.is_zero_vartime(),
+        }
+    }
+
+    /// The public is_zero bit.
+    pub fn instances(&self) -> Vec<Vec<F>> {
+        vec![self.public_inputs()]
+    }
+
+    /// A single instance column holding the is_zero bit.
+    pub fn instance_layout() -> InstanceLayout {
+        InstanceLayout::new().column(["is_zero"])
+    }
+
+    fn configure_gates(
+        meta: &mut ConstraintSystem<F>,
+        value: Column<Advice>,
+        value_inv: Column<Advice>,
+        out: Column<Advice>,
+    ) -> IsZeroCircuitConfig<F> {
+        let q_enable = meta.selector();
+
+        // | value | value_inv | out | q |
+        // | v     | inv0(v)   | b   | 1 |
+        let is_zero = IsZeroChip::configure(
+            meta,
+            |meta| meta.query_selector(q_enable),
+            |meta| meta.query_advice(value, Rotation::cur()),
+            value_inv,
+            out,
+        );
+
+        IsZeroCircuitConfig {
+            q_enable,
+            value,
+            is_zero,
+        }
+    }
+
+    /// Assigns `value` and returns the `out` cell.
+    fn assign(
+        &self,
+        config: &IsZeroCircuitConfig<F>,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let chip = IsZeroChip::construct(config.is_zero.clone());
+
+        layouter.assign_region(
+            || "is_zero",
+            |mut region| {
+                config.q_enable.enable(&mut region, 0)?;
+                region.assign_advice(|| "value", config.value, 0, || self.value)?;
+                chip.assign(&mut region, 0, self.value)
+            },
+        )
+    }
+}
+
+impl<F: Field> Circuit<F> for IsZeroCircuit<F> {
+    type Config = (IsZeroCircuitConfig<F>, InstanceColumns);
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let value = meta.advice_column();
+        let value_inv = meta.advice_column();
+        let out = meta.advice_column();
+
+        let config = Self::configure_gates(meta, value, value_inv, out);
+        (config, Self::instance_layout().configure(meta))
+    }
+
+    fn synthesize(&self, (config, instance): Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let out = self.assign(&config, layouter.namespace(|| "is_zero"))?;
+
+        instance.expose_public(&mut layouter, &out, 0)
+    }
+}
+
+impl<F: Field> SubCircuit<F> for IsZeroCircuit<F> {
+    type Config = IsZeroCircuitConfig<F>;
+
+    fn configure_sub(meta: &mut ConstraintSystem<F>, shared: SharedColumns) -> Self::Config {
+        let [value, value_inv, out, ..] = shared.advice;
+        Self::configure_gates(meta, value, value_inv, out)
+    }
+
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        layouter: impl Layouter<F>,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        Ok(vec![self.assign(config, layouter)?])
+    }
+
+    fn min_rows(&self) -> usize {
+        1
+    }
+
+    fn public_inputs(&self) -> Vec<F> {
+        vec![F::from(self.is_zero as u64)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::dev::MockProver;
+
+    use super::IsZeroCircuit;
+    use crate::dev::fuzz::{field, gadget_proptest};
+    use crate::field::TestField as Fp;
+
+    #[test]
+    fn is_zero_circuit() {
+        for value in [0, 1, 7] {
+            let circuit = IsZeroCircuit::new(Fp::from(value));
+            let prover = MockProver::run(4, &circuit, circuit.instances()).unwrap();
+            prover.assert_satisfied();
+        }
+
+        // Claiming the wrong bit fails.
+        let circuit = IsZeroCircuit::new(Fp::from(0));
+        let prover = MockProver::run(4, &circuit, vec![vec![Fp::from(0)]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    gadget_proptest! {
+        is_zero_complete(value in field()) {
+            circuit: IsZeroCircuit::new(value),
+            instances: IsZeroCircuit::new(value).instances(),
+            valid: true,
+        }
+        is_zero_sound(value in field()) {
+            circuit: IsZeroCircuit::new(value),
+            instances: vec![vec![Fp::from((value != Fp::from(0)) as u64)]],
+            valid: false,
+        }
+    }
+}
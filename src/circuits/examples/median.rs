@@ -0,0 +1,218 @@
+//! The median of a committed private dataset.
+//!
+//! The prover commits to `N` values, `N` odd, as
+//! `H(salt, values[0], ..., values[N - 1])` with [`PoseidonChip`], and
+//! witnesses them sorted. Three gadgets then pin the median:
+//!
+//! - [`GrandProductConfig`] argues that the sorted values are a permutation
+//!   of the committed ones, multiplicities included,
+//! - [`SortedChip`] checks that they are in non-decreasing order, each step
+//!   fitting in [`VALUE_BYTES`] bytes,
+//! - the middle one, `sorted[N / 2]`, is copied to the instance.
+//!
+//! | instance           |
+//! | commitment, median |
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+};
+
+use crate::{
+    circuits::{
+        gadgets::{
+            grand_product::GrandProductConfig,
+            poseidon::{PoseidonChip, PoseidonConfig, Spec},
+            sorted::{SortedChip, SortedConfig},
+        },
+        instance::{InstanceColumns, InstanceLayout},
+        table::U8Table,
+    },
+    field::Field,
+};
+
+/// Bytes of a value.
+pub const VALUE_BYTES: usize = 4;
+
+/// Config for [`MedianCircuit`].
+#[derive(Clone, Debug)]
+pub struct MedianConfig<F: Field> {
+    input: Column<Advice>,
+    poseidon: PoseidonConfig<F>,
+    permutation: GrandProductConfig<1>,
+    sorted: SortedConfig<VALUE_BYTES>,
+    u8_table: U8Table,
+    instance: InstanceColumns,
+}
+
+/// Circuit exposing the median of `N` committed values.
+#[derive(Clone, Debug)]
+pub struct MedianCircuit<F: Field, const N: usize> {
+    values: Value<[u32; N]>,
+    sorted: Value<[u32; N]>,
+    salt: Value<F>,
+}
+
+impl<F: Field, const N: usize> Default for MedianCircuit<F, N> {
+    fn default() -> Self {
+        Self {
+            values: Value::unknown(),
+            sorted: Value::unknown(),
+            salt: Value::unknown(),
+        }
+    }
+}
+
+impl<F: Field, const N: usize> MedianCircuit<F, N> {
+    /// Creates the circuit for the private `values` and `salt`.
+    pub fn new(values: [u32; N], salt: F) -> Self {
+        assert!(N % 2 == 1, "the median of an even number of values is not an element");
+        let mut sorted = values;
+        sorted.sort_unstable();
+        Self {
+            values: Value::known(values),
+            sorted: Value::known(sorted),
+            salt: Value::known(salt),
+        }
+    }
+
+    /// The commitment and the median.
+    pub fn instances(&self) -> Vec<Vec<F>> {
+        let mut instances = vec![];
+        self.values.zip(self.sorted).zip(self.salt).map(|((values, sorted), salt)| {
+            let preimage: Vec<F> = [salt].into_iter().chain(values.map(|value| F::from(value as u64))).collect();
+            instances.extend([Spec::new().hash(&preimage), F::from(sorted[N / 2] as u64)]);
+        });
+        vec![instances]
+    }
+
+    /// A single instance column, see the [module documentation](self).
+    pub fn instance_layout() -> InstanceLayout {
+        InstanceLayout::new().column(["commitment", "median"])
+    }
+}
+
+impl<F: Field, const N: usize> Circuit<F> for MedianCircuit<F, N> {
+    type Config = MedianConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let [input, a, b, s0, s1, s2, i0, i1] = [(); 8].map(|_| meta.advice_column());
+        let constant = meta.fixed_column();
+        let u8_table = U8Table::configure(meta);
+        meta.enable_equality(input);
+
+        MedianConfig {
+            input,
+            poseidon: PoseidonChip::configure(meta, [s0, s1, s2], [i0, i1], constant),
+            permutation: GrandProductConfig::configure(meta, [a], [b]),
+            sorted: SortedChip::configure(meta, u8_table),
+            u8_table,
+            instance: Self::instance_layout().configure(meta),
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        config.u8_table.load(&mut layouter)?;
+
+        let (salt, values, sorted) = layouter.assign_region(
+            || "inputs",
+            |mut region| {
+                let salt = region.assign_advice(|| "salt", config.input, 0, || self.salt)?;
+                let mut assign = |name: &str, values: Value<[u32; N]>, offset: usize| {
+                    (0..N)
+                        .map(|i| {
+                            let value = values.map(|values| F::from(values[i] as u64));
+                            region.assign_advice(|| format!("{name}[{i}]"), config.input, offset + i, || value)
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                };
+                let values = assign("values", self.values, 1)?;
+                let sorted = assign("sorted", self.sorted, 1 + N)?;
+                Ok((salt, values, sorted))
+            },
+        )?;
+
+        let poseidon = PoseidonChip::construct(config.poseidon);
+        let preimage: Vec<_> = [salt].into_iter().chain(values.iter().cloned()).collect();
+        let commitment = poseidon.hash(layouter.namespace(|| "commitment"), &preimage)?;
+
+        let values: Vec<_> = values.into_iter().map(|cell| [cell]).collect();
+        let tuples: Vec<_> = sorted.iter().map(|cell| [cell.clone()]).collect();
+        config.permutation.assign(layouter.namespace(|| "permutation"), &values, &tuples)?;
+        SortedChip::construct(config.sorted).assign(layouter.namespace(|| "sorted"), &sorted)?;
+
+        config.instance.expose_public(&mut layouter, &commitment, 0)?;
+        config.instance.expose_public(&mut layouter, &sorted[N / 2], 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::circuit::Value;
+
+    use super::MedianCircuit;
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::TestField as Fp,
+    };
+
+    #[test]
+    fn median() {
+        let values = [50, 10, 90, 30, 70, 30, 1 << 31];
+        let circuit = MedianCircuit::new(values, Fp::from(77));
+        assert_eq!(circuit.instances()[0][1], Fp::from(50));
+        expect_satisfied(&circuit, circuit.instances());
+
+        // Claiming another median.
+        let mut wrong = circuit.instances();
+        wrong[0][1] = Fp::from(30);
+        expect_failure(
+            &circuit,
+            wrong,
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 1 },
+            },
+        );
+
+        // Sorted values that are not the committed ones.
+        let circuit = MedianCircuit {
+            sorted: Value::known([10, 30, 30, 60, 70, 90, 1 << 31]),
+            ..MedianCircuit::new(values, Fp::from(77))
+        };
+        expect_failure(
+            &circuit,
+            circuit.instances(),
+            FailureMatcher::Constraint {
+                gate: "grand product end",
+                location: Location::InRegion {
+                    region: "grand product",
+                    offset: 7,
+                },
+            },
+        );
+
+        // The committed values, out of order.
+        let circuit = MedianCircuit {
+            sorted: Value::known([10, 30, 30, 70, 50, 90, 1 << 31]),
+            ..MedianCircuit::new(values, Fp::from(77))
+        };
+        expect_failure(
+            &circuit,
+            circuit.instances(),
+            FailureMatcher::Constraint {
+                gate: "sorted",
+                location: Location::InRegion {
+                    region: "sorted",
+                    offset: 4,
+                },
+            },
+        );
+    }
+}
@@ -0,0 +1,364 @@
+//! Memory consistency of a public trace of reads and writes, with the
+//! address-ordered memory argument.
+//!
+//! The trace lists `N` accesses `(addr, value, is_write)` in execution order,
+//! the `i`th one at timestamp `ts = i + 1`. The same accesses are witnessed a
+//! second time sorted by `(addr, ts)`, where checking consistency is local:
+//! every read must return the value of the previous access to its address, or
+//! 0 if it is the first one.
+//!
+//! | trace: addr | ts (fixed) | value | is_write | sorted: addr | ts | value | is_write | diff[0..2] | same_addr |
+//!
+//! - permutation: every sorted row is looked up in the trace. Sorted rows are
+//!   distinct as they are strictly ordered, and there are `N` of each, so the
+//!   lookup is a bijection.
+//! - order: `diff = same_addr ? ts' - ts - 1 : addr' - addr - 1` is in
+//!   `[0, 2^16)`, where `'` is the next row and `same_addr = (addr' == addr)`.
+//! - consistency: a read returns the previous value of the same address, and
+//!   0 on a new address.
+//!
+//! Addresses must be less than `2^16` apart, and `N` less than `2^16`.
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+use crate::{
+    circuits::{
+        gadgets::is_zero_2::{IsZeroChip, IsZeroConfig, IsZeroInstruction},
+        instance::{InstanceColumns, InstanceLayout},
+        table::U8Table,
+        util::constraint_builder::ConstraintBuilder,
+    },
+    field::Field,
+};
+
+/// A memory access.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryOp<F> {
+    pub addr: F,
+    pub value: F,
+    pub is_write: bool,
+}
+
+/// Columns of a `(addr, ts, value, is_write)` table.
+#[derive(Clone, Copy, Debug)]
+struct Accesses<C> {
+    addr: Column<Advice>,
+    ts: C,
+    value: Column<Advice>,
+    is_write: Column<Advice>,
+}
+
+/// Config for [`MemoryCircuit`].
+#[derive(Clone, Debug)]
+pub struct MemoryConfig<F> {
+    q_trace: Selector,
+    q_sorted: Selector,
+    q_first: Selector,
+    q_step: Selector,
+    trace: Accesses<Column<Fixed>>,
+    sorted: Accesses<Column<Advice>>,
+    diff: [Column<Advice>; 2],
+    same_addr: IsZeroConfig<F>,
+    u8_table: U8Table,
+    instance: InstanceColumns,
+}
+
+/// Circuit proving that the public trace of `N` memory accesses is
+/// consistent.
+#[derive(Clone, Debug)]
+pub struct MemoryCircuit<F: Field, const N: usize> {
+    ops: Value<[MemoryOp<F>; N]>,
+}
+
+impl<F: Field, const N: usize> Default for MemoryCircuit<F, N> {
+    fn default() -> Self {
+        Self { ops: Value::unknown() }
+    }
+}
+
+impl<F: Field, const N: usize> MemoryCircuit<F, N> {
+    /// Creates the circuit for the trace `ops`, in execution order.
+    pub fn new(ops: [MemoryOp<F>; N]) -> Self {
+        Self { ops: Value::known(ops) }
+    }
+
+    /// The trace, as `(addr, value, is_write)` for each access.
+    pub fn instances(&self) -> Vec<Vec<F>> {
+        let mut instances = vec![];
+        self.ops.map(|ops| {
+            instances.extend(ops.iter().flat_map(|op| [op.addr, op.value, F::from(op.is_write as u64)]));
+        });
+        vec![instances]
+    }
+
+    /// A single instance column holding the trace, see [`Self::instances`].
+    pub fn instance_layout() -> InstanceLayout {
+        let names = (0..N).flat_map(|i| [format!("addr[{i}]"), format!("value[{i}]"), format!("is_write[{i}]")]);
+        InstanceLayout::new().column(names)
+    }
+}
+
+impl<F: Field, const N: usize> Circuit<F> for MemoryCircuit<F, N> {
+    type Config = MemoryConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let q_trace = meta.complex_selector();
+        let q_sorted = meta.complex_selector();
+        let q_first = meta.selector();
+        let q_step = meta.complex_selector();
+        let [addr, value, is_write] = [(); 3].map(|_| meta.advice_column());
+        let trace = Accesses {
+            addr,
+            ts: meta.fixed_column(),
+            value,
+            is_write,
+        };
+        let [addr, ts, value, is_write] = [(); 4].map(|_| meta.advice_column());
+        let sorted = Accesses {
+            addr,
+            ts,
+            value,
+            is_write,
+        };
+        let diff = [(); 2].map(|_| meta.advice_column());
+        let [addr_diff_inv, same_addr] = [(); 2].map(|_| meta.advice_column());
+        let u8_table = U8Table::configure(meta);
+        let instance = Self::instance_layout().configure(meta);
+
+        for column in [trace.addr, trace.value, trace.is_write] {
+            meta.enable_equality(column);
+        }
+
+        meta.lookup_any("memory permutation", |meta| {
+            let q_trace = meta.query_selector(q_trace);
+            let q_sorted = meta.query_selector(q_sorted);
+            let trace = [
+                meta.query_advice(trace.addr, Rotation::cur()),
+                meta.query_fixed(trace.ts, Rotation::cur()),
+                meta.query_advice(trace.value, Rotation::cur()),
+                meta.query_advice(trace.is_write, Rotation::cur()),
+            ];
+            let sorted = [sorted.addr, sorted.ts, sorted.value, sorted.is_write]
+                .map(|column| meta.query_advice(column, Rotation::cur()));
+
+            // The selectors themselves tell real rows from padding.
+            std::iter::once((q_sorted.clone(), q_trace.clone()))
+                .chain(sorted.into_iter().zip(trace).map(|(sorted, trace)| {
+                    (q_sorted.clone() * sorted, q_trace.clone() * trace)
+                }))
+                .collect()
+        });
+
+        for column in diff {
+            meta.lookup("memory diff byte", |meta| {
+                let q_step = meta.query_selector(q_step);
+                vec![(q_step * meta.query_advice(column, Rotation::cur()), u8_table.value)]
+            });
+        }
+
+        let same_addr = IsZeroChip::configure(
+            meta,
+            |meta| meta.query_selector(q_step),
+            |meta| {
+                meta.query_advice(sorted.addr, Rotation::next()) - meta.query_advice(sorted.addr, Rotation::cur())
+            },
+            addr_diff_inv,
+            same_addr,
+        );
+
+        meta.create_gate("memory", |meta| {
+            let [q_sorted, q_first, q_step] = [q_sorted, q_first, q_step].map(|q| meta.query_selector(q));
+            let [addr, ts, value, is_write] = [sorted.addr, sorted.ts, sorted.value, sorted.is_write]
+                .map(|column| meta.query_advice(column, Rotation::cur()));
+            let [addr_next, ts_next, value_next, is_write_next] =
+                [sorted.addr, sorted.ts, sorted.value, sorted.is_write]
+                    .map(|column| meta.query_advice(column, Rotation::next()));
+            let diff = meta.query_advice(diff[0], Rotation::cur())
+                + meta.query_advice(diff[1], Rotation::cur()) * Expression::Constant(F::from(256));
+
+            let one = Expression::Constant(F::ONE);
+            let same_addr = same_addr.expr();
+            let is_read_next = one.clone() - is_write_next;
+
+            let mut cb = ConstraintBuilder::default();
+            cb.condition(q_sorted, |cb| cb.require_boolean("is_write", is_write.clone()));
+            cb.condition(q_first * (one.clone() - is_write), |cb| cb.require_zero("first read is zero", value.clone()));
+            cb.condition(q_step, |cb| {
+                cb.require_equal(
+                    "sorted by address, then time",
+                    diff,
+                    same_addr.clone() * (ts_next - ts - one.clone())
+                        + (one.clone() - same_addr.clone()) * (addr_next - addr - one.clone()),
+                );
+                cb.condition(is_read_next, |cb| {
+                    cb.condition(same_addr.clone(), |cb| {
+                        cb.require_equal("read returns the last value", value_next.clone(), value)
+                    });
+                    cb.condition(one - same_addr, |cb| cb.require_zero("new address reads zero", value_next));
+                });
+            });
+            cb.build()
+        });
+
+        MemoryConfig {
+            q_trace,
+            q_sorted,
+            q_first,
+            q_step,
+            trace,
+            sorted,
+            diff,
+            same_addr,
+            u8_table,
+            instance,
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        config.u8_table.load(&mut layouter)?;
+
+        let trace = layouter.assign_region(
+            || "trace",
+            |mut region| {
+                let mut cells: Vec<AssignedCell<F, F>> = vec![];
+                for i in 0..N {
+                    config.q_trace.enable(&mut region, i)?;
+                    let op = self.ops.map(|ops| ops[i]);
+                    region.assign_fixed(|| "ts", config.trace.ts, i, || Value::known(F::from(i as u64 + 1)))?;
+                    cells.push(region.assign_advice(|| "addr", config.trace.addr, i, || op.map(|op| op.addr))?);
+                    cells.push(region.assign_advice(|| "value", config.trace.value, i, || op.map(|op| op.value))?);
+                    let is_write = op.map(|op| F::from(op.is_write as u64));
+                    cells.push(region.assign_advice(|| "is_write", config.trace.is_write, i, || is_write)?);
+                }
+                Ok(cells)
+            },
+        )?;
+
+        layouter.assign_region(
+            || "sorted",
+            |mut region| {
+                let same_addr = IsZeroChip::construct(config.same_addr.clone());
+                let sorted = self.ops.map(|ops| {
+                    let mut sorted: Vec<_> = (ops.into_iter().enumerate())
+                        .map(|(i, op)| (op, F::from(i as u64 + 1)))
+                        .collect();
+                    sorted.sort_by(|(a, a_ts), (b, b_ts)| (a.addr, a_ts).cmp(&(b.addr, b_ts)));
+                    sorted
+                });
+
+                for i in 0..N {
+                    config.q_sorted.enable(&mut region, i)?;
+                    let (op, ts) = sorted.as_ref().map(|sorted| sorted[i]).unzip();
+                    region.assign_advice(|| "addr", config.sorted.addr, i, || op.map(|op| op.addr))?;
+                    region.assign_advice(|| "ts", config.sorted.ts, i, || ts)?;
+                    region.assign_advice(|| "value", config.sorted.value, i, || op.map(|op| op.value))?;
+                    let is_write = op.map(|op| F::from(op.is_write as u64));
+                    region.assign_advice(|| "is_write", config.sorted.is_write, i, || is_write)?;
+
+                    if i == 0 {
+                        config.q_first.enable(&mut region, i)?;
+                    }
+                    if i + 1 < N {
+                        config.q_step.enable(&mut region, i)?;
+                        let (next, ts_next) = sorted.as_ref().map(|sorted| sorted[i + 1]).unzip();
+                        let addr_diff = next.zip(op).map(|(next, op)| next.addr - op.addr);
+                        let diff = addr_diff.zip(ts.zip(ts_next)).map(|(addr_diff, (ts, ts_next))| {
+                            if addr_diff == F::ZERO {
+                                ts_next - ts - F::ONE
+                            } else {
+                                addr_diff - F::ONE
+                            }
+                        });
+                        for (j, column) in config.diff.iter().enumerate() {
+                            let byte = diff.map(|diff| F::from(diff.to_repr()[j] as u64));
+                            region.assign_advice(|| format!("diff[{j}]"), *column, i, || byte)?;
+                        }
+                        same_addr.assign(&mut region, i, addr_diff)?;
+                    }
+                }
+                Ok(())
+            },
+        )?;
+
+        for (i, cell) in trace.iter().enumerate() {
+            config.instance.expose_public(&mut layouter, cell, i)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MemoryCircuit, MemoryOp};
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::TestField as Fp,
+    };
+
+    fn op(addr: u64, value: u64, is_write: bool) -> MemoryOp<Fp> {
+        MemoryOp {
+            addr: Fp::from(addr),
+            value: Fp::from(value),
+            is_write,
+        }
+    }
+
+    #[test]
+    fn memory_consistency() {
+        let ops = [
+            op(7, 0, false),
+            op(7, 5, true),
+            op(3, 9, true),
+            op(7, 5, false),
+            op(3, 9, false),
+            op(7, 6, true),
+            op(3, 9, false),
+            op(7, 6, false),
+        ];
+        let circuit = MemoryCircuit::new(ops);
+        expect_satisfied(&circuit, circuit.instances());
+
+        // Reading 5 at address 7 after 6 was written.
+        let mut stale = ops;
+        stale[7] = op(7, 5, false);
+        let circuit = MemoryCircuit::new(stale);
+        expect_failure(
+            &circuit,
+            circuit.instances(),
+            FailureMatcher::Constraint {
+                gate: "memory",
+                location: Location::InRegion {
+                    region: "sorted",
+                    offset: 6,
+                },
+            },
+        );
+
+        // Reading an address that was never written.
+        let mut uninit = ops;
+        uninit[4] = op(4, 1, false);
+        let circuit = MemoryCircuit::new(uninit);
+        expect_failure(
+            &circuit,
+            circuit.instances(),
+            FailureMatcher::Constraint {
+                gate: "memory",
+                location: Location::InRegion {
+                    region: "sorted",
+                    offset: 1,
+                },
+            },
+        );
+    }
+}
@@ -0,0 +1,324 @@
+//! Merkle Mountain Range inclusion: a leaf is in an append-only log committed
+//! to by a public root.
+//!
+//! An MMR of `size` leaves is a list of perfect Merkle trees, its peaks, one
+//! of depth `d` for each bit `2^d` of `size`, from the largest to the
+//! smallest. Appending a leaf only merges the smallest peaks, so the roots of
+//! older peaks never change. The root bags the peaks together with the size:
+//!
+//! ```text
+//! root = H(size, peak[0], ..., peak[n - 1])
+//! ```
+//!
+//! A proof gives the path of the leaf in its peak, checked with
+//! [`MerkleChip`], and the other peaks. The size and which peak holds the
+//! leaf fix the shape of the circuit, while the position of the leaf in its
+//! peak stays private. They are the [`MmrParams`] of the circuit, its
+//! `Params` with the `circuit-params` feature, and like [`MerkleParams`] they
+//! only set the number of levels hashed, not the gates.
+//!
+//! [`MerkleParams`]: crate::circuits::gadgets::merkle::MerkleParams
+//!
+//! | instance   |
+//! | root, leaf |
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+};
+
+use crate::{
+    circuits::{
+        gadgets::{
+            hash::{HashInstructions, HashSpec},
+            merkle::{MerkleChip, MerkleConfig, MerkleTree},
+            poseidon::{PoseidonChip, Spec},
+            select::SelectChip,
+        },
+        instance::{InstanceColumns, InstanceLayout},
+    },
+    field::Field,
+};
+
+/// The depths of the peaks of an MMR of `size` leaves, largest first.
+pub fn peak_depths(size: usize) -> Vec<usize> {
+    (0..usize::BITS as usize)
+        .rev()
+        .filter(|depth| (size >> depth) & 1 == 1)
+        .collect()
+}
+
+/// `H(size, peaks[0], ..., peaks[n - 1])`, the root of an MMR of `size`
+/// leaves with the roots of its `peaks`.
+pub fn bag_peaks<F: Field>(spec: &impl HashSpec<F>, size: usize, peaks: &[F]) -> F {
+    let mut inputs = vec![F::from(size as u64)];
+    inputs.extend_from_slice(peaks);
+    spec.hash(&inputs)
+}
+
+/// Off-circuit Merkle Mountain Range.
+#[derive(Clone, Debug)]
+pub struct Mmr<F: Field> {
+    size: usize,
+    peaks: Vec<MerkleTree<F>>,
+}
+
+/// A proof of inclusion of a leaf in an [`Mmr`].
+#[derive(Clone, Debug)]
+pub struct MmrProof<F> {
+    /// The peak holding the leaf.
+    pub peak: usize,
+    /// The path of the leaf in its peak.
+    pub path: Vec<(F, bool)>,
+    /// The roots of all peaks.
+    pub peaks: Vec<F>,
+}
+
+impl<F: Field> Mmr<F> {
+    /// Builds the Poseidon MMR of `leaves`.
+    pub fn new(leaves: Vec<F>) -> Self {
+        Self::with_spec(leaves, &Spec::new())
+    }
+
+    /// Builds the MMR of `leaves`, hashing with `spec`.
+    pub fn with_spec(leaves: Vec<F>, spec: &impl HashSpec<F>) -> Self {
+        let size = leaves.len();
+        let mut leaves = leaves.into_iter();
+        let peaks = (peak_depths(size).into_iter())
+            .map(|depth| MerkleTree::with_spec(depth, leaves.by_ref().take(1 << depth).collect(), spec))
+            .collect();
+        Self { size, peaks }
+    }
+
+    pub fn root(&self) -> F {
+        self.root_with(&Spec::new())
+    }
+
+    /// [`Mmr::root`] with the hash of `spec`, which must be the one the MMR
+    /// was built with.
+    pub fn root_with(&self, spec: &impl HashSpec<F>) -> F {
+        let peaks: Vec<_> = self.peaks.iter().map(MerkleTree::root).collect();
+        bag_peaks(spec, self.size, &peaks)
+    }
+
+    /// The proof of inclusion of leaf `index`.
+    pub fn proof(&self, index: usize) -> MmrProof<F> {
+        assert!(index < self.size);
+        let mut start = 0;
+        for (peak, depth) in peak_depths(self.size).into_iter().enumerate() {
+            if index < start + (1 << depth) {
+                return MmrProof {
+                    peak,
+                    path: self.peaks[peak].path(index - start),
+                    peaks: self.peaks.iter().map(MerkleTree::root).collect(),
+                };
+            }
+            start += 1 << depth;
+        }
+        unreachable!()
+    }
+}
+
+/// Config for [`MmrCircuit`].
+#[derive(Clone, Debug)]
+pub struct MmrConfig<F: Field, H: HashInstructions<F>> {
+    merkle: MerkleConfig<F, H>,
+    input: Column<Advice>,
+    instance: InstanceColumns,
+}
+
+/// Size of a [`MmrCircuit`], its `Params` with the `circuit-params` feature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MmrParams {
+    /// Number of leaves of the MMR.
+    pub size: usize,
+    /// Index of the peak holding the leaf, largest first.
+    pub peak: usize,
+}
+
+impl Default for MmrParams {
+    /// An MMR of 11 leaves, with peaks of depth 3, 1 and 0, and a leaf in the
+    /// first peak.
+    fn default() -> Self {
+        Self { size: 11, peak: 0 }
+    }
+}
+
+/// Circuit proving inclusion of a leaf in peak `params.peak` of an MMR of
+/// `params.size` leaves.
+#[derive(Clone, Debug)]
+pub struct MmrCircuit<F: Field, H: HashInstructions<F> = PoseidonChip<F>> {
+    params: MmrParams,
+    leaf: Value<F>,
+    proof: Value<MmrProof<F>>,
+    _marker: PhantomData<H>,
+}
+
+impl<F: Field, H: HashInstructions<F>> MmrCircuit<F, H> {
+    /// Creates the circuit proving `leaf` is in an MMR of `size` leaves with
+    /// `proof`.
+    pub fn new(size: usize, leaf: F, proof: MmrProof<F>) -> Self {
+        let depths = peak_depths(size);
+        assert_eq!(proof.peaks.len(), depths.len());
+        assert_eq!(proof.path.len(), depths[proof.peak]);
+        Self {
+            params: MmrParams { size, peak: proof.peak },
+            leaf: Value::known(leaf),
+            proof: Value::known(proof),
+            _marker: PhantomData,
+        }
+    }
+
+    /// The circuit for an MMR of `params`, without witnesses.
+    pub fn with_params(params: MmrParams) -> Self {
+        Self {
+            params,
+            leaf: Value::unknown(),
+            proof: Value::unknown(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// The root and the leaf.
+    pub fn instances(&self) -> Vec<Vec<F>> {
+        let mut instances = vec![];
+        self.leaf.zip(self.proof.as_ref()).map(|(leaf, proof)| {
+            instances.extend([bag_peaks(&H::Spec::default(), self.params.size, &proof.peaks), leaf]);
+        });
+        vec![instances]
+    }
+
+    /// A single instance column, see the [module documentation](self).
+    pub fn instance_layout() -> InstanceLayout {
+        InstanceLayout::new().column(["root", "leaf"])
+    }
+}
+
+impl<F: Field, H: HashInstructions<F>> Circuit<F> for MmrCircuit<F, H> {
+    type Config = MmrConfig<F, H>;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = MmrParams;
+
+    fn without_witnesses(&self) -> Self {
+        Self::with_params(self.params)
+    }
+
+    #[cfg(feature = "circuit-params")]
+    fn params(&self) -> Self::Params {
+        self.params
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let [input, s0, s1, s2, i0, i1] = [(); 6].map(|_| meta.advice_column());
+        let constant = meta.fixed_column();
+        meta.enable_equality(input);
+
+        MmrConfig {
+            merkle: MerkleConfig {
+                hash: H::configure(meta, [s0, s1, s2], [i0, i1], constant),
+                select: SelectChip::configure(meta),
+            },
+            input,
+            instance: Self::instance_layout().configure(meta),
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let depths = peak_depths(self.params.size);
+        let mut inputs = vec![self.leaf];
+        for level in 0..depths[self.params.peak] {
+            let node = self.proof.as_ref().map(|proof| proof.path[level]);
+            inputs.extend([
+                node.map(|(sibling, _)| sibling),
+                node.map(|(_, is_right)| F::from(is_right as u64)),
+            ]);
+        }
+        for peak in (0..depths.len()).filter(|peak| *peak != self.params.peak) {
+            inputs.push(self.proof.as_ref().map(|proof| proof.peaks[peak]));
+        }
+
+        let (size, inputs) = layouter.assign_region(
+            || "inputs",
+            |mut region| {
+                let size = F::from(self.params.size as u64);
+                let size = region.assign_advice_from_constant(|| "size", config.input, 0, size)?;
+                let inputs = (inputs.iter().enumerate())
+                    .map(|(i, value)| region.assign_advice(|| "input", config.input, 1 + i, || *value))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok((size, inputs))
+            },
+        )?;
+        let leaf = &inputs[0];
+        let (path, others) = inputs[1..].split_at(2 * depths[self.params.peak]);
+        let path: Vec<_> = path.chunks(2).map(|node| (node[0].clone(), node[1].clone())).collect();
+
+        let hash = H::construct(config.merkle.hash.clone());
+        let peak = MerkleChip::construct(config.merkle).root(layouter.namespace(|| "peak"), leaf, &path)?;
+        let mut bagged = vec![size];
+        bagged.extend(others[..self.params.peak].iter().cloned());
+        bagged.push(peak);
+        bagged.extend(others[self.params.peak..].iter().cloned());
+        let root = hash.hash(layouter.namespace(|| "root"), &bagged)?;
+
+        config.instance.expose_public(&mut layouter, &root, 0)?;
+        config.instance.expose_public(&mut layouter, leaf, 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::plonk::Circuit;
+
+    use super::{peak_depths, Mmr, MmrCircuit, MmrParams};
+    use crate::{
+        dev::{
+            mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+            rows::rows_used,
+        },
+        field::TestField as Fp,
+    };
+
+    #[test]
+    fn mmr() {
+        assert_eq!(peak_depths(11), [3, 1, 0]);
+        let leaves: Vec<_> = (1..=11).map(Fp::from).collect();
+        let mmr = Mmr::new(leaves.clone());
+
+        // A leaf in each peak.
+        for index in [5, 9, 10] {
+            let circuit = MmrCircuit::<_>::new(11, leaves[index], mmr.proof(index));
+            assert_eq!(circuit.instances()[0][0], mmr.root());
+            expect_satisfied(&circuit, circuit.instances());
+
+            // The keys are generated without witnesses, for the same peak.
+            let keygen = circuit.without_witnesses();
+            assert_eq!(keygen.params, MmrParams { size: 11, peak: mmr.proof(index).peak });
+            assert_eq!(rows_used(&keygen).unwrap(), rows_used(&circuit).unwrap());
+        }
+
+        // Appending a leaf changes the root, though not the first peak.
+        let appended = Mmr::new((1..=12).map(Fp::from).collect());
+        assert_eq!(appended.proof(5).peaks[0], mmr.proof(5).peaks[0]);
+        let circuit = MmrCircuit::<_>::new(11, leaves[5], mmr.proof(5));
+        expect_failure(
+            &circuit,
+            vec![vec![appended.root(), leaves[5]]],
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+
+        // Leaf 9 with the proof of leaf 8.
+        let circuit = MmrCircuit::<_>::new(11, leaves[9], mmr.proof(8));
+        expect_failure(
+            &circuit,
+            vec![vec![mmr.root(), leaves[9]]],
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+    }
+}
@@ -0,0 +1,32 @@
+//! Complete example circuits built from the chips and gadgets of this crate.
+
+pub mod aes;
+pub mod aggregation;
+pub mod airdrop;
+pub mod auction;
+pub mod battleship;
+pub mod bytecode;
+pub mod chacha20;
+pub mod coloring;
+pub mod dfa;
+pub mod elgamal;
+pub mod evm_add_sub;
+pub mod histogram;
+pub mod instance_commitment;
+pub mod is_zero;
+pub mod median;
+pub mod memory;
+pub mod mmr;
+pub mod poseidon;
+pub mod range_check;
+pub mod reachability;
+pub mod shuffle;
+pub mod simple;
+pub mod stacked_planner;
+pub mod statistics;
+pub mod super_circuit;
+pub mod threshold;
+pub mod tornado;
+pub mod tuple_lookup;
+pub mod vdf;
+pub mod vrf;
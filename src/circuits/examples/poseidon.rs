@@ -0,0 +1,144 @@
+//! Exposes the Poseidon hash of private inputs, using [`PoseidonChip`].
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+};
+
+use crate::{
+    circuits::{
+        gadgets::poseidon::{PoseidonChip, PoseidonConfig, Spec},
+        instance::{InstanceColumns, InstanceLayout},
+        sub_circuit::{SharedColumns, SubCircuit},
+    },
+    field::Field,
+};
+
+/// Config for [`PoseidonCircuit`].
+#[derive(Clone, Debug)]
+pub struct PoseidonCircuitConfig<F: Field> {
+    poseidon: PoseidonConfig<F>,
+    input: Column<Advice>,
+}
+
+/// Circuit exposing the hash of `L` private inputs.
+#[derive(Clone, Debug, Default)]
+pub struct PoseidonCircuit<F: Field, const L: usize> {
+    inputs: Value<[F; L]>,
+    hash: F,
+}
+
+impl<F: Field, const L: usize> PoseidonCircuit<F, L> {
+    /// Creates the circuit for the private `inputs`.
+    pub fn new(inputs: [F; L]) -> Self {
+        Self {
+            inputs: Value::known(inputs),
+            hash: Spec::new().hash(&inputs),
+        }
+    }
+
+    /// The public hash.
+    pub fn instances(&self) -> Vec<Vec<F>> {
+        vec![self.public_inputs()]
+    }
+
+    /// A single instance column holding the hash.
+    pub fn instance_layout() -> InstanceLayout {
+        InstanceLayout::new().column(["hash"])
+    }
+}
+
+impl<F: Field, const L: usize> Circuit<F> for PoseidonCircuit<F, L> {
+    type Config = (PoseidonCircuitConfig<F>, InstanceColumns);
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let shared = SharedColumns::configure(meta);
+        (Self::configure_sub(meta, shared), Self::instance_layout().configure(meta))
+    }
+
+    fn synthesize(&self, (config, instance): Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let hash = self.synthesize_sub(&config, layouter.namespace(|| "poseidon"))?;
+        instance.expose_public(&mut layouter, &hash[0], 0)
+    }
+}
+
+impl<F: Field, const L: usize> SubCircuit<F> for PoseidonCircuit<F, L> {
+    type Config = PoseidonCircuitConfig<F>;
+
+    fn configure_sub(meta: &mut ConstraintSystem<F>, shared: SharedColumns) -> Self::Config {
+        let [s0, s1, s2, i0, i1] = shared.advice;
+        PoseidonCircuitConfig {
+            poseidon: PoseidonChip::configure(meta, [s0, s1, s2], [i0, i1], shared.constant),
+            input: s0,
+        }
+    }
+
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let chip = PoseidonChip::construct(config.poseidon.clone());
+
+        let inputs = layouter.assign_region(
+            || "inputs",
+            |mut region| {
+                (0..L)
+                    .map(|i| {
+                        let input = self.inputs.map(|inputs| inputs[i]);
+                        region.assign_advice(|| format!("input {i}"), config.input, i, || input)
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            },
+        )?;
+
+        Ok(vec![chip.hash(layouter.namespace(|| "hash"), &inputs)?])
+    }
+
+    fn min_rows(&self) -> usize {
+        L + PoseidonChip::<F>::rows(L)
+    }
+
+    fn public_inputs(&self) -> Vec<F> {
+        vec![self.hash]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PoseidonCircuit;
+    use crate::dev::fuzz::{field, gadget_proptest};
+    use crate::dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location};
+    use crate::field::TestField as Fp;
+
+    #[test]
+    fn poseidon_circuit() {
+        let circuit = PoseidonCircuit::new([Fp::from(1), Fp::from(2)]);
+        expect_satisfied(&circuit, circuit.instances());
+
+        // The inputs are not hashed in any order.
+        let swapped = PoseidonCircuit::new([Fp::from(2), Fp::from(1)]);
+        expect_failure(
+            &circuit,
+            swapped.instances(),
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+    }
+
+    gadget_proptest! {
+        poseidon_complete(a in field(), b in field()) {
+            circuit: PoseidonCircuit::new([a, b]),
+            instances: PoseidonCircuit::new([a, b]).instances(),
+            valid: true,
+        }
+    }
+}
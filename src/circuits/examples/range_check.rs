@@ -0,0 +1,186 @@
+//! Checks that a private value lies in `[0, range)`, using the polynomial range
+//! check of [`crate::circuits::range_check_1`].
+//!
+//! The range sets the degree of the gate, so it is part of the configuration
+//! rather than of the witness. It is given by the [`RangeCheckParams`] of the
+//! circuit, which halo2 passes to `configure_with_params` with the
+//! `circuit-params` feature. Without it, circuits are configured with the
+//! default params, and [`RangeCheckCircuit`] fails to synthesize for any
+//! other range.
+
+use halo2_proofs::{
+    circuit::{floor_planner::V1, AssignedCell, Layouter, Value},
+    plonk::{Assigned, Circuit, ConstraintSystem, Error},
+};
+
+use crate::circuits::range_check_1::RangeCheckConfig;
+use crate::circuits::sub_circuit::{SharedColumns, SubCircuit};
+use crate::field::Field;
+
+/// Size of a [`RangeCheckCircuit`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RangeCheckParams {
+    /// The checked value lies in `[0, range)`.
+    pub range: usize,
+}
+
+impl Default for RangeCheckParams {
+    fn default() -> Self {
+        Self { range: 16 }
+    }
+}
+
+/// Circuit proving that the private `value` is in `[0, range)`.
+#[derive(Clone, Debug, Default)]
+pub struct RangeCheckCircuit<F: Field> {
+    value: Value<F>,
+    params: RangeCheckParams,
+}
+
+impl<F: Field> RangeCheckCircuit<F> {
+    /// Creates the circuit for the private `value`, with the default params.
+    pub fn new(value: F) -> Self {
+        Self::with_params(value, RangeCheckParams::default())
+    }
+
+    /// Creates the circuit for the private `value` in `[0, params.range)`.
+    pub fn with_params(value: F, params: RangeCheckParams) -> Self {
+        Self {
+            value: Value::known(value),
+            params,
+        }
+    }
+
+    /// The circuit has no public inputs.
+    pub fn instances(&self) -> Vec<Vec<F>> {
+        vec![]
+    }
+}
+
+impl<F: Field> Circuit<F> for RangeCheckCircuit<F> {
+    type Config = RangeCheckConfig<F>;
+    type FloorPlanner = V1;
+    #[cfg(feature = "circuit-params")]
+    type Params = RangeCheckParams;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            params: self.params,
+            ..Self::default()
+        }
+    }
+
+    #[cfg(feature = "circuit-params")]
+    fn params(&self) -> Self::Params {
+        self.params
+    }
+
+    #[cfg(feature = "circuit-params")]
+    fn configure_with_params(meta: &mut ConstraintSystem<F>, params: Self::Params) -> Self::Config {
+        let value = meta.advice_column();
+        RangeCheckConfig::configure(meta, value, params.range)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let value = meta.advice_column();
+        RangeCheckConfig::configure(meta, value, RangeCheckParams::default().range)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        self.synthesize_sub(&config, layouter.namespace(|| "Assign value"))?;
+
+        Ok(())
+    }
+}
+
+impl<F: Field> SubCircuit<F> for RangeCheckCircuit<F> {
+    type Config = RangeCheckConfig<F>;
+
+    fn configure_sub(meta: &mut ConstraintSystem<F>, shared: SharedColumns) -> Self::Config {
+        RangeCheckConfig::configure(meta, shared.advice[0], RangeCheckParams::default().range)
+    }
+
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        layouter: impl Layouter<F>,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        // Configured with other params, see the module documentation.
+        if config.range() != self.params.range {
+            return Err(Error::Synthesis);
+        }
+        config.assign(layouter, self.value.map(Assigned::from))?;
+
+        Ok(vec![])
+    }
+
+    fn min_rows(&self) -> usize {
+        1
+    }
+
+    fn public_inputs(&self) -> Vec<F> {
+        vec![]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, plonk::Circuit};
+
+    use super::{RangeCheckCircuit, RangeCheckParams};
+    use crate::dev::fuzz::{field, gadget_proptest};
+    use crate::dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location};
+    use crate::field::TestField as Fp;
+
+    #[test]
+    fn range_check_circuit() {
+        let circuit = RangeCheckCircuit::<Fp>::new(Fp::from(15));
+        expect_satisfied(&circuit, circuit.instances());
+
+        let circuit = RangeCheckCircuit::<Fp>::new(Fp::from(16));
+        expect_failure(
+            &circuit,
+            circuit.instances(),
+            FailureMatcher::Constraint {
+                gate: "range check",
+                location: Location::InRegion {
+                    region: "Assign value",
+                    offset: 0,
+                },
+            },
+        );
+    }
+
+    #[test]
+    fn range_params() {
+        let params = RangeCheckParams { range: 4 };
+        let circuit = RangeCheckCircuit::with_params(Fp::from(3), params);
+        assert_eq!(circuit.without_witnesses().params, params);
+
+        if cfg!(feature = "circuit-params") {
+            expect_satisfied(&circuit, vec![]);
+            expect_failure(
+                &RangeCheckCircuit::with_params(Fp::from(4), params),
+                vec![],
+                FailureMatcher::Constraint {
+                    gate: "range check",
+                    location: Location::InRegion {
+                        region: "Assign value",
+                        offset: 0,
+                    },
+                },
+            );
+        } else {
+            // Configured for the default range.
+            assert!(MockProver::run(4, &circuit, vec![]).is_err());
+        }
+    }
+
+    gadget_proptest! {
+        range_check(value in field()) {
+            circuit: RangeCheckCircuit::<Fp>::new(value),
+            instances: vec![],
+            valid: value < Fp::from(16),
+        }
+    }
+}
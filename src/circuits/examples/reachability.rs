@@ -0,0 +1,331 @@
+//! Reachability in a committed graph: a private walk of at most `L` edges
+//! connects two public vertices.
+//!
+//! The graph is committed as the root of a Merkle tree of its directed edges
+//! `H(u, v)`, so neither its edges nor the walk are revealed. The walk is
+//! `L` steps over the vertices `v_0, ..., v_L`: a step either moves along an
+//! edge, whose leaf `H(v_i, v_i+1)` is proven in the tree with [`MerkleChip`],
+//! or stays in place, which pads shorter walks:
+//!
+//! | vertex | stay | q_step |
+//! | v_0    | s_0  | 1      |
+//! | v_1    | s_1  | 1      |
+//! | ...    | ...  | ...    |
+//! | v_L    |      | 0      |
+//!
+//! - `stay` is boolean,
+//! - `stay = 1 ⇒ v' = v`.
+//!
+//! The root of a staying step isn't checked: [`SelectChip`] replaces it with
+//! the public root before it is copied to the instance.
+//!
+//! | instance             |
+//! | root, source, target |
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
+    poly::Rotation,
+};
+
+use crate::{
+    circuits::{
+        gadgets::{
+            merkle::{MerkleChip, MerkleConfig, MerkleTree},
+            poseidon::{PoseidonChip, Spec},
+            select::SelectChip,
+        },
+        instance::{InstanceColumns, InstanceLayout},
+        util::constraint_builder::ConstraintBuilder,
+    },
+    field::Field,
+};
+
+/// A directed graph committed as the Merkle tree of its edges.
+#[derive(Clone, Debug)]
+pub struct CommittedGraph<F: Field> {
+    edges: Vec<(u64, u64)>,
+    tree: MerkleTree<F>,
+}
+
+impl<F: Field> CommittedGraph<F> {
+    /// Commits to `edges` in a tree of depth `depth`.
+    pub fn new(depth: usize, edges: Vec<(u64, u64)>) -> Self {
+        let tree = MerkleTree::new(depth, edges.iter().map(|(u, v)| Self::leaf(*u, *v)).collect());
+        Self { edges, tree }
+    }
+
+    /// `H(u, v)`, the leaf of the edge from `u` to `v`.
+    pub fn leaf(u: u64, v: u64) -> F {
+        Spec::new().hash(&[F::from(u), F::from(v)])
+    }
+
+    pub fn root(&self) -> F {
+        self.tree.root()
+    }
+
+    /// The Merkle path of the edge from `u` to `v`, if there is one.
+    pub fn path(&self, u: u64, v: u64) -> Option<Vec<(F, bool)>> {
+        let index = self.edges.iter().position(|edge| *edge == (u, v))?;
+        Some(self.tree.path(index))
+    }
+}
+
+/// A step of a walk: the next vertex, and the path of the edge to it in a
+/// tree of depth `DEPTH`, or `None` to stay in place.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Step<F, const DEPTH: usize> {
+    pub to: u64,
+    pub edge: Option<[(F, bool); DEPTH]>,
+}
+
+/// Config for [`ReachabilityCircuit`].
+#[derive(Clone, Debug)]
+pub struct ReachabilityConfig<F: Field> {
+    q_step: Selector,
+    vertex: Column<Advice>,
+    stay: Column<Advice>,
+    input: Column<Advice>,
+    merkle: MerkleConfig<F>,
+    instance: InstanceColumns,
+}
+
+/// Circuit proving a walk of at most `L` edges in a graph committed in a tree
+/// of depth `DEPTH`.
+#[derive(Clone, Debug)]
+pub struct ReachabilityCircuit<F: Field, const L: usize, const DEPTH: usize> {
+    root: Value<F>,
+    source: Value<u64>,
+    steps: Value<[Step<F, DEPTH>; L]>,
+}
+
+impl<F: Field, const L: usize, const DEPTH: usize> Default for ReachabilityCircuit<F, L, DEPTH> {
+    fn default() -> Self {
+        Self {
+            root: Value::unknown(),
+            source: Value::unknown(),
+            steps: Value::unknown(),
+        }
+    }
+}
+
+impl<F: Field, const L: usize, const DEPTH: usize> ReachabilityCircuit<F, L, DEPTH> {
+    /// Creates the circuit for the walk through `vertices` in `graph`, padded
+    /// to `L` steps.
+    ///
+    /// # Panics
+    ///
+    /// If the walk is empty or longer than `L` edges, or if it follows an edge
+    /// that isn't in the graph.
+    pub fn new(graph: &CommittedGraph<F>, vertices: &[u64]) -> Self {
+        assert!(!vertices.is_empty() && vertices.len() <= L + 1);
+        let target = vertices[vertices.len() - 1];
+        let steps = std::array::from_fn(|i| match vertices.get(i..i + 2) {
+            Some(&[u, v]) => {
+                let path = graph.path(u, v).expect("edge of the walk in the graph");
+                Step {
+                    to: v,
+                    edge: Some(path.try_into().unwrap()),
+                }
+            }
+            _ => Step { to: target, edge: None },
+        });
+        Self {
+            root: Value::known(graph.root()),
+            source: Value::known(vertices[0]),
+            steps: Value::known(steps),
+        }
+    }
+
+    /// The root of the graph, the source and the target.
+    pub fn instances(&self) -> Vec<Vec<F>> {
+        let mut instances = vec![];
+        (self.root.zip(self.source).zip(self.steps)).map(|((root, source), steps)| {
+            let target = steps.last().map_or(source, |step| step.to);
+            instances.extend([root, F::from(source), F::from(target)]);
+        });
+        vec![instances]
+    }
+
+    /// A single instance column, see the [module documentation](self).
+    pub fn instance_layout() -> InstanceLayout {
+        InstanceLayout::new().column(["root", "source", "target"])
+    }
+}
+
+impl<F: Field, const L: usize, const DEPTH: usize> Circuit<F> for ReachabilityCircuit<F, L, DEPTH> {
+    type Config = ReachabilityConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let q_step = meta.selector();
+        let [vertex, stay, input, s0, s1, s2, i0, i1] = [(); 8].map(|_| meta.advice_column());
+        let constant = meta.fixed_column();
+        for column in [vertex, stay, input] {
+            meta.enable_equality(column);
+        }
+
+        meta.create_gate("walk", |meta| {
+            let vertex_next = meta.query_advice(vertex, Rotation::next());
+            let vertex = meta.query_advice(vertex, Rotation::cur());
+            let stay = meta.query_advice(stay, Rotation::cur());
+
+            let mut cb = ConstraintBuilder::default();
+            cb.require_boolean("stay is boolean", stay.clone());
+            cb.condition(stay, |cb| cb.require_equal("stay in place", vertex_next, vertex));
+            cb.gate(meta.query_selector(q_step))
+        });
+
+        ReachabilityConfig {
+            q_step,
+            vertex,
+            stay,
+            input,
+            merkle: MerkleConfig {
+                hash: PoseidonChip::configure(meta, [s0, s1, s2], [i0, i1], constant),
+                select: SelectChip::configure(meta),
+            },
+            instance: Self::instance_layout().configure(meta),
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let mut inputs = vec![self.root];
+        for i in 0..L {
+            let edge = self.steps.map(|steps| steps[i].edge.unwrap_or([(F::ZERO, false); DEPTH]));
+            for level in 0..DEPTH {
+                let node = edge.map(|edge| edge[level]);
+                inputs.extend([node.map(|(sibling, _)| sibling), node.map(|(_, is_right)| F::from(is_right as u64))]);
+            }
+        }
+        let inputs = layouter.assign_region(
+            || "inputs",
+            |mut region| {
+                (inputs.iter().enumerate())
+                    .map(|(i, value)| region.assign_advice(|| "input", config.input, i, || *value))
+                    .collect::<Result<Vec<_>, _>>()
+            },
+        )?;
+        let root = inputs[0].clone();
+        let paths: Vec<Vec<_>> = (inputs[1..].chunks(2 * DEPTH))
+            .map(|path| path.chunks(2).map(|node| (node[0].clone(), node[1].clone())).collect())
+            .collect();
+
+        let (vertices, stays) = layouter.assign_region(
+            || "walk",
+            |mut region| {
+                let source = self.source.map(|source| F::from(source));
+                let mut vertices = vec![region.assign_advice(|| "v_0", config.vertex, 0, || source)?];
+                let mut stays = vec![];
+                for i in 0..L {
+                    config.q_step.enable(&mut region, i)?;
+                    let step = self.steps.map(|steps| steps[i]);
+                    let stay = step.map(|step| F::from(step.edge.is_none() as u64));
+                    let to = step.map(|step| F::from(step.to));
+                    stays.push(region.assign_advice(|| format!("s_{i}"), config.stay, i, || stay)?);
+                    vertices.push(region.assign_advice(|| format!("v_{}", i + 1), config.vertex, i + 1, || to)?);
+                }
+                Ok((vertices, stays))
+            },
+        )?;
+
+        let poseidon = PoseidonChip::construct(config.merkle.hash.clone());
+        let select = SelectChip::construct(config.merkle.select.clone());
+        let merkle = MerkleChip::construct(config.merkle);
+        for (i, (stay, path)) in stays.iter().zip(&paths).enumerate() {
+            let mut layouter = layouter.namespace(|| format!("step {i}"));
+            let edge = [vertices[i].clone(), vertices[i + 1].clone()];
+            let leaf = poseidon.hash(layouter.namespace(|| "leaf"), &edge)?;
+            let edge_root = merkle.root(layouter.namespace(|| "merkle"), &leaf, path)?;
+            let edge_root = select.select(layouter.namespace(|| "root"), stay, &root, &edge_root)?;
+            config.instance.expose_public(&mut layouter, &edge_root, 0)?;
+        }
+
+        for (i, cell) in [&root, &vertices[0], &vertices[L]].into_iter().enumerate() {
+            config.instance.expose_public(&mut layouter, cell, i)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CommittedGraph, ReachabilityCircuit, Step};
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::TestField as Fp,
+    };
+
+    fn graph() -> CommittedGraph<Fp> {
+        CommittedGraph::new(3, vec![(1, 2), (2, 3), (3, 4), (2, 5), (5, 4), (4, 6), (6, 1)])
+    }
+
+    #[test]
+    fn reachability() {
+        let graph = graph();
+        for walk in [&[1, 2, 5, 4, 6][..], &[1, 2, 3, 4], &[6, 1], &[3]] {
+            let circuit = ReachabilityCircuit::<_, 4, 3>::new(&graph, walk);
+            let target = *walk.last().unwrap();
+            assert_eq!(circuit.instances()[0][1..], [Fp::from(walk[0]), Fp::from(target)]);
+            expect_satisfied(&circuit, circuit.instances());
+        }
+
+        // Claiming to reach another vertex.
+        let circuit = ReachabilityCircuit::<_, 4, 3>::new(&graph, &[1, 2, 3]);
+        let mut wrong = circuit.instances();
+        wrong[0][2] = Fp::from(4);
+        expect_failure(
+            &circuit,
+            wrong,
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 2 },
+            },
+        );
+
+        // A shortcut from 1 to 4 along the path of the edge from 3 to 4.
+        let mut circuit = ReachabilityCircuit::<_, 4, 3>::new(&graph, &[1, 2, 3, 4]);
+        let edge = graph.path(3, 4).unwrap().try_into().unwrap();
+        circuit.steps = circuit.steps.map(|mut steps| {
+            steps[1] = Step {
+                to: 4,
+                edge: Some(edge),
+            };
+            steps[2] = Step { to: 4, edge: None };
+            steps
+        });
+        expect_failure(
+            &circuit,
+            circuit.instances(),
+            FailureMatcher::Permutation {
+                location: Location::InRegion {
+                    region: "select",
+                    offset: 0,
+                },
+            },
+        );
+
+        // Jumping to 6 on a step that stays in place.
+        let mut circuit = ReachabilityCircuit::<_, 4, 3>::new(&graph, &[1, 2]);
+        circuit.steps = circuit.steps.map(|mut steps| {
+            steps[1..].iter_mut().for_each(|step| step.to = 6);
+            steps
+        });
+        expect_failure(
+            &circuit,
+            circuit.instances(),
+            FailureMatcher::Constraint {
+                gate: "walk",
+                location: Location::InRegion {
+                    region: "walk",
+                    offset: 1,
+                },
+            },
+        );
+    }
+}
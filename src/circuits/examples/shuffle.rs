@@ -0,0 +1,319 @@
+//! A verifiable card shuffle: a deck permuted by a Fisher–Yates trace whose
+//! swaps are drawn from a committed seed.
+//!
+//! The `N` cards start in order, `0, ..., N - 1`. Step `s` swaps the card at
+//! `i = N - 1 - s` with the one at `j = r_s mod (i + 1)`, where `r_s` is the
+//! `(s + 1)`-th output of the [`PrngChip`] stream of the seed. To reduce it,
+//! `r_s` is decomposed into its canonical bytes with [`WordConfig`] and
+//! [`CanonicalChip`], its low 8 bytes are packed into a 64-bit value with
+//! [`PackConfig`], and [`DivChip`] takes the remainder: the bias of
+//! `2^64 mod (i + 1)` is negligible.
+//!
+//! Each row of the trace holds the deck before a step, `j` in one-hot bits
+//! and the position `i` in fixed one-hot columns:
+//!
+//! | deck[0..N]  | bits[0..N]    | j   | position[0..N] (fixed) | q_step |
+//! | 0, ..., N-1 | one-hot j_0   | j_0 | one-hot N - 1          | 1      |
+//! | deck_1      | one-hot j_1   | j_1 | one-hot N - 2          | 1      |
+//! | ...         | ...           | ... | ...                    | ...    |
+//! | deck_(N-1)  |               |     |                        | 0      |
+//!
+//! With `top = Σ position[p] ⋅ deck[p]` and `picked = Σ bits[p] ⋅ deck[p]`:
+//!
+//! - `Σ p ⋅ bits[p] = j`,
+//! - `deck_next[p] = deck[p] + bits[p] ⋅ (top - deck[p]) + position[p] ⋅ (picked - deck[p])`.
+//!
+//! Every step is a swap, so the final deck is a permutation of the cards by
+//! construction. It is committed as `H(salt, deck[0], ..., deck[N - 1])`, and
+//! both commitments are public:
+//!
+//! | instance                         |
+//! | seed_commitment, deck_commitment |
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+use crate::{
+    circuits::{
+        gadgets::{
+            canonical::{CanonicalChip, CanonicalConfig},
+            div::{DivChip, DivConfig},
+            lt::LtChip,
+            lt_word::LtWordChip,
+            one_hot::{OneHotChip, OneHotConfig},
+            poseidon::{PoseidonChip, PoseidonConfig, Spec},
+            prng::{Prng, PrngChip, PrngConfig},
+            select::SelectChip,
+        },
+        instance::{InstanceColumns, InstanceLayout},
+        pack::PackConfig,
+        table::U8Table,
+        word::WordConfig,
+    },
+    field::Field,
+};
+
+/// Shuffles `n` cards with the stream of `seed`, as the circuit does.
+pub fn shuffle<F: Field>(seed: F, n: usize) -> Vec<u64> {
+    let mut deck: Vec<u64> = (0..n as u64).collect();
+    for (s, r) in Prng { seed }.expand(n - 1).into_iter().enumerate() {
+        let i = n - 1 - s;
+        let r = u64::from_le_bytes(r.to_repr()[..8].try_into().unwrap());
+        deck.swap(i, (r % (i as u64 + 1)) as usize);
+    }
+    deck
+}
+
+/// Config for [`ShuffleCircuit`].
+#[derive(Clone, Debug)]
+pub struct ShuffleConfig<F: Field, const N: usize> {
+    q_step: Selector,
+    deck: [Column<Advice>; N],
+    bits: OneHotConfig<F, N>,
+    j: Column<Advice>,
+    position: [Column<Fixed>; N],
+    input: Column<Advice>,
+    poseidon: PoseidonConfig<F>,
+    prng: PrngConfig<F>,
+    word: WordConfig,
+    canonical: CanonicalConfig<F>,
+    div: DivConfig<F, 8>,
+    pack: PackConfig,
+    u8_table: U8Table,
+    instance: InstanceColumns,
+}
+
+/// Circuit shuffling `N` cards with a committed seed.
+#[derive(Clone, Debug)]
+pub struct ShuffleCircuit<F: Field, const N: usize> {
+    seed: Value<F>,
+    salt: Value<F>,
+}
+
+impl<F: Field, const N: usize> Default for ShuffleCircuit<F, N> {
+    fn default() -> Self {
+        Self {
+            seed: Value::unknown(),
+            salt: Value::unknown(),
+        }
+    }
+}
+
+impl<F: Field, const N: usize> ShuffleCircuit<F, N> {
+    /// Creates the circuit shuffling with `seed`, committing to the deck with
+    /// `salt`.
+    pub fn new(seed: F, salt: F) -> Self {
+        assert!(N >= 2);
+        Self {
+            seed: Value::known(seed),
+            salt: Value::known(salt),
+        }
+    }
+
+    /// The commitments to the seed and to the shuffled deck.
+    pub fn instances(&self) -> Vec<Vec<F>> {
+        let mut instances = vec![];
+        self.seed.zip(self.salt).map(|(seed, salt)| {
+            let deck = shuffle(seed, N).into_iter().map(F::from);
+            let preimage: Vec<F> = [salt].into_iter().chain(deck).collect();
+            instances.extend([Prng { seed }.commitment(), Spec::new().hash(&preimage)]);
+        });
+        vec![instances]
+    }
+
+    /// A single instance column, see the [module documentation](self).
+    pub fn instance_layout() -> InstanceLayout {
+        InstanceLayout::new().column(["seed_commitment", "deck_commitment"])
+    }
+}
+
+impl<F: Field, const N: usize> Circuit<F> for ShuffleCircuit<F, N> {
+    type Config = ShuffleConfig<F, N>;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let q_step = meta.selector();
+        let deck = [(); N].map(|_| meta.advice_column());
+        let bits = [(); N].map(|_| meta.advice_column());
+        let position = [(); N].map(|_| meta.fixed_column());
+        let [j, input, counter, s0, s1, s2, i0, i1] = [(); 8].map(|_| meta.advice_column());
+        let [byte, lo, hi, pack_byte, pack_acc] = [(); 5].map(|_| meta.advice_column());
+        let constant = meta.fixed_column();
+
+        let u8_table = U8Table::configure(meta);
+        let poseidon = PoseidonChip::configure(meta, [s0, s1, s2], [i0, i1], constant);
+        let prng = PrngChip::configure(meta, poseidon.clone(), counter);
+        let word = WordConfig::configure(meta, byte, lo, hi, u8_table);
+        let pack = PackConfig::configure(meta, pack_byte, pack_acc, u8_table);
+        let lt_word = LtWordChip::configure(LtChip::configure(meta, pack.clone()), SelectChip::configure(meta));
+        let canonical = CanonicalChip::configure(meta, lt_word, constant);
+        let div = DivChip::configure(meta, LtChip::configure(meta, pack.clone()), pack.clone(), constant);
+        let instance = Self::instance_layout().configure(meta);
+
+        for column in deck.into_iter().chain([j, input]) {
+            meta.enable_equality(column);
+        }
+
+        let bits = OneHotChip::configure(meta, |meta| meta.query_selector(q_step), bits);
+
+        meta.create_gate("shuffle step", |meta| {
+            let q_step = meta.query_selector(q_step);
+            let next = deck.map(|column| meta.query_advice(column, Rotation::next()));
+            let cur = deck.map(|column| meta.query_advice(column, Rotation::cur()));
+            let position = position.map(|column| meta.query_fixed(column, Rotation::cur()));
+            let j = meta.query_advice(j, Rotation::cur());
+            let index = bits.index();
+            let bits = bits.bits();
+
+            let dot = |weights: &[Expression<F>; N]| {
+                (weights.iter().zip(&cur))
+                    .fold(Expression::Constant(F::ZERO), |acc, (weight, card)| acc + weight.clone() * card.clone())
+            };
+            let top = dot(&position);
+            let picked = dot(bits);
+
+            let mut constraints = vec![q_step.clone() * (index - j)];
+            for p in 0..N {
+                let swapped = cur[p].clone()
+                    + bits[p].clone() * (top.clone() - cur[p].clone())
+                    + position[p].clone() * (picked.clone() - cur[p].clone());
+                constraints.push(q_step.clone() * (next[p].clone() - swapped));
+            }
+            constraints
+        });
+
+        ShuffleConfig {
+            q_step,
+            deck,
+            bits,
+            j,
+            position,
+            input,
+            poseidon,
+            prng,
+            word,
+            canonical,
+            div,
+            pack,
+            u8_table,
+            instance,
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        config.u8_table.load(&mut layouter)?;
+
+        let (seed, salt, divisors) = layouter.assign_region(
+            || "inputs",
+            |mut region| {
+                let seed = region.assign_advice(|| "seed", config.input, 0, || self.seed)?;
+                let salt = region.assign_advice(|| "salt", config.input, 1, || self.salt)?;
+                let divisors = (0..N - 1)
+                    .map(|s| {
+                        let divisor = F::from((N - s) as u64);
+                        region.assign_advice_from_constant(|| format!("divisor {s}"), config.input, s + 2, divisor)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok((seed, salt, divisors))
+            },
+        )?;
+
+        let prng = PrngChip::construct(config.prng.clone());
+        let seed_commitment = prng.commit(layouter.namespace(|| "seed commitment"), &seed)?;
+        let stream = prng.expand(layouter.namespace(|| "stream"), &seed, N - 1)?;
+
+        let canonical = CanonicalChip::construct(config.canonical.clone());
+        let div = DivChip::construct(config.div.clone());
+        let mut js = vec![];
+        for (s, (r, divisor)) in stream.iter().zip(&divisors).enumerate() {
+            let mut layouter = layouter.namespace(|| format!("step {s}"));
+            let word = config.word.assign(layouter.namespace(|| "bytes"), r.value().map(|r| r.to_repr()))?;
+            let value = canonical.assign(layouter.namespace(|| "canonical"), &word)?;
+            layouter.assign_region(|| "stream bytes", |mut region| region.constrain_equal(value.cell(), r.cell()))?;
+            let low = config.pack.pack(layouter.namespace(|| "low bytes"), &word.bytes.limbs[..8])?;
+            let (_, j) = div.assign(layouter.namespace(|| "j"), &low, divisor)?;
+            js.push(j);
+        }
+
+        let chip = OneHotChip::construct(config.bits.clone());
+        let deck = layouter.assign_region(
+            || "shuffle",
+            |mut region| {
+                let mut deck = (0..N)
+                    .map(|p| region.assign_advice_from_constant(|| "card", config.deck[p], 0, F::from(p as u64)))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                for (s, j) in js.iter().enumerate() {
+                    let i = N - 1 - s;
+                    config.q_step.enable(&mut region, s)?;
+                    j.copy_advice(|| "j", &mut region, config.j, s)?;
+                    for (p, column) in config.position.iter().enumerate() {
+                        region.assign_fixed(|| "position", *column, s, || Value::known(F::from((p == i) as u64)))?;
+                    }
+                    let bits = chip.assign(&mut region, s, j.value().copied())?;
+
+                    let cur: Vec<Value<F>> = deck.iter().map(|cell| cell.value().copied()).collect();
+                    let picked = (bits.iter().zip(&cur))
+                        .fold(Value::known(F::ZERO), |acc, (bit, card)| acc + bit.value().copied() * *card);
+                    deck = (0..N)
+                        .map(|p| {
+                            let card = cur[p] + bits[p].value().copied() * (cur[i] - cur[p]);
+                            let card = if p == i { picked } else { card };
+                            region.assign_advice(|| "card", config.deck[p], s + 1, || card)
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                }
+                Ok(deck)
+            },
+        )?;
+
+        let poseidon = PoseidonChip::construct(config.poseidon);
+        let preimage: Vec<AssignedCell<F, F>> = [salt].into_iter().chain(deck).collect();
+        let deck_commitment = poseidon.hash(layouter.namespace(|| "deck commitment"), &preimage)?;
+
+        config.instance.expose_public(&mut layouter, &seed_commitment, 0)?;
+        config.instance.expose_public(&mut layouter, &deck_commitment, 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{shuffle, ShuffleCircuit};
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::TestField as Fp,
+    };
+
+    #[test]
+    fn shuffle_circuit() {
+        let seed = Fp::from(0x5eed);
+        let mut deck = shuffle(seed, 6);
+        assert_ne!(deck, (0..6).collect::<Vec<_>>());
+        deck.sort_unstable();
+        assert_eq!(deck, (0..6).collect::<Vec<_>>());
+
+        let circuit = ShuffleCircuit::<_, 6>::new(seed, Fp::from(1));
+        expect_satisfied(&circuit, circuit.instances());
+
+        // The deck of another seed, under the commitment to this one.
+        let other = ShuffleCircuit::<_, 6>::new(Fp::from(0xbad), Fp::from(1));
+        let mut wrong = circuit.instances();
+        wrong[0][1] = other.instances()[0][1];
+        expect_failure(
+            &circuit,
+            wrong,
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 1 },
+            },
+        );
+    }
+}
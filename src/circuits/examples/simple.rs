@@ -0,0 +1,100 @@
+//! `a^2 * b^2 * c = out`, with `a` and `b` private, `c` a circuit constant and
+//! `out` public. Wraps the chip of [`crate::circuits::simple`].
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+use crate::circuits::simple::{FieldChip, FieldConfig};
+use crate::field::Field;
+
+/// Circuit proving knowledge of `a`, `b` with `a^2 * b^2 * c = out`.
+#[derive(Clone, Debug, Default)]
+pub struct SimpleCircuit<F: Field> {
+    a: Value<F>,
+    b: Value<F>,
+    constant: F,
+    out: F,
+}
+
+impl<F: Field> SimpleCircuit<F> {
+    /// Creates the circuit for the private `a`, `b` and the constant `c`.
+    pub fn new(a: F, b: F, constant: F) -> Self {
+        Self {
+            a: Value::known(a),
+            b: Value::known(b),
+            constant,
+            out: a.square() * b.square() * constant,
+        }
+    }
+
+    /// The public `out`.
+    pub fn instances(&self) -> Vec<Vec<F>> {
+        vec![vec![self.out]]
+    }
+}
+
+impl<F: Field> Circuit<F> for SimpleCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        // The constant lives in a fixed column, so it is part of the keys.
+        Self {
+            constant: self.constant,
+            ..Self::default()
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [meta.advice_column(), meta.advice_column()];
+        let instance = meta.instance_column();
+        let constant = meta.fixed_column();
+
+        FieldChip::configure(meta, advice, instance, constant)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let out = layouter.assign_region(
+            || "witness",
+            |mut region| {
+                config.s_mul.enable(&mut region, 0)?;
+
+                region.assign_advice(|| "a", config.advice[0], 0, || self.a)?;
+                region.assign_advice(|| "b", config.advice[1], 0, || self.b)?;
+                region.assign_fixed(|| "c", config.constant, 0, || Value::known(self.constant))?;
+
+                region.assign_advice(
+                    || "out",
+                    config.advice[0],
+                    1,
+                    || self.a * self.a * self.b * self.b * Value::known(self.constant),
+                )
+            },
+        )?;
+
+        layouter.namespace(|| "out").constrain_instance(out.cell(), config.instance, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::dev::MockProver;
+
+    use super::SimpleCircuit;
+    use crate::field::TestField as Fp;
+
+    #[test]
+    fn simple_circuit() {
+        let circuit = SimpleCircuit::new(Fp::from(3), Fp::from(5), Fp::from(4));
+        assert_eq!(circuit.instances(), vec![vec![Fp::from(900)]]);
+        let prover = MockProver::run(4, &circuit, circuit.instances()).unwrap();
+        prover.assert_satisfied();
+
+        let prover = MockProver::run(4, &circuit, vec![vec![Fp::from(901)]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
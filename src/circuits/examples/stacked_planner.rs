@@ -0,0 +1,408 @@
+//! A custom [`FloorPlanner`] that stacks regions: each region starts on the
+//! row after the previous one ends, whatever columns they use.
+//!
+//! A floor planner decides the absolute row of every region. The planners
+//! differ in how hard they try to share rows between regions:
+//!
+//! - [`SimpleFloorPlanner`] measures each region just before assigning it and
+//!   starts it at the first row where all of its columns are free, so regions
+//!   on disjoint columns end up side by side.
+//! - [`V1`] measures all regions first, then places them with a packing
+//!   strategy over the columns, and puts the constants of the circuit at the
+//!   end.
+//! - [`StackedFloorPlanner`] never shares rows. The circuit takes the sum of
+//!   the heights of its regions, which is the worst case of the two others,
+//!   but the row of a region only depends on the regions before it.
+//!
+//! [`crate::dev::planner`] lays out a circuit with each of them and reports
+//! the rows used.
+//!
+//! The planner drives a [`Layouter`] which, like the ones of halo2, runs the
+//! closure of each region twice: once on a [`RegionShape`] to measure its
+//! rows, then on a [`StackedRegion`] assigning into the constraint system.
+//! The constants of a region are assigned to the first constants column on
+//! the rows right below it. Lookup tables don't take rows from regions, they
+//! are assigned like [`SimpleFloorPlanner`] does.
+//!
+//! [`SimpleFloorPlanner`]: halo2_proofs::circuit::SimpleFloorPlanner
+//! [`V1`]: halo2_proofs::circuit::floor_planner::V1
+
+use std::{fmt, marker::PhantomData};
+
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{
+        floor_planner::single_pass::SingleChipLayouter,
+        layouter::{RegionLayouter, RegionShape},
+        Cell, FloorPlanner, Layouter, Region, RegionIndex, Table, Value,
+    },
+    plonk::{Advice, Any, Assigned, Assignment, Challenge, Circuit, Column, Error, Fixed, Instance, Selector},
+};
+
+/// Floor planner laying regions out one below the other, see the
+/// [module documentation](self).
+#[derive(Clone, Copy, Debug)]
+pub struct StackedFloorPlanner;
+
+impl FloorPlanner for StackedFloorPlanner {
+    fn synthesize<F: Field, CS: Assignment<F>, C: Circuit<F>>(
+        cs: &mut CS,
+        circuit: &C,
+        config: C::Config,
+        constants: Vec<Column<Fixed>>,
+    ) -> Result<(), Error> {
+        circuit.synthesize(config, StackedLayouter::new(cs, constants))
+    }
+}
+
+/// Layouter of [`StackedFloorPlanner`].
+pub struct StackedLayouter<'a, F: Field, CS: Assignment<F> + 'a> {
+    cs: &'a mut CS,
+    constants: Vec<Column<Fixed>>,
+    /// Start row of each region, by region index.
+    regions: Vec<usize>,
+    /// First row after the last region.
+    next_row: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<'a, F: Field, CS: Assignment<F> + 'a> fmt::Debug for StackedLayouter<'a, F, CS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StackedLayouter")
+            .field("regions", &self.regions)
+            .field("next_row", &self.next_row)
+            .finish()
+    }
+}
+
+impl<'a, F: Field, CS: Assignment<F> + 'a> StackedLayouter<'a, F, CS> {
+    /// Creates a layouter assigning into `cs`, placing the constants of the
+    /// regions in the first of the `constants` columns.
+    pub fn new(cs: &'a mut CS, constants: Vec<Column<Fixed>>) -> Self {
+        Self {
+            cs,
+            constants,
+            regions: vec![],
+            next_row: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The absolute row of `cell`.
+    fn row(&self, cell: &Cell) -> usize {
+        self.regions[*cell.region_index] + cell.row_offset
+    }
+}
+
+impl<'a, F: Field, CS: Assignment<F> + 'a> Layouter<F> for StackedLayouter<'a, F, CS> {
+    type Root = Self;
+
+    fn assign_region<A, AR, N, NR>(&mut self, name: N, mut assignment: A) -> Result<AR, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        let region_index = self.regions.len();
+
+        let mut shape = RegionShape::new(region_index.into());
+        {
+            let region: &mut dyn RegionLayouter<F> = &mut shape;
+            assignment(region.into())?;
+        }
+
+        let start = self.next_row;
+        self.regions.push(start);
+        self.cs.enter_region(name);
+        let mut region = StackedRegion {
+            layouter: self,
+            region_index: region_index.into(),
+            constants: vec![],
+        };
+        let result = {
+            let region: &mut dyn RegionLayouter<F> = &mut region;
+            assignment(region.into())
+        }?;
+        let constants = region.constants;
+        self.cs.exit_region();
+
+        let mut next_row = start + shape.row_count();
+        if !constants.is_empty() {
+            let column = *self.constants.first().ok_or(Error::NotEnoughColumnsForConstants)?;
+            for (constant, cell) in constants {
+                self.cs.assign_fixed(
+                    || format!("Constant({:?})", constant.evaluate()),
+                    column,
+                    next_row,
+                    || Value::known(constant),
+                )?;
+                let row = self.row(&cell);
+                self.cs.copy(column.into(), next_row, cell.column, row)?;
+                next_row += 1;
+            }
+        }
+        self.next_row = next_row;
+
+        Ok(result)
+    }
+
+    fn assign_table<A, N, NR>(&mut self, name: N, assignment: A) -> Result<(), Error>
+    where
+        A: FnMut(Table<'_, F>) -> Result<(), Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        SingleChipLayouter::new(&mut *self.cs, vec![])?.assign_table(name, assignment)
+    }
+
+    fn constrain_instance(&mut self, cell: Cell, column: Column<Instance>, row: usize) -> Result<(), Error> {
+        let cell_row = self.row(&cell);
+        self.cs.copy(cell.column, cell_row, column.into(), row)
+    }
+
+    fn get_challenge(&self, challenge: Challenge) -> Value<F> {
+        self.cs.get_challenge(challenge)
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.cs.push_namespace(name_fn)
+    }
+
+    fn pop_namespace(&mut self, gadget_name: Option<String>) {
+        self.cs.pop_namespace(gadget_name)
+    }
+}
+
+/// A region being assigned by a [`StackedLayouter`].
+pub struct StackedRegion<'r, 'a, F: Field, CS: Assignment<F> + 'a> {
+    layouter: &'r mut StackedLayouter<'a, F, CS>,
+    region_index: RegionIndex,
+    /// Cells constrained to constants, assigned after the region.
+    constants: Vec<(Assigned<F>, Cell)>,
+}
+
+impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> fmt::Debug for StackedRegion<'r, 'a, F, CS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StackedRegion")
+            .field("region_index", &self.region_index)
+            .finish()
+    }
+}
+
+impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> StackedRegion<'r, 'a, F, CS> {
+    fn start(&self) -> usize {
+        self.layouter.regions[*self.region_index]
+    }
+
+    fn cell(&self, column: Column<Any>, offset: usize) -> Cell {
+        Cell {
+            region_index: self.region_index,
+            row_offset: offset,
+            column,
+        }
+    }
+}
+
+impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> RegionLayouter<F> for StackedRegion<'r, 'a, F, CS> {
+    fn enable_selector<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        selector: &Selector,
+        offset: usize,
+    ) -> Result<(), Error> {
+        let row = self.start() + offset;
+        self.layouter.cs.enable_selector(annotation, selector, row)
+    }
+
+    fn name_column<'v>(&'v mut self, annotation: &'v (dyn Fn() -> String + 'v), column: Column<Any>) {
+        self.layouter.cs.annotate_column(annotation, column);
+    }
+
+    fn assign_advice<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        to: &'v mut (dyn FnMut() -> Value<Assigned<F>> + 'v),
+    ) -> Result<Cell, Error> {
+        let row = self.start() + offset;
+        self.layouter.cs.assign_advice(annotation, column, row, to)?;
+        Ok(self.cell(column.into(), offset))
+    }
+
+    fn assign_advice_from_constant<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        constant: Assigned<F>,
+    ) -> Result<Cell, Error> {
+        let cell = self.assign_advice(annotation, column, offset, &mut || Value::known(constant))?;
+        self.constrain_constant(cell, constant)?;
+        Ok(cell)
+    }
+
+    fn assign_advice_from_instance<'v>(
+        &mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        instance: Column<Instance>,
+        row: usize,
+        advice: Column<Advice>,
+        offset: usize,
+    ) -> Result<(Cell, Value<F>), Error> {
+        let value = self.layouter.cs.query_instance(instance, row)?;
+        let cell = self.assign_advice(annotation, advice, offset, &mut || value.map(Assigned::from))?;
+        let advice_row = self.start() + offset;
+        self.layouter.cs.copy(cell.column, advice_row, instance.into(), row)?;
+        Ok((cell, value))
+    }
+
+    fn instance_value(&mut self, instance: Column<Instance>, row: usize) -> Result<Value<F>, Error> {
+        self.layouter.cs.query_instance(instance, row)
+    }
+
+    fn assign_fixed<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Fixed>,
+        offset: usize,
+        to: &'v mut (dyn FnMut() -> Value<Assigned<F>> + 'v),
+    ) -> Result<Cell, Error> {
+        let row = self.start() + offset;
+        self.layouter.cs.assign_fixed(annotation, column, row, to)?;
+        Ok(self.cell(column.into(), offset))
+    }
+
+    fn constrain_constant(&mut self, cell: Cell, constant: Assigned<F>) -> Result<(), Error> {
+        self.constants.push((constant, cell));
+        Ok(())
+    }
+
+    fn constrain_equal(&mut self, left: Cell, right: Cell) -> Result<(), Error> {
+        let (left_row, right_row) = (self.layouter.row(&left), self.layouter.row(&right));
+        self.layouter.cs.copy(left.column, left_row, right.column, right_row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Selector},
+        poly::Rotation,
+    };
+
+    use super::StackedFloorPlanner;
+    use crate::{
+        circuits::examples::simple::SimpleCircuit,
+        dev::planner::{planner_rows, WithFloorPlanner},
+        field::TestField as Fp,
+    };
+
+    /// Two regions on disjoint columns, each counting up from the last cell
+    /// of the previous one, the first starting from a constant.
+    #[derive(Clone, Default)]
+    struct CountCircuit {
+        next: Value<Fp>,
+    }
+
+    #[derive(Clone)]
+    struct CountConfig {
+        a: Column<Advice>,
+        b: Column<Advice>,
+        q_a: Selector,
+        q_b: Selector,
+    }
+
+    fn count_gate(meta: &mut ConstraintSystem<Fp>, column: Column<Advice>, q: Selector) {
+        meta.create_gate("count", |meta| {
+            let q = meta.query_selector(q);
+            let cur = meta.query_advice(column, Rotation::cur());
+            let next = meta.query_advice(column, Rotation::next());
+            vec![q * (next - cur - Expression::Constant(Fp::from(1)))]
+        });
+    }
+
+    impl Circuit<Fp> for CountCircuit {
+        type Config = CountConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let (a, b) = (meta.advice_column(), meta.advice_column());
+            let constant = meta.fixed_column();
+            meta.enable_equality(a);
+            meta.enable_equality(b);
+            meta.enable_constant(constant);
+
+            let (q_a, q_b) = (meta.selector(), meta.selector());
+            count_gate(meta, a, q_a);
+            count_gate(meta, b, q_b);
+
+            CountConfig { a, b, q_a, q_b }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let last = layouter.assign_region(
+                || "a",
+                |mut region| {
+                    config.q_a.enable(&mut region, 0)?;
+                    region.assign_advice_from_constant(|| "a 0", config.a, 0, Fp::from(5))?;
+                    region.assign_advice(|| "a 1", config.a, 1, || Value::known(Fp::from(6)))
+                },
+            )?;
+
+            layouter.assign_region(
+                || "b",
+                |mut region| {
+                    config.q_b.enable(&mut region, 0)?;
+                    config.q_b.enable(&mut region, 1)?;
+                    last.copy_advice(|| "b 0", &mut region, config.b, 0)?;
+                    region.assign_advice(|| "b 1", config.b, 1, || self.next)?;
+                    region.assign_advice(|| "b 2", config.b, 2, || self.next + Value::known(Fp::from(1)))?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn regions_are_stacked() {
+        let rows = planner_rows(&CountCircuit::default()).unwrap();
+        assert_eq!((rows[0].planner, rows[0].rows), ("simple", 3));
+        // Both regions and the constant of the first one, below it.
+        assert_eq!((rows[2].planner, rows[2].rows), ("stacked", 2 + 1 + 3));
+    }
+
+    #[test]
+    fn stacked_circuit() {
+        let circuit = WithFloorPlanner::<_, StackedFloorPlanner>::new(CountCircuit {
+            next: Value::known(Fp::from(7)),
+        });
+        MockProver::run(4, &circuit, vec![]).unwrap().assert_satisfied();
+
+        let circuit = WithFloorPlanner::<_, StackedFloorPlanner>::new(CountCircuit {
+            next: Value::known(Fp::from(8)),
+        });
+        assert!(MockProver::run(4, &circuit, vec![]).unwrap().verify().is_err());
+
+        let circuit = SimpleCircuit::new(Fp::from(3), Fp::from(5), Fp::from(4));
+        let instances = circuit.instances();
+        let circuit = WithFloorPlanner::<_, StackedFloorPlanner>::new(circuit);
+        MockProver::run(4, &circuit, instances).unwrap().assert_satisfied();
+    }
+}
@@ -0,0 +1,253 @@
+//! Mean and variance of a private dataset, for audits of figures that can't
+//! be disclosed.
+//!
+//! The `N` values are fixed-point numbers of an application-chosen scale,
+//! each range checked to [`VALUE_BYTES`] bytes with [`PackConfig`]. Their sum
+//! `S` and sum of squares `Q` are computed with [`LinearCombinationChip`], and
+//! the numerator of the variance with a custom gate:
+//!
+//! | x     | square     | numerator | q_square | q_numerator |
+//! | x_i   | x_i ⋅ x_i  |           | 1        | 0           |
+//! | S     | Q          | N⋅Q − S⋅S | 0        | 1           |
+//!
+//! [`DivChip`] then rounds both statistics down, with range-checked
+//! remainders, to `mean = ⌊S / N⌋` and `variance = ⌊(N⋅Q − S⋅S) / (N⋅N)⌋`,
+//! which are public:
+//!
+//! | instance       |
+//! | mean, variance |
+//!
+//! The mean has the scale of the values, and the variance its square: for
+//! values in cents, the variance is in square cents.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::{
+    circuits::{
+        gadgets::{
+            div::{DivChip, DivConfig},
+            linear_combination::{LinearCombinationChip, LinearCombinationConfig},
+            lt::LtChip,
+        },
+        instance::{InstanceColumns, InstanceLayout},
+        pack::PackConfig,
+        table::U8Table,
+    },
+    field::Field,
+};
+
+/// Bytes of a value.
+pub const VALUE_BYTES: usize = 4;
+
+/// Bytes of the quotients and divisors of [`DivChip`], enough for the
+/// numerator of the variance of up to `2^16` values.
+const DIV_BYTES: usize = 12;
+
+/// Config for [`StatisticsCircuit`].
+#[derive(Clone, Debug)]
+pub struct StatisticsConfig<F: Field> {
+    q_square: Selector,
+    q_numerator: Selector,
+    x: Column<Advice>,
+    square: Column<Advice>,
+    numerator: Column<Advice>,
+    sum: LinearCombinationConfig<4>,
+    div: DivConfig<F, DIV_BYTES>,
+    pack: PackConfig,
+    u8_table: U8Table,
+    instance: InstanceColumns,
+}
+
+/// Circuit exposing the mean and variance of `N` private values.
+#[derive(Clone, Debug)]
+pub struct StatisticsCircuit<F: Field, const N: usize> {
+    values: Value<[u64; N]>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field, const N: usize> Default for StatisticsCircuit<F, N> {
+    fn default() -> Self {
+        Self {
+            values: Value::unknown(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: Field, const N: usize> StatisticsCircuit<F, N> {
+    pub fn new(values: [u64; N]) -> Self {
+        assert!(N > 0 && N <= 1 << 16);
+        Self {
+            values: Value::known(values),
+            _marker: PhantomData,
+        }
+    }
+
+    /// The mean and the variance, rounded down.
+    pub fn instances(&self) -> Vec<Vec<F>> {
+        let mut instances = vec![];
+        self.values.map(|values| {
+            let n = N as u128;
+            let sum: u128 = values.iter().map(|x| *x as u128).sum();
+            let squares: u128 = values.iter().map(|x| (*x as u128) * (*x as u128)).sum();
+            instances.extend([F::from_u128(sum / n), F::from_u128((n * squares - sum * sum) / (n * n))]);
+        });
+        vec![instances]
+    }
+
+    /// A single instance column, see the [module documentation](self).
+    pub fn instance_layout() -> InstanceLayout {
+        InstanceLayout::new().column(["mean", "variance"])
+    }
+}
+
+impl<F: Field, const N: usize> Circuit<F> for StatisticsCircuit<F, N> {
+    type Config = StatisticsConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let q_square = meta.selector();
+        let q_numerator = meta.selector();
+        let [x, square, numerator, byte, acc] = [(); 5].map(|_| meta.advice_column());
+        let constant = meta.fixed_column();
+        let u8_table = U8Table::configure(meta);
+        let pack = PackConfig::configure(meta, byte, acc, u8_table);
+        let lt = LtChip::configure(meta, pack.clone());
+        let div = DivChip::configure(meta, lt, pack.clone(), constant);
+        let sum = LinearCombinationChip::configure(meta);
+        let instance = Self::instance_layout().configure(meta);
+
+        for column in [x, square, numerator] {
+            meta.enable_equality(column);
+        }
+
+        meta.create_gate("square", |meta| {
+            let q_square = meta.query_selector(q_square);
+            let x = meta.query_advice(x, Rotation::cur());
+            let square = meta.query_advice(square, Rotation::cur());
+            vec![q_square * (square - x.clone() * x)]
+        });
+
+        meta.create_gate("variance numerator", |meta| {
+            let q_numerator = meta.query_selector(q_numerator);
+            let sum = meta.query_advice(x, Rotation::cur());
+            let squares = meta.query_advice(square, Rotation::cur());
+            let numerator = meta.query_advice(numerator, Rotation::cur());
+            let n = Expression::Constant(F::from(N as u64));
+            vec![q_numerator * (numerator - (n * squares - sum.clone() * sum))]
+        });
+
+        StatisticsConfig {
+            q_square,
+            q_numerator,
+            x,
+            square,
+            numerator,
+            sum,
+            div,
+            pack,
+            u8_table,
+            instance,
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        config.u8_table.load(&mut layouter)?;
+
+        let (xs, squares) = layouter.assign_region(
+            || "values",
+            |mut region| {
+                let (mut xs, mut squares) = (vec![], vec![]);
+                for i in 0..N {
+                    config.q_square.enable(&mut region, i)?;
+                    let x = self.values.map(|values| F::from(values[i]));
+                    xs.push(region.assign_advice(|| format!("x[{i}]"), config.x, i, || x)?);
+                    squares.push(region.assign_advice(|| format!("x[{i}]^2"), config.square, i, || x * x)?);
+                }
+                Ok((xs, squares))
+            },
+        )?;
+        for x in &xs {
+            config.pack.unpack(layouter.namespace(|| "value range"), x, VALUE_BYTES)?;
+        }
+
+        let sum = LinearCombinationChip::construct(config.sum);
+        let ones = [F::ONE; N];
+        let s = sum.assign(layouter.namespace(|| "sum"), &xs, &ones)?;
+        let q = sum.assign(layouter.namespace(|| "sum of squares"), &squares, &ones)?;
+
+        let (numerator, n, n_squared) = layouter.assign_region(
+            || "variance numerator",
+            |mut region| {
+                config.q_numerator.enable(&mut region, 0)?;
+                let s = s.copy_advice(|| "S", &mut region, config.x, 0)?;
+                let q = q.copy_advice(|| "Q", &mut region, config.square, 0)?;
+                let n = F::from(N as u64);
+                let numerator = s.value().zip(q.value()).map(|(s, q)| n * q - *s * s);
+                let numerator = region.assign_advice(|| "numerator", config.numerator, 0, || numerator)?;
+                let n_cell = region.assign_advice_from_constant(|| "N", config.x, 1, n)?;
+                let n_squared = region.assign_advice_from_constant(|| "N^2", config.square, 1, n * n)?;
+                Ok((numerator, n_cell, n_squared))
+            },
+        )?;
+
+        let div = DivChip::construct(config.div);
+        let (mean, _) = div.assign(layouter.namespace(|| "mean"), &s, &n)?;
+        let (variance, _) = div.assign(layouter.namespace(|| "variance"), &numerator, &n_squared)?;
+
+        for (i, cell) in [&mean, &variance].into_iter().enumerate() {
+            config.instance.expose_public(&mut layouter, cell, i)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StatisticsCircuit;
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, is_satisfied, FailureMatcher, Location},
+        field::TestField as Fp,
+    };
+
+    #[test]
+    fn statistics() {
+        // S = 105, Q = 3425, N⋅Q − S⋅S = 2675.
+        let circuit = StatisticsCircuit::<Fp, 4>::new([10, 20, 30, 45]);
+        assert_eq!(circuit.instances(), vec![vec![Fp::from(26), Fp::from(167)]]);
+        expect_satisfied(&circuit, circuit.instances());
+
+        let circuit = StatisticsCircuit::<Fp, 5>::new([7; 5]);
+        assert_eq!(circuit.instances(), vec![vec![Fp::from(7), Fp::from(0)]]);
+        expect_satisfied(&circuit, circuit.instances());
+
+        let circuit = StatisticsCircuit::<Fp, 3>::new([u32::MAX as u64, 0, 1]);
+        expect_satisfied(&circuit, circuit.instances());
+
+        // Rounding the variance up.
+        let circuit = StatisticsCircuit::<Fp, 4>::new([10, 20, 30, 45]);
+        expect_failure(
+            &circuit,
+            vec![vec![Fp::from(26), Fp::from(168)]],
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 1 },
+            },
+        );
+
+        // A value that doesn't fit in VALUE_BYTES.
+        let circuit = StatisticsCircuit::<Fp, 2>::new([1 << 32, 0]);
+        assert!(!is_satisfied(&circuit, circuit.instances()));
+    }
+}
@@ -0,0 +1,163 @@
+//! Composes the [`IsZeroCircuit`], a [`RangeCheckCircuit`] and a
+//! [`PoseidonCircuit`] into one circuit.
+//!
+//! The three are configured as [`SubCircuit`]s on the same [`SharedColumns`],
+//! so the super circuit has the columns of its widest sub-circuit rather than
+//! the sum of their columns. Their public inputs are laid out one after the
+//! other in a single instance column:
+//!
+//! | instance | sub-circuit  |
+//! | is_zero  | is_zero      |
+//! | hash     | poseidon     |
+//!
+//! The range check has no public input.
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+use super::{
+    is_zero::{IsZeroCircuit, IsZeroCircuitConfig},
+    poseidon::{PoseidonCircuit, PoseidonCircuitConfig},
+    range_check::RangeCheckCircuit,
+};
+use crate::{
+    circuits::{
+        instance::{InstanceColumns, InstanceLayout},
+        range_check_1::RangeCheckConfig,
+        sub_circuit::{SharedColumns, SubCircuit},
+    },
+    field::Field,
+};
+
+/// Config for [`SuperCircuit`].
+#[derive(Clone, Debug)]
+pub struct SuperCircuitConfig<F: Field> {
+    is_zero: IsZeroCircuitConfig<F>,
+    range_check: RangeCheckConfig<F>,
+    poseidon: PoseidonCircuitConfig<F>,
+    instance: InstanceColumns,
+}
+
+/// Circuit proving the statements of its three sub-circuits at once.
+#[derive(Clone, Debug, Default)]
+pub struct SuperCircuit<F: Field> {
+    is_zero: IsZeroCircuit<F>,
+    range_check: RangeCheckCircuit<F>,
+    poseidon: PoseidonCircuit<F, 2>,
+}
+
+impl<F: Field> SuperCircuit<F> {
+    /// Creates the circuit checking whether `value` is zero, that `small` is
+    /// in the default range of [`RangeCheckParams`](super::range_check::RangeCheckParams)
+    /// and hashing `inputs`.
+    pub fn new(value: F, small: F, inputs: [F; 2]) -> Self {
+        Self {
+            is_zero: IsZeroCircuit::new(value),
+            range_check: RangeCheckCircuit::new(small),
+            poseidon: PoseidonCircuit::new(inputs),
+        }
+    }
+
+    /// The public inputs of the sub-circuits, in order.
+    pub fn instances(&self) -> Vec<Vec<F>> {
+        let mut instances = self.is_zero.public_inputs();
+        instances.extend(self.range_check.public_inputs());
+        instances.extend(self.poseidon.public_inputs());
+        vec![instances]
+    }
+
+    /// The public inputs of the sub-circuits, in a single instance column.
+    pub fn instance_layout() -> InstanceLayout {
+        InstanceLayout::new().column(["is_zero", "hash"])
+    }
+
+    /// Rows assigned by the sub-circuits, an upper bound of the rows of the
+    /// super circuit as they share columns.
+    pub fn min_rows(&self) -> usize {
+        self.is_zero.min_rows() + self.range_check.min_rows() + self.poseidon.min_rows()
+    }
+}
+
+impl<F: Field> Circuit<F> for SuperCircuit<F> {
+    type Config = SuperCircuitConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let shared = SharedColumns::configure(meta);
+        let instance = Self::instance_layout().configure(meta);
+
+        SuperCircuitConfig {
+            is_zero: IsZeroCircuit::configure_sub(meta, shared),
+            range_check: RangeCheckCircuit::configure_sub(meta, shared),
+            poseidon: PoseidonCircuit::<F, 2>::configure_sub(meta, shared),
+            instance,
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let layout = Self::instance_layout();
+
+        let is_zero = self.is_zero.synthesize_sub(&config.is_zero, layouter.namespace(|| "is_zero"))?;
+        self.range_check.synthesize_sub(&config.range_check, layouter.namespace(|| "range_check"))?;
+        let hash = self.poseidon.synthesize_sub(&config.poseidon, layouter.namespace(|| "poseidon"))?;
+
+        config.instance.expose_public(&mut layouter, &is_zero[0], layout.index("is_zero"))?;
+        config.instance.expose_public(&mut layouter, &hash[0], layout.index("hash"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SuperCircuit;
+    use crate::{
+        circuits::gadgets::poseidon::Spec,
+        dev::{
+            mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+            rows::rows_used,
+        },
+        field::TestField as Fp,
+    };
+
+    #[test]
+    fn super_circuit() {
+        let inputs = [Fp::from(1), Fp::from(2)];
+        let circuit = SuperCircuit::new(Fp::from(0), Fp::from(15), inputs);
+        let instances = circuit.instances();
+        assert_eq!(instances, vec![vec![Fp::from(1), Spec::new().hash(&inputs)]]);
+        expect_satisfied(&circuit, instances.clone());
+        assert!(rows_used(&circuit).unwrap() <= circuit.min_rows());
+
+        // The range check fails on its own, in the shared columns.
+        let circuit = SuperCircuit::new(Fp::from(0), Fp::from(16), inputs);
+        expect_failure(
+            &circuit,
+            instances.clone(),
+            FailureMatcher::Constraint {
+                gate: "range check",
+                location: Location::InRegion {
+                    region: "Assign value",
+                    offset: 0,
+                },
+            },
+        );
+
+        // The hash is the second instance, after the is_zero bit.
+        let mut wrong = instances;
+        wrong[0].swap(0, 1);
+        expect_failure(
+            &SuperCircuit::new(Fp::from(0), Fp::from(15), inputs),
+            wrong,
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 1 },
+            },
+        );
+    }
+}
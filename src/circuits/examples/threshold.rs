@@ -0,0 +1,204 @@
+//! How many of a committed set of private values exceed a public threshold,
+//! as in compliance checks of the form "at most k accounts above T".
+//!
+//! The prover commits to `N` values as `H(salt, values[0], ...,
+//! values[N - 1])` with [`PoseidonChip`]. Each value is range checked to
+//! [`VALUE_BYTES`] bytes with [`PackConfig`], and compared to the threshold
+//! `T` with its own [`LtChip`] comparison `T < values[i]`. The comparisons are
+//! boolean, and [`LinearCombinationChip`] sums them into the count:
+//!
+//! | instance                     |
+//! | commitment, threshold, count |
+//!
+//! The threshold is range checked too, as [`LtChip`] requires.
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+};
+
+use crate::{
+    circuits::{
+        gadgets::{
+            linear_combination::{LinearCombinationChip, LinearCombinationConfig},
+            lt::{LtChip, LtConfig},
+            poseidon::{PoseidonChip, PoseidonConfig, Spec},
+        },
+        instance::{InstanceColumns, InstanceLayout},
+        pack::PackConfig,
+        table::U8Table,
+    },
+    field::Field,
+};
+
+/// Bytes of a value and of the threshold.
+pub const VALUE_BYTES: usize = 4;
+
+/// Config for [`ThresholdCircuit`].
+#[derive(Clone, Debug)]
+pub struct ThresholdConfig<F: Field> {
+    input: Column<Advice>,
+    poseidon: PoseidonConfig<F>,
+    lt: LtConfig<F, VALUE_BYTES>,
+    sum: LinearCombinationConfig<4>,
+    pack: PackConfig,
+    u8_table: U8Table,
+    instance: InstanceColumns,
+}
+
+/// Circuit exposing how many of `N` committed values exceed a threshold.
+#[derive(Clone, Debug)]
+pub struct ThresholdCircuit<F: Field, const N: usize> {
+    values: Value<[u32; N]>,
+    salt: Value<F>,
+    threshold: Value<u32>,
+}
+
+impl<F: Field, const N: usize> Default for ThresholdCircuit<F, N> {
+    fn default() -> Self {
+        Self {
+            values: Value::unknown(),
+            salt: Value::unknown(),
+            threshold: Value::unknown(),
+        }
+    }
+}
+
+impl<F: Field, const N: usize> ThresholdCircuit<F, N> {
+    /// Creates the circuit for the private `values` and `salt`, counting the
+    /// values above `threshold`.
+    pub fn new(values: [u32; N], salt: F, threshold: u32) -> Self {
+        Self {
+            values: Value::known(values),
+            salt: Value::known(salt),
+            threshold: Value::known(threshold),
+        }
+    }
+
+    /// The commitment, the threshold and the count.
+    pub fn instances(&self) -> Vec<Vec<F>> {
+        let mut instances = vec![];
+        self.values.zip(self.salt).zip(self.threshold).map(|((values, salt), threshold)| {
+            let preimage: Vec<F> = [salt].into_iter().chain(values.map(|value| F::from(value as u64))).collect();
+            let count = values.iter().filter(|value| **value > threshold).count();
+            instances.extend([Spec::new().hash(&preimage), F::from(threshold as u64), F::from(count as u64)]);
+        });
+        vec![instances]
+    }
+
+    /// A single instance column, see the [module documentation](self).
+    pub fn instance_layout() -> InstanceLayout {
+        InstanceLayout::new().column(["commitment", "threshold", "count"])
+    }
+}
+
+impl<F: Field, const N: usize> Circuit<F> for ThresholdCircuit<F, N> {
+    type Config = ThresholdConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let [input, byte, acc, s0, s1, s2, i0, i1] = [(); 8].map(|_| meta.advice_column());
+        let constant = meta.fixed_column();
+        let u8_table = U8Table::configure(meta);
+        let pack = PackConfig::configure(meta, byte, acc, u8_table);
+        meta.enable_equality(input);
+
+        ThresholdConfig {
+            input,
+            poseidon: PoseidonChip::configure(meta, [s0, s1, s2], [i0, i1], constant),
+            lt: LtChip::configure(meta, pack.clone()),
+            sum: LinearCombinationChip::configure(meta),
+            pack,
+            u8_table,
+            instance: Self::instance_layout().configure(meta),
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        config.u8_table.load(&mut layouter)?;
+
+        let (salt, threshold, values) = layouter.assign_region(
+            || "inputs",
+            |mut region| {
+                let salt = region.assign_advice(|| "salt", config.input, 0, || self.salt)?;
+                let threshold = self.threshold.map(|threshold| F::from(threshold as u64));
+                let threshold = region.assign_advice(|| "threshold", config.input, 1, || threshold)?;
+                let values = (0..N)
+                    .map(|i| {
+                        let value = self.values.map(|values| F::from(values[i] as u64));
+                        region.assign_advice(|| format!("values[{i}]"), config.input, i + 2, || value)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok((salt, threshold, values))
+            },
+        )?;
+
+        for cell in [&threshold].into_iter().chain(&values) {
+            config.pack.unpack(layouter.namespace(|| "range"), cell, VALUE_BYTES)?;
+        }
+
+        let lt = LtChip::construct(config.lt);
+        let above = (values.iter())
+            .map(|value| Ok(lt.assign(layouter.namespace(|| "above"), &threshold, value)?.lt))
+            .collect::<Result<Vec<_>, Error>>()?;
+        let sum = LinearCombinationChip::construct(config.sum);
+        let count = sum.assign(layouter.namespace(|| "count"), &above, &[F::ONE; N])?;
+
+        let poseidon = PoseidonChip::construct(config.poseidon);
+        let preimage: Vec<_> = [salt].into_iter().chain(values).collect();
+        let commitment = poseidon.hash(layouter.namespace(|| "commitment"), &preimage)?;
+
+        for (i, cell) in [&commitment, &threshold, &count].into_iter().enumerate() {
+            config.instance.expose_public(&mut layouter, cell, i)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ThresholdCircuit;
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::TestField as Fp,
+    };
+
+    #[test]
+    fn threshold() {
+        let values = [120, 5, 100, 101, u32::MAX, 0, 100, 99];
+        for (threshold, count) in [(100, 3), (99, 5), (0, 7), (u32::MAX, 0)] {
+            let circuit = ThresholdCircuit::new(values, Fp::from(9), threshold);
+            assert_eq!(circuit.instances()[0][2], Fp::from(count));
+            expect_satisfied(&circuit, circuit.instances());
+        }
+
+        // Counting the values equal to the threshold too.
+        let circuit = ThresholdCircuit::new(values, Fp::from(9), 100);
+        let mut wrong = circuit.instances();
+        wrong[0][2] = Fp::from(5);
+        expect_failure(
+            &circuit,
+            wrong,
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 2 },
+            },
+        );
+
+        // The count above 100, claimed above 99.
+        let mut wrong = circuit.instances();
+        wrong[0][1] = Fp::from(99);
+        expect_failure(
+            &circuit,
+            wrong,
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 1 },
+            },
+        );
+    }
+}
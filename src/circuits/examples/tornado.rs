@@ -0,0 +1,399 @@
+//! A Tornado-style mixer: a deposit circuit committing to a note, and a
+//! withdrawal circuit spending it without revealing which one.
+//!
+//! A note is a private `(nullifier, secret)` pair, and is deposited as the
+//! leaf `C = H(nullifier, secret)` of a Merkle tree kept by the contract.
+//! [`DepositCircuit`] proves that `C` is well formed:
+//!
+//! | instance   |
+//! | commitment |
+//!
+//! [`WithdrawCircuit`] proves that the commitment of a note is a leaf of the
+//! tree, with [`MerkleChip`], and exposes `H(nullifier)`, which the contract
+//! records to reject a second withdrawal of the same note:
+//!
+//! | instance                        |
+//! | root, nullifier_hash, recipient |
+//!
+//! The recipient is assigned to an advice cell and exposed, but takes no part
+//! in any gate: the proof is bound to it as to every instance value, through
+//! the transcript, so a front-runner can't replay the proof with their own
+//! address.
+//!
+//! Both circuits are generic over the [`HashInstructions`] chip hashing the
+//! notes and the tree, Poseidon by default, with the `_with` helpers of
+//! [`Note`] as their native counterparts. The depth of the tree is the
+//! [`MerkleParams`] of [`WithdrawCircuit`], set from the length of the path.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+};
+
+use crate::{
+    circuits::{
+        gadgets::{
+            hash::{HashInstructions, HashSpec},
+            merkle::{MerkleChip, MerkleConfig, MerkleParams},
+            poseidon::{PoseidonChip, Spec},
+            select::SelectChip,
+        },
+        instance::{InstanceColumns, InstanceLayout},
+    },
+    field::Field,
+};
+
+/// A deposited note.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Note<F> {
+    pub nullifier: F,
+    pub secret: F,
+}
+
+impl<F: Field> Note<F> {
+    /// `H(nullifier, secret)`, the leaf of the note.
+    pub fn commitment(&self) -> F {
+        self.commitment_with(&Spec::new())
+    }
+
+    /// `H(nullifier)`, revealed on withdrawal.
+    pub fn nullifier_hash(&self) -> F {
+        self.nullifier_hash_with(&Spec::new())
+    }
+
+    /// [`Note::commitment`] with the hash of `spec`.
+    pub fn commitment_with(&self, spec: &impl HashSpec<F>) -> F {
+        spec.hash(&[self.nullifier, self.secret])
+    }
+
+    /// [`Note::nullifier_hash`] with the hash of `spec`.
+    pub fn nullifier_hash_with(&self, spec: &impl HashSpec<F>) -> F {
+        spec.hash(&[self.nullifier])
+    }
+}
+
+/// Config shared by [`DepositCircuit`] and [`WithdrawCircuit`].
+#[derive(Clone, Debug)]
+pub struct TornadoConfig<F: Field, H: HashInstructions<F>> {
+    merkle: MerkleConfig<F, H>,
+    input: Column<Advice>,
+    instance: InstanceColumns,
+}
+
+impl<F: Field, H: HashInstructions<F>> TornadoConfig<F, H> {
+    fn configure(meta: &mut ConstraintSystem<F>, instance_layout: InstanceLayout) -> Self {
+        let [input, s0, s1, s2, i0, i1] = [(); 6].map(|_| meta.advice_column());
+        let constant = meta.fixed_column();
+        meta.enable_equality(input);
+
+        TornadoConfig {
+            merkle: MerkleConfig {
+                hash: H::configure(meta, [s0, s1, s2], [i0, i1], constant),
+                select: SelectChip::configure(meta),
+            },
+            input,
+            instance: instance_layout.configure(meta),
+        }
+    }
+
+    /// Assigns `values` to consecutive rows of the input column.
+    fn assign_inputs(
+        &self,
+        mut layouter: impl Layouter<F>,
+        values: &[Value<F>],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        layouter.assign_region(
+            || "inputs",
+            |mut region| {
+                (values.iter().enumerate())
+                    .map(|(i, value)| region.assign_advice(|| "input", self.input, i, || *value))
+                    .collect()
+            },
+        )
+    }
+}
+
+/// Circuit exposing the commitment of a private note.
+#[derive(Clone, Debug)]
+pub struct DepositCircuit<F: Field, H: HashInstructions<F> = PoseidonChip<F>> {
+    note: Value<Note<F>>,
+    _marker: PhantomData<H>,
+}
+
+impl<F: Field, H: HashInstructions<F>> Default for DepositCircuit<F, H> {
+    fn default() -> Self {
+        Self {
+            note: Value::unknown(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: Field, H: HashInstructions<F>> DepositCircuit<F, H> {
+    pub fn new(note: Note<F>) -> Self {
+        Self {
+            note: Value::known(note),
+            _marker: PhantomData,
+        }
+    }
+
+    /// The commitment.
+    pub fn instances(&self) -> Vec<Vec<F>> {
+        let mut instances = vec![];
+        self.note.map(|note| instances.push(note.commitment_with(&H::Spec::default())));
+        vec![instances]
+    }
+
+    /// A single instance column holding the commitment.
+    pub fn instance_layout() -> InstanceLayout {
+        InstanceLayout::new().column(["commitment"])
+    }
+}
+
+impl<F: Field, H: HashInstructions<F>> Circuit<F> for DepositCircuit<F, H> {
+    type Config = TornadoConfig<F, H>;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        TornadoConfig::configure(meta, Self::instance_layout())
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let note = [self.note.map(|note| note.nullifier), self.note.map(|note| note.secret)];
+        let note = config.assign_inputs(layouter.namespace(|| "note"), &note)?;
+
+        let hash = H::construct(config.merkle.hash.clone());
+        let commitment = hash.hash(layouter.namespace(|| "commitment"), &note)?;
+        config.instance.expose_public(&mut layouter, &commitment, 0)
+    }
+}
+
+/// Circuit withdrawing a note from a tree to a recipient.
+#[derive(Clone, Debug)]
+pub struct WithdrawCircuit<F: Field, H: HashInstructions<F> = PoseidonChip<F>> {
+    note: Value<Note<F>>,
+    path: Value<Vec<(F, bool)>>,
+    root: Value<F>,
+    recipient: Value<F>,
+    params: MerkleParams,
+    _marker: PhantomData<H>,
+}
+
+impl<F: Field, H: HashInstructions<F>> Default for WithdrawCircuit<F, H> {
+    fn default() -> Self {
+        Self::with_params(MerkleParams::default())
+    }
+}
+
+impl<F: Field, H: HashInstructions<F>> WithdrawCircuit<F, H> {
+    /// Creates the circuit withdrawing `note`, whose commitment is in the
+    /// tree of `root` at the end of `path`, to `recipient`.
+    pub fn new(note: Note<F>, path: Vec<(F, bool)>, root: F, recipient: F) -> Self {
+        Self {
+            note: Value::known(note),
+            params: MerkleParams { depth: path.len() },
+            path: Value::known(path),
+            root: Value::known(root),
+            recipient: Value::known(recipient),
+            _marker: PhantomData,
+        }
+    }
+
+    /// The circuit for a tree of `params`, without witnesses.
+    pub fn with_params(params: MerkleParams) -> Self {
+        Self {
+            note: Value::unknown(),
+            path: Value::unknown(),
+            root: Value::unknown(),
+            recipient: Value::unknown(),
+            params,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The root, the nullifier hash and the recipient.
+    pub fn instances(&self) -> Vec<Vec<F>> {
+        let mut instances = vec![];
+        self.note.zip(self.root).zip(self.recipient).map(|((note, root), recipient)| {
+            instances.extend([root, note.nullifier_hash_with(&H::Spec::default()), recipient]);
+        });
+        vec![instances]
+    }
+
+    /// A single instance column, see the [module documentation](self).
+    pub fn instance_layout() -> InstanceLayout {
+        InstanceLayout::new().column(["root", "nullifier_hash", "recipient"])
+    }
+}
+
+impl<F: Field, H: HashInstructions<F>> Circuit<F> for WithdrawCircuit<F, H> {
+    type Config = TornadoConfig<F, H>;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = MerkleParams;
+
+    fn without_witnesses(&self) -> Self {
+        Self::with_params(self.params)
+    }
+
+    #[cfg(feature = "circuit-params")]
+    fn params(&self) -> Self::Params {
+        self.params
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        TornadoConfig::configure(meta, Self::instance_layout())
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let mut inputs = vec![
+            self.note.map(|note| note.nullifier),
+            self.note.map(|note| note.secret),
+            self.recipient,
+        ];
+        for level in 0..self.params.depth {
+            let node = self.path.as_ref().map(|path| path[level]);
+            inputs.extend([node.map(|(sibling, _)| sibling), node.map(|(_, is_right)| F::from(is_right as u64))]);
+        }
+        let inputs = config.assign_inputs(layouter.namespace(|| "inputs"), &inputs)?;
+        let (note, rest) = inputs.split_at(2);
+        let recipient = &rest[0];
+        let path: Vec<_> = rest[1..].chunks(2).map(|node| (node[0].clone(), node[1].clone())).collect();
+
+        let hash = H::construct(config.merkle.hash.clone());
+        let commitment = hash.hash(layouter.namespace(|| "commitment"), note)?;
+        let nullifier_hash = hash.hash(layouter.namespace(|| "nullifier hash"), &note[..1])?;
+        let root = MerkleChip::construct(config.merkle).root(layouter.namespace(|| "merkle"), &commitment, &path)?;
+
+        for (i, cell) in [&root, &nullifier_hash, recipient].into_iter().enumerate() {
+            config.instance.expose_public(&mut layouter, cell, i)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::plonk::Circuit;
+
+    use super::{DepositCircuit, Note, WithdrawCircuit};
+    use crate::{
+        circuits::gadgets::{
+            merkle::{MerkleParams, MerkleTree},
+            rescue::{self, RescueChip},
+        },
+        dev::{
+            mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+            rows::rows_used,
+        },
+        field::TestField as Fp,
+    };
+
+    fn notes() -> Vec<Note<Fp>> {
+        (0..4)
+            .map(|i| Note {
+                nullifier: Fp::from(100 + i),
+                secret: Fp::from(200 + i),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn deposit() {
+        let note = notes()[0];
+        let circuit = DepositCircuit::<_>::new(note);
+        assert_eq!(circuit.instances(), vec![vec![note.commitment()]]);
+        expect_satisfied(&circuit, circuit.instances());
+
+        let mut wrong = circuit.instances();
+        wrong[0][0] = notes()[1].commitment();
+        expect_failure(
+            &circuit,
+            wrong,
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+    }
+
+    #[test]
+    fn withdraw() {
+        let notes = notes();
+        let tree = MerkleTree::new(3, notes.iter().map(Note::commitment).collect());
+        let recipient = Fp::from(0xdead);
+
+        let path = tree.path(2);
+        let circuit = WithdrawCircuit::<_>::new(notes[2], path.clone(), tree.root(), recipient);
+        expect_satisfied(&circuit, circuit.instances());
+
+        // The instances of the proof for one recipient, with another one.
+        let mut wrong = circuit.instances();
+        wrong[0][2] = Fp::from(0xbeef);
+        expect_failure(
+            &circuit,
+            wrong,
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 2 },
+            },
+        );
+
+        // A note that was never deposited, at the position of note 2.
+        let forged = Note {
+            nullifier: notes[2].nullifier,
+            secret: Fp::from(0),
+        };
+        let circuit = WithdrawCircuit::<_>::new(forged, path, tree.root(), recipient);
+        expect_failure(
+            &circuit,
+            circuit.instances(),
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+    }
+
+    #[test]
+    fn withdraw_params() {
+        let notes = notes();
+        let tree = MerkleTree::new(5, notes.iter().map(Note::commitment).collect());
+        let circuit = WithdrawCircuit::<_>::new(notes[0], tree.path(0), tree.root(), Fp::from(0xdead));
+
+        // The keys are generated without witnesses, for the same depth.
+        let keygen = circuit.without_witnesses();
+        assert_eq!(keygen.params, MerkleParams { depth: 5 });
+        assert_eq!(rows_used(&keygen).unwrap(), rows_used(&circuit).unwrap());
+        assert!(rows_used(&WithdrawCircuit::<Fp>::default()).unwrap() > rows_used(&circuit).unwrap());
+    }
+
+    #[test]
+    fn withdraw_rescue() {
+        let notes = notes();
+        let spec = rescue::Spec::new();
+        let tree = MerkleTree::with_spec(3, notes.iter().map(|note| note.commitment_with(&spec)).collect(), &spec);
+        let recipient = Fp::from(0xdead);
+
+        let circuit = WithdrawCircuit::<_, RescueChip<_>>::new(notes[1], tree.path(1), tree.root(), recipient);
+        assert_eq!(circuit.instances()[0][1], notes[1].nullifier_hash_with(&spec));
+        expect_satisfied(&circuit, circuit.instances());
+
+        // The Poseidon tree of the same notes.
+        let poseidon = MerkleTree::new(3, notes.iter().map(Note::commitment).collect());
+        let mut wrong = circuit.instances();
+        wrong[0][0] = poseidon.root();
+        expect_failure(
+            &circuit,
+            wrong,
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+    }
+}
@@ -0,0 +1,252 @@
+//! Looks up `(op, a, b, out)` tuples of advice cells in a multi-column fixed
+//! table of all the additions and multiplications of `BITS`-bit operands.
+//!
+//! | op | a | b | out | q_lookup |      table: | op | a | b | out        |
+//! | 1  | 3 | 5 | 15  | 1        |             | 0  | 0 | 0 | 0          |
+//!                                             | ...                      |
+//!                                             | 1  | a | b | a ⋅ b      |
+//!
+//! All four columns are looked up together, so a row must match a single
+//! table row: a correct product of in-range operands. Rows with `q_lookup`
+//! off look up `(0, 0, 0, 0)`, which is the table row of `0 + 0`.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use crate::{
+    circuits::instance::{InstanceColumns, InstanceLayout},
+    field::Field,
+};
+
+/// Operation of a tuple, encoded in the table by its discriminant.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ArithOp {
+    #[default]
+    Add = 0,
+    Mul = 1,
+}
+
+impl ArithOp {
+    pub fn apply(self, a: u64, b: u64) -> u64 {
+        match self {
+            ArithOp::Add => a + b,
+            ArithOp::Mul => a * b,
+        }
+    }
+}
+
+/// A looked up tuple.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ArithRow {
+    pub op: ArithOp,
+    pub a: u64,
+    pub b: u64,
+    pub out: u64,
+}
+
+impl ArithRow {
+    /// The row computing `op(a, b)`.
+    pub fn new(op: ArithOp, a: u64, b: u64) -> Self {
+        Self {
+            op,
+            a,
+            b,
+            out: op.apply(a, b),
+        }
+    }
+}
+
+/// Config for [`TupleLookupCircuit`].
+#[derive(Clone, Debug)]
+pub struct TupleLookupConfig {
+    q_lookup: Selector,
+    advice: [Column<Advice>; 4],
+    table: [TableColumn; 4],
+    instance: InstanceColumns,
+}
+
+/// Circuit proving `N` private `(op, a, b)` rows with public results.
+#[derive(Clone, Debug)]
+pub struct TupleLookupCircuit<F: Field, const BITS: usize, const N: usize> {
+    rows: Value<[ArithRow; N]>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field, const BITS: usize, const N: usize> Default for TupleLookupCircuit<F, BITS, N> {
+    fn default() -> Self {
+        Self {
+            rows: Value::unknown(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: Field, const BITS: usize, const N: usize> TupleLookupCircuit<F, BITS, N> {
+    /// Creates the circuit for the private `rows`.
+    pub fn new(rows: [ArithRow; N]) -> Self {
+        Self {
+            rows: Value::known(rows),
+            _marker: PhantomData,
+        }
+    }
+
+    /// The `out` of every row.
+    pub fn instances(&self) -> Vec<Vec<F>> {
+        let mut instances = vec![];
+        self.rows.map(|rows| {
+            instances.extend(rows.iter().map(|row| F::from(row.out)));
+        });
+        vec![instances]
+    }
+
+    /// A single instance column holding the `out` of every row.
+    pub fn instance_layout() -> InstanceLayout {
+        InstanceLayout::new().column((0..N).map(|i| format!("out[{i}]")))
+    }
+}
+
+impl<F: Field, const BITS: usize, const N: usize> Circuit<F> for TupleLookupCircuit<F, BITS, N> {
+    type Config = TupleLookupConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let q_lookup = meta.complex_selector();
+        let advice = [(); 4].map(|_| meta.advice_column());
+        let table = [(); 4].map(|_| meta.lookup_table_column());
+        let instance = Self::instance_layout().configure(meta);
+
+        meta.enable_equality(advice[3]);
+
+        meta.lookup("arith table", |meta| {
+            let q_lookup = meta.query_selector(q_lookup);
+            (advice.iter().zip(table))
+                .map(|(column, table)| (q_lookup.clone() * meta.query_advice(*column, Rotation::cur()), table))
+                .collect()
+        });
+
+        TupleLookupConfig {
+            q_lookup,
+            advice,
+            table,
+            instance,
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "arith table",
+            |mut table| {
+                let mut offset = 0;
+                for op in [ArithOp::Add, ArithOp::Mul] {
+                    for a in 0..1 << BITS {
+                        for b in 0..1 << BITS {
+                            let row = [op as u64, a, b, op.apply(a, b)];
+                            for (column, value) in config.table.iter().zip(row) {
+                                table.assign_cell(|| "arith table", *column, offset, || Value::known(F::from(value)))?;
+                            }
+                            offset += 1;
+                        }
+                    }
+                }
+                Ok(())
+            },
+        )?;
+
+        let outs = layouter.assign_region(
+            || "tuple lookup",
+            |mut region| {
+                let mut outs = vec![];
+                for i in 0..N {
+                    config.q_lookup.enable(&mut region, i)?;
+                    let row = self.rows.map(|rows| rows[i]);
+                    let values = [
+                        row.map(|row| row.op as u64),
+                        row.map(|row| row.a),
+                        row.map(|row| row.b),
+                        row.map(|row| row.out),
+                    ];
+                    let mut cells = vec![];
+                    for (column, value) in config.advice.iter().zip(values) {
+                        cells.push(region.assign_advice(|| "tuple", *column, i, || value.map(F::from))?);
+                    }
+                    outs.push(cells.pop().unwrap());
+                }
+                Ok(outs)
+            },
+        )?;
+
+        for (i, out) in outs.iter().enumerate() {
+            config.instance.expose_public(&mut layouter, out, i)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::marker::PhantomData;
+
+    use halo2_proofs::circuit::Value;
+
+    use super::{ArithOp, ArithRow, TupleLookupCircuit};
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::TestField as Fp,
+    };
+
+    #[test]
+    fn tuple_lookup() {
+        let rows = [
+            ArithRow::new(ArithOp::Mul, 3, 5),
+            ArithRow::new(ArithOp::Add, 15, 15),
+            ArithRow::new(ArithOp::Mul, 15, 15),
+        ];
+        let circuit = TupleLookupCircuit::<Fp, 4, 3>::new(rows);
+        expect_satisfied(&circuit, circuit.instances());
+
+        // Each column is in the table on its own, but not the tuple: 3 ⋅ 5
+        // claimed to be 8.
+        let mut wrong = rows;
+        wrong[0].out = 8;
+        let circuit = TupleLookupCircuit::<Fp, 4, 3> {
+            rows: Value::known(wrong),
+            _marker: PhantomData,
+        };
+        expect_failure(
+            &circuit,
+            circuit.instances(),
+            FailureMatcher::Lookup {
+                name: "arith table",
+                location: Location::InRegion {
+                    region: "tuple lookup",
+                    offset: 0,
+                },
+            },
+        );
+
+        // 16 is not a 4-bit operand.
+        let circuit = TupleLookupCircuit::<Fp, 4, 3>::new([rows[0], rows[1], ArithRow::new(ArithOp::Add, 16, 0)]);
+        expect_failure(
+            &circuit,
+            circuit.instances(),
+            FailureMatcher::Lookup {
+                name: "arith table",
+                location: Location::InRegion {
+                    region: "tuple lookup",
+                    offset: 2,
+                },
+            },
+        );
+    }
+}
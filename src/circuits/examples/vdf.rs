@@ -0,0 +1,365 @@
+//! Verification of a Wesolowski proof of sequential work: `y = x^(2^T)`,
+//! computed by `T` squarings, checked with two short exponentiations.
+//!
+//! Given a prime challenge `l` derived from `(x, y)`, the prover gives
+//! `π = x^⌊2^T / l⌋`, and with `r = 2^T mod l`
+//!
+//! ```text
+//! π^l ⋅ x^r = x^(l ⋅ ⌊2^T / l⌋ + r) = x^(2^T) = y
+//! ```
+//!
+//! The verifier derives `l` and `r` from `(x, y, T)` off circuit, with
+//! [`challenge`], and the circuit checks the equation with square and
+//! multiply over the bits of `l` and `r`, one per step. The cells of a step
+//! are handed out by a [`CellManager`] over two columns, so a step spans two
+//! rows:
+//!
+//! | columns[0] | columns[1] | q_pow |
+//! | b_0        | 0          | 1     |
+//! | 1          | π          | 0     |
+//! | b_1        | b_0        | 1     |
+//! | π^b_0      | π          | 0     |
+//! | ...        | ...        | ...   |
+//! |            | l          | 0     |
+//! | π^l        | π          | 0     |
+//!
+//! where the cells of a step are `bit`, `exp`, `acc` and `base`. `exp`
+//! recomposes the bits from the most significant, and is copied to the public
+//! `l`, while `acc' = acc^2 ⋅ base^bit`, `'` being the next step.
+//!
+//! | instance    |
+//! | x, y, l, r  |
+//!
+//! This is a toy: the group is the multiplicative group of the native field,
+//! whose order is known, so `x^(2^T)` can be computed without sequential
+//! work through `2^T mod (p - 1)`. A real VDF works in a group of unknown
+//! order, such as an RSA group, over big integer chips, but the proof
+//! equation and its circuit have the same shape.
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Selector},
+};
+
+use crate::{
+    circuits::{
+        gadgets::{hash::HashSpec, poseidon::Spec},
+        instance::{InstanceColumns, InstanceLayout},
+        util::cell_manager::{Cell, CellManager},
+    },
+    field::Field,
+};
+
+/// Bits of the challenge `l` and the remainder `r`.
+pub const EXP_BITS: usize = 64;
+
+/// `y = x^(2^t)` and the proof `π = x^⌊2^t / l⌋` for the challenge `l` of
+/// `(x, y)`, computed together with `t` squarings each.
+pub fn evaluate<F: Field>(x: F, t: u64) -> (F, F) {
+    let y = (0..t).fold(x, |y, _| y.square());
+    let l = challenge(x, y) as u128;
+
+    // Long division of `2^t` by `l`, one quotient bit per step.
+    let (mut pi, mut r) = (F::ONE, 1u128);
+    for _ in 0..t {
+        let bit = 2 * r / l;
+        r = 2 * r % l;
+        pi = pi.square() * if bit == 1 { x } else { F::ONE };
+    }
+    (y, pi)
+}
+
+/// The prime challenge of `(x, y)`: the first prime from 62 bits of
+/// `H(x, y)`.
+pub fn challenge<F: Field>(x: F, y: F) -> u64 {
+    let hash = Spec::new().hash(&[x, y]).to_repr();
+    let candidate = (u64::from_le_bytes(hash[..8].try_into().unwrap()) >> 2) | (1 << 62);
+    (candidate..).find(|n| is_prime(*n)).unwrap()
+}
+
+/// `2^t mod l`.
+pub fn remainder(t: u64, l: u64) -> u64 {
+    let (mut r, mut base, mut t) = (1u128, 2 % l as u128, t);
+    while t > 0 {
+        if t & 1 == 1 {
+            r = r * base % l as u128;
+        }
+        base = base * base % l as u128;
+        t >>= 1;
+    }
+    r as u64
+}
+
+/// Deterministic Miller-Rabin for 64-bit integers.
+fn is_prime(n: u64) -> bool {
+    const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+    if n < 2 {
+        return false;
+    }
+    if let Some(base) = BASES.iter().find(|base| n % **base == 0) {
+        return n == *base;
+    }
+
+    let mul = |a: u64, b: u64| (a as u128 * b as u128 % n as u128) as u64;
+    let pow = |mut base: u64, mut e: u64| {
+        let mut acc = 1;
+        while e > 0 {
+            if e & 1 == 1 {
+                acc = mul(acc, base);
+            }
+            base = mul(base, base);
+            e >>= 1;
+        }
+        acc
+    };
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    BASES.iter().all(|base| {
+        let mut x = pow(*base, d);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        (1..s).any(|_| {
+            x = mul(x, x);
+            x == n - 1
+        })
+    })
+}
+
+/// Config for [`VdfCircuit`].
+#[derive(Clone, Debug)]
+pub struct VdfConfig<F: Field> {
+    q_pow: Selector,
+    q_mul: Selector,
+    columns: [Column<Advice>; 2],
+    /// `bit`, `exp`, `acc` and `base` of a square and multiply step.
+    pow: [Cell<F>; 4],
+    pow_height: usize,
+    /// `a`, `b` and `a ⋅ b`.
+    mul: [Cell<F>; 3],
+    instance: InstanceColumns,
+}
+
+impl<F: Field> VdfConfig<F> {
+    /// Returns `base^exp` and the cell of `exp`, for `exp` of [`EXP_BITS`]
+    /// bits.
+    fn pow(
+        &self,
+        mut layouter: impl Layouter<F>,
+        base: &AssignedCell<F, F>,
+        exp: Value<u64>,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), Error> {
+        layouter.assign_region(
+            || "vdf pow",
+            |mut region| {
+                let [bit_cell, exp_cell, acc_cell, base_cell] = &self.pow;
+                let mut exp_value = exp_cell.assign(&mut region, 0, Value::known(F::ZERO))?;
+                region.constrain_constant(exp_value.cell(), F::ZERO)?;
+                let mut acc = acc_cell.assign(&mut region, 0, Value::known(F::ONE))?;
+                region.constrain_constant(acc.cell(), F::ONE)?;
+                base_cell.copy(&mut region, 0, base)?;
+
+                for i in 0..EXP_BITS {
+                    let (offset, next_offset) = (i * self.pow_height, (i + 1) * self.pow_height);
+                    self.q_pow.enable(&mut region, offset)?;
+                    let bit = exp.map(|exp| (exp >> (EXP_BITS - 1 - i)) & 1);
+                    bit_cell.assign(&mut region, offset, bit.map(F::from))?;
+
+                    let next = exp_value.value().zip(bit).map(|(exp, bit)| exp.double() + F::from(bit));
+                    exp_value = exp_cell.assign(&mut region, next_offset, next)?;
+                    let next = (acc.value().zip(base.value()).zip(bit))
+                        .map(|((acc, base), bit)| acc.square() * if bit == 1 { *base } else { F::ONE });
+                    acc = acc_cell.assign(&mut region, next_offset, next)?;
+                    base_cell.assign(&mut region, next_offset, base.value().copied())?;
+                }
+                Ok((acc, exp_value))
+            },
+        )
+    }
+
+    /// Returns `a ⋅ b`.
+    fn mul(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || "vdf mul",
+            |mut region| {
+                let [a_cell, b_cell, product] = &self.mul;
+                self.q_mul.enable(&mut region, 0)?;
+                a_cell.copy(&mut region, 0, a)?;
+                b_cell.copy(&mut region, 0, b)?;
+                product.assign(&mut region, 0, a.value().zip(b.value()).map(|(a, b)| *a * b))
+            },
+        )
+    }
+}
+
+/// Circuit verifying a Wesolowski proof that `y = x^(2^t)`.
+#[derive(Clone, Debug, Default)]
+pub struct VdfCircuit<F: Field> {
+    x: Value<F>,
+    y: Value<F>,
+    pi: Value<F>,
+    l: Value<u64>,
+    r: Value<u64>,
+}
+
+impl<F: Field> VdfCircuit<F> {
+    /// Creates the circuit for `t` squarings of `x`, which runs them.
+    pub fn new(x: F, t: u64) -> Self {
+        let (y, pi) = evaluate(x, t);
+        let l = challenge(x, y);
+        Self {
+            x: Value::known(x),
+            y: Value::known(y),
+            pi: Value::known(pi),
+            l: Value::known(l),
+            r: Value::known(remainder(t, l)),
+        }
+    }
+
+    /// `x`, `y`, and the `l` and `r` the verifier derives from them.
+    pub fn instances(&self) -> Vec<Vec<F>> {
+        let mut instances = vec![];
+        self.x.zip(self.y).zip(self.l).zip(self.r).map(|(((x, y), l), r)| {
+            instances.extend([x, y, F::from(l), F::from(r)]);
+        });
+        vec![instances]
+    }
+
+    /// A single instance column, see the [module documentation](self).
+    pub fn instance_layout() -> InstanceLayout {
+        InstanceLayout::new().column(["x", "y", "l", "r"])
+    }
+}
+
+impl<F: Field> Circuit<F> for VdfCircuit<F> {
+    type Config = VdfConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let q_pow = meta.selector();
+        let q_mul = meta.selector();
+        let columns = [(); 2].map(|_| meta.advice_column());
+        let constant = meta.fixed_column();
+        for column in columns {
+            meta.enable_equality(column);
+        }
+        meta.enable_constant(constant);
+
+        let mut cell_manager = CellManager::new(&columns, 2);
+        let mut pow = None;
+        meta.create_gate("vdf pow", |meta| {
+            let q_pow = meta.query_selector(q_pow);
+            let cells = pow.insert(cell_manager.query_cells(meta));
+            let height = cell_manager.height();
+            let [exp_next, acc_next, base_next] = [&cells[1], &cells[2], &cells[3]].map(|cell| cell.next(meta, height));
+            let [bit, exp, acc, base] = cells.clone().map(|cell| cell.expr());
+            let one = Expression::Constant(F::ONE);
+
+            vec![
+                q_pow.clone() * bit.clone() * (one.clone() - bit.clone()),
+                q_pow.clone() * (exp_next - exp * Expression::Constant(F::from(2)) - bit.clone()),
+                q_pow.clone() * (acc_next - acc.clone() * acc * (one.clone() + bit * (base.clone() - one))),
+                q_pow * (base_next - base),
+            ]
+        });
+
+        let pow_height = cell_manager.height();
+
+        let mut cell_manager = CellManager::new(&columns, 2);
+        let mut mul = None;
+        meta.create_gate("vdf mul", |meta| {
+            let q_mul = meta.query_selector(q_mul);
+            let [a, b, product] = mul.insert(cell_manager.query_cells(meta)).clone().map(|cell| cell.expr());
+
+            vec![q_mul * (a * b - product)]
+        });
+
+        VdfConfig {
+            q_pow,
+            q_mul,
+            columns,
+            pow: pow.unwrap(),
+            pow_height,
+            mul: mul.unwrap(),
+            instance: Self::instance_layout().configure(meta),
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let (x, pi) = layouter.assign_region(
+            || "inputs",
+            |mut region| {
+                Ok((
+                    region.assign_advice(|| "x", config.columns[0], 0, || self.x)?,
+                    region.assign_advice(|| "π", config.columns[0], 1, || self.pi)?,
+                ))
+            },
+        )?;
+
+        let (pi_l, l) = config.pow(layouter.namespace(|| "π^l"), &pi, self.l)?;
+        let (x_r, r) = config.pow(layouter.namespace(|| "x^r"), &x, self.r)?;
+        let y = config.mul(layouter.namespace(|| "π^l ⋅ x^r"), &pi_l, &x_r)?;
+
+        for (i, cell) in [&x, &y, &l, &r].into_iter().enumerate() {
+            config.instance.expose_public(&mut layouter, cell, i)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{challenge, evaluate, is_prime, remainder, VdfCircuit};
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::{Field, TestField as Fp},
+    };
+
+    #[test]
+    fn vdf() {
+        assert!([2, 3, 61, (1 << 61) - 1].iter().all(|n| is_prime(*n)));
+        assert!(![1, 4, 561, 3215031751].iter().any(|n| is_prime(*n)));
+        assert_eq!(remainder(100, 1_000_003), (2u128.pow(100) % 1_000_003) as u64);
+
+        let x = Fp::from(3);
+        let (y, pi) = evaluate(x, 1000);
+        let l = challenge(x, y);
+        assert_eq!(pi.pow_vartime([l]) * x.pow_vartime([remainder(1000, l)]), y);
+
+        let circuit = VdfCircuit::new(x, 1000);
+        expect_satisfied(&circuit, circuit.instances());
+
+        // Claiming one squaring less.
+        let mut instances = circuit.instances();
+        instances[0][1] = evaluate(x, 999).0;
+        expect_failure(
+            &circuit,
+            instances,
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 1 },
+            },
+        );
+
+        // The remainder of another number of squarings.
+        let mut instances = circuit.instances();
+        instances[0][3] = Fp::from(remainder(999, l));
+        expect_failure(
+            &circuit,
+            instances,
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 3 },
+            },
+        );
+    }
+}
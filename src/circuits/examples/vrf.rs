@@ -0,0 +1,173 @@
+//! A Poseidon-based verifiable random function, for lotteries and leader
+//! election.
+//!
+//! The secret key `sk` is any field element, and the public key is
+//! `pk = H(sk)`. The output on an `input` is `H(input, sk)`, and the proof of
+//! this circuit is the VRF proof: it shows that the output was computed with
+//! the secret key of `pk`, which the verifier can't compute themselves.
+//!
+//! | instance          |
+//! | pk, input, output |
+//!
+//! The output is unique for a public key, as two keys with the same `pk`
+//! would be a Poseidon collision, and pseudo-random to anyone who doesn't
+//! know `sk`. The secret key is the second input of the output hash, so that
+//! an output `H(0, sk)` is never the public key `H(sk) = H(sk, 0)`.
+//!
+//! Unlike an EC-VRF, verifying needs a proving system, but the circuit is a
+//! handful of Poseidon permutations.
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+};
+
+use crate::{
+    circuits::{
+        gadgets::poseidon::{PoseidonChip, PoseidonConfig, Spec},
+        instance::{InstanceColumns, InstanceLayout},
+    },
+    field::Field,
+};
+
+/// A VRF key pair.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VrfKey<F> {
+    pub sk: F,
+}
+
+impl<F: Field> VrfKey<F> {
+    /// `H(sk)`.
+    pub fn pk(&self) -> F {
+        Spec::new().hash(&[self.sk])
+    }
+
+    /// `H(input, sk)`.
+    pub fn evaluate(&self, input: F) -> F {
+        Spec::new().hash(&[input, self.sk])
+    }
+}
+
+/// Config for [`VrfCircuit`].
+#[derive(Clone, Debug)]
+pub struct VrfConfig<F: Field> {
+    poseidon: PoseidonConfig<F>,
+    input: Column<Advice>,
+    instance: InstanceColumns,
+}
+
+/// Circuit proving a VRF output.
+#[derive(Clone, Debug)]
+pub struct VrfCircuit<F: Field> {
+    key: Value<VrfKey<F>>,
+    input: Value<F>,
+}
+
+impl<F: Field> Default for VrfCircuit<F> {
+    fn default() -> Self {
+        Self {
+            key: Value::unknown(),
+            input: Value::unknown(),
+        }
+    }
+}
+
+impl<F: Field> VrfCircuit<F> {
+    /// Creates the circuit evaluating the VRF of `key` on `input`.
+    pub fn new(key: VrfKey<F>, input: F) -> Self {
+        Self {
+            key: Value::known(key),
+            input: Value::known(input),
+        }
+    }
+
+    /// The public key, the input and the output.
+    pub fn instances(&self) -> Vec<Vec<F>> {
+        let mut instances = vec![];
+        self.key.zip(self.input).map(|(key, input)| {
+            instances.extend([key.pk(), input, key.evaluate(input)]);
+        });
+        vec![instances]
+    }
+
+    /// A single instance column, see the [module documentation](self).
+    pub fn instance_layout() -> InstanceLayout {
+        InstanceLayout::new().column(["pk", "input", "output"])
+    }
+}
+
+impl<F: Field> Circuit<F> for VrfCircuit<F> {
+    type Config = VrfConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let [input, s0, s1, s2, i0, i1] = [(); 6].map(|_| meta.advice_column());
+        let constant = meta.fixed_column();
+        meta.enable_equality(input);
+
+        VrfConfig {
+            poseidon: PoseidonChip::configure(meta, [s0, s1, s2], [i0, i1], constant),
+            input,
+            instance: Self::instance_layout().configure(meta),
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let (sk, input) = layouter.assign_region(
+            || "inputs",
+            |mut region| {
+                let sk = region.assign_advice(|| "sk", config.input, 0, || self.key.map(|key| key.sk))?;
+                let input = region.assign_advice(|| "input", config.input, 1, || self.input)?;
+                Ok((sk, input))
+            },
+        )?;
+
+        let poseidon = PoseidonChip::construct(config.poseidon);
+        let pk = poseidon.hash(layouter.namespace(|| "pk"), &[sk.clone()])?;
+        let output = poseidon.hash(layouter.namespace(|| "output"), &[input.clone(), sk])?;
+
+        for (i, cell) in [&pk, &input, &output].into_iter().enumerate() {
+            config.instance.expose_public(&mut layouter, cell, i)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{VrfCircuit, VrfKey};
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::TestField as Fp,
+    };
+
+    #[test]
+    fn vrf() {
+        let key = VrfKey { sk: Fp::from(31337) };
+        assert_ne!(key.evaluate(Fp::from(0)), key.pk());
+
+        // One proof per round of a leader election.
+        for round in 0..3 {
+            let circuit = VrfCircuit::new(key, Fp::from(round));
+            expect_satisfied(&circuit, circuit.instances());
+        }
+
+        // Another key's output on the same input, under this public key.
+        let circuit = VrfCircuit::new(key, Fp::from(7));
+        let mut wrong = circuit.instances();
+        wrong[0][2] = VrfKey { sk: Fp::from(31338) }.evaluate(Fp::from(7));
+        expect_failure(
+            &circuit,
+            wrong,
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 2 },
+            },
+        );
+    }
+}
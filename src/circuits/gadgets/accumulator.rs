@@ -0,0 +1,246 @@
+//! Running sum or product of a column of values.
+//!
+//! The accumulator starts from the identity of the operation, folds in one
+//! value per row, and ends by copying its last value to `out`:
+//!
+//! | value | acc                  | out | q_first | q_step | q_last |
+//! |       | identity             |     | 1       | 0      | 0      |
+//! | v_1   | acc[0] op v_1        |     | 0       | 1      | 0      |
+//! | ...   | ...                  |     | 0       | 1      | 0      |
+//! | v_n   | acc[n - 1] op v_n    | out | 0       | 1      | 1      |
+//!
+//! - start: `q_first ⋅ (acc - identity) = 0`,
+//! - transition: `q_step ⋅ (acc - acc[prev] op value) = 0`,
+//! - end: `q_last ⋅ (out - acc) = 0`.
+//!
+//! Only `value` and `out` take part in the permutation, `acc` is constrained
+//! by rotations alone.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::field::Field;
+
+/// Operation folded down the column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccumulatorOp {
+    Sum,
+    Product,
+}
+
+impl AccumulatorOp {
+    fn identity<F: Field>(self) -> F {
+        match self {
+            AccumulatorOp::Sum => F::ZERO,
+            AccumulatorOp::Product => F::ONE,
+        }
+    }
+
+    fn apply<T: std::ops::Add<Output = T> + std::ops::Mul<Output = T>>(self, acc: T, value: T) -> T {
+        match self {
+            AccumulatorOp::Sum => acc + value,
+            AccumulatorOp::Product => acc * value,
+        }
+    }
+}
+
+/// Config for [`AccumulatorChip`].
+#[derive(Clone, Debug)]
+pub struct AccumulatorConfig {
+    op: AccumulatorOp,
+    q_first: Selector,
+    q_step: Selector,
+    q_last: Selector,
+    value: Column<Advice>,
+    acc: Column<Advice>,
+    out: Column<Advice>,
+}
+
+/// Chip accumulating cells with a fixed [`AccumulatorOp`].
+#[derive(Clone, Debug)]
+pub struct AccumulatorChip<F: Field> {
+    config: AccumulatorConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> Chip<F> for AccumulatorChip<F> {
+    type Config = AccumulatorConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field> AccumulatorChip<F> {
+    pub fn configure(meta: &mut ConstraintSystem<F>, op: AccumulatorOp) -> AccumulatorConfig {
+        let [q_first, q_step, q_last] = [(); 3].map(|_| meta.selector());
+        let [value, acc, out] = [(); 3].map(|_| meta.advice_column());
+
+        meta.enable_equality(value);
+        meta.enable_equality(out);
+
+        meta.create_gate("accumulator", |meta| {
+            let [q_first, q_step, q_last] = [q_first, q_step, q_last].map(|q| meta.query_selector(q));
+            let value = meta.query_advice(value, Rotation::cur());
+            let acc_prev = meta.query_advice(acc, Rotation::prev());
+            let acc = meta.query_advice(acc, Rotation::cur());
+            let out = meta.query_advice(out, Rotation::cur());
+
+            vec![
+                q_first * (acc.clone() - Expression::Constant(op.identity())),
+                q_step * (acc.clone() - op.apply(acc_prev, value)),
+                q_last * (out - acc),
+            ]
+        });
+
+        AccumulatorConfig {
+            op,
+            q_first,
+            q_step,
+            q_last,
+            value,
+            acc,
+            out,
+        }
+    }
+
+    pub fn construct(config: AccumulatorConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Folds `values` into a single cell, the identity if there are none.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        values: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        layouter.assign_region(
+            || format!("{:?}", config.op),
+            |mut region| {
+                config.q_first.enable(&mut region, 0)?;
+                let mut acc = Value::known(config.op.identity());
+                region.assign_advice(|| "acc", config.acc, 0, || acc)?;
+
+                for (i, value) in values.iter().enumerate() {
+                    let row = i + 1;
+                    config.q_step.enable(&mut region, row)?;
+                    value.copy_advice(|| "value", &mut region, config.value, row)?;
+                    acc = config.op.apply(acc, value.value().copied());
+                    region.assign_advice(|| "acc", config.acc, row, || acc)?;
+                }
+
+                config.q_last.enable(&mut region, values.len())?;
+                region.assign_advice(|| "out", config.out, values.len(), || acc)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+
+    use super::{AccumulatorChip, AccumulatorConfig, AccumulatorOp};
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::{Field, TestField as Fp},
+    };
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig {
+        sum: AccumulatorConfig,
+        product: AccumulatorConfig,
+        input: Column<Advice>,
+        instance: Column<Instance>,
+    }
+
+    /// Exposes the sum and the product of `values`.
+    #[derive(Default)]
+    struct TestCircuit {
+        values: Vec<Value<u64>>,
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit {
+        type Config = TestCircuitConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                values: vec![Value::unknown(); self.values.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let sum = AccumulatorChip::configure(meta, AccumulatorOp::Sum);
+            let product = AccumulatorChip::configure(meta, AccumulatorOp::Product);
+            let input = meta.advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(input);
+            meta.enable_equality(instance);
+
+            TestCircuitConfig {
+                sum,
+                product,
+                input,
+                instance,
+            }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let values = layouter.assign_region(
+                || "values",
+                |mut region| {
+                    (self.values.iter().enumerate())
+                        .map(|(i, value)| region.assign_advice(|| "value", config.input, i, || value.map(F::from)))
+                        .collect::<Result<Vec<_>, _>>()
+                },
+            )?;
+
+            for (i, accumulator) in [config.sum, config.product].into_iter().enumerate() {
+                let chip = AccumulatorChip::construct(accumulator);
+                let out = chip.assign(layouter.namespace(|| "accumulate"), &values)?;
+                layouter.constrain_instance(out.cell(), config.instance, i)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn accumulator() {
+        let circuit = TestCircuit {
+            values: [2, 3, 4, 5].map(Value::known).to_vec(),
+        };
+        expect_satisfied(&circuit, vec![vec![Fp::from(14), Fp::from(120)]]);
+
+        // An empty column accumulates to the identities.
+        let empty = TestCircuit { values: vec![] };
+        expect_satisfied(&empty, vec![vec![Fp::from(0), Fp::from(1)]]);
+
+        expect_failure(
+            &circuit,
+            vec![vec![Fp::from(14), Fp::from(119)]],
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 1 },
+            },
+        );
+    }
+}
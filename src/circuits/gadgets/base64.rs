@@ -0,0 +1,396 @@
+//! Base64 decoding of ASCII characters into bytes, with `=` padding.
+//!
+//! Every quantum of 4 characters takes a row. Each character is looked up in
+//! the [`Base64Table`] for its 6-bit value and whether it is `=`, and the 24
+//! bits of the values are repacked into 3 bytes, looked up in the
+//! [`U8Table`]:
+//!
+//! | chars[0..4] | values[0..4] | pad[0..4] | bytes[0..3] | padding | q_quantum | q_inner |
+//! | T W F u     | 19 22 5 46   | 0 0 0 0   | 77 97 110   | 0       | 1         | 1       |
+//! | T W E =     | 19 22 4 0    | 0 0 0 1   | 77 97 0     | 1       | 1         | 0       |
+//!
+//! - `Σ values[i] ⋅ 2^(6 ⋅ (3 - i)) = Σ bytes[j] ⋅ 2^(8 ⋅ (2 - j))`,
+//! - only the last two characters of the last quantum may be `=`, and the
+//!   third only if the fourth is,
+//! - the bytes of padding characters are zero, which also rejects encodings
+//!   with non-zero unused bits, so that the encoding of bytes is unique,
+//! - `padding = pad[2] + pad[3]`.
+//!
+//! `=` decodes to 0, so a padded quantum yields zero bytes in place of the
+//! missing ones: the decoded data is the first `3 ⋅ n - padding` bytes.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use crate::{circuits::table::U8Table, field::Field};
+
+/// The base64 alphabet, indexed by 6-bit value.
+pub const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// The padding character.
+pub const PAD: u8 = b'=';
+
+/// Table of `(tag, char, value, is_pad)` for the alphabet and `=`.
+///
+/// The tag is 1 on characters and 0 on a padding row, so that the all-zero
+/// input of disabled rows does not decode the NUL character.
+#[derive(Clone, Copy, Debug)]
+pub struct Base64Table {
+    columns: [TableColumn; 4],
+}
+
+impl Base64Table {
+    pub fn configure<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            columns: [(); 4].map(|_| meta.lookup_table_column()),
+        }
+    }
+
+    /// Fills the table, once per circuit.
+    pub fn load<F: Field>(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "base64 table",
+            |mut table| {
+                let chars = (ALPHABET.iter().enumerate()).map(|(value, char)| [1, *char as u64, value as u64, 0]);
+                let rows = [[0; 4]].into_iter().chain(chars).chain([[1, PAD as u64, 0, 1]]);
+                for (offset, row) in rows.enumerate() {
+                    for (column, value) in self.columns.iter().zip(row) {
+                        table.assign_cell(|| "base64", *column, offset, || Value::known(F::from(value)))?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+/// The 6-bit value of `char` and whether it is `=`, or `None` outside of the
+/// alphabet.
+pub fn decode_char(char: u8) -> Option<(u8, bool)> {
+    if char == PAD {
+        return Some((0, true));
+    }
+    ALPHABET.iter().position(|c| *c == char).map(|value| (value as u8, false))
+}
+
+/// Config for [`Base64Chip`].
+#[derive(Clone, Debug)]
+pub struct Base64Config {
+    q_quantum: Selector,
+    q_inner: Selector,
+    chars: [Column<Advice>; 4],
+    values: [Column<Advice>; 4],
+    pad: [Column<Advice>; 4],
+    bytes: [Column<Advice>; 3],
+    padding: Column<Advice>,
+    table: Base64Table,
+}
+
+/// The output of [`Base64Chip::decode`].
+#[derive(Clone, Debug)]
+pub struct Base64Decoded<F: Field> {
+    /// `3 ⋅ n` bytes, the last `padding` of which are zero.
+    pub bytes: Vec<AssignedCell<F, F>>,
+    /// The number of `=` characters, 0 to 2.
+    pub padding: AssignedCell<F, F>,
+}
+
+/// Chip decoding base64 strings.
+#[derive(Clone, Debug)]
+pub struct Base64Chip<F: Field> {
+    config: Base64Config,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> Chip<F> for Base64Chip<F> {
+    type Config = Base64Config;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field> Base64Chip<F> {
+    /// Configures the chip, range checking the decoded bytes with `u8_table`.
+    pub fn configure(meta: &mut ConstraintSystem<F>, u8_table: U8Table) -> Base64Config {
+        let q_quantum = meta.complex_selector();
+        let q_inner = meta.selector();
+        let [chars, values, pad] = [(); 3].map(|_| [(); 4].map(|_| meta.advice_column()));
+        let bytes = [(); 3].map(|_| meta.advice_column());
+        let padding = meta.advice_column();
+        let table = Base64Table::configure(meta);
+
+        for column in chars.into_iter().chain(bytes).chain([padding]) {
+            meta.enable_equality(column);
+        }
+
+        for columns in chars.into_iter().zip(values).zip(pad).map(|((char, value), pad)| [char, value, pad]) {
+            meta.lookup("base64 char", |meta| {
+                let q_quantum = meta.query_selector(q_quantum);
+                let [char, value, pad] = columns.map(|column| meta.query_advice(column, Rotation::cur()));
+
+                [q_quantum.clone(), q_quantum.clone() * char, q_quantum.clone() * value, q_quantum * pad]
+                    .into_iter()
+                    .zip(table.columns)
+                    .collect()
+            });
+        }
+        for byte in bytes {
+            meta.lookup("base64 byte", |meta| {
+                let q_quantum = meta.query_selector(q_quantum);
+                vec![(q_quantum * meta.query_advice(byte, Rotation::cur()), u8_table.value)]
+            });
+        }
+
+        meta.create_gate("base64 quantum", |meta| {
+            let q_quantum = meta.query_selector(q_quantum);
+            let values = values.map(|column| meta.query_advice(column, Rotation::cur()));
+            let pad = pad.map(|column| meta.query_advice(column, Rotation::cur()));
+            let bytes = bytes.map(|column| meta.query_advice(column, Rotation::cur()));
+            let padding = meta.query_advice(padding, Rotation::cur());
+
+            let pack = |cells: &[Expression<F>], bits: u64| {
+                (cells.iter()).fold(Expression::Constant(F::ZERO), |acc, cell| {
+                    acc * Expression::Constant(F::from(1 << bits)) + cell.clone()
+                })
+            };
+            let [pad0, pad1, pad2, pad3] = pad;
+            let one = Expression::Constant(F::ONE);
+
+            vec![
+                q_quantum.clone() * (pack(&values, 6) - pack(&bytes, 8)),
+                q_quantum.clone() * pad0,
+                q_quantum.clone() * pad1,
+                q_quantum.clone() * pad2.clone() * (one - pad3.clone()),
+                q_quantum.clone() * pad2.clone() * bytes[1].clone(),
+                q_quantum.clone() * pad3.clone() * bytes[2].clone(),
+                q_quantum * (padding - pad2 - pad3),
+            ]
+        });
+
+        meta.create_gate("base64 padding", |meta| {
+            let q_inner = meta.query_selector(q_inner);
+            vec![q_inner * meta.query_advice(pad[3], Rotation::cur())]
+        });
+
+        Base64Config {
+            q_quantum,
+            q_inner,
+            chars,
+            values,
+            pad,
+            bytes,
+            padding,
+            table,
+        }
+    }
+
+    pub fn construct(config: Base64Config) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Fills the [`Base64Table`], once per circuit.
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        self.config.table.load(layouter)
+    }
+
+    /// Decodes `chars`, a non-empty multiple of 4 ASCII characters.
+    pub fn decode(
+        &self,
+        mut layouter: impl Layouter<F>,
+        chars: &[AssignedCell<F, F>],
+    ) -> Result<Base64Decoded<F>, Error> {
+        assert!(!chars.is_empty() && chars.len() % 4 == 0);
+        let config = &self.config;
+
+        layouter.assign_region(
+            || "base64",
+            |mut region| {
+                let mut bytes = vec![];
+                let mut padding = None;
+                for (row, quantum) in chars.chunks(4).enumerate() {
+                    config.q_quantum.enable(&mut region, row)?;
+                    if (row + 1) * 4 < chars.len() {
+                        config.q_inner.enable(&mut region, row)?;
+                    }
+
+                    // Characters outside of the alphabet decode to 0, and
+                    // fail the lookup.
+                    let decoded: Vec<Value<(u8, bool)>> = (quantum.iter())
+                        .map(|char| char.value().map(|char| decode_char(char.to_repr()[0]).unwrap_or_default()))
+                        .collect();
+                    for (i, char) in quantum.iter().enumerate() {
+                        char.copy_advice(|| format!("chars[{i}]"), &mut region, config.chars[i], row)?;
+                        let value = decoded[i].map(|(value, _)| F::from(value as u64));
+                        let pad = decoded[i].map(|(_, pad)| F::from(pad as u64));
+                        region.assign_advice(|| format!("values[{i}]"), config.values[i], row, || value)?;
+                        region.assign_advice(|| format!("pad[{i}]"), config.pad[i], row, || pad)?;
+                    }
+
+                    let bits = (decoded.iter()).fold(Value::known(0u32), |acc, char| {
+                        acc.zip(*char).map(|(acc, (value, _))| (acc << 6) | value as u32)
+                    });
+                    for (j, column) in config.bytes.iter().enumerate() {
+                        let byte = bits.map(|bits| F::from(((bits >> (8 * (2 - j))) & 0xff) as u64));
+                        bytes.push(region.assign_advice(|| format!("bytes[{j}]"), *column, row, || byte)?);
+                    }
+
+                    let count = (decoded[2..].iter())
+                        .fold(Value::known(0), |acc, char| acc.zip(*char).map(|(acc, (_, pad))| acc + pad as u64));
+                    let count = count.map(F::from);
+                    padding = Some(region.assign_advice(|| "padding", config.padding, row, || count)?);
+                }
+
+                Ok(Base64Decoded {
+                    bytes,
+                    padding: padding.unwrap(),
+                })
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+    };
+
+    use super::{decode_char, Base64Chip, Base64Config};
+    use crate::{
+        circuits::{
+            instance::{InstanceColumns, InstanceLayout},
+            table::U8Table,
+        },
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::{Field, TestField as Fp},
+    };
+
+    /// Decodes `encoded`, exposing the bytes followed by the padding count.
+    #[derive(Default)]
+    struct TestCircuit {
+        encoded: &'static str,
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit {
+        type Config = (Base64Config, U8Table, Column<Advice>, InstanceColumns);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let advice = meta.advice_column();
+            let u8_table = U8Table::configure(meta);
+            let config = Base64Chip::configure(meta, u8_table);
+            let instance = InstanceLayout::new().column(["decoded"]).configure(meta);
+            meta.enable_equality(advice);
+            (config, u8_table, advice, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, u8_table, advice, instance): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = Base64Chip::construct(config);
+            chip.load(&mut layouter)?;
+            u8_table.load(&mut layouter)?;
+
+            let chars = layouter.assign_region(
+                || "chars",
+                |mut region| {
+                    (self.encoded.bytes().enumerate())
+                        .map(|(i, char)| {
+                            region.assign_advice(|| "char", advice, i, || Value::known(F::from(char as u64)))
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                },
+            )?;
+            let decoded = chip.decode(layouter.namespace(|| "decode"), &chars)?;
+
+            for (i, cell) in decoded.bytes.iter().chain([&decoded.padding]).enumerate() {
+                instance.expose_public(&mut layouter, cell, i)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn instances(bytes: &[u8], padding: u64) -> Vec<Vec<Fp>> {
+        vec![(bytes.iter().map(|byte| Fp::from(*byte as u64)).chain([Fp::from(padding)])).collect()]
+    }
+
+    #[test]
+    fn base64() {
+        assert_eq!(decode_char(b'A'), Some((0, false)));
+        assert_eq!(decode_char(b'/'), Some((63, false)));
+        assert_eq!(decode_char(b'='), Some((0, true)));
+        assert_eq!(decode_char(b'!'), None);
+
+        let cases: [(&str, &[u8], u64); 4] = [
+            ("TWFu", b"Man", 0),
+            ("TWFuTWE=", b"ManMa\0", 1),
+            ("TWFuTQ==", b"ManM\0\0", 2),
+            ("+/+/", &[0xfb, 0xff, 0xbf], 0),
+        ];
+        for (encoded, bytes, padding) in cases {
+            expect_satisfied(&TestCircuit { encoded }, instances(bytes, padding));
+        }
+
+        // `!` is not in the alphabet.
+        expect_failure(
+            &TestCircuit { encoded: "TW!u" },
+            instances(&[0x4d, 0x60, 0x2e], 0),
+            FailureMatcher::Lookup {
+                name: "base64 char",
+                location: Location::InRegion {
+                    region: "base64",
+                    offset: 0,
+                },
+            },
+        );
+
+        // Padding in the middle of the string.
+        expect_failure(
+            &TestCircuit { encoded: "TQ==TWFu" },
+            instances(b"M\0\0Man", 0),
+            FailureMatcher::Constraint {
+                gate: "base64 padding",
+                location: Location::InRegion {
+                    region: "base64",
+                    offset: 0,
+                },
+            },
+        );
+
+        // `R` leaves unused bits set: "TR==" is a non-canonical "TQ==".
+        expect_failure(
+            &TestCircuit { encoded: "TR==" },
+            instances(&[0x4d, 0x10, 0], 2),
+            FailureMatcher::Constraint {
+                gate: "base64 quantum",
+                location: Location::InRegion {
+                    region: "base64",
+                    offset: 0,
+                },
+            },
+        );
+    }
+}
@@ -0,0 +1,288 @@
+//! Little-endian bit decomposition with a running sum.
+//!
+//! | acc            | q_bit | q_end |
+//! | value = acc[0] | 1     | 0     |
+//! | acc[1]         | 1     | 0     |
+//! | ...            | ...   | ...   |
+//! | acc[n] = 0     | 0     | 1     |
+//!
+//! where `acc[i] = 2 * acc[i + 1] + bit[i]`. Each `bit[i]` is constrained to
+//! be boolean and `acc[n]` to be zero, which proves `value < 2^n`.
+//!
+//! With `NUM_COLUMNS` columns, the running sum is laid out row by row, so a
+//! decomposition takes `n / NUM_COLUMNS + 1` rows instead of `n + 1`, and
+//! `acc[n]` is checked by the end selector of its column:
+//!
+//! | acc[0]  | acc[1]  | q_bit | q_end[0] | q_end[1] |
+//! | value   | acc[1]  | 1     | 0        | 0        |
+//! | acc[2]  | acc[3]  | 1     | 0        | 0        |
+//! | ...     | ...     | ...   | ...      | ...      |
+//! | acc[n]  | 0       | 0     | 1        | 0        |
+//!
+//! Rows past `acc[n]` hold `value >> i`, which is zero when the check
+//! passes. More columns fit larger decompositions in a smaller `k`, at the
+//! cost of a wider gate, see `benches/columns.rs`.
+//!
+//! As `acc[i] = value >> i`, each row of the running sum is computed on its
+//! own, in parallel with [`par_rows`], and [`running_sums`] decomposes a batch
+//! of values in parallel too.
+//!
+//! [`running_sums`]: BitsConfig::running_sums
+
+use std::array;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+use rayon::prelude::*;
+
+use crate::{circuits::util::parallel::par_rows, field::Field};
+
+/// Config of the running sum decomposition over `NUM_COLUMNS` columns.
+#[derive(Clone, Debug)]
+pub struct BitsConfig<const NUM_COLUMNS: usize = 1> {
+    q_bit: Selector,
+    q_end: [Selector; NUM_COLUMNS],
+    acc: [Column<Advice>; NUM_COLUMNS],
+}
+
+impl BitsConfig {
+    pub fn configure<F: Field>(meta: &mut ConstraintSystem<F>, acc: Column<Advice>) -> Self {
+        Self::configure_columns(meta, [acc])
+    }
+}
+
+impl<const NUM_COLUMNS: usize> BitsConfig<NUM_COLUMNS> {
+    /// Configures the decomposition over the `acc` columns.
+    pub fn configure_columns<F: Field>(meta: &mut ConstraintSystem<F>, acc: [Column<Advice>; NUM_COLUMNS]) -> Self {
+        assert!(NUM_COLUMNS > 0);
+        let q_bit = meta.selector();
+        let q_end = array::from_fn(|_| meta.selector());
+
+        for column in acc {
+            meta.enable_equality(column);
+        }
+
+        meta.create_gate("bit", |meta| {
+            let q_bit = meta.query_selector(q_bit);
+            let acc_cur = acc.map(|column| meta.query_advice(column, Rotation::cur()));
+            let acc_next = meta.query_advice(acc[0], Rotation::next());
+
+            (0..NUM_COLUMNS)
+                .map(|i| {
+                    let next = acc_cur.get(i + 1).unwrap_or(&acc_next).clone();
+                    let bit = acc_cur[i].clone() - Expression::Constant(F::from(2)) * next;
+                    q_bit.clone() * bit.clone() * (Expression::Constant(F::ONE) - bit)
+                })
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("bits end", |meta| {
+            (q_end.iter().zip(acc))
+                .map(|(q_end, acc)| meta.query_selector(*q_end) * meta.query_advice(acc, Rotation::cur()))
+                .collect::<Vec<_>>()
+        });
+
+        Self { q_bit, q_end, acc }
+    }
+
+    /// Rows of the `q_bit` gate of a decomposition into `num_bits` bits.
+    fn num_rows(num_bits: usize) -> usize {
+        num_bits.div_ceil(NUM_COLUMNS)
+    }
+
+    /// Constrains `value < 2^num_bits` by decomposing it into bits, returning
+    /// the running sum: `acc[i] = value >> i`, for `i` in `[0, num_bits]`.
+    pub fn decompose<F: Field>(
+        &self,
+        layouter: impl Layouter<F>,
+        value: &AssignedCell<F, F>,
+        num_bits: usize,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let num_accs = Self::num_rows(num_bits) * NUM_COLUMNS;
+        let accs = value.value().map(|value| running_sum_values(value, 1, num_accs));
+        self.assign_running_sum(layouter, value, num_bits, accs)
+    }
+
+    /// Decomposes each of `values` like [`decompose`](Self::decompose),
+    /// computing their running sums in parallel.
+    pub fn running_sums<F: Field>(
+        &self,
+        mut layouter: impl Layouter<F>,
+        values: &[AssignedCell<F, F>],
+        num_bits: usize,
+    ) -> Result<Vec<Vec<AssignedCell<F, F>>>, Error> {
+        let num_accs = Self::num_rows(num_bits) * NUM_COLUMNS;
+        let known: Vec<_> = values.iter().map(|value| value.value().copied()).collect();
+        let accs: Vec<_> = (known.into_par_iter())
+            .map(|value| value.map(|value| running_sum_values(&value, 1, num_accs)))
+            .collect();
+
+        (values.iter().zip(accs).enumerate())
+            .map(|(i, (value, accs))| {
+                self.assign_running_sum(layouter.namespace(|| format!("value {i}")), value, num_bits, accs)
+            })
+            .collect()
+    }
+
+    /// Assigns the precomputed running sum `accs` of `value`, padded to a
+    /// whole number of rows.
+    fn assign_running_sum<F: Field>(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: &AssignedCell<F, F>,
+        num_bits: usize,
+        accs: Value<Vec<F>>,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        layouter.assign_region(
+            || format!("{num_bits} bits"),
+            |mut region| {
+                let mut cells = vec![value.copy_advice(|| "value", &mut region, self.acc[0], 0)?];
+
+                let num_rows = Self::num_rows(num_bits);
+                for row in 0..num_rows {
+                    self.q_bit.enable(&mut region, row)?;
+                }
+                for i in 1..=num_rows * NUM_COLUMNS {
+                    let (row, column) = (i / NUM_COLUMNS, i % NUM_COLUMNS);
+                    let acc = accs.as_ref().map(|accs| accs[i]);
+                    let cell = region.assign_advice(|| format!("acc {i}"), self.acc[column], row, || acc)?;
+                    if i <= num_bits {
+                        cells.push(cell);
+                    }
+                }
+                let (row, column) = (num_bits / NUM_COLUMNS, num_bits % NUM_COLUMNS);
+                self.q_end[column].enable(&mut region, row)?;
+
+                Ok(cells)
+            },
+        )
+    }
+}
+
+/// The running sum `acc[i] = value >> (step ⋅ i)` of a decomposition into
+/// `num_steps` limbs of `step` bits, for `i` in `[0, num_steps]`.
+pub fn running_sum_values<F: Field>(value: &F, step: usize, num_steps: usize) -> Vec<F> {
+    let repr = value.to_repr();
+    par_rows(num_steps + 1, |i| F::from_repr(shr(repr, step * i)).unwrap())
+}
+
+/// The little-endian integer `repr` shifted right by `shift` bits.
+fn shr(repr: [u8; 32], shift: usize) -> [u8; 32] {
+    let (bytes, bits) = (shift / 8, shift % 8);
+    let byte = |i: usize| repr.get(i).copied().unwrap_or(0) as u16;
+    std::array::from_fn(|i| {
+        let pair = byte(i + bytes) | byte(i + bytes + 1) << 8;
+        (pair >> bits) as u8
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        halo2curves::group::ff::{Field as _, PrimeField},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+    };
+
+    use super::{running_sum_values, BitsConfig};
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::{Field, TestField as Fp},
+    };
+
+    #[derive(Default)]
+    struct TestCircuit<const BITS: usize, const COLUMNS: usize = 1> {
+        value: Value<u64>,
+    }
+
+    impl<F: Field, const BITS: usize, const COLUMNS: usize> Circuit<F> for TestCircuit<BITS, COLUMNS> {
+        type Config = (BitsConfig<COLUMNS>, Column<Advice>);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let value = meta.advice_column();
+            let acc = [(); COLUMNS].map(|_| meta.advice_column());
+            meta.enable_equality(value);
+            (BitsConfig::configure_columns(meta, acc), value)
+        }
+
+        fn synthesize(&self, (bits, value): Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let value = layouter.assign_region(
+                || "value",
+                |mut region| region.assign_advice(|| "value", value, 0, || self.value.map(F::from)),
+            )?;
+            let accs = bits.decompose(layouter.namespace(|| "decompose"), &value, BITS)?;
+            assert_eq!(accs.len(), BITS + 1);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn bits() {
+        expect_satisfied::<Fp, _>(&TestCircuit::<8> { value: Value::known(255) }, vec![]);
+        expect_satisfied::<Fp, _>(&TestCircuit::<64> { value: Value::known(u64::MAX) }, vec![]);
+
+        // 256 needs a ninth bit: the running sum ends at 1.
+        expect_failure::<Fp, _>(
+            &TestCircuit::<8> { value: Value::known(256) },
+            vec![],
+            FailureMatcher::Constraint {
+                gate: "bits end",
+                location: Location::InRegion {
+                    region: "8 bits",
+                    offset: 8,
+                },
+            },
+        );
+    }
+
+    #[test]
+    fn bits_columns() {
+        expect_satisfied::<Fp, _>(&TestCircuit::<8, 3> { value: Value::known(255) }, vec![]);
+        expect_satisfied::<Fp, _>(&TestCircuit::<64, 4> { value: Value::known(u64::MAX) }, vec![]);
+
+        // acc[8] is in the last column of the third row.
+        expect_failure::<Fp, _>(
+            &TestCircuit::<8, 3> { value: Value::known(256) },
+            vec![],
+            FailureMatcher::Constraint {
+                gate: "bits end",
+                location: Location::InRegion {
+                    region: "8 bits",
+                    offset: 2,
+                },
+            },
+        );
+    }
+
+    #[test]
+    fn running_sum() {
+        let num_bits = Fp::NUM_BITS as usize;
+        let value = -Fp::from(3);
+        let accs = running_sum_values(&value, 1, num_bits);
+        assert_eq!(accs.len(), num_bits + 1);
+        assert_eq!(accs[0], value);
+
+        // Each row matches halving the previous one, less its low bit.
+        let half = Fp::from(2).invert().unwrap();
+        for pair in accs.windows(2) {
+            let bit = Fp::from((pair[0].to_repr()[0] & 1) as u64);
+            assert_eq!(pair[1], (pair[0] - bit) * half);
+        }
+        assert_eq!(accs[num_bits], Fp::ZERO);
+
+        assert_eq!(
+            running_sum_values(&Fp::from(0xabcd), 4, 4),
+            [0xabcd, 0xabc, 0xab, 0xa, 0].map(Fp::from)
+        );
+    }
+}
@@ -0,0 +1,327 @@
+//! Bitwise AND/OR/XOR of 256-bit words.
+//!
+//! Bitwise operations have no low degree arithmetization, so each byte of the
+//! result is looked up in a fixed table of all `(op, a, b, a op b)` tuples
+//! over bytes. Words go in and come out as [`WordCells`], the byte
+//! decomposition of [`WordConfig`].
+//!
+//! | op (fixed) | a           | b           | result      | q_lookup |
+//! | op         | a.bytes[0]  | b.bytes[0]  | r.bytes[0]  | 1        |
+//! | ...        | ...         | ...         | ...         | 1        |
+//! | op         | a.bytes[31] | b.bytes[31] | r.bytes[31] | 1        |
+//!
+//! With `NUM_COLUMNS` groups of `a`, `b` and `result` columns, each looked
+//! up on its own, the bytes are laid out row by row and an operation takes
+//! `32 / NUM_COLUMNS` rows. Padding bytes are zero, which every operation
+//! maps to zero.
+//!
+//! The table has `3 * 2^16` rows, so circuits using this chip need `k >= 18`.
+
+use std::{array, marker::PhantomData};
+
+use halo2_proofs::{
+    circuit::{Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Fixed, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use crate::{
+    circuits::word::{WordCells, WordConfig},
+    field::Field,
+};
+
+/// A bitwise operation, encoded in the table by its discriminant.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BitwiseOp {
+    And = 0,
+    Or = 1,
+    Xor = 2,
+}
+
+impl BitwiseOp {
+    pub const ALL: [BitwiseOp; 3] = [BitwiseOp::And, BitwiseOp::Or, BitwiseOp::Xor];
+
+    pub fn apply(self, a: u8, b: u8) -> u8 {
+        match self {
+            BitwiseOp::And => a & b,
+            BitwiseOp::Or => a | b,
+            BitwiseOp::Xor => a ^ b,
+        }
+    }
+}
+
+/// Config for [`BitwiseChip`].
+#[derive(Clone, Debug)]
+pub struct BitwiseConfig<const NUM_COLUMNS: usize = 1> {
+    q_lookup: Selector,
+    op: Column<Fixed>,
+    a: [Column<Advice>; NUM_COLUMNS],
+    b: [Column<Advice>; NUM_COLUMNS],
+    result: [Column<Advice>; NUM_COLUMNS],
+    table: [TableColumn; 4],
+    word: WordConfig<NUM_COLUMNS>,
+}
+
+/// Chip computing bitwise operations on words through a byte lookup, over
+/// `NUM_COLUMNS` groups of columns.
+#[derive(Clone, Debug)]
+pub struct BitwiseChip<F: Field, const NUM_COLUMNS: usize = 1> {
+    config: BitwiseConfig<NUM_COLUMNS>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field, const NUM_COLUMNS: usize> Chip<F> for BitwiseChip<F, NUM_COLUMNS> {
+    type Config = BitwiseConfig<NUM_COLUMNS>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field, const NUM_COLUMNS: usize> BitwiseChip<F, NUM_COLUMNS> {
+    /// Rows taken by an operation.
+    pub const NUM_ROWS: usize = 32usize.div_ceil(NUM_COLUMNS);
+
+    /// Configures the chip, assembling results with `word`.
+    pub fn configure(meta: &mut ConstraintSystem<F>, word: WordConfig<NUM_COLUMNS>) -> BitwiseConfig<NUM_COLUMNS> {
+        let q_lookup = meta.complex_selector();
+        let op = meta.fixed_column();
+        let groups: [[Column<Advice>; 3]; NUM_COLUMNS] = array::from_fn(|_| [(); 3].map(|_| meta.advice_column()));
+        let table = [(); 4].map(|_| meta.lookup_table_column());
+
+        for column in groups.iter().flatten() {
+            meta.enable_equality(*column);
+        }
+
+        for [a, b, result] in groups {
+            meta.lookup("bitwise byte", |meta| {
+                let q_lookup = meta.query_selector(q_lookup);
+                let inputs = [
+                    meta.query_fixed(op, Rotation::cur()),
+                    meta.query_advice(a, Rotation::cur()),
+                    meta.query_advice(b, Rotation::cur()),
+                    meta.query_advice(result, Rotation::cur()),
+                ];
+
+                inputs
+                    .into_iter()
+                    .zip(table)
+                    .map(|(input, column)| (q_lookup.clone() * input, column))
+                    .collect()
+            });
+        }
+
+        BitwiseConfig {
+            q_lookup,
+            op,
+            a: groups.map(|group| group[0]),
+            b: groups.map(|group| group[1]),
+            result: groups.map(|group| group[2]),
+            table,
+            word,
+        }
+    }
+
+    pub fn construct(config: BitwiseConfig<NUM_COLUMNS>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Fills the `(op, a, b, a op b)` table, once per circuit.
+    pub fn load_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        let table = self.config.table;
+        layouter.assign_table(
+            || "bitwise table",
+            |mut t| {
+                let mut offset = 0;
+                for op in BitwiseOp::ALL {
+                    for a in 0..=255u8 {
+                        for b in 0..=255u8 {
+                            let row = [op as u64, a as u64, b as u64, op.apply(a, b) as u64];
+                            for (column, value) in table.iter().zip(row) {
+                                t.assign_cell(|| "bitwise table", *column, offset, || Value::known(F::from(value)))?;
+                            }
+                            offset += 1;
+                        }
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Computes `a op b` byte by byte.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        op: BitwiseOp,
+        a: &WordCells<F>,
+        b: &WordCells<F>,
+    ) -> Result<WordCells<F>, Error> {
+        let config = &self.config;
+
+        let result_bytes = a
+            .bytes_value()
+            .zip(b.bytes_value())
+            .map(|(a, b)| array::from_fn(|i| op.apply(a[i], b[i])));
+        let result = config.word.assign(layouter.namespace(|| "result"), result_bytes)?;
+
+        layouter.assign_region(
+            || format!("bitwise {op:?}"),
+            |mut region| {
+                for row in 0..Self::NUM_ROWS {
+                    config.q_lookup.enable(&mut region, row)?;
+                    region.assign_fixed(|| "op", config.op, row, || Value::known(F::from(op as u64)))?;
+                }
+                for i in 0..Self::NUM_ROWS * NUM_COLUMNS {
+                    let (row, group) = (i / NUM_COLUMNS, i % NUM_COLUMNS);
+                    let cells = [&a.bytes, &b.bytes, &result.bytes].map(|bytes| bytes.limbs.get(i));
+                    for ((name, column), cell) in [("a", config.a), ("b", config.b), ("result", config.result)]
+                        .into_iter()
+                        .zip(cells)
+                    {
+                        match cell {
+                            Some(cell) => cell.copy_advice(|| name, &mut region, column[group], row)?,
+                            None => region.assign_advice(|| "padding", column[group], row, || Value::known(F::ZERO))?,
+                        };
+                    }
+                }
+                Ok(())
+            },
+        )?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+
+    use super::{BitwiseChip, BitwiseConfig, BitwiseOp};
+    use crate::{
+        circuits::{
+            table::U8Table,
+            word::{Word, WordConfig},
+        },
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::{Field, TestField as Fp},
+    };
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig<const COLUMNS: usize> {
+        bitwise: BitwiseConfig<COLUMNS>,
+        word: WordConfig<COLUMNS>,
+        u8_table: U8Table,
+        instance: Column<Instance>,
+    }
+
+    /// Exposes `lo`, `hi` of `a & b`, `a | b` and `a ^ b`.
+    #[derive(Default)]
+    struct TestCircuit<const COLUMNS: usize = 1> {
+        a: Value<[u8; 32]>,
+        b: Value<[u8; 32]>,
+    }
+
+    impl<F: Field, const COLUMNS: usize> Circuit<F> for TestCircuit<COLUMNS> {
+        type Config = TestCircuitConfig<COLUMNS>;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let u8_table = U8Table::configure(meta);
+            let byte = [(); COLUMNS].map(|_| meta.advice_column());
+            let [lo, hi] = [(); 2].map(|_| meta.advice_column());
+            let word = WordConfig::configure_columns(meta, byte, lo, hi, u8_table);
+            let bitwise = BitwiseChip::configure(meta, word.clone());
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            TestCircuitConfig {
+                bitwise,
+                word,
+                u8_table,
+                instance,
+            }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let chip = BitwiseChip::construct(config.bitwise);
+            config.u8_table.load(&mut layouter)?;
+            chip.load_table(&mut layouter)?;
+
+            let a = config.word.assign(layouter.namespace(|| "a"), self.a)?;
+            let b = config.word.assign(layouter.namespace(|| "b"), self.b)?;
+
+            for (i, op) in BitwiseOp::ALL.into_iter().enumerate() {
+                let result = chip.assign(layouter.namespace(|| format!("{op:?}")), op, &a, &b)?;
+                layouter.constrain_instance(result.word.lo().cell(), config.instance, 2 * i)?;
+                layouter.constrain_instance(result.word.hi().cell(), config.instance, 2 * i + 1)?;
+            }
+            Ok(())
+        }
+    }
+
+    /// `lo`, `hi` of `a & b`, `a | b` and `a ^ b`.
+    fn results(a: [u8; 32], b: [u8; 32]) -> Vec<Fp> {
+        BitwiseOp::ALL
+            .into_iter()
+            .flat_map(|op| {
+                let word = Word::<Fp>::from_le_bytes(std::array::from_fn(|i| op.apply(a[i], b[i])));
+                [word.lo(), word.hi()]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn bitwise_ops() {
+        let a: [u8; 32] = std::array::from_fn(|i| (i * 37 + 11) as u8);
+        let b: [u8; 32] = std::array::from_fn(|i| (i * 101 + 5) as u8);
+        let instances = results(a, b);
+
+        let circuit = TestCircuit::<1> {
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        expect_satisfied(&circuit, vec![instances.clone()]);
+
+        // Claiming `a | b` as the result of `a & b`.
+        let mut wrong = instances;
+        wrong.swap(0, 2);
+        expect_failure(
+            &circuit,
+            vec![wrong],
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+    }
+
+    #[test]
+    fn bitwise_columns() {
+        let a: [u8; 32] = std::array::from_fn(|i| (i * 13 + 200) as u8);
+        let b: [u8; 32] = std::array::from_fn(|i| (i * 29 + 7) as u8);
+
+        // 11 rows, the last one with a padding byte.
+        let circuit = TestCircuit::<3> {
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        expect_satisfied(&circuit, vec![results(a, b)]);
+    }
+}
@@ -0,0 +1,236 @@
+//! Field elements from 32 bytes, checked to be canonical.
+//!
+//! The bytes of a word only encode a field element canonically if the word is
+//! below the modulus `p`. Two encodings, `x` and `x + p`, would otherwise map
+//! to the same element, which breaks deserializing hashes into field elements.
+//! The word is compared to the constant `p - 1`, whose halves both fit in a
+//! field element, with the [`LtWordChip`]: `p - 1 < word` must be 0.
+//!
+//! | lo       | hi       | value              | q_canonical |
+//! | word.lo  | word.hi  | lo + 2^128 ⋅ hi    | 1           |
+//! | (p-1).lo | (p-1).hi |                    | 0           |
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+use super::lt_word::{LtWordChip, LtWordConfig};
+use crate::{
+    circuits::word::{Word, WordCells},
+    field::Field,
+};
+
+/// Config for [`CanonicalChip`].
+#[derive(Clone, Debug)]
+pub struct CanonicalConfig<F: Field> {
+    q_canonical: Selector,
+    lo: Column<Advice>,
+    hi: Column<Advice>,
+    value: Column<Advice>,
+    lt_word: LtWordConfig<F>,
+}
+
+/// Chip converting words to the field elements they canonically encode.
+#[derive(Clone, Debug)]
+pub struct CanonicalChip<F: Field> {
+    config: CanonicalConfig<F>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> Chip<F> for CanonicalChip<F> {
+    type Config = CanonicalConfig<F>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field> CanonicalChip<F> {
+    /// Configures the chip, comparing with `lt_word` and pinning the modulus
+    /// and the comparison with `constant`.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        lt_word: LtWordConfig<F>,
+        constant: Column<Fixed>,
+    ) -> CanonicalConfig<F> {
+        let q_canonical = meta.selector();
+        let [lo, hi, value] = [(); 3].map(|_| meta.advice_column());
+
+        for column in [lo, hi, value] {
+            meta.enable_equality(column);
+        }
+        meta.enable_constant(constant);
+
+        meta.create_gate("field from word", |meta| {
+            let q_canonical = meta.query_selector(q_canonical);
+            let [lo, hi, value] = [lo, hi, value].map(|column| meta.query_advice(column, Rotation::cur()));
+            let shift = Expression::Constant(F::from(2).pow_vartime([128]));
+
+            vec![q_canonical * (value - lo - shift * hi)]
+        });
+
+        CanonicalConfig {
+            q_canonical,
+            lo,
+            hi,
+            value,
+            lt_word,
+        }
+    }
+
+    pub fn construct(config: CanonicalConfig<F>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the field element encoded by the little-endian bytes of `word`,
+    /// failing if they are not below the modulus.
+    pub fn assign(&self, mut layouter: impl Layouter<F>, word: &WordCells<F>) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        let lt_word = LtWordChip::construct(config.lt_word.clone());
+
+        let (max, value) = layouter.assign_region(
+            || "canonical",
+            |mut region| {
+                config.q_canonical.enable(&mut region, 0)?;
+                let lo = word.word.lo().copy_advice(|| "lo", &mut region, config.lo, 0)?;
+                let hi = word.word.hi().copy_advice(|| "hi", &mut region, config.hi, 0)?;
+                let value = lo.value().zip(hi.value()).map(|(lo, hi)| *lo + F::from(2).pow_vartime([128]) * hi);
+                let value = region.assign_advice(|| "value", config.value, 0, || value)?;
+
+                let max = Word::<F>::from_le_bytes((-F::ONE).to_repr());
+                let max_lo = region.assign_advice_from_constant(|| "(p - 1).lo", config.lo, 1, max.lo())?;
+                let max_hi = region.assign_advice_from_constant(|| "(p - 1).hi", config.hi, 1, max.hi())?;
+                Ok((Word::new([max_lo, max_hi]), value))
+            },
+        )?;
+
+        let above = lt_word.assign(layouter.namespace(|| "p - 1 < word"), &max, &word.word)?;
+        layouter.assign_region(|| "canonical bound", |mut region| region.constrain_constant(above.cell(), F::ZERO))?;
+
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        halo2curves::group::ff::{Field as _, PrimeField},
+        plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+
+    use super::{CanonicalChip, CanonicalConfig};
+    use crate::{
+        circuits::{
+            gadgets::{lt::LtChip, lt_word::LtWordChip, select::SelectChip},
+            pack::PackConfig,
+            table::U8Table,
+            word::WordConfig,
+        },
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::{Field, TestField as Fp},
+    };
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig<F: Field> {
+        canonical: CanonicalConfig<F>,
+        word: WordConfig,
+        u8_table: U8Table,
+        instance: Column<Instance>,
+    }
+
+    /// Exposes the field element encoded by `bytes`.
+    #[derive(Default)]
+    struct TestCircuit {
+        bytes: Value<[u8; 32]>,
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit {
+        type Config = TestCircuitConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let u8_table = U8Table::configure(meta);
+            let [byte, lo, hi, pack_byte, pack_acc] = [(); 5].map(|_| meta.advice_column());
+            let constant = meta.fixed_column();
+            let word = WordConfig::configure(meta, byte, lo, hi, u8_table);
+            let pack = PackConfig::configure(meta, pack_byte, pack_acc, u8_table);
+            let lt = LtChip::configure(meta, pack);
+            let select = SelectChip::configure(meta);
+            let canonical = CanonicalChip::configure(meta, LtWordChip::configure(lt, select), constant);
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            TestCircuitConfig {
+                canonical,
+                word,
+                u8_table,
+                instance,
+            }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let chip = CanonicalChip::construct(config.canonical);
+            config.u8_table.load(&mut layouter)?;
+
+            let word = config.word.assign(layouter.namespace(|| "bytes"), self.bytes)?;
+            let value = chip.assign(layouter.namespace(|| "canonical"), &word)?;
+            layouter.constrain_instance(value.cell(), config.instance, 0)
+        }
+    }
+
+    /// Little-endian bytes of the modulus.
+    fn modulus() -> [u8; 32] {
+        let mut bytes = (-Fp::ONE).to_repr();
+        for byte in bytes.iter_mut() {
+            *byte = byte.wrapping_add(1);
+            if *byte != 0 {
+                break;
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn canonical() {
+        for value in [Fp::ZERO, Fp::from(12345), Fp::from_u128(u128::MAX), -Fp::ONE] {
+            let circuit = TestCircuit {
+                bytes: Value::known(value.to_repr()),
+            };
+            expect_satisfied(&circuit, vec![vec![value]]);
+        }
+
+        // `p` encodes 0, but not canonically.
+        let circuit = TestCircuit {
+            bytes: Value::known(modulus()),
+        };
+        expect_failure(
+            &circuit,
+            vec![vec![Fp::ZERO]],
+            FailureMatcher::Permutation {
+                location: Location::InRegion {
+                    region: "select",
+                    offset: 0,
+                },
+            },
+        );
+    }
+}
@@ -0,0 +1,149 @@
+//! Little-endian decomposition into 2-bit crumbs with a running sum.
+//!
+//! | acc            | q_crumb | q_end |
+//! | value = acc[0] | 1       | 0     |
+//! | acc[1]         | 1       | 0     |
+//! | ...            | ...     | ...   |
+//! | acc[n] = 0     | 0       | 1     |
+//!
+//! where `acc[i] = 4 * acc[i + 1] + crumb[i]`. Each `crumb[i]` is constrained
+//! to be in `[0, 4)` by the degree 4 polynomial
+//! `crumb ⋅ (1 - crumb) ⋅ (2 - crumb) ⋅ (3 - crumb)`, so this halves the rows
+//! of [`super::bits`] without needing a lookup table.
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use super::bits::running_sum_values;
+use crate::field::Field;
+
+/// Config of the running sum decomposition.
+#[derive(Clone, Debug)]
+pub struct CrumbsConfig {
+    q_crumb: Selector,
+    q_end: Selector,
+    acc: Column<Advice>,
+}
+
+impl CrumbsConfig {
+    pub fn configure<F: Field>(meta: &mut ConstraintSystem<F>, acc: Column<Advice>) -> Self {
+        let q_crumb = meta.selector();
+        let q_end = meta.selector();
+
+        meta.enable_equality(acc);
+
+        meta.create_gate("crumb", |meta| {
+            let q_crumb = meta.query_selector(q_crumb);
+            let acc_cur = meta.query_advice(acc, Rotation::cur());
+            let acc_next = meta.query_advice(acc, Rotation::next());
+
+            let crumb = acc_cur - Expression::Constant(F::from(4)) * acc_next;
+            let range_check = (1..4).fold(crumb.clone(), |expr, i| {
+                expr * (Expression::Constant(F::from(i)) - crumb.clone())
+            });
+            vec![q_crumb * range_check]
+        });
+
+        meta.create_gate("crumbs end", |meta| {
+            let q_end = meta.query_selector(q_end);
+            vec![q_end * meta.query_advice(acc, Rotation::cur())]
+        });
+
+        Self { q_crumb, q_end, acc }
+    }
+
+    /// Constrains `value < 4^num_crumbs` by decomposing it into crumbs,
+    /// returning the running sum: `acc[i] = value >> 2i`, for `i` in
+    /// `[0, num_crumbs]`.
+    pub fn decompose<F: Field>(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: &AssignedCell<F, F>,
+        num_crumbs: usize,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let accs = value.value().map(|value| running_sum_values(value, 2, num_crumbs));
+        layouter.assign_region(
+            || format!("{num_crumbs} crumbs"),
+            |mut region| {
+                let mut cells = vec![value.copy_advice(|| "value", &mut region, self.acc, 0)?];
+
+                for i in 0..num_crumbs {
+                    self.q_crumb.enable(&mut region, i)?;
+                    let acc = accs.as_ref().map(|accs| accs[i + 1]);
+                    cells.push(region.assign_advice(|| format!("acc {}", i + 1), self.acc, i + 1, || acc)?);
+                }
+                self.q_end.enable(&mut region, num_crumbs)?;
+
+                Ok(cells)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+    };
+
+    use super::CrumbsConfig;
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::{Field, TestField as Fp},
+    };
+
+    #[derive(Default)]
+    struct TestCircuit<const CRUMBS: usize> {
+        value: Value<u64>,
+    }
+
+    impl<F: Field, const CRUMBS: usize> Circuit<F> for TestCircuit<CRUMBS> {
+        type Config = (CrumbsConfig, Column<Advice>);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let [value, acc] = [(); 2].map(|_| meta.advice_column());
+            meta.enable_equality(value);
+            (CrumbsConfig::configure(meta, acc), value)
+        }
+
+        fn synthesize(&self, (crumbs, value): Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let value = layouter.assign_region(
+                || "value",
+                |mut region| region.assign_advice(|| "value", value, 0, || self.value.map(F::from)),
+            )?;
+            let accs = crumbs.decompose(layouter.namespace(|| "decompose"), &value, CRUMBS)?;
+            assert_eq!(accs.len(), CRUMBS + 1);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn crumbs() {
+        expect_satisfied::<Fp, _>(&TestCircuit::<4> { value: Value::known(255) }, vec![]);
+        expect_satisfied::<Fp, _>(&TestCircuit::<32> { value: Value::known(u64::MAX) }, vec![]);
+
+        // 256 needs a fifth crumb: the running sum ends at 1.
+        expect_failure::<Fp, _>(
+            &TestCircuit::<4> { value: Value::known(256) },
+            vec![],
+            FailureMatcher::Constraint {
+                gate: "crumbs end",
+                location: Location::InRegion {
+                    region: "4 crumbs",
+                    offset: 4,
+                },
+            },
+        );
+    }
+}
@@ -0,0 +1,278 @@
+//! Integer division with remainder of values of at most `N_BYTES` bytes.
+//!
+//! The quotient `q = floor(a / d)` and remainder `r` are witnessed, and their
+//! defining properties are constrained:
+//!
+//! | a | d | q | r | q_div |
+//! | a | d | q | r | 1     |
+//!
+//! - `a = q ⋅ d + r`,
+//! - `q` and `r` are range checked to `N_BYTES` bytes with [`PackConfig`],
+//! - `r < d`, with an [`LtChip`] comparison pinned to 1.
+//!
+//! `d` must already be less than `2^(8 ⋅ N_BYTES)`, and dividing by zero
+//! makes the circuit unsatisfiable. `N_BYTES` is at most 15 so that
+//! `q ⋅ d + r` can't wrap around the modulus.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter},
+    plonk::{Advice, Column, ConstraintSystem, Error, Fixed, Selector},
+    poly::Rotation,
+};
+
+use super::lt::{LtChip, LtConfig};
+use crate::{circuits::pack::PackConfig, field::Field};
+
+/// Config for [`DivChip`].
+#[derive(Clone, Debug)]
+pub struct DivConfig<F: Field, const N_BYTES: usize> {
+    q_div: Selector,
+    a: Column<Advice>,
+    d: Column<Advice>,
+    q: Column<Advice>,
+    r: Column<Advice>,
+    lt: LtConfig<F, N_BYTES>,
+    pack: PackConfig,
+}
+
+/// Chip dividing integers with remainder.
+#[derive(Clone, Debug)]
+pub struct DivChip<F: Field, const N_BYTES: usize> {
+    config: DivConfig<F, N_BYTES>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field, const N_BYTES: usize> Chip<F> for DivChip<F, N_BYTES> {
+    type Config = DivConfig<F, N_BYTES>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field, const N_BYTES: usize> DivChip<F, N_BYTES> {
+    /// Configures the chip, comparing with `lt`, range checking with `pack`
+    /// and pinning the comparison with `constant`.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        lt: LtConfig<F, N_BYTES>,
+        pack: PackConfig,
+        constant: Column<Fixed>,
+    ) -> DivConfig<F, N_BYTES> {
+        assert!(N_BYTES <= 15);
+
+        let q_div = meta.selector();
+        let [a, d, q, r] = [(); 4].map(|_| meta.advice_column());
+
+        for column in [a, d, q, r] {
+            meta.enable_equality(column);
+        }
+        meta.enable_constant(constant);
+
+        meta.create_gate("div", |meta| {
+            let q_div = meta.query_selector(q_div);
+            let [a, d, q, r] = [a, d, q, r].map(|column| meta.query_advice(column, Rotation::cur()));
+            vec![q_div * (a - q * d - r)]
+        });
+
+        DivConfig {
+            q_div,
+            a,
+            d,
+            q,
+            r,
+            lt,
+            pack,
+        }
+    }
+
+    pub fn construct(config: DivConfig<F, N_BYTES>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns `floor(a / d)` and `a mod d`.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        d: &AssignedCell<F, F>,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), Error> {
+        let config = &self.config;
+        let lt = LtChip::construct(config.lt.clone());
+
+        let (d, q, r) = layouter.assign_region(
+            || "div",
+            |mut region| {
+                config.q_div.enable(&mut region, 0)?;
+                a.copy_advice(|| "a", &mut region, config.a, 0)?;
+                let d = d.copy_advice(|| "d", &mut region, config.d, 0)?;
+
+                // Inputs that don't fit in 128 bits, or a zero divisor, give
+                // a zero quotient and remainder, which fail verification.
+                let qr = a.value().zip(d.value()).map(|(a, d)| match (to_u128(a), to_u128(d)) {
+                    (Some(a), Some(d)) if d != 0 => (a / d, a % d),
+                    _ => (0, 0),
+                });
+                let q = region.assign_advice(|| "q", config.q, 0, || qr.map(|(q, _)| F::from_u128(q)))?;
+                let r = region.assign_advice(|| "r", config.r, 0, || qr.map(|(_, r)| F::from_u128(r)))?;
+                Ok((d, q, r))
+            },
+        )?;
+
+        config.pack.unpack(layouter.namespace(|| "q"), &q, N_BYTES)?;
+        config.pack.unpack(layouter.namespace(|| "r"), &r, N_BYTES)?;
+
+        let below = lt.assign(layouter.namespace(|| "r < d"), &r, &d)?;
+        layouter.assign_region(
+            || "div bounds",
+            |mut region| region.constrain_constant(below.lt.cell(), F::ONE),
+        )?;
+
+        Ok((q, r))
+    }
+}
+
+fn to_u128<F: Field>(value: &F) -> Option<u128> {
+    let repr = value.to_repr();
+    repr[16..]
+        .iter()
+        .all(|byte| *byte == 0)
+        .then(|| u128::from_le_bytes(repr[..16].try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+    use proptest::prelude::any;
+
+    use super::{DivChip, DivConfig};
+    use crate::{
+        circuits::{gadgets::lt::LtChip, pack::PackConfig, table::U8Table},
+        dev::{
+            fuzz::gadget_proptest,
+            mock::{expect_failure, expect_satisfied, is_satisfied, FailureMatcher, Location},
+        },
+        field::{Field, TestField as Fp},
+    };
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig<F: Field> {
+        div: DivConfig<F, 8>,
+        u8_table: U8Table,
+        input: Column<Advice>,
+        instance: Column<Instance>,
+    }
+
+    /// Exposes the quotient and remainder of 64-bit `a` and `d`.
+    #[derive(Default)]
+    struct TestCircuit {
+        a: Value<u64>,
+        d: Value<u64>,
+    }
+
+    impl TestCircuit {
+        fn new(a: u64, d: u64) -> Self {
+            Self {
+                a: Value::known(a),
+                d: Value::known(d),
+            }
+        }
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit {
+        type Config = TestCircuitConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let u8_table = U8Table::configure(meta);
+            let [input, byte, acc] = [(); 3].map(|_| meta.advice_column());
+            let constant = meta.fixed_column();
+            let pack = PackConfig::configure(meta, byte, acc, u8_table);
+            let lt = LtChip::configure(meta, pack.clone());
+            let div = DivChip::configure(meta, lt, pack, constant);
+            let instance = meta.instance_column();
+            meta.enable_equality(input);
+            meta.enable_equality(instance);
+
+            TestCircuitConfig {
+                div,
+                u8_table,
+                input,
+                instance,
+            }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let chip = DivChip::construct(config.div);
+            config.u8_table.load(&mut layouter)?;
+
+            let (a, d) = layouter.assign_region(
+                || "inputs",
+                |mut region| {
+                    let a = region.assign_advice(|| "a", config.input, 0, || self.a.map(F::from))?;
+                    let d = region.assign_advice(|| "d", config.input, 1, || self.d.map(F::from))?;
+                    Ok((a, d))
+                },
+            )?;
+            let (q, r) = chip.assign(layouter.namespace(|| "div"), &a, &d)?;
+            layouter.constrain_instance(q.cell(), config.instance, 0)?;
+            layouter.constrain_instance(r.cell(), config.instance, 1)
+        }
+    }
+
+    fn instances(a: u64, d: u64) -> Vec<Vec<Fp>> {
+        vec![vec![Fp::from(a / d), Fp::from(a % d)]]
+    }
+
+    #[test]
+    fn div() {
+        for (a, d) in [(100, 7), (0, 5), (42, 1), (41, 42), (u64::MAX, 3), (u64::MAX, u64::MAX)] {
+            expect_satisfied(&TestCircuit::new(a, d), instances(a, d));
+        }
+
+        // 100 = 13 ⋅ 7 + 9, but 9 is not a remainder of 7, and the witnessed
+        // 14 isn't copied to 13.
+        expect_failure(
+            &TestCircuit::new(100, 7),
+            vec![vec![Fp::from(13), Fp::from(9)]],
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+
+        // Dividing by zero.
+        assert!(!is_satisfied(&TestCircuit::new(100, 0), vec![vec![Fp::from(0), Fp::from(0)]]));
+    }
+
+    gadget_proptest! {
+        div_complete(a in any::<u64>(), d in 1..=u64::MAX) {
+            circuit: TestCircuit::new(a, d),
+            instances: instances(a, d),
+            valid: true,
+        }
+        div_sound(a in any::<u64>(), d in 1..=u64::MAX, delta in 1u64..4) {
+            circuit: TestCircuit::new(a, d),
+            instances: vec![vec![Fp::from((a / d) ^ delta), Fp::from(a % d)]],
+            valid: false,
+        }
+    }
+}
@@ -0,0 +1,206 @@
+//! Byte order conversion.
+//!
+//! A value is unpacked into its little-endian bytes with [`PackConfig`], and
+//! the same byte cells are copied in reverse order into a second packing:
+//!
+//! ```text
+//! value    = Σ bytes[i] ⋅ 256^i              (unpack bytes)
+//! reversed = Σ bytes[n - 1 - i] ⋅ 256^i      (pack bytes)
+//! ```
+//!
+//! Both sides are recomposed by the running sum gate of [`PackConfig`] and
+//! tied together by copy constraints, so nothing but the order changes.
+//! Words don't fit in a field element and are reversed through their halves:
+//! the reversed `lo` is the reversed `hi`, and conversely.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter},
+    plonk::Error,
+};
+
+use crate::{
+    circuits::{pack::PackConfig, word::Word},
+    field::Field,
+};
+
+/// Config for [`EndiannessChip`].
+#[derive(Clone, Debug)]
+pub struct EndiannessConfig {
+    pack: PackConfig,
+}
+
+/// Chip reversing the byte order of values and words.
+#[derive(Clone, Debug)]
+pub struct EndiannessChip<F: Field> {
+    config: EndiannessConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> Chip<F> for EndiannessChip<F> {
+    type Config = EndiannessConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field> EndiannessChip<F> {
+    /// Configures the chip, unpacking and packing bytes with `pack`.
+    pub fn configure(pack: PackConfig) -> EndiannessConfig {
+        EndiannessConfig { pack }
+    }
+
+    pub fn construct(config: EndiannessConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Reverses the byte order of the `num_bytes` bytes of `value`, failing if
+    /// it does not fit.
+    pub fn reverse(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: &AssignedCell<F, F>,
+        num_bytes: usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let pack = &self.config.pack;
+        let mut bytes = pack.unpack(layouter.namespace(|| "bytes"), value, num_bytes)?;
+        bytes.reverse();
+        pack.pack(layouter.namespace(|| "reversed"), &bytes)
+    }
+
+    /// Reverses the byte order of a word given as 128-bit halves.
+    pub fn reverse_word(
+        &self,
+        mut layouter: impl Layouter<F>,
+        word: &Word<AssignedCell<F, F>>,
+    ) -> Result<Word<AssignedCell<F, F>>, Error> {
+        let lo = self.reverse(layouter.namespace(|| "hi"), &word.hi(), 16)?;
+        let hi = self.reverse(layouter.namespace(|| "lo"), &word.lo(), 16)?;
+        Ok(Word::new([lo, hi]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+
+    use super::{EndiannessChip, EndiannessConfig};
+    use crate::{
+        circuits::{
+            pack::PackConfig,
+            table::U8Table,
+            word::{field_from_le_bytes, Word},
+        },
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::{Field, TestField as Fp},
+    };
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig {
+        endianness: EndiannessConfig,
+        u8_table: U8Table,
+        input: Column<Advice>,
+        instance: Column<Instance>,
+    }
+
+    /// Exposes the reversed 3-byte `value`, then the reversed `word`.
+    #[derive(Default)]
+    struct TestCircuit {
+        value: Value<[u8; 3]>,
+        word: Value<[u8; 32]>,
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit {
+        type Config = TestCircuitConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let u8_table = U8Table::configure(meta);
+            let [input, byte, acc] = [(); 3].map(|_| meta.advice_column());
+            let pack = PackConfig::configure(meta, byte, acc, u8_table);
+            let endianness = EndiannessChip::configure(pack);
+            let instance = meta.instance_column();
+            meta.enable_equality(input);
+            meta.enable_equality(instance);
+
+            TestCircuitConfig {
+                endianness,
+                u8_table,
+                input,
+                instance,
+            }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let chip = EndiannessChip::construct(config.endianness);
+            config.u8_table.load(&mut layouter)?;
+
+            let (value, word) = layouter.assign_region(
+                || "inputs",
+                |mut region| {
+                    let value = self.value.map(|value| field_from_le_bytes::<F>(&value));
+                    let value = region.assign_advice(|| "value", config.input, 0, || value)?;
+                    let word = self.word.map(Word::<F>::from_le_bytes);
+                    let lo = region.assign_advice(|| "lo", config.input, 1, || word.map(|word| word.lo()))?;
+                    let hi = region.assign_advice(|| "hi", config.input, 2, || word.map(|word| word.hi()))?;
+                    Ok((value, Word::new([lo, hi])))
+                },
+            )?;
+
+            let value = chip.reverse(layouter.namespace(|| "value"), &value, 3)?;
+            let word = chip.reverse_word(layouter.namespace(|| "word"), &word)?;
+
+            layouter.constrain_instance(value.cell(), config.instance, 0)?;
+            layouter.constrain_instance(word.lo().cell(), config.instance, 1)?;
+            layouter.constrain_instance(word.hi().cell(), config.instance, 2)
+        }
+    }
+
+    fn instances(mut value: [u8; 3], mut word: [u8; 32]) -> Vec<Vec<Fp>> {
+        value.reverse();
+        word.reverse();
+        let word = Word::<Fp>::from_le_bytes(word);
+        vec![vec![field_from_le_bytes(&value), word.lo(), word.hi()]]
+    }
+
+    #[test]
+    fn reverse() {
+        let value = [1, 2, 3];
+        let word: [u8; 32] = std::array::from_fn(|i| (i * 29 + 7) as u8);
+        let circuit = TestCircuit {
+            value: Value::known(value),
+            word: Value::known(word),
+        };
+        expect_satisfied(&circuit, instances(value, word));
+
+        // The value left in little-endian order.
+        let mut wrong = instances(value, word);
+        wrong[0][0] = field_from_le_bytes(&value);
+        expect_failure(
+            &circuit,
+            wrong,
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+    }
+}
@@ -0,0 +1,640 @@
+//! Arithmetic written as straight-line code, in the style of halo2-lib.
+//!
+//! [`GateChip`] has a single advice column and a single "vertical" gate over
+//! four consecutive rows:
+//!
+//! | value | q_gate |
+//! | a     | 1      |
+//! | b     | 0      |
+//! | c     | 0      |
+//! | d     | 0      |
+//!
+//! constraining `a + b ⋅ c = d`. Additions, multiplications and their
+//! combinations are all this gate with some of its cells fixed to constants,
+//! and a gate may start on the last cell of the previous one, so an inner
+//! product of `n` terms takes `3n + 1` rows.
+//!
+//! Instead of laying out regions, a circuit records its computation in a
+//! [`Context`], which returns a [`ContextCell`] for each result and places
+//! the cells, the gates and the copies between them in a virtual column.
+//! [`GateChip::assign`] then flushes the context into a region of the chip,
+//! returning the assigned cells by [`ContextCell`]. Operands are
+//! [`Operand`]s: cells of the context, which are copied into the gate,
+//! constants, or new witnesses.
+//!
+//! [`AssignedValue`] wraps a [`ContextCell`] together with its context, so
+//! that `x * x * x + x + F::from(5)` records the gates of the polynomial in
+//! the context. Constants can only be on the right of an operator.
+
+use std::{
+    cell::RefCell,
+    marker::PhantomData,
+    ops::{Add, Index, Mul, Sub},
+    rc::Rc,
+};
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Fixed, Selector},
+    poly::Rotation,
+};
+
+use crate::field::Field;
+
+/// Config for [`GateChip`].
+#[derive(Clone, Debug)]
+pub struct GateConfig {
+    q_gate: Selector,
+    value: Column<Advice>,
+}
+
+/// Chip assigning [`Context`]s, see the [module documentation](self).
+#[derive(Clone, Debug)]
+pub struct GateChip<F: Field> {
+    config: GateConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> Chip<F> for GateChip<F> {
+    type Config = GateConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field> GateChip<F> {
+    /// Configures the gate, placing the constants of contexts in `constant`.
+    pub fn configure(meta: &mut ConstraintSystem<F>, constant: Column<Fixed>) -> GateConfig {
+        let q_gate = meta.selector();
+        let value = meta.advice_column();
+        meta.enable_equality(value);
+        meta.enable_constant(constant);
+
+        meta.create_gate("a + b * c = d", |meta| {
+            let q_gate = meta.query_selector(q_gate);
+            let [a, b, c, d] = [0, 1, 2, 3].map(|row| meta.query_advice(value, Rotation(row)));
+
+            vec![q_gate * (a + b * c - d)]
+        });
+
+        GateConfig { q_gate, value }
+    }
+
+    pub fn construct(config: GateConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Assigns the cells of `ctx` in a region, enabling its gates and
+    /// constraining its copies and constants.
+    pub fn assign(&self, mut layouter: impl Layouter<F>, ctx: &Context<F>) -> Result<AssignedContext<F>, Error> {
+        let config = &self.config;
+
+        layouter.assign_region(
+            || "context",
+            |mut region| {
+                for &offset in &ctx.gates {
+                    config.q_gate.enable(&mut region, offset)?;
+                }
+                let cells = (ctx.cells.iter().enumerate())
+                    .map(|(offset, cell)| match cell {
+                        CellValue::Witness(value) => {
+                            region.assign_advice(|| "witness", config.value, offset, || *value)
+                        }
+                        CellValue::Constant(constant) => {
+                            region.assign_advice_from_constant(|| "constant", config.value, offset, *constant)
+                        }
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                for &(a, b) in &ctx.copies {
+                    region.constrain_equal(cells[a].cell(), cells[b].cell())?;
+                }
+
+                Ok(AssignedContext { cells })
+            },
+        )
+    }
+}
+
+/// A cell of a [`Context`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ContextCell(usize);
+
+impl ContextCell {
+    /// Row of the cell in the region of its context.
+    pub fn offset(&self) -> usize {
+        self.0
+    }
+}
+
+/// An input of an operation of a [`Context`].
+#[derive(Clone, Copy, Debug)]
+pub enum Operand<F> {
+    /// A cell of the context, copied into the gate.
+    Cell(ContextCell),
+    /// A constant, fixed in the gate.
+    Constant(F),
+    /// A new witness, only constrained by the gate.
+    Witness(Value<F>),
+}
+
+impl<F> From<ContextCell> for Operand<F> {
+    fn from(cell: ContextCell) -> Self {
+        Operand::Cell(cell)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum CellValue<F> {
+    Witness(Value<F>),
+    Constant(F),
+}
+
+/// Computation recorded for a [`GateChip`], see the
+/// [module documentation](self).
+#[derive(Clone, Debug)]
+pub struct Context<F: Field> {
+    cells: Vec<CellValue<F>>,
+    /// Offsets of the first cell of each gate.
+    gates: Vec<usize>,
+    /// Pairs of cells constrained to be equal.
+    copies: Vec<(usize, usize)>,
+}
+
+impl<F: Field> Default for Context<F> {
+    fn default() -> Self {
+        Self {
+            cells: vec![],
+            gates: vec![],
+            copies: vec![],
+        }
+    }
+}
+
+impl<F: Field> Context<F> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rows taken by the context once assigned.
+    pub fn num_rows(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// The value of `operand`.
+    pub fn value(&self, operand: impl Into<Operand<F>>) -> Value<F> {
+        match operand.into() {
+            Operand::Cell(cell) => match self.cells[cell.0] {
+                CellValue::Witness(value) => value,
+                CellValue::Constant(constant) => Value::known(constant),
+            },
+            Operand::Constant(constant) => Value::known(constant),
+            Operand::Witness(value) => value,
+        }
+    }
+
+    /// A cell holding `value`, free of constraints.
+    pub fn load_witness(&mut self, value: Value<F>) -> ContextCell {
+        self.place(Operand::Witness(value))
+    }
+
+    /// A cell holding `constant`.
+    pub fn load_constant(&mut self, constant: F) -> ContextCell {
+        self.place(Operand::Constant(constant))
+    }
+
+    /// `a + b`.
+    pub fn add(&mut self, a: impl Into<Operand<F>>, b: impl Into<Operand<F>>) -> ContextCell {
+        let (a, b) = (a.into(), b.into());
+        let d = self.value(a) + self.value(b);
+        self.gate([a, b, Operand::Constant(F::ONE), Operand::Witness(d)])[3]
+    }
+
+    /// `a - b`, as `(a - b) + b ⋅ 1 = a`.
+    pub fn sub(&mut self, a: impl Into<Operand<F>>, b: impl Into<Operand<F>>) -> ContextCell {
+        let (a, b) = (a.into(), b.into());
+        let diff = self.value(a) - self.value(b);
+        self.gate([Operand::Witness(diff), b, Operand::Constant(F::ONE), a])[0]
+    }
+
+    /// `a ⋅ b`.
+    pub fn mul(&mut self, a: impl Into<Operand<F>>, b: impl Into<Operand<F>>) -> ContextCell {
+        let (a, b) = (a.into(), b.into());
+        let d = self.value(a) * self.value(b);
+        self.gate([Operand::Constant(F::ZERO), a, b, Operand::Witness(d)])[3]
+    }
+
+    /// `a ⋅ b + c`, in a single gate.
+    pub fn mul_add(
+        &mut self,
+        a: impl Into<Operand<F>>,
+        b: impl Into<Operand<F>>,
+        c: impl Into<Operand<F>>,
+    ) -> ContextCell {
+        let (a, b, c) = (a.into(), b.into(), c.into());
+        let d = self.value(c) + self.value(a) * self.value(b);
+        self.gate([c, a, b, Operand::Witness(d)])[3]
+    }
+
+    /// `sel ? a : b`, as `sel ⋅ (a - b) + b`. `sel` is assumed to be
+    /// boolean, see [`Self::assert_bit`].
+    pub fn select(
+        &mut self,
+        a: impl Into<Operand<F>>,
+        b: impl Into<Operand<F>>,
+        sel: impl Into<Operand<F>>,
+    ) -> ContextCell {
+        let b = self.place(b.into());
+        let diff = self.sub(a, b);
+        self.mul_add(sel, diff, b)
+    }
+
+    /// `Σ a[i] ⋅ b[i]`, each term adding a gate on top of the running sum.
+    pub fn inner_product<A, B>(&mut self, a: impl IntoIterator<Item = A>, b: impl IntoIterator<Item = B>) -> ContextCell
+    where
+        A: Into<Operand<F>>,
+        B: Into<Operand<F>>,
+    {
+        let mut acc = self.load_constant(F::ZERO);
+        for (a, b) in a.into_iter().zip(b) {
+            let (a, b) = (a.into(), b.into());
+            let sum = self.value(acc) + self.value(a) * self.value(b);
+            // The gate starts on the running sum instead of a copy of it.
+            self.gates.push(acc.0);
+            self.place(a);
+            self.place(b);
+            acc = self.place(Operand::Witness(sum));
+        }
+        acc
+    }
+
+    /// Constrains `a` to be 0 or 1, as `0 + a ⋅ a = a`.
+    pub fn assert_bit(&mut self, a: impl Into<Operand<F>>) {
+        let a = self.place(a.into());
+        self.gate([Operand::Constant(F::ZERO), a.into(), a.into(), a.into()]);
+    }
+
+    /// Constrains `a` and `b` to be equal.
+    pub fn assert_equal(&mut self, a: ContextCell, b: ContextCell) {
+        self.copies.push((a.0, b.0));
+    }
+
+    /// Places `operands` in a new gate.
+    fn gate(&mut self, operands: [Operand<F>; 4]) -> [ContextCell; 4] {
+        self.gates.push(self.cells.len());
+        operands.map(|operand| self.place(operand))
+    }
+
+    /// Places `operand` in a new cell, copying the cells of the context.
+    fn place(&mut self, operand: Operand<F>) -> ContextCell {
+        let cell = ContextCell(self.cells.len());
+        let value = match operand {
+            Operand::Cell(copied) => {
+                self.copies.push((copied.0, cell.0));
+                self.cells[copied.0]
+            }
+            Operand::Constant(constant) => CellValue::Constant(constant),
+            Operand::Witness(value) => CellValue::Witness(value),
+        };
+        self.cells.push(value);
+        cell
+    }
+}
+
+/// The cells of a [`Context`] assigned by [`GateChip::assign`], indexed by
+/// [`ContextCell`].
+#[derive(Clone, Debug)]
+pub struct AssignedContext<F: Field> {
+    cells: Vec<AssignedCell<F, F>>,
+}
+
+impl<F: Field> Index<ContextCell> for AssignedContext<F> {
+    type Output = AssignedCell<F, F>;
+
+    fn index(&self, cell: ContextCell) -> &Self::Output {
+        &self.cells[cell.0]
+    }
+}
+
+/// A [`Context`] shared by the [`AssignedValue`]s recording into it.
+pub type SharedContext<F> = Rc<RefCell<Context<F>>>;
+
+/// A cell of a [`SharedContext`], whose `+`, `-` and `*` record gates in the
+/// context, see the [module documentation](self).
+#[derive(Clone, Debug)]
+pub struct AssignedValue<F: Field> {
+    ctx: SharedContext<F>,
+    cell: ContextCell,
+}
+
+impl<F: Field> AssignedValue<F> {
+    pub fn new(ctx: &SharedContext<F>, cell: ContextCell) -> Self {
+        Self { ctx: ctx.clone(), cell }
+    }
+
+    /// A new witness holding `value`.
+    pub fn witness(ctx: &SharedContext<F>, value: Value<F>) -> Self {
+        let cell = ctx.borrow_mut().load_witness(value);
+        Self::new(ctx, cell)
+    }
+
+    /// A new cell holding `constant`.
+    pub fn constant(ctx: &SharedContext<F>, constant: F) -> Self {
+        let cell = ctx.borrow_mut().load_constant(constant);
+        Self::new(ctx, cell)
+    }
+
+    pub fn cell(&self) -> ContextCell {
+        self.cell
+    }
+
+    pub fn value(&self) -> Value<F> {
+        self.ctx.borrow().value(self.cell)
+    }
+
+    /// `sel ? self : other`, see [`Context::select`].
+    pub fn select(&self, other: &Self, sel: &Self) -> Self {
+        let (other, sel) = (self.operand(other), self.operand(sel));
+        let cell = self.ctx.borrow_mut().select(self.cell, other, sel);
+        Self::new(&self.ctx, cell)
+    }
+
+    /// Constrains `self` to be 0 or 1.
+    pub fn assert_bit(&self) {
+        self.ctx.borrow_mut().assert_bit(self.cell);
+    }
+
+    /// Constrains `self` and `other` to be equal.
+    pub fn assert_equal(&self, other: &Self) {
+        let other = self.operand(other);
+        self.ctx.borrow_mut().assert_equal(self.cell, other);
+    }
+
+    /// The cell of `other`, which must be in the same context.
+    fn operand(&self, other: &Self) -> ContextCell {
+        assert!(Rc::ptr_eq(&self.ctx, &other.ctx), "values of different contexts");
+        other.cell
+    }
+
+    fn record(
+        &self,
+        rhs: Operand<F>,
+        op: impl FnOnce(&mut Context<F>, ContextCell, Operand<F>) -> ContextCell,
+    ) -> Self {
+        let cell = op(&mut self.ctx.borrow_mut(), self.cell, rhs);
+        Self::new(&self.ctx, cell)
+    }
+}
+
+/// Implements `$trait` for owned and borrowed [`AssignedValue`]s, against
+/// other values and constants, with the operation of [`Context`] of the same
+/// name.
+macro_rules! impl_op {
+    ($trait:ident, $method:ident) => {
+        impl<F: Field> $trait<&AssignedValue<F>> for &AssignedValue<F> {
+            type Output = AssignedValue<F>;
+
+            fn $method(self, rhs: &AssignedValue<F>) -> AssignedValue<F> {
+                let rhs = self.operand(rhs);
+                self.record(rhs.into(), |ctx, a, b| ctx.$method(a, b))
+            }
+        }
+
+        impl<F: Field> $trait<F> for &AssignedValue<F> {
+            type Output = AssignedValue<F>;
+
+            fn $method(self, rhs: F) -> AssignedValue<F> {
+                self.record(Operand::Constant(rhs), |ctx, a, b| ctx.$method(a, b))
+            }
+        }
+
+        impl<F: Field> $trait<AssignedValue<F>> for &AssignedValue<F> {
+            type Output = AssignedValue<F>;
+
+            fn $method(self, rhs: AssignedValue<F>) -> AssignedValue<F> {
+                self.$method(&rhs)
+            }
+        }
+
+        impl<F: Field> $trait<&AssignedValue<F>> for AssignedValue<F> {
+            type Output = AssignedValue<F>;
+
+            fn $method(self, rhs: &AssignedValue<F>) -> AssignedValue<F> {
+                (&self).$method(rhs)
+            }
+        }
+
+        impl<F: Field> $trait<AssignedValue<F>> for AssignedValue<F> {
+            type Output = AssignedValue<F>;
+
+            fn $method(self, rhs: AssignedValue<F>) -> AssignedValue<F> {
+                (&self).$method(&rhs)
+            }
+        }
+
+        impl<F: Field> $trait<F> for AssignedValue<F> {
+            type Output = AssignedValue<F>;
+
+            fn $method(self, rhs: F) -> AssignedValue<F> {
+                (&self).$method(rhs)
+            }
+        }
+    };
+}
+
+impl_op!(Add, add);
+impl_op!(Sub, sub);
+impl_op!(Mul, mul);
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+
+    use super::{AssignedValue, Context, GateChip, GateConfig, Operand, SharedContext};
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::{Field, TestField as Fp},
+    };
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig {
+        gate: GateConfig,
+        instance: Column<Instance>,
+    }
+
+    /// Exposes `sel ? x ⋅ y + 7 : x - y` and `Σ xs[i] ⋅ (i + 1)`.
+    #[derive(Default)]
+    struct TestCircuit {
+        x: Value<u64>,
+        y: Value<u64>,
+        sel: Value<u64>,
+        xs: [Value<u64>; 3],
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit {
+        type Config = TestCircuitConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let constant = meta.fixed_column();
+            let gate = GateChip::configure(meta, constant);
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            TestCircuitConfig { gate, instance }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let mut ctx = Context::new();
+            let [x, y, sel] = [self.x, self.y, self.sel].map(|value| ctx.load_witness(value.map(F::from)));
+            ctx.assert_bit(sel);
+            let product = ctx.mul_add(x, y, Operand::Constant(F::from(7)));
+            let diff = ctx.sub(x, y);
+            let out = ctx.select(product, diff, sel);
+
+            let xs = self.xs.map(|x| ctx.load_witness(x.map(F::from)));
+            let weights = [1, 2, 3].map(|weight| Operand::Constant(F::from(weight)));
+            let sum = ctx.inner_product(xs, weights);
+
+            let cells = GateChip::construct(config.gate).assign(layouter.namespace(|| "context"), &ctx)?;
+            layouter.constrain_instance(cells[out].cell(), config.instance, 0)?;
+            layouter.constrain_instance(cells[sum].cell(), config.instance, 1)
+        }
+    }
+
+    fn circuit(sel: u64) -> TestCircuit {
+        TestCircuit {
+            x: Value::known(5),
+            y: Value::known(3),
+            sel: Value::known(sel),
+            xs: [10, 20, 30].map(Value::known),
+        }
+    }
+
+    #[test]
+    fn context() {
+        let sum = Fp::from(10 + 2 * 20 + 3 * 30);
+        expect_satisfied(&circuit(1), vec![vec![Fp::from(5 * 3 + 7), sum]]);
+        expect_satisfied(&circuit(0), vec![vec![Fp::from(5 - 3), sum]]);
+
+        expect_failure(
+            &circuit(1),
+            vec![vec![Fp::from(5 - 3), sum]],
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+
+        // A selector of 2 is not a bit, which the first gate catches.
+        expect_failure(
+            &circuit(2),
+            vec![vec![Fp::from(2 * (5 * 3 + 7 - (5 - 3)) + 5 - 3), sum]],
+            FailureMatcher::Constraint {
+                gate: "a + b * c = d",
+                location: Location::InRegion {
+                    region: "context",
+                    offset: 4,
+                },
+            },
+        );
+    }
+
+    #[test]
+    fn rows() {
+        let mut ctx = Context::<Fp>::new();
+        let xs: Vec<_> = (0..4).map(|i| ctx.load_witness(Value::known(Fp::from(i)))).collect();
+        assert_eq!(ctx.num_rows(), 4);
+
+        let sum = ctx.inner_product(xs.clone(), xs);
+        assert_eq!(ctx.num_rows(), 4 + 3 * 4 + 1);
+        ctx.value(sum).assert_if_known(|sum| *sum == Fp::from(1 + 4 + 9));
+
+        let sum = ctx.add(sum, Operand::Constant(Fp::from(1)));
+        assert_eq!(ctx.num_rows(), 4 + 3 * 4 + 1 + 4);
+        ctx.value(sum).assert_if_known(|sum| *sum == Fp::from(15));
+    }
+
+    /// Exposes `x³ + x + 5`, written with [`AssignedValue`]s.
+    #[derive(Default)]
+    struct CubicCircuit {
+        x: Value<u64>,
+    }
+
+    impl<F: Field> Circuit<F> for CubicCircuit {
+        type Config = TestCircuitConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            <TestCircuit as Circuit<F>>::configure(meta)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let ctx: SharedContext<F> = Rc::default();
+            let x = AssignedValue::witness(&ctx, self.x.map(F::from));
+            let out = &x * &x * &x + &x + F::from(5);
+
+            let cells = GateChip::construct(config.gate).assign(layouter.namespace(|| "context"), &ctx.borrow())?;
+            layouter.constrain_instance(cells[out.cell()].cell(), config.instance, 0)
+        }
+    }
+
+    #[test]
+    fn assigned_value() {
+        let circuit = CubicCircuit { x: Value::known(3) };
+        expect_satisfied(&circuit, vec![vec![Fp::from(35)]]);
+        expect_failure(
+            &circuit,
+            vec![vec![Fp::from(36)]],
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+    }
+
+    #[test]
+    fn operators() {
+        let ctx = Rc::new(RefCell::new(Context::new()));
+        let x = AssignedValue::witness(&ctx, Value::known(Fp::from(7)));
+        let y = AssignedValue::constant(&ctx, Fp::from(2));
+
+        (&x - &y).value().assert_if_known(|value| *value == Fp::from(5));
+        (x.clone() * Fp::from(3)).value().assert_if_known(|value| *value == Fp::from(21));
+        (y.clone() - x.clone()).value().assert_if_known(|value| *value == -Fp::from(5));
+
+        let bit = AssignedValue::constant(&ctx, Fp::from(1));
+        bit.assert_bit();
+        x.select(&y, &bit).value().assert_if_known(|value| *value == Fp::from(7));
+    }
+
+    #[test]
+    #[should_panic(expected = "values of different contexts")]
+    fn different_contexts() {
+        let [a, b] = [(); 2].map(|_| AssignedValue::constant(&Rc::new(RefCell::new(Context::new())), Fp::from(1)));
+        let _ = a + b;
+    }
+}
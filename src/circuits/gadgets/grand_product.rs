@@ -0,0 +1,270 @@
+//! Grand-product argument that the rows of two sets of `W` columns are a
+//! permutation of each other.
+//!
+//! This is the textbook argument behind halo2's own copy constraints, spelled
+//! out with challenges. Each tuple is compressed with `α` into
+//! `c(t) = Σ t[j] ⋅ α^j`, and a running product `z` in a second phase column
+//! accumulates `z_(i+1) ⋅ (c(b_i) + γ) = z_i ⋅ (c(a_i) + γ)`:
+//!
+//! | a[0..W] | b[0..W] | z (second phase) | q_first | q_step | q_last |
+//! | a_0     | b_0     | z_0 = 1          | 1       | 1      | 0      |
+//! | a_1     | b_1     | z_1              | 0       | 1      | 0      |
+//! | ...     | ...     | ...              | 0       | 1      | 0      |
+//! |         |         | z_n = 1          | 0       | 0      | 1      |
+//!
+//! As `z` starts and ends at 1, `Π (c(a_i) + γ) = Π (c(b_i) + γ)`. Both sides
+//! are polynomials in `γ` with roots `-c(a_i)` and `-c(b_i)`, so this holds
+//! for random `α`, `γ` only if the multisets of tuples are equal. It is an
+//! alternative to `meta.shuffle`, which the halo2 version used by this crate
+//! does not have, and unlike the `lookup_any` of the memory example it
+//! accounts for repeated tuples.
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{
+        Advice, Challenge, Column, ConstraintSystem, Error, Expression, FirstPhase, SecondPhase, Selector,
+        VirtualCells,
+    },
+    poly::Rotation,
+};
+
+use crate::field::Field;
+
+/// Config of the grand-product argument over tuples of `W` cells.
+#[derive(Clone, Debug)]
+pub struct GrandProductConfig<const W: usize> {
+    q_first: Selector,
+    q_step: Selector,
+    q_last: Selector,
+    a: [Column<Advice>; W],
+    b: [Column<Advice>; W],
+    z: Column<Advice>,
+    alpha: Challenge,
+    gamma: Challenge,
+}
+
+/// `Σ tuple[j] ⋅ α^j + γ`.
+fn compress<F: Field, const W: usize>(
+    meta: &mut VirtualCells<'_, F>,
+    columns: [Column<Advice>; W],
+    alpha: &Expression<F>,
+    gamma: &Expression<F>,
+) -> Expression<F> {
+    (columns.into_iter().rev())
+        .fold(Expression::Constant(F::ZERO), |acc, column| {
+            acc * alpha.clone() + meta.query_advice(column, Rotation::cur())
+        })
+        + gamma.clone()
+}
+
+fn compress_value<F: Field, const W: usize>(
+    tuple: &[AssignedCell<F, F>; W],
+    alpha: Value<F>,
+    gamma: Value<F>,
+) -> Value<F> {
+    (tuple.iter().rev()).fold(Value::known(F::ZERO), |acc, cell| acc * alpha + cell.value().copied()) + gamma
+}
+
+impl<const W: usize> GrandProductConfig<W> {
+    /// Configures the argument between the tuples of the `a` and `b` columns,
+    /// which must be first phase columns.
+    pub fn configure<F: Field>(
+        meta: &mut ConstraintSystem<F>,
+        a: [Column<Advice>; W],
+        b: [Column<Advice>; W],
+    ) -> Self {
+        let q_first = meta.selector();
+        let q_step = meta.selector();
+        let q_last = meta.selector();
+        let z = meta.advice_column_in(SecondPhase);
+        let alpha = meta.challenge_usable_after(FirstPhase);
+        let gamma = meta.challenge_usable_after(FirstPhase);
+
+        for column in a.into_iter().chain(b) {
+            meta.enable_equality(column);
+        }
+
+        meta.create_gate("grand product start", |meta| {
+            let q_first = meta.query_selector(q_first);
+            let z = meta.query_advice(z, Rotation::cur());
+            vec![q_first * (z - Expression::Constant(F::ONE))]
+        });
+
+        meta.create_gate("grand product step", |meta| {
+            let q_step = meta.query_selector(q_step);
+            let z_cur = meta.query_advice(z, Rotation::cur());
+            let z_next = meta.query_advice(z, Rotation::next());
+            let alpha = meta.query_challenge(alpha);
+            let gamma = meta.query_challenge(gamma);
+
+            let a = compress(meta, a, &alpha, &gamma);
+            let b = compress(meta, b, &alpha, &gamma);
+            vec![q_step * (z_next * b - z_cur * a)]
+        });
+
+        meta.create_gate("grand product end", |meta| {
+            let q_last = meta.query_selector(q_last);
+            let z = meta.query_advice(z, Rotation::cur());
+            vec![q_last * (z - Expression::Constant(F::ONE))]
+        });
+
+        Self {
+            q_first,
+            q_step,
+            q_last,
+            a,
+            b,
+            z,
+            alpha,
+            gamma,
+        }
+    }
+
+    /// Constrains the tuples `b` to be a permutation of the tuples `a`, using
+    /// `a.len() + 1` rows, and returns the copies of `b` in the region.
+    pub fn assign<F: Field>(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &[[AssignedCell<F, F>; W]],
+        b: &[[AssignedCell<F, F>; W]],
+    ) -> Result<Vec<[AssignedCell<F, F>; W]>, Error> {
+        assert_eq!(a.len(), b.len());
+        let alpha = layouter.get_challenge(self.alpha);
+        let gamma = layouter.get_challenge(self.gamma);
+
+        layouter.assign_region(
+            || "grand product",
+            |mut region| {
+                self.q_first.enable(&mut region, 0)?;
+
+                let mut z = Value::known(F::ONE);
+                region.assign_advice(|| "z[0]", self.z, 0, || z)?;
+                let mut copies = Vec::with_capacity(b.len());
+                for (i, (a, b)) in a.iter().zip(b).enumerate() {
+                    self.q_step.enable(&mut region, i)?;
+                    for (cell, column) in a.iter().zip(self.a) {
+                        cell.copy_advice(|| "a", &mut region, column, i)?;
+                    }
+                    let copy = (b.iter().zip(self.b))
+                        .map(|(cell, column)| cell.copy_advice(|| "b", &mut region, column, i))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    copies.push(copy.try_into().unwrap());
+
+                    let ratio = compress_value(a, alpha, gamma)
+                        .zip(compress_value(b, alpha, gamma))
+                        .map(|(a, b)| a * b.invert().unwrap_or(F::ZERO));
+                    z = z * ratio;
+                    region.assign_advice(|| format!("z[{}]", i + 1), self.z, i + 1, || z)?;
+                }
+                self.q_last.enable(&mut region, a.len())?;
+
+                Ok(copies)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+    };
+
+    use super::GrandProductConfig;
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::{Field, TestField as Fp},
+    };
+
+    /// Assigns the `(key, value)` tuples `a` and `b` and argues that they are
+    /// permutations of each other.
+    #[derive(Default)]
+    struct TestCircuit {
+        a: Vec<[u64; 2]>,
+        b: Vec<[u64; 2]>,
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit {
+        type Config = (GrandProductConfig<2>, Column<Advice>);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let advice = meta.advice_column();
+            let a = [(); 2].map(|_| meta.advice_column());
+            let b = [(); 2].map(|_| meta.advice_column());
+            meta.enable_equality(advice);
+            (GrandProductConfig::configure(meta, a, b), advice)
+        }
+
+        fn synthesize(&self, (config, advice): Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let tuples = layouter.assign_region(
+                || "tuples",
+                |mut region| {
+                    let mut cells: Vec<[AssignedCell<F, F>; 2]> = vec![];
+                    for (i, [key, value]) in self.a.iter().chain(&self.b).enumerate() {
+                        cells.push([
+                            region.assign_advice(|| "key", advice, 2 * i, || Value::known(F::from(*key)))?,
+                            region.assign_advice(|| "value", advice, 2 * i + 1, || Value::known(F::from(*value)))?,
+                        ]);
+                    }
+                    Ok(cells)
+                },
+            )?;
+
+            let (a, b) = tuples.split_at(self.a.len());
+            config.assign(layouter.namespace(|| "permutation"), a, b)?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn grand_product() {
+        let a = vec![[1, 10], [2, 20], [2, 20], [3, 30]];
+        let circuit = TestCircuit {
+            a: a.clone(),
+            b: vec![[2, 20], [3, 30], [1, 10], [2, 20]],
+        };
+        expect_satisfied::<Fp, _>(&circuit, vec![]);
+
+        // Same keys and values, but not the same tuples.
+        let circuit = TestCircuit {
+            a: a.clone(),
+            b: vec![[2, 20], [3, 20], [1, 10], [2, 30]],
+        };
+        expect_failure::<Fp, _>(
+            &circuit,
+            vec![],
+            FailureMatcher::Constraint {
+                gate: "grand product end",
+                location: Location::InRegion {
+                    region: "grand product",
+                    offset: 4,
+                },
+            },
+        );
+
+        // Same set of tuples with different multiplicities.
+        let circuit = TestCircuit {
+            a,
+            b: vec![[1, 10], [2, 20], [3, 30], [3, 30]],
+        };
+        expect_failure::<Fp, _>(
+            &circuit,
+            vec![],
+            FailureMatcher::Constraint {
+                gate: "grand product end",
+                location: Location::InRegion {
+                    region: "grand product",
+                    offset: 4,
+                },
+            },
+        );
+    }
+}
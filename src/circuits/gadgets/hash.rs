@@ -0,0 +1,224 @@
+//! A common interface to the sponge hash chips, so that gadgets and examples
+//! can be generic over the hash function.
+//!
+//! [`PoseidonChip`](super::poseidon::PoseidonChip) and
+//! [`RescueChip`](super::rescue::RescueChip) share their sponge: a width 3
+//! state whose first element is the capacity, absorbing two inputs per
+//! permutation, so they also share the columns [`HashInstructions::configure`]
+//! takes. [`MimcChip`](super::mimc::MimcChip) absorbs one input per
+//! permutation into a two-element Feistel state, on the first two state columns
+//! and the first input column. Each chip comes with the native [`HashSpec`] it
+//! is checked against.
+//!
+//! [`Sponge`] absorbs cells over several calls and squeezes one element at a
+//! time. Each squeeze hashes the previous output with the cells absorbed since:
+//!
+//! ```text
+//! out_0 = H(in_0, ..., in_n)
+//! out_1 = H(out_0, in_n+1, ..., in_m)
+//! ```
+//!
+//! [`TranscriptChip`](super::transcript::TranscriptChip) is this sponge over
+//! Poseidon, and [`NativeSponge`] that of its native transcript.
+
+use std::fmt::Debug;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, ConstraintSystem, Error, Fixed},
+};
+
+use crate::field::Field;
+
+/// Native evaluation of a hash.
+pub trait HashSpec<F: Field>: Clone + Debug + Default {
+    /// The hash of `inputs`.
+    fn hash(&self, inputs: &[F]) -> F;
+}
+
+/// Instructions of a chip hashing cells.
+pub trait HashInstructions<F: Field>: Clone + Debug {
+    type Config: Clone + Debug;
+    /// The native hash the chip computes.
+    type Spec: HashSpec<F>;
+
+    /// Configures the chip on the `state` and `input` columns, initializing
+    /// and padding the state with `constant`.
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        state: [Column<Advice>; 3],
+        input: [Column<Advice>; 2],
+        constant: Column<Fixed>,
+    ) -> Self::Config;
+
+    fn construct(config: Self::Config) -> Self;
+
+    /// Returns the hash of `inputs`, as [`HashSpec::hash`].
+    fn hash(&self, layouter: impl Layouter<F>, inputs: &[AssignedCell<F, F>]) -> Result<AssignedCell<F, F>, Error>;
+
+    /// Returns `H(a, b)`, the node of a Merkle tree.
+    fn hash2(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.hash(layouter, &[a.clone(), b.clone()])
+    }
+}
+
+/// Sponge absorbing and squeezing cells, see the [module documentation](self).
+#[derive(Clone, Debug)]
+pub struct Sponge<F: Field, H: HashInstructions<F>> {
+    chip: H,
+    absorbed: Vec<AssignedCell<F, F>>,
+}
+
+impl<F: Field, H: HashInstructions<F>> Sponge<F, H> {
+    pub fn new(chip: H) -> Self {
+        Self { chip, absorbed: vec![] }
+    }
+
+    /// The chip hashing on squeezes.
+    pub fn chip(&self) -> &H {
+        &self.chip
+    }
+
+    /// Absorbs `cells`, hashed on the next squeeze.
+    pub fn absorb(&mut self, cells: &[AssignedCell<F, F>]) {
+        self.absorbed.extend_from_slice(cells);
+    }
+
+    /// Returns the hash of the previous output and the cells absorbed since.
+    pub fn squeeze(&mut self, layouter: impl Layouter<F>) -> Result<AssignedCell<F, F>, Error> {
+        let output = self.chip.hash(layouter, &self.absorbed)?;
+        self.absorbed = vec![output.clone()];
+        Ok(output)
+    }
+}
+
+/// Native counterpart of [`Sponge`].
+#[derive(Clone, Debug, Default)]
+pub struct NativeSponge<F: Field, S: HashSpec<F>> {
+    spec: S,
+    absorbed: Vec<F>,
+}
+
+impl<F: Field, S: HashSpec<F>> NativeSponge<F, S> {
+    pub fn absorb(&mut self, values: &[F]) {
+        self.absorbed.extend_from_slice(values);
+    }
+
+    pub fn squeeze(&mut self) -> F {
+        let output = self.spec.hash(&self.absorbed);
+        self.absorbed = vec![output];
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::marker::PhantomData;
+
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+    };
+
+    use super::{HashInstructions, HashSpec, NativeSponge, Sponge};
+    use crate::{
+        circuits::{
+            gadgets::{mimc::MimcChip, poseidon::PoseidonChip, rescue::RescueChip},
+            instance::{InstanceColumns, InstanceLayout},
+        },
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::TestField as Fp,
+    };
+
+    /// Absorbs `[1, 2, 3]`, squeezes, absorbs `[4]` and squeezes twice,
+    /// exposing the three outputs.
+    struct TestCircuit<H>(PhantomData<H>);
+
+    impl<H: HashInstructions<Fp>> Circuit<Fp> for TestCircuit<H> {
+        type Config = (H::Config, Column<Advice>, InstanceColumns);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self(PhantomData)
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let [advice, s0, s1, s2, i0, i1] = [(); 6].map(|_| meta.advice_column());
+            let constant = meta.fixed_column();
+            meta.enable_equality(advice);
+
+            let config = H::configure(meta, [s0, s1, s2], [i0, i1], constant);
+            (config, advice, InstanceLayout::new().column(["out0", "out1", "out2"]).configure(meta))
+        }
+
+        fn synthesize(
+            &self,
+            (config, advice, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let inputs = layouter.assign_region(
+                || "inputs",
+                |mut region| {
+                    (1..=4)
+                        .map(|i| region.assign_advice(|| "input", advice, i - 1, || Value::known(Fp::from(i as u64))))
+                        .collect::<Result<Vec<_>, _>>()
+                },
+            )?;
+
+            let mut sponge = Sponge::new(H::construct(config));
+            sponge.absorb(&inputs[..3]);
+            let out0 = sponge.squeeze(layouter.namespace(|| "out0"))?;
+            sponge.absorb(&inputs[3..]);
+            let out1 = sponge.squeeze(layouter.namespace(|| "out1"))?;
+            let out2 = sponge.squeeze(layouter.namespace(|| "out2"))?;
+            for (i, cell) in [out0, out1, out2].iter().enumerate() {
+                instance.expose_public(&mut layouter, cell, i)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn sponge<H: HashInstructions<Fp>>() {
+        let mut native = NativeSponge::<Fp, H::Spec>::default();
+        native.absorb(&[1, 2, 3].map(Fp::from));
+        let out0 = native.squeeze();
+        native.absorb(&[Fp::from(4)]);
+        let outputs = vec![out0, native.squeeze(), native.squeeze()];
+
+        let circuit = TestCircuit::<H>(PhantomData);
+        expect_satisfied(&circuit, vec![outputs.clone()]);
+
+        // The second squeeze without the absorbed `4`.
+        let mut wrong = outputs;
+        wrong[1] = H::Spec::default().hash(&[out0]);
+        expect_failure(
+            &circuit,
+            vec![wrong],
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 1 },
+            },
+        );
+    }
+
+    #[test]
+    fn poseidon_sponge() {
+        sponge::<PoseidonChip<Fp>>();
+    }
+
+    #[test]
+    fn rescue_sponge() {
+        sponge::<RescueChip<Fp>>();
+    }
+
+    #[test]
+    fn mimc_sponge() {
+        sponge::<MimcChip<Fp>>();
+    }
+}
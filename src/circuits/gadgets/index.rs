@@ -0,0 +1,223 @@
+//! Dynamic array access: selects `values[index]` for a witnessed `index`.
+//!
+//! `index` is encoded as a one-hot `indicator` witnessed next to the values,
+//! see [`OneHotChip`]:
+//!
+//! | values[0..N] | index | indicator[0..N]  | out              | q_index |
+//! | v_0 ... v_N  | i     | 0 .. 1 .. 0      | Σ ind_j ⋅ v_j    | 1       |
+//!
+//! - `indicator` is one-hot,
+//! - `index = Σ j ⋅ indicator_j`.
+//!
+//! So `indicator_index` is the only bit set, and an `index` outside `[0, N)`
+//! has no valid indicator.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use super::one_hot::{OneHotChip, OneHotConfig};
+use crate::field::Field;
+
+/// Config for [`IndexChip`].
+#[derive(Clone, Debug)]
+pub struct IndexConfig<F, const N: usize> {
+    q_index: Selector,
+    values: [Column<Advice>; N],
+    index: Column<Advice>,
+    indicator: OneHotConfig<F, N>,
+    out: Column<Advice>,
+}
+
+/// Chip selecting one of `N` cells by index.
+#[derive(Clone, Debug)]
+pub struct IndexChip<F: Field, const N: usize> {
+    config: IndexConfig<F, N>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field, const N: usize> Chip<F> for IndexChip<F, N> {
+    type Config = IndexConfig<F, N>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field, const N: usize> IndexChip<F, N> {
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> IndexConfig<F, N> {
+        let q_index = meta.selector();
+        let values = [(); N].map(|_| meta.advice_column());
+        let index = meta.advice_column();
+        let indicator = [(); N].map(|_| meta.advice_column());
+        let out = meta.advice_column();
+        let indicator = OneHotChip::configure(meta, |meta| meta.query_selector(q_index), indicator);
+
+        for column in values.into_iter().chain([index, out]) {
+            meta.enable_equality(column);
+        }
+
+        meta.create_gate("select by index", |meta| {
+            let q_index = meta.query_selector(q_index);
+            let values = values.map(|column| meta.query_advice(column, Rotation::cur()));
+            let index = meta.query_advice(index, Rotation::cur());
+            let out = meta.query_advice(out, Rotation::cur());
+
+            let selected = (indicator.bits().iter().zip(values))
+                .fold(Expression::Constant(F::ZERO), |acc, (ind, value)| acc + ind.clone() * value);
+
+            vec![
+                q_index.clone() * (index - indicator.index()),
+                q_index * (out - selected),
+            ]
+        });
+
+        IndexConfig {
+            q_index,
+            values,
+            index,
+            indicator,
+            out,
+        }
+    }
+
+    pub fn construct(config: IndexConfig<F, N>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns `values[index]`, failing verification if `index` is not in
+    /// `[0, N)`.
+    pub fn select(
+        &self,
+        mut layouter: impl Layouter<F>,
+        values: &[AssignedCell<F, F>; N],
+        index: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        layouter.assign_region(
+            || "select by index",
+            |mut region| {
+                config.q_index.enable(&mut region, 0)?;
+                for (j, value) in values.iter().enumerate() {
+                    value.copy_advice(|| format!("values[{j}]"), &mut region, config.values[j], 0)?;
+                }
+                let index = index.copy_advice(|| "index", &mut region, config.index, 0)?;
+
+                OneHotChip::construct(config.indicator.clone()).assign(&mut region, 0, index.value().copied())?;
+
+                let out = index.value().and_then(|index| {
+                    let j = (0..N).find(|j| *index == F::from(*j as u64));
+                    j.map_or(Value::known(F::ZERO), |j| values[j].value().copied())
+                });
+                region.assign_advice(|| "out", config.out, 0, || out)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+
+    use super::{IndexChip, IndexConfig};
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::{Field, TestField as Fp},
+    };
+
+    const VALUES: [u64; 4] = [10, 20, 30, 40];
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig<F> {
+        index: IndexConfig<F, 4>,
+        input: Column<Advice>,
+        instance: Column<Instance>,
+    }
+
+    /// Exposes `VALUES[index]`.
+    #[derive(Default)]
+    struct TestCircuit {
+        index: Value<u64>,
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit {
+        type Config = TestCircuitConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let index = IndexChip::configure(meta);
+            let input = meta.advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(input);
+            meta.enable_equality(instance);
+
+            TestCircuitConfig { index, input, instance }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let chip = IndexChip::construct(config.index);
+            let (values, index) = layouter.assign_region(
+                || "inputs",
+                |mut region| {
+                    let values = (VALUES.into_iter().enumerate())
+                        .map(|(j, value)| {
+                            region.assign_advice(|| "value", config.input, j, || Value::known(F::from(value)))
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let index = region.assign_advice(|| "index", config.input, 4, || self.index.map(F::from))?;
+                    Ok((values.try_into().unwrap(), index))
+                },
+            )?;
+
+            let out = chip.select(layouter.namespace(|| "select"), &values, &index)?;
+            layouter.constrain_instance(out.cell(), config.instance, 0)
+        }
+    }
+
+    #[test]
+    fn select_by_index() {
+        for (index, value) in VALUES.into_iter().enumerate() {
+            let circuit = TestCircuit {
+                index: Value::known(index as u64),
+            };
+            expect_satisfied(&circuit, vec![vec![Fp::from(value)]]);
+        }
+
+        // Index 4 is out of bounds: no indicator can be set.
+        let circuit = TestCircuit {
+            index: Value::known(4),
+        };
+        expect_failure(
+            &circuit,
+            vec![vec![Fp::from(0)]],
+            FailureMatcher::Constraint {
+                gate: "one hot",
+                location: Location::InRegion {
+                    region: "select by index",
+                    offset: 0,
+                },
+            },
+        );
+    }
+}
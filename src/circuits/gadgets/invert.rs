@@ -0,0 +1,184 @@
+//! Field inversion.
+//!
+//! | x | x_inv | q_invert |
+//! | x | 1/x   | 1        |
+//!
+//! Unlike the `inv0` witnessed by the [`is_zero`](super::is_zero) gadgets,
+//! `x ⋅ x_inv = 1` is enforced, so inverting zero makes the circuit
+//! unsatisfiable rather than returning 0.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::field::Field;
+
+/// Config for [`InvertChip`].
+#[derive(Clone, Debug)]
+pub struct InvertConfig {
+    q_invert: Selector,
+    x: Column<Advice>,
+    x_inv: Column<Advice>,
+}
+
+/// Chip inverting non-zero field elements.
+#[derive(Clone, Debug)]
+pub struct InvertChip<F: Field> {
+    config: InvertConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> Chip<F> for InvertChip<F> {
+    type Config = InvertConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field> InvertChip<F> {
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> InvertConfig {
+        let q_invert = meta.selector();
+        let [x, x_inv] = [(); 2].map(|_| meta.advice_column());
+
+        meta.enable_equality(x);
+        meta.enable_equality(x_inv);
+
+        meta.create_gate("invert", |meta| {
+            let q_invert = meta.query_selector(q_invert);
+            let x = meta.query_advice(x, Rotation::cur());
+            let x_inv = meta.query_advice(x_inv, Rotation::cur());
+            vec![q_invert * (x * x_inv - Expression::Constant(F::ONE))]
+        });
+
+        InvertConfig { q_invert, x, x_inv }
+    }
+
+    pub fn construct(config: InvertConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns `1 / x`, failing verification if `x` is zero.
+    pub fn invert(&self, mut layouter: impl Layouter<F>, x: &AssignedCell<F, F>) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        layouter.assign_region(
+            || "invert",
+            |mut region| {
+                config.q_invert.enable(&mut region, 0)?;
+                let x = x.copy_advice(|| "x", &mut region, config.x, 0)?;
+                let x_inv = x.value().map(|x| x.invert().unwrap_or(F::ZERO));
+                region.assign_advice(|| "x_inv", config.x_inv, 0, || x_inv)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        halo2curves::group::ff::Field as _,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+
+    use super::{InvertChip, InvertConfig};
+    use crate::{
+        dev::{
+            fuzz::{field, gadget_proptest},
+            mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        },
+        field::{Field, TestField as Fp},
+    };
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig {
+        invert: InvertConfig,
+        input: Column<Advice>,
+        instance: Column<Instance>,
+    }
+
+    /// Exposes `1 / x`.
+    #[derive(Default)]
+    struct TestCircuit<F: Field> {
+        x: Value<F>,
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit<F> {
+        type Config = TestCircuitConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let invert = InvertChip::configure(meta);
+            let input = meta.advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(input);
+            meta.enable_equality(instance);
+
+            TestCircuitConfig {
+                invert,
+                input,
+                instance,
+            }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let chip = InvertChip::construct(config.invert);
+            let x = layouter.assign_region(
+                || "x",
+                |mut region| region.assign_advice(|| "x", config.input, 0, || self.x),
+            )?;
+            let x_inv = chip.invert(layouter.namespace(|| "invert"), &x)?;
+            layouter.constrain_instance(x_inv.cell(), config.instance, 0)
+        }
+    }
+
+    #[test]
+    fn invert() {
+        let circuit = TestCircuit {
+            x: Value::known(Fp::from(3)),
+        };
+        expect_satisfied(&circuit, vec![vec![Fp::from(3).invert().unwrap()]]);
+
+        // Zero has no inverse, not even the 0 of `inv0`.
+        let circuit = TestCircuit {
+            x: Value::known(Fp::ZERO),
+        };
+        expect_failure(
+            &circuit,
+            vec![vec![Fp::ZERO]],
+            FailureMatcher::Constraint {
+                gate: "invert",
+                location: Location::InRegion {
+                    region: "invert",
+                    offset: 0,
+                },
+            },
+        );
+    }
+
+    gadget_proptest! {
+        invert_complete(x in field()) {
+            circuit: TestCircuit { x: Value::known(x) },
+            instances: vec![vec![x.invert().unwrap_or(Fp::ZERO)]],
+            valid: x != Fp::ZERO,
+        }
+    }
+}
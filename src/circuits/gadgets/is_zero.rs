@@ -3,14 +3,16 @@
 //! Given a `value` to be checked if it is zero:
 //!  - witnesses `inv0(value)`, where `inv0(x)` is 0 when `x` = 0, and
 //!  `1/x` otherwise
+//!  - witnesses `is_zero = 1 - value * inv0(value)`, and returns its cell
 
-use eth_types::Field;
 use halo2_proofs::{
-    circuit::{Chip, Region, Value},
+    circuit::{AssignedCell, Chip, Region, Value},
     plonk::{Advice, Column, ConstraintSystem, Error, Expression, VirtualCells},
     poly::Rotation,
 };
 
+use crate::field::Field;
+
 /// Trait that implements functionality to get a constant expression from
 /// commonly used types.
 pub trait Expr<F: Field> {
@@ -33,6 +35,8 @@ impl<F: Field> Expr<F> for i32 {
 pub struct IsZeroConfig<F> {
     /// Modular inverse of the value.
     pub value_inv: Column<Advice>,
+    /// Witnessed result, 1 if `value` is zero, and 0 otherwise.
+    pub is_zero: Column<Advice>,
     /// This can be used directly for custom gate at the offset if `is_zero` is
     /// called, it will be 1 if `value` is zero, and 0 otherwise.
     pub is_zero_expression: Expression<F>,
@@ -56,9 +60,12 @@ impl<F: Field> IsZeroConfig<F> {
 
     /// Annotates columns of this gadget embedded within a circuit region.
     pub fn annotate_columns_in_region(&self, region: &mut Region<F>, prefix: &str) {
-        [(self.value_inv, "GADGETS_IS_ZERO_inverse_witness")]
-            .iter()
-            .for_each(|(col, ann)| region.name_column(|| format!("{prefix}_{ann}"), *col));
+        [
+            (self.value_inv, "GADGETS_IS_ZERO_inverse_witness"),
+            (self.is_zero, "GADGETS_IS_ZERO_result"),
+        ]
+        .iter()
+        .for_each(|(col, ann)| region.name_column(|| format!("{prefix}_{ann}"), *col));
     }
 }
 
@@ -72,6 +79,8 @@ pub struct IsZeroChip<F> {
 impl<F: Field> IsZeroChip<F> {
     /// Sets up the configuration of the chip by creating the required columns
     /// and defining the constraints that take part when using `is_zero` gate.
+    /// The `is_zero` column is constrained to `1 - value ⋅ value_inv`, and
+    /// equality is enabled on it.
     ///
     /// Truth table of iz_zero gate:
     /// +----+-------+-----------+-----------------------+---------------------------------+-------------------------------------+
@@ -88,7 +97,10 @@ impl<F: Field> IsZeroChip<F> {
         q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
         value: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
         value_inv: Column<Advice>,
+        is_zero: Column<Advice>,
     ) -> IsZeroConfig<F> {
+        meta.enable_equality(is_zero);
+
         // dummy initialization
         let mut is_zero_expression = 0.expr();
 
@@ -97,6 +109,7 @@ impl<F: Field> IsZeroChip<F> {
 
             let value_inv = meta.query_advice(value_inv, Rotation::cur());
             let value = value(meta);
+            let is_zero = meta.query_advice(is_zero, Rotation::cur());
 
             is_zero_expression = 1.expr() - value.clone() * value_inv;
 
@@ -104,11 +117,17 @@ impl<F: Field> IsZeroChip<F> {
             //
             // 1. value == 0
             // 2. if value != 0, require is_zero_expression == 0 => value_inv == value.invert()
-            [q_enable * value * is_zero_expression.clone()]
+            //
+            // and the witnessed result to be is_zero_expression.
+            [
+                q_enable.clone() * value * is_zero_expression.clone(),
+                q_enable * (is_zero - is_zero_expression.clone()),
+            ]
         });
 
         IsZeroConfig::<F> {
             value_inv,
+            is_zero,
             is_zero_expression,
         }
     }
@@ -137,12 +156,13 @@ impl<F: Field> Chip<F> for IsZeroChip<F> {
 pub trait IsZeroInstruction<F: Field> {
     /// Given a `value` to be checked if it is zero:
     ///   - witnesses `inv0(value)`, where `inv0(x)` is 0 when `x` = 0, and `1/x` otherwise
+    ///   - witnesses and returns the `is_zero` bit
     fn assign(
         &self,
         region: &mut Region<'_, F>,
         offset: usize,
         value: Value<F>,
-    ) -> Result<(), Error>;
+    ) -> Result<AssignedCell<F, F>, Error>;
 }
 
 impl<F: Field> IsZeroInstruction<F> for IsZeroChip<F> {
@@ -151,7 +171,7 @@ impl<F: Field> IsZeroInstruction<F> for IsZeroChip<F> {
         region: &mut Region<'_, F>,
         offset: usize,
         value: Value<F>,
-    ) -> Result<(), Error> {
+    ) -> Result<AssignedCell<F, F>, Error> {
         let config = self.config();
         // postpone the invert to prover which has batch_invert function to
         // amortize among all is_zero_chip assignments.
@@ -163,7 +183,12 @@ impl<F: Field> IsZeroInstruction<F> for IsZeroChip<F> {
             || value_invert,
         )?;
 
-        Ok(())
+        region.assign_advice(
+            || "is_zero",
+            config.is_zero,
+            offset,
+            || value.map(|value| F::from(value.is_zero_vartime() as u64)),
+        )
     }
 }
 
@@ -171,18 +196,15 @@ impl<F: Field> IsZeroInstruction<F> for IsZeroChip<F> {
 mod test {
     use super::{IsZeroChip, IsZeroConfig, IsZeroInstruction};
 
-    use eth_types::Field;
     use halo2_proofs::{
         circuit::{Layouter, SimpleFloorPlanner, Value},
         dev::MockProver,
-        halo2curves::bn256::Fr as Fp,
         plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
         poly::Rotation,
     };
     use std::marker::PhantomData;
 
-    use halo2_proofs::dev::CircuitLayout;
-    use plotters::prelude::*;
+    use crate::field::{Field, TestField as Fp};
 
     macro_rules! try_test_circuit {
         ($values:expr, $checks:expr) => {{
@@ -246,6 +268,7 @@ mod test {
                 let q_enable = meta.complex_selector();
                 let value = meta.advice_column();
                 let value_diff_inv = meta.advice_column();
+                let value_diff_is_zero = meta.advice_column();
                 let check = meta.advice_column();
 
                 let is_zero = IsZeroChip::configure(
@@ -257,6 +280,7 @@ mod test {
                         value_cur - value_prev
                     },
                     value_diff_inv,
+                    value_diff_is_zero,
                 );
 
                 let config = Self::Config {
@@ -375,6 +399,7 @@ mod test {
                 let q_enable = meta.complex_selector();
                 let (value_a, value_b) = (meta.advice_column(), meta.advice_column());
                 let value_diff_inv = meta.advice_column();
+                let value_diff_is_zero = meta.advice_column();
                 let check = meta.advice_column();
 
                 let is_zero = IsZeroChip::configure(
@@ -386,6 +411,7 @@ mod test {
                         value_a - value_b
                     },
                     value_diff_inv,
+                    value_diff_is_zero,
                 );
 
                 let config = Self::Config {
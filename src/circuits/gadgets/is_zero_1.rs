@@ -3,25 +3,28 @@
 //! Given a `value` to be checked if it is zero:
 //!  - witnesses `inv0(value)`, where `inv0(x)` is 0 when `x` = 0, and
 //!  `1/x` otherwise
+//!  - witnesses `is_zero = 1 - value * inv0(value)`, and returns its cell
 
-use eth_types::Field;
 use halo2_proofs::{
-    circuit::{Chip, Region, Value},
+    circuit::{AssignedCell, Chip, Region, Value},
     plonk::{Advice, Column, ConstraintSystem, Error, Expression, VirtualCells},
     poly::Rotation,
 };
 
+use crate::field::Field;
+
 /// Trait that needs to be implemented for any gadget or circuit that wants to
 /// implement `IsZero`.
 pub trait IsZeroInstruction<F: Field> {
     /// Given a `value` to be checked if it is zero:
     ///   - witnesses `inv0(value)`, where `inv0(x)` is 0 when `x` = 0, and `1/x` otherwise
+    ///   - witnesses and returns the `is_zero` bit
     fn assign(
         &self,
         region: &mut Region<'_, F>,
         offset: usize,
         value: Value<F>,
-    ) -> Result<(), Error>;
+    ) -> Result<AssignedCell<F, F>, Error>;
 }
 
 /// Config struct representing the required fields for an `IsZero` config to
@@ -30,6 +33,8 @@ pub trait IsZeroInstruction<F: Field> {
 pub struct IsZeroConfig<F> {
     /// Modular inverse of the value.
     pub value_inv: Column<Advice>,
+    /// Witnessed result, 1 if `value` is zero, and 0 otherwise.
+    pub is_zero: Column<Advice>,
     /// This can be used directly for custom gate at the offset if `is_zero` is
     /// called, it will be 1 if `value` is zero, and 0 otherwise.
     pub is_zero_expression: Expression<F>,
@@ -45,6 +50,8 @@ pub struct IsZeroChip<F> {
 impl<F: Field> IsZeroChip<F> {
     /// Sets up the configuration of the chip by creating the required columns
     /// and defining the constraints that take part when using `is_zero` gate.
+    /// The `is_zero` column is constrained to `1 - value ⋅ value_inv`, and
+    /// equality is enabled on it.
     ///
     /// Truth table of iz_zero gate:
     /// +----+-------+-----------+-----------------------+---------------------------------+-------------------------------------+
@@ -61,7 +68,10 @@ impl<F: Field> IsZeroChip<F> {
         q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
         value: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
         value_inv: Column<Advice>,
+        is_zero: Column<Advice>,
     ) -> IsZeroConfig<F> {
+        meta.enable_equality(is_zero);
+
         // dummy initialization
         let mut is_zero_expression = Expression::Constant(F::ZERO);
 
@@ -70,6 +80,7 @@ impl<F: Field> IsZeroChip<F> {
 
             let value_inv = meta.query_advice(value_inv, Rotation::cur());
             let value = value(meta);
+            let is_zero = meta.query_advice(is_zero, Rotation::cur());
 
             is_zero_expression = Expression::Constant(F::ONE) - value.clone() * value_inv;
 
@@ -77,11 +88,17 @@ impl<F: Field> IsZeroChip<F> {
             //
             // 1. value == 0
             // 2. if value != 0, require is_zero_expression == 0 => value_inv == value.invert()
-            [q_enable * value * is_zero_expression.clone()]
+            //
+            // and the witnessed result to be is_zero_expression.
+            [
+                q_enable.clone() * value * is_zero_expression.clone(),
+                q_enable * (is_zero - is_zero_expression.clone()),
+            ]
         });
 
         IsZeroConfig::<F> {
             value_inv,
+            is_zero,
             is_zero_expression,
         }
     }
@@ -98,7 +115,7 @@ impl<F: Field> IsZeroInstruction<F> for IsZeroChip<F> {
         region: &mut Region<'_, F>,
         offset: usize,
         value: Value<F>,
-    ) -> Result<(), Error> {
+    ) -> Result<AssignedCell<F, F>, Error> {
         let config = self.config();
         // postpone the invert to prover which has batch_invert function to
         // amortize among all is_zero_chip assignments.
@@ -110,7 +127,12 @@ impl<F: Field> IsZeroInstruction<F> for IsZeroChip<F> {
             || value_invert,
         )?;
 
-        Ok(())
+        region.assign_advice(
+            || "is_zero",
+            config.is_zero,
+            offset,
+            || value.map(|value| F::from(value.is_zero_vartime() as u64)),
+        )
     }
 }
 
@@ -130,26 +152,24 @@ impl<F: Field> Chip<F> for IsZeroChip<F> {
 mod test {
     use super::{IsZeroChip, IsZeroConfig, IsZeroInstruction};
 
-    use eth_types::Field;
     use halo2_proofs::{
         circuit::{Layouter, SimpleFloorPlanner, Value},
         dev::MockProver,
-        halo2curves::bn256::Fr as Fp,
-        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector, Instance},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
         poly::Rotation,
     };
     use std::marker::PhantomData;
 
-    use halo2_proofs::dev::CircuitLayout;
-    use plotters::prelude::*;
+    use crate::circuits::instance::{InstanceColumns, InstanceLayout};
+    use crate::field::{Field, TestField as Fp};
 
     macro_rules! try_test_circuit {
-        ($value:expr) => {{
+        ($value:expr, $is_zero:expr) => {{
             let circuit = TestCircuit::<Fp> {
                 value: $value,
                 _marker: PhantomData,
             };
-            let prover = MockProver::<Fp>::run(4, &circuit, vec![vec![Fp::from(1)]]).unwrap();
+            let prover = MockProver::<Fp>::run(4, &circuit, vec![vec![Fp::from($is_zero)]]).unwrap();
             prover.assert_satisfied()
         }};
     }
@@ -160,7 +180,7 @@ mod test {
         struct TestCircuitConfig<F> {
             q_enable: Selector,
             value: Column<Advice>,
-            instance: Column<Instance>,
+            instance: InstanceColumns,
             is_zero: IsZeroConfig<F>,
         }
 
@@ -184,9 +204,9 @@ mod test {
                 let q_enable = meta.complex_selector();
                 let value = meta.advice_column();
                 let value_inv = meta.advice_column();
-                let instance = meta.instance_column();
+                let value_is_zero = meta.advice_column();
+                let instance = InstanceLayout::new().column(["is_zero"]).configure(meta);
 
-                meta.enable_equality(instance);
                 meta.enable_equality(value);
 
                 let is_zero = IsZeroChip::configure(
@@ -196,6 +216,7 @@ mod test {
                         meta.query_advice(value, Rotation::cur())
                     },
                     value_inv,
+                    value_is_zero,
                 );
 
                 let config = Self::Config {
@@ -228,36 +249,17 @@ mod test {
                             || v.clone(),
                         )?;
 
-                        chip.assign(&mut region, 0, v.clone())?;
-
-                        // let value_invert = if self.value != 0 {
-                        //     (1.0 / self.value as f64) as u64
-                        // } else {
-                        //     0 as u64
-                        // };
-
-                        let is_zero = if self.value != 0 {
-                            0 as u64
-                        } else {
-                            1 as u64
-                        };
-                        region.assign_advice(
-                            || "instance",
-                            config.value,
-                            1,
-                            || Value::known(F::from(is_zero)),
-                        )
+                        chip.assign(&mut region, 0, v.clone())
                     },
                 )?;
 
                 // Ok(())
-                layouter
-                    .namespace(|| "out")
-                    .constrain_instance(out.cell(), config.instance, 0)
+                config.instance.expose_public(&mut layouter.namespace(|| "out"), &out, 0)
             }
         }
 
         // ok
-        try_test_circuit!(0 as u64);
+        try_test_circuit!(0 as u64, 1);
+        try_test_circuit!(5 as u64, 0);
     }
 }
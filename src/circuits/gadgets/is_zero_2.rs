@@ -0,0 +1,247 @@
+//! IsZero gadget that witnesses its result:
+//!
+//! Given a `value` to be checked if it is zero:
+//!  - witnesses `inv0(value)`, where `inv0(x)` is 0 when `x` = 0, and
+//!  `1/x` otherwise
+//!  - witnesses `is_zero = 1 - value * inv0(value)` in its own advice column
+//!
+//! Like [`super::is_zero`], `assign` returns the `is_zero` cell. Unlike it,
+//! [`IsZeroConfig::expr`] queries that cell instead of `1 - value * value_inv`,
+//! which keeps the degree of the gates using it down.
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, VirtualCells},
+    poly::Rotation,
+};
+
+use crate::field::Field;
+
+/// Trait that needs to be implemented for any gadget or circuit that wants to
+/// implement `IsZero` with a witnessed result.
+pub trait IsZeroInstruction<F: Field> {
+    /// Given a `value` to be checked if it is zero:
+    ///   - witnesses `inv0(value)`, where `inv0(x)` is 0 when `x` = 0, and `1/x` otherwise
+    ///   - witnesses and returns the `is_zero` bit
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        value: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error>;
+}
+
+/// Config struct representing the required fields for an `IsZero` config to
+/// exist.
+#[derive(Clone, Debug)]
+pub struct IsZeroConfig<F> {
+    /// Modular inverse of the value.
+    pub value_inv: Column<Advice>,
+    /// Witnessed result, 1 if `value` is zero, and 0 otherwise.
+    pub is_zero: Column<Advice>,
+    /// Same as the `is_zero` column, for use in custom gates at the offset
+    /// `is_zero` is assigned at.
+    pub is_zero_expression: Expression<F>,
+}
+
+impl<F: Field> IsZeroConfig<F> {
+    /// Returns the is_zero expression
+    pub fn expr(&self) -> Expression<F> {
+        self.is_zero_expression.clone()
+    }
+}
+
+/// Wrapper arround [`IsZeroConfig`] for which [`Chip`] is implemented.
+#[derive(Clone, Debug)]
+pub struct IsZeroChip<F> {
+    config: IsZeroConfig<F>,
+}
+
+impl<F: Field> IsZeroChip<F> {
+    /// Sets up the configuration of the chip. On top of the `is_zero gate` of
+    /// [`super::is_zero`], the `is_zero` column is constrained to equal
+    /// `1 - value ⋅ value_inv`, which makes it boolean.
+    ///
+    /// | value | value_inv | is_zero        | q |
+    /// | 0     | 0         | 1              | 1 |
+    /// | x     | 1/x       | 0              | 1 |
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        value: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        value_inv: Column<Advice>,
+        is_zero: Column<Advice>,
+    ) -> IsZeroConfig<F> {
+        meta.enable_equality(is_zero);
+
+        // dummy initialization
+        let mut is_zero_expression = Expression::Constant(F::ZERO);
+
+        meta.create_gate("is_zero gate", |meta| {
+            let q_enable = q_enable(meta);
+
+            let value_inv = meta.query_advice(value_inv, Rotation::cur());
+            let value = value(meta);
+            let is_zero = meta.query_advice(is_zero, Rotation::cur());
+
+            let expected = Expression::Constant(F::ONE) - value.clone() * value_inv;
+            is_zero_expression = is_zero.clone();
+
+            [
+                q_enable.clone() * value * expected.clone(),
+                q_enable * (is_zero - expected),
+            ]
+        });
+
+        IsZeroConfig::<F> {
+            value_inv,
+            is_zero,
+            is_zero_expression,
+        }
+    }
+
+    /// Given an `IsZeroConfig`, construct the chip.
+    pub fn construct(config: IsZeroConfig<F>) -> Self {
+        IsZeroChip { config }
+    }
+}
+
+impl<F: Field> IsZeroInstruction<F> for IsZeroChip<F> {
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        value: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = self.config();
+        let value_invert = value.map(|value| value.invert().unwrap_or(F::ZERO));
+        region.assign_advice(
+            || "witness inverse of value",
+            config.value_inv,
+            offset,
+            || value_invert,
+        )?;
+
+        region.assign_advice(
+            || "is_zero",
+            config.is_zero,
+            offset,
+            || value.map(|value| F::from(value.is_zero_vartime() as u64)),
+        )
+    }
+}
+
+impl<F: Field> Chip<F> for IsZeroChip<F> {
+    type Config = IsZeroConfig<F>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{IsZeroChip, IsZeroConfig, IsZeroInstruction};
+
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance, Selector},
+        poly::Rotation,
+    };
+
+    use crate::dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location};
+    use crate::field::{Field, TestField as Fp};
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig<F> {
+        q_enable: Selector,
+        value: Column<Advice>,
+        instance: Column<Instance>,
+        is_zero: IsZeroConfig<F>,
+    }
+
+    #[derive(Default)]
+    struct TestCircuit<F: Field> {
+        value: Value<F>,
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit<F> {
+        type Config = TestCircuitConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let q_enable = meta.selector();
+            let value = meta.advice_column();
+            let value_inv = meta.advice_column();
+            let is_zero = meta.advice_column();
+            let instance = meta.instance_column();
+
+            meta.enable_equality(instance);
+
+            let is_zero = IsZeroChip::configure(
+                meta,
+                |meta| meta.query_selector(q_enable),
+                |meta| meta.query_advice(value, Rotation::cur()),
+                value_inv,
+                is_zero,
+            );
+
+            TestCircuitConfig {
+                q_enable,
+                value,
+                instance,
+                is_zero,
+            }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let chip = IsZeroChip::construct(config.is_zero.clone());
+
+            let is_zero = layouter.assign_region(
+                || "witness",
+                |mut region| {
+                    config.q_enable.enable(&mut region, 0)?;
+                    region.assign_advice(|| "value", config.value, 0, || self.value)?;
+                    chip.assign(&mut region, 0, self.value)
+                },
+            )?;
+
+            // The result is only usable elsewhere through a copy constraint.
+            layouter
+                .namespace(|| "out")
+                .constrain_instance(is_zero.cell(), config.instance, 0)
+        }
+    }
+
+    #[test]
+    fn is_zero_cell() {
+        for (value, is_zero) in [(0, 1), (1, 0), (42, 0)] {
+            let circuit = TestCircuit {
+                value: Value::known(Fp::from(value)),
+            };
+            expect_satisfied(&circuit, vec![vec![Fp::from(is_zero)]]);
+        }
+
+        let circuit = TestCircuit {
+            value: Value::known(Fp::from(0)),
+        };
+        expect_failure(
+            &circuit,
+            vec![vec![Fp::from(0)]],
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+    }
+}
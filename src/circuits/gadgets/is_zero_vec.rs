@@ -0,0 +1,216 @@
+//! IsZero over a vector of `N` values with a single inverse.
+//!
+//! Instead of one [`super::is_zero`] gadget, and one inverse column, per
+//! value, the values are folded into the random linear combination
+//! `rlc = Σ values[i] ⋅ r^i`, where `r` is a challenge drawn after the values
+//! are committed, and only `rlc` is checked for zero:
+//!
+//! | values[0] | ... | values[N - 1] | rlc_inv (second phase) | all_zero (second phase) | q_enable |
+//! | v0        | ... | v(N-1)        | inv0(rlc)              | 1 - rlc ⋅ inv0(rlc)     | 1        |
+//!
+//! If any value is nonzero, `rlc` is zero only with probability `N / |F|`
+//! over the choice of `r`, so [`IsZeroVecConfig::all_zero`] is sound as long
+//! as the values live in first phase columns.
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Region, Value},
+    plonk::{Challenge, ConstraintSystem, Error, Expression, FirstPhase, SecondPhase, VirtualCells},
+};
+
+use super::is_zero::{IsZeroChip, IsZeroConfig, IsZeroInstruction};
+use crate::field::Field;
+
+/// Config for [`IsZeroVecChip`].
+#[derive(Clone, Debug)]
+pub struct IsZeroVecConfig<F, const N: usize> {
+    /// Challenge the values are combined with.
+    pub challenge: Challenge,
+    is_zero: IsZeroConfig<F>,
+}
+
+impl<F: Field, const N: usize> IsZeroVecConfig<F, N> {
+    /// 1 if all the values are zero, and 0 otherwise.
+    pub fn all_zero(&self) -> Expression<F> {
+        self.is_zero.expr()
+    }
+
+    /// 1 if any of the values is nonzero, and 0 otherwise.
+    pub fn any_nonzero(&self) -> Expression<F> {
+        Expression::Constant(F::ONE) - self.all_zero()
+    }
+}
+
+/// Chip checking whether a vector of values is all zero.
+#[derive(Clone, Debug)]
+pub struct IsZeroVecChip<F, const N: usize> {
+    config: IsZeroVecConfig<F, N>,
+}
+
+impl<F: Field, const N: usize> Chip<F> for IsZeroVecChip<F, N> {
+    type Config = IsZeroVecConfig<F, N>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field, const N: usize> IsZeroVecChip<F, N> {
+    /// Configures the chip over the `values` expressions, which must only
+    /// query first phase columns.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        values: impl FnOnce(&mut VirtualCells<'_, F>) -> [Expression<F>; N],
+    ) -> IsZeroVecConfig<F, N> {
+        let rlc_inv = meta.advice_column_in(SecondPhase);
+        let all_zero = meta.advice_column_in(SecondPhase);
+        let challenge = meta.challenge_usable_after(FirstPhase);
+
+        let is_zero = IsZeroChip::configure(
+            meta,
+            q_enable,
+            |meta| {
+                let r = meta.query_challenge(challenge);
+                (values(meta).into_iter().rev())
+                    .fold(Expression::Constant(F::ZERO), |acc, value| acc * r.clone() + value)
+            },
+            rlc_inv,
+            all_zero,
+        );
+
+        IsZeroVecConfig { challenge, is_zero }
+    }
+
+    pub fn construct(config: IsZeroVecConfig<F, N>) -> Self {
+        Self { config }
+    }
+
+    /// Witnesses the inverse of the combination of `values` with
+    /// `challenge`, the value of [`IsZeroVecConfig::challenge`] obtained from
+    /// the layouter, and returns the cell of [`IsZeroVecConfig::all_zero`].
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        values: [Value<F>; N],
+        challenge: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let rlc = (values.into_iter().rev()).fold(Value::known(F::ZERO), |acc, value| acc * challenge + value);
+        IsZeroChip::construct(self.config.is_zero.clone()).assign(region, offset, rlc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
+        poly::Rotation,
+    };
+
+    use super::{IsZeroVecChip, IsZeroVecConfig};
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::{Field, TestField as Fp},
+    };
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig<F> {
+        q_enable: Selector,
+        values: [Column<Advice>; 4],
+        any_nonzero: Column<Advice>,
+        is_zero_vec: IsZeroVecConfig<F, 4>,
+    }
+
+    /// Assigns four values and a claim that one of them is nonzero.
+    #[derive(Default)]
+    struct TestCircuit {
+        values: [u64; 4],
+        any_nonzero: bool,
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit {
+        type Config = TestCircuitConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let q_enable = meta.selector();
+            let values = [(); 4].map(|_| meta.advice_column());
+            let any_nonzero = meta.advice_column();
+
+            let is_zero_vec = IsZeroVecChip::configure(
+                meta,
+                |meta| meta.query_selector(q_enable),
+                |meta| values.map(|column| meta.query_advice(column, Rotation::cur())),
+            );
+
+            meta.create_gate("any nonzero", |meta| {
+                let q_enable = meta.query_selector(q_enable);
+                let any_nonzero = meta.query_advice(any_nonzero, Rotation::cur());
+                vec![q_enable * (any_nonzero - is_zero_vec.any_nonzero())]
+            });
+
+            TestCircuitConfig {
+                q_enable,
+                values,
+                any_nonzero,
+                is_zero_vec,
+            }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let challenge = layouter.get_challenge(config.is_zero_vec.challenge);
+            let chip = IsZeroVecChip::construct(config.is_zero_vec);
+
+            layouter.assign_region(
+                || "is zero vec",
+                |mut region| {
+                    config.q_enable.enable(&mut region, 0)?;
+                    let values = self.values.map(|value| Value::known(F::from(value)));
+                    for (i, (column, value)) in config.values.iter().zip(values).enumerate() {
+                        region.assign_advice(|| format!("values[{i}]"), *column, 0, || value)?;
+                    }
+                    let any_nonzero = Value::known(F::from(self.any_nonzero as u64));
+                    region.assign_advice(|| "any nonzero", config.any_nonzero, 0, || any_nonzero)?;
+                    chip.assign(&mut region, 0, values, challenge)?;
+
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn is_zero_vec() {
+        for (values, any_nonzero) in [([0; 4], false), ([0, 0, 0, 1], true), ([5, 0, 7, 0], true)] {
+            expect_satisfied::<Fp, _>(&TestCircuit { values, any_nonzero }, vec![]);
+        }
+
+        // Claiming a zero vector has a nonzero value.
+        expect_failure::<Fp, _>(
+            &TestCircuit {
+                values: [0; 4],
+                any_nonzero: true,
+            },
+            vec![],
+            FailureMatcher::Constraint {
+                gate: "any nonzero",
+                location: Location::InRegion {
+                    region: "is zero vec",
+                    offset: 0,
+                },
+            },
+        );
+    }
+}
@@ -0,0 +1,304 @@
+//! Integer square root of values of at most `N_BYTES` bytes.
+//!
+//! The root `s = floor(sqrt(x))` is witnessed, and only its defining property
+//! `s^2 <= x < (s + 1)^2` is constrained, with two [`LtChip`] comparisons:
+//! `x < s^2` must be 0 and `x < (s + 1)^2` must be 1.
+//!
+//! | x | s | sq  | sq_next       | q_isqrt |
+//! | x | s | s^2 | s^2 + 2s + 1  | 1       |
+//!
+//! `s` is range checked to `(N_BYTES + 1) / 2` bytes, so `(s + 1)^2` is at
+//! most `2^(16 ⋅ ((N_BYTES + 1) / 2))`. For this to stay below the modulus,
+//! `N_BYTES` is at most [`MAX_BYTES`].
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+use super::lt::{LtChip, LtConfig};
+use crate::{circuits::pack::PackConfig, field::Field};
+
+/// Most bytes of `x`, such that `s` has at most 15 bytes and `(s + 1)^2 <= 2^240`.
+pub const MAX_BYTES: usize = 30;
+
+/// Config for [`IsqrtChip`].
+#[derive(Clone, Debug)]
+pub struct IsqrtConfig<F: Field, const N_BYTES: usize> {
+    q_isqrt: Selector,
+    x: Column<Advice>,
+    s: Column<Advice>,
+    sq: Column<Advice>,
+    sq_next: Column<Advice>,
+    lt: LtConfig<F, N_BYTES>,
+    pack: PackConfig,
+}
+
+/// Chip computing integer square roots.
+#[derive(Clone, Debug)]
+pub struct IsqrtChip<F: Field, const N_BYTES: usize> {
+    config: IsqrtConfig<F, N_BYTES>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field, const N_BYTES: usize> Chip<F> for IsqrtChip<F, N_BYTES> {
+    type Config = IsqrtConfig<F, N_BYTES>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field, const N_BYTES: usize> IsqrtChip<F, N_BYTES> {
+    /// Configures the chip, comparing with `lt`, range checking the root with
+    /// `pack` and pinning the comparisons with `constant`.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        lt: LtConfig<F, N_BYTES>,
+        pack: PackConfig,
+        constant: Column<Fixed>,
+    ) -> IsqrtConfig<F, N_BYTES> {
+        assert!(N_BYTES <= MAX_BYTES, "isqrt of at most {MAX_BYTES} bytes, got {N_BYTES}");
+        let q_isqrt = meta.selector();
+        let [x, s, sq, sq_next] = [(); 4].map(|_| meta.advice_column());
+
+        for column in [x, s, sq, sq_next] {
+            meta.enable_equality(column);
+        }
+        meta.enable_constant(constant);
+
+        meta.create_gate("isqrt", |meta| {
+            let q_isqrt = meta.query_selector(q_isqrt);
+            let [s, sq, sq_next] = [s, sq, sq_next].map(|column| meta.query_advice(column, Rotation::cur()));
+
+            vec![
+                q_isqrt.clone() * (sq.clone() - s.clone() * s.clone()),
+                q_isqrt * (sq_next - sq - Expression::Constant(F::from(2)) * s - Expression::Constant(F::ONE)),
+            ]
+        });
+
+        IsqrtConfig {
+            q_isqrt,
+            x,
+            s,
+            sq,
+            sq_next,
+            lt,
+            pack,
+        }
+    }
+
+    pub fn construct(config: IsqrtConfig<F, N_BYTES>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns `floor(sqrt(x))` for `x < 2^(8 ⋅ N_BYTES)`.
+    pub fn assign(&self, mut layouter: impl Layouter<F>, x: &AssignedCell<F, F>) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        let lt = LtChip::construct(config.lt.clone());
+
+        let (x, s, sq, sq_next) = layouter.assign_region(
+            || "isqrt",
+            |mut region| {
+                config.q_isqrt.enable(&mut region, 0)?;
+                let x = x.copy_advice(|| "x", &mut region, config.x, 0)?;
+
+                let s = x.value().map(|x| isqrt::<F>(*x, 4 * N_BYTES));
+                let sq = s * s;
+                let sq_next = sq + s + s + Value::known(F::ONE);
+
+                let s = region.assign_advice(|| "s", config.s, 0, || s)?;
+                let sq = region.assign_advice(|| "s^2", config.sq, 0, || sq)?;
+                let sq_next = region.assign_advice(|| "(s + 1)^2", config.sq_next, 0, || sq_next)?;
+                Ok((x, s, sq, sq_next))
+            },
+        )?;
+
+        config.pack.unpack(layouter.namespace(|| "s"), &s, (N_BYTES + 1) / 2)?;
+
+        let below = lt.assign(layouter.namespace(|| "x < s^2"), &x, &sq)?;
+        let above = lt.assign(layouter.namespace(|| "x < (s + 1)^2"), &x, &sq_next)?;
+        layouter.assign_region(
+            || "isqrt bounds",
+            |mut region| {
+                region.constrain_constant(below.lt.cell(), F::ZERO)?;
+                region.constrain_constant(above.lt.cell(), F::ONE)
+            },
+        )?;
+
+        Ok(s)
+    }
+}
+
+/// `floor(sqrt(x))`, bit by bit from the `bits - 1`th, for `x < 2^(2 ⋅ bits)`.
+fn isqrt<F: Field>(x: F, bits: usize) -> F {
+    (0..bits).rev().fold(F::ZERO, |s, bit| {
+        let candidate = s + F::from(2).pow_vartime([bit as u64]);
+        if candidate * candidate <= x {
+            candidate
+        } else {
+            s
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+    use proptest::prelude::any;
+
+    use super::{IsqrtChip, IsqrtConfig, MAX_BYTES};
+    use crate::{
+        circuits::{gadgets::lt::LtChip, pack::PackConfig, table::U8Table},
+        dev::{
+            fuzz::gadget_proptest,
+            mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        },
+        field::{Field, TestField as Fp},
+    };
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig<F: Field, const N_BYTES: usize> {
+        isqrt: IsqrtConfig<F, N_BYTES>,
+        u8_table: U8Table,
+        input: Column<Advice>,
+        instance: Column<Instance>,
+    }
+
+    /// Exposes the square root of an `N_BYTES`-byte `x`.
+    #[derive(Default)]
+    struct TestCircuit<F: Field, const N_BYTES: usize> {
+        x: Value<F>,
+    }
+
+    impl<F: Field, const N_BYTES: usize> Circuit<F> for TestCircuit<F, N_BYTES> {
+        type Config = TestCircuitConfig<F, N_BYTES>;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let u8_table = U8Table::configure(meta);
+            let [input, byte, acc] = [(); 3].map(|_| meta.advice_column());
+            let constant = meta.fixed_column();
+            let pack = PackConfig::configure(meta, byte, acc, u8_table);
+            let lt = LtChip::configure(meta, pack.clone());
+            let isqrt = IsqrtChip::configure(meta, lt, pack, constant);
+            let instance = meta.instance_column();
+            meta.enable_equality(input);
+            meta.enable_equality(instance);
+
+            TestCircuitConfig {
+                isqrt,
+                u8_table,
+                input,
+                instance,
+            }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let chip = IsqrtChip::construct(config.isqrt);
+            config.u8_table.load(&mut layouter)?;
+
+            let x = layouter.assign_region(
+                || "x",
+                |mut region| region.assign_advice(|| "x", config.input, 0, || self.x),
+            )?;
+            let s = chip.assign(layouter.namespace(|| "isqrt"), &x)?;
+            layouter.constrain_instance(s.cell(), config.instance, 0)
+        }
+    }
+
+    fn circuit(x: u64) -> TestCircuit<Fp, 8> {
+        TestCircuit { x: Value::known(Fp::from(x)) }
+    }
+
+    fn isqrt(x: u64) -> u64 {
+        let s = (x as f64).sqrt() as u64;
+        (s.saturating_sub(2)..=s + 2)
+            .filter(|s| s.checked_mul(*s).map_or(false, |sq| sq <= x))
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn isqrt_circuit() {
+        for x in [0, 1, 2, 3, 4, 15, 16, 17, 1 << 40, u64::MAX] {
+            expect_satisfied(&circuit(x), vec![vec![Fp::from(isqrt(x))]]);
+        }
+
+        // 3 is not the root of 17, and the witnessed 4 isn't copied to it.
+        expect_failure(
+            &circuit(17),
+            vec![vec![Fp::from(3)]],
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+    }
+
+    #[test]
+    fn isqrt_max_bytes() {
+        // 2^120 - 1 is the largest root of a 30-byte value.
+        let root = Fp::from(1 << 60) * Fp::from(1 << 60) - Fp::from(1);
+        let square = root * root;
+        for x in [square, square + root + root] {
+            let circuit = TestCircuit::<_, MAX_BYTES> { x: Value::known(x) };
+            expect_satisfied(&circuit, vec![vec![root]]);
+        }
+
+        let circuit = TestCircuit::<_, MAX_BYTES> {
+            x: Value::known(square - Fp::from(1)),
+        };
+        expect_failure(
+            &circuit,
+            vec![vec![root]],
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "isqrt of at most 30 bytes, got 31")]
+    fn isqrt_too_wide() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let u8_table = U8Table::configure(&mut meta);
+        let [byte, acc] = [(); 2].map(|_| meta.advice_column());
+        let constant = meta.fixed_column();
+        let pack = PackConfig::configure(&mut meta, byte, acc, u8_table);
+        let lt = LtChip::<Fp, 31>::configure(&mut meta, pack.clone());
+        IsqrtChip::configure(&mut meta, lt, pack, constant);
+    }
+
+    gadget_proptest! {
+        isqrt_complete(x in any::<u64>()) {
+            circuit: circuit(x),
+            instances: vec![vec![Fp::from(isqrt(x))]],
+            valid: true,
+        }
+        isqrt_sound(x in any::<u64>(), delta in 1u64..4) {
+            circuit: circuit(x),
+            instances: vec![vec![Fp::from(isqrt(x) ^ delta)]],
+            valid: false,
+        }
+    }
+}
@@ -0,0 +1,219 @@
+//! Inner product `Σ w_i ⋅ x_i` of advice cells with fixed weights.
+//!
+//! Terms are laid out `K` per row, with a running sum in `acc`:
+//!
+//! | x[0..K]          | w[0..K] (fixed)  | acc                        | q_first | q_next |
+//! | x_0 ... x_{K-1}  | w_0 ... w_{K-1}  | Σ w_j ⋅ x_j                | 1       | 0      |
+//! | x_K ... x_{2K-1} | w_K ... w_{2K-1} | acc[prev] + Σ w_j ⋅ x_j    | 0       | 1      |
+//!
+//! The last row is padded with zero weights. Larger `K` means fewer rows but
+//! more columns, with the same degree 3 gate.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+use crate::field::Field;
+
+/// Config for [`LinearCombinationChip`].
+#[derive(Clone, Debug)]
+pub struct LinearCombinationConfig<const K: usize> {
+    q_first: Selector,
+    q_next: Selector,
+    x: [Column<Advice>; K],
+    w: [Column<Fixed>; K],
+    acc: Column<Advice>,
+}
+
+/// Chip computing inner products with `K` terms per row.
+#[derive(Clone, Debug)]
+pub struct LinearCombinationChip<F: Field, const K: usize> {
+    config: LinearCombinationConfig<K>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field, const K: usize> Chip<F> for LinearCombinationChip<F, K> {
+    type Config = LinearCombinationConfig<K>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field, const K: usize> LinearCombinationChip<F, K> {
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> LinearCombinationConfig<K> {
+        let q_first = meta.selector();
+        let q_next = meta.selector();
+        let x = [(); K].map(|_| meta.advice_column());
+        let w = [(); K].map(|_| meta.fixed_column());
+        let acc = meta.advice_column();
+
+        for column in x {
+            meta.enable_equality(column);
+        }
+        meta.enable_equality(acc);
+
+        meta.create_gate("linear combination", |meta| {
+            let q_first = meta.query_selector(q_first);
+            let q_next = meta.query_selector(q_next);
+            let x = x.map(|column| meta.query_advice(column, Rotation::cur()));
+            let w = w.map(|column| meta.query_fixed(column, Rotation::cur()));
+            let acc = meta.query_advice(acc, Rotation::cur());
+            let acc_prev = meta.query_advice(acc, Rotation::prev());
+
+            let row = (x.into_iter().zip(w)).fold(Expression::Constant(F::ZERO), |sum, (x, w)| sum + x * w);
+
+            vec![
+                q_first * (acc.clone() - row.clone()),
+                q_next * (acc - acc_prev - row),
+            ]
+        });
+
+        LinearCombinationConfig {
+            q_first,
+            q_next,
+            x,
+            w,
+            acc,
+        }
+    }
+
+    pub fn construct(config: LinearCombinationConfig<K>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns `Σ weights[i] ⋅ xs[i]`.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        xs: &[AssignedCell<F, F>],
+        weights: &[F],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        assert_eq!(xs.len(), weights.len());
+        assert!(!xs.is_empty());
+        let config = &self.config;
+
+        layouter.assign_region(
+            || "linear combination",
+            |mut region| {
+                let mut acc = Value::known(F::ZERO);
+                let mut acc_cell = None;
+
+                for (row, (xs, weights)) in xs.chunks(K).zip(weights.chunks(K)).enumerate() {
+                    if row == 0 {
+                        config.q_first.enable(&mut region, row)?;
+                    } else {
+                        config.q_next.enable(&mut region, row)?;
+                    }
+
+                    for j in 0..K {
+                        let w = weights.get(j).copied().unwrap_or(F::ZERO);
+                        let x = match xs.get(j) {
+                            Some(x) => x.copy_advice(|| format!("x[{j}]"), &mut region, config.x[j], row)?,
+                            None => region.assign_advice(|| "padding", config.x[j], row, || Value::known(F::ZERO))?,
+                        };
+                        region.assign_fixed(|| format!("w[{j}]"), config.w[j], row, || Value::known(w))?;
+                        acc = acc + x.value().copied() * Value::known(w);
+                    }
+                    acc_cell = Some(region.assign_advice(|| "acc", config.acc, row, || acc)?);
+                }
+
+                Ok(acc_cell.unwrap())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+
+    use super::{LinearCombinationChip, LinearCombinationConfig};
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::{Field, TestField as Fp},
+    };
+
+    const WEIGHTS: [u64; 5] = [1, 2, 3, 4, 5];
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig {
+        lc: LinearCombinationConfig<2>,
+        input: Column<Advice>,
+        instance: Column<Instance>,
+    }
+
+    /// Exposes `Σ WEIGHTS[i] ⋅ xs[i]`, two terms per row.
+    #[derive(Default)]
+    struct TestCircuit {
+        xs: [Value<u64>; 5],
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit {
+        type Config = TestCircuitConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let lc = LinearCombinationChip::configure(meta);
+            let input = meta.advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(input);
+            meta.enable_equality(instance);
+
+            TestCircuitConfig { lc, input, instance }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let chip = LinearCombinationChip::construct(config.lc);
+            let xs = layouter.assign_region(
+                || "xs",
+                |mut region| {
+                    (self.xs.iter().enumerate())
+                        .map(|(i, x)| region.assign_advice(|| "x", config.input, i, || x.map(F::from)))
+                        .collect::<Result<Vec<_>, _>>()
+                },
+            )?;
+
+            let weights = WEIGHTS.map(F::from);
+            let out = chip.assign(layouter.namespace(|| "inner product"), &xs, &weights)?;
+            layouter.constrain_instance(out.cell(), config.instance, 0)
+        }
+    }
+
+    #[test]
+    fn linear_combination() {
+        let circuit = TestCircuit {
+            xs: [10, 20, 30, 40, 50].map(Value::known),
+        };
+        expect_satisfied(&circuit, vec![vec![Fp::from(10 + 40 + 90 + 160 + 250)]]);
+
+        expect_failure(
+            &circuit,
+            vec![vec![Fp::from(551)]],
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+    }
+}
@@ -0,0 +1,275 @@
+//! Less-than comparison of values of at most `N_BYTES` bytes.
+//!
+//! Witnesses `lt = a < b` and `diff = a - b + lt ⋅ 2^(8 ⋅ N_BYTES)`, which is
+//! in `[0, 2^(8 ⋅ N_BYTES))` exactly when `lt` is right. `diff` is range
+//! checked by unpacking it into `N_BYTES` bytes with [`PackConfig`], and
+//! [`is_zero_2`](super::is_zero_2) on `diff` gives `eq = a == b` for free.
+//!
+//! | a | b | lt | diff | diff_inv | eq | q_lt |
+//! | a | b | lt | diff | 1/diff   | eq | 1    |
+//!
+//! Both `a` and `b` must be less than `2^(8 ⋅ N_BYTES)`, and `N_BYTES` at most
+//! [`MAX_BYTES`] so that `diff` doesn't wrap around.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use super::is_zero_2::{IsZeroChip, IsZeroConfig, IsZeroInstruction};
+use crate::{
+    circuits::pack::{PackConfig, MAX_BYTES},
+    field::Field,
+};
+
+/// Config for [`LtChip`].
+#[derive(Clone, Debug)]
+pub struct LtConfig<F: Field, const N_BYTES: usize> {
+    q_lt: Selector,
+    a: Column<Advice>,
+    b: Column<Advice>,
+    lt: Column<Advice>,
+    diff: Column<Advice>,
+    is_zero: IsZeroConfig<F>,
+    pack: PackConfig,
+}
+
+/// Results of a comparison.
+#[derive(Clone, Debug)]
+pub struct LtCells<F: Field> {
+    /// 1 if `a < b`, and 0 otherwise.
+    pub lt: AssignedCell<F, F>,
+    /// 1 if `a == b`, and 0 otherwise.
+    pub eq: AssignedCell<F, F>,
+}
+
+/// Chip comparing values of at most `N_BYTES` bytes.
+#[derive(Clone, Debug)]
+pub struct LtChip<F: Field, const N_BYTES: usize> {
+    config: LtConfig<F, N_BYTES>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field, const N_BYTES: usize> Chip<F> for LtChip<F, N_BYTES> {
+    type Config = LtConfig<F, N_BYTES>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field, const N_BYTES: usize> LtChip<F, N_BYTES> {
+    /// Configures the chip, range checking `diff` with `pack`.
+    pub fn configure(meta: &mut ConstraintSystem<F>, pack: PackConfig) -> LtConfig<F, N_BYTES> {
+        assert!(N_BYTES <= MAX_BYTES);
+
+        let q_lt = meta.selector();
+        let [a, b, lt, diff, diff_inv, eq] = [(); 6].map(|_| meta.advice_column());
+
+        for column in [a, b, lt, diff] {
+            meta.enable_equality(column);
+        }
+
+        meta.create_gate("lt", |meta| {
+            let q_lt = meta.query_selector(q_lt);
+            let [a, b, lt, diff] = [a, b, lt, diff].map(|column| meta.query_advice(column, Rotation::cur()));
+            let range = Expression::Constant(F::from(2).pow_vartime([8 * N_BYTES as u64]));
+
+            vec![
+                q_lt.clone() * lt.clone() * (Expression::Constant(F::ONE) - lt.clone()),
+                q_lt * (diff - (a - b + lt * range)),
+            ]
+        });
+
+        let is_zero = IsZeroChip::configure(
+            meta,
+            |meta| meta.query_selector(q_lt),
+            |meta| meta.query_advice(diff, Rotation::cur()),
+            diff_inv,
+            eq,
+        );
+
+        LtConfig {
+            q_lt,
+            a,
+            b,
+            lt,
+            diff,
+            is_zero,
+            pack,
+        }
+    }
+
+    pub fn construct(config: LtConfig<F, N_BYTES>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Compares `a` and `b`.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<LtCells<F>, Error> {
+        let config = &self.config;
+        let is_zero = IsZeroChip::construct(config.is_zero.clone());
+
+        let (diff, cells) = layouter.assign_region(
+            || "lt",
+            |mut region| {
+                config.q_lt.enable(&mut region, 0)?;
+                let a = a.copy_advice(|| "a", &mut region, config.a, 0)?;
+                let b = b.copy_advice(|| "b", &mut region, config.b, 0)?;
+
+                let lt = a.value().zip(b.value()).map(|(a, b)| a < b);
+                let diff = a.value().zip(b.value()).zip(lt).map(|((a, b), lt)| {
+                    let range = F::from(2).pow_vartime([8 * N_BYTES as u64]);
+                    *a - b + if lt { range } else { F::ZERO }
+                });
+
+                let lt = region.assign_advice(|| "lt", config.lt, 0, || lt.map(|lt| F::from(lt as u64)))?;
+                let diff = region.assign_advice(|| "diff", config.diff, 0, || diff)?;
+                let eq = is_zero.assign(&mut region, 0, diff.value().copied())?;
+
+                Ok((diff, LtCells { lt, eq }))
+            },
+        )?;
+
+        config.pack.unpack(layouter.namespace(|| "diff"), &diff, N_BYTES)?;
+
+        Ok(cells)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+    use proptest::prelude::any;
+
+    use super::{LtChip, LtConfig};
+    use crate::{
+        circuits::{pack::PackConfig, table::U8Table},
+        dev::{
+            fuzz::gadget_proptest,
+            mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        },
+        field::{Field, TestField as Fp},
+    };
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig<F: Field> {
+        lt: LtConfig<F, 8>,
+        u8_table: U8Table,
+        input: Column<Advice>,
+        instance: Column<Instance>,
+    }
+
+    /// Exposes `a < b` and `a == b` for 64-bit `a`, `b`.
+    #[derive(Default)]
+    struct TestCircuit {
+        a: Value<u64>,
+        b: Value<u64>,
+    }
+
+    impl TestCircuit {
+        fn new(a: u64, b: u64) -> Self {
+            Self {
+                a: Value::known(a),
+                b: Value::known(b),
+            }
+        }
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit {
+        type Config = TestCircuitConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let u8_table = U8Table::configure(meta);
+            let [input, byte, acc] = [(); 3].map(|_| meta.advice_column());
+            let pack = PackConfig::configure(meta, byte, acc, u8_table);
+            let lt = LtChip::configure(meta, pack);
+            let instance = meta.instance_column();
+            meta.enable_equality(input);
+            meta.enable_equality(instance);
+
+            TestCircuitConfig {
+                lt,
+                u8_table,
+                input,
+                instance,
+            }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let chip = LtChip::construct(config.lt);
+            config.u8_table.load(&mut layouter)?;
+
+            let (a, b) = layouter.assign_region(
+                || "inputs",
+                |mut region| {
+                    let a = region.assign_advice(|| "a", config.input, 0, || self.a.map(F::from))?;
+                    let b = region.assign_advice(|| "b", config.input, 1, || self.b.map(F::from))?;
+                    Ok((a, b))
+                },
+            )?;
+
+            let cells = chip.assign(layouter.namespace(|| "a < b"), &a, &b)?;
+            layouter.constrain_instance(cells.lt.cell(), config.instance, 0)?;
+            layouter.constrain_instance(cells.eq.cell(), config.instance, 1)
+        }
+    }
+
+    fn instances(a: u64, b: u64) -> Vec<Vec<Fp>> {
+        vec![vec![Fp::from((a < b) as u64), Fp::from((a == b) as u64)]]
+    }
+
+    #[test]
+    fn lt() {
+        for (a, b) in [(0, 0), (1, 2), (2, 1), (u64::MAX - 1, u64::MAX), (u64::MAX, 0)] {
+            expect_satisfied(&TestCircuit::new(a, b), instances(a, b));
+        }
+
+        // Claiming 2 < 1.
+        expect_failure(
+            &TestCircuit::new(2, 1),
+            vec![vec![Fp::from(1), Fp::from(0)]],
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+    }
+
+    gadget_proptest! {
+        lt_complete(a in any::<u64>(), b in any::<u64>()) {
+            circuit: TestCircuit::new(a, b),
+            instances: instances(a, b),
+            valid: true,
+        }
+        lt_sound(a in any::<u64>(), b in any::<u64>()) {
+            circuit: TestCircuit::new(a, b),
+            instances: vec![vec![Fp::from((a >= b) as u64), Fp::from((a == b) as u64)]],
+            valid: false,
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! Less-than comparison of 256-bit words.
+//!
+//! A word doesn't fit in a field element, so [`LtChip`] can't compare it
+//! directly. Instead its `lo` and `hi` 128-bit halves are compared, and
+//!
+//! ```text
+//! a < b = (a.hi == b.hi) ? (a.lo < b.lo) : (a.hi < b.hi)
+//! ```
+//!
+//! is picked with the [`SelectChip`].
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter},
+    plonk::Error,
+};
+
+use super::{
+    lt::{LtChip, LtConfig},
+    select::{SelectChip, SelectConfig},
+};
+use crate::{circuits::word::Word, field::Field};
+
+/// Config for [`LtWordChip`].
+#[derive(Clone, Debug)]
+pub struct LtWordConfig<F: Field> {
+    lt: LtConfig<F, 16>,
+    select: SelectConfig,
+}
+
+/// Chip comparing words given as `lo`/`hi` halves.
+#[derive(Clone, Debug)]
+pub struct LtWordChip<F: Field> {
+    config: LtWordConfig<F>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> Chip<F> for LtWordChip<F> {
+    type Config = LtWordConfig<F>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field> LtWordChip<F> {
+    /// Configures the chip from a 128-bit [`LtChip`] and a [`SelectChip`].
+    pub fn configure(lt: LtConfig<F, 16>, select: SelectConfig) -> LtWordConfig<F> {
+        LtWordConfig { lt, select }
+    }
+
+    pub fn construct(config: LtWordConfig<F>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns 1 if `a < b`, and 0 otherwise. The halves of both words must be
+    /// 128-bit.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &Word<AssignedCell<F, F>>,
+        b: &Word<AssignedCell<F, F>>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let lt = LtChip::construct(self.config.lt.clone());
+        let select = SelectChip::construct(self.config.select.clone());
+
+        let lo = lt.assign(layouter.namespace(|| "lo"), &a.lo(), &b.lo())?;
+        let hi = lt.assign(layouter.namespace(|| "hi"), &a.hi(), &b.hi())?;
+        select.select(layouter.namespace(|| "a < b"), &hi.eq, &lo.lt, &hi.lt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+
+    use super::{LtWordChip, LtWordConfig};
+    use crate::{
+        circuits::{
+            gadgets::{lt::LtChip, select::SelectChip},
+            pack::PackConfig,
+            table::U8Table,
+            word::Word,
+        },
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::{Field, TestField as Fp},
+    };
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig<F: Field> {
+        lt_word: LtWordConfig<F>,
+        u8_table: U8Table,
+        input: Column<Advice>,
+        instance: Column<Instance>,
+    }
+
+    /// Exposes `a < b` for words given as `[lo, hi]`.
+    #[derive(Default)]
+    struct TestCircuit {
+        a: Value<[u128; 2]>,
+        b: Value<[u128; 2]>,
+    }
+
+    impl TestCircuit {
+        fn new(a: [u128; 2], b: [u128; 2]) -> Self {
+            Self {
+                a: Value::known(a),
+                b: Value::known(b),
+            }
+        }
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit {
+        type Config = TestCircuitConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let u8_table = U8Table::configure(meta);
+            let [input, byte, acc] = [(); 3].map(|_| meta.advice_column());
+            let pack = PackConfig::configure(meta, byte, acc, u8_table);
+            let lt = LtChip::configure(meta, pack);
+            let select = SelectChip::configure(meta);
+            let lt_word = LtWordChip::configure(lt, select);
+            let instance = meta.instance_column();
+            meta.enable_equality(input);
+            meta.enable_equality(instance);
+
+            TestCircuitConfig {
+                lt_word,
+                u8_table,
+                input,
+                instance,
+            }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let chip = LtWordChip::construct(config.lt_word);
+            config.u8_table.load(&mut layouter)?;
+
+            let limbs = layouter.assign_region(
+                || "inputs",
+                |mut region| {
+                    (0..4)
+                        .map(|i| {
+                            let limb = self.a.zip(self.b).map(|(a, b)| F::from_u128([a, b][i / 2][i % 2]));
+                            region.assign_advice(|| "limb", config.input, i, || limb)
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                },
+            )?;
+            let a = Word::new([limbs[0].clone(), limbs[1].clone()]);
+            let b = Word::new([limbs[2].clone(), limbs[3].clone()]);
+
+            let lt = chip.assign(layouter.namespace(|| "a < b"), &a, &b)?;
+            layouter.constrain_instance(lt.cell(), config.instance, 0)
+        }
+    }
+
+    /// Native `a < b` of words given as `[lo, hi]`.
+    fn lt([a_lo, a_hi]: [u128; 2], [b_lo, b_hi]: [u128; 2]) -> bool {
+        (a_hi, a_lo) < (b_hi, b_lo)
+    }
+
+    #[test]
+    fn lt_word() {
+        let max = u128::MAX;
+        let cases = [
+            ([0, 0], [0, 0]),
+            ([1, 5], [2, 5]),
+            ([2, 5], [1, 5]),
+            ([max, 4], [0, 5]),
+            ([0, 5], [max, 4]),
+            ([max, max], [max, max]),
+            ([max - 1, max], [max, max]),
+        ];
+        for (a, b) in cases {
+            expect_satisfied(&TestCircuit::new(a, b), vec![vec![Fp::from(lt(a, b) as u64)]]);
+        }
+
+        // The `lo` halves alone would say `a < b`.
+        expect_failure(
+            &TestCircuit::new([0, 5], [max, 4]),
+            vec![vec![Fp::from(1)]],
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+    }
+}
@@ -0,0 +1,389 @@
+//! Merkle tree membership over a [`HashInstructions`] chip, [`PoseidonChip`]
+//! by default.
+//!
+//! A node is `H(left, right)`. A path from a leaf to
+//! the root gives, at each level, the sibling and whether the current node is
+//! the right child, and [`SelectChip`] orders the pair:
+//!
+//! ```text
+//! left  = is_right ? sibling : node
+//! right = is_right ? node : sibling
+//! node' = H(left, right)
+//! ```
+//!
+//! `is_right` is constrained to be boolean by the select gate. [`MerkleTree`]
+//! builds trees and paths off circuit.
+//!
+//! A multiproof shows membership of several leaves at known indices at once.
+//! Going up level by level, two known nodes that are siblings hash together,
+//! and each other known node takes its sibling from the proof, so nodes
+//! shared by the paths are only given and hashed once. The indices fix the
+//! order of the pairs, so no selects are needed.
+
+use std::collections::BTreeMap;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::Error,
+};
+
+use super::{
+    hash::{HashInstructions, HashSpec},
+    poseidon::{PoseidonChip, Spec},
+    select::{SelectChip, SelectConfig},
+};
+use crate::field::Field;
+
+/// Config for [`MerkleChip`], made of the configs of the chips it uses.
+#[derive(Clone, Debug)]
+pub struct MerkleConfig<F: Field, H: HashInstructions<F> = PoseidonChip<F>> {
+    pub hash: H::Config,
+    pub select: SelectConfig,
+}
+
+/// Chip computing Merkle roots from leaves and paths.
+#[derive(Clone, Debug)]
+pub struct MerkleChip<F: Field, H: HashInstructions<F> = PoseidonChip<F>> {
+    hash: H,
+    select: SelectChip<F>,
+}
+
+impl<F: Field, H: HashInstructions<F>> MerkleChip<F, H> {
+    pub fn construct(config: MerkleConfig<F, H>) -> Self {
+        Self {
+            hash: H::construct(config.hash),
+            select: SelectChip::construct(config.select),
+        }
+    }
+
+    /// Returns the root of the tree containing `leaf` at the end of `path`,
+    /// given from the leaf up as `(sibling, is_right)` pairs.
+    pub fn root(
+        &self,
+        mut layouter: impl Layouter<F>,
+        leaf: &AssignedCell<F, F>,
+        path: &[(AssignedCell<F, F>, AssignedCell<F, F>)],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let mut node = leaf.clone();
+        for (level, (sibling, is_right)) in path.iter().enumerate() {
+            let mut layouter = layouter.namespace(|| format!("level {level}"));
+            let left = self.select.select(layouter.namespace(|| "left"), is_right, sibling, &node)?;
+            let right = self.select.select(layouter.namespace(|| "right"), is_right, &node, sibling)?;
+            node = self.hash.hash2(layouter.namespace(|| "node"), &left, &right)?;
+        }
+        Ok(node)
+    }
+
+    /// Returns the root of the tree of depth `depth` containing each of
+    /// `leaves` at its index, given the `siblings` of [`MerkleTree::multiproof`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if an index is out of the tree, or if `siblings` doesn't have
+    /// exactly the nodes the multiproof needs.
+    pub fn multiproof_root(
+        &self,
+        mut layouter: impl Layouter<F>,
+        depth: usize,
+        leaves: &[(usize, AssignedCell<F, F>)],
+        siblings: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        assert!(!leaves.is_empty() && leaves.iter().all(|(index, _)| *index < 1 << depth));
+        let mut known: BTreeMap<_, _> = leaves.iter().cloned().collect();
+        assert_eq!(known.len(), leaves.len(), "duplicate leaf index");
+        let mut siblings = siblings.iter();
+
+        for level in 0..depth {
+            let mut layouter = layouter.namespace(|| format!("level {level}"));
+            let mut parents = BTreeMap::new();
+            while let Some((index, node)) = known.pop_first() {
+                let sibling = match known.remove(&(index ^ 1)) {
+                    Some(sibling) => sibling,
+                    None => siblings.next().expect("missing sibling").clone(),
+                };
+                let (left, right) = if index & 1 == 0 {
+                    (node, sibling)
+                } else {
+                    (sibling, node)
+                };
+                let parent = self
+                    .hash
+                    .hash2(layouter.namespace(|| format!("node {}", index >> 1)), &left, &right)?;
+                parents.insert(index >> 1, parent);
+            }
+            known = parents;
+        }
+
+        assert!(siblings.next().is_none(), "unused siblings");
+        Ok(known.remove(&0).unwrap())
+    }
+}
+
+/// Size of a circuit proving membership in a tree, its `Params` with the
+/// `circuit-params` feature.
+///
+/// The depth only sets the number of levels the circuit hashes, not its
+/// gates, so the circuits keep it along with their witness.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MerkleParams {
+    pub depth: usize,
+}
+
+impl Default for MerkleParams {
+    /// The depth of the trees of Tornado Cash.
+    fn default() -> Self {
+        Self { depth: 20 }
+    }
+}
+
+/// Off-circuit Merkle tree of `2^depth` leaves.
+#[derive(Clone, Debug)]
+pub struct MerkleTree<F: Field> {
+    /// The levels of the tree, from the leaves up to the root.
+    levels: Vec<Vec<F>>,
+}
+
+impl<F: Field> MerkleTree<F> {
+    /// Builds the Poseidon tree of depth `depth` over `leaves`, padded with
+    /// zeros.
+    pub fn new(depth: usize, leaves: Vec<F>) -> Self {
+        Self::with_spec(depth, leaves, &Spec::new())
+    }
+
+    /// Builds the tree of depth `depth` over `leaves`, padded with zeros,
+    /// hashing with `spec`.
+    pub fn with_spec(depth: usize, mut leaves: Vec<F>, spec: &impl HashSpec<F>) -> Self {
+        assert!(leaves.len() <= 1 << depth);
+        leaves.resize(1 << depth, F::ZERO);
+
+        let mut levels = vec![leaves];
+        for _ in 0..depth {
+            let level = (levels.last().unwrap().chunks(2)).map(|pair| spec.hash(pair)).collect();
+            levels.push(level);
+        }
+        Self { levels }
+    }
+
+    pub fn root(&self) -> F {
+        self.levels.last().unwrap()[0]
+    }
+
+    /// The `(sibling, is_right)` pairs from leaf `index` up to the root.
+    pub fn path(&self, mut index: usize) -> Vec<(F, bool)> {
+        let mut path = vec![];
+        for level in &self.levels[..self.levels.len() - 1] {
+            path.push((level[index ^ 1], index & 1 == 1));
+            index >>= 1;
+        }
+        path
+    }
+
+    /// The siblings proving the leaves at `indices` at once, level by level
+    /// from the leaves up and by increasing index within a level, skipping
+    /// the nodes the leaves already determine.
+    pub fn multiproof(&self, indices: &[usize]) -> Vec<F> {
+        let mut known = indices.to_vec();
+        let mut siblings = vec![];
+        for level in &self.levels[..self.levels.len() - 1] {
+            known.sort_unstable();
+            known.dedup();
+            for index in &known {
+                if !known.contains(&(index ^ 1)) {
+                    siblings.push(level[index ^ 1]);
+                }
+            }
+            known = known.iter().map(|index| index >> 1).collect();
+        }
+        siblings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+    };
+
+    use super::{MerkleChip, MerkleConfig, MerkleTree};
+    use crate::{
+        circuits::{
+            gadgets::{poseidon::PoseidonChip, select::SelectChip},
+            instance::{InstanceColumns, InstanceLayout},
+        },
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::{Field, TestField as Fp},
+    };
+
+    /// Exposes the root computed from `leaf` and `path`.
+    #[derive(Default)]
+    struct TestCircuit {
+        leaf: Fp,
+        path: Vec<(Fp, bool)>,
+    }
+
+    impl Circuit<Fp> for TestCircuit {
+        type Config = (MerkleConfig<Fp>, Column<Advice>, InstanceColumns);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let [advice, s0, s1, s2, i0, i1] = [(); 6].map(|_| meta.advice_column());
+            let constant = meta.fixed_column();
+            meta.enable_equality(advice);
+
+            let config = MerkleConfig {
+                hash: PoseidonChip::configure(meta, [s0, s1, s2], [i0, i1], constant),
+                select: SelectChip::configure(meta),
+            };
+            (config, advice, InstanceLayout::new().column(["root"]).configure(meta))
+        }
+
+        fn synthesize(
+            &self,
+            (config, advice, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let (leaf, path) = layouter.assign_region(
+                || "inputs",
+                |mut region| {
+                    let leaf = region.assign_advice(|| "leaf", advice, 0, || Value::known(self.leaf))?;
+                    let mut path = vec![];
+                    for (level, (sibling, is_right)) in self.path.iter().enumerate() {
+                        let is_right = Value::known(Fp::from(*is_right as u64));
+                        path.push((
+                            region.assign_advice(|| "sibling", advice, 2 * level + 1, || Value::known(*sibling))?,
+                            region.assign_advice(|| "is_right", advice, 2 * level + 2, || is_right)?,
+                        ));
+                    }
+                    Ok((leaf, path))
+                },
+            )?;
+
+            let root = MerkleChip::construct(config).root(layouter.namespace(|| "merkle"), &leaf, &path)?;
+            instance.expose_public(&mut layouter, &root, 0)
+        }
+    }
+
+    /// Exposes the root computed from the multiproof of `leaves`.
+    #[derive(Default)]
+    struct MultiproofCircuit {
+        depth: usize,
+        leaves: Vec<(usize, Fp)>,
+        siblings: Vec<Fp>,
+    }
+
+    impl Circuit<Fp> for MultiproofCircuit {
+        type Config = (MerkleConfig<Fp>, Column<Advice>, InstanceColumns);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                depth: self.depth,
+                leaves: (self.leaves.iter()).map(|(index, _)| (*index, Fp::ZERO)).collect(),
+                siblings: vec![Fp::ZERO; self.siblings.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            TestCircuit::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            (config, advice, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let (leaves, siblings) = layouter.assign_region(
+                || "inputs",
+                |mut region| {
+                    let values = (self.leaves.iter().map(|(_, leaf)| leaf)).chain(&self.siblings);
+                    let cells = (values.enumerate())
+                        .map(|(i, value)| region.assign_advice(|| "node", advice, i, || Value::known(*value)))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let (leaves, siblings) = cells.split_at(self.leaves.len());
+                    let leaves = (self.leaves.iter().zip(leaves)).map(|((index, _), cell)| (*index, cell.clone()));
+                    Ok((leaves.collect::<Vec<_>>(), siblings.to_vec()))
+                },
+            )?;
+
+            let chip = MerkleChip::construct(config);
+            let root = chip.multiproof_root(layouter.namespace(|| "multiproof"), self.depth, &leaves, &siblings)?;
+            instance.expose_public(&mut layouter, &root, 0)
+        }
+    }
+
+    #[test]
+    fn merkle() {
+        let leaves: Vec<_> = (1..=6).map(Fp::from).collect();
+        let tree = MerkleTree::new(3, leaves.clone());
+
+        for index in [0, 5, 7] {
+            let leaf = leaves.get(index).copied().unwrap_or(Fp::ZERO);
+            let circuit = TestCircuit {
+                leaf,
+                path: tree.path(index),
+            };
+            expect_satisfied(&circuit, vec![vec![tree.root()]]);
+        }
+
+        // Leaf 5 with the path of leaf 4.
+        let circuit = TestCircuit {
+            leaf: leaves[5],
+            path: tree.path(4),
+        };
+        expect_failure(
+            &circuit,
+            vec![vec![tree.root()]],
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+    }
+
+    #[test]
+    fn multiproof() {
+        let leaves: Vec<_> = (1..=6).map(Fp::from).collect();
+        let tree = MerkleTree::new(3, leaves.clone());
+
+        // Leaves 0 and 1 are siblings, and their parent is the sibling of
+        // nothing else, so three nodes prove all of them.
+        let indices = [0, 1, 5];
+        let siblings = tree.multiproof(&indices);
+        assert_eq!(siblings.len(), 3);
+        let circuit = MultiproofCircuit {
+            depth: 3,
+            leaves: indices.iter().map(|index| (*index, leaves[*index])).collect(),
+            siblings: siblings.clone(),
+        };
+        expect_satisfied(&circuit, vec![vec![tree.root()]]);
+
+        // A single leaf needs its whole path.
+        assert_eq!(
+            tree.multiproof(&[3]),
+            tree.path(3).iter().map(|(sibling, _)| *sibling).collect::<Vec<_>>()
+        );
+
+        // The siblings out of order.
+        let mut reordered = siblings;
+        reordered.swap(1, 2);
+        let circuit = MultiproofCircuit {
+            siblings: reordered,
+            ..circuit
+        };
+        expect_failure(
+            &circuit,
+            vec![vec![tree.root()]],
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+    }
+}
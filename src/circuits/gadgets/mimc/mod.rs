@@ -0,0 +1,303 @@
+//! MiMC hash over a two-element Feistel state, one round per row.
+//!
+//! Inputs are absorbed one at a time into the left half `l` of the state,
+//! each absorption followed by a permutation:
+//!
+//! | state    | input | round_constant | q_absorb | q_round |
+//! | 0, 0     | in[0] |                | 1        | 0       |
+//! | l, r     |       | c[0]           | 0        | 1       |
+//! | ...      |       | ...            | 0        | 1       |
+//! | l', r'   | in[1] |                | 1        | 0       |
+//! | ...      |       | ...            | 0        | ...     |
+//! | hash, r  |       |                | 0        | 0       |
+//!
+//! ```text
+//! absorb: next = (l + in, r)
+//! round:  next = (r + (l + c)^5, l)
+//! ```
+//!
+//! The round gate is degree 6, like Poseidon's, but a permutation takes
+//! [`ROUNDS`] rows for a single input, so MiMC is the cheapest per row and the
+//! most expensive per input of the [`HashInstructions`] chips.
+
+mod spec;
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Fixed, Selector},
+    poly::Rotation,
+};
+
+pub use spec::{Spec, RATE, ROUNDS, WIDTH};
+
+use super::hash::HashInstructions;
+use crate::field::Field;
+
+/// Config for [`MimcChip`].
+#[derive(Clone, Debug)]
+pub struct MimcConfig<F: Field> {
+    q_absorb: Selector,
+    q_round: Selector,
+    state: [Column<Advice>; WIDTH],
+    input: Column<Advice>,
+    round_constant: Column<Fixed>,
+    spec: Spec<F>,
+}
+
+/// Chip hashing cells with MiMC.
+#[derive(Clone, Debug)]
+pub struct MimcChip<F: Field> {
+    config: MimcConfig<F>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> Chip<F> for MimcChip<F> {
+    type Config = MimcConfig<F>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field> MimcChip<F> {
+    /// Configures the chip on the `state` and `input` columns, initializing
+    /// and padding the state with `constant`.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        state: [Column<Advice>; WIDTH],
+        input: Column<Advice>,
+        constant: Column<Fixed>,
+    ) -> MimcConfig<F> {
+        let q_absorb = meta.selector();
+        let q_round = meta.selector();
+        let round_constant = meta.fixed_column();
+
+        for column in state.into_iter().chain([input]) {
+            meta.enable_equality(column);
+        }
+        meta.enable_constant(constant);
+
+        meta.create_gate("mimc absorb", |meta| {
+            let q_absorb = meta.query_selector(q_absorb);
+            let [l, r] = state.map(|column| meta.query_advice(column, Rotation::cur()));
+            let [l_next, r_next] = state.map(|column| meta.query_advice(column, Rotation::next()));
+            let input = meta.query_advice(input, Rotation::cur());
+
+            vec![q_absorb.clone() * (l_next - l - input), q_absorb * (r_next - r)]
+        });
+
+        meta.create_gate("mimc round", |meta| {
+            let q_round = meta.query_selector(q_round);
+            let [l, r] = state.map(|column| meta.query_advice(column, Rotation::cur()));
+            let [l_next, r_next] = state.map(|column| meta.query_advice(column, Rotation::next()));
+            let x = l.clone() + meta.query_fixed(round_constant, Rotation::cur());
+            let sboxed = x.clone() * x.clone() * x.clone() * x.clone() * x;
+
+            vec![q_round.clone() * (l_next - r - sboxed), q_round * (r_next - l)]
+        });
+
+        MimcConfig {
+            q_absorb,
+            q_round,
+            state,
+            input,
+            round_constant,
+            spec: Spec::new(),
+        }
+    }
+
+    pub fn construct(config: MimcConfig<F>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Rows of the region hashing `num_inputs` inputs.
+    pub fn rows(num_inputs: usize) -> usize {
+        spec::chunks(num_inputs).len() * (ROUNDS + 1) + 1
+    }
+
+    /// Returns the hash of `inputs`, as [`Spec::hash`].
+    pub fn hash(
+        &self,
+        mut layouter: impl Layouter<F>,
+        inputs: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+
+        layouter.assign_region(
+            || "mimc",
+            |mut region| {
+                let mut cells = Vec::with_capacity(WIDTH);
+                for (i, column) in config.state.iter().enumerate() {
+                    cells.push(region.assign_advice_from_constant(|| format!("state {i}"), *column, 0, F::ZERO)?);
+                }
+                let mut state: Value<[F; WIDTH]> = Value::known([F::ZERO; WIDTH]);
+
+                let mut offset = 0;
+                for chunk in spec::chunks(inputs.len()) {
+                    config.q_absorb.enable(&mut region, offset)?;
+                    let input = match inputs.get(chunk) {
+                        Some(input) => input.copy_advice(|| "input", &mut region, config.input, offset)?,
+                        None => region.assign_advice_from_constant(|| "padding", config.input, offset, F::ZERO)?,
+                    };
+                    state = state.zip(input.value()).map(|(mut state, input)| {
+                        state[0] += input;
+                        state
+                    });
+                    offset += 1;
+                    cells = self.assign_state(&mut region, offset, state)?;
+
+                    for round in 0..ROUNDS {
+                        config.q_round.enable(&mut region, offset)?;
+                        let constant = Value::known(config.spec.round_constants[round]);
+                        region.assign_fixed(|| format!("c {round}"), config.round_constant, offset, || constant)?;
+                        state = state.map(|mut state| {
+                            config.spec.round(round, &mut state);
+                            state
+                        });
+                        offset += 1;
+                        cells = self.assign_state(&mut region, offset, state)?;
+                    }
+                }
+
+                Ok(cells.swap_remove(0))
+            },
+        )
+    }
+
+    fn assign_state(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        state: Value<[F; WIDTH]>,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        (self.config.state.iter().enumerate())
+            .map(|(i, column)| {
+                region.assign_advice(|| format!("state {i}"), *column, offset, || state.map(|state| state[i]))
+            })
+            .collect()
+    }
+}
+
+/// Hashes on the first two `state` columns and the first `input` column,
+/// leaving the others free.
+impl<F: Field> HashInstructions<F> for MimcChip<F> {
+    type Config = MimcConfig<F>;
+    type Spec = Spec<F>;
+
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        state: [Column<Advice>; 3],
+        input: [Column<Advice>; 2],
+        constant: Column<Fixed>,
+    ) -> Self::Config {
+        MimcChip::configure(meta, [state[0], state[1]], input[0], constant)
+    }
+
+    fn construct(config: Self::Config) -> Self {
+        MimcChip::construct(config)
+    }
+
+    fn hash(&self, layouter: impl Layouter<F>, inputs: &[AssignedCell<F, F>]) -> Result<AssignedCell<F, F>, Error> {
+        MimcChip::hash(self, layouter, inputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+
+    use super::{MimcChip, MimcConfig, Spec};
+    use crate::{
+        circuits::gadgets::poseidon,
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::{Field, TestField as Fp},
+    };
+
+    /// Exposes the hash of `inputs`.
+    #[derive(Default)]
+    struct TestCircuit {
+        inputs: Vec<Value<u64>>,
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit {
+        type Config = (MimcConfig<F>, Column<Advice>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                inputs: vec![Value::unknown(); self.inputs.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let [input, l, r, i0] = [(); 4].map(|_| meta.advice_column());
+            let constant = meta.fixed_column();
+            let mimc = MimcChip::configure(meta, [l, r], i0, constant);
+            let instance = meta.instance_column();
+            meta.enable_equality(input);
+            meta.enable_equality(instance);
+            (mimc, input, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, input, instance): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = MimcChip::construct(config);
+
+            let inputs = layouter.assign_region(
+                || "inputs",
+                |mut region| {
+                    (self.inputs.iter().enumerate())
+                        .map(|(i, value)| region.assign_advice(|| "input", input, i, || value.map(F::from)))
+                        .collect::<Result<Vec<_>, _>>()
+                },
+            )?;
+
+            let hash = chip.hash(layouter.namespace(|| "hash"), &inputs)?;
+            layouter.constrain_instance(hash.cell(), instance, 0)
+        }
+    }
+
+    #[test]
+    fn mimc_hash() {
+        let spec = Spec::<Fp>::new();
+        // One, two and more inputs, each taking its own permutation.
+        for inputs in [vec![7], vec![1, 2], vec![1, 2, 3, 4, 5]] {
+            let elements: Vec<_> = inputs.iter().map(|input| Fp::from(*input)).collect();
+            let hash = spec.hash(&elements);
+            assert_ne!(hash, poseidon::Spec::new().hash(&elements));
+            let circuit = TestCircuit {
+                inputs: inputs.into_iter().map(Value::known).collect(),
+            };
+            expect_satisfied(&circuit, vec![vec![hash]]);
+        }
+
+        let circuit = TestCircuit {
+            inputs: vec![Value::known(1), Value::known(2)],
+        };
+        expect_failure(
+            &circuit,
+            vec![vec![spec.hash(&[Fp::from(2), Fp::from(1)])]],
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+    }
+}
@@ -0,0 +1,106 @@
+//! Parameters of the MiMC Feistel permutation, and its native evaluation.
+//!
+//! A round maps the halves `(l, r)` of the state to `(r + (l + c)^5, l)`.
+//! MiMC-2n/n needs `2 ⋅ ⌈log_5 p⌉` rounds for a prime `p` of `n` bits, which
+//! is [`ROUNDS`] for the 254 and 255-bit fields of this crate, as in the
+//! MiMCSponge of circomlib.
+//!
+//! The round constants are drawn from the Grain LFSR of
+//! [`poseidon`](crate::circuits::gadgets::poseidon), seeded with a width of
+//! [`WIDTH`], [`ROUNDS`] full rounds and no partial rounds, rather than from
+//! Keccak as in circomlib. Every round also swaps the halves, where circomlib
+//! doesn't swap after the last one, so hashes don't match other
+//! implementations.
+
+use crate::{
+    circuits::gadgets::{hash::HashSpec, poseidon::Grain},
+    field::Field,
+};
+
+/// Elements of the state, the two halves of the Feistel network.
+pub const WIDTH: usize = 2;
+/// Elements absorbed per permutation.
+pub const RATE: usize = 1;
+/// Rounds of a permutation.
+pub const ROUNDS: usize = 220;
+
+/// Round constants of the permutation.
+#[derive(Clone, Debug)]
+pub struct Spec<F: Field> {
+    pub round_constants: Vec<F>,
+}
+
+impl<F: Field> Default for Spec<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: Field> Spec<F> {
+    /// Derives the round constants from the Grain LFSR.
+    pub fn new() -> Self {
+        let mut grain = Grain::new(F::NUM_BITS as u16, WIDTH, ROUNDS, 0);
+        let round_constants = (0..ROUNDS).map(|_| grain.next_field_element::<F>()).collect();
+
+        Self { round_constants }
+    }
+
+    /// Applies round `round` to `state`.
+    pub fn round(&self, round: usize, state: &mut [F; WIDTH]) {
+        let [l, r] = *state;
+        let x = l + self.round_constants[round];
+        *state = [r + x.square().square() * x, l];
+    }
+
+    /// Applies the permutation to `state`.
+    pub fn permute(&self, state: &mut [F; WIDTH]) {
+        for round in 0..ROUNDS {
+            self.round(round, state);
+        }
+    }
+
+    /// Hashes `inputs` with a sponge: each input is added to the left half of
+    /// the state, starting at zero, before a permutation, and the hash is the
+    /// left half of the final state. No inputs hash as a single zero.
+    pub fn hash(&self, inputs: &[F]) -> F {
+        let mut state = [F::ZERO; WIDTH];
+        for chunk in chunks(inputs.len()) {
+            state[0] += inputs.get(chunk).copied().unwrap_or(F::ZERO);
+            self.permute(&mut state);
+        }
+        state[0]
+    }
+}
+
+impl<F: Field> HashSpec<F> for Spec<F> {
+    fn hash(&self, inputs: &[F]) -> F {
+        Spec::hash(self, inputs)
+    }
+}
+
+/// The permutations needed to absorb `num_inputs` inputs, at least one.
+pub(super) fn chunks(num_inputs: usize) -> std::ops::Range<usize> {
+    0..num_inputs.div_ceil(RATE).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Spec, ROUNDS};
+    use crate::field::TestField as Fp;
+
+    #[test]
+    fn feistel_inverse() {
+        let spec = Spec::<Fp>::new();
+        let mut state = [1, 2].map(Fp::from);
+        spec.permute(&mut state);
+
+        // Each round is undone from its output alone: `l` is the new right
+        // half, and `r` the new left half minus the S-box of `l`.
+        for round in (0..ROUNDS).rev() {
+            let [l, r] = state;
+            let x = r + spec.round_constants[round];
+            state = [r, l - x * x * x * x * x];
+        }
+        assert_eq!(state, [1, 2].map(Fp::from));
+    }
+}
@@ -1,2 +1,37 @@
 mod is_zero_1;
-mod is_zero;
\ No newline at end of file
+pub mod accumulator;
+pub mod base64;
+pub mod bits;
+pub mod bitwise;
+pub mod canonical;
+pub mod crumbs;
+pub mod div;
+pub mod endianness;
+pub mod gate;
+pub mod grand_product;
+pub mod hash;
+pub mod index;
+pub mod invert;
+pub mod is_zero;
+pub mod is_zero_2;
+pub mod is_zero_vec;
+pub mod isqrt;
+pub mod linear_combination;
+pub mod lt;
+pub mod lt_word;
+pub mod merkle;
+pub mod mimc;
+pub mod one_hot;
+pub mod poseidon;
+pub mod poseidon2;
+pub mod prng;
+pub mod rescue;
+pub mod ripemd160;
+pub mod select;
+pub mod shift;
+pub mod sorted;
+pub mod sqrt;
+pub mod substring;
+pub mod transcript;
+pub mod word32;
+pub mod word_add;
\ No newline at end of file
@@ -0,0 +1,212 @@
+//! One-hot vectors: `N` boolean cells of a row, exactly one of which is set.
+//!
+//! | bits[0] | ... | bits[N - 1] | q_enable |
+//! | 0       | 1   | 0           | 1        |
+//!
+//! - every `bits[j]` is boolean,
+//! - `Σ bits[j] = 1`.
+//!
+//! The position of the set bit, `Σ j ⋅ bits[j]`, is available to custom gates
+//! through [`OneHotConfig::index`].
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, VirtualCells},
+    poly::Rotation,
+};
+
+use crate::field::Field;
+
+/// Config for [`OneHotChip`].
+#[derive(Clone, Debug)]
+pub struct OneHotConfig<F, const N: usize> {
+    /// The bits of the vector.
+    pub bits: [Column<Advice>; N],
+    bits_expressions: [Expression<F>; N],
+}
+
+impl<F: Field, const N: usize> OneHotConfig<F, N> {
+    /// The bits, for use in custom gates at the offset they are assigned at.
+    pub fn bits(&self) -> &[Expression<F>; N] {
+        &self.bits_expressions
+    }
+
+    /// The encoded index `Σ j ⋅ bits[j]`.
+    pub fn index(&self) -> Expression<F> {
+        (self.bits_expressions.iter().enumerate())
+            .fold(Expression::Constant(F::ZERO), |acc, (j, bit)| {
+                acc + Expression::Constant(F::from(j as u64)) * bit.clone()
+            })
+    }
+}
+
+/// Chip constraining one-hot vectors.
+#[derive(Clone, Debug)]
+pub struct OneHotChip<F, const N: usize> {
+    config: OneHotConfig<F, N>,
+}
+
+impl<F: Field, const N: usize> Chip<F> for OneHotChip<F, N> {
+    type Config = OneHotConfig<F, N>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field, const N: usize> OneHotChip<F, N> {
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        bits: [Column<Advice>; N],
+    ) -> OneHotConfig<F, N> {
+        // dummy initialization
+        let mut bits_expressions = [(); N].map(|_| Expression::Constant(F::ZERO));
+
+        meta.create_gate("one hot", |meta| {
+            let q_enable = q_enable(meta);
+            bits_expressions = bits.map(|column| meta.query_advice(column, Rotation::cur()));
+
+            let one = Expression::Constant(F::ONE);
+            let sum = (bits_expressions.iter()).fold(Expression::Constant(F::ZERO), |acc, bit| acc + bit.clone());
+
+            let mut constraints = vec![q_enable.clone() * (sum - one.clone())];
+            constraints.extend(
+                (bits_expressions.iter()).map(|bit| q_enable.clone() * bit.clone() * (one.clone() - bit.clone())),
+            );
+            constraints
+        });
+
+        OneHotConfig { bits, bits_expressions }
+    }
+
+    pub fn construct(config: OneHotConfig<F, N>) -> Self {
+        Self { config }
+    }
+
+    /// Assigns the one-hot encoding of `index`. An `index` outside `[0, N)`
+    /// is encoded as all zeros, which fails verification.
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        index: Value<F>,
+    ) -> Result<[AssignedCell<F, F>; N], Error> {
+        let cells = (self.config.bits.iter().enumerate())
+            .map(|(j, column)| {
+                let bit = index.map(|index| F::from((index == F::from(j as u64)) as u64));
+                region.assign_advice(|| format!("bits[{j}]"), *column, offset, || bit)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(cells.try_into().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance, Selector},
+        poly::Rotation,
+    };
+
+    use super::{OneHotChip, OneHotConfig};
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::{Field, TestField as Fp},
+    };
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig<F> {
+        q_enable: Selector,
+        one_hot: OneHotConfig<F, 4>,
+        index: Column<Advice>,
+        instance: Column<Instance>,
+    }
+
+    /// Assigns raw `bits` and exposes the index they encode.
+    #[derive(Default)]
+    struct TestCircuit {
+        bits: [u64; 4],
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit {
+        type Config = TestCircuitConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let q_enable = meta.selector();
+            let bits = [(); 4].map(|_| meta.advice_column());
+            let index = meta.advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(index);
+            meta.enable_equality(instance);
+
+            let one_hot = OneHotChip::configure(meta, |meta| meta.query_selector(q_enable), bits);
+
+            meta.create_gate("index", |meta| {
+                let q_enable = meta.query_selector(q_enable);
+                let index = meta.query_advice(index, Rotation::cur());
+                vec![q_enable * (index - one_hot.index())]
+            });
+
+            TestCircuitConfig {
+                q_enable,
+                one_hot,
+                index,
+                instance,
+            }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let index = layouter.assign_region(
+                || "one hot",
+                |mut region| {
+                    config.q_enable.enable(&mut region, 0)?;
+                    for (column, bit) in config.one_hot.bits.iter().zip(self.bits) {
+                        region.assign_advice(|| "bit", *column, 0, || Value::known(F::from(bit)))?;
+                    }
+                    let index = self.bits.iter().enumerate().map(|(j, bit)| j as u64 * bit).sum::<u64>();
+                    region.assign_advice(|| "index", config.index, 0, || Value::known(F::from(index)))
+                },
+            )?;
+            layouter.constrain_instance(index.cell(), config.instance, 0)
+        }
+    }
+
+    #[test]
+    fn one_hot() {
+        for index in 0..4 {
+            let mut bits = [0; 4];
+            bits[index] = 1;
+            expect_satisfied(&TestCircuit { bits }, vec![vec![Fp::from(index as u64)]]);
+        }
+
+        // No bit, two bits, and a bit that is not boolean.
+        let invalid = [([0, 0, 0, 0], 0), ([0, 1, 1, 0], 3), ([2, 0, 0, 0], 0)];
+        for (bits, index) in invalid {
+            expect_failure(
+                &TestCircuit { bits },
+                vec![vec![Fp::from(index)]],
+                FailureMatcher::Constraint {
+                    gate: "one hot",
+                    location: Location::InRegion {
+                        region: "one hot",
+                        offset: 0,
+                    },
+                },
+            );
+        }
+    }
+}
@@ -0,0 +1,347 @@
+//! Poseidon hash over a width 3 state, one round per row.
+//!
+//! Inputs are absorbed [`RATE`] at a time into the last two elements of the
+//! state, the first being the capacity, and each absorption is followed by a
+//! permutation. A hash of `n` inputs is laid out in a single region:
+//!
+//! | state    | input       | round_constants | q_absorb | q_full | q_partial |
+//! | 0, 0, 0  | in[0..2]    |                 | 1        | 0      | 0         |
+//! | s        |             | rc[0]           | 0        | 1      | 0         |
+//! | ...      |             | ...             | 0        | ...    | ...       |
+//! | s'       | in[2..4]    |                 | 1        | 0      | 0         |
+//! | ...      |             | ...             | 0        | ...    | ...       |
+//! | hash, ...|             |                 | 0        | 0      | 0         |
+//!
+//! An absorption row adds its inputs to the state of the next row, and a
+//! round row constrains the next state to be the round applied to its own:
+//!
+//! ```text
+//! full:    next[i] = Σ_j mds[i][j] ⋅ (cur[j] + rc[j])^5
+//! partial: next[i] = mds[i][0] ⋅ (cur[0] + rc[0])^5 + Σ_{j>0} mds[i][j] ⋅ (cur[j] + rc[j])
+//! ```
+//!
+//! The S-box makes the round gates degree 6.
+
+mod spec;
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+pub(crate) use spec::Grain;
+pub use spec::{Spec, FULL_ROUNDS, PARTIAL_ROUNDS, RATE, ROUNDS, WIDTH};
+
+use super::hash::HashInstructions;
+use crate::field::Field;
+
+/// Config for [`PoseidonChip`].
+#[derive(Clone, Debug)]
+pub struct PoseidonConfig<F: Field> {
+    q_absorb: Selector,
+    q_full: Selector,
+    q_partial: Selector,
+    state: [Column<Advice>; WIDTH],
+    input: [Column<Advice>; RATE],
+    round_constants: [Column<Fixed>; WIDTH],
+    spec: Spec<F>,
+}
+
+/// Chip hashing cells with Poseidon.
+#[derive(Clone, Debug)]
+pub struct PoseidonChip<F: Field> {
+    config: PoseidonConfig<F>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> Chip<F> for PoseidonChip<F> {
+    type Config = PoseidonConfig<F>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field> PoseidonChip<F> {
+    /// Configures the chip on the `state` and `input` columns, initializing
+    /// and padding the state with `constant`.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        state: [Column<Advice>; WIDTH],
+        input: [Column<Advice>; RATE],
+        constant: Column<Fixed>,
+    ) -> PoseidonConfig<F> {
+        let q_absorb = meta.selector();
+        let q_full = meta.selector();
+        let q_partial = meta.selector();
+        let round_constants = [(); WIDTH].map(|_| meta.fixed_column());
+        let spec = Spec::new();
+
+        for column in state.into_iter().chain(input) {
+            meta.enable_equality(column);
+        }
+        meta.enable_constant(constant);
+
+        meta.create_gate("poseidon absorb", |meta| {
+            let q_absorb = meta.query_selector(q_absorb);
+            let cur = state.map(|column| meta.query_advice(column, Rotation::cur()));
+            let next = state.map(|column| meta.query_advice(column, Rotation::next()));
+            let input = input.map(|column| meta.query_advice(column, Rotation::cur()));
+
+            (0..WIDTH)
+                .map(|i| {
+                    let absorbed = if i == 0 { cur[0].clone() } else { cur[i].clone() + input[i - 1].clone() };
+                    q_absorb.clone() * (next[i].clone() - absorbed)
+                })
+                .collect::<Vec<_>>()
+        });
+
+        for (name, q_round, full) in [
+            ("poseidon full round", q_full, true),
+            ("poseidon partial round", q_partial, false),
+        ] {
+            meta.create_gate(name, |meta| {
+                let q_round = meta.query_selector(q_round);
+                let next = state.map(|column| meta.query_advice(column, Rotation::next()));
+                let sboxed: Vec<_> = (0..WIDTH)
+                    .map(|j| {
+                        let x = meta.query_advice(state[j], Rotation::cur())
+                            + meta.query_fixed(round_constants[j], Rotation::cur());
+                        if j == 0 || full {
+                            x.clone() * x.clone() * x.clone() * x.clone() * x
+                        } else {
+                            x
+                        }
+                    })
+                    .collect();
+
+                (next.into_iter().zip(&spec.mds))
+                    .map(|(next, row)| {
+                        let mixed = (row.iter().zip(&sboxed))
+                            .fold(Expression::Constant(F::ZERO), |acc, (m, x)| {
+                                acc + Expression::Constant(*m) * x.clone()
+                            });
+                        q_round.clone() * (next - mixed)
+                    })
+                    .collect::<Vec<_>>()
+            });
+        }
+
+        PoseidonConfig {
+            q_absorb,
+            q_full,
+            q_partial,
+            state,
+            input,
+            round_constants,
+            spec,
+        }
+    }
+
+    pub fn construct(config: PoseidonConfig<F>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Rows of the region hashing `num_inputs` inputs.
+    pub fn rows(num_inputs: usize) -> usize {
+        spec::chunks(num_inputs).len() * (ROUNDS + 1) + 1
+    }
+
+    /// Returns the hash of `inputs`, as [`Spec::hash`].
+    pub fn hash(
+        &self,
+        mut layouter: impl Layouter<F>,
+        inputs: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+
+        layouter.assign_region(
+            || "poseidon",
+            |mut region| {
+                let mut cells = Vec::with_capacity(WIDTH);
+                for (i, column) in config.state.iter().enumerate() {
+                    cells.push(region.assign_advice_from_constant(|| format!("state {i}"), *column, 0, F::ZERO)?);
+                }
+                let mut state: Value<[F; WIDTH]> = Value::known([F::ZERO; WIDTH]);
+
+                let mut offset = 0;
+                for chunk in spec::chunks(inputs.len()) {
+                    config.q_absorb.enable(&mut region, offset)?;
+                    for (k, column) in config.input.iter().enumerate() {
+                        let input = match inputs.get(chunk * RATE + k) {
+                            Some(input) => input.copy_advice(|| "input", &mut region, *column, offset)?,
+                            None => region.assign_advice_from_constant(|| "padding", *column, offset, F::ZERO)?,
+                        };
+                        state = state.zip(input.value()).map(|(mut state, input)| {
+                            state[k + 1] += input;
+                            state
+                        });
+                    }
+                    offset += 1;
+                    cells = self.assign_state(&mut region, offset, state)?;
+
+                    for round in 0..ROUNDS {
+                        if Spec::<F>::is_full_round(round) {
+                            config.q_full.enable(&mut region, offset)?;
+                        } else {
+                            config.q_partial.enable(&mut region, offset)?;
+                        }
+                        for (i, column) in config.round_constants.iter().enumerate() {
+                            let constant = Value::known(config.spec.round_constants[round][i]);
+                            region.assign_fixed(|| format!("rc {round} {i}"), *column, offset, || constant)?;
+                        }
+                        state = state.map(|mut state| {
+                            config.spec.round(round, &mut state);
+                            state
+                        });
+                        offset += 1;
+                        cells = self.assign_state(&mut region, offset, state)?;
+                    }
+                }
+
+                Ok(cells.swap_remove(0))
+            },
+        )
+    }
+
+    fn assign_state(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        state: Value<[F; WIDTH]>,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        (self.config.state.iter().enumerate())
+            .map(|(i, column)| {
+                region.assign_advice(|| format!("state {i}"), *column, offset, || state.map(|state| state[i]))
+            })
+            .collect()
+    }
+}
+
+impl<F: Field> HashInstructions<F> for PoseidonChip<F> {
+    type Config = PoseidonConfig<F>;
+    type Spec = Spec<F>;
+
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        state: [Column<Advice>; WIDTH],
+        input: [Column<Advice>; RATE],
+        constant: Column<Fixed>,
+    ) -> Self::Config {
+        PoseidonChip::configure(meta, state, input, constant)
+    }
+
+    fn construct(config: Self::Config) -> Self {
+        PoseidonChip::construct(config)
+    }
+
+    fn hash(&self, layouter: impl Layouter<F>, inputs: &[AssignedCell<F, F>]) -> Result<AssignedCell<F, F>, Error> {
+        PoseidonChip::hash(self, layouter, inputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+
+    use super::{PoseidonChip, PoseidonConfig, Spec};
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::{Field, TestField as Fp},
+    };
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig<F: Field> {
+        poseidon: PoseidonConfig<F>,
+        input: Column<Advice>,
+        instance: Column<Instance>,
+    }
+
+    /// Exposes the hash of `inputs`.
+    #[derive(Default)]
+    struct TestCircuit {
+        inputs: Vec<Value<u64>>,
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit {
+        type Config = TestCircuitConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                inputs: vec![Value::unknown(); self.inputs.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let [input, s0, s1, s2, i0, i1] = [(); 6].map(|_| meta.advice_column());
+            let constant = meta.fixed_column();
+            let poseidon = PoseidonChip::configure(meta, [s0, s1, s2], [i0, i1], constant);
+            let instance = meta.instance_column();
+            meta.enable_equality(input);
+            meta.enable_equality(instance);
+
+            TestCircuitConfig {
+                poseidon,
+                input,
+                instance,
+            }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let chip = PoseidonChip::construct(config.poseidon);
+
+            let inputs = layouter.assign_region(
+                || "inputs",
+                |mut region| {
+                    (self.inputs.iter().enumerate())
+                        .map(|(i, input)| region.assign_advice(|| "input", config.input, i, || input.map(F::from)))
+                        .collect::<Result<Vec<_>, _>>()
+                },
+            )?;
+
+            let hash = chip.hash(layouter.namespace(|| "hash"), &inputs)?;
+            layouter.constrain_instance(hash.cell(), config.instance, 0)
+        }
+    }
+
+    #[test]
+    fn poseidon_hash() {
+        let spec = Spec::<Fp>::new();
+        // One, exactly two and more than two inputs.
+        for inputs in [vec![7], vec![1, 2], vec![1, 2, 3, 4, 5]] {
+            let hash = spec.hash(&inputs.iter().map(|input| Fp::from(*input)).collect::<Vec<_>>());
+            let circuit = TestCircuit {
+                inputs: inputs.into_iter().map(Value::known).collect(),
+            };
+            expect_satisfied(&circuit, vec![vec![hash]]);
+        }
+
+        let circuit = TestCircuit {
+            inputs: vec![Value::known(1), Value::known(2)],
+        };
+        expect_failure(
+            &circuit,
+            vec![vec![spec.hash(&[Fp::from(2), Fp::from(1)])]],
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+    }
+}
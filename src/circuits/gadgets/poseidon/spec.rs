@@ -0,0 +1,231 @@
+//! Parameters of the width 3 Poseidon permutation, and its native evaluation.
+//!
+//! The round constants and the MDS matrix are derived with the Grain LFSR of
+//! the reference implementation, for `x^5` S-boxes, [`FULL_ROUNDS`] full
+//! rounds and [`PARTIAL_ROUNDS`] partial rounds. Over the bn256 scalar field
+//! this is the permutation of circomlib, so [`Spec::hash`] of two elements
+//! matches its `Poseidon(2)`.
+
+use std::collections::VecDeque;
+
+use crate::{circuits::gadgets::hash::HashSpec, field::Field};
+
+/// Elements of the state.
+pub const WIDTH: usize = 3;
+/// Elements absorbed per permutation.
+pub const RATE: usize = 2;
+/// Rounds applying the S-box to the whole state, half before and half after
+/// the partial rounds.
+pub const FULL_ROUNDS: usize = 8;
+/// Rounds applying the S-box to the first element only.
+pub const PARTIAL_ROUNDS: usize = 57;
+/// All rounds of a permutation.
+pub const ROUNDS: usize = FULL_ROUNDS + PARTIAL_ROUNDS;
+
+/// Round constants and MDS matrix of the permutation.
+#[derive(Clone, Debug)]
+pub struct Spec<F: Field> {
+    pub round_constants: Vec<[F; WIDTH]>,
+    pub mds: [[F; WIDTH]; WIDTH],
+}
+
+impl<F: Field> Default for Spec<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: Field> Spec<F> {
+    /// Derives the parameters from the Grain LFSR.
+    pub fn new() -> Self {
+        let mut grain = Grain::new(F::NUM_BITS as u16, WIDTH, FULL_ROUNDS, PARTIAL_ROUNDS);
+
+        let round_constants = (0..ROUNDS)
+            .map(|_| [(); WIDTH].map(|_| grain.next_field_element::<F>()))
+            .collect();
+        let mds = grain.next_mds();
+
+        Self { round_constants, mds }
+    }
+
+    /// Whether `round` applies the S-box to the whole state.
+    pub fn is_full_round(round: usize) -> bool {
+        round < FULL_ROUNDS / 2 || round >= FULL_ROUNDS / 2 + PARTIAL_ROUNDS
+    }
+
+    /// Applies round `round` to `state`: adds the round constants, applies the
+    /// S-boxes and multiplies by the MDS matrix.
+    pub fn round(&self, round: usize, state: &mut [F; WIDTH]) {
+        for (i, value) in state.iter_mut().enumerate() {
+            *value += self.round_constants[round][i];
+            if i == 0 || Self::is_full_round(round) {
+                *value = sbox(*value);
+            }
+        }
+        let input = *state;
+        for (value, row) in state.iter_mut().zip(&self.mds) {
+            *value = row.iter().zip(&input).map(|(m, x)| *m * x).sum();
+        }
+    }
+
+    /// Applies the permutation to `state`.
+    pub fn permute(&self, state: &mut [F; WIDTH]) {
+        for round in 0..ROUNDS {
+            self.round(round, state);
+        }
+    }
+
+    /// Hashes `inputs` with a sponge: the first element of the state is the
+    /// capacity, starting at zero, and the inputs are added [`RATE`] at a time
+    /// to the others, padded with zeros, before each permutation. The hash is
+    /// the first element of the final state.
+    ///
+    /// The number of inputs is fixed by the circuit, and is not part of the
+    /// hash: callers hashing inputs of different lengths must separate them.
+    pub fn hash(&self, inputs: &[F]) -> F {
+        let mut state = [F::ZERO; WIDTH];
+        for chunk in chunks(inputs.len()) {
+            for (k, value) in state[1..].iter_mut().enumerate() {
+                *value += inputs.get(chunk * RATE + k).copied().unwrap_or(F::ZERO);
+            }
+            self.permute(&mut state);
+        }
+        state[0]
+    }
+}
+
+impl<F: Field> HashSpec<F> for Spec<F> {
+    fn hash(&self, inputs: &[F]) -> F {
+        Spec::hash(self, inputs)
+    }
+}
+
+/// The permutations needed to absorb `num_inputs` inputs, at least one.
+pub(super) fn chunks(num_inputs: usize) -> std::ops::Range<usize> {
+    0..num_inputs.div_ceil(RATE).max(1)
+}
+
+fn sbox<F: Field>(x: F) -> F {
+    x.square().square() * x
+}
+
+/// The self-shrinking Grain LFSR generating the parameters.
+pub(crate) struct Grain {
+    state: VecDeque<bool>,
+}
+
+impl Grain {
+    /// Seeds the LFSR with the parameters of a permutation over a field of
+    /// `num_bits` bits with `x^5` S-boxes.
+    pub(crate) fn new(num_bits: u16, width: usize, full_rounds: usize, partial_rounds: usize) -> Self {
+        let mut state = VecDeque::with_capacity(80);
+        // Prime field, x^5 S-box, field size, width, full and partial rounds,
+        // each most significant bit first, then ones.
+        for (value, len) in [
+            (1, 2),
+            (0, 4),
+            (num_bits, 12),
+            (width as u16, 12),
+            (full_rounds as u16, 10),
+            (partial_rounds as u16, 10),
+        ] {
+            state.extend((0..len).rev().map(|i| (value >> i) & 1 == 1));
+        }
+        state.resize(80, true);
+
+        let mut grain = Self { state };
+        for _ in 0..160 {
+            grain.next_raw_bit();
+        }
+        grain
+    }
+
+    fn next_raw_bit(&mut self) -> bool {
+        let s = &self.state;
+        let bit = s[62] ^ s[51] ^ s[38] ^ s[23] ^ s[13] ^ s[0];
+        self.state.pop_front();
+        self.state.push_back(bit);
+        bit
+    }
+
+    /// Outputs the second bit of each pair whose first bit is set.
+    fn next_bit(&mut self) -> bool {
+        loop {
+            let keep = self.next_raw_bit();
+            let bit = self.next_raw_bit();
+            if keep {
+                return bit;
+            }
+        }
+    }
+
+    /// The next `F::NUM_BITS` bits, most significant first, as little-endian
+    /// bytes.
+    fn next_bytes<F: Field, const N: usize>(&mut self) -> [u8; N] {
+        let mut bytes = [0; N];
+        for i in (0..F::NUM_BITS as usize).rev() {
+            bytes[i / 8] |= (self.next_bit() as u8) << (i % 8);
+        }
+        bytes
+    }
+
+    /// The next field element, rejecting values not below the modulus.
+    pub(crate) fn next_field_element<F: Field>(&mut self) -> F {
+        loop {
+            if let Some(value) = Option::<F>::from(F::from_repr(self.next_bytes::<F, 32>())) {
+                return value;
+            }
+        }
+    }
+
+    /// The next field element, reducing values modulo the modulus.
+    fn next_field_element_without_rejection<F: Field>(&mut self) -> F {
+        F::from_uniform_bytes(&self.next_bytes::<F, 64>())
+    }
+
+    /// The next Cauchy matrix `1 / (x_i + y_j)`, with distinct `x_i` and
+    /// `y_j`, which is MDS.
+    pub(crate) fn next_mds<F: Field, const N: usize>(&mut self) -> [[F; N]; N] {
+        let (xs, ys) = loop {
+            let values: Vec<F> = (0..2 * N).map(|_| self.next_field_element_without_rejection()).collect();
+            let mut unique = values.clone();
+            unique.sort();
+            unique.dedup();
+            if unique.len() == values.len() {
+                break (values[..N].to_vec(), values[N..].to_vec());
+            }
+        };
+        let mut mds = [[F::ZERO; N]; N];
+        for (i, row) in mds.iter_mut().enumerate() {
+            for (j, entry) in row.iter_mut().enumerate() {
+                *entry = (xs[i] + ys[j]).invert().unwrap();
+            }
+        }
+        mds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use serde_json::json;
+
+    use super::Spec;
+    use crate::registry::parse_field;
+
+    #[test]
+    fn circomlib_vector() {
+        let spec = Spec::<Fr>::new();
+        let expected = [
+            "0x115cc0f5e7d690413df64c6b9662e9cf2a3617f2743245519e19607a4417189a",
+            "0x0fca49b798923ab0239de1c9e7a4a9a2210312b6a2f616d18b5a87f9b628ae29",
+            "0x0e7ae82e40091e63cbd4f16a6d16310b3729d4b6e138fcf54110e2867045a30c",
+        ]
+        .map(|hex| parse_field(&json!(hex)).unwrap());
+
+        let mut state = [0, 1, 2].map(Fr::from);
+        spec.permute(&mut state);
+        assert_eq!(state, expected);
+        assert_eq!(spec.hash(&[Fr::from(1), Fr::from(2)]), expected[0]);
+    }
+}
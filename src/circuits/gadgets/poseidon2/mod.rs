@@ -0,0 +1,363 @@
+//! Poseidon2 hash over a width 3 state, one round per row.
+//!
+//! The sponge and the layout are those of
+//! [`PoseidonChip`](super::poseidon::PoseidonChip), which this chip can
+//! replace through [`HashInstructions`]. The initial linear layer of the
+//! permutation is folded into the absorption row, and the round rows use the
+//! external matrix `M_E` and the internal matrix `M_I` of [`Spec`] instead
+//! of the MDS matrix:
+//!
+//! ```text
+//! absorb:  next = M_E ⋅ (cur[0], cur[1] + in[0], cur[2] + in[1])
+//! full:    next = M_E ⋅ ((cur[j] + rc[j])^5)_j
+//! partial: next = M_I ⋅ ((cur[0] + rc[0])^5, cur[1], cur[2])
+//! ```
+//!
+//! Both matrices are sums of the state plus a diagonal, so the gates stay
+//! degree 6 with fewer terms than Poseidon's, and a permutation takes
+//! [`ROUNDS`] rows, one partial round less than Poseidon.
+
+mod spec;
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+pub use spec::{Spec, FULL_ROUNDS, INTERNAL_DIAGONAL, PARTIAL_ROUNDS, RATE, ROUNDS, WIDTH};
+
+use super::hash::HashInstructions;
+use crate::field::Field;
+
+/// Config for [`Poseidon2Chip`].
+#[derive(Clone, Debug)]
+pub struct Poseidon2Config<F: Field> {
+    q_absorb: Selector,
+    q_full: Selector,
+    q_partial: Selector,
+    state: [Column<Advice>; WIDTH],
+    input: [Column<Advice>; RATE],
+    round_constants: [Column<Fixed>; WIDTH],
+    spec: Spec<F>,
+}
+
+/// Chip hashing cells with Poseidon2.
+#[derive(Clone, Debug)]
+pub struct Poseidon2Chip<F: Field> {
+    config: Poseidon2Config<F>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> Chip<F> for Poseidon2Chip<F> {
+    type Config = Poseidon2Config<F>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field> Poseidon2Chip<F> {
+    /// Configures the chip on the `state` and `input` columns, initializing
+    /// and padding the state with `constant`.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        state: [Column<Advice>; WIDTH],
+        input: [Column<Advice>; RATE],
+        constant: Column<Fixed>,
+    ) -> Poseidon2Config<F> {
+        let q_absorb = meta.selector();
+        let q_full = meta.selector();
+        let q_partial = meta.selector();
+        let round_constants = [(); WIDTH].map(|_| meta.fixed_column());
+        let spec = Spec::new();
+
+        for column in state.into_iter().chain(input) {
+            meta.enable_equality(column);
+        }
+        meta.enable_constant(constant);
+
+        let pow5 = |x: Expression<F>| x.clone() * x.clone() * x.clone() * x.clone() * x;
+        let sum = |xs: &[Expression<F>]| xs.iter().fold(Expression::Constant(F::ZERO), |acc, x| acc + x.clone());
+
+        meta.create_gate("poseidon2 absorb", |meta| {
+            let q_absorb = meta.query_selector(q_absorb);
+            let next = state.map(|column| meta.query_advice(column, Rotation::next()));
+            let input = input.map(|column| meta.query_advice(column, Rotation::cur()));
+            let absorbed: Vec<_> = (0..WIDTH)
+                .map(|i| {
+                    let cur = meta.query_advice(state[i], Rotation::cur());
+                    if i == 0 {
+                        cur
+                    } else {
+                        cur + input[i - 1].clone()
+                    }
+                })
+                .collect();
+            let total = sum(&absorbed);
+
+            (next.into_iter().zip(absorbed))
+                .map(|(next, x)| q_absorb.clone() * (next - x - total.clone()))
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("poseidon2 full round", |meta| {
+            let q_full = meta.query_selector(q_full);
+            let next = state.map(|column| meta.query_advice(column, Rotation::next()));
+            let sboxed: Vec<_> = (state.iter().zip(round_constants))
+                .map(|(column, rc)| {
+                    pow5(meta.query_advice(*column, Rotation::cur()) + meta.query_fixed(rc, Rotation::cur()))
+                })
+                .collect();
+            let total = sum(&sboxed);
+
+            (next.into_iter().zip(sboxed))
+                .map(|(next, x)| q_full.clone() * (next - x - total.clone()))
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("poseidon2 partial round", |meta| {
+            let q_partial = meta.query_selector(q_partial);
+            let next = state.map(|column| meta.query_advice(column, Rotation::next()));
+            let mut xs = state.map(|column| meta.query_advice(column, Rotation::cur())).to_vec();
+            xs[0] = pow5(xs[0].clone() + meta.query_fixed(round_constants[0], Rotation::cur()));
+            let total = sum(&xs);
+
+            (next.into_iter().zip(xs).zip(INTERNAL_DIAGONAL))
+                .map(|((next, x), diagonal)| {
+                    q_partial.clone() * (next - x * Expression::Constant(F::from(diagonal)) - total.clone())
+                })
+                .collect::<Vec<_>>()
+        });
+
+        Poseidon2Config {
+            q_absorb,
+            q_full,
+            q_partial,
+            state,
+            input,
+            round_constants,
+            spec,
+        }
+    }
+
+    pub fn construct(config: Poseidon2Config<F>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Rows of the region hashing `num_inputs` inputs.
+    pub fn rows(num_inputs: usize) -> usize {
+        spec::chunks(num_inputs).len() * (ROUNDS + 1) + 1
+    }
+
+    /// Returns the hash of `inputs`, as [`Spec::hash`].
+    pub fn hash(
+        &self,
+        mut layouter: impl Layouter<F>,
+        inputs: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+
+        layouter.assign_region(
+            || "poseidon2",
+            |mut region| {
+                let mut cells = Vec::with_capacity(WIDTH);
+                for (i, column) in config.state.iter().enumerate() {
+                    cells.push(region.assign_advice_from_constant(|| format!("state {i}"), *column, 0, F::ZERO)?);
+                }
+                let mut state: Value<[F; WIDTH]> = Value::known([F::ZERO; WIDTH]);
+
+                let mut offset = 0;
+                for chunk in spec::chunks(inputs.len()) {
+                    config.q_absorb.enable(&mut region, offset)?;
+                    for (k, column) in config.input.iter().enumerate() {
+                        let input = match inputs.get(chunk * RATE + k) {
+                            Some(input) => input.copy_advice(|| "input", &mut region, *column, offset)?,
+                            None => region.assign_advice_from_constant(|| "padding", *column, offset, F::ZERO)?,
+                        };
+                        state = state.zip(input.value()).map(|(mut state, input)| {
+                            state[k + 1] += input;
+                            state
+                        });
+                    }
+                    state = state.map(|mut state| {
+                        Spec::external(&mut state);
+                        state
+                    });
+                    offset += 1;
+                    cells = self.assign_state(&mut region, offset, state)?;
+
+                    for round in 0..ROUNDS {
+                        if Spec::<F>::is_full_round(round) {
+                            config.q_full.enable(&mut region, offset)?;
+                        } else {
+                            config.q_partial.enable(&mut region, offset)?;
+                        }
+                        for (i, column) in config.round_constants.iter().enumerate() {
+                            let constant = Value::known(config.spec.round_constants[round][i]);
+                            region.assign_fixed(|| format!("rc {round} {i}"), *column, offset, || constant)?;
+                        }
+                        state = state.map(|mut state| {
+                            config.spec.round(round, &mut state);
+                            state
+                        });
+                        offset += 1;
+                        cells = self.assign_state(&mut region, offset, state)?;
+                    }
+                }
+
+                Ok(cells.swap_remove(0))
+            },
+        )
+    }
+
+    fn assign_state(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        state: Value<[F; WIDTH]>,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        (self.config.state.iter().enumerate())
+            .map(|(i, column)| {
+                region.assign_advice(|| format!("state {i}"), *column, offset, || state.map(|state| state[i]))
+            })
+            .collect()
+    }
+}
+
+impl<F: Field> HashInstructions<F> for Poseidon2Chip<F> {
+    type Config = Poseidon2Config<F>;
+    type Spec = Spec<F>;
+
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        state: [Column<Advice>; WIDTH],
+        input: [Column<Advice>; RATE],
+        constant: Column<Fixed>,
+    ) -> Self::Config {
+        Poseidon2Chip::configure(meta, state, input, constant)
+    }
+
+    fn construct(config: Self::Config) -> Self {
+        Poseidon2Chip::construct(config)
+    }
+
+    fn hash(&self, layouter: impl Layouter<F>, inputs: &[AssignedCell<F, F>]) -> Result<AssignedCell<F, F>, Error> {
+        Poseidon2Chip::hash(self, layouter, inputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+
+    use super::{Poseidon2Chip, Poseidon2Config, Spec, WIDTH};
+    use crate::{
+        circuits::gadgets::poseidon,
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::{Field, TestField as Fp},
+    };
+
+    /// Exposes the hash of `inputs`.
+    #[derive(Default)]
+    struct TestCircuit {
+        inputs: Vec<Value<u64>>,
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit {
+        type Config = (Poseidon2Config<F>, Column<Advice>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                inputs: vec![Value::unknown(); self.inputs.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let [input, s0, s1, s2, i0, i1] = [(); 6].map(|_| meta.advice_column());
+            let constant = meta.fixed_column();
+            let poseidon2 = Poseidon2Chip::configure(meta, [s0, s1, s2], [i0, i1], constant);
+            let instance = meta.instance_column();
+            meta.enable_equality(input);
+            meta.enable_equality(instance);
+            (poseidon2, input, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, input, instance): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = Poseidon2Chip::construct(config);
+
+            let inputs = layouter.assign_region(
+                || "inputs",
+                |mut region| {
+                    (self.inputs.iter().enumerate())
+                        .map(|(i, value)| region.assign_advice(|| "input", input, i, || value.map(F::from)))
+                        .collect::<Result<Vec<_>, _>>()
+                },
+            )?;
+
+            let hash = chip.hash(layouter.namespace(|| "hash"), &inputs)?;
+            layouter.constrain_instance(hash.cell(), instance, 0)
+        }
+    }
+
+    #[test]
+    fn linear_layers() {
+        // `circ(2, 1, 1)` and `J + diag(1, 1, 2)` on the unit vectors.
+        let unit = |i: usize| std::array::from_fn::<_, WIDTH, _>(|j| Fp::from((i == j) as u64));
+        let columns = [([2, 1, 1], [2, 1, 1]), ([1, 2, 1], [1, 2, 1]), ([1, 1, 2], [1, 1, 3])];
+        for (i, (external, internal)) in columns.into_iter().enumerate() {
+            let mut state = unit(i);
+            Spec::external(&mut state);
+            assert_eq!(state, external.map(Fp::from));
+            let mut state = unit(i);
+            Spec::internal(&mut state);
+            assert_eq!(state, internal.map(Fp::from));
+        }
+    }
+
+    #[test]
+    fn poseidon2_hash() {
+        let spec = Spec::<Fp>::new();
+        // One, exactly two and more than two inputs.
+        for inputs in [vec![7], vec![1, 2], vec![1, 2, 3, 4, 5]] {
+            let elements: Vec<_> = inputs.iter().map(|input| Fp::from(*input)).collect();
+            let hash = spec.hash(&elements);
+            assert_ne!(hash, poseidon::Spec::new().hash(&elements));
+            let circuit = TestCircuit {
+                inputs: inputs.into_iter().map(Value::known).collect(),
+            };
+            expect_satisfied(&circuit, vec![vec![hash]]);
+        }
+
+        let circuit = TestCircuit {
+            inputs: vec![Value::known(1), Value::known(2)],
+        };
+        expect_failure(
+            &circuit,
+            vec![vec![spec.hash(&[Fp::from(2), Fp::from(1)])]],
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+    }
+}
@@ -0,0 +1,198 @@
+//! A pseudo-random stream expanded from a committed seed, with Poseidon in
+//! counter mode.
+//!
+//! The seed is committed as `H(seed)`, and the `i`-th output, counting from
+//! 1, is `H(seed, i)`. The counters are assigned from constants, so the
+//! stream is a deterministic function of the seed that the prover can't
+//! steer:
+//!
+//! | counter |
+//! | 1       |
+//! | 2       |
+//! | ...     |
+//!
+//! The stream starts at 1 because the zero padding of [`Spec::hash`] makes
+//! `H(seed, 0)` equal to the commitment `H(seed)`. The outputs are uniform
+//! field elements, unbiased only over the whole field: reducing them to a
+//! small range is left to the caller.
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, ConstraintSystem, Error},
+};
+
+use super::poseidon::{PoseidonChip, PoseidonConfig, Spec};
+use crate::field::Field;
+
+/// Config for [`PrngChip`].
+#[derive(Clone, Debug)]
+pub struct PrngConfig<F: Field> {
+    poseidon: PoseidonConfig<F>,
+    counter: Column<Advice>,
+}
+
+/// Chip committing to seeds and expanding them into streams.
+#[derive(Clone, Debug)]
+pub struct PrngChip<F: Field> {
+    config: PrngConfig<F>,
+}
+
+impl<F: Field> PrngChip<F> {
+    /// Configures the chip, hashing with `poseidon`, whose constant column
+    /// also fixes the counters of the `counter` column.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        poseidon: PoseidonConfig<F>,
+        counter: Column<Advice>,
+    ) -> PrngConfig<F> {
+        meta.enable_equality(counter);
+        PrngConfig { poseidon, counter }
+    }
+
+    pub fn construct(config: PrngConfig<F>) -> Self {
+        Self { config }
+    }
+
+    /// Returns `H(seed)`.
+    pub fn commit(
+        &self,
+        mut layouter: impl Layouter<F>,
+        seed: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let poseidon = PoseidonChip::construct(self.config.poseidon.clone());
+        poseidon.hash(layouter.namespace(|| "commitment"), &[seed.clone()])
+    }
+
+    /// Returns the first `n` outputs of the stream of `seed`.
+    pub fn expand(
+        &self,
+        mut layouter: impl Layouter<F>,
+        seed: &AssignedCell<F, F>,
+        n: usize,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let config = &self.config;
+        let counters = layouter.assign_region(
+            || "prng counters",
+            |mut region| {
+                (1..=n)
+                    .map(|i| {
+                        let counter = F::from(i as u64);
+                        region.assign_advice_from_constant(|| format!("counter {i}"), config.counter, i - 1, counter)
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            },
+        )?;
+
+        let poseidon = PoseidonChip::construct(config.poseidon.clone());
+        (counters.into_iter().enumerate())
+            .map(|(i, counter)| poseidon.hash(layouter.namespace(|| format!("output {i}")), &[seed.clone(), counter]))
+            .collect()
+    }
+}
+
+/// Off-circuit stream of a seed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Prng<F> {
+    pub seed: F,
+}
+
+impl<F: Field> Prng<F> {
+    /// `H(seed)`.
+    pub fn commitment(&self) -> F {
+        Spec::new().hash(&[self.seed])
+    }
+
+    /// The first `n` outputs.
+    pub fn expand(&self, n: usize) -> Vec<F> {
+        let spec = Spec::new();
+        (1..=n).map(|i| spec.hash(&[self.seed, F::from(i as u64)])).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+    };
+
+    use super::{Prng, PrngChip, PrngConfig};
+    use crate::{
+        circuits::{
+            gadgets::poseidon::PoseidonChip,
+            instance::{InstanceColumns, InstanceLayout},
+        },
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::TestField as Fp,
+    };
+
+    const N: usize = 3;
+
+    /// Exposes the commitment to `seed` and the first [`N`] outputs.
+    #[derive(Default)]
+    struct TestCircuit {
+        seed: Fp,
+    }
+
+    impl Circuit<Fp> for TestCircuit {
+        type Config = (PrngConfig<Fp>, Column<Advice>, InstanceColumns);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let [advice, counter, s0, s1, s2, i0, i1] = [(); 7].map(|_| meta.advice_column());
+            let constant = meta.fixed_column();
+            meta.enable_equality(advice);
+
+            let poseidon = PoseidonChip::configure(meta, [s0, s1, s2], [i0, i1], constant);
+            let config = PrngChip::configure(meta, poseidon, counter);
+            (config, advice, InstanceLayout::new().column(["commitment", "r1", "r2", "r3"]).configure(meta))
+        }
+
+        fn synthesize(
+            &self,
+            (config, advice, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let seed = layouter.assign_region(
+                || "seed",
+                |mut region| region.assign_advice(|| "seed", advice, 0, || Value::known(self.seed)),
+            )?;
+
+            let chip = PrngChip::construct(config);
+            let commitment = chip.commit(layouter.namespace(|| "commit"), &seed)?;
+            let outputs = chip.expand(layouter.namespace(|| "expand"), &seed, N)?;
+            for (i, cell) in [&commitment].into_iter().chain(&outputs).enumerate() {
+                instance.expose_public(&mut layouter, cell, i)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn prng() {
+        let prng = Prng { seed: Fp::from(42) };
+        let outputs = prng.expand(N);
+        assert!(!outputs.contains(&prng.commitment()));
+        assert_eq!(prng.expand(N + 1)[..N], outputs);
+
+        let instances: Vec<_> = [prng.commitment()].into_iter().chain(outputs).collect();
+        expect_satisfied(&TestCircuit { seed: prng.seed }, vec![instances.clone()]);
+
+        // The stream of another seed, under the same commitment.
+        let mut wrong = instances;
+        wrong[1..].copy_from_slice(&Prng { seed: Fp::from(43) }.expand(N));
+        expect_failure(
+            &TestCircuit { seed: prng.seed },
+            vec![wrong],
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 1 },
+            },
+        );
+    }
+}
@@ -0,0 +1,342 @@
+//! Rescue-Prime hash over a width 3 state, one half-round per row.
+//!
+//! The sponge and the layout are those of
+//! [`PoseidonChip`](super::poseidon::PoseidonChip), with forward and backward
+//! half-rounds instead of full and partial rounds:
+//!
+//! | state    | input       | round_constants | q_absorb | q_forward | q_backward |
+//! | 0, 0, 0  | in[0..2]    |                 | 1        | 0         | 0          |
+//! | s        |             | rc[0]           | 0        | 1         | 0          |
+//! | s'       |             | rc[1]           | 0        | 0         | 1          |
+//! | ...      |             | ...             | 0        | ...       | ...        |
+//! | hash, ...|             |                 | 0        | 0         | 0          |
+//!
+//! The backward S-box `x^(1/5)` is checked through its inverse, by undoing
+//! the MDS matrix and the constants of the next state:
+//!
+//! ```text
+//! forward:  next[i] = Σ_j mds[i][j] ⋅ cur[j]^5 + rc[i]
+//! backward: cur[i] = (Σ_j mds_inv[i][j] ⋅ (next[j] - rc[j]))^5
+//! ```
+//!
+//! Both gates are degree 6, like Poseidon's, but a permutation takes
+//! `2 ⋅ ROUNDS` rows instead of [`poseidon::ROUNDS`](super::poseidon::ROUNDS).
+
+mod spec;
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+pub use spec::{Spec, RATE, ROUNDS, WIDTH};
+
+use super::hash::HashInstructions;
+use crate::field::Field;
+
+/// Config for [`RescueChip`].
+#[derive(Clone, Debug)]
+pub struct RescueConfig<F: Field> {
+    q_absorb: Selector,
+    q_forward: Selector,
+    q_backward: Selector,
+    state: [Column<Advice>; WIDTH],
+    input: [Column<Advice>; RATE],
+    round_constants: [Column<Fixed>; WIDTH],
+    spec: Spec<F>,
+}
+
+/// Chip hashing cells with Rescue-Prime.
+#[derive(Clone, Debug)]
+pub struct RescueChip<F: Field> {
+    config: RescueConfig<F>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> Chip<F> for RescueChip<F> {
+    type Config = RescueConfig<F>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field> RescueChip<F> {
+    /// Configures the chip on the `state` and `input` columns, initializing
+    /// and padding the state with `constant`.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        state: [Column<Advice>; WIDTH],
+        input: [Column<Advice>; RATE],
+        constant: Column<Fixed>,
+    ) -> RescueConfig<F> {
+        let q_absorb = meta.selector();
+        let q_forward = meta.selector();
+        let q_backward = meta.selector();
+        let round_constants = [(); WIDTH].map(|_| meta.fixed_column());
+        let spec = Spec::new();
+
+        for column in state.into_iter().chain(input) {
+            meta.enable_equality(column);
+        }
+        meta.enable_constant(constant);
+
+        meta.create_gate("rescue absorb", |meta| {
+            let q_absorb = meta.query_selector(q_absorb);
+            let cur = state.map(|column| meta.query_advice(column, Rotation::cur()));
+            let next = state.map(|column| meta.query_advice(column, Rotation::next()));
+            let input = input.map(|column| meta.query_advice(column, Rotation::cur()));
+
+            (0..WIDTH)
+                .map(|i| {
+                    let absorbed = if i == 0 { cur[0].clone() } else { cur[i].clone() + input[i - 1].clone() };
+                    q_absorb.clone() * (next[i].clone() - absorbed)
+                })
+                .collect::<Vec<_>>()
+        });
+
+        let pow5 = |x: Expression<F>| x.clone() * x.clone() * x.clone() * x.clone() * x;
+        let mix = |matrix: &[F; WIDTH], xs: &[Expression<F>]| {
+            (matrix.iter().zip(xs)).fold(Expression::Constant(F::ZERO), |acc, (m, x)| {
+                acc + Expression::Constant(*m) * x.clone()
+            })
+        };
+
+        meta.create_gate("rescue forward", |meta| {
+            let q_forward = meta.query_selector(q_forward);
+            let sboxed = state.map(|column| pow5(meta.query_advice(column, Rotation::cur())));
+            let next = state.map(|column| meta.query_advice(column, Rotation::next()));
+            let rc = round_constants.map(|column| meta.query_fixed(column, Rotation::cur()));
+
+            (next.into_iter().zip(&spec.mds).zip(rc))
+                .map(|((next, row), rc)| q_forward.clone() * (next - mix(row, &sboxed) - rc))
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("rescue backward", |meta| {
+            let q_backward = meta.query_selector(q_backward);
+            let cur = state.map(|column| meta.query_advice(column, Rotation::cur()));
+            let unshifted: Vec<_> = (state.iter().zip(round_constants))
+                .map(|(column, rc)| {
+                    meta.query_advice(*column, Rotation::next()) - meta.query_fixed(rc, Rotation::cur())
+                })
+                .collect();
+
+            (cur.into_iter().zip(&spec.mds_inv))
+                .map(|(cur, row)| q_backward.clone() * (cur - pow5(mix(row, &unshifted))))
+                .collect::<Vec<_>>()
+        });
+
+        RescueConfig {
+            q_absorb,
+            q_forward,
+            q_backward,
+            state,
+            input,
+            round_constants,
+            spec,
+        }
+    }
+
+    pub fn construct(config: RescueConfig<F>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Rows of the region hashing `num_inputs` inputs.
+    pub fn rows(num_inputs: usize) -> usize {
+        spec::chunks(num_inputs).len() * (2 * ROUNDS + 1) + 1
+    }
+
+    /// Returns the hash of `inputs`, as [`Spec::hash`].
+    pub fn hash(
+        &self,
+        mut layouter: impl Layouter<F>,
+        inputs: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+
+        layouter.assign_region(
+            || "rescue",
+            |mut region| {
+                let mut cells = Vec::with_capacity(WIDTH);
+                for (i, column) in config.state.iter().enumerate() {
+                    cells.push(region.assign_advice_from_constant(|| format!("state {i}"), *column, 0, F::ZERO)?);
+                }
+                let mut state: Value<[F; WIDTH]> = Value::known([F::ZERO; WIDTH]);
+
+                let mut offset = 0;
+                for chunk in spec::chunks(inputs.len()) {
+                    config.q_absorb.enable(&mut region, offset)?;
+                    for (k, column) in config.input.iter().enumerate() {
+                        let input = match inputs.get(chunk * RATE + k) {
+                            Some(input) => input.copy_advice(|| "input", &mut region, *column, offset)?,
+                            None => region.assign_advice_from_constant(|| "padding", *column, offset, F::ZERO)?,
+                        };
+                        state = state.zip(input.value()).map(|(mut state, input)| {
+                            state[k + 1] += input;
+                            state
+                        });
+                    }
+                    offset += 1;
+                    cells = self.assign_state(&mut region, offset, state)?;
+
+                    for half in 0..2 * ROUNDS {
+                        if half % 2 == 0 {
+                            config.q_forward.enable(&mut region, offset)?;
+                        } else {
+                            config.q_backward.enable(&mut region, offset)?;
+                        }
+                        for (i, column) in config.round_constants.iter().enumerate() {
+                            let constant = Value::known(config.spec.round_constants[half][i]);
+                            region.assign_fixed(|| format!("rc {half} {i}"), *column, offset, || constant)?;
+                        }
+                        state = state.map(|mut state| {
+                            config.spec.half_round(half, &mut state);
+                            state
+                        });
+                        offset += 1;
+                        cells = self.assign_state(&mut region, offset, state)?;
+                    }
+                }
+
+                Ok(cells.swap_remove(0))
+            },
+        )
+    }
+
+    fn assign_state(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        state: Value<[F; WIDTH]>,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        (self.config.state.iter().enumerate())
+            .map(|(i, column)| {
+                region.assign_advice(|| format!("state {i}"), *column, offset, || state.map(|state| state[i]))
+            })
+            .collect()
+    }
+}
+
+impl<F: Field> HashInstructions<F> for RescueChip<F> {
+    type Config = RescueConfig<F>;
+    type Spec = Spec<F>;
+
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        state: [Column<Advice>; WIDTH],
+        input: [Column<Advice>; RATE],
+        constant: Column<Fixed>,
+    ) -> Self::Config {
+        RescueChip::configure(meta, state, input, constant)
+    }
+
+    fn construct(config: Self::Config) -> Self {
+        RescueChip::construct(config)
+    }
+
+    fn hash(&self, layouter: impl Layouter<F>, inputs: &[AssignedCell<F, F>]) -> Result<AssignedCell<F, F>, Error> {
+        RescueChip::hash(self, layouter, inputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+
+    use super::{RescueChip, RescueConfig, Spec};
+    use crate::{
+        circuits::gadgets::poseidon,
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::{Field, TestField as Fp},
+    };
+
+    /// Exposes the hash of `inputs`.
+    #[derive(Default)]
+    struct TestCircuit {
+        inputs: Vec<Value<u64>>,
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit {
+        type Config = (RescueConfig<F>, Column<Advice>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                inputs: vec![Value::unknown(); self.inputs.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let [input, s0, s1, s2, i0, i1] = [(); 6].map(|_| meta.advice_column());
+            let constant = meta.fixed_column();
+            let rescue = RescueChip::configure(meta, [s0, s1, s2], [i0, i1], constant);
+            let instance = meta.instance_column();
+            meta.enable_equality(input);
+            meta.enable_equality(instance);
+            (rescue, input, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, input, instance): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = RescueChip::construct(config);
+
+            let inputs = layouter.assign_region(
+                || "inputs",
+                |mut region| {
+                    (self.inputs.iter().enumerate())
+                        .map(|(i, value)| region.assign_advice(|| "input", input, i, || value.map(F::from)))
+                        .collect::<Result<Vec<_>, _>>()
+                },
+            )?;
+
+            let hash = chip.hash(layouter.namespace(|| "hash"), &inputs)?;
+            layouter.constrain_instance(hash.cell(), instance, 0)
+        }
+    }
+
+    #[test]
+    fn rescue_hash() {
+        let spec = Spec::<Fp>::new();
+        // One, exactly two and more than two inputs.
+        for inputs in [vec![7], vec![1, 2], vec![1, 2, 3, 4, 5]] {
+            let elements: Vec<_> = inputs.iter().map(|input| Fp::from(*input)).collect();
+            let hash = spec.hash(&elements);
+            assert_ne!(hash, poseidon::Spec::new().hash(&elements));
+            let circuit = TestCircuit {
+                inputs: inputs.into_iter().map(Value::known).collect(),
+            };
+            expect_satisfied(&circuit, vec![vec![hash]]);
+        }
+
+        let circuit = TestCircuit {
+            inputs: vec![Value::known(1), Value::known(2)],
+        };
+        expect_failure(
+            &circuit,
+            vec![vec![spec.hash(&[Fp::from(2), Fp::from(1)])]],
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+    }
+}
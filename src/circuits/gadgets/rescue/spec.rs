@@ -0,0 +1,182 @@
+//! Parameters of the width 3 Rescue-Prime permutation, and its native
+//! evaluation.
+//!
+//! [`ROUNDS`] is the count given by the round formula of the Rescue-Prime
+//! paper for 128 bits of security, a width 3 state and `x^5` S-boxes:
+//! `⌈1.5 ⋅ max(5, ℓ0, ℓ1)⌉` with `ℓ1 = ⌈(128 + 3) / (5.5 ⋅ 3)⌉ = 8` dominating.
+//! The round constants and the MDS matrix are drawn from the Grain LFSR of
+//! [`poseidon`](crate::circuits::gadgets::poseidon), seeded with these
+//! parameters, rather than from SHAKE256 as in the reference, so hashes don't
+//! match other implementations.
+
+use std::array;
+
+use crate::{
+    circuits::gadgets::{hash::HashSpec, poseidon::Grain},
+    field::Field,
+};
+
+/// Elements of the state.
+pub const WIDTH: usize = 3;
+/// Elements absorbed per permutation.
+pub const RATE: usize = 2;
+/// Rounds of a permutation, each made of a forward and a backward half.
+pub const ROUNDS: usize = 12;
+
+/// Round constants and MDS matrix of the permutation.
+#[derive(Clone, Debug)]
+pub struct Spec<F: Field> {
+    /// The constants of each half-round, forward ones at even indices.
+    pub round_constants: Vec<[F; WIDTH]>,
+    pub mds: [[F; WIDTH]; WIDTH],
+    pub mds_inv: [[F; WIDTH]; WIDTH],
+    /// The exponent of the inverse S-box, as little-endian limbs.
+    alpha_inv: [u64; 4],
+}
+
+impl<F: Field> Default for Spec<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: Field> Spec<F> {
+    /// Derives the parameters from the Grain LFSR.
+    pub fn new() -> Self {
+        let mut grain = Grain::new(F::NUM_BITS as u16, WIDTH, 2 * ROUNDS, 0);
+
+        let round_constants = (0..2 * ROUNDS)
+            .map(|_| [(); WIDTH].map(|_| grain.next_field_element::<F>()))
+            .collect();
+        let mds = grain.next_mds();
+
+        Self {
+            round_constants,
+            mds,
+            mds_inv: invert(&mds),
+            alpha_inv: alpha_inv::<F>(),
+        }
+    }
+
+    /// Applies half-round `half` to `state`: the S-boxes, `x^5` for even
+    /// halves and `x^(1/5)` for odd ones, then the MDS matrix and the round
+    /// constants.
+    pub fn half_round(&self, half: usize, state: &mut [F; WIDTH]) {
+        let sboxed = state.map(|x| {
+            if half % 2 == 0 {
+                x.square().square() * x
+            } else {
+                x.pow_vartime(self.alpha_inv)
+            }
+        });
+        for ((value, row), constant) in state.iter_mut().zip(&self.mds).zip(&self.round_constants[half]) {
+            *value = row.iter().zip(&sboxed).map(|(m, x)| *m * x).sum::<F>() + constant;
+        }
+    }
+
+    /// Applies the permutation to `state`.
+    pub fn permute(&self, state: &mut [F; WIDTH]) {
+        for half in 0..2 * ROUNDS {
+            self.half_round(half, state);
+        }
+    }
+
+    /// Hashes `inputs` with the same sponge as
+    /// [`poseidon::Spec::hash`](crate::circuits::gadgets::poseidon::Spec::hash):
+    /// the first element of the state is the capacity, and the inputs are
+    /// added [`RATE`] at a time to the others, padded with zeros, before each
+    /// permutation. The hash is the first element of the final state.
+    pub fn hash(&self, inputs: &[F]) -> F {
+        let mut state = [F::ZERO; WIDTH];
+        for chunk in chunks(inputs.len()) {
+            for (k, value) in state[1..].iter_mut().enumerate() {
+                *value += inputs.get(chunk * RATE + k).copied().unwrap_or(F::ZERO);
+            }
+            self.permute(&mut state);
+        }
+        state[0]
+    }
+}
+
+impl<F: Field> HashSpec<F> for Spec<F> {
+    fn hash(&self, inputs: &[F]) -> F {
+        Spec::hash(self, inputs)
+    }
+}
+
+/// The permutations needed to absorb `num_inputs` inputs, at least one.
+pub(super) fn chunks(num_inputs: usize) -> std::ops::Range<usize> {
+    0..num_inputs.div_ceil(RATE).max(1)
+}
+
+/// The inverse of a 3 × 3 matrix, as its adjugate over its determinant.
+fn invert<F: Field>(m: &[[F; WIDTH]; WIDTH]) -> [[F; WIDTH]; WIDTH] {
+    let cofactor = |i: usize, j: usize| {
+        let [i1, i2, j1, j2] = [(i + 1) % 3, (i + 2) % 3, (j + 1) % 3, (j + 2) % 3];
+        m[i1][j1] * m[i2][j2] - m[i1][j2] * m[i2][j1]
+    };
+    let det: F = (0..WIDTH).map(|j| m[0][j] * cofactor(0, j)).sum();
+    let det_inv = det.invert().unwrap();
+    array::from_fn(|i| array::from_fn(|j| cofactor(j, i) * det_inv))
+}
+
+/// The `d` with `5 ⋅ d = 1 mod (p - 1)`, found as the integer among
+/// `(k ⋅ (p - 1) + 1) / 5` for `k` in `1..5`.
+fn alpha_inv<F: Field>() -> [u64; 4] {
+    let bytes = (-F::ONE).to_repr();
+    let p_minus_one: [u64; 4] = array::from_fn(|i| u64::from_le_bytes(bytes[8 * i..8 * i + 8].try_into().unwrap()));
+
+    for k in 1..5 {
+        let mut n = [0; 5];
+        let mut carry = 1;
+        for (limb, p) in n.iter_mut().zip(p_minus_one) {
+            let value = p as u128 * k + carry;
+            *limb = value as u64;
+            carry = value >> 64;
+        }
+        n[4] = carry as u64;
+
+        let mut d = [0; 5];
+        let mut rem = 0;
+        for (limb, n) in d.iter_mut().zip(n).rev() {
+            let value = (rem << 64) | n as u128;
+            *limb = (value / 5) as u64;
+            rem = value % 5;
+        }
+        if rem == 0 {
+            return d[..4].try_into().unwrap();
+        }
+    }
+    unreachable!("x^5 is a permutation of the field")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Spec, WIDTH};
+    use crate::field::{Field, TestField as Fp};
+
+    #[test]
+    fn inverses() {
+        let spec = Spec::<Fp>::new();
+        let x = Fp::from(0xdead_beef);
+        assert_eq!(x.pow_vartime(spec.alpha_inv).pow_vartime([5]), x);
+
+        for (i, row) in spec.mds.iter().enumerate() {
+            for j in 0..WIDTH {
+                let entry: Fp = row.iter().zip(&spec.mds_inv).map(|(m, inv)| *m * inv[j]).sum();
+                assert_eq!(entry, if i == j { Fp::ONE } else { Fp::ZERO });
+            }
+        }
+
+        // Undoing the MDS matrix and the constants of a backward half gives
+        // fifth roots of its input, as checked by the backward gate.
+        let mut state = [1, 2, 3].map(Fp::from);
+        spec.half_round(1, &mut state);
+        let unmixed: [Fp; WIDTH] = std::array::from_fn(|i| {
+            (0..WIDTH)
+                .map(|j| spec.mds_inv[i][j] * (state[j] - spec.round_constants[1][j]))
+                .sum()
+        });
+        assert_eq!(unmixed.map(|y| y.square().square() * y), [1, 2, 3].map(Fp::from));
+    }
+}
@@ -0,0 +1,371 @@
+//! RIPEMD-160 compression of one block, the last hash of Bitcoin's
+//! `hash160 = RIPEMD160(SHA256(pk))`.
+//!
+//! The additions and rotations of the steps of the [`spec`] are those of
+//! [`Word32Chip`]. The boolean functions decompose their inputs and output
+//! into nibbles like its XOR, and look up each `(f, x, y, z, f(x, y, z))` in a
+//! table of the five functions over nibbles:
+//!
+//! | x      | y      | z      | out      | function | q_f | q_f_end |
+//! | x      | y      | z      | out      | f        | 1   | 0       |
+//! | x >> 4 | y >> 4 | z >> 4 | out >> 4 | f        | 1   | 0       |
+//! | ...    | ...    | ...    | ...      | f        | 1   | 0       |
+//! | 0      | 0      | 0      | 0        |          | 0   | 1       |
+//!
+//! The table has `5 ⋅ 2^12` rows, so circuits using this chip need `k >= 15`.
+
+pub mod spec;
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Fixed, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use self::spec::{Line, BLOCK_WORDS, FUNCTIONS, IV, LEFT, RIGHT, STATE_WORDS, STEPS};
+use super::word32::{assign_nibbles, nibble, to_u32, Word32, Word32Chip, Word32Config};
+use crate::field::Field;
+
+/// Config for [`Ripemd160Chip`].
+#[derive(Clone, Debug)]
+pub struct Ripemd160Config {
+    q_f: Selector,
+    q_f_end: Selector,
+    inputs: [Column<Advice>; 3],
+    out: Column<Advice>,
+    function: Column<Fixed>,
+    table: [TableColumn; 5],
+    word: Word32Config,
+}
+
+/// Chip compressing blocks with RIPEMD-160.
+#[derive(Clone, Debug)]
+pub struct Ripemd160Chip<F: Field> {
+    config: Ripemd160Config,
+    word: Word32Chip<F>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> Chip<F> for Ripemd160Chip<F> {
+    type Config = Ripemd160Config;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field> Ripemd160Chip<F> {
+    /// Configures the chip, computing the boolean functions on the `advice`
+    /// columns, which may be shared with `word`.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        word: Word32Config,
+        advice: [Column<Advice>; 4],
+    ) -> Ripemd160Config {
+        let q_f = meta.complex_selector();
+        let q_f_end = meta.selector();
+        let [x, y, z, out] = advice;
+        let function = meta.fixed_column();
+        let table = [(); 5].map(|_| meta.lookup_table_column());
+
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        meta.lookup("ripemd160 f", |meta| {
+            let q_f = meta.query_selector(q_f);
+            let nibbles = advice.map(|column| nibble(meta, column));
+            let inputs = std::iter::once(meta.query_fixed(function, Rotation::cur())).chain(nibbles);
+
+            (inputs.zip(table))
+                .map(|(input, column)| (q_f.clone() * input, column))
+                .collect()
+        });
+
+        meta.create_gate("ripemd160 f end", |meta| {
+            let q_f_end = meta.query_selector(q_f_end);
+            advice.map(|column| q_f_end.clone() * meta.query_advice(column, Rotation::cur()))
+        });
+
+        Ripemd160Config {
+            q_f,
+            q_f_end,
+            inputs: [x, y, z],
+            out,
+            function,
+            table,
+            word,
+        }
+    }
+
+    pub fn construct(config: Ripemd160Config) -> Self {
+        Self {
+            word: Word32Chip::construct(config.word.clone()),
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Fills the `(f, x, y, z, f(x, y, z))` table over nibbles, and the table
+    /// of [`Word32Chip`], once per circuit.
+    pub fn load_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        self.word.load_table(layouter)?;
+
+        let table = self.config.table;
+        layouter.assign_table(
+            || "ripemd160 f table",
+            |mut t| {
+                let mut offset = 0;
+                for (f, function) in FUNCTIONS.iter().enumerate() {
+                    for x in 0..16 {
+                        for y in 0..16 {
+                            for z in 0..16 {
+                                let row = [f as u32, x, y, z, function(x, y, z) & 0xf];
+                                for (column, value) in table.iter().zip(row) {
+                                    let value = Value::known(F::from(value as u64));
+                                    t.assign_cell(|| "ripemd160 f table", *column, offset, || value)?;
+                                }
+                                offset += 1;
+                            }
+                        }
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Assigns [`IV`], the state before the first block.
+    pub fn initial_state(&self, layouter: impl Layouter<F>) -> Result<[AssignedCell<F, F>; STATE_WORDS], Error> {
+        let cells = self.word.constants(layouter, &IV)?;
+        Ok(cells.try_into().unwrap())
+    }
+
+    /// Returns the compression of `block` into `state`, as
+    /// [`spec::compress`], range checking all of them to 32 bits.
+    pub fn compress(
+        &self,
+        mut layouter: impl Layouter<F>,
+        state: &[AssignedCell<F, F>; STATE_WORDS],
+        block: &[AssignedCell<F, F>; BLOCK_WORDS],
+    ) -> Result<[AssignedCell<F, F>; STATE_WORDS], Error> {
+        let words: Vec<_> = state.iter().chain(block).cloned().collect();
+        let words = self.word.decompose_all(layouter.namespace(|| "words"), &words)?;
+        let (state, block) = words.split_at(STATE_WORDS);
+
+        let left = self.line(layouter.namespace(|| "left"), &LEFT, state, block)?;
+        let right = self.line(layouter.namespace(|| "right"), &RIGHT, state, block)?;
+
+        let mut output = Vec::with_capacity(STATE_WORDS);
+        for i in 0..STATE_WORDS {
+            let terms = [state[(i + 1) % 5].cell(), &left[(i + 2) % 5], &right[(i + 3) % 5]];
+            let word = self.word.add(layouter.namespace(|| format!("output {i}")), &terms, 0)?;
+            output.push(word.cell().clone());
+        }
+        Ok(output.try_into().unwrap())
+    }
+
+    /// Runs `line` over the decomposed `state` and `block`, returning its
+    /// final `(A, B, C, D, E)`.
+    fn line(
+        &self,
+        mut layouter: impl Layouter<F>,
+        line: &Line,
+        state: &[Word32<F>],
+        block: &[Word32<F>],
+    ) -> Result<[AssignedCell<F, F>; STATE_WORDS], Error> {
+        // `B` becomes `C`, which is rotated, so both keep their running sums.
+        let mut a = state[0].cell().clone();
+        let mut b = state[1].clone();
+        let mut c = state[2].clone();
+        let mut d = state[3].cell().clone();
+        let mut e = state[4].cell().clone();
+
+        for j in 0..STEPS {
+            let mut layouter = layouter.namespace(|| format!("step {j}"));
+            let f = self.f(layouter.namespace(|| "f"), line.f[j / 16], [b.cell(), c.cell(), &d])?;
+            let terms = [&a, &f, block[line.r[j]].cell()];
+            let sum = self.word.add(layouter.namespace(|| "sum"), &terms, line.k[j / 16])?;
+            let rotated = self.word.rotate_left(layouter.namespace(|| "rol s"), &sum, line.s[j])?;
+            let t = self.word.add(layouter.namespace(|| "t"), &[&rotated, &e], 0)?;
+            let rotated_c = self.word.rotate_left(layouter.namespace(|| "rol 10"), &c, 10)?;
+            (a, b, c, d, e) = (e, t, b, rotated_c, d);
+        }
+
+        Ok([a, b.cell().clone(), c.cell().clone(), d, e])
+    }
+
+    /// Returns `FUNCTIONS[f](x, y, z)`, looked up nibble by nibble.
+    fn f(
+        &self,
+        mut layouter: impl Layouter<F>,
+        f: usize,
+        inputs: [&AssignedCell<F, F>; 3],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+
+        layouter.assign_region(
+            || "ripemd160 f",
+            |mut region| {
+                for offset in 0..8 {
+                    config.q_f.enable(&mut region, offset)?;
+                    region.assign_fixed(|| "f", config.function, offset, || Value::known(F::from(f as u64)))?;
+                }
+                config.q_f_end.enable(&mut region, 8)?;
+
+                let mut values = vec![];
+                for (input, column) in inputs.iter().zip(config.inputs) {
+                    input.copy_advice(|| "input", &mut region, column, 0)?;
+                    let value = input.value().map(to_u32);
+                    assign_nibbles(&mut region, column, value)?;
+                    values.push(value);
+                }
+
+                let out = values[0]
+                    .zip(values[1])
+                    .zip(values[2])
+                    .map(|((x, y), z)| FUNCTIONS[f](x, y, z));
+                let cell = region.assign_advice(|| "out", config.out, 0, || out.map(|out| F::from(out as u64)))?;
+                assign_nibbles(&mut region, config.out, out)?;
+                Ok(cell)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+    };
+
+    use super::{
+        spec::{self, BLOCK_WORDS, IV},
+        Ripemd160Chip, Ripemd160Config,
+    };
+    use crate::{
+        circuits::{
+            gadgets::{bits::BitsConfig, word32::Word32Chip},
+            instance::{InstanceColumns, InstanceLayout},
+        },
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::TestField as Fp,
+    };
+
+    /// Exposes the state after compressing `blocks` from the initial state.
+    #[derive(Default)]
+    struct TestCircuit {
+        blocks: Vec<[Value<u64>; BLOCK_WORDS]>,
+    }
+
+    impl TestCircuit {
+        fn new(message: &[u8]) -> Self {
+            let blocks = spec::pad(message);
+            Self {
+                blocks: blocks
+                    .iter()
+                    .map(|block| block.map(|word| Value::known(word as u64)))
+                    .collect(),
+            }
+        }
+    }
+
+    impl Circuit<Fp> for TestCircuit {
+        type Config = (Ripemd160Config, Column<Advice>, InstanceColumns);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                blocks: vec![[Value::unknown(); BLOCK_WORDS]; self.blocks.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let [input, acc, t0, t1, t2, sum, carry] = [(); 7].map(|_| meta.advice_column());
+            let constant = meta.fixed_column();
+            meta.enable_equality(input);
+
+            let bits = BitsConfig::configure(meta, acc);
+            let word = Word32Chip::configure(meta, [t0, t1, t2, sum, carry], bits, constant);
+            let config = Ripemd160Chip::configure(meta, word, [t0, t1, t2, sum]);
+            let instance = InstanceLayout::new()
+                .column(["h0", "h1", "h2", "h3", "h4"])
+                .configure(meta);
+            (config, input, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, input, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = Ripemd160Chip::construct(config);
+            chip.load_table(&mut layouter)?;
+
+            let mut state = chip.initial_state(layouter.namespace(|| "iv"))?;
+            for (i, block) in self.blocks.iter().enumerate() {
+                let block = layouter.assign_region(
+                    || "inputs",
+                    |mut region| {
+                        (block.iter().enumerate())
+                            .map(|(j, word)| region.assign_advice(|| "word", input, j, || word.map(Fp::from)))
+                            .collect::<Result<Vec<_>, _>>()
+                    },
+                )?;
+                let block = block.try_into().unwrap();
+                state = chip.compress(layouter.namespace(|| format!("block {i}")), &state, &block)?;
+            }
+
+            for (i, word) in state.iter().enumerate() {
+                instance.expose_public(&mut layouter, word, i)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn digest(message: &[u8]) -> Vec<Fp> {
+        let state = spec::pad(message).iter().fold(IV, spec::compress);
+        state.map(|word| Fp::from(word as u64)).to_vec()
+    }
+
+    #[test]
+    fn ripemd160() {
+        // One and two blocks.
+        for message in [&b"abc"[..], &[b'a'; 64]] {
+            expect_satisfied(&TestCircuit::new(message), vec![digest(message)]);
+        }
+
+        expect_failure(
+            &TestCircuit::new(b"abc"),
+            vec![digest(b"abd")],
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+
+        // A message word of 33 bits.
+        let mut circuit = TestCircuit::new(b"abc");
+        circuit.blocks[0][0] = Value::known(1 << 32);
+        expect_failure(
+            &circuit,
+            vec![digest(b"abc")],
+            FailureMatcher::Constraint {
+                gate: "bits end",
+                location: Location::InRegion {
+                    region: "32 bits",
+                    offset: 32,
+                },
+            },
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! Constants of RIPEMD-160 and its native evaluation.
+//!
+//! The compression function runs two lines of [`STEPS`] steps over a copy of
+//! the state, each step being
+//!
+//! ```text
+//! T = rol_s(A + f(B, C, D) + X[r] + K) + E
+//! (A, B, C, D, E) = (E, T, B, rol_10(C), D)
+//! ```
+//!
+//! with the message word `r`, rotation `s`, constant `K` and boolean function
+//! `f` of the step in its line, and the lines are mixed back into the state.
+
+/// Words of the state.
+pub const STATE_WORDS: usize = 5;
+/// Words of a message block.
+pub const BLOCK_WORDS: usize = 16;
+/// Steps of each line.
+pub const STEPS: usize = 80;
+/// Rounds of each line, of 16 steps sharing a constant and boolean function.
+pub const ROUNDS: usize = 5;
+
+/// The initial state.
+pub const IV: [u32; STATE_WORDS] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+/// One of the two parallel lines of the compression function.
+#[derive(Clone, Copy, Debug)]
+pub struct Line {
+    /// Message word of each step.
+    pub r: [usize; STEPS],
+    /// Rotation of each step.
+    pub s: [u32; STEPS],
+    /// Constant of each round.
+    pub k: [u32; ROUNDS],
+    /// Boolean function of each round, as an index in [`FUNCTIONS`].
+    pub f: [usize; ROUNDS],
+}
+
+pub const LEFT: Line = Line {
+    r: [
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, //
+        7, 4, 13, 1, 10, 6, 15, 3, 12, 0, 9, 5, 2, 14, 11, 8, //
+        3, 10, 14, 4, 9, 15, 8, 1, 2, 7, 0, 6, 13, 11, 5, 12, //
+        1, 9, 11, 10, 0, 8, 12, 4, 13, 3, 7, 15, 14, 5, 6, 2, //
+        4, 0, 5, 9, 7, 12, 2, 10, 14, 1, 3, 8, 11, 6, 15, 13,
+    ],
+    s: [
+        11, 14, 15, 12, 5, 8, 7, 9, 11, 13, 14, 15, 6, 7, 9, 8, //
+        7, 6, 8, 13, 11, 9, 7, 15, 7, 12, 15, 9, 11, 7, 13, 12, //
+        11, 13, 6, 7, 14, 9, 13, 15, 14, 8, 13, 6, 5, 12, 7, 5, //
+        11, 12, 14, 15, 14, 15, 9, 8, 9, 14, 5, 6, 8, 6, 5, 12, //
+        9, 15, 5, 11, 6, 8, 13, 12, 5, 12, 13, 14, 11, 8, 5, 6,
+    ],
+    k: [0x00000000, 0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xa953fd4e],
+    f: [0, 1, 2, 3, 4],
+};
+
+pub const RIGHT: Line = Line {
+    r: [
+        5, 14, 7, 0, 9, 2, 11, 4, 13, 6, 15, 8, 1, 10, 3, 12, //
+        6, 11, 3, 7, 0, 13, 5, 10, 14, 15, 8, 12, 4, 9, 1, 2, //
+        15, 5, 1, 3, 7, 14, 6, 9, 11, 8, 12, 2, 10, 0, 4, 13, //
+        8, 6, 4, 1, 3, 11, 15, 0, 5, 12, 2, 13, 9, 7, 10, 14, //
+        12, 15, 10, 4, 1, 5, 8, 7, 6, 2, 13, 14, 0, 3, 9, 11,
+    ],
+    s: [
+        8, 9, 9, 11, 13, 15, 15, 5, 7, 7, 8, 11, 14, 14, 12, 6, //
+        9, 13, 15, 7, 12, 8, 9, 11, 7, 7, 12, 7, 6, 15, 13, 11, //
+        9, 7, 15, 11, 8, 6, 6, 14, 12, 13, 5, 14, 13, 13, 7, 5, //
+        15, 5, 8, 11, 14, 14, 6, 14, 6, 9, 12, 9, 12, 5, 15, 8, //
+        8, 5, 12, 9, 12, 5, 14, 6, 8, 13, 6, 5, 15, 13, 11, 11,
+    ],
+    k: [0x50a28be6, 0x5c4dd124, 0x6d703ef3, 0x7a6d76e9, 0x00000000],
+    f: [4, 3, 2, 1, 0],
+};
+
+/// The boolean functions of the rounds, applied bitwise.
+pub const FUNCTIONS: [fn(u32, u32, u32) -> u32; ROUNDS] = [
+    |x, y, z| x ^ y ^ z,
+    |x, y, z| (x & y) | (!x & z),
+    |x, y, z| (x | !y) ^ z,
+    |x, y, z| (x & z) | (y & !z),
+    |x, y, z| x ^ (y | !z),
+];
+
+impl Line {
+    /// Runs the line over `state`, returning its final `(A, B, C, D, E)`.
+    pub fn run(&self, state: [u32; STATE_WORDS], block: &[u32; BLOCK_WORDS]) -> [u32; STATE_WORDS] {
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for j in 0..STEPS {
+            let f = FUNCTIONS[self.f[j / 16]](b, c, d);
+            let sum = a
+                .wrapping_add(f)
+                .wrapping_add(block[self.r[j]])
+                .wrapping_add(self.k[j / 16]);
+            let t = sum.rotate_left(self.s[j]).wrapping_add(e);
+            (a, b, c, d, e) = (e, t, b, c.rotate_left(10), d);
+        }
+        [a, b, c, d, e]
+    }
+}
+
+/// Compresses `block` into `state`.
+pub fn compress(state: [u32; STATE_WORDS], block: &[u32; BLOCK_WORDS]) -> [u32; STATE_WORDS] {
+    let left = LEFT.run(state, block);
+    let right = RIGHT.run(state, block);
+    [
+        state[1].wrapping_add(left[2]).wrapping_add(right[3]),
+        state[2].wrapping_add(left[3]).wrapping_add(right[4]),
+        state[3].wrapping_add(left[4]).wrapping_add(right[0]),
+        state[4].wrapping_add(left[0]).wrapping_add(right[1]),
+        state[0].wrapping_add(left[1]).wrapping_add(right[2]),
+    ]
+}
+
+/// Splits `message` into padded blocks of little-endian words: a one bit,
+/// zeros and the bit length as a little-endian `u64`.
+pub fn pad(message: &[u8]) -> Vec<[u32; BLOCK_WORDS]> {
+    let mut bytes = message.to_vec();
+    bytes.push(0x80);
+    while bytes.len() % 64 != 56 {
+        bytes.push(0);
+    }
+    bytes.extend_from_slice(&(message.len() as u64 * 8).to_le_bytes());
+
+    (bytes.chunks(64))
+        .map(|block| std::array::from_fn(|i| u32::from_le_bytes(block[4 * i..4 * i + 4].try_into().unwrap())))
+        .collect()
+}
+
+/// The RIPEMD-160 digest of `message`.
+pub fn hash(message: &[u8]) -> [u8; 20] {
+    let state = pad(message).iter().fold(IV, compress);
+    let mut digest = [0; 20];
+    for (chunk, word) in digest.chunks_mut(4).zip(state) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::hash;
+
+    #[test]
+    fn test_vectors() {
+        for (message, digest) in [
+            ("", "9c1185a5c5e9fc54612808977ee8f548b2258d31"),
+            ("abc", "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc"),
+            ("message digest", "5d0689ef49d2fae572b881b123a85ffa21595f36"),
+        ] {
+            assert_eq!(hex::encode(hash(message.as_bytes())), digest);
+        }
+    }
+}
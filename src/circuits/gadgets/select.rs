@@ -0,0 +1,206 @@
+//! Select between two cells on a boolean condition.
+//!
+//! | cond | a | b | out                     | q_select |
+//! | c    | a | b | c ⋅ a + (1 - c) ⋅ b     | 1        |
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::field::Field;
+
+/// Config for [`SelectChip`].
+#[derive(Clone, Debug)]
+pub struct SelectConfig {
+    q_select: Selector,
+    cond: Column<Advice>,
+    a: Column<Advice>,
+    b: Column<Advice>,
+    out: Column<Advice>,
+}
+
+/// Chip returning `a` if `cond` is 1 and `b` if it is 0.
+#[derive(Clone, Debug)]
+pub struct SelectChip<F: Field> {
+    config: SelectConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> Chip<F> for SelectChip<F> {
+    type Config = SelectConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field> SelectChip<F> {
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> SelectConfig {
+        let q_select = meta.selector();
+        let [cond, a, b, out] = [(); 4].map(|_| meta.advice_column());
+
+        for column in [cond, a, b, out] {
+            meta.enable_equality(column);
+        }
+
+        meta.create_gate("select", |meta| {
+            let q_select = meta.query_selector(q_select);
+            let [cond, a, b, out] = [cond, a, b, out].map(|column| meta.query_advice(column, Rotation::cur()));
+
+            vec![
+                q_select.clone() * cond.clone() * (Expression::Constant(F::ONE) - cond.clone()),
+                q_select * (out - b.clone() - cond * (a - b)),
+            ]
+        });
+
+        SelectConfig {
+            q_select,
+            cond,
+            a,
+            b,
+            out,
+        }
+    }
+
+    pub fn construct(config: SelectConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns `cond ? a : b`, constraining `cond` to be boolean.
+    pub fn select(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cond: &AssignedCell<F, F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        layouter.assign_region(
+            || "select",
+            |mut region| {
+                config.q_select.enable(&mut region, 0)?;
+                let cond = cond.copy_advice(|| "cond", &mut region, config.cond, 0)?;
+                let a = a.copy_advice(|| "a", &mut region, config.a, 0)?;
+                let b = b.copy_advice(|| "b", &mut region, config.b, 0)?;
+
+                let out = cond
+                    .value()
+                    .zip(a.value().zip(b.value()))
+                    .map(|(cond, (a, b))| if *cond == F::ONE { *a } else { *b });
+                region.assign_advice(|| "out", config.out, 0, || out)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+
+    use super::{SelectChip, SelectConfig};
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::{Field, TestField as Fp},
+    };
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig {
+        select: SelectConfig,
+        input: Column<Advice>,
+        instance: Column<Instance>,
+    }
+
+    /// Exposes `cond ? a : b`.
+    #[derive(Default)]
+    struct TestCircuit {
+        cond: Value<u64>,
+        a: Value<u64>,
+        b: Value<u64>,
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit {
+        type Config = TestCircuitConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let select = SelectChip::configure(meta);
+            let input = meta.advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(input);
+            meta.enable_equality(instance);
+
+            TestCircuitConfig {
+                select,
+                input,
+                instance,
+            }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let chip = SelectChip::construct(config.select);
+            let [cond, a, b] = layouter.assign_region(
+                || "inputs",
+                |mut region| {
+                    let cond = region.assign_advice(|| "cond", config.input, 0, || self.cond.map(F::from))?;
+                    let a = region.assign_advice(|| "a", config.input, 1, || self.a.map(F::from))?;
+                    let b = region.assign_advice(|| "b", config.input, 2, || self.b.map(F::from))?;
+                    Ok([cond, a, b])
+                },
+            )?;
+
+            let out = chip.select(layouter.namespace(|| "select"), &cond, &a, &b)?;
+            layouter.constrain_instance(out.cell(), config.instance, 0)
+        }
+    }
+
+    #[test]
+    fn select() {
+        for (cond, out) in [(1, 3), (0, 5)] {
+            let circuit = TestCircuit {
+                cond: Value::known(cond),
+                a: Value::known(3),
+                b: Value::known(5),
+            };
+            expect_satisfied(&circuit, vec![vec![Fp::from(out)]]);
+        }
+
+        // A condition of 2 is not boolean.
+        let circuit = TestCircuit {
+            cond: Value::known(2),
+            a: Value::known(3),
+            b: Value::known(5),
+        };
+        expect_failure(
+            &circuit,
+            vec![vec![Fp::from(5)]],
+            FailureMatcher::Constraint {
+                gate: "select",
+                location: Location::InRegion {
+                    region: "select",
+                    offset: 0,
+                },
+            },
+        );
+    }
+}
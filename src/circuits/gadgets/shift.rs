@@ -0,0 +1,385 @@
+//! Shifts and rotations of 64-bit words by a constant or witnessed amount.
+//!
+//! Every operation is one multiplication by a power of two: for `t` in
+//! `[0, 64]`, `x * 2^t` is split into `hi * 2^64 + lo` with `x`, `lo` and `hi`
+//! decomposed into 64 bits by [`BitsConfig`]. Then
+//!
+//! - `x << s` is `lo` with `t = s`,
+//! - `x >> s` is `hi` with `t = 64 - s`,
+//! - `x.rotate_left(s)` is `lo + hi` with `t = s`,
+//! - `x.rotate_right(s)` is `lo + hi` with `t = 64 - s`.
+//!
+//! `(t, 2^t)` is looked up in a table of the 65 powers, which also bounds the
+//! amount to `[0, 64]`.
+//!
+//! | x | s | t | pow | lo | hi | out | q_shl | q_shr | q_rotl | q_rotr |
+//! | x | s | t | 2^t | lo | hi | out | 1     | 0     | 0      | 0      |
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use super::bits::BitsConfig;
+use crate::field::Field;
+
+/// A shift or rotation of a 64-bit word.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShiftOp {
+    Shl = 0,
+    Shr = 1,
+    Rotl = 2,
+    Rotr = 3,
+}
+
+impl ShiftOp {
+    pub const ALL: [ShiftOp; 4] = [ShiftOp::Shl, ShiftOp::Shr, ShiftOp::Rotl, ShiftOp::Rotr];
+
+    /// Native result, for amounts in `[0, 64]`.
+    pub fn apply(self, x: u64, amount: u32) -> u64 {
+        match self {
+            ShiftOp::Shl => x.checked_shl(amount).unwrap_or(0),
+            ShiftOp::Shr => x.checked_shr(amount).unwrap_or(0),
+            ShiftOp::Rotl => x.rotate_left(amount),
+            ShiftOp::Rotr => x.rotate_right(amount),
+        }
+    }
+
+    /// Whether `x` is multiplied by `2^s` rather than `2^(64 - s)`.
+    fn is_left(self) -> bool {
+        matches!(self, ShiftOp::Shl | ShiftOp::Rotl)
+    }
+}
+
+/// Amount of a shift, in `[0, 64]`.
+#[derive(Clone, Copy, Debug)]
+pub enum ShiftAmount<'a, F: Field> {
+    Constant(u32),
+    Witness(&'a AssignedCell<F, F>),
+}
+
+/// Config for [`ShiftChip`].
+#[derive(Clone, Debug)]
+pub struct ShiftConfig {
+    q_ops: [Selector; 4],
+    x: Column<Advice>,
+    s: Column<Advice>,
+    t: Column<Advice>,
+    pow: Column<Advice>,
+    lo: Column<Advice>,
+    hi: Column<Advice>,
+    out: Column<Advice>,
+    constant: Column<Fixed>,
+    table: [TableColumn; 2],
+    bits: BitsConfig,
+}
+
+/// Chip shifting and rotating 64-bit words.
+#[derive(Clone, Debug)]
+pub struct ShiftChip<F: Field> {
+    config: ShiftConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> Chip<F> for ShiftChip<F> {
+    type Config = ShiftConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field> ShiftChip<F> {
+    /// Configures the chip, range checking words with `bits` and taking
+    /// constant amounts from `constant`.
+    pub fn configure(meta: &mut ConstraintSystem<F>, bits: BitsConfig, constant: Column<Fixed>) -> ShiftConfig {
+        let q_ops = [(); 4].map(|_| meta.complex_selector());
+        let [x, s, t, pow, lo, hi, out] = [(); 7].map(|_| meta.advice_column());
+        let table = [(); 2].map(|_| meta.lookup_table_column());
+
+        for column in [x, s, lo, hi, out] {
+            meta.enable_equality(column);
+        }
+        meta.enable_constant(constant);
+
+        meta.create_gate("shift", |meta| {
+            let [q_shl, q_shr, q_rotl, q_rotr] = q_ops.map(|q| meta.query_selector(q));
+            let [x, s, t, pow, lo, hi, out] =
+                [x, s, t, pow, lo, hi, out].map(|column| meta.query_advice(column, Rotation::cur()));
+            let two_64 = Expression::Constant(F::from_u128(1 << 64));
+            let sixty_four = Expression::Constant(F::from(64));
+
+            let q_any = q_shl.clone() + q_shr.clone() + q_rotl.clone() + q_rotr.clone();
+            let q_left = q_shl.clone() + q_rotl.clone();
+            let q_right = q_shr.clone() + q_rotr.clone();
+            let q_rot = q_rotl + q_rotr;
+
+            vec![
+                q_any * (x * pow - (hi.clone() * two_64 + lo.clone())),
+                q_left * (t.clone() - s.clone()) + q_right * (t - (sixty_four - s)),
+                q_shl * (out.clone() - lo.clone()) + q_shr * (out.clone() - hi.clone()) + q_rot * (out - lo - hi),
+            ]
+        });
+
+        // Unused rows look up `(0, 1)`.
+        meta.lookup("shift pow2", |meta| {
+            let q_any = q_ops
+                .map(|q| meta.query_selector(q))
+                .into_iter()
+                .reduce(|acc, q| acc + q)
+                .unwrap();
+            let t = meta.query_advice(t, Rotation::cur());
+            let pow = meta.query_advice(pow, Rotation::cur());
+
+            vec![
+                (q_any.clone() * t, table[0]),
+                (q_any.clone() * pow + (Expression::Constant(F::ONE) - q_any), table[1]),
+            ]
+        });
+
+        ShiftConfig {
+            q_ops,
+            x,
+            s,
+            t,
+            pow,
+            lo,
+            hi,
+            out,
+            constant,
+            table,
+            bits,
+        }
+    }
+
+    pub fn construct(config: ShiftConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Fills the `(t, 2^t)` table, once per circuit.
+    pub fn load_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        let table = self.config.table;
+        layouter.assign_table(
+            || "pow2 table",
+            |mut t| {
+                for i in 0..=64 {
+                    t.assign_cell(|| "t", table[0], i, || Value::known(F::from(i as u64)))?;
+                    t.assign_cell(|| "2^t", table[1], i, || Value::known(F::from_u128(1 << i)))?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Applies `op` to the 64-bit word `x`, also range checking `x`.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        op: ShiftOp,
+        x: &AssignedCell<F, F>,
+        amount: ShiftAmount<'_, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+
+        let (x, lo, hi, out) = layouter.assign_region(
+            || format!("shift {op:?}"),
+            |mut region| {
+                config.q_ops[op as usize].enable(&mut region, 0)?;
+
+                let x = x.copy_advice(|| "x", &mut region, config.x, 0)?;
+                let s = match amount {
+                    ShiftAmount::Constant(s) => {
+                        region.assign_advice_from_constant(|| "s", config.s, 0, F::from(s as u64))?
+                    }
+                    ShiftAmount::Witness(s) => s.copy_advice(|| "s", &mut region, config.s, 0)?,
+                };
+
+                let t = s.value().map(|s| if op.is_left() { *s } else { F::from(64) - s });
+                // Out of range amounts get a zero power, which the lookup rejects.
+                let t_u64 = t.map(|t| to_u64(&t).filter(|t| *t <= 64));
+                let product = x
+                    .value()
+                    .zip(t_u64)
+                    .map(|(x, t)| t.map_or(0, |t| (to_u64(x).unwrap_or(0) as u128) << t));
+                let pow = t_u64.map(|t| t.map_or(F::ZERO, |t| F::from_u128(1 << t)));
+                let lo = product.map(|p| F::from(p as u64));
+                let hi = product.map(|p| F::from((p >> 64) as u64));
+
+                region.assign_advice(|| "t", config.t, 0, || t)?;
+                region.assign_advice(|| "pow", config.pow, 0, || pow)?;
+                let lo = region.assign_advice(|| "lo", config.lo, 0, || lo)?;
+                let hi = region.assign_advice(|| "hi", config.hi, 0, || hi)?;
+
+                let out = match op {
+                    ShiftOp::Shl => lo.value().copied(),
+                    ShiftOp::Shr => hi.value().copied(),
+                    ShiftOp::Rotl | ShiftOp::Rotr => lo.value().copied() + hi.value().copied(),
+                };
+                let out = region.assign_advice(|| "out", config.out, 0, || out)?;
+
+                Ok((x, lo, hi, out))
+            },
+        )?;
+
+        for (name, cell) in [("x", &x), ("lo", &lo), ("hi", &hi)] {
+            config.bits.decompose(layouter.namespace(|| name), cell, 64)?;
+        }
+
+        Ok(out)
+    }
+}
+
+/// The value as a `u64`, if it fits.
+fn to_u64<F: Field>(value: &F) -> Option<u64> {
+    let repr = value.to_repr();
+    repr[8..]
+        .iter()
+        .all(|byte| *byte == 0)
+        .then(|| u64::from_le_bytes(repr[..8].try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+
+    use super::{ShiftAmount, ShiftChip, ShiftConfig, ShiftOp};
+    use crate::{
+        circuits::gadgets::bits::BitsConfig,
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::{Field, TestField as Fp},
+    };
+
+    const CONSTANT: u32 = 13;
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig {
+        shift: ShiftConfig,
+        input: Column<Advice>,
+        instance: Column<Instance>,
+    }
+
+    /// Exposes every op of `x` by the witnessed `s`, then by [`CONSTANT`].
+    #[derive(Default)]
+    struct TestCircuit {
+        x: Value<u64>,
+        s: Value<u64>,
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit {
+        type Config = TestCircuitConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let [input, acc] = [(); 2].map(|_| meta.advice_column());
+            let constant = meta.fixed_column();
+            let bits = BitsConfig::configure(meta, acc);
+            let shift = ShiftChip::configure(meta, bits, constant);
+            let instance = meta.instance_column();
+            meta.enable_equality(input);
+            meta.enable_equality(instance);
+
+            TestCircuitConfig { shift, input, instance }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let chip = ShiftChip::construct(config.shift);
+            chip.load_table(&mut layouter)?;
+
+            let (x, s) = layouter.assign_region(
+                || "inputs",
+                |mut region| {
+                    let x = region.assign_advice(|| "x", config.input, 0, || self.x.map(F::from))?;
+                    let s = region.assign_advice(|| "s", config.input, 1, || self.s.map(F::from))?;
+                    Ok((x, s))
+                },
+            )?;
+
+            let amounts = [ShiftAmount::Witness(&s), ShiftAmount::Constant(CONSTANT)];
+            for (i, (amount, op)) in amounts
+                .into_iter()
+                .flat_map(|amount| ShiftOp::ALL.map(|op| (amount, op)))
+                .enumerate()
+            {
+                let out = chip.assign(layouter.namespace(|| format!("{op:?}")), op, &x, amount)?;
+                layouter.constrain_instance(out.cell(), config.instance, i)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn instances(x: u64, s: u32) -> Vec<Vec<Fp>> {
+        let outputs = [s, CONSTANT]
+            .into_iter()
+            .flat_map(|s| ShiftOp::ALL.map(|op| Fp::from(op.apply(x, s))))
+            .collect();
+        vec![outputs]
+    }
+
+    #[test]
+    fn shifts() {
+        let x = 0xdead_beef_0123_4567;
+        for s in [0, 1, 31, 63, 64] {
+            let circuit = TestCircuit {
+                x: Value::known(x),
+                s: Value::known(s as u64),
+            };
+            expect_satisfied(&circuit, instances(x, s));
+        }
+
+        let circuit = TestCircuit {
+            x: Value::known(x),
+            s: Value::known(7),
+        };
+        let mut wrong = instances(x, 7);
+        wrong[0][0] = Fp::from(x << 8);
+        expect_failure(
+            &circuit,
+            wrong,
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+    }
+
+    #[test]
+    fn amount_out_of_range() {
+        let circuit = TestCircuit {
+            x: Value::known(1),
+            s: Value::known(65),
+        };
+        // The witness falls back to a zero power and output.
+        let mut instances = instances(1, 0);
+        instances[0][..4].fill(Fp::from(0));
+        expect_failure(
+            &circuit,
+            instances,
+            FailureMatcher::Lookup {
+                name: "shift pow2",
+                location: Location::InRegion {
+                    region: "shift Shl",
+                    offset: 0,
+                },
+            },
+        );
+    }
+}
@@ -0,0 +1,237 @@
+//! Sortedness of a column: `values[i - 1] <= values[i]` for every row.
+//!
+//! Each row decomposes the difference with the previous row into `N_BYTES`
+//! bytes looked up in the [`U8Table`], which proves it is in
+//! `[0, 2^(8 ⋅ N_BYTES))`. The first row decomposes the first value itself,
+//! so that no value can wrap around the modulus.
+//!
+//! | value    | diff[0..N_BYTES]              | q_first | q_step |
+//! | v_0      | bytes of v_0                  | 1       | 0      |
+//! | v_1      | bytes of v_1 - v_0            | 0       | 1      |
+//! | ...      | ...                           | 0       | 1      |
+//!
+//! Consecutive values may thus differ by less than `2^(8 ⋅ N_BYTES)`.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::{circuits::table::U8Table, field::Field};
+
+/// Config for [`SortedChip`].
+#[derive(Clone, Debug)]
+pub struct SortedConfig<const N_BYTES: usize> {
+    q_first: Selector,
+    q_step: Selector,
+    q_byte: Selector,
+    value: Column<Advice>,
+    diff: [Column<Advice>; N_BYTES],
+}
+
+/// Chip proving that cells are sorted in non-decreasing order.
+#[derive(Clone, Debug)]
+pub struct SortedChip<F: Field, const N_BYTES: usize> {
+    config: SortedConfig<N_BYTES>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field, const N_BYTES: usize> Chip<F> for SortedChip<F, N_BYTES> {
+    type Config = SortedConfig<N_BYTES>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field, const N_BYTES: usize> SortedChip<F, N_BYTES> {
+    pub fn configure(meta: &mut ConstraintSystem<F>, u8_table: U8Table) -> SortedConfig<N_BYTES> {
+        let q_first = meta.selector();
+        let q_step = meta.selector();
+        let q_byte = meta.complex_selector();
+        let value = meta.advice_column();
+        let diff = [(); N_BYTES].map(|_| meta.advice_column());
+
+        meta.enable_equality(value);
+
+        for column in diff {
+            meta.lookup("sorted diff byte", |meta| {
+                let q_byte = meta.query_selector(q_byte);
+                let byte = meta.query_advice(column, Rotation::cur());
+                vec![(q_byte * byte, u8_table.value)]
+            });
+        }
+
+        meta.create_gate("sorted", |meta| {
+            let q_first = meta.query_selector(q_first);
+            let q_step = meta.query_selector(q_step);
+            let value = meta.query_advice(value, Rotation::cur());
+            let value_prev = meta.query_advice(value, Rotation::prev());
+            let diff = (diff.iter().enumerate()).fold(Expression::Constant(F::ZERO), |acc, (j, column)| {
+                let weight = F::from(256).pow_vartime([j as u64]);
+                acc + meta.query_advice(*column, Rotation::cur()) * Expression::Constant(weight)
+            });
+
+            vec![
+                q_first * (value.clone() - diff.clone()),
+                q_step * (value - value_prev - diff),
+            ]
+        });
+
+        SortedConfig {
+            q_first,
+            q_step,
+            q_byte,
+            value,
+            diff,
+        }
+    }
+
+    pub fn construct(config: SortedConfig<N_BYTES>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Constrains `values` to be sorted in non-decreasing order, returning
+    /// their copies in the region.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        values: &[AssignedCell<F, F>],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let config = &self.config;
+        layouter.assign_region(
+            || "sorted",
+            |mut region| {
+                let mut prev = Value::known(F::ZERO);
+                let mut copies = Vec::with_capacity(values.len());
+                for (row, value) in values.iter().enumerate() {
+                    if row == 0 {
+                        config.q_first.enable(&mut region, row)?;
+                    } else {
+                        config.q_step.enable(&mut region, row)?;
+                    }
+                    config.q_byte.enable(&mut region, row)?;
+
+                    let value = value.copy_advice(|| "value", &mut region, config.value, row)?;
+                    let diff = value.value().copied() - prev;
+                    for (j, column) in config.diff.iter().enumerate() {
+                        let byte = diff.map(|diff| F::from(diff.to_repr()[j] as u64));
+                        region.assign_advice(|| format!("diff[{j}]"), *column, row, || byte)?;
+                    }
+                    prev = value.value().copied();
+                    copies.push(value);
+                }
+                Ok(copies)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+    };
+
+    use super::{SortedChip, SortedConfig};
+    use crate::{
+        circuits::table::U8Table,
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::{Field, TestField as Fp},
+    };
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig {
+        sorted: SortedConfig<2>,
+        u8_table: U8Table,
+        input: Column<Advice>,
+    }
+
+    /// Checks that `values` are sorted, with differences of up to 2 bytes.
+    #[derive(Default)]
+    struct TestCircuit {
+        values: Vec<u64>,
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit {
+        type Config = TestCircuitConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                values: vec![0; self.values.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let u8_table = U8Table::configure(meta);
+            let sorted = SortedChip::configure(meta, u8_table);
+            let input = meta.advice_column();
+            meta.enable_equality(input);
+
+            TestCircuitConfig {
+                sorted,
+                u8_table,
+                input,
+            }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let chip = SortedChip::construct(config.sorted);
+            config.u8_table.load(&mut layouter)?;
+
+            let values = layouter.assign_region(
+                || "values",
+                |mut region| {
+                    (self.values.iter().enumerate())
+                        .map(|(i, value)| {
+                            region.assign_advice(|| "value", config.input, i, || Value::known(F::from(*value)))
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                },
+            )?;
+            let sorted = chip.assign(layouter.namespace(|| "sorted"), &values)?;
+            assert_eq!(sorted.len(), values.len());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn sorted() {
+        expect_satisfied::<Fp, _>(
+            &TestCircuit {
+                values: vec![0, 0, 1, 300, 300, 65835],
+            },
+            vec![],
+        );
+
+        // 4 comes after 5: the difference -1 doesn't fit in 2 bytes.
+        expect_failure::<Fp, _>(
+            &TestCircuit {
+                values: vec![1, 5, 4, 7],
+            },
+            vec![],
+            FailureMatcher::Constraint {
+                gate: "sorted",
+                location: Location::InRegion {
+                    region: "sorted",
+                    offset: 2,
+                },
+            },
+        );
+    }
+}
@@ -0,0 +1,240 @@
+//! Square roots in the field, and quadratic residuosity.
+//!
+//! Given `x`, witnesses `is_square` and a root `r` of either `x` or `n ⋅ x`,
+//! where `n` is the fixed non-residue [`MULTIPLICATIVE_GENERATOR`]:
+//!
+//! | x | r | x_inv | is_square | q_sqrt |
+//! | x | r | 1/x   | 1 or 0    | 1      |
+//!
+//! - `r^2 = is_square ? x : n ⋅ x`,
+//! - `is_square` is boolean,
+//! - `x` is invertible when `is_square` is 0.
+//!
+//! As `n ⋅ x` is a non-residue exactly when `x` is a non-zero residue, only
+//! one value of `is_square` has a root. The last constraint settles `x = 0`,
+//! for which both `x` and `n ⋅ x` are squares.
+//!
+//! [`MULTIPLICATIVE_GENERATOR`]: halo2_proofs::halo2curves::group::ff::PrimeField::MULTIPLICATIVE_GENERATOR
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::field::Field;
+
+/// Config for [`SqrtChip`].
+#[derive(Clone, Debug)]
+pub struct SqrtConfig {
+    q_sqrt: Selector,
+    x: Column<Advice>,
+    root: Column<Advice>,
+    x_inv: Column<Advice>,
+    is_square: Column<Advice>,
+}
+
+/// Results of a square root.
+#[derive(Clone, Debug)]
+pub struct SqrtCells<F: Field> {
+    /// A root of `x` if it is a square, and of `n ⋅ x` otherwise.
+    pub root: AssignedCell<F, F>,
+    /// 1 if `x` is a square, and 0 otherwise.
+    pub is_square: AssignedCell<F, F>,
+}
+
+/// Chip witnessing square roots.
+#[derive(Clone, Debug)]
+pub struct SqrtChip<F: Field> {
+    config: SqrtConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> Chip<F> for SqrtChip<F> {
+    type Config = SqrtConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field> SqrtChip<F> {
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> SqrtConfig {
+        let q_sqrt = meta.selector();
+        let [x, root, x_inv, is_square] = [(); 4].map(|_| meta.advice_column());
+
+        for column in [x, root, is_square] {
+            meta.enable_equality(column);
+        }
+
+        meta.create_gate("sqrt", |meta| {
+            let q_sqrt = meta.query_selector(q_sqrt);
+            let [x, root, x_inv, is_square] =
+                [x, root, x_inv, is_square].map(|column| meta.query_advice(column, Rotation::cur()));
+            let one = Expression::Constant(F::ONE);
+            let non_residue = Expression::Constant(F::MULTIPLICATIVE_GENERATOR);
+            let not_square = one.clone() - is_square.clone();
+
+            vec![
+                q_sqrt.clone()
+                    * (root.clone() * root
+                        - is_square.clone() * x.clone()
+                        - not_square.clone() * non_residue * x.clone()),
+                q_sqrt.clone() * is_square * not_square.clone(),
+                q_sqrt * not_square * (one - x * x_inv),
+            ]
+        });
+
+        SqrtConfig {
+            q_sqrt,
+            x,
+            root,
+            x_inv,
+            is_square,
+        }
+    }
+
+    pub fn construct(config: SqrtConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Witnesses a root of `x`, or of `n ⋅ x` when `x` is not a square.
+    pub fn assign(&self, mut layouter: impl Layouter<F>, x: &AssignedCell<F, F>) -> Result<SqrtCells<F>, Error> {
+        let config = &self.config;
+        layouter.assign_region(
+            || "sqrt",
+            |mut region| {
+                config.q_sqrt.enable(&mut region, 0)?;
+                let x = x.copy_advice(|| "x", &mut region, config.x, 0)?;
+
+                let roots = x.value().map(|x| {
+                    let root: Option<F> = x.sqrt().into();
+                    let is_square = root.is_some();
+                    let root = root.unwrap_or_else(|| (F::MULTIPLICATIVE_GENERATOR * x).sqrt().unwrap());
+                    (root, is_square)
+                });
+                let x_inv = x.value().map(|x| x.invert().unwrap_or(F::ZERO));
+
+                let root = region.assign_advice(|| "root", config.root, 0, || roots.map(|(root, _)| root))?;
+                region.assign_advice(|| "x_inv", config.x_inv, 0, || x_inv)?;
+                let is_square = region.assign_advice(
+                    || "is_square",
+                    config.is_square,
+                    0,
+                    || roots.map(|(_, is_square)| F::from(is_square as u64)),
+                )?;
+
+                Ok(SqrtCells { root, is_square })
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        halo2curves::group::ff::{Field as _, PrimeField},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+
+    use super::{SqrtChip, SqrtConfig};
+    use crate::{
+        dev::{
+            fuzz::{field, gadget_proptest},
+            mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        },
+        field::{Field, TestField as Fp},
+    };
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig {
+        sqrt: SqrtConfig,
+        input: Column<Advice>,
+        instance: Column<Instance>,
+    }
+
+    /// Exposes `is_square` of `x`.
+    #[derive(Default)]
+    struct TestCircuit<F: Field> {
+        x: Value<F>,
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit<F> {
+        type Config = TestCircuitConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let sqrt = SqrtChip::configure(meta);
+            let input = meta.advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(input);
+            meta.enable_equality(instance);
+
+            TestCircuitConfig { sqrt, input, instance }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let chip = SqrtChip::construct(config.sqrt);
+            let x = layouter.assign_region(
+                || "x",
+                |mut region| region.assign_advice(|| "x", config.input, 0, || self.x),
+            )?;
+            let cells = chip.assign(layouter.namespace(|| "sqrt"), &x)?;
+            layouter.constrain_instance(cells.is_square.cell(), config.instance, 0)
+        }
+    }
+
+    fn is_square(x: Fp) -> Fp {
+        Fp::from(bool::from(x.sqrt().is_some()) as u64)
+    }
+
+    #[test]
+    fn sqrt() {
+        let non_residue = Fp::MULTIPLICATIVE_GENERATOR;
+        for (x, expected) in [(Fp::ZERO, 1), (Fp::ONE, 1), (Fp::from(4), 1), (non_residue, 0)] {
+            let circuit = TestCircuit { x: Value::known(x) };
+            expect_satisfied(&circuit, vec![vec![Fp::from(expected)]]);
+        }
+
+        // 4 is a square.
+        expect_failure(
+            &TestCircuit {
+                x: Value::known(Fp::from(4)),
+            },
+            vec![vec![Fp::ZERO]],
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+    }
+
+    gadget_proptest! {
+        sqrt_complete(x in field()) {
+            circuit: TestCircuit { x: Value::known(x) },
+            instances: vec![vec![is_square(x)]],
+            valid: true,
+        }
+        sqrt_sound(x in field()) {
+            circuit: TestCircuit { x: Value::known(x) },
+            instances: vec![vec![Fp::ONE - is_square(x)]],
+            valid: false,
+        }
+    }
+}
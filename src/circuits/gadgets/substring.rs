@@ -0,0 +1,278 @@
+//! Substring matching: a string of `N` cells contains a pattern at a
+//! witnessed offset.
+//!
+//! The positions `offset + k` of the pattern are laid out as a running count,
+//! and each is used to select a byte of the string with the [`IndexChip`]:
+//!
+//! | position       | q_step |
+//! | offset         | 1      |
+//! | offset + 1     | 1      |
+//! | ...            | ...    |
+//! | offset + m - 1 | 0      |
+//!
+//! The selected bytes are then compared with the pattern:
+//!
+//! | pattern      | selected                       | q_match |
+//! | pattern[k]   | string[offset + k]             | 1       |
+//!
+//! The [`IndexChip`] rejects positions outside of `[0, N)`, so the pattern
+//! must fit in the string. The chip only relates cells: to prove that a
+//! *private* string contains a public pattern, the caller commits to the
+//! string, for instance with [`PoseidonChip`](super::poseidon::PoseidonChip),
+//! and exposes the commitment and the pattern.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use super::index::{IndexChip, IndexConfig};
+use crate::field::Field;
+
+/// Config for [`SubstringChip`].
+#[derive(Clone, Debug)]
+pub struct SubstringConfig<F, const N: usize> {
+    q_step: Selector,
+    q_match: Selector,
+    position: Column<Advice>,
+    pattern: Column<Advice>,
+    selected: Column<Advice>,
+    index: IndexConfig<F, N>,
+}
+
+/// Chip matching patterns in strings of `N` cells.
+#[derive(Clone, Debug)]
+pub struct SubstringChip<F: Field, const N: usize> {
+    config: SubstringConfig<F, N>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field, const N: usize> Chip<F> for SubstringChip<F, N> {
+    type Config = SubstringConfig<F, N>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field, const N: usize> SubstringChip<F, N> {
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> SubstringConfig<F, N> {
+        let q_step = meta.selector();
+        let q_match = meta.selector();
+        let [position, pattern, selected] = [(); 3].map(|_| meta.advice_column());
+        let index = IndexChip::configure(meta);
+
+        for column in [position, pattern, selected] {
+            meta.enable_equality(column);
+        }
+
+        meta.create_gate("substring position", |meta| {
+            let q_step = meta.query_selector(q_step);
+            let position_next = meta.query_advice(position, Rotation::next());
+            let position = meta.query_advice(position, Rotation::cur());
+            vec![q_step * (position_next - position - Expression::Constant(F::ONE))]
+        });
+
+        meta.create_gate("substring match", |meta| {
+            let q_match = meta.query_selector(q_match);
+            let pattern = meta.query_advice(pattern, Rotation::cur());
+            let selected = meta.query_advice(selected, Rotation::cur());
+            vec![q_match * (pattern - selected)]
+        });
+
+        SubstringConfig {
+            q_step,
+            q_match,
+            position,
+            pattern,
+            selected,
+            index,
+        }
+    }
+
+    pub fn construct(config: SubstringConfig<F, N>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Constrains `string[offset..offset + pattern.len()]` to equal the
+    /// non-empty `pattern`, returning the cells selected from `string`.
+    pub fn contains(
+        &self,
+        mut layouter: impl Layouter<F>,
+        string: &[AssignedCell<F, F>; N],
+        pattern: &[AssignedCell<F, F>],
+        offset: &AssignedCell<F, F>,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        assert!(!pattern.is_empty() && pattern.len() <= N);
+        let config = &self.config;
+
+        let positions = layouter.assign_region(
+            || "substring positions",
+            |mut region| {
+                let mut position = offset.copy_advice(|| "position 0", &mut region, config.position, 0)?;
+                let mut positions = vec![position.clone()];
+                for k in 1..pattern.len() {
+                    config.q_step.enable(&mut region, k - 1)?;
+                    let next = position.value().map(|position| *position + F::ONE);
+                    position = region.assign_advice(|| format!("position {k}"), config.position, k, || next)?;
+                    positions.push(position.clone());
+                }
+                Ok(positions)
+            },
+        )?;
+
+        let index = IndexChip::construct(config.index.clone());
+        let selected = (positions.iter())
+            .map(|position| index.select(layouter.namespace(|| "select"), string, position))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        layouter.assign_region(
+            || "substring match",
+            |mut region| {
+                let mut matched = Vec::with_capacity(pattern.len());
+                for (k, (pattern, selected)) in pattern.iter().zip(&selected).enumerate() {
+                    config.q_match.enable(&mut region, k)?;
+                    pattern.copy_advice(|| format!("pattern[{k}]"), &mut region, config.pattern, k)?;
+                    matched.push(selected.copy_advice(|| format!("selected[{k}]"), &mut region, config.selected, k)?);
+                }
+                Ok(matched)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+    };
+
+    use super::{SubstringChip, SubstringConfig};
+    use crate::{
+        circuits::{
+            gadgets::poseidon::{PoseidonChip, PoseidonConfig, Spec},
+            instance::{InstanceColumns, InstanceLayout},
+        },
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::{Field, TestField as Fp},
+    };
+
+    const STRING: &[u8; 8] = b"the halo";
+    const PATTERN: &[u8; 4] = b"halo";
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig<F: Field> {
+        substring: SubstringConfig<F, 8>,
+        poseidon: PoseidonConfig<F>,
+        advice: Column<Advice>,
+        instance: InstanceColumns,
+    }
+
+    /// Proves that the private `STRING`, exposed as its Poseidon hash,
+    /// contains the public `PATTERN` at the private `offset`.
+    #[derive(Default)]
+    struct TestCircuit {
+        offset: u64,
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit {
+        type Config = TestCircuitConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let [advice, s0, s1, s2, i0, i1] = [(); 6].map(|_| meta.advice_column());
+            let constant = meta.fixed_column();
+            let names = ["commitment".to_string()].into_iter().chain((0..4).map(|k| format!("pattern[{k}]")));
+            meta.enable_equality(advice);
+
+            TestCircuitConfig {
+                substring: SubstringChip::configure(meta),
+                poseidon: PoseidonChip::configure(meta, [s0, s1, s2], [i0, i1], constant),
+                advice,
+                instance: InstanceLayout::new().column(names).configure(meta),
+            }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let (string, pattern, offset) = layouter.assign_region(
+                || "inputs",
+                |mut region| {
+                    let mut cells = (STRING.iter().chain(PATTERN).chain([&(self.offset as u8)]).enumerate())
+                        .map(|(i, byte)| {
+                            region.assign_advice(|| "input", config.advice, i, || Value::known(F::from(*byte as u64)))
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let offset = cells.pop().unwrap();
+                    let pattern = cells.split_off(STRING.len());
+                    Ok((cells, pattern, offset))
+                },
+            )?;
+
+            let commitment = PoseidonChip::construct(config.poseidon).hash(layouter.namespace(|| "commit"), &string)?;
+            let chip = SubstringChip::construct(config.substring);
+            let string = string.try_into().unwrap();
+            let matched = chip.contains(layouter.namespace(|| "contains"), &string, &pattern, &offset)?;
+            assert_eq!(matched.len(), pattern.len());
+
+            for (i, cell) in [&commitment].into_iter().chain(&pattern).enumerate() {
+                config.instance.expose_public(&mut layouter, cell, i)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn instances() -> Vec<Vec<Fp>> {
+        let string: Vec<_> = STRING.iter().map(|byte| Fp::from(*byte as u64)).collect();
+        let commitment = Spec::new().hash(&string);
+        vec![[commitment].into_iter().chain(PATTERN.iter().map(|byte| Fp::from(*byte as u64))).collect()]
+    }
+
+    #[test]
+    fn substring() {
+        expect_satisfied(&TestCircuit { offset: 4 }, instances());
+
+        // "the halo"[3..7] is " hal".
+        expect_failure(
+            &TestCircuit { offset: 3 },
+            instances(),
+            FailureMatcher::Constraint {
+                gate: "substring match",
+                location: Location::InRegion {
+                    region: "substring match",
+                    offset: 0,
+                },
+            },
+        );
+
+        // The last position, 8, is out of the string.
+        expect_failure(
+            &TestCircuit { offset: 5 },
+            instances(),
+            FailureMatcher::Constraint {
+                gate: "one hot",
+                location: Location::InRegion {
+                    region: "select by index",
+                    offset: 0,
+                },
+            },
+        );
+    }
+}
@@ -0,0 +1,206 @@
+//! Fiat–Shamir transcript over the Poseidon hash.
+//!
+//! Elements are absorbed into a buffer, and squeezing a challenge hashes the
+//! previous challenge, if any, followed by the buffer:
+//!
+//! ```text
+//! c_0     = H(m_0[0], m_0[1], ...)
+//! c_(i+1) = H(c_i, m_(i+1)[0], m_(i+1)[1], ...)
+//! ```
+//!
+//! so every challenge depends on everything absorbed before it. [`Transcript`]
+//! computes the same challenges off circuit, for the prover and for tests.
+//!
+//! This is the [`Sponge`] of [`super::hash`] over Poseidon, which both
+//! transcripts wrap with a Fiat–Shamir vocabulary.
+//!
+//! [`Spec::hash`] pads with zeros, so absorbing `[m]` and `[m, 0]` gives the
+//! same challenge: the number of elements absorbed before each squeeze must be
+//! fixed by the protocol, as it is for a verifier of a fixed circuit.
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter},
+    plonk::Error,
+};
+
+use super::{
+    hash::{NativeSponge, Sponge},
+    poseidon::{PoseidonChip, PoseidonConfig, Spec},
+};
+use crate::field::Field;
+
+/// In-circuit transcript, see the [module documentation](self).
+#[derive(Clone, Debug)]
+pub struct TranscriptChip<F: Field> {
+    sponge: Sponge<F, PoseidonChip<F>>,
+}
+
+impl<F: Field> Chip<F> for TranscriptChip<F> {
+    type Config = PoseidonConfig<F>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        self.sponge.chip().config()
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field> TranscriptChip<F> {
+    /// Creates an empty transcript hashing with the [`PoseidonChip`] of
+    /// `config`.
+    pub fn construct(config: PoseidonConfig<F>) -> Self {
+        Self {
+            sponge: Sponge::new(PoseidonChip::construct(config)),
+        }
+    }
+
+    pub fn absorb(&mut self, cell: &AssignedCell<F, F>) {
+        self.sponge.absorb(std::slice::from_ref(cell));
+    }
+
+    /// Squeezes a challenge, which becomes the state of the transcript.
+    pub fn squeeze(&mut self, layouter: impl Layouter<F>) -> Result<AssignedCell<F, F>, Error> {
+        self.sponge.squeeze(layouter)
+    }
+}
+
+/// Off-circuit transcript, squeezing the same challenges as
+/// [`TranscriptChip`].
+#[derive(Clone, Debug, Default)]
+pub struct Transcript<F: Field> {
+    sponge: NativeSponge<F, Spec<F>>,
+}
+
+impl<F: Field> Transcript<F> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn absorb(&mut self, value: F) {
+        self.sponge.absorb(&[value]);
+    }
+
+    pub fn squeeze(&mut self) -> F {
+        self.sponge.squeeze()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+    };
+
+    use super::{Transcript, TranscriptChip};
+    use crate::{
+        circuits::{
+            gadgets::poseidon::{PoseidonChip, PoseidonConfig},
+            instance::{InstanceColumns, InstanceLayout},
+        },
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::{Field, TestField as Fp},
+    };
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig<F: Field> {
+        poseidon: PoseidonConfig<F>,
+        message: Column<Advice>,
+        instance: InstanceColumns,
+    }
+
+    /// Absorbs `messages[0]`, squeezes, absorbs `messages[1]` and squeezes
+    /// twice, exposing the three challenges.
+    #[derive(Default)]
+    struct TestCircuit {
+        messages: [Vec<u64>; 2],
+    }
+
+    impl TestCircuit {
+        fn challenges(&self) -> Vec<Fp> {
+            let mut transcript = Transcript::new();
+            self.messages[0].iter().for_each(|m| transcript.absorb(Fp::from(*m)));
+            let c0 = transcript.squeeze();
+            self.messages[1].iter().for_each(|m| transcript.absorb(Fp::from(*m)));
+            vec![c0, transcript.squeeze(), transcript.squeeze()]
+        }
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit {
+        type Config = TestCircuitConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let [message, s0, s1, s2, i0, i1] = [(); 6].map(|_| meta.advice_column());
+            let constant = meta.fixed_column();
+            let poseidon = PoseidonChip::configure(meta, [s0, s1, s2], [i0, i1], constant);
+            meta.enable_equality(message);
+
+            TestCircuitConfig {
+                poseidon,
+                message,
+                instance: InstanceLayout::new().column(["c0", "c1", "c2"]).configure(meta),
+            }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let mut transcript = TranscriptChip::construct(config.poseidon);
+
+            let messages = layouter.assign_region(
+                || "messages",
+                |mut region| {
+                    (self.messages.iter().flatten().enumerate())
+                        .map(|(i, m)| {
+                            region.assign_advice(|| "message", config.message, i, || Value::known(F::from(*m)))
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                },
+            )?;
+            let (first, second) = messages.split_at(self.messages[0].len());
+
+            first.iter().for_each(|cell| transcript.absorb(cell));
+            let c0 = transcript.squeeze(layouter.namespace(|| "c0"))?;
+            second.iter().for_each(|cell| transcript.absorb(cell));
+            let c1 = transcript.squeeze(layouter.namespace(|| "c1"))?;
+            let c2 = transcript.squeeze(layouter.namespace(|| "c2"))?;
+
+            for (i, challenge) in [c0, c1, c2].iter().enumerate() {
+                config.instance.expose_public(&mut layouter, challenge, i)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn transcript() {
+        let circuit = TestCircuit {
+            messages: [vec![1, 2, 3], vec![4]],
+        };
+        let challenges = circuit.challenges();
+        assert_ne!(challenges[1], challenges[2]);
+        expect_satisfied(&circuit, vec![challenges.clone()]);
+
+        // Challenges depend on everything absorbed before them.
+        let tampered = TestCircuit {
+            messages: [vec![1, 2, 3], vec![5]],
+        };
+        let mut wrong = challenges;
+        wrong[1] = tampered.challenges()[1];
+        expect_failure(
+            &circuit,
+            vec![wrong],
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 1 },
+            },
+        );
+    }
+}
@@ -0,0 +1,467 @@
+//! Additions modulo `2^32`, constant rotations and XOR of 32-bit words, the
+//! operations of ARX designs like ChaCha20 and of the steps of
+//! [`ripemd160`](super::ripemd160).
+//!
+//! Words are cells below `2^32`. [`Word32Chip::decompose`] range checks one
+//! with its [`BitsConfig`] running sum `acc[i] = x >> i`, kept in a
+//! [`Word32`]. Each operation is a small region:
+//!
+//! - an addition witnesses the sum and a carry in `[0, 4)`,
+//!   `t[0] + t[1] + t[2] + constant = sum + carry ⋅ 2^32`, and decomposes the
+//!   sum,
+//! - a rotation of a decomposed `x` by `s` reads `hi = x >> (32 - s)` off its
+//!   running sum, so that `rotl_s(x) = 2^s ⋅ x - hi ⋅ (2^32 - 1)`,
+//! - an XOR decomposes its inputs and output into nibbles with running sums
+//!   in base 16, and looks up each `(a, b, a ^ b)` in a table of all nibbles:
+//!
+//! | t[0]   | t[1]   | sum      | q_xor | q_xor_end |
+//! | a      | b      | out      | 1     | 0         |
+//! | a >> 4 | b >> 4 | out >> 4 | 1     | 0         |
+//! | ...    | ...    | ...      | 1     | 0         |
+//! | 0      | 0      | 0        | 0     | 1         |
+//!
+//! where the nibbles are `acc[i] - 16 ⋅ acc[i + 1]`.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector, TableColumn, VirtualCells},
+    poly::Rotation,
+};
+
+use super::bits::BitsConfig;
+use crate::field::Field;
+
+/// A range checked 32-bit word and its running sum.
+#[derive(Clone, Debug)]
+pub struct Word32<F: Field> {
+    /// `acc[i] = x >> i`, for `i` in `[0, 32]`.
+    acc: Vec<AssignedCell<F, F>>,
+}
+
+impl<F: Field> Word32<F> {
+    /// The cell of the word itself.
+    pub fn cell(&self) -> &AssignedCell<F, F> {
+        &self.acc[0]
+    }
+}
+
+/// Config for [`Word32Chip`].
+#[derive(Clone, Debug)]
+pub struct Word32Config {
+    q_add: Selector,
+    q_rotate: Selector,
+    q_xor: Selector,
+    q_xor_end: Selector,
+    terms: [Column<Advice>; 3],
+    sum: Column<Advice>,
+    carry: Column<Advice>,
+    fixed: Column<Fixed>,
+    table: [TableColumn; 3],
+    bits: BitsConfig,
+}
+
+/// Chip computing on 32-bit words.
+#[derive(Clone, Debug)]
+pub struct Word32Chip<F: Field> {
+    config: Word32Config,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> Chip<F> for Word32Chip<F> {
+    type Config = Word32Config;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field> Word32Chip<F> {
+    /// Configures the chip on the `advice` columns, range checking words with
+    /// `bits` and assigning constant words with `constant`.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 5],
+        bits: BitsConfig,
+        constant: Column<Fixed>,
+    ) -> Word32Config {
+        let q_add = meta.selector();
+        let q_rotate = meta.selector();
+        let q_xor = meta.complex_selector();
+        let q_xor_end = meta.selector();
+        let [t0, t1, t2, sum, carry] = advice;
+        let terms = [t0, t1, t2];
+        let fixed = meta.fixed_column();
+        let table = [(); 3].map(|_| meta.lookup_table_column());
+
+        for column in advice {
+            meta.enable_equality(column);
+        }
+        meta.enable_constant(constant);
+
+        meta.create_gate("word32 add", |meta| {
+            let q_add = meta.query_selector(q_add);
+            let total = (terms.iter()).fold(meta.query_fixed(fixed, Rotation::cur()), |acc, column| {
+                acc + meta.query_advice(*column, Rotation::cur())
+            });
+            let sum = meta.query_advice(sum, Rotation::cur());
+            let carry = meta.query_advice(carry, Rotation::cur());
+
+            let range_check = (1..4).fold(carry.clone(), |expr, i| {
+                expr * (Expression::Constant(F::from(i)) - carry.clone())
+            });
+            vec![
+                q_add.clone() * (total - sum - carry * Expression::Constant(F::from(1u64 << 32))),
+                q_add * range_check,
+            ]
+        });
+
+        meta.create_gate("word32 rotate", |meta| {
+            let q_rotate = meta.query_selector(q_rotate);
+            let pow = meta.query_fixed(fixed, Rotation::cur());
+            let x = meta.query_advice(t0, Rotation::cur());
+            let hi = meta.query_advice(t1, Rotation::cur());
+            let out = meta.query_advice(sum, Rotation::cur());
+
+            vec![q_rotate * (out - pow * x + hi * Expression::Constant(F::from(u32::MAX as u64)))]
+        });
+
+        meta.lookup("word32 xor", |meta| {
+            let q_xor = meta.query_selector(q_xor);
+            let nibbles = [t0, t1, sum].map(|column| nibble(meta, column));
+
+            (nibbles.into_iter().zip(table))
+                .map(|(nibble, column)| (q_xor.clone() * nibble, column))
+                .collect()
+        });
+
+        meta.create_gate("word32 xor end", |meta| {
+            let q_xor_end = meta.query_selector(q_xor_end);
+            [t0, t1, sum].map(|column| q_xor_end.clone() * meta.query_advice(column, Rotation::cur()))
+        });
+
+        Word32Config {
+            q_add,
+            q_rotate,
+            q_xor,
+            q_xor_end,
+            terms,
+            sum,
+            carry,
+            fixed,
+            table,
+            bits,
+        }
+    }
+
+    pub fn construct(config: Word32Config) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Fills the `(a, b, a ^ b)` table over nibbles, once per circuit.
+    pub fn load_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        let table = self.config.table;
+        layouter.assign_table(
+            || "xor table",
+            |mut t| {
+                for a in 0..16 {
+                    for b in 0..16 {
+                        for (column, value) in table.iter().zip([a, b, a ^ b]) {
+                            let value = Value::known(F::from(value));
+                            t.assign_cell(|| "xor table", *column, 16 * a as usize + b as usize, || value)?;
+                        }
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Assigns the constant `words`.
+    pub fn constants(&self, mut layouter: impl Layouter<F>, words: &[u32]) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        layouter.assign_region(
+            || "word32 constants",
+            |mut region| {
+                (words.iter().enumerate())
+                    .map(|(i, word)| {
+                        region.assign_advice_from_constant(|| "word", self.config.terms[0], i, F::from(*word as u64))
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    /// Range checks `word` to 32 bits.
+    pub fn decompose(&self, layouter: impl Layouter<F>, word: &AssignedCell<F, F>) -> Result<Word32<F>, Error> {
+        let acc = self.config.bits.decompose(layouter, word, 32)?;
+        Ok(Word32 { acc })
+    }
+
+    /// Range checks each of `words` to 32 bits, computing their
+    /// decompositions in parallel.
+    pub fn decompose_all(
+        &self,
+        layouter: impl Layouter<F>,
+        words: &[AssignedCell<F, F>],
+    ) -> Result<Vec<Word32<F>>, Error> {
+        let accs = self.config.bits.running_sums(layouter, words, 32)?;
+        Ok(accs.into_iter().map(|acc| Word32 { acc }).collect())
+    }
+
+    /// Returns `Σ terms + constant mod 2^32`, for up to three terms below
+    /// `2^32`.
+    pub fn add(
+        &self,
+        mut layouter: impl Layouter<F>,
+        terms: &[&AssignedCell<F, F>],
+        constant: u32,
+    ) -> Result<Word32<F>, Error> {
+        let config = &self.config;
+        assert!(terms.len() <= 3);
+
+        let sum = layouter.assign_region(
+            || "word32 add",
+            |mut region| {
+                config.q_add.enable(&mut region, 0)?;
+                let constant_value = Value::known(F::from(constant as u64));
+                region.assign_fixed(|| "constant", config.fixed, 0, || constant_value)?;
+
+                let mut total = Value::known(constant as u64);
+                for (i, column) in config.terms.iter().enumerate() {
+                    let term = match terms.get(i) {
+                        Some(term) => term.copy_advice(|| "term", &mut region, *column, 0)?,
+                        None => region.assign_advice_from_constant(|| "term", *column, 0, F::ZERO)?,
+                    };
+                    total = total.zip(term.value()).map(|(total, term)| total + to_u32(term) as u64);
+                }
+
+                region.assign_advice(|| "carry", config.carry, 0, || total.map(|total| F::from(total >> 32)))?;
+                let sum = total.map(|total| F::from(total as u32 as u64));
+                region.assign_advice(|| "sum", config.sum, 0, || sum)
+            },
+        )?;
+
+        self.decompose(layouter.namespace(|| "sum"), &sum)
+    }
+
+    /// Returns `x.rotate_left(s)`, for `s` in `[1, 32)`.
+    pub fn rotate_left(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: &Word32<F>,
+        s: u32,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        assert!(0 < s && s < 32);
+
+        layouter.assign_region(
+            || "word32 rotate",
+            |mut region| {
+                config.q_rotate.enable(&mut region, 0)?;
+                region.assign_fixed(|| "2^s", config.fixed, 0, || Value::known(F::from(1u64 << s)))?;
+
+                let word = x.cell().copy_advice(|| "x", &mut region, config.terms[0], 0)?;
+                x.acc[(32 - s) as usize].copy_advice(|| "hi", &mut region, config.terms[1], 0)?;
+                let out = word.value().map(|x| F::from(to_u32(x).rotate_left(s) as u64));
+                region.assign_advice(|| "out", config.sum, 0, || out)
+            },
+        )
+    }
+
+    /// Returns `a ^ b`, looked up nibble by nibble, which also range checks
+    /// `a` and `b`.
+    pub fn xor(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+
+        layouter.assign_region(
+            || "word32 xor",
+            |mut region| {
+                for offset in 0..8 {
+                    config.q_xor.enable(&mut region, offset)?;
+                }
+                config.q_xor_end.enable(&mut region, 8)?;
+
+                let mut values = vec![];
+                for (input, column) in [a, b].into_iter().zip(config.terms) {
+                    input.copy_advice(|| "input", &mut region, column, 0)?;
+                    let value = input.value().map(to_u32);
+                    assign_nibbles(&mut region, column, value)?;
+                    values.push(value);
+                }
+
+                let out = values[0].zip(values[1]).map(|(a, b)| a ^ b);
+                let cell = region.assign_advice(|| "out", config.sum, 0, || out.map(|out| F::from(out as u64)))?;
+                assign_nibbles(&mut region, config.sum, out)?;
+                Ok(cell)
+            },
+        )
+    }
+}
+
+/// The nibble `acc[i] - 16 ⋅ acc[i + 1]` of a base 16 running sum in `column`.
+pub(crate) fn nibble<F: Field>(meta: &mut VirtualCells<'_, F>, column: Column<Advice>) -> Expression<F> {
+    meta.query_advice(column, Rotation::cur())
+        - Expression::Constant(F::from(16)) * meta.query_advice(column, Rotation::next())
+}
+
+/// Assigns the base 16 running sum of `value` in the 8 rows below the first.
+pub(crate) fn assign_nibbles<F: Field>(
+    region: &mut Region<'_, F>,
+    column: Column<Advice>,
+    value: Value<u32>,
+) -> Result<(), Error> {
+    for i in 1..=8 {
+        let acc = value.map(|value| F::from(value as u64 >> (4 * i)));
+        region.assign_advice(|| format!("acc {i}"), column, i, || acc)?;
+    }
+    Ok(())
+}
+
+/// The low 32 bits of `value`.
+pub(crate) fn to_u32<F: Field>(value: &F) -> u32 {
+    u32::from_le_bytes(value.to_repr()[..4].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+    };
+
+    use super::{Word32Chip, Word32Config};
+    use crate::{
+        circuits::{
+            gadgets::bits::BitsConfig,
+            instance::{InstanceColumns, InstanceLayout},
+        },
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::TestField as Fp,
+    };
+
+    /// Exposes `a + b + 0x80000000`, `a ^ b` and `a.rotate_left(7)`.
+    #[derive(Default)]
+    struct TestCircuit {
+        a: Value<u64>,
+        b: Value<u64>,
+    }
+
+    impl Circuit<Fp> for TestCircuit {
+        type Config = (Word32Config, Column<Advice>, InstanceColumns);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let [input, acc, t0, t1, t2, sum, carry] = [(); 7].map(|_| meta.advice_column());
+            let constant = meta.fixed_column();
+            meta.enable_equality(input);
+
+            let bits = BitsConfig::configure(meta, acc);
+            let config = Word32Chip::configure(meta, [t0, t1, t2, sum, carry], bits, constant);
+            (
+                config,
+                input,
+                InstanceLayout::new().column(["add", "xor", "rotate"]).configure(meta),
+            )
+        }
+
+        fn synthesize(
+            &self,
+            (config, input, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = Word32Chip::construct(config);
+            chip.load_table(&mut layouter)?;
+
+            let (a, b) = layouter.assign_region(
+                || "inputs",
+                |mut region| {
+                    Ok((
+                        region.assign_advice(|| "a", input, 0, || self.a.map(Fp::from))?,
+                        region.assign_advice(|| "b", input, 1, || self.b.map(Fp::from))?,
+                    ))
+                },
+            )?;
+
+            let a = chip.decompose(layouter.namespace(|| "a"), &a)?;
+            let b = chip.decompose(layouter.namespace(|| "b"), &b)?;
+            let sum = chip.add(layouter.namespace(|| "add"), &[a.cell(), b.cell()], 0x80000000)?;
+            let xor = chip.xor(layouter.namespace(|| "xor"), a.cell(), b.cell())?;
+            let rotated = chip.rotate_left(layouter.namespace(|| "rotate"), &a, 7)?;
+
+            for (i, cell) in [sum.cell(), &xor, &rotated].into_iter().enumerate() {
+                instance.expose_public(&mut layouter, cell, i)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn outputs(a: u32, b: u32) -> Vec<Fp> {
+        let sum = a.wrapping_add(b).wrapping_add(0x80000000);
+        [sum, a ^ b, a.rotate_left(7)]
+            .map(|word| Fp::from(word as u64))
+            .to_vec()
+    }
+
+    #[test]
+    fn word32() {
+        for (a, b) in [(0, 0), (0xdeadbeef, 0x01234567), (u32::MAX, u32::MAX)] {
+            let circuit = TestCircuit {
+                a: Value::known(a as u64),
+                b: Value::known(b as u64),
+            };
+            expect_satisfied(&circuit, vec![outputs(a, b)]);
+        }
+
+        // The sum without wrapping around.
+        let circuit = TestCircuit {
+            a: Value::known(0xdeadbeef),
+            b: Value::known(0x01234567),
+        };
+        let mut wrong = outputs(0xdeadbeef, 0x01234567);
+        wrong[0] = Fp::from(0xdeadbeef + 0x01234567 + 0x80000000);
+        expect_failure(
+            &circuit,
+            vec![wrong],
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+
+        // A 33-bit input.
+        let circuit = TestCircuit {
+            a: Value::known(1 << 32),
+            b: Value::known(1),
+        };
+        expect_failure(
+            &circuit,
+            vec![outputs(0, 1)],
+            FailureMatcher::Constraint {
+                gate: "bits end",
+                location: Location::InRegion {
+                    region: "32 bits",
+                    offset: 32,
+                },
+            },
+        );
+    }
+}
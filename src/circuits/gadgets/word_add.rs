@@ -0,0 +1,373 @@
+//! Addition and subtraction of 256-bit words with overflow detection.
+//!
+//! Words are added by their `lo`/`hi` 128-bit halves, carrying from `lo` to
+//! `hi`, and the carry out of `hi` is the overflow:
+//!
+//! | a.lo | a.hi | b.lo | b.hi | c.lo | c.hi | carry | overflow | q_add |
+//! | a.lo | a.hi | b.lo | b.hi | c.lo | c.hi | carry | overflow | 1     |
+//!
+//! `a + b = c + overflow ⋅ 2^256`. Subtraction `a - b` uses the same row with
+//! `b + diff = a + underflow ⋅ 2^256`. The witnessed halves, `c` when adding
+//! and `diff` when subtracting, are range checked by unpacking them into 16
+//! bytes with [`PackConfig`], so the flags can't be lied about. The halves of
+//! the inputs must already be 128-bit.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::{
+    circuits::{pack::PackConfig, word::Word},
+    field::Field,
+};
+
+/// Config for [`WordAddChip`].
+#[derive(Clone, Debug)]
+pub struct WordAddConfig {
+    q_add: Selector,
+    a: [Column<Advice>; 2],
+    b: [Column<Advice>; 2],
+    c: [Column<Advice>; 2],
+    carry: Column<Advice>,
+    overflow: Column<Advice>,
+    pack: PackConfig,
+}
+
+/// Chip adding and subtracting words, flagging wrap-arounds.
+#[derive(Clone, Debug)]
+pub struct WordAddChip<F: Field> {
+    config: WordAddConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> Chip<F> for WordAddChip<F> {
+    type Config = WordAddConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: Field> WordAddChip<F> {
+    /// Configures the chip, range checking results with `pack`.
+    pub fn configure(meta: &mut ConstraintSystem<F>, pack: PackConfig) -> WordAddConfig {
+        let q_add = meta.selector();
+        let [a, b, c] = [(); 3].map(|_| [(); 2].map(|_| meta.advice_column()));
+        let carry = meta.advice_column();
+        let overflow = meta.advice_column();
+
+        for column in [a, b, c].concat() {
+            meta.enable_equality(column);
+        }
+        meta.enable_equality(overflow);
+
+        meta.create_gate("word add", |meta| {
+            let q_add = meta.query_selector(q_add);
+            let [a, b, c] = [a, b, c].map(|word| word.map(|column| meta.query_advice(column, Rotation::cur())));
+            let carry = meta.query_advice(carry, Rotation::cur());
+            let overflow = meta.query_advice(overflow, Rotation::cur());
+
+            let two_128 = Expression::Constant(F::from_u128(u128::MAX) + F::ONE);
+            let boolean = |x: Expression<F>| x.clone() * (Expression::Constant(F::ONE) - x);
+            let [a_lo, a_hi] = a;
+            let [b_lo, b_hi] = b;
+            let [c_lo, c_hi] = c;
+
+            vec![
+                q_add.clone() * (a_lo + b_lo - c_lo - carry.clone() * two_128.clone()),
+                q_add.clone() * (a_hi + b_hi + carry.clone() - c_hi - overflow.clone() * two_128),
+                q_add.clone() * boolean(carry),
+                q_add * boolean(overflow),
+            ]
+        });
+
+        WordAddConfig {
+            q_add,
+            a,
+            b,
+            c,
+            carry,
+            overflow,
+            pack,
+        }
+    }
+
+    pub fn construct(config: WordAddConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns `a + b mod 2^256` and whether it overflowed.
+    pub fn add(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &Word<AssignedCell<F, F>>,
+        b: &Word<AssignedCell<F, F>>,
+    ) -> Result<(Word<AssignedCell<F, F>>, AssignedCell<F, F>), Error> {
+        let config = &self.config;
+
+        let (sum, overflow) = layouter.assign_region(
+            || "word add",
+            |mut region| {
+                config.q_add.enable(&mut region, 0)?;
+                let a = copy_word(&mut region, "a", a, config.a)?;
+                let b = copy_word(&mut region, "b", b, config.b)?;
+
+                let added = to_u128s(&a).zip(to_u128s(&b)).map(|(a, b)| add(a, b));
+                let sum = assign_word(&mut region, "sum", added.map(|(sum, _, _)| sum), config.c)?;
+                let overflow = self.assign_carries(&mut region, added)?;
+
+                Ok((sum, overflow))
+            },
+        )?;
+
+        self.range_check(layouter.namespace(|| "sum"), &sum)?;
+
+        Ok((sum, overflow))
+    }
+
+    /// Returns `a - b mod 2^256` and whether it underflowed.
+    pub fn sub(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &Word<AssignedCell<F, F>>,
+        b: &Word<AssignedCell<F, F>>,
+    ) -> Result<(Word<AssignedCell<F, F>>, AssignedCell<F, F>), Error> {
+        let config = &self.config;
+
+        let (diff, underflow) = layouter.assign_region(
+            || "word sub",
+            |mut region| {
+                config.q_add.enable(&mut region, 0)?;
+                let b = copy_word(&mut region, "b", b, config.a)?;
+                copy_word(&mut region, "a", a, config.c)?;
+
+                let diff = to_u128s(a).zip(to_u128s(&b)).map(|(a, b)| sub(a, b));
+                let added = to_u128s(&b).zip(diff).map(|(b, diff)| add(b, diff));
+                let diff = assign_word(&mut region, "diff", diff, config.b)?;
+                let underflow = self.assign_carries(&mut region, added)?;
+
+                Ok((diff, underflow))
+            },
+        )?;
+
+        self.range_check(layouter.namespace(|| "diff"), &diff)?;
+
+        Ok((diff, underflow))
+    }
+
+    /// Assigns the carry out of `lo` and the overflow, returning the latter.
+    fn assign_carries(
+        &self,
+        region: &mut Region<'_, F>,
+        added: Value<([u128; 2], bool, bool)>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        let carry = added.map(|(_, carry, _)| F::from(carry as u64));
+        let overflow = added.map(|(_, _, overflow)| F::from(overflow as u64));
+        region.assign_advice(|| "carry", config.carry, 0, || carry)?;
+        region.assign_advice(|| "overflow", config.overflow, 0, || overflow)
+    }
+
+    /// Constrains both halves of `word` to be 128-bit.
+    fn range_check(&self, mut layouter: impl Layouter<F>, word: &Word<AssignedCell<F, F>>) -> Result<(), Error> {
+        self.config.pack.unpack(layouter.namespace(|| "lo"), &word.lo(), 16)?;
+        self.config.pack.unpack(layouter.namespace(|| "hi"), &word.hi(), 16)?;
+        Ok(())
+    }
+}
+
+fn copy_word<F: Field>(
+    region: &mut Region<'_, F>,
+    name: &str,
+    word: &Word<AssignedCell<F, F>>,
+    columns: [Column<Advice>; 2],
+) -> Result<Word<AssignedCell<F, F>>, Error> {
+    let lo = word.lo().copy_advice(|| format!("{name}.lo"), region, columns[0], 0)?;
+    let hi = word.hi().copy_advice(|| format!("{name}.hi"), region, columns[1], 0)?;
+    Ok(Word::new([lo, hi]))
+}
+
+fn assign_word<F: Field>(
+    region: &mut Region<'_, F>,
+    name: &str,
+    word: Value<[u128; 2]>,
+    columns: [Column<Advice>; 2],
+) -> Result<Word<AssignedCell<F, F>>, Error> {
+    let lo = region.assign_advice(|| format!("{name}.lo"), columns[0], 0, || word.map(|w| F::from_u128(w[0])))?;
+    let hi = region.assign_advice(|| format!("{name}.hi"), columns[1], 0, || word.map(|w| F::from_u128(w[1])))?;
+    Ok(Word::new([lo, hi]))
+}
+
+/// The `lo`/`hi` halves of `word`, truncated to 128 bits.
+fn to_u128s<F: Field>(word: &Word<AssignedCell<F, F>>) -> Value<[u128; 2]> {
+    let half = |cell: AssignedCell<F, F>| {
+        cell.value()
+            .map(|value| u128::from_le_bytes(value.to_repr()[..16].try_into().unwrap()))
+    };
+    half(word.lo()).zip(half(word.hi())).map(|(lo, hi)| [lo, hi])
+}
+
+/// `a + b` with the carry out of `lo` and the overflow.
+fn add([a_lo, a_hi]: [u128; 2], [b_lo, b_hi]: [u128; 2]) -> ([u128; 2], bool, bool) {
+    let (lo, carry) = a_lo.overflowing_add(b_lo);
+    let (hi, overflow_b) = a_hi.overflowing_add(b_hi);
+    let (hi, overflow_carry) = hi.overflowing_add(carry as u128);
+    ([lo, hi], carry, overflow_b || overflow_carry)
+}
+
+/// `a - b mod 2^256`.
+fn sub([a_lo, a_hi]: [u128; 2], [b_lo, b_hi]: [u128; 2]) -> [u128; 2] {
+    let (lo, borrow) = a_lo.overflowing_sub(b_lo);
+    [lo, a_hi.wrapping_sub(b_hi).wrapping_sub(borrow as u128)]
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+
+    use super::{add, sub, WordAddChip, WordAddConfig};
+    use crate::{
+        circuits::{pack::PackConfig, table::U8Table, word::Word},
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::{Field, TestField as Fp},
+    };
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig {
+        word_add: WordAddConfig,
+        u8_table: U8Table,
+        input: Column<Advice>,
+        instance: Column<Instance>,
+    }
+
+    /// Exposes `a + b` and its overflow, then `a - b` and its underflow.
+    #[derive(Default)]
+    struct TestCircuit {
+        a: Value<[u128; 2]>,
+        b: Value<[u128; 2]>,
+    }
+
+    impl TestCircuit {
+        fn new(a: [u128; 2], b: [u128; 2]) -> Self {
+            Self {
+                a: Value::known(a),
+                b: Value::known(b),
+            }
+        }
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit {
+        type Config = TestCircuitConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let u8_table = U8Table::configure(meta);
+            let [input, byte, acc] = [(); 3].map(|_| meta.advice_column());
+            let pack = PackConfig::configure(meta, byte, acc, u8_table);
+            let word_add = WordAddChip::configure(meta, pack);
+            let instance = meta.instance_column();
+            meta.enable_equality(input);
+            meta.enable_equality(instance);
+
+            TestCircuitConfig {
+                word_add,
+                u8_table,
+                input,
+                instance,
+            }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let chip = WordAddChip::construct(config.word_add);
+            config.u8_table.load(&mut layouter)?;
+
+            let limbs = layouter.assign_region(
+                || "inputs",
+                |mut region| {
+                    (0..4)
+                        .map(|i| {
+                            let limb = self.a.zip(self.b).map(|(a, b)| F::from_u128([a, b][i / 2][i % 2]));
+                            region.assign_advice(|| "limb", config.input, i, || limb)
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                },
+            )?;
+            let a = Word::new([limbs[0].clone(), limbs[1].clone()]);
+            let b = Word::new([limbs[2].clone(), limbs[3].clone()]);
+
+            let (sum, overflow) = chip.add(layouter.namespace(|| "a + b"), &a, &b)?;
+            let (diff, underflow) = chip.sub(layouter.namespace(|| "a - b"), &a, &b)?;
+
+            for (i, cell) in [sum.lo(), sum.hi(), overflow, diff.lo(), diff.hi(), underflow]
+                .iter()
+                .enumerate()
+            {
+                layouter.constrain_instance(cell.cell(), config.instance, i)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn instances(a: [u128; 2], b: [u128; 2]) -> Vec<Vec<Fp>> {
+        let (sum, _, overflow) = add(a, b);
+        let diff = sub(a, b);
+        let underflow = (a[1], a[0]) < (b[1], b[0]);
+        vec![vec![
+            Fp::from_u128(sum[0]),
+            Fp::from_u128(sum[1]),
+            Fp::from(overflow as u64),
+            Fp::from_u128(diff[0]),
+            Fp::from_u128(diff[1]),
+            Fp::from(underflow as u64),
+        ]]
+    }
+
+    #[test]
+    fn word_add() {
+        let max = u128::MAX;
+        let cases = [
+            ([0, 0], [0, 0]),
+            ([5, 7], [3, 2]),
+            ([max, 0], [1, 0]),
+            ([3, 2], [5, 7]),
+            ([max, max], [1, 0]),
+            ([max, max], [max, max]),
+            ([0, 1 << 127], [0, 1 << 127]),
+        ];
+        for (a, b) in cases {
+            expect_satisfied(&TestCircuit::new(a, b), instances(a, b));
+        }
+
+        // Hiding the overflow of `2^256 - 1 + 1`.
+        let mut wrong = instances([max, max], [1, 0]);
+        wrong[0][2] = Fp::from(0);
+        expect_failure(
+            &TestCircuit::new([max, max], [1, 0]),
+            wrong,
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 2 },
+            },
+        );
+    }
+}
@@ -0,0 +1,198 @@
+//! Public inputs.
+//!
+//! A circuit describes its public inputs with an [`InstanceLayout`], the
+//! names of the rows of each of its instance columns, and exposes cells
+//! through the [`InstanceColumns`] the layout configures:
+//!
+//! ```ignore
+//! let layout = InstanceLayout::new().column(["is_zero", "hash"]);
+//! let instance = layout.configure(meta);
+//! // ...
+//! instance.expose_public(&mut layouter, &hash, layout.index("hash"))?;
+//! ```
+//!
+//! The layout is also where the verifier side finds how many instances each
+//! column has, see [`InstanceLayout::num_instance`].
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::{Column, ConstraintSystem, Error, Instance},
+};
+
+use crate::field::Field;
+
+/// Position of a public input, a row of an instance column. A bare `usize`
+/// is a row of the first column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InstanceIndex {
+    pub column: usize,
+    pub row: usize,
+}
+
+impl From<usize> for InstanceIndex {
+    fn from(row: usize) -> Self {
+        Self { column: 0, row }
+    }
+}
+
+/// Names of the public inputs of a circuit, one list per instance column.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InstanceLayout {
+    columns: Vec<Vec<String>>,
+}
+
+impl InstanceLayout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an instance column whose rows are the public inputs `names`.
+    pub fn column<S: Into<String>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        self.columns.push(names.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Number of instances of each instance column.
+    pub fn num_instance(&self) -> Vec<usize> {
+        self.columns.iter().map(Vec::len).collect()
+    }
+
+    /// Position of the public input `name`.
+    ///
+    /// # Panics
+    ///
+    /// If the layout has no public input `name`.
+    pub fn index(&self, name: &str) -> InstanceIndex {
+        self.columns
+            .iter()
+            .enumerate()
+            .find_map(|(column, names)| {
+                let row = names.iter().position(|n| n == name)?;
+                Some(InstanceIndex { column, row })
+            })
+            .unwrap_or_else(|| panic!("no public input {name:?}"))
+    }
+
+    /// Creates the instance columns, with equality enabled.
+    pub fn configure<F: Field>(&self, meta: &mut ConstraintSystem<F>) -> InstanceColumns {
+        let columns = (self.columns.iter())
+            .map(|_| {
+                let column = meta.instance_column();
+                meta.enable_equality(column);
+                column
+            })
+            .collect();
+        InstanceColumns { columns }
+    }
+}
+
+/// Instance columns created by [`InstanceLayout::configure`].
+#[derive(Clone, Debug)]
+pub struct InstanceColumns {
+    columns: Vec<Column<Instance>>,
+}
+
+impl InstanceColumns {
+    /// The `i`-th instance column.
+    pub fn column(&self, i: usize) -> Column<Instance> {
+        self.columns[i]
+    }
+
+    /// Constrains `cell` to equal the public input at `index`.
+    pub fn expose_public<F: Field>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+        index: impl Into<InstanceIndex>,
+    ) -> Result<(), Error> {
+        let index = index.into();
+        layouter.constrain_instance(cell.cell(), self.columns[index.column], index.row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+    };
+
+    use super::{InstanceColumns, InstanceIndex, InstanceLayout};
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::{Field, TestField as Fp},
+    };
+
+    fn layout() -> InstanceLayout {
+        InstanceLayout::new().column(["a", "b"]).column(["sum"])
+    }
+
+    /// Exposes `a`, `b` in the first instance column and `a + b` in the
+    /// second.
+    #[derive(Default)]
+    struct TestCircuit {
+        a: u64,
+        b: u64,
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit {
+        type Config = (Column<Advice>, InstanceColumns);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let advice = meta.advice_column();
+            meta.enable_equality(advice);
+            (advice, layout().configure(meta))
+        }
+
+        fn synthesize(&self, (advice, instance): Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let layout = layout();
+            let values = [("a", self.a), ("b", self.b), ("sum", self.a + self.b)];
+            let cells = layouter.assign_region(
+                || "values",
+                |mut region| {
+                    (values.iter().enumerate())
+                        .map(|(i, (name, value))| {
+                            region.assign_advice(|| *name, advice, i, || Value::known(F::from(*value)))
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                },
+            )?;
+
+            for ((name, _), cell) in values.iter().zip(&cells) {
+                instance.expose_public(&mut layouter, cell, layout.index(name))?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn layout_index() {
+        let layout = layout();
+        assert_eq!(layout.num_instance(), vec![2, 1]);
+        assert_eq!(layout.index("b"), InstanceIndex::from(1));
+        assert_eq!(layout.index("sum"), InstanceIndex { column: 1, row: 0 });
+    }
+
+    #[test]
+    fn expose_public() {
+        let circuit = TestCircuit { a: 2, b: 3 };
+        let [a, b, sum] = [2, 3, 5].map(Fp::from);
+        expect_satisfied(&circuit, vec![vec![a, b], vec![sum]]);
+
+        // The sum is not in the first column.
+        expect_failure(
+            &circuit,
+            vec![vec![a, sum], vec![b]],
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+    }
+}
@@ -1,12 +1,13 @@
 use std::marker::PhantomData;
 
-use eth_types::Field;
 use halo2_proofs::{
     circuit::Chip,
     plonk::{Advice, Column, ConstraintSystem, Fixed, Selector},
     poly::Rotation,
 };
 
+use crate::field::Field;
+
 /// Config for the IsEqual chip.
 #[derive(Clone, Debug)]
 pub struct IsEqualConfig {
@@ -83,15 +84,15 @@ impl<F: Field> Chip<F> for IsEqualChip<F> {
 mod tests {
     use std::marker::PhantomData;
 
-    use eth_types::Field;
     use halo2_proofs::{
         circuit::{Layouter, SimpleFloorPlanner, Value},
         dev::MockProver,
-        halo2curves::bn256::Fr as Fp,
         plonk::{Circuit, ConstraintSystem, Error},
     };
     
     use super::{IsEqualChip, IsEqualConfig};
+    use crate::dev::fuzz::{field, gadget_proptest};
+    use crate::field::{Field, TestField as Fp};
 
     #[derive(Default)]
     struct TestCircuit<F: Field> {
@@ -178,4 +179,25 @@ mod tests {
             is_err
         );
     }
+
+    gadget_proptest! {
+        is_equal_complete(a in field()) {
+            circuit: TestCircuit::<Fp> {
+                a: Value::known(a),
+                b: Value::known(a),
+                _marker: PhantomData,
+            },
+            instances: vec![],
+            valid: true,
+        }
+        is_equal_sound(a in field(), b in field()) {
+            circuit: TestCircuit::<Fp> {
+                a: Value::known(a),
+                b: Value::known(b),
+                _marker: PhantomData,
+            },
+            instances: vec![],
+            valid: a == b,
+        }
+    }
 }
\ No newline at end of file
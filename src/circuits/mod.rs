@@ -1,7 +1,17 @@
 mod is_equal;
-mod simple;
+pub(crate) mod simple;
 mod simple_1;
 mod is_equal_1;
-mod gadgets;
-mod range_check_1;
-mod range_check_2;
\ No newline at end of file
+pub mod gadgets;
+pub(crate) mod range_check_1;
+mod range_check_2;
+mod range_check_2b;
+pub mod bls12_381;
+pub mod examples;
+pub mod instance;
+pub mod pack;
+pub mod sub_circuit;
+pub mod table;
+pub mod util;
+pub mod verifier;
+pub mod word;
\ No newline at end of file
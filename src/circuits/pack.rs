@@ -0,0 +1,380 @@
+//! Packing of byte cells into a field element, and back.
+//!
+//! Up to [`MAX_BYTES`] little-endian bytes are packed with a running sum from
+//! the last byte down, so that the packed value ends up in `acc[0]`:
+//!
+//! | byte        | acc                   | q_step | q_last | q_byte |
+//! | bytes[0]    | bytes[0] + 256⋅acc[1] | 1      | 0      | 1      |
+//! | ...         | ...                   | 1      | 0      | 1      |
+//! | bytes[n-1]  | bytes[n-1]            | 0      | 1      | 1      |
+//!
+//! Every byte is looked up in the [`U8Table`]. As `256^31` is below the
+//! modulus, the sum never wraps around: a packed value has a unique byte
+//! decomposition, and unpacking a value of `2^(8n)` or more fails.
+//!
+//! With `NUM_COLUMNS` pairs of `byte` and `acc` columns, each byte column
+//! looked up on its own, the bytes are laid out row by row and `n` bytes take
+//! `ceil(n / NUM_COLUMNS)` rows. The step and last selectors are per column, and
+//! the last row is padded with zero bytes outside of the running sum:
+//!
+//! | byte[0]    | acc[0] | byte[1]    | acc[1] | q_step[0..2] | q_last[0..2] | q_byte |
+//! | bytes[0]   | acc[0] | bytes[1]   | acc[1] | 1, 1         | 0, 0         | 1      |
+//! | ...        | ...    | ...        | ...    | ...          | ...          | 1      |
+//! | bytes[n-1] | acc    | 0          |        | 0, 0         | 1, 0         | 1      |
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::{circuits::table::U8Table, field::Field};
+
+/// Most bytes that fit in a field element without wrapping around.
+pub const MAX_BYTES: usize = 31;
+
+/// Packs byte cells into a field element, and unpacks them, over
+/// `NUM_COLUMNS` pairs of columns.
+#[derive(Clone, Debug)]
+pub struct PackConfig<const NUM_COLUMNS: usize = 1> {
+    q_step: [Selector; NUM_COLUMNS],
+    q_last: [Selector; NUM_COLUMNS],
+    q_byte: Selector,
+    byte: [Column<Advice>; NUM_COLUMNS],
+    acc: [Column<Advice>; NUM_COLUMNS],
+}
+
+impl PackConfig {
+    pub fn configure<F: Field>(
+        meta: &mut ConstraintSystem<F>,
+        byte: Column<Advice>,
+        acc: Column<Advice>,
+        u8_table: U8Table,
+    ) -> Self {
+        Self::configure_columns(meta, [byte], [acc], u8_table)
+    }
+}
+
+impl<const NUM_COLUMNS: usize> PackConfig<NUM_COLUMNS> {
+    /// Configures packing with the bytes in the `byte` columns and the
+    /// running sum in the `acc` columns.
+    pub fn configure_columns<F: Field>(
+        meta: &mut ConstraintSystem<F>,
+        byte: [Column<Advice>; NUM_COLUMNS],
+        acc: [Column<Advice>; NUM_COLUMNS],
+        u8_table: U8Table,
+    ) -> Self {
+        assert!(NUM_COLUMNS > 0);
+        let q_step = [(); NUM_COLUMNS].map(|_| meta.selector());
+        let q_last = [(); NUM_COLUMNS].map(|_| meta.selector());
+        let q_byte = meta.complex_selector();
+
+        for column in byte.into_iter().chain(acc) {
+            meta.enable_equality(column);
+        }
+
+        for column in byte {
+            meta.lookup("pack byte", |meta| {
+                let q_byte = meta.query_selector(q_byte);
+                let byte = meta.query_advice(column, Rotation::cur());
+                vec![(q_byte * byte, u8_table.value)]
+            });
+        }
+
+        // One gate per column, so that the selectors of a column only query
+        // its own cells and the next running sum.
+        for i in 0..NUM_COLUMNS {
+            meta.create_gate("byte pack", |meta| {
+                let q_step = meta.query_selector(q_step[i]);
+                let q_last = meta.query_selector(q_last[i]);
+                let byte = meta.query_advice(byte[i], Rotation::cur());
+                let acc_cur = meta.query_advice(acc[i], Rotation::cur());
+                let acc_next = match acc.get(i + 1) {
+                    Some(column) => meta.query_advice(*column, Rotation::cur()),
+                    None => meta.query_advice(acc[0], Rotation::next()),
+                };
+
+                vec![
+                    q_step * (acc_cur.clone() - byte.clone() - Expression::Constant(F::from(256)) * acc_next),
+                    q_last * (acc_cur - byte),
+                ]
+            });
+        }
+
+        Self {
+            q_step,
+            q_last,
+            q_byte,
+            byte,
+            acc,
+        }
+    }
+
+    /// The row and column pair of byte `i`.
+    fn position(i: usize) -> (usize, usize) {
+        (i / NUM_COLUMNS, i % NUM_COLUMNS)
+    }
+
+    /// Packs the little-endian `bytes` into a field element.
+    pub fn pack<F: Field>(
+        &self,
+        mut layouter: impl Layouter<F>,
+        bytes: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        check_len(bytes.len());
+        let values: Vec<_> = bytes.iter().map(|byte| byte.value().copied()).collect();
+        let accs = running_sums(&values);
+        layouter.assign_region(
+            || "pack bytes",
+            |mut region| {
+                let mut packed = None;
+                for (i, acc) in accs.iter().copied().enumerate() {
+                    let (row, column) = Self::position(i);
+                    self.enable(&mut region, i, bytes.len())?;
+                    bytes[i].copy_advice(|| format!("byte {i}"), &mut region, self.byte[column], row)?;
+                    let acc = region.assign_advice(|| format!("acc {i}"), self.acc[column], row, || acc)?;
+                    packed.get_or_insert(acc);
+                }
+                self.pad(&mut region, bytes.len())?;
+                Ok(packed.unwrap())
+            },
+        )
+    }
+
+    /// Unpacks `value` into `num_bytes` little-endian byte cells, failing if
+    /// it does not fit.
+    pub fn unpack<F: Field>(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: &AssignedCell<F, F>,
+        num_bytes: usize,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        check_len(num_bytes);
+        let repr = value.value().map(|value| value.to_repr());
+        let values: Vec<_> = (0..num_bytes)
+            .map(|i| repr.map(|repr| F::from(repr[i] as u64)))
+            .collect();
+        let accs = running_sums(&values);
+        layouter.assign_region(
+            || "unpack bytes",
+            |mut region| {
+                value.copy_advice(|| "value", &mut region, self.acc[0], 0)?;
+
+                let mut bytes = vec![];
+                for (i, acc) in accs.iter().copied().enumerate() {
+                    let (row, column) = Self::position(i);
+                    self.enable(&mut region, i, num_bytes)?;
+                    bytes.push(region.assign_advice(|| format!("byte {i}"), self.byte[column], row, || values[i])?);
+                    if i > 0 {
+                        region.assign_advice(|| format!("acc {i}"), self.acc[column], row, || acc)?;
+                    }
+                }
+                self.pad(&mut region, num_bytes)?;
+                Ok(bytes)
+            },
+        )
+    }
+
+    /// Enables the selectors of byte `i` out of `len`.
+    fn enable<F: Field>(&self, region: &mut Region<'_, F>, i: usize, len: usize) -> Result<(), Error> {
+        let (row, column) = Self::position(i);
+        self.q_byte.enable(region, row)?;
+        if i + 1 == len {
+            self.q_last[column].enable(region, row)
+        } else {
+            self.q_step[column].enable(region, row)
+        }
+    }
+
+    /// Fills the byte columns of the last row after `len` bytes with zeros,
+    /// and the running sum after the last byte, which its gate queries.
+    fn pad<F: Field>(&self, region: &mut Region<'_, F>, len: usize) -> Result<(), Error> {
+        let zero = Value::known(F::ZERO);
+        for i in len..len.div_ceil(NUM_COLUMNS) * NUM_COLUMNS {
+            let (row, column) = Self::position(i);
+            region.assign_advice(|| "padding", self.byte[column], row, || zero)?;
+        }
+        let (row, column) = Self::position(len);
+        region.assign_advice(|| "acc end", self.acc[column], row, || zero)?;
+        Ok(())
+    }
+}
+
+fn check_len(len: usize) {
+    assert!((1..=MAX_BYTES).contains(&len), "can only pack 1 to {MAX_BYTES} bytes, got {len}");
+}
+
+/// `acc[i] = bytes[i] + 256 ⋅ acc[i + 1]`, with `acc[n] = 0`.
+fn running_sums<F: Field>(bytes: &[Value<F>]) -> Vec<Value<F>> {
+    let mut sums: Vec<_> = bytes
+        .iter()
+        .rev()
+        .scan(Value::known(F::ZERO), |acc, byte| {
+            *acc = *acc * Value::known(F::from(256)) + *byte;
+            Some(*acc)
+        })
+        .collect();
+    sums.reverse();
+    sums
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+
+    use super::{PackConfig, MAX_BYTES};
+    use crate::{
+        circuits::{table::U8Table, word::field_from_le_bytes},
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::{Field, TestField as Fp},
+    };
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig<const COLUMNS: usize> {
+        pack: PackConfig<COLUMNS>,
+        u8_table: U8Table,
+        input: Column<Advice>,
+        instance: Column<Instance>,
+    }
+
+    /// Packs `bytes` and unpacks the result into `bytes.len()` bytes, exposing
+    /// the packed value and the unpacked bytes.
+    #[derive(Default)]
+    struct TestCircuit<const COLUMNS: usize = 1> {
+        bytes: Vec<Value<u64>>,
+    }
+
+    impl<F: Field, const COLUMNS: usize> Circuit<F> for TestCircuit<COLUMNS> {
+        type Config = TestCircuitConfig<COLUMNS>;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                bytes: vec![Value::unknown(); self.bytes.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let u8_table = U8Table::configure(meta);
+            let input = meta.advice_column();
+            let [byte, acc] = [(); 2].map(|_| [(); COLUMNS].map(|_| meta.advice_column()));
+            let pack = PackConfig::configure_columns(meta, byte, acc, u8_table);
+            let instance = meta.instance_column();
+            meta.enable_equality(input);
+            meta.enable_equality(instance);
+
+            TestCircuitConfig {
+                pack,
+                u8_table,
+                input,
+                instance,
+            }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            config.u8_table.load(&mut layouter)?;
+
+            let bytes = layouter.assign_region(
+                || "bytes",
+                |mut region| {
+                    (self.bytes.iter().enumerate())
+                        .map(|(i, byte)| region.assign_advice(|| "byte", config.input, i, || byte.map(F::from)))
+                        .collect::<Result<Vec<_>, _>>()
+                },
+            )?;
+
+            let packed = config.pack.pack(layouter.namespace(|| "pack"), &bytes)?;
+            let unpacked = config.pack.unpack(layouter.namespace(|| "unpack"), &packed, bytes.len())?;
+
+            layouter.constrain_instance(packed.cell(), config.instance, 0)?;
+            for (i, byte) in unpacked.iter().enumerate() {
+                layouter.constrain_instance(byte.cell(), config.instance, i + 1)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn instances(bytes: &[u8]) -> Vec<Vec<Fp>> {
+        let mut instances = vec![field_from_le_bytes(bytes)];
+        instances.extend(bytes.iter().map(|byte| Fp::from(*byte as u64)));
+        vec![instances]
+    }
+
+    fn circuit<const COLUMNS: usize>(bytes: &[u8]) -> TestCircuit<COLUMNS> {
+        TestCircuit {
+            bytes: bytes.iter().map(|byte| Value::known(*byte as u64)).collect(),
+        }
+    }
+
+    #[test]
+    fn pack_unpack() {
+        for len in [1, 2, MAX_BYTES] {
+            let bytes: Vec<u8> = (0..len).map(|i| (i * 53 + 255) as u8).collect();
+            expect_satisfied(&circuit::<1>(&bytes), instances(&bytes));
+        }
+    }
+
+    #[test]
+    fn pack_unpack_columns() {
+        for len in [1, 2, 3, 4, MAX_BYTES] {
+            let bytes: Vec<u8> = (0..len).map(|i| (i * 53 + 255) as u8).collect();
+            expect_satisfied(&circuit::<3>(&bytes), instances(&bytes));
+            expect_satisfied(&circuit::<4>(&bytes), instances(&bytes));
+        }
+
+        // 256 is not a byte: with 3 columns, the fifth byte is in the middle
+        // column of the second row.
+        let circuit = TestCircuit::<3> {
+            bytes: [1, 2, 3, 4, 256].map(Value::known).to_vec(),
+        };
+        let packed = Fp::from(0x01_00_04_03_02_01);
+        let instances = vec![[packed].into_iter().chain([1, 2, 3, 4, 0].map(Fp::from)).collect()];
+        expect_failure(
+            &circuit,
+            instances,
+            FailureMatcher::Lookup {
+                name: "pack byte",
+                location: Location::InRegion {
+                    region: "pack bytes",
+                    offset: 1,
+                },
+            },
+        );
+    }
+
+    #[test]
+    fn out_of_range() {
+        // 256 is not a byte, and does not unpack into one byte.
+        let circuit = TestCircuit::<1> {
+            bytes: vec![Value::known(256)],
+        };
+        let instances = vec![vec![Fp::from(256), Fp::from(0)]];
+        expect_failure(
+            &circuit,
+            instances.clone(),
+            FailureMatcher::Lookup {
+                name: "pack byte",
+                location: Location::InRegion {
+                    region: "pack bytes",
+                    offset: 0,
+                },
+            },
+        );
+        expect_failure(
+            &circuit,
+            instances,
+            FailureMatcher::Constraint {
+                gate: "byte pack",
+                location: Location::InRegion {
+                    region: "unpack bytes",
+                    offset: 0,
+                },
+            },
+        );
+    }
+}
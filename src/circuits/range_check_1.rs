@@ -1,34 +1,52 @@
 use std::marker::PhantomData;
 
-use eth_types::Field;
 use halo2_proofs::{
     circuit::{AssignedCell, Layouter, Value},
     plonk::{Advice, Assigned, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
     poly::Rotation,
 };
 
+use crate::field::Field;
+
 #[derive(Debug, Clone)]
 /// A range-constrained value in the circuit produced by the RangeCheckConfig.
-struct RangeConstrained<F: Field, const RANGE: usize>(AssignedCell<Assigned<F>, F>);
+pub(crate) struct RangeConstrained<F: Field>(pub(crate) AssignedCell<Assigned<F>, F>);
 
+/// Range checks values over `NUM_COLUMNS` columns, one value per column and
+/// row, so that `n` values take `n / NUM_COLUMNS` rows.
 #[derive(Debug, Clone)]
-struct RangeCheckConfig<F: Field, const RANGE: usize> {
-    value: Column<Advice>,
+pub(crate) struct RangeCheckConfig<F: Field, const NUM_COLUMNS: usize = 1> {
+    value: [Column<Advice>; NUM_COLUMNS],
     q_range_check: Selector,
+    /// The checked values lie in `[0, range)`.
+    range: usize,
     _marker: PhantomData<F>,
 }
 
-impl<F: Field, const RANGE: usize> RangeCheckConfig<F, RANGE> {
-    pub fn configure(meta: &mut ConstraintSystem<F>, value: Column<Advice>) -> Self {
+impl<F: Field> RangeCheckConfig<F> {
+    /// Configures a check that `value` is in `[0, range)`, with a gate of
+    /// degree `range`.
+    pub fn configure(meta: &mut ConstraintSystem<F>, value: Column<Advice>, range: usize) -> Self {
+        Self::configure_columns(meta, [value], range)
+    }
+}
+
+impl<F: Field, const NUM_COLUMNS: usize> RangeCheckConfig<F, NUM_COLUMNS> {
+    /// Configures the check over the `value` columns.
+    pub fn configure_columns(
+        meta: &mut ConstraintSystem<F>,
+        value: [Column<Advice>; NUM_COLUMNS],
+        range: usize,
+    ) -> Self {
+        assert!(NUM_COLUMNS > 0);
         let q_range_check = meta.selector();
 
         meta.create_gate("range check", |meta| {
-            //        value     |    q_range_check
-            //       ------------------------------
-            //          v       |         1
+            //     value[0]   |  ...  |  value[NUM_COLUMNS - 1]  |  q_range_check
+            //   -----------------------------------------------------------------
+            //        v0      |  ...  |            vn            |        1
 
             let q = meta.query_selector(q_range_check);
-            let value = meta.query_advice(value, Rotation::cur());
 
             // TODO move this to assign
             // Given a range R and a value v, returns the expression
@@ -40,33 +58,65 @@ impl<F: Field, const RANGE: usize> RangeCheckConfig<F, RANGE> {
                 })
             };
 
-            Constraints::with_selector(q, [("range check", range_check(RANGE, value))])
+            let constraints = value.map(|value| {
+                let value = meta.query_advice(value, Rotation::cur());
+                ("range check", range_check(range, value))
+            });
+            Constraints::with_selector(q, constraints)
         });
 
         Self {
             q_range_check,
             value,
+            range,
             _marker: PhantomData,
         }
     }
 
+    /// The checked values lie in `[0, range)`.
+    pub fn range(&self) -> usize {
+        self.range
+    }
+
     pub fn assign(
         &self,
-        mut layouter: impl Layouter<F>,
+        layouter: impl Layouter<F>,
         value: Value<Assigned<F>>,
-    ) -> Result<RangeConstrained<F, RANGE>, Error> {
+    ) -> Result<RangeConstrained<F>, Error> {
+        let mut values = self.assign_values(layouter, &[value])?;
+        Ok(values.remove(0))
+    }
+
+    /// Range checks `values`, `NUM_COLUMNS` per row. The last row is padded
+    /// with zeros.
+    pub fn assign_values(
+        &self,
+        mut layouter: impl Layouter<F>,
+        values: &[Value<Assigned<F>>],
+    ) -> Result<Vec<RangeConstrained<F>>, Error> {
         layouter.assign_region(
             || "Assign value",
             |mut region| {
-                let offset = 0;
+                let num_rows = values.len().div_ceil(NUM_COLUMNS);
 
                 // Enable q_range_check
-                self.q_range_check.enable(&mut region, offset)?;
+                for offset in 0..num_rows {
+                    self.q_range_check.enable(&mut region, offset)?;
+                }
+
+                for i in values.len()..num_rows * NUM_COLUMNS {
+                    let zero = Value::known(Assigned::from(F::ZERO));
+                    region.assign_advice(|| "padding", self.value[i % NUM_COLUMNS], i / NUM_COLUMNS, || zero)?;
+                }
 
-                // Assign value
-                region
-                    .assign_advice(|| "value", self.value, offset, || value)
-                    .map(RangeConstrained)
+                // Assign values
+                (values.iter().enumerate())
+                    .map(|(i, value)| {
+                        region
+                            .assign_advice(|| "value", self.value[i % NUM_COLUMNS], i / NUM_COLUMNS, || *value)
+                            .map(RangeConstrained)
+                    })
+                    .collect()
             },
         )
     }
@@ -77,11 +127,14 @@ mod tests {
     use halo2_proofs::{
         circuit::floor_planner::V1,
         dev::{FailureLocation, MockProver, VerifyFailure},
-        halo2curves::bn256::Fr as Fp,
         plonk::{Any, Circuit},
     };
 
     use super::*;
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::TestField as Fp,
+    };
 
     #[derive(Default)]
     struct MyCircuit<F: Field, const RANGE: usize> {
@@ -89,7 +142,7 @@ mod tests {
     }
 
     impl<F: Field, const RANGE: usize> Circuit<F> for MyCircuit<F, RANGE> {
-        type Config = RangeCheckConfig<F, RANGE>;
+        type Config = RangeCheckConfig<F>;
         // or SimpleFloorPlanner
         type FloorPlanner = V1;
 
@@ -99,7 +152,7 @@ mod tests {
 
         fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
             let value = meta.advice_column();
-            RangeCheckConfig::configure(meta, value)
+            RangeCheckConfig::configure(meta, value, RANGE)
         }
 
         fn synthesize(
@@ -148,22 +201,66 @@ mod tests {
         }
     }
 
-    #[cfg(feature = "dev-graph")]
+    /// Range checks `values` over `COLUMNS` columns.
+    #[derive(Default)]
+    struct ColumnsCircuit<const RANGE: usize, const COLUMNS: usize> {
+        values: Vec<u64>,
+    }
+
+    impl<F: Field, const RANGE: usize, const COLUMNS: usize> Circuit<F> for ColumnsCircuit<RANGE, COLUMNS> {
+        type Config = RangeCheckConfig<F, COLUMNS>;
+        type FloorPlanner = V1;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let value = [(); COLUMNS].map(|_| meta.advice_column());
+            RangeCheckConfig::configure_columns(meta, value, RANGE)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let values: Vec<_> = (self.values.iter())
+                .map(|value| Value::known(Assigned::from(F::from(*value))))
+                .collect();
+            let checked = config.assign_values(layouter.namespace(|| "Assign values"), &values)?;
+            assert_eq!(checked.len(), values.len());
+
+            Ok(())
+        }
+    }
+
     #[test]
-    fn print_range_check_1() {
-        use plotters::prelude::*;
+    fn range_check_columns() {
+        let values = vec![0, 1, 2, 3, 4, 5, 6, 7];
+        expect_satisfied::<Fp, _>(&ColumnsCircuit::<8, 3> { values: values.clone() }, vec![]);
+        expect_satisfied::<Fp, _>(&ColumnsCircuit::<8, 8> { values }, vec![]);
 
-        let root = BitMapBackend::new("range-check-1-layout.png", (1024, 3096)).into_drawing_area();
-        root.fill(&WHITE).unwrap();
-        let root = root
-            .titled("Range Check 1 Layout", ("sans-serif", 60))
-            .unwrap();
+        // The fifth value is in the middle column of the second row.
+        expect_failure::<Fp, _>(
+            &ColumnsCircuit::<8, 3> {
+                values: vec![0, 1, 2, 3, 8],
+            },
+            vec![],
+            FailureMatcher::Constraint {
+                gate: "range check",
+                location: Location::InRegion {
+                    region: "Assign value",
+                    offset: 1,
+                },
+            },
+        );
+    }
 
+    #[cfg(feature = "dev-graph")]
+    #[test]
+    fn print_range_check_1() {
         let circuit = MyCircuit::<Fp, 8> {
             value: Value::unknown(),
         };
-        halo2_proofs::dev::CircuitLayout::default()
-            .render(3, &circuit, &root)
-            .unwrap();
+        crate::dev::layout::render_layout("range-check-1", 3, &circuit).unwrap();
     }
 }
\ No newline at end of file
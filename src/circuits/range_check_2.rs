@@ -1,36 +1,49 @@
+//! A range check whose gate doesn't bind the range polynomial: the product
+//! is computed in the witness and the gate only constrains it to be zero.
+//! See `range_check_2b` for the fixed gate.
+
 use std::marker::PhantomData;
 
-use eth_types::Field;
 use halo2_proofs::{
     circuit::{AssignedCell, Layouter, Value},
     plonk::{Advice, Assigned, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
     poly::Rotation,
 };
 
+use crate::field::Field;
+
 #[derive(Debug, Clone)]
 /// A range-constrained value in the circuit produced by the RangeCheckConfig.
 struct RangeConstrained<F: Field, const RANGE: usize>(AssignedCell<Assigned<F>, F>);
 
 #[derive(Debug, Clone)]
-struct RangeCheckConfig<F: Field, const RANGE: usize> {
-    value: Column<Advice>,
+struct RangeCheckConfig<F: Field, const RANGE: usize, const NUM_COLUMNS: usize = 1> {
+    value: [Column<Advice>; NUM_COLUMNS],
     q_range_check: Selector,
     _marker: PhantomData<F>,
 }
 
 impl<F: Field, const RANGE: usize> RangeCheckConfig<F, RANGE> {
     pub fn configure(meta: &mut ConstraintSystem<F>, value: Column<Advice>) -> Self {
+        Self::configure_columns(meta, [value])
+    }
+}
+
+impl<F: Field, const RANGE: usize, const NUM_COLUMNS: usize> RangeCheckConfig<F, RANGE, NUM_COLUMNS> {
+    /// Configures the check over the `value` columns, one value per column
+    /// and row.
+    pub fn configure_columns(meta: &mut ConstraintSystem<F>, value: [Column<Advice>; NUM_COLUMNS]) -> Self {
+        assert!(NUM_COLUMNS > 0);
         let q_range_check = meta.selector();
 
         meta.create_gate("range check", |meta| {
-            //        value     |    q_range_check
-            //       ------------------------------
-            //          v       |         1
+            //     value[0]   |  ...  |  value[NUM_COLUMNS - 1]  |  q_range_check
+            //   -----------------------------------------------------------------
+            //        v0      |  ...  |            vn            |        1
 
             let q = meta.query_selector(q_range_check);
-            let value = meta.query_advice(value, Rotation::cur());
 
-            vec![q * value] 
+            value.map(|value| q.clone() * meta.query_advice(value, Rotation::cur()))
         });
 
         Self {
@@ -42,16 +55,34 @@ impl<F: Field, const RANGE: usize> RangeCheckConfig<F, RANGE> {
 
     pub fn assign(
         &self,
-        mut layouter: impl Layouter<F>,
+        layouter: impl Layouter<F>,
         value: Value<Assigned<F>>,
     ) -> Result<RangeConstrained<F, RANGE>, Error> {
+        let mut values = self.assign_values(layouter, &[value])?;
+        Ok(values.remove(0))
+    }
+
+    /// Range checks `values`, `NUM_COLUMNS` per row. The last row is padded
+    /// with zeros.
+    pub fn assign_values(
+        &self,
+        mut layouter: impl Layouter<F>,
+        values: &[Value<Assigned<F>>],
+    ) -> Result<Vec<RangeConstrained<F, RANGE>>, Error> {
         layouter.assign_region(
             || "Assign value",
             |mut region| {
-                let offset = 0;
+                let num_rows = values.len().div_ceil(NUM_COLUMNS);
 
                 // Enable q_range_check
-                self.q_range_check.enable(&mut region, offset)?;
+                for offset in 0..num_rows {
+                    self.q_range_check.enable(&mut region, offset)?;
+                }
+
+                for i in values.len()..num_rows * NUM_COLUMNS {
+                    let zero = Value::known(Assigned::from(F::ZERO));
+                    region.assign_advice(|| "padding", self.value[i % NUM_COLUMNS], i / NUM_COLUMNS, || zero)?;
+                }
 
                 let range_check = |range: usize, value: Value<Assigned<F>>| {
                     assert!(range > 0);
@@ -60,10 +91,15 @@ impl<F: Field, const RANGE: usize> RangeCheckConfig<F, RANGE> {
                     })
                 };
 
-                // Assign value
-                region
-                    .assign_advice(|| "value", self.value, offset, || range_check(RANGE, value))
-                    .map(RangeConstrained)
+                // Assign values
+                (values.iter().enumerate())
+                    .map(|(i, value)| {
+                        let (column, offset) = (self.value[i % NUM_COLUMNS], i / NUM_COLUMNS);
+                        region
+                            .assign_advice(|| "value", column, offset, || range_check(RANGE, *value))
+                            .map(RangeConstrained)
+                    })
+                    .collect()
             },
         )
     }
@@ -75,11 +111,17 @@ mod tests {
     use halo2_proofs::{
         circuit::floor_planner::V1,
         dev::{MockProver},
-        halo2curves::bn256::Fr as Fp,
         plonk::{Circuit},
     };
 
     use super::*;
+    use crate::{
+        dev::{
+            expr::dump_gates,
+            mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        },
+        field::TestField as Fp,
+    };
 
     #[derive(Default)]
     struct MyCircuit<F: Field, const RANGE: usize> {
@@ -88,8 +130,11 @@ mod tests {
 
     impl<F: Field, const RANGE: usize> Circuit<F> for MyCircuit<F, RANGE> {
         type Config = RangeCheckConfig<F, RANGE>;
-        // or SimpleFloorPlanner
+        // A single region starts on the first row whatever the planner, see
+        // `crate::dev::planner`.
         type FloorPlanner = V1;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
 
         fn without_witnesses(&self) -> Self {
             Self::default()
@@ -135,22 +180,122 @@ mod tests {
         // prover.assert_satisfied();
     }
 
-    #[cfg(feature = "dev-graph")]
     #[test]
-    fn print_range_check_1() {
-        use plotters::prelude::*;
+    fn planner_rows() {
+        let rows = crate::dev::planner::planner_rows(&MyCircuit::<Fp, 8>::default()).unwrap();
+        assert!(rows.iter().all(|rows| rows.rows == 1), "{rows:?}");
+    }
+
+    /// Lays out the region of [`RangeCheckConfig::assign`] with a product
+    /// chosen by the prover instead of the computed one.
+    #[derive(Default)]
+    struct ForgedCircuit<F: Field, const RANGE: usize> {
+        product: Value<Assigned<F>>,
+    }
+
+    impl<F: Field, const RANGE: usize> Circuit<F> for ForgedCircuit<F, RANGE> {
+        type Config = RangeCheckConfig<F, RANGE>;
+        type FloorPlanner = V1;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            MyCircuit::<F, RANGE>::configure(meta)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            layouter.assign_region(
+                || "Assign value",
+                |mut region| {
+                    config.q_range_check.enable(&mut region, 0)?;
+                    region.assign_advice(|| "value", config.value[0], 0, || self.product)
+                },
+            )?;
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn misses_out_of_range_values() {
+        // The gate only sees the product, so a prover range checking 9 can
+        // write 0 instead of its product, and 9 is never part of the circuit.
+        let circuit = ForgedCircuit::<Fp, 8> {
+            product: Value::known(Fp::from(0).into()),
+        };
+        expect_satisfied(&circuit, vec![]);
+
+        // The gate doesn't depend on the range either.
+        let gates = dump_gates(&MyCircuit::<Fp, 8>::default());
+        assert_eq!(gates, dump_gates(&MyCircuit::<Fp, 4>::default()));
+        assert_eq!(gates[0].constraints[0].degree, 2);
+    }
+
+    /// Range checks `values` over `COLUMNS` columns.
+    #[derive(Default)]
+    struct ColumnsCircuit<const RANGE: usize, const COLUMNS: usize> {
+        values: Vec<u64>,
+    }
+
+    impl<F: Field, const RANGE: usize, const COLUMNS: usize> Circuit<F> for ColumnsCircuit<RANGE, COLUMNS> {
+        type Config = RangeCheckConfig<F, RANGE, COLUMNS>;
+        type FloorPlanner = V1;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
 
-        let root = BitMapBackend::new("range-check-1-layout.png", (1024, 3096)).into_drawing_area();
-        root.fill(&WHITE).unwrap();
-        let root = root
-            .titled("Range Check 1 Layout", ("sans-serif", 60))
-            .unwrap();
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
 
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let value = [(); COLUMNS].map(|_| meta.advice_column());
+            RangeCheckConfig::configure_columns(meta, value)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let values: Vec<_> = (self.values.iter())
+                .map(|value| Value::known(Assigned::from(F::from(*value))))
+                .collect();
+            let checked = config.assign_values(layouter.namespace(|| "Assign values"), &values)?;
+            assert_eq!(checked.len(), values.len());
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn range_check_columns() {
+        expect_satisfied::<Fp, _>(&ColumnsCircuit::<8, 3> { values: (0..8).collect() }, vec![]);
+        expect_satisfied::<Fp, _>(&ColumnsCircuit::<8, 8> { values: (0..8).collect() }, vec![]);
+
+        // An honest product of an out of range value is not zero: only a
+        // forged one passes, see `misses_out_of_range_values`. The fifth value
+        // is in the middle column of the second row.
+        expect_failure::<Fp, _>(
+            &ColumnsCircuit::<8, 3> {
+                values: vec![0, 1, 2, 3, 8],
+            },
+            vec![],
+            FailureMatcher::Constraint {
+                gate: "range check",
+                location: Location::InRegion {
+                    region: "Assign value",
+                    offset: 1,
+                },
+            },
+        );
+    }
+
+    #[cfg(feature = "dev-graph")]
+    #[test]
+    fn print_range_check_2() {
         let circuit = MyCircuit::<Fp, 8> {
             value: Value::unknown(),
         };
-        halo2_proofs::dev::CircuitLayout::default()
-            .render(3, &circuit, &root)
-            .unwrap();
+        crate::dev::layout::render_layout("range-check-2", 3, &circuit).unwrap();
     }
 }
\ No newline at end of file
@@ -0,0 +1,242 @@
+//! `range_check_2` with the range polynomial in the gate.
+//!
+//! `range_check_2` computes `v ⋅ (1 - v) ⋅ .. ⋅ (RANGE - 1 - v)` in the
+//! witness and only constrains the assigned product to be zero, so the value
+//! itself never appears in the circuit. Here the cell holds the value and the
+//! gate constrains `q ⋅ Π (v - i)` over `i` in `[0, RANGE)`, of degree
+//! `RANGE + 1`. Both are kept to compare them.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Assigned, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::field::Field;
+
+#[derive(Debug, Clone)]
+/// A range-constrained value in the circuit produced by the RangeCheckConfig.
+struct RangeConstrained<F: Field, const RANGE: usize>(AssignedCell<Assigned<F>, F>);
+
+#[derive(Debug, Clone)]
+struct RangeCheckConfig<F: Field, const RANGE: usize, const NUM_COLUMNS: usize = 1> {
+    value: [Column<Advice>; NUM_COLUMNS],
+    q_range_check: Selector,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field, const RANGE: usize> RangeCheckConfig<F, RANGE> {
+    pub fn configure(meta: &mut ConstraintSystem<F>, value: Column<Advice>) -> Self {
+        Self::configure_columns(meta, [value])
+    }
+}
+
+impl<F: Field, const RANGE: usize, const NUM_COLUMNS: usize> RangeCheckConfig<F, RANGE, NUM_COLUMNS> {
+    /// Configures the check over the `value` columns, one value per column
+    /// and row.
+    pub fn configure_columns(meta: &mut ConstraintSystem<F>, value: [Column<Advice>; NUM_COLUMNS]) -> Self {
+        assert!(RANGE > 0 && NUM_COLUMNS > 0);
+        let q_range_check = meta.selector();
+
+        meta.create_gate("range check", |meta| {
+            //     value[0]   |  ...  |  value[NUM_COLUMNS - 1]  |  q_range_check
+            //   -----------------------------------------------------------------
+            //        v0      |  ...  |            vn            |        1
+
+            let q = meta.query_selector(q_range_check);
+
+            // v ⋅ (v - 1) ⋅ .. ⋅ (v - (RANGE - 1))
+            let constraints = value.map(|value| {
+                let value = meta.query_advice(value, Rotation::cur());
+                let range_check = (1..RANGE).fold(value.clone(), |expr, i| {
+                    expr * (value.clone() - Expression::Constant(F::from(i as u64)))
+                });
+                ("range check", range_check)
+            });
+
+            Constraints::with_selector(q, constraints)
+        });
+
+        Self {
+            q_range_check,
+            value,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn assign(
+        &self,
+        layouter: impl Layouter<F>,
+        value: Value<Assigned<F>>,
+    ) -> Result<RangeConstrained<F, RANGE>, Error> {
+        let mut values = self.assign_values(layouter, &[value])?;
+        Ok(values.remove(0))
+    }
+
+    /// Range checks `values`, `NUM_COLUMNS` per row. The last row is padded
+    /// with zeros.
+    pub fn assign_values(
+        &self,
+        mut layouter: impl Layouter<F>,
+        values: &[Value<Assigned<F>>],
+    ) -> Result<Vec<RangeConstrained<F, RANGE>>, Error> {
+        layouter.assign_region(
+            || "Assign value",
+            |mut region| {
+                let num_rows = values.len().div_ceil(NUM_COLUMNS);
+
+                // Enable q_range_check
+                for offset in 0..num_rows {
+                    self.q_range_check.enable(&mut region, offset)?;
+                }
+
+                for i in values.len()..num_rows * NUM_COLUMNS {
+                    let zero = Value::known(Assigned::from(F::ZERO));
+                    region.assign_advice(|| "padding", self.value[i % NUM_COLUMNS], i / NUM_COLUMNS, || zero)?;
+                }
+
+                // Assign values
+                (values.iter().enumerate())
+                    .map(|(i, value)| {
+                        region
+                            .assign_advice(|| "value", self.value[i % NUM_COLUMNS], i / NUM_COLUMNS, || *value)
+                            .map(RangeConstrained)
+                    })
+                    .collect()
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::floor_planner::V1, plonk::Circuit};
+
+    use super::*;
+    use crate::{
+        dev::{
+            expr::dump_gates,
+            mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        },
+        field::TestField as Fp,
+    };
+
+    #[derive(Default)]
+    struct MyCircuit<F: Field, const RANGE: usize> {
+        value: Value<Assigned<F>>,
+    }
+
+    impl<F: Field, const RANGE: usize> Circuit<F> for MyCircuit<F, RANGE> {
+        type Config = RangeCheckConfig<F, RANGE>;
+        type FloorPlanner = V1;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let value = meta.advice_column();
+            RangeCheckConfig::configure(meta, value)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            config.assign(layouter.namespace(|| "Assign value"), self.value)?;
+
+            Ok(())
+        }
+    }
+
+    fn circuit(value: u64) -> MyCircuit<Fp, 8> {
+        MyCircuit {
+            value: Value::known(Fp::from(value).into()),
+        }
+    }
+
+    #[test]
+    fn test_range_check_2b() {
+        for i in 0..8 {
+            expect_satisfied(&circuit(i), vec![]);
+        }
+
+        // Unlike `range_check_2`, the gate catches values out of the range.
+        for i in [8, 9, 100] {
+            expect_failure(
+                &circuit(i),
+                vec![],
+                FailureMatcher::Constraint {
+                    gate: "range check",
+                    location: Location::InRegion {
+                        region: "Assign value",
+                        offset: 0,
+                    },
+                },
+            );
+        }
+    }
+
+    /// Range checks `values` over `COLUMNS` columns.
+    #[derive(Default)]
+    struct ColumnsCircuit<const RANGE: usize, const COLUMNS: usize> {
+        values: Vec<u64>,
+    }
+
+    impl<F: Field, const RANGE: usize, const COLUMNS: usize> Circuit<F> for ColumnsCircuit<RANGE, COLUMNS> {
+        type Config = RangeCheckConfig<F, RANGE, COLUMNS>;
+        type FloorPlanner = V1;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let value = [(); COLUMNS].map(|_| meta.advice_column());
+            RangeCheckConfig::configure_columns(meta, value)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let values: Vec<_> = (self.values.iter())
+                .map(|value| Value::known(Assigned::from(F::from(*value))))
+                .collect();
+            let checked = config.assign_values(layouter.namespace(|| "Assign values"), &values)?;
+            assert_eq!(checked.len(), values.len());
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn range_check_columns() {
+        expect_satisfied::<Fp, _>(&ColumnsCircuit::<8, 3> { values: (0..8).collect() }, vec![]);
+        expect_satisfied::<Fp, _>(&ColumnsCircuit::<8, 8> { values: (0..8).collect() }, vec![]);
+
+        // The fifth value is in the middle column of the second row.
+        expect_failure::<Fp, _>(
+            &ColumnsCircuit::<8, 3> {
+                values: vec![0, 1, 2, 3, 8],
+            },
+            vec![],
+            FailureMatcher::Constraint {
+                gate: "range check",
+                location: Location::InRegion {
+                    region: "Assign value",
+                    offset: 1,
+                },
+            },
+        );
+    }
+
+    #[test]
+    fn gate_degree() {
+        let gates = dump_gates(&MyCircuit::<Fp, 8>::default());
+        assert_eq!(gates[0].constraints[0].degree, 9);
+
+        let gates = dump_gates(&MyCircuit::<Fp, 4>::default());
+        assert_eq!(gates[0].constraints[0].degree, 5);
+    }
+}
@@ -1,13 +1,14 @@
 use std::marker::PhantomData;
 
-use eth_types::Field;
 use halo2_proofs::{plonk::{Column, Advice, Instance, Selector, ConstraintSystem, Fixed}, circuit::Chip, poly::Rotation};
 
+use crate::field::Field;
+
 
 
 /// This chip will implement our instructions! Chips store their own
 /// config, as well as type markers if necessary.
-struct FieldChip<F: Field> {
+pub(crate) struct FieldChip<F: Field> {
     config: FieldConfig,
     _marker: PhantomData<F>,
 }
@@ -19,18 +20,18 @@ pub struct FieldConfig {
     /// For this chip, we will use two advice columns to implement our instructions.
     /// These are also the columns through which we communicate with other parts of
     /// the circuits.
-    advice: [Column<Advice>; 2],
+    pub(crate) advice: [Column<Advice>; 2],
 
     /// This is the public input (instance) column.
-    instance: Column<Instance>,
+    pub(crate) instance: Column<Instance>,
 
-    constant: Column<Fixed>,
+    pub(crate) constant: Column<Fixed>,
 
     /// We need a selector to enable the multiplication gate, so that we aren't placing
     /// any constraints on cells where `NumericInstructions::mul` is not being used.
     /// This is important when building larger circuits, where columns are used by
     /// multiple sets of instructions.
-    s_mul: Selector,
+    pub(crate) s_mul: Selector,
 }
 
 impl<F: Field> Chip<F> for FieldChip<F> {
@@ -47,7 +48,7 @@ impl<F: Field> Chip<F> for FieldChip<F> {
 }
 
 impl<F: Field> FieldChip<F> {
-    fn configure(meta: &mut ConstraintSystem<F>, advice: [Column<Advice>; 2], instance: Column<Instance>, constant: Column<Fixed>,) -> <Self as Chip<F>>::Config {
+    pub(crate) fn configure(meta: &mut ConstraintSystem<F>, advice: [Column<Advice>; 2], instance: Column<Instance>, constant: Column<Fixed>,) -> <Self as Chip<F>>::Config {
         meta.enable_equality(instance);
         meta.enable_constant(constant);
         for column in &advice {
@@ -81,15 +82,14 @@ impl<F: Field> FieldChip<F> {
 mod tests {
     use std::marker::PhantomData;
 
-    use eth_types::Field;
     use halo2_proofs::{
         circuit::{Layouter, SimpleFloorPlanner, Value},
         dev::MockProver,
-        halo2curves::bn256::Fr as Fp,
         plonk::{Circuit, ConstraintSystem, Error},
     };
     
     use super::{FieldConfig, FieldChip};
+    use crate::field::{Field, TestField as Fp};
 
     #[derive(Default)]
     struct TestCircuit<F: Field> {
@@ -1,8 +1,9 @@
 use std::marker::PhantomData;
 
-use eth_types::Field;
 use halo2_proofs::{plonk::{Column, Advice, Instance, Selector, ConstraintSystem, Fixed}, circuit::Chip, poly::Rotation};
 
+use crate::field::Field;
+
 
 
 /// This chip will implement our instructions! Chips store their own
@@ -81,15 +82,14 @@ impl<F: Field> FieldChip<F> {
 mod tests {
     use std::marker::PhantomData;
 
-    use eth_types::Field;
     use halo2_proofs::{
         circuit::{Layouter, SimpleFloorPlanner, Value},
         dev::MockProver,
-        halo2curves::bn256::Fr as Fp,
         plonk::{Circuit, ConstraintSystem, Error},
     };
     
     use super::{FieldConfig, FieldChip};
+    use crate::field::{Field, TestField as Fp};
 
     #[derive(Default)]
     struct TestCircuit<F: Field> {
@@ -0,0 +1,62 @@
+//! Composition of circuits into a super circuit.
+//!
+//! A [`SubCircuit`] configures its gates on columns handed to it instead of
+//! allocating its own, and returns the cells of its public inputs instead of
+//! constraining them to an instance column. A super circuit can then lay out
+//! several sub-circuits on the same [`SharedColumns`], and expose all their
+//! public inputs one after the other in its single instance column.
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, ConstraintSystem, Error, Fixed},
+};
+
+use crate::field::Field;
+
+/// Advice columns available to each sub-circuit.
+pub const SHARED_ADVICE: usize = 5;
+
+/// Columns shared by the sub-circuits of a super circuit.
+#[derive(Clone, Copy, Debug)]
+pub struct SharedColumns {
+    /// Advice columns, with equality enabled.
+    pub advice: [Column<Advice>; SHARED_ADVICE],
+    /// Fixed column enabled for constants.
+    pub constant: Column<Fixed>,
+}
+
+impl SharedColumns {
+    pub fn configure<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
+        let advice = [(); SHARED_ADVICE].map(|_| meta.advice_column());
+        let constant = meta.fixed_column();
+
+        for column in advice {
+            meta.enable_equality(column);
+        }
+        meta.enable_constant(constant);
+
+        Self { advice, constant }
+    }
+}
+
+/// A circuit that can be embedded in a super circuit.
+pub trait SubCircuit<F: Field> {
+    type Config: Clone;
+
+    /// Configures the gates of the sub-circuit on the `shared` columns.
+    fn configure_sub(meta: &mut ConstraintSystem<F>, shared: SharedColumns) -> Self::Config;
+
+    /// Assigns the sub-circuit, returning the cells of its public inputs in
+    /// the order of [`Self::public_inputs`].
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        layouter: impl Layouter<F>,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error>;
+
+    /// Rows the sub-circuit assigns.
+    fn min_rows(&self) -> usize;
+
+    /// Values of the public inputs.
+    fn public_inputs(&self) -> Vec<F>;
+}
@@ -0,0 +1,35 @@
+//! Fixed lookup tables shared by the chips.
+
+use halo2_proofs::{
+    circuit::{Layouter, Value},
+    plonk::{ConstraintSystem, Error, TableColumn},
+};
+
+use crate::field::Field;
+
+/// Table of the values `0..256`.
+#[derive(Clone, Copy, Debug)]
+pub struct U8Table {
+    pub value: TableColumn,
+}
+
+impl U8Table {
+    pub fn configure<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            value: meta.lookup_table_column(),
+        }
+    }
+
+    /// Fills the table, once per circuit.
+    pub fn load<F: Field>(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "u8 table",
+            |mut table| {
+                for i in 0..256 {
+                    table.assign_cell(|| "value", self.value, i, || Value::known(F::from(i as u64)))?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
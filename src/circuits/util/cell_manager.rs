@@ -0,0 +1,250 @@
+//! Allocation of cells from a pool of advice columns.
+//!
+//! Instead of picking a column and an offset for every value by hand, a
+//! gadget asks a [`CellManager`] for cells while configuring its gates. Each
+//! cell is placed in the least filled column of the pool, at a row relative to
+//! the start of the step it belongs to:
+//!
+//! | columns[0] | columns[1] | columns[2] |
+//! | a          | b          | c          |  row 0
+//! | d          | e          |            |  row 1
+//!
+//! The cells of a step span [`CellManager::height`] rows, so steps repeated
+//! down a region are assigned at offsets that are multiples of it, and
+//! [`Cell::assign`] adds the row of the cell to the offset of its step.
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Region, Value},
+    plonk::{Advice, Column, Error, Expression, VirtualCells},
+    poly::Rotation,
+};
+
+use crate::field::Field;
+
+/// A cell handed out by a [`CellManager`].
+#[derive(Clone, Debug)]
+pub struct Cell<F: Field> {
+    expression: Expression<F>,
+    column: Column<Advice>,
+    row: usize,
+}
+
+impl<F: Field> Cell<F> {
+    /// The cell queried at its row, relative to the first row of its step.
+    pub fn expr(&self) -> Expression<F> {
+        self.expression.clone()
+    }
+
+    pub fn column(&self) -> Column<Advice> {
+        self.column
+    }
+
+    /// Row of the cell in its step.
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    /// The cell of the next step, `height` rows below, for transitions
+    /// between steps.
+    pub fn next(&self, meta: &mut VirtualCells<'_, F>, height: usize) -> Expression<F> {
+        meta.query_advice(self.column, Rotation((self.row + height) as i32))
+    }
+
+    /// Assigns `value` to the cell of the step starting at `offset`.
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        value: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let row = offset + self.row;
+        region.assign_advice(|| format!("cell {:?} {}", self.column, self.row), self.column, row, || value)
+    }
+
+    /// Copies `cell` to the cell of the step starting at `offset`.
+    pub fn copy(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        cell: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let row = offset + self.row;
+        cell.copy_advice(|| format!("cell {:?} {}", self.column, self.row), region, self.column, row)
+    }
+}
+
+/// Hands out cells from `columns`, up to `max_height` rows of each.
+#[derive(Clone, Debug)]
+pub struct CellManager {
+    columns: Vec<Column<Advice>>,
+    heights: Vec<usize>,
+    max_height: usize,
+}
+
+impl CellManager {
+    pub fn new(columns: &[Column<Advice>], max_height: usize) -> Self {
+        assert!(!columns.is_empty(), "a cell manager needs at least one column");
+        Self {
+            columns: columns.to_vec(),
+            heights: vec![0; columns.len()],
+            max_height,
+        }
+    }
+
+    /// Allocates a cell in the least filled column, the first one on ties.
+    ///
+    /// Panics if all the columns are `max_height` rows high.
+    pub fn query_cell<F: Field>(&mut self, meta: &mut VirtualCells<'_, F>) -> Cell<F> {
+        let (index, row) = (self.heights.iter().copied().enumerate())
+            .min_by_key(|(_, height)| *height)
+            .unwrap();
+        assert!(row < self.max_height, "cell manager is full at {} rows", self.max_height);
+        self.heights[index] += 1;
+
+        let column = self.columns[index];
+        Cell {
+            expression: meta.query_advice(column, Rotation(row as i32)),
+            column,
+            row,
+        }
+    }
+
+    /// Allocates `N` cells, see [`Self::query_cell`].
+    pub fn query_cells<F: Field, const N: usize>(&mut self, meta: &mut VirtualCells<'_, F>) -> [Cell<F>; N] {
+        [(); N].map(|_| self.query_cell(meta))
+    }
+
+    /// Rows spanned by the cells allocated so far.
+    pub fn height(&self) -> usize {
+        self.heights.iter().copied().max().unwrap()
+    }
+
+    pub fn columns(&self) -> &[Column<Advice>] {
+        &self.columns
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Circuit, Column, ConstraintSystem, Error, Instance, Selector},
+    };
+
+    use super::{Cell, CellManager};
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::TestField as Fp,
+    };
+
+    #[test]
+    fn cells_fill_least_filled_column() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let columns = [meta.advice_column(), meta.advice_column()];
+        let mut cell_manager = CellManager::new(&columns, 2);
+
+        meta.create_gate("cells", |meta| {
+            let cells: [Cell<Fp>; 3] = cell_manager.query_cells(meta);
+            let layout: Vec<_> = cells.iter().map(|cell| (cell.column(), cell.row())).collect();
+            assert_eq!(layout, [(columns[0], 0), (columns[1], 0), (columns[0], 1)]);
+            vec![cells[0].expr()]
+        });
+        assert_eq!(cell_manager.height(), 2);
+    }
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig {
+        q_step: Selector,
+        height: usize,
+        cells: [Cell<Fp>; 3],
+        instance: Column<Instance>,
+    }
+
+    /// Proves `a ⋅ b = c` for each `(a, b)` step, one step every `height`
+    /// rows, and exposes the products.
+    #[derive(Default)]
+    struct TestCircuit {
+        steps: Vec<Value<(u64, u64)>>,
+    }
+
+    impl Circuit<Fp> for TestCircuit {
+        type Config = TestCircuitConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                steps: vec![Value::unknown(); self.steps.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let q_step = meta.selector();
+            let columns = [meta.advice_column(), meta.advice_column()];
+            let instance = meta.instance_column();
+            for column in columns {
+                meta.enable_equality(column);
+            }
+            meta.enable_equality(instance);
+
+            let mut cell_manager = CellManager::new(&columns, 2);
+            let mut cells = None;
+            meta.create_gate("product", |meta| {
+                let q_step = meta.query_selector(q_step);
+                let [a, b, c] = cells.insert(cell_manager.query_cells(meta)).clone();
+                vec![q_step * (a.expr() * b.expr() - c.expr())]
+            });
+
+            TestCircuitConfig {
+                q_step,
+                height: cell_manager.height(),
+                cells: cells.unwrap(),
+                instance,
+            }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let products = layouter.assign_region(
+                || "steps",
+                |mut region| {
+                    let mut products = vec![];
+                    for (i, step) in self.steps.iter().enumerate() {
+                        let offset = i * config.height;
+                        config.q_step.enable(&mut region, offset)?;
+                        let [a, b, c] = &config.cells;
+                        a.assign(&mut region, offset, step.map(|(a, _)| Fp::from(a)))?;
+                        b.assign(&mut region, offset, step.map(|(_, b)| Fp::from(b)))?;
+                        products.push(c.assign(&mut region, offset, step.map(|(a, b)| Fp::from(a * b)))?);
+                    }
+                    Ok(products)
+                },
+            )?;
+
+            for (i, product) in products.iter().enumerate() {
+                layouter.constrain_instance(product.cell(), config.instance, i)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn steps() {
+        let circuit = TestCircuit {
+            steps: vec![Value::known((2, 3)), Value::known((4, 5)), Value::known((6, 7))],
+        };
+        expect_satisfied(&circuit, vec![vec![Fp::from(6), Fp::from(20), Fp::from(42)]]);
+
+        // The product of the second step is in its second row.
+        expect_failure(
+            &circuit,
+            vec![vec![Fp::from(6), Fp::from(21), Fp::from(42)]],
+            FailureMatcher::Permutation {
+                location: Location::InRegion {
+                    region: "steps",
+                    offset: 3,
+                },
+            },
+        );
+    }
+}
@@ -0,0 +1,166 @@
+//! Gate definitions as a list of named requirements.
+//!
+//! Instead of multiplying every constraint of a gate by its selector and
+//! conditions by hand, the constraints are collected in a
+//! [`ConstraintBuilder`] inside the gate's closure:
+//!
+//! ```ignore
+//! meta.create_gate("memory", |meta| {
+//!     let mut cb = ConstraintBuilder::default();
+//!     cb.require_boolean("is_write", is_write.clone());
+//!     cb.condition(is_read, |cb| cb.require_equal("read value", value_next, value));
+//!     cb.gate(meta.query_selector(q_step))
+//! });
+//! ```
+//!
+//! Each requirement is multiplied by the conditions it was added under, and
+//! [`ConstraintBuilder::gate`] multiplies all of them by the selector. The
+//! names show up in the failures of the mock prover.
+
+use halo2_proofs::plonk::Expression;
+
+use crate::field::Field;
+
+/// Collects the named constraints of a gate.
+#[derive(Clone, Debug)]
+pub struct ConstraintBuilder<F: Field> {
+    constraints: Vec<(&'static str, Expression<F>)>,
+    conditions: Vec<Expression<F>>,
+}
+
+impl<F: Field> Default for ConstraintBuilder<F> {
+    fn default() -> Self {
+        Self {
+            constraints: vec![],
+            conditions: vec![],
+        }
+    }
+}
+
+impl<F: Field> ConstraintBuilder<F> {
+    /// Requires `expr` to be zero.
+    pub fn require_zero(&mut self, name: &'static str, expr: Expression<F>) {
+        let expr = match self.conditions.iter().cloned().reduce(|acc, condition| acc * condition) {
+            Some(condition) => condition * expr,
+            None => expr,
+        };
+        self.constraints.push((name, expr));
+    }
+
+    /// Requires `lhs` and `rhs` to be equal.
+    pub fn require_equal(&mut self, name: &'static str, lhs: Expression<F>, rhs: Expression<F>) {
+        self.require_zero(name, lhs - rhs);
+    }
+
+    /// Requires `expr` to be 0 or 1.
+    pub fn require_boolean(&mut self, name: &'static str, expr: Expression<F>) {
+        self.require_zero(name, expr.clone() * (Expression::Constant(F::ONE) - expr));
+    }
+
+    /// Adds the requirements of `f` only where `condition` is non-zero. Nested
+    /// conditions multiply.
+    pub fn condition<R>(&mut self, condition: Expression<F>, f: impl FnOnce(&mut Self) -> R) -> R {
+        self.conditions.push(condition);
+        let ret = f(self);
+        self.conditions.pop();
+        ret
+    }
+
+    /// The constraints, to be returned from a gate's closure.
+    pub fn build(self) -> Vec<(&'static str, Expression<F>)> {
+        self.constraints
+    }
+
+    /// The constraints multiplied by `selector`, to be returned from a gate's
+    /// closure.
+    pub fn gate(self, selector: Expression<F>) -> Vec<(&'static str, Expression<F>)> {
+        (self.constraints.into_iter())
+            .map(|(name, constraint)| (name, selector.clone() * constraint))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
+        poly::Rotation,
+    };
+
+    use super::ConstraintBuilder;
+    use crate::{
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::{Field, TestField as Fp},
+    };
+
+    /// Requires `flag` to be boolean, and `a == b` when it is set.
+    #[derive(Default)]
+    struct TestCircuit {
+        flag: u64,
+        a: u64,
+        b: u64,
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit {
+        type Config = (Selector, [Column<Advice>; 3]);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let q_enable = meta.selector();
+            let columns = [(); 3].map(|_| meta.advice_column());
+
+            meta.create_gate("flagged equality", |meta| {
+                let [flag, a, b] = columns.map(|column| meta.query_advice(column, Rotation::cur()));
+
+                let mut cb = ConstraintBuilder::default();
+                cb.require_boolean("flag", flag.clone());
+                cb.condition(flag, |cb| cb.require_equal("a == b", a, b));
+                cb.gate(meta.query_selector(q_enable))
+            });
+
+            (q_enable, columns)
+        }
+
+        fn synthesize(&self, (q_enable, columns): Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            layouter.assign_region(
+                || "flagged equality",
+                |mut region| {
+                    q_enable.enable(&mut region, 0)?;
+                    for (column, value) in columns.into_iter().zip([self.flag, self.a, self.b]) {
+                        region.assign_advice(|| "value", column, 0, || Value::known(F::from(value)))?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn conditions() {
+        for (flag, a, b) in [(0, 1, 2), (1, 3, 3)] {
+            expect_satisfied::<Fp, _>(&TestCircuit { flag, a, b }, vec![]);
+        }
+
+        let location = Location::InRegion {
+            region: "flagged equality",
+            offset: 0,
+        };
+        for (flag, a, b) in [(1, 1, 2), (2, 3, 3)] {
+            expect_failure::<Fp, _>(
+                &TestCircuit { flag, a, b },
+                vec![],
+                FailureMatcher::Constraint {
+                    gate: "flagged equality",
+                    location,
+                },
+            );
+        }
+    }
+}
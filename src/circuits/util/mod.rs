@@ -0,0 +1,6 @@
+//! Helpers for laying out the cells and gates of larger circuits, and for
+//! computing their witness.
+
+pub mod cell_manager;
+pub mod constraint_builder;
+pub mod parallel;
@@ -0,0 +1,34 @@
+//! Parallel witness generation for gadgets whose rows don't depend on each
+//! other.
+//!
+//! Floor planners may call the closure of a region several times, so gadgets
+//! compute their witness before entering it. When every row of the witness
+//! can be computed on its own, like the running sums of a range check where
+//! `acc[i] = value >> i`, [`par_rows`] spreads the rows over the rayon thread
+//! pool. Short regions stay on the current thread, as splitting them costs
+//! more than it saves.
+
+use rayon::prelude::*;
+
+/// Fewest rows computed by a rayon task.
+pub const MIN_ROWS_PER_TASK: usize = 32;
+
+/// `[row(0), .., row(num_rows - 1)]`, computed in parallel.
+pub fn par_rows<T: Send>(num_rows: usize, row: impl Fn(usize) -> T + Sync + Send) -> Vec<T> {
+    (0..num_rows)
+        .into_par_iter()
+        .with_min_len(MIN_ROWS_PER_TASK)
+        .map(row)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::par_rows;
+
+    #[test]
+    fn rows_in_order() {
+        assert_eq!(par_rows(1000, |i| i * i), (0..1000).map(|i| i * i).collect::<Vec<_>>());
+        assert!(par_rows(0, |i| i).is_empty());
+    }
+}
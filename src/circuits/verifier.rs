@@ -0,0 +1,407 @@
+//! In-circuit verification of halo2 proofs over KZG, with the pairing
+//! deferred.
+//!
+//! A KZG opening is checked by the pairing `e(lhs, [1]) == e(rhs, [tau])`,
+//! which is far too expensive to compute in a bn256 circuit. The *succinct*
+//! Plonk verifier therefore does everything but that check and returns the
+//! pair `(lhs, rhs)`, a KZG accumulator. Several accumulators are folded into
+//! one with a random linear combination, so that a single pairing at the end,
+//! by whoever verifies the outer proof, settles all of them.
+//!
+//! [`VerifierConfig::verify`] runs succinct verification and folding in
+//! circuit, on non-native ECC chips, and returns the folded accumulator as
+//! `4 * LIMBS` limbs of its coordinates, along with the cells of the inner
+//! instances. The outer circuit must expose both: the inner proofs are only
+//! proofs of the instances it makes public. [`accumulate`] does the same
+//! natively, which the prover needs for the folding proof, and [`decide`]
+//! runs the deferred pairing.
+//!
+//! The verified proofs must be created with GWC multi-opening and the
+//! [`PoseidonTranscript`] below, see [`gen_snark`]. The shape of the verified
+//! circuits is fixed when the verifier is configured, through their
+//! [`PlonkProtocol`].
+
+use std::rc::Rc;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Value},
+    halo2curves::bn256::{Bn256, Fq, Fr, G1Affine},
+    plonk::{self, create_proof, Circuit, ConstraintSystem, ProvingKey},
+    poly::{
+        commitment::ParamsProver,
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::ProverGWC,
+        },
+    },
+    transcript::TranscriptWriterBuffer,
+};
+use itertools::Itertools;
+use rand::rngs::OsRng;
+use snark_verifier::{
+    loader::{
+        self,
+        halo2::halo2_wrong_ecc::{
+            self,
+            integer::rns::Rns,
+            maingate::{
+                MainGate, MainGateConfig, MainGateInstructions, RangeChip, RangeConfig, RangeInstructions,
+                RegionCtx,
+            },
+            EccConfig,
+        },
+        native::NativeLoader,
+    },
+    pcs::{
+        kzg::{
+            Gwc19, KzgAccumulator, KzgAs, KzgDecidingKey, KzgSuccinctVerifyingKey, LimbsEncoding,
+            LimbsEncodingInstructions,
+        },
+        AccumulationDecider, AccumulationScheme, AccumulationSchemeProver,
+    },
+    system::{
+        self,
+        halo2::{compile, Config},
+    },
+    util::arithmetic::fe_to_limbs,
+    verifier::{self, plonk::PlonkProtocol, SnarkVerifier},
+};
+
+/// Number of limbs a base field element is split into.
+pub const LIMBS: usize = 4;
+/// Bit size of each limb.
+pub const BITS: usize = 68;
+
+const T: usize = 5;
+const RATE: usize = 4;
+const R_F: usize = 8;
+const R_P: usize = 60;
+
+type As = KzgAs<Bn256, Gwc19>;
+type PlonkSuccinctVerifier = verifier::plonk::PlonkSuccinctVerifier<As, LimbsEncoding<LIMBS, BITS>>;
+pub(crate) type BaseFieldEccChip = halo2_wrong_ecc::BaseFieldEccChip<G1Affine, LIMBS, BITS>;
+type Halo2Loader<'a> = loader::halo2::Halo2Loader<'a, G1Affine, BaseFieldEccChip>;
+
+/// Succinct verifying key, the first point of the SRS.
+pub type Svk = KzgSuccinctVerifyingKey<G1Affine>;
+
+/// KZG accumulator computed natively.
+pub type Accumulator = KzgAccumulator<G1Affine, NativeLoader>;
+
+/// Poseidon transcript shared by the inner provers and the in-circuit verifier.
+pub type PoseidonTranscript<L, S> =
+    system::halo2::transcript::halo2::PoseidonTranscript<G1Affine, L, S, T, RATE, R_F, R_P>;
+
+/// A proof together with what is needed to verify it.
+pub struct Snark {
+    protocol: PlonkProtocol<G1Affine>,
+    instances: Vec<Vec<Fr>>,
+    proof: Vec<u8>,
+}
+
+impl Snark {
+    /// Bundles a compiled protocol with one of its proofs.
+    pub fn new(protocol: PlonkProtocol<G1Affine>, instances: Vec<Vec<Fr>>, proof: Vec<u8>) -> Self {
+        Self {
+            protocol,
+            instances,
+            proof,
+        }
+    }
+
+    /// The instances the proof is of, per instance column.
+    pub fn instances(&self) -> &[Vec<Fr>] {
+        &self.instances
+    }
+}
+
+/// Creates a proof of `circuit` that [`VerifierConfig::verify`] can verify.
+pub fn gen_snark<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: Vec<Vec<Fr>>,
+) -> Result<Snark, plonk::Error> {
+    let protocol = compile(
+        params,
+        pk.get_vk(),
+        Config::kzg().with_num_instance(instances.iter().map(Vec::len).collect()),
+    );
+
+    let proof = {
+        let instances = instances.iter().map(Vec::as_slice).collect_vec();
+        let mut transcript = PoseidonTranscript::<NativeLoader, _>::init(Vec::new());
+        create_proof::<KZGCommitmentScheme<Bn256>, ProverGWC<_>, _, _, _, _>(
+            params,
+            pk,
+            &[circuit],
+            &[&instances],
+            OsRng,
+            &mut transcript,
+        )?;
+        transcript.finalize()
+    };
+
+    Ok(Snark::new(protocol, instances, proof))
+}
+
+/// A [`Snark`] as witnessed by the verifier circuit: the protocol is part of
+/// the circuit, the instances and the proof are not.
+#[derive(Clone)]
+pub struct SnarkWitness {
+    protocol: PlonkProtocol<G1Affine>,
+    instances: Vec<Vec<Value<Fr>>>,
+    proof: Value<Vec<u8>>,
+}
+
+impl From<Snark> for SnarkWitness {
+    fn from(snark: Snark) -> Self {
+        Self {
+            protocol: snark.protocol,
+            instances: snark
+                .instances
+                .into_iter()
+                .map(|instances| instances.into_iter().map(Value::known).collect())
+                .collect(),
+            proof: Value::known(snark.proof),
+        }
+    }
+}
+
+impl SnarkWitness {
+    /// The same protocol, with unknown instances and proof.
+    pub fn without_witnesses(&self) -> Self {
+        SnarkWitness {
+            protocol: self.protocol.clone(),
+            instances: self
+                .instances
+                .iter()
+                .map(|instances| vec![Value::unknown(); instances.len()])
+                .collect(),
+            proof: Value::unknown(),
+        }
+    }
+
+    fn proof(&self) -> Value<&[u8]> {
+        self.proof.as_ref().map(Vec::as_slice)
+    }
+}
+
+/// Runs succinct verification of `snarks` natively and folds their
+/// accumulators, returning the folded accumulator and the folding proof that
+/// [`VerifierConfig::verify`] witnesses.
+///
+/// # Panics
+///
+/// If a proof does not verify.
+pub fn accumulate(svk: &Svk, snarks: &[Snark]) -> (Accumulator, Vec<u8>) {
+    let accumulators = snarks
+        .iter()
+        .flat_map(|snark| {
+            let mut transcript = PoseidonTranscript::<NativeLoader, _>::new(snark.proof.as_slice());
+            let proof =
+                PlonkSuccinctVerifier::read_proof(svk, &snark.protocol, &snark.instances, &mut transcript).unwrap();
+            PlonkSuccinctVerifier::verify(svk, &snark.protocol, &snark.instances, &proof).unwrap()
+        })
+        .collect_vec();
+
+    let mut transcript = PoseidonTranscript::<NativeLoader, _>::new(Vec::new());
+    let accumulator = As::create_proof(&Default::default(), &accumulators, &mut transcript, OsRng).unwrap();
+    (accumulator, transcript.finalize())
+}
+
+/// The coordinates of `accumulator` as the `4 * LIMBS` limbs exposed by the
+/// verifier circuit.
+pub fn accumulator_limbs(accumulator: &Accumulator) -> Vec<Fr> {
+    let KzgAccumulator { lhs, rhs } = accumulator;
+    [lhs, rhs].map(point_limbs).concat()
+}
+
+/// The `2 * LIMBS` limbs of the coordinates of `point`, `x` then `y`, as
+/// assigned by the ECC chip.
+pub fn point_limbs(point: &G1Affine) -> Vec<Fr> {
+    [point.x, point.y].map(fe_to_limbs::<_, _, LIMBS, BITS>).concat()
+}
+
+/// Runs the deferred pairing check `e(lhs, [1]) == e(rhs, [tau])`.
+pub fn decide(params: &ParamsKZG<Bn256>, accumulator: Accumulator) -> bool {
+    let dk: KzgDecidingKey<Bn256> = (params.get_g()[0], params.g2(), params.s_g2()).into();
+    As::decide(&dk, accumulator).is_ok()
+}
+
+/// Verifies `snarks` with the halo2 loader and folds their accumulators,
+/// returning the assigned instances of `snarks`, flattened in order, and the
+/// folded accumulator.
+fn aggregate<'a>(
+    svk: &Svk,
+    loader: &Rc<Halo2Loader<'a>>,
+    snarks: &[SnarkWitness],
+    as_proof: Value<&'_ [u8]>,
+) -> Result<(Vec<AssignedCell<Fr, Fr>>, KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>), plonk::Error> {
+    let mut assigned_instances = Vec::new();
+    let mut accumulators = Vec::new();
+    for snark in snarks {
+        let protocol = snark.protocol.loaded(loader);
+        let instances = snark
+            .instances
+            .iter()
+            .map(|instances| instances.iter().map(|instance| loader.assign_scalar(*instance)).collect_vec())
+            .collect_vec();
+        let mut transcript = PoseidonTranscript::<Rc<Halo2Loader>, _>::new(loader, snark.proof());
+        let proof =
+            PlonkSuccinctVerifier::read_proof(svk, &protocol, &instances, &mut transcript).map_err(synthesis_error)?;
+        let verified = PlonkSuccinctVerifier::verify(svk, &protocol, &instances, &proof).map_err(synthesis_error)?;
+        accumulators.extend(verified);
+        assigned_instances.extend(instances.into_iter().flatten().map(|instance| instance.into_assigned()));
+    }
+
+    let mut transcript = PoseidonTranscript::<Rc<Halo2Loader>, _>::new(loader, as_proof);
+    let proof = As::read_proof(&Default::default(), &accumulators, &mut transcript).map_err(synthesis_error)?;
+    let accumulator = As::verify(&Default::default(), &accumulators, &proof).map_err(synthesis_error)?;
+    Ok((assigned_instances, accumulator))
+}
+
+/// A proof or a folding proof that fails to verify in circuit can't be
+/// synthesized.
+fn synthesis_error(_: snark_verifier::Error) -> plonk::Error {
+    plonk::Error::Synthesis
+}
+
+/// The cells returned by [`VerifierConfig::verify`].
+#[derive(Clone, Debug)]
+pub struct VerifiedSnarks {
+    /// Limbs of the folded accumulator, in the order of [`accumulator_limbs`].
+    pub accumulator: Vec<AssignedCell<Fr, Fr>>,
+    /// Instances of the verified snarks, flattened in order.
+    pub instances: Vec<AssignedCell<Fr, Fr>>,
+}
+
+/// Config of the in-circuit verifier: a main gate and the range chip used by
+/// the non-native ECC chip. Other bn256 G1 circuits, such as
+/// [`crate::circuits::examples::elgamal`], use it for its ECC chip.
+#[derive(Clone, Debug)]
+pub struct VerifierConfig {
+    main_gate_config: MainGateConfig,
+    range_config: RangeConfig,
+}
+
+impl VerifierConfig {
+    /// Configures the main gate and the range chip for base field elements
+    /// split into `LIMBS` limbs of `BITS` bits.
+    pub fn configure(meta: &mut ConstraintSystem<Fr>) -> Self {
+        let main_gate_config = MainGate::<Fr>::configure(meta);
+        let range_config = RangeChip::<Fr>::configure(
+            meta,
+            &main_gate_config,
+            vec![BITS / LIMBS],
+            Rns::<Fq, Fr, LIMBS, BITS>::construct().overflow_lengths(),
+        );
+
+        Self {
+            main_gate_config,
+            range_config,
+        }
+    }
+
+    pub(crate) fn main_gate(&self) -> MainGate<Fr> {
+        MainGate::new(self.main_gate_config.clone())
+    }
+
+    fn range_chip(&self) -> RangeChip<Fr> {
+        RangeChip::new(self.range_config.clone())
+    }
+
+    pub(crate) fn ecc_chip(&self) -> BaseFieldEccChip {
+        BaseFieldEccChip::new(EccConfig::new(self.range_config.clone(), self.main_gate_config.clone()))
+    }
+
+    /// Fills the range table, once per circuit.
+    pub fn load(&self, layouter: &mut impl Layouter<Fr>) -> Result<(), plonk::Error> {
+        self.range_chip().load_table(layouter)
+    }
+
+    /// Verifies `snarks` and folds their accumulators with the folding proof
+    /// `as_proof` of [`accumulate`], returning the limbs of the folded
+    /// accumulator and the instances of `snarks`.
+    ///
+    /// Fails with [`plonk::Error::Synthesis`] if a proof does not verify.
+    pub fn verify(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        svk: &Svk,
+        snarks: &[SnarkWitness],
+        as_proof: Value<&[u8]>,
+    ) -> Result<VerifiedSnarks, plonk::Error> {
+        layouter.assign_region(
+            || "aggregate",
+            |region| {
+                let ctx = RegionCtx::new(region, 0);
+                let loader = Halo2Loader::new(self.ecc_chip(), ctx);
+                let (instances, accumulator) = aggregate(svk, &loader, snarks, as_proof)?;
+
+                let limbs = [accumulator.lhs, accumulator.rhs]
+                    .iter()
+                    .map(|ec_point| {
+                        loader
+                            .ecc_chip()
+                            .assign_ec_point_to_limbs(&mut loader.ctx_mut(), ec_point.assigned())
+                    })
+                    .collect::<Result<Vec<_>, plonk::Error>>()?;
+
+                Ok(VerifiedSnarks {
+                    accumulator: limbs.into_iter().flatten().collect(),
+                    instances,
+                })
+            },
+        )
+    }
+
+    /// Constrains `cell` to the `row`th instance of the main gate's instance
+    /// column.
+    pub fn expose_public(
+        &self,
+        layouter: impl Layouter<Fr>,
+        cell: AssignedCell<Fr, Fr>,
+        row: usize,
+    ) -> Result<(), plonk::Error> {
+        self.main_gate().expose_public(layouter, cell, row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::Value,
+        halo2curves::bn256::{Bn256, Fr},
+        poly::{commitment::ParamsProver, kzg::commitment::ParamsKZG},
+    };
+    use rand::rngs::OsRng;
+
+    use super::{accumulate, accumulator_limbs, decide, gen_snark, LIMBS};
+    use crate::prover::{keygen, tests::TestCircuit};
+
+    #[test]
+    fn deferred_pairing() {
+        let params = ParamsKZG::<Bn256>::setup(4, OsRng);
+        let pk = keygen(&params, &TestCircuit::default()).unwrap();
+
+        let snarks = [(3, 5), (7, 11)].map(|(a, b)| {
+            let circuit = TestCircuit {
+                a: Value::known(Fr::from(a)),
+                b: Value::known(Fr::from(b)),
+            };
+            gen_snark(&params, &pk, circuit, vec![vec![Fr::from(a * b)]]).unwrap()
+        });
+
+        let svk = params.get_g()[0].into();
+        let (accumulator, _) = accumulate(&svk, &snarks);
+        assert_eq!(accumulator_limbs(&accumulator).len(), 4 * LIMBS);
+        assert!(decide(&params, accumulator));
+
+        // An accumulator that was not produced by verification fails the
+        // pairing.
+        let (mut accumulator, _) = accumulate(&svk, &snarks[..1]);
+        accumulator.rhs = params.get_g()[1];
+        assert!(!decide(&params, accumulator));
+    }
+}
@@ -0,0 +1,435 @@
+//! 256-bit words.
+//!
+//! A [`WordLimbs<T, N>`] splits a word into `N` little-endian limbs of
+//! `256 / N` bits each, where `T` is whatever represents a limb: a `u8`, a
+//! field element, a [`Value`], an [`Expression`] or an [`AssignedCell`].
+//! [`Word32`] holds the bytes of a word, and [`Word`] the `lo` and `hi`
+//! 128-bit halves, which is the narrowest split whose limbs fit in a field
+//! element.
+//!
+//! [`WordConfig`] assembles a word from byte cells:
+//!
+//! | byte     | lo | hi | q_word | q_byte |
+//! | bytes[0] | lo | hi | 1      | 1      |
+//! | bytes[1] |    |    | 0      | 1      |
+//! | ...      |    |    | 0      | 1      |
+//! | bytes[31]|    |    | 0      | 1      |
+//!
+//! With `NUM_COLUMNS` byte columns, the bytes are laid out row by row, so a
+//! word takes `32 / NUM_COLUMNS` rows, and each byte column has its own
+//! lookup. The last row is padded with zero bytes:
+//!
+//! | byte[0]   | byte[1]   | byte[2]   | lo | hi | q_word | q_byte |
+//! | bytes[0]  | bytes[1]  | bytes[2]  | lo | hi | 1      | 1      |
+//! | ...       | ...       | ...       |    |    | 0      | 1      |
+//! | bytes[30] | bytes[31] | 0         |    |    | 0      | 1      |
+
+use std::array;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::{circuits::table::U8Table, field::Field};
+
+/// A 256-bit word as `N` little-endian limbs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WordLimbs<T, const N: usize> {
+    pub limbs: [T; N],
+}
+
+/// A word as 32 little-endian bytes.
+pub type Word32<T> = WordLimbs<T, 32>;
+
+/// A word as its 64-bit limbs.
+pub type WordU64<T> = WordLimbs<T, 4>;
+
+/// A word as its `lo` and `hi` 128-bit halves.
+pub type Word<T> = WordLimbs<T, 2>;
+
+impl<T, const N: usize> WordLimbs<T, N> {
+    /// Bits per limb.
+    pub const LIMB_BITS: usize = 256 / N;
+
+    pub fn new(limbs: [T; N]) -> Self {
+        Self { limbs }
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> WordLimbs<U, N> {
+        WordLimbs::new(self.limbs.map(f))
+    }
+}
+
+impl<T: Clone> Word<T> {
+    pub fn lo(&self) -> T {
+        self.limbs[0].clone()
+    }
+
+    pub fn hi(&self) -> T {
+        self.limbs[1].clone()
+    }
+}
+
+/// Little-endian `bytes`, at most 31 of them, as a field element.
+pub(crate) fn field_from_le_bytes<F: Field>(bytes: &[u8]) -> F {
+    assert!(bytes.len() < 32, "{} bytes may not fit in a field element, at most 31", bytes.len());
+    let mut repr = [0u8; 32];
+    repr[..bytes.len()].copy_from_slice(bytes);
+    F::from_repr(repr).unwrap()
+}
+
+impl<F: Field, const N: usize> WordLimbs<F, N> {
+    /// Splits the little-endian `bytes` of a word into limbs. The word needs
+    /// at least 2 limbs, as 256 bits may not fit in a field element.
+    pub fn from_le_bytes(bytes: [u8; 32]) -> Self {
+        assert!(N >= 2 && 32 % N == 0, "a word splits into 2, 4, 8, 16 or 32 field limbs, not {N}");
+        let limb_bytes = 32 / N;
+        Self::new(array::from_fn(|i| {
+            field_from_le_bytes(&bytes[i * limb_bytes..(i + 1) * limb_bytes])
+        }))
+    }
+}
+
+impl<F: Field, const N: usize> WordLimbs<Expression<F>, N> {
+    /// Constraints that `self` and `other` are equal, one per limb.
+    pub fn eq_constraints(&self, other: &Self) -> Vec<Expression<F>> {
+        self.limbs.iter().zip(&other.limbs).map(|(a, b)| a.clone() - b.clone()).collect()
+    }
+
+    /// Random linear combination `Σ limbs[i] ⋅ randomness^i`.
+    pub fn rlc(&self, randomness: Expression<F>) -> Expression<F> {
+        self.limbs
+            .iter()
+            .rev()
+            .fold(Expression::Constant(F::ZERO), |acc, limb| acc * randomness.clone() + limb.clone())
+    }
+
+    /// Recomposes the limbs into `M` wider limbs, e.g. bytes into `lo`/`hi`.
+    pub fn to_limbs<const M: usize>(&self) -> WordLimbs<Expression<F>, M> {
+        assert!(M <= N && N % M == 0);
+        let ratio = N / M;
+        WordLimbs::new(array::from_fn(|i| {
+            self.limbs[i * ratio..(i + 1) * ratio]
+                .iter()
+                .enumerate()
+                .fold(Expression::Constant(F::ZERO), |acc, (j, limb)| {
+                    let shift = F::from(2).pow_vartime([(Self::LIMB_BITS * j) as u64]);
+                    acc + limb.clone() * Expression::Constant(shift)
+                })
+        }))
+    }
+}
+
+impl<F: Field, const N: usize> WordLimbs<Value<F>, N> {
+    /// Random linear combination `Σ limbs[i] ⋅ randomness^i`.
+    pub fn rlc(&self, randomness: Value<F>) -> Value<F> {
+        self.limbs
+            .iter()
+            .rev()
+            .fold(Value::known(F::ZERO), |acc, limb| acc * randomness + *limb)
+    }
+}
+
+impl<F: Field, const N: usize> WordLimbs<AssignedCell<F, F>, N> {
+    /// Copy-constrains `self` and `other` limb by limb.
+    pub fn constrain_equal(&self, region: &mut Region<'_, F>, other: &Self) -> Result<(), Error> {
+        for (a, b) in self.limbs.iter().zip(&other.limbs) {
+            region.constrain_equal(a.cell(), b.cell())?;
+        }
+        Ok(())
+    }
+
+    pub fn value(&self) -> WordLimbs<Value<F>, N> {
+        WordLimbs::new(array::from_fn(|i| self.limbs[i].value().copied()))
+    }
+}
+
+/// Cells of a word assigned by [`WordConfig`].
+#[derive(Clone, Debug)]
+pub struct WordCells<F: Field> {
+    pub bytes: Word32<AssignedCell<F, F>>,
+    pub word: Word<AssignedCell<F, F>>,
+}
+
+impl<F: Field> WordCells<F> {
+    /// The little-endian bytes of the word.
+    pub fn bytes_value(&self) -> Value<[u8; 32]> {
+        let bytes: Value<Vec<u8>> = self
+            .bytes
+            .limbs
+            .iter()
+            .map(|cell| cell.value().map(|byte| byte.to_repr()[0]))
+            .collect();
+        bytes.map(|bytes| bytes.try_into().unwrap())
+    }
+}
+
+/// Assembles words from range checked byte cells, over `NUM_COLUMNS` byte
+/// columns.
+#[derive(Clone, Debug)]
+pub struct WordConfig<const NUM_COLUMNS: usize = 1> {
+    q_word: Selector,
+    q_byte: Selector,
+    byte: [Column<Advice>; NUM_COLUMNS],
+    lo: Column<Advice>,
+    hi: Column<Advice>,
+}
+
+impl WordConfig {
+    pub fn configure<F: Field>(
+        meta: &mut ConstraintSystem<F>,
+        byte: Column<Advice>,
+        lo: Column<Advice>,
+        hi: Column<Advice>,
+        u8_table: U8Table,
+    ) -> Self {
+        Self::configure_columns(meta, [byte], lo, hi, u8_table)
+    }
+}
+
+impl<const NUM_COLUMNS: usize> WordConfig<NUM_COLUMNS> {
+    /// Rows taken by a word.
+    pub const NUM_ROWS: usize = 32usize.div_ceil(NUM_COLUMNS);
+
+    /// Configures the word with its bytes in the `byte` columns.
+    pub fn configure_columns<F: Field>(
+        meta: &mut ConstraintSystem<F>,
+        byte: [Column<Advice>; NUM_COLUMNS],
+        lo: Column<Advice>,
+        hi: Column<Advice>,
+        u8_table: U8Table,
+    ) -> Self {
+        assert!(NUM_COLUMNS > 0);
+        let q_word = meta.selector();
+        let q_byte = meta.complex_selector();
+
+        for column in byte.into_iter().chain([lo, hi]) {
+            meta.enable_equality(column);
+        }
+
+        for column in byte {
+            meta.lookup("word byte", |meta| {
+                let q_byte = meta.query_selector(q_byte);
+                let byte = meta.query_advice(column, Rotation::cur());
+                vec![(q_byte * byte, u8_table.value)]
+            });
+        }
+
+        meta.create_gate("word from bytes", |meta| {
+            let q_word = meta.query_selector(q_word);
+            let bytes = Word32::new(array::from_fn(|i| {
+                let (row, column) = Self::position(i);
+                meta.query_advice(byte[column], Rotation(row as i32))
+            }));
+            let word = Word::new([lo, hi].map(|column| meta.query_advice(column, Rotation::cur())));
+
+            bytes
+                .to_limbs::<2>()
+                .eq_constraints(&word)
+                .into_iter()
+                .map(|constraint| q_word.clone() * constraint)
+                .collect::<Vec<_>>()
+        });
+
+        Self {
+            q_word,
+            q_byte,
+            byte,
+            lo,
+            hi,
+        }
+    }
+
+    /// The row and byte column of byte `i`.
+    fn position(i: usize) -> (usize, usize) {
+        (i / NUM_COLUMNS, i % NUM_COLUMNS)
+    }
+
+    /// Assigns the little-endian `bytes` of a word, returning the byte cells
+    /// and the `lo`/`hi` cells.
+    pub fn assign<F: Field>(
+        &self,
+        mut layouter: impl Layouter<F>,
+        bytes: Value<[u8; 32]>,
+    ) -> Result<WordCells<F>, Error> {
+        let byte_values: [_; 32] = array::from_fn(|i| bytes.map(|bytes| F::from(bytes[i] as u64)));
+        let word = bytes.map(Word::<F>::from_le_bytes);
+        layouter.assign_region(
+            || "word from bytes",
+            |mut region| {
+                self.q_word.enable(&mut region, 0)?;
+                for row in 0..Self::NUM_ROWS {
+                    self.q_byte.enable(&mut region, row)?;
+                }
+
+                let mut byte_cells = vec![];
+                for (i, byte) in byte_values.iter().enumerate() {
+                    let (row, column) = Self::position(i);
+                    byte_cells.push(region.assign_advice(|| format!("byte {i}"), self.byte[column], row, || *byte)?);
+                }
+                for i in 32..Self::NUM_ROWS * NUM_COLUMNS {
+                    let (row, column) = Self::position(i);
+                    region.assign_advice(|| "padding", self.byte[column], row, || Value::known(F::ZERO))?;
+                }
+
+                let lo = region.assign_advice(|| "lo", self.lo, 0, || word.map(|word| word.lo()))?;
+                let hi = region.assign_advice(|| "hi", self.hi, 0, || word.map(|word| word.hi()))?;
+
+                Ok(WordCells {
+                    bytes: Word32::new(byte_cells.try_into().unwrap()),
+                    word: Word::new([lo, hi]),
+                })
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        halo2curves::group::ff::{Field as _, PrimeField},
+        plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+
+    use super::{field_from_le_bytes, Word, Word32, WordConfig, WordLimbs};
+    use crate::{
+        circuits::table::U8Table,
+        dev::mock::{expect_failure, expect_satisfied, FailureMatcher, Location},
+        field::{Field, TestField as Fp},
+    };
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig<const COLUMNS: usize> {
+        word: WordConfig<COLUMNS>,
+        u8_table: U8Table,
+        instance: Column<Instance>,
+    }
+
+    /// Assigns the same word twice, constrains both copies equal and exposes
+    /// `lo`, `hi`.
+    #[derive(Default)]
+    struct TestCircuit<const COLUMNS: usize = 1> {
+        bytes: Value<[u8; 32]>,
+    }
+
+    impl<F: Field, const COLUMNS: usize> Circuit<F> for TestCircuit<COLUMNS> {
+        type Config = TestCircuitConfig<COLUMNS>;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let u8_table = U8Table::configure(meta);
+            let byte = [(); COLUMNS].map(|_| meta.advice_column());
+            let [lo, hi] = [(); 2].map(|_| meta.advice_column());
+            let word = WordConfig::configure_columns(meta, byte, lo, hi, u8_table);
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            TestCircuitConfig {
+                word,
+                u8_table,
+                instance,
+            }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            config.u8_table.load(&mut layouter)?;
+
+            let a = config.word.assign(layouter.namespace(|| "a"), self.bytes)?;
+            let b = config.word.assign(layouter.namespace(|| "b"), self.bytes)?;
+            layouter.assign_region(|| "a == b", |mut region| a.bytes.constrain_equal(&mut region, &b.bytes))?;
+
+            layouter.constrain_instance(a.word.lo().cell(), config.instance, 0)?;
+            layouter.constrain_instance(a.word.hi().cell(), config.instance, 1)
+        }
+    }
+
+    #[test]
+    fn word_from_bytes() {
+        let bytes: [u8; 32] = std::array::from_fn(|i| (i * 7 + 3) as u8);
+        let word = Word::<Fp>::from_le_bytes(bytes);
+        assert_eq!(word.lo(), Fp::from_u128(u128::from_le_bytes(bytes[..16].try_into().unwrap())));
+
+        let circuit = TestCircuit::<1> {
+            bytes: Value::known(bytes),
+        };
+        expect_satisfied(&circuit, vec![vec![word.lo(), word.hi()]]);
+        expect_failure(
+            &circuit,
+            vec![vec![word.hi(), word.lo()]],
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+    }
+
+    #[test]
+    fn word_from_bytes_columns() {
+        let bytes: [u8; 32] = std::array::from_fn(|i| 255 - i as u8);
+        let word = Word::<Fp>::from_le_bytes(bytes);
+        let instances = vec![vec![word.lo(), word.hi()]];
+
+        expect_satisfied(
+            &TestCircuit::<3> {
+                bytes: Value::known(bytes),
+            },
+            instances.clone(),
+        );
+        expect_satisfied(
+            &TestCircuit::<32> {
+                bytes: Value::known(bytes),
+            },
+            instances,
+        );
+    }
+
+    #[test]
+    fn from_le_bytes_edges() {
+        assert_eq!(field_from_le_bytes::<Fp>(&[]), Fp::ZERO);
+        assert_eq!(field_from_le_bytes::<Fp>(&[1]), Fp::ONE);
+        assert_eq!(
+            field_from_le_bytes::<Fp>(&[0xff; 31]),
+            Fp::from(2).pow_vartime([248]) - Fp::ONE
+        );
+
+        let bytes = [0xff; 32];
+        let halves = Word::<Fp>::from_le_bytes(bytes);
+        assert_eq!(halves.lo(), Fp::from_u128(u128::MAX));
+        assert_eq!(halves.hi(), Fp::from_u128(u128::MAX));
+        let limbs = Word32::<Fp>::from_le_bytes(bytes);
+        assert!(limbs.limbs.iter().all(|limb| *limb == Fp::from(0xff)));
+    }
+
+    #[test]
+    #[should_panic(expected = "a word splits into 2, 4, 8, 16 or 32 field limbs, not 1")]
+    fn from_le_bytes_single_limb() {
+        WordLimbs::<Fp, 1>::from_le_bytes([0; 32]);
+    }
+
+    #[test]
+    #[should_panic(expected = "32 bytes may not fit in a field element, at most 31")]
+    fn field_from_32_bytes() {
+        field_from_le_bytes::<Fp>(&[0; 32]);
+    }
+
+    #[test]
+    fn rlc() {
+        let bytes = Word32::<Fp>::from_le_bytes(std::array::from_fn(|i| i as u8));
+        let r = Fp::from(1000);
+        let mut expected = Fp::ZERO;
+        for (i, byte) in bytes.limbs.iter().enumerate() {
+            expected += byte * r.pow_vartime([i as u64]);
+        }
+
+        let rlc = bytes.map(Value::known).rlc(Value::known(r));
+        rlc.assert_if_known(|rlc| *rlc == expected);
+    }
+}
@@ -0,0 +1,237 @@
+//! Pretty-printing of the gates of a circuit.
+//!
+//! The `Debug` output of an [`Expression`] is a nested tree of queries and
+//! boxed operations. [`format_expression`] prints it as a polynomial instead,
+//! with columns named after their kind and index, the way the
+//! [`ConstraintSystem`] numbers them:
+//!
+//! ```text
+//! selector[0] * (advice[0] * advice[0] * advice[1] * advice[1] * fixed[0] - advice[0]@1)
+//! ```
+//!
+//! A query at a rotation other than the current row is suffixed with
+//! `@rotation`. Constants that fit in 64 bits, or whose negation does, are
+//! printed in decimal, others in big-endian hex.
+
+use halo2_proofs::plonk::{Circuit, ConstraintSystem, Expression};
+use serde_json::{json, Value as Json};
+
+use crate::field::Field;
+
+/// A named constraint of a gate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConstraintDump {
+    pub name: String,
+    pub degree: usize,
+    /// The polynomial, see [`format_expression`].
+    pub expression: String,
+}
+
+/// A gate and its constraints.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GateDump {
+    pub name: String,
+    pub constraints: Vec<ConstraintDump>,
+}
+
+impl GateDump {
+    pub fn to_json(&self) -> Json {
+        let constraints: Vec<_> = (self.constraints.iter())
+            .map(|constraint| {
+                json!({
+                    "name": constraint.name,
+                    "degree": constraint.degree,
+                    "expression": constraint.expression,
+                })
+            })
+            .collect();
+        json!({ "name": self.name, "constraints": constraints })
+    }
+}
+
+/// The gates of `circuit`, in the order they were created by `configure`.
+pub fn dump_gates<F: Field, C: Circuit<F>>(circuit: &C) -> Vec<GateDump> {
+    let mut cs = ConstraintSystem::default();
+    super::configure(&mut cs, circuit);
+
+    (cs.gates().iter())
+        .map(|gate| GateDump {
+            name: gate.name().to_string(),
+            constraints: (gate.polynomials().iter().enumerate())
+                .map(|(i, poly)| ConstraintDump {
+                    name: gate.constraint_name(i).to_string(),
+                    degree: poly.degree(),
+                    expression: format_expression(poly),
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+/// Formats `gates` with one line per constraint, under the name of its gate.
+pub fn gates_text(gates: &[GateDump]) -> String {
+    let mut out = String::new();
+    for gate in gates {
+        out += &format!("gate \"{}\"\n", gate.name);
+        for constraint in &gate.constraints {
+            let name = if constraint.name.is_empty() {
+                "-"
+            } else {
+                &constraint.name
+            };
+            out += &format!("  [{name}] (degree {}) {}\n", constraint.degree, constraint.expression);
+        }
+    }
+    out
+}
+
+/// Binding strength of the outermost operation of a formatted expression.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    Sum,
+    Product,
+    Atom,
+}
+
+/// `expr` as a polynomial, see the [module documentation](self).
+pub fn format_expression<F: Field>(expr: &Expression<F>) -> String {
+    format_with_precedence(expr).0
+}
+
+fn format_with_precedence<F: Field>(expr: &Expression<F>) -> (String, Precedence) {
+    // `expr`, in parentheses if it binds looser than `min`.
+    let operand = |expr: &Expression<F>, min: Precedence| {
+        let (s, precedence) = format_with_precedence(expr);
+        if precedence < min {
+            format!("({s})")
+        } else {
+            s
+        }
+    };
+
+    match expr {
+        Expression::Constant(value) => (format_constant(value), Precedence::Atom),
+        Expression::Selector(selector) => (format!("selector[{}]", selector.index()), Precedence::Atom),
+        Expression::Fixed(query) => (
+            format_query("fixed", query.column_index(), query.rotation().0),
+            Precedence::Atom,
+        ),
+        Expression::Advice(query) => (
+            format_query("advice", query.column_index(), query.rotation().0),
+            Precedence::Atom,
+        ),
+        Expression::Instance(query) => (
+            format_query("instance", query.column_index(), query.rotation().0),
+            Precedence::Atom,
+        ),
+        Expression::Challenge(challenge) => (format!("challenge[{}]", challenge.index()), Precedence::Atom),
+        Expression::Negated(inner) => (format!("-{}", operand(inner, Precedence::Product)), Precedence::Product),
+        Expression::Sum(a, b) => match b.as_ref() {
+            Expression::Negated(b) => (
+                format!("{} - {}", operand(a, Precedence::Sum), operand(b, Precedence::Product)),
+                Precedence::Sum,
+            ),
+            _ => (
+                format!("{} + {}", operand(a, Precedence::Sum), operand(b, Precedence::Sum)),
+                Precedence::Sum,
+            ),
+        },
+        Expression::Product(a, b) => (
+            format!(
+                "{} * {}",
+                operand(a, Precedence::Product),
+                operand(b, Precedence::Product)
+            ),
+            Precedence::Product,
+        ),
+        Expression::Scaled(inner, scalar) => (
+            format!("{} * {}", format_constant(scalar), operand(inner, Precedence::Product)),
+            Precedence::Product,
+        ),
+    }
+}
+
+fn format_query(kind: &str, column: usize, rotation: i32) -> String {
+    match rotation {
+        0 => format!("{kind}[{column}]"),
+        rotation => format!("{kind}[{column}]@{rotation}"),
+    }
+}
+
+/// `value` in decimal, or as the negation of a decimal, when it fits in 64
+/// bits, and in big-endian hex otherwise.
+pub(crate) fn format_constant<F: Field>(value: &F) -> String {
+    let small = |value: F| {
+        let repr = value.to_repr();
+        repr[8..]
+            .iter()
+            .all(|byte| *byte == 0)
+            .then(|| u64::from_le_bytes(repr[..8].try_into().unwrap()))
+    };
+
+    match (small(*value), small(-*value)) {
+        (Some(value), _) => value.to_string(),
+        (None, Some(negated)) => format!("-{negated}"),
+        (None, None) => {
+            let mut repr = value.to_repr();
+            repr.reverse();
+            format!("0x{}", hex::encode(repr))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        plonk::{ConstraintSystem, Expression},
+        poly::Rotation,
+    };
+
+    use super::{dump_gates, format_expression, gates_text};
+    use crate::{
+        circuits::{examples::simple::SimpleCircuit, gadgets::bits::BitsConfig},
+        field::TestField as Fp,
+    };
+
+    #[test]
+    fn simple_circuit_gates() {
+        let gates = dump_gates(&SimpleCircuit::<Fp>::default());
+        assert_eq!(gates.len(), 1);
+        assert_eq!(gates[0].name, "mul");
+        assert_eq!(gates[0].constraints[0].degree, 6);
+        assert_eq!(
+            gates[0].constraints[0].expression,
+            "selector[0] * (advice[0] * advice[0] * advice[1] * advice[1] * fixed[0] - advice[0]@1)"
+        );
+
+        assert_eq!(gates_text(&gates).lines().count(), 2);
+        assert_eq!(gates[0].to_json()["constraints"][0]["degree"], 6);
+    }
+
+    #[test]
+    fn expressions() {
+        let mut cs = ConstraintSystem::<Fp>::default();
+        let acc = cs.advice_column();
+        BitsConfig::configure(&mut cs, acc);
+
+        // bit * (1 - bit), with bit = acc - 2 * acc'.
+        assert_eq!(
+            format_expression(&cs.gates()[0].polynomials()[0]),
+            "selector[0] * (advice[0] - 2 * advice[0]@1) * (1 - (advice[0] - 2 * advice[0]@1))"
+        );
+
+        let a = || Expression::<Fp>::Constant(Fp::from(3));
+        assert_eq!(format_expression(&-(a() + a())), "-(3 + 3)");
+        assert_eq!(format_expression(&(a() * Fp::from(5))), "5 * 3");
+        assert_eq!(format_expression(&Expression::Constant(-Fp::from(7))), "-7");
+        assert_eq!(
+            format_expression(&Expression::Constant(Fp::from(u64::MAX) * Fp::from(u64::MAX))).len(),
+            66
+        );
+
+        let mut cs = ConstraintSystem::<Fp>::default();
+        let advice = cs.advice_column();
+        cs.create_gate("rotation", |meta| vec![meta.query_advice(advice, Rotation::prev())]);
+        assert_eq!(format_expression(&cs.gates()[0].polynomials()[0]), "advice[0]@-1");
+    }
+}
@@ -0,0 +1,83 @@
+//! Structural fingerprints of constraint systems, checked against golden
+//! files.
+//!
+//! A [`fingerprint`] is a text rendering of everything `configure` puts in
+//! the [`ConstraintSystem`]: the columns, the gates, the lookups, the columns
+//! of the permutation and the constant columns. Any change to it changes the
+//! verifying key, so the fingerprint of each registered circuit is kept in a
+//! `<name>.cs` [golden] file and the tests fail with a diff when it drifts.
+
+use halo2_proofs::plonk::{Any, Circuit, Column, ConstraintSystem};
+
+use super::{
+    expr::{dump_gates, format_expression, gates_text},
+    golden,
+};
+use crate::field::Field;
+
+/// The fingerprint of the constraint system of `circuit`.
+pub fn fingerprint<F: Field, C: Circuit<F>>(circuit: &C) -> String {
+    let mut cs = ConstraintSystem::default();
+    super::configure(&mut cs, circuit);
+
+    let mut out = format!(
+        "columns: advice {}, fixed {}, instance {}, selectors {}\ndegree: {}\n",
+        cs.num_advice_columns(),
+        cs.num_fixed_columns(),
+        cs.num_instance_columns(),
+        cs.num_selectors(),
+        cs.degree(),
+    );
+    out += &gates_text(&dump_gates(circuit));
+    for (i, lookup) in cs.lookups().iter().enumerate() {
+        out += &format!("lookup {i}\n");
+        for (input, table) in lookup.input_expressions().iter().zip(lookup.table_expressions()) {
+            out += &format!("  {} -> {}\n", format_expression(input), format_expression(table));
+        }
+    }
+    let columns: Vec<_> = cs.permutation().get_columns().iter().map(format_column).collect();
+    out += &format!("permutation: {}\n", columns.join(", "));
+    let constants: Vec<_> = cs
+        .constants()
+        .iter()
+        .map(|column| format_column(&(*column).into()))
+        .collect();
+    out += &format!("constants: {}\n", constants.join(", "));
+    out
+}
+
+fn format_column(column: &Column<Any>) -> String {
+    let kind = match column.column_type() {
+        Any::Advice(_) => "advice",
+        Any::Fixed => "fixed",
+        Any::Instance => "instance",
+    };
+    format!("{kind}[{}]", column.index())
+}
+
+/// Compares the fingerprint of circuit `name` with its golden file, see
+/// [`golden::check`].
+pub fn check_golden(name: &str, fingerprint: &str) -> Result<(), String> {
+    golden::check(&format!("{name}.cs"), fingerprint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_golden, fingerprint};
+    use crate::{circuits::examples::simple::SimpleCircuit, field::TestField as Fp, registry::iter_circuits};
+
+    #[test]
+    fn registered_circuits_match_golden() {
+        let mismatches: Vec<_> = iter_circuits()
+            .filter_map(|entry| check_golden(entry.name, &(entry.without_witnesses)().fingerprint()).err())
+            .collect();
+        assert!(mismatches.is_empty(), "{}", mismatches.join("\n"));
+    }
+
+    #[test]
+    fn simple_circuit_fingerprint() {
+        let fingerprint = fingerprint(&SimpleCircuit::<Fp>::default());
+        assert!(fingerprint.starts_with("columns: advice 2, fixed 1, instance 1, selectors 1\ndegree: "));
+        assert!(fingerprint.contains("gate \"mul\"\n"));
+    }
+}
@@ -0,0 +1,54 @@
+//! Property tests for gadgets, see [`gadget_proptest`].
+
+use halo2_proofs::halo2curves::group::ff::Field;
+use proptest::prelude::*;
+
+use crate::field::TestField as Fp;
+
+/// Number of cases of each property.
+pub(crate) const CASES: u32 = 64;
+
+/// Field elements, biased towards zero and small values so that gadgets
+/// special-casing them are exercised.
+pub(crate) fn field() -> impl Strategy<Value = Fp> {
+    prop_oneof![
+        Just(Fp::ZERO),
+        (0u64..256).prop_map(Fp::from),
+        any::<[u64; 4]>().prop_map(Fp::from_raw),
+    ]
+}
+
+/// Declares property tests asserting that a circuit is satisfied with its
+/// instances exactly when `valid` holds.
+///
+/// ```ignore
+/// gadget_proptest! {
+///     is_equal(a in field(), b in field()) {
+///         circuit: TestCircuit::new(a, b),
+///         instances: vec![],
+///         valid: a == b,
+///     }
+/// }
+/// ```
+macro_rules! gadget_proptest {
+    ($(
+        $name:ident($($arg:ident in $strategy:expr),+ $(,)?) {
+            circuit: $circuit:expr,
+            instances: $instances:expr,
+            valid: $valid:expr $(,)?
+        }
+    )*) => {
+        proptest::proptest! {
+            #![proptest_config(proptest::prelude::ProptestConfig::with_cases($crate::dev::fuzz::CASES))]
+            $(
+                #[test]
+                fn $name($($arg in $strategy),+) {
+                    let circuit = $circuit;
+                    proptest::prop_assert_eq!($crate::dev::mock::is_satisfied(&circuit, $instances), $valid);
+                }
+            )*
+        }
+    };
+}
+
+pub(crate) use gadget_proptest;
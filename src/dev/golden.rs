@@ -0,0 +1,94 @@
+//! Golden files of the dev tools, under [`GOLDEN_DIR`].
+//!
+//! A golden file pins some output derived from a circuit, such as the
+//! [fingerprint](super::fingerprint) of its constraint system or the hash of
+//! its [verifying key](super::vk), and [`check`] fails with a diff when the
+//! output drifts. After an intended change, rerun the tests with
+//! `HALO2_BLESS=1` to rewrite the golden files, and commit them with the
+//! change. A missing golden file, as for a newly registered circuit, is
+//! written on the first run.
+
+use std::{env, fs, io, path::Path};
+
+/// Directory of the golden files, relative to the crate root.
+pub const GOLDEN_DIR: &str = "tests/golden";
+
+/// Variable rewriting the golden files instead of comparing with them.
+pub const BLESS_ENV: &str = "HALO2_BLESS";
+
+/// Writes `contents` to the golden file `file`.
+pub fn bless(file: &str, contents: &str) -> io::Result<()> {
+    fs::create_dir_all(GOLDEN_DIR)?;
+    fs::write(Path::new(GOLDEN_DIR).join(file), contents)
+}
+
+/// Compares `actual` with the golden file `file`, returning the diff on a
+/// mismatch.
+///
+/// The golden file is written instead when it is missing or when
+/// [`BLESS_ENV`] is set.
+pub fn check(file: &str, actual: &str) -> Result<(), String> {
+    let path = Path::new(GOLDEN_DIR).join(file);
+    let io_error = |err: io::Error| format!("{}: {err}", path.display());
+
+    if env::var_os(BLESS_ENV).is_some() || !path.exists() {
+        return bless(file, actual).map_err(io_error);
+    }
+
+    let golden = fs::read_to_string(&path).map_err(io_error)?;
+    if golden == actual {
+        return Ok(());
+    }
+    Err(format!(
+        "{} is out of date, rerun with {BLESS_ENV}=1 if this is intended:\n{}",
+        path.display(),
+        diff(&golden, actual)
+    ))
+}
+
+/// The lines removed from `old` and added in `new`, prefixed with `-` and
+/// `+`, from a longest common subsequence of lines.
+pub fn diff(old: &str, new: &str) -> String {
+    let old: Vec<_> = old.lines().collect();
+    let new: Vec<_> = new.lines().collect();
+
+    // lcs[i][j]: length of the longest common subsequence of old[i..] and new[j..].
+    let mut lcs = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            out += &format!("-{}\n", old[i]);
+            i += 1;
+        } else {
+            out += &format!("+{}\n", new[j]);
+            j += 1;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::diff;
+
+    #[test]
+    fn line_diff() {
+        assert_eq!(diff("a\nb\nc\n", "a\nb\nc\n"), "");
+        assert_eq!(diff("a\nb\nc\n", "a\nx\nc\nd\n"), "-b\n+x\n+d\n");
+        assert_eq!(diff("", "a\n"), "+a\n");
+    }
+}
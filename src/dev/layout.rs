@@ -0,0 +1,61 @@
+//! Layout diagrams of circuits, drawn with halo2's [`CircuitLayout`].
+//!
+//! The output format follows the file extension: `.svg` files are drawn with
+//! the SVG backend and anything else as a bitmap.
+
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use halo2_proofs::{arithmetic::Field, dev::CircuitLayout, plonk::Circuit};
+use plotters::{coord::Shift, prelude::*};
+
+/// Directory [`render_layout`] writes to.
+pub const LAYOUT_DIR: &str = "target/halo2-layouts";
+
+/// Size in pixels of the rendered diagrams.
+const LAYOUT_SIZE: (u32, u32) = (1024, 1536);
+
+/// Renders the layout of `circuit` at `k` to `target/halo2-layouts/<name>.png`,
+/// returning the path of the file.
+pub fn render_layout<F: Field, C: Circuit<F>>(name: &str, k: u32, circuit: &C) -> Result<PathBuf, Box<dyn Error>> {
+    let path = Path::new(LAYOUT_DIR).join(format!("{name}.png"));
+    render_layout_to(&path, name, k, circuit)?;
+    Ok(path)
+}
+
+/// Renders the layout of `circuit` at `k` to `path`, titled `title`.
+pub fn render_layout_to<F: Field, C: Circuit<F>>(
+    path: &Path,
+    title: &str,
+    k: u32,
+    circuit: &C,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    if path.extension().map_or(false, |ext| ext == "svg") {
+        draw(SVGBackend::new(path, LAYOUT_SIZE).into_drawing_area(), title, k, circuit)
+    } else {
+        draw(BitMapBackend::new(path, LAYOUT_SIZE).into_drawing_area(), title, k, circuit)
+    }
+}
+
+fn draw<F: Field, C: Circuit<F>, DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    title: &str,
+    k: u32,
+    circuit: &C,
+) -> Result<(), Box<dyn Error>>
+where
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)?;
+    let root = root.titled(title, ("sans-serif", 60))?;
+    CircuitLayout::default().render(k, circuit, &root)?;
+    root.present()?;
+    Ok(())
+}
@@ -0,0 +1,186 @@
+//! Test helpers around [`MockProver`] that check *which* constraint failed
+//! instead of only whether verification failed.
+//!
+//! ```ignore
+//! expect_failure(
+//!     &circuit,
+//!     vec![],
+//!     FailureMatcher::Constraint {
+//!         gate: "range check",
+//!         location: Location::InRegion { region: "Assign value", offset: 0 },
+//!     },
+//! );
+//! ```
+//!
+//! `k` is the smallest one that fits the rows used by the circuit and its
+//! instances.
+
+use std::fmt::Display;
+
+use halo2_proofs::{
+    dev::{FailureLocation, MockProver, VerifyFailure},
+    plonk::Circuit,
+};
+
+use super::rows::{min_k, rows_used};
+use crate::field::Field;
+
+/// Where a failure is expected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Location {
+    /// At `offset` of the region named `region`.
+    InRegion { region: &'static str, offset: usize },
+    /// At the absolute `row`, outside of any region.
+    OutsideRegion { row: usize },
+}
+
+impl Location {
+    fn matches(&self, location: &FailureLocation) -> bool {
+        match (self, location) {
+            (
+                Location::InRegion { region, offset },
+                FailureLocation::InRegion {
+                    region: failed_region,
+                    offset: failed_offset,
+                },
+            ) => named(failed_region, region) && offset == failed_offset,
+            (Location::OutsideRegion { row }, FailureLocation::OutsideRegion { row: failed_row }) => row == failed_row,
+            _ => false,
+        }
+    }
+}
+
+/// A [`VerifyFailure`] expected by [`expect_failure`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FailureMatcher {
+    /// A constraint of the gate named `gate` is not satisfied.
+    Constraint { gate: &'static str, location: Location },
+    /// An input of the lookup named `name` is not in its table.
+    Lookup { name: &'static str, location: Location },
+    /// A copy constraint does not hold.
+    Permutation { location: Location },
+    /// The gate named `gate`, enabled in `region`, queries an unassigned cell
+    /// at `offset`.
+    CellNotAssigned {
+        gate: &'static str,
+        region: &'static str,
+        offset: usize,
+    },
+}
+
+impl FailureMatcher {
+    /// Whether `failure` is the failure described by `self`.
+    pub fn matches(&self, failure: &VerifyFailure) -> bool {
+        match (self, failure) {
+            (
+                FailureMatcher::Constraint { gate, location },
+                VerifyFailure::ConstraintNotSatisfied {
+                    constraint,
+                    location: failed_location,
+                    ..
+                },
+            ) => named(constraint, gate) && location.matches(failed_location),
+            (
+                FailureMatcher::Lookup { name, location },
+                VerifyFailure::Lookup {
+                    name: failed_name,
+                    location: failed_location,
+                    ..
+                },
+            ) => *failed_name == *name && location.matches(failed_location),
+            (
+                FailureMatcher::Permutation { location },
+                VerifyFailure::Permutation {
+                    location: failed_location,
+                    ..
+                },
+            ) => location.matches(failed_location),
+            (
+                FailureMatcher::CellNotAssigned { gate, region, offset },
+                VerifyFailure::CellNotAssigned {
+                    gate: failed_gate,
+                    region: failed_region,
+                    offset: failed_offset,
+                    ..
+                },
+            ) => named(failed_gate, gate) && named(failed_region, region) && *failed_offset as usize == *offset,
+            _ => false,
+        }
+    }
+}
+
+/// The metadata types of halo2 keep their names private, but display them
+/// as `... ('<name>')`.
+fn named(metadata: impl Display, name: &str) -> bool {
+    metadata.to_string().ends_with(&format!("('{name}')"))
+}
+
+fn mock_prover<F: Field, C: Circuit<F>>(circuit: &C, instances: Vec<Vec<F>>) -> MockProver<F> {
+    let rows = rows_used(circuit)
+        .expect("circuit synthesizes")
+        .max(instances.iter().map(Vec::len).max().unwrap_or(0));
+    let k = min_k(circuit, rows);
+
+    MockProver::run(k, circuit, instances).expect("mock prover runs")
+}
+
+/// Whether `circuit` is satisfied with `instances`.
+pub fn is_satisfied<F: Field, C: Circuit<F>>(circuit: &C, instances: Vec<Vec<F>>) -> bool {
+    mock_prover(circuit, instances).verify().is_ok()
+}
+
+/// Asserts that `circuit` is satisfied with `instances`.
+#[track_caller]
+pub fn expect_satisfied<F: Field, C: Circuit<F>>(circuit: &C, instances: Vec<Vec<F>>) {
+    mock_prover(circuit, instances).assert_satisfied();
+}
+
+/// Asserts that `circuit` is not satisfied with `instances`, and that one of
+/// the failures is `expected`.
+#[track_caller]
+pub fn expect_failure<F: Field, C: Circuit<F>>(circuit: &C, instances: Vec<Vec<F>>, expected: FailureMatcher) {
+    match mock_prover(circuit, instances).verify() {
+        Ok(()) => panic!("circuit is satisfied, expected {expected:?}"),
+        Err(failures) => assert!(
+            failures.iter().any(|failure| expected.matches(failure)),
+            "expected {expected:?}, got:\n{}",
+            failures.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expect_failure, expect_satisfied, FailureMatcher, Location};
+    use crate::circuits::examples::{is_zero::IsZeroCircuit, simple::SimpleCircuit};
+    use crate::field::TestField as Fp;
+
+    #[test]
+    fn simple_circuit_failures() {
+        let circuit = SimpleCircuit::new(Fp::from(2), Fp::from(3), Fp::from(4));
+        expect_satisfied(&circuit, circuit.instances());
+
+        // `out` is copied to the first instance row.
+        expect_failure(
+            &circuit,
+            vec![vec![Fp::from(1)]],
+            FailureMatcher::Permutation {
+                location: Location::OutsideRegion { row: 0 },
+            },
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "expected")]
+    fn wrong_matcher_panics() {
+        let circuit = IsZeroCircuit::new(Fp::from(0));
+        expect_failure(
+            &circuit,
+            vec![vec![Fp::from(0)]],
+            FailureMatcher::Constraint {
+                gate: "missing gate",
+                location: Location::InRegion { region: "is_zero", offset: 0 },
+            },
+        );
+    }
+}
@@ -0,0 +1,35 @@
+//! Development tools for inspecting the example circuits.
+//!
+//! The tools configure circuits with [`configure`], which passes their
+//! `Circuit::Params` with the `circuit-params` feature, like the prover
+//! does.
+
+pub mod expr;
+pub mod fingerprint;
+#[cfg(test)]
+pub(crate) mod fuzz;
+pub mod golden;
+#[cfg(feature = "dev-graph")]
+pub mod layout;
+pub mod mock;
+pub mod planner;
+pub mod rows;
+pub mod stats;
+pub mod vk;
+pub mod witness;
+
+use halo2_proofs::plonk::{Circuit, ConstraintSystem};
+
+use crate::field::Field;
+
+/// Configures `circuit` in `cs`, with its params.
+#[cfg(feature = "circuit-params")]
+pub fn configure<F: Field, C: Circuit<F>>(cs: &mut ConstraintSystem<F>, circuit: &C) -> C::Config {
+    C::configure_with_params(cs, circuit.params())
+}
+
+/// Configures `circuit` in `cs`.
+#[cfg(not(feature = "circuit-params"))]
+pub fn configure<F: Field, C: Circuit<F>>(cs: &mut ConstraintSystem<F>, _circuit: &C) -> C::Config {
+    C::configure(cs)
+}
@@ -0,0 +1,137 @@
+//! Rows used by a circuit under each floor planner.
+//!
+//! The floor planner of a circuit is an associated type of [`Circuit`], so
+//! [`WithFloorPlanner`] wraps a circuit to lay it out with another planner,
+//! forwarding everything else. [`planner_rows`] counts the rows used under
+//! [`SimpleFloorPlanner`], [`V1`] and the [`StackedFloorPlanner`] example,
+//! see its documentation for how they differ.
+//!
+//! The layout doesn't depend on the witness, so the circuits are laid out
+//! without one.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{floor_planner::V1, FloorPlanner, Layouter, SimpleFloorPlanner},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+use super::{rows::rows_used, stats::format_table};
+use crate::{circuits::examples::stacked_planner::StackedFloorPlanner, field::Field};
+
+/// `C` laid out with the floor planner `P`.
+#[derive(Clone, Debug)]
+pub struct WithFloorPlanner<C, P> {
+    circuit: C,
+    _marker: PhantomData<P>,
+}
+
+impl<C, P> WithFloorPlanner<C, P> {
+    pub fn new(circuit: C) -> Self {
+        Self {
+            circuit,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: Field, C: Circuit<F>, P: FloorPlanner> Circuit<F> for WithFloorPlanner<C, P> {
+    type Config = C::Config;
+    type FloorPlanner = P;
+    #[cfg(feature = "circuit-params")]
+    type Params = C::Params;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(self.circuit.without_witnesses())
+    }
+
+    #[cfg(feature = "circuit-params")]
+    fn params(&self) -> Self::Params {
+        self.circuit.params()
+    }
+
+    #[cfg(feature = "circuit-params")]
+    fn configure_with_params(meta: &mut ConstraintSystem<F>, params: Self::Params) -> Self::Config {
+        C::configure_with_params(meta, params)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        C::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        self.circuit.synthesize(config, layouter)
+    }
+}
+
+/// Rows used by a circuit under a floor planner.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlannerRows {
+    pub planner: &'static str,
+    pub rows: usize,
+}
+
+/// The rows used by `circuit` under each floor planner.
+pub fn planner_rows<F: Field, C: Circuit<F>>(circuit: &C) -> Result<Vec<PlannerRows>, Error> {
+    fn measure<F: Field, C: Circuit<F>, P: FloorPlanner>(
+        planner: &'static str,
+        circuit: &C,
+    ) -> Result<PlannerRows, Error> {
+        let rows = rows_used(&WithFloorPlanner::<_, P>::new(circuit.without_witnesses()))?;
+        Ok(PlannerRows { planner, rows })
+    }
+
+    Ok(vec![
+        measure::<F, C, SimpleFloorPlanner>("simple", circuit)?,
+        measure::<F, C, V1>("v1", circuit)?,
+        measure::<F, C, StackedFloorPlanner>("stacked", circuit)?,
+    ])
+}
+
+/// Formats the rows of each named circuit under each planner as a table.
+pub fn planner_table(circuits: &[(&str, Vec<PlannerRows>)]) -> String {
+    let planners = circuits.first().map(|(_, rows)| rows.as_slice()).unwrap_or_default();
+    let header: Vec<&str> = ["circuit"]
+        .into_iter()
+        .chain(planners.iter().map(|rows| rows.planner))
+        .collect();
+    let rows: Vec<Vec<String>> = (circuits.iter())
+        .map(|(name, rows)| {
+            [name.to_string()]
+                .into_iter()
+                .chain(rows.iter().map(|rows| rows.rows.to_string()))
+                .collect()
+        })
+        .collect();
+
+    format_table(&header, &rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{planner_rows, planner_table};
+    use crate::{circuits::examples::simple::SimpleCircuit, field::TestField as Fp, registry::iter_circuits};
+
+    #[test]
+    fn single_region() {
+        // One region, which every planner puts on the first rows.
+        let rows = planner_rows(&SimpleCircuit::new(Fp::from(2), Fp::from(3), Fp::from(4))).unwrap();
+        assert!(rows.iter().all(|rows| rows.rows == 2), "{rows:?}");
+
+        let table = planner_table(&[("simple", rows)]);
+        assert_eq!(table.lines().next(), Some("| circuit | simple | v1 | stacked |"));
+        assert_eq!(table.lines().count(), 3);
+    }
+
+    #[test]
+    fn registered_circuits() {
+        for entry in iter_circuits() {
+            let rows = (entry.without_witnesses)().planner_rows().unwrap();
+            let planners: Vec<_> = rows.iter().map(|rows| rows.planner).collect();
+            assert_eq!(planners, ["simple", "v1", "stacked"]);
+
+            // Stacking regions never saves rows.
+            assert!(rows[2].rows >= rows[0].rows, "{}: {rows:?}", entry.name);
+        }
+    }
+}
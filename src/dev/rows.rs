@@ -0,0 +1,231 @@
+//! Counts the rows a circuit uses by synthesizing it into a recording
+//! [`Assignment`], without running the mock prover, overall with
+//! [`rows_used`] and per region with [`region_usage`].
+//!
+//! Witness closures are evaluated like they are during proving, so running
+//! [`rows_used`] also measures the cost of witness generation.
+//!
+//! [`estimate_k`] derives the smallest `k` a circuit fits in from its rows,
+//! which saves guessing it. The rows of a circuit usually don't depend on its
+//! witness, so the estimate of a circuit without witnesses is the `k` of its
+//! keys.
+
+use std::{collections::BTreeSet, ops::Range};
+
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{FloorPlanner, Value},
+    plonk::{
+        Advice, Any, Assigned, Assignment, Challenge, Circuit, Column, ConstraintSystem, Error, Fixed, Instance,
+        Selector,
+    },
+};
+
+/// Rows and columns assigned in a region.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegionUsage {
+    pub name: String,
+    /// The rows from the first to the last one touched in the region.
+    pub rows: Range<usize>,
+    /// The advice and fixed columns assigned in the region.
+    pub columns: BTreeSet<String>,
+}
+
+impl RegionUsage {
+    /// Number of cells the region spans, its rows times its columns.
+    pub fn cells(&self) -> usize {
+        self.rows.len() * self.columns.len()
+    }
+
+    fn touch(&mut self, row: usize) {
+        self.rows = if self.rows.is_empty() {
+            row..row + 1
+        } else {
+            self.rows.start.min(row)..self.rows.end.max(row + 1)
+        };
+    }
+}
+
+/// Highest row touched by any assignment, selector or copy, and the rows
+/// touched in each region when `regions` is set.
+#[derive(Debug, Default)]
+struct RowCounter {
+    max_row: Option<usize>,
+    regions: Option<Vec<RegionUsage>>,
+    in_region: bool,
+}
+
+impl RowCounter {
+    fn touch(&mut self, row: usize) {
+        self.max_row = Some(self.max_row.map_or(row, |max_row| max_row.max(row)));
+    }
+
+    /// Touches `row` of the current region, assigning the column `kind[index]`
+    /// if any.
+    fn touch_region(&mut self, row: usize, column: Option<(&str, usize)>) {
+        self.touch(row);
+        if let (Some(regions), true) = (&mut self.regions, self.in_region) {
+            let region = regions.last_mut().expect("in a region");
+            region.touch(row);
+            if let Some((kind, index)) = column {
+                region.columns.insert(format!("{kind}[{index}]"));
+            }
+        }
+    }
+}
+
+impl<F: Field> Assignment<F> for RowCounter {
+    fn enter_region<NR, N>(&mut self, name: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        if let Some(regions) = &mut self.regions {
+            regions.push(RegionUsage {
+                name: name().into(),
+                rows: 0..0,
+                columns: BTreeSet::new(),
+            });
+        }
+        self.in_region = true;
+    }
+
+    fn annotate_column<A, AR>(&mut self, _: A, _: Column<Any>)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+    }
+
+    fn exit_region(&mut self) {
+        self.in_region = false;
+    }
+
+    fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.touch_region(row, None);
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<F>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(&mut self, _: A, column: Column<Advice>, row: usize, to: V) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.touch_region(row, Some(("advice", column.index())));
+        to().map(|value| value.into());
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(&mut self, _: A, column: Column<Fixed>, row: usize, to: V) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.touch_region(row, Some(("fixed", column.index())));
+        to().map(|value| value.into());
+        Ok(())
+    }
+
+    fn copy(&mut self, _: Column<Any>, left_row: usize, _: Column<Any>, right_row: usize) -> Result<(), Error> {
+        self.touch(left_row);
+        self.touch(right_row);
+        Ok(())
+    }
+
+    fn fill_from_row(&mut self, _: Column<Fixed>, row: usize, _: Value<Assigned<F>>) -> Result<(), Error> {
+        self.touch(row);
+        Ok(())
+    }
+
+    fn get_challenge(&self, _: Challenge) -> Value<F> {
+        Value::unknown()
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+fn count_rows<F: Field, C: Circuit<F>>(circuit: &C, per_region: bool) -> Result<RowCounter, Error> {
+    let mut cs = ConstraintSystem::default();
+    let config = super::configure(&mut cs, circuit);
+
+    let mut counter = RowCounter {
+        regions: per_region.then(Vec::new),
+        ..RowCounter::default()
+    };
+    C::FloorPlanner::synthesize(&mut counter, circuit, config, cs.constants().clone())?;
+    Ok(counter)
+}
+
+/// Number of rows used by `circuit`, not counting the rows reserved for
+/// blinding factors.
+pub fn rows_used<F: Field, C: Circuit<F>>(circuit: &C) -> Result<usize, Error> {
+    Ok(count_rows(circuit, false)?.max_row.map_or(0, |max_row| max_row + 1))
+}
+
+/// The rows and columns used by each region of `circuit`, in layout order.
+///
+/// Copies into a region from the outside, such as the ones of
+/// `constrain_instance`, are not counted as part of it.
+pub fn region_usage<F: Field, C: Circuit<F>>(circuit: &C) -> Result<Vec<RegionUsage>, Error> {
+    Ok(count_rows(circuit, true)?.regions.unwrap_or_default())
+}
+
+/// Smallest `k` such that `2^k` rows hold `rows` usable rows of `circuit`
+/// after its blinding rows.
+pub fn min_k<F: Field, C: Circuit<F>>(circuit: &C, rows: usize) -> u32 {
+    let mut cs = ConstraintSystem::default();
+    super::configure(&mut cs, circuit);
+
+    let n = (rows + cs.blinding_factors() + 1).max(cs.minimum_rows());
+    n.next_power_of_two().trailing_zeros()
+}
+
+/// Smallest `k` at which `circuit` fits, from the [`rows_used`] by it.
+///
+/// Instances are placed in rows too, so a circuit with more instances than
+/// rows needs the [`min_k`] of its number of instances instead.
+pub fn estimate_k<F: Field, C: Circuit<F>>(circuit: &C) -> Result<u32, Error> {
+    Ok(min_k(circuit, rows_used(circuit)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{estimate_k, region_usage, rows_used};
+    use crate::circuits::examples::simple::SimpleCircuit;
+    use crate::field::TestField as Fp;
+
+    #[test]
+    fn simple_circuit_rows() {
+        // One row for the inputs, one for `out`.
+        let circuit = SimpleCircuit::new(Fp::from(2), Fp::from(3), Fp::from(4));
+        assert_eq!(rows_used(&circuit).unwrap(), 2);
+        // Two rows, and the blinding rows of the mock prover.
+        assert_eq!(estimate_k(&circuit).unwrap(), 3);
+
+        let regions = region_usage(&circuit).unwrap();
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].name, "witness");
+        assert_eq!(regions[0].rows, 0..2);
+        assert_eq!(regions[0].columns.len(), 3);
+        assert_eq!(regions[0].cells(), 6);
+    }
+}
@@ -0,0 +1,237 @@
+//! Size and cost statistics of a circuit.
+//!
+//! Column, gate and lookup counts come straight from the [`ConstraintSystem`]
+//! built by `configure`; the proof size estimate is the one of
+//! [`CircuitCost`], which counts commitments and evaluations for the
+//! configured columns and queries.
+//!
+//! [`region_stats`] breaks the rows down by region, to find what to optimize
+//! in the larger circuits.
+
+use std::collections::BTreeSet;
+
+use halo2_proofs::{
+    dev::CircuitCost,
+    halo2curves::bn256::{Fr, G1},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+use serde_json::{json, Value as Json};
+
+use super::rows::{region_usage, rows_used};
+
+/// Statistics of a circuit at a given `k`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CircuitStats {
+    pub name: String,
+    pub k: u32,
+    pub advice_columns: usize,
+    pub fixed_columns: usize,
+    pub instance_columns: usize,
+    pub selectors: usize,
+    pub gates: usize,
+    /// Number of polynomial constraints over all gates.
+    pub constraints: usize,
+    pub max_gate_degree: usize,
+    /// Degree of the constraint system, including lookups and permutation.
+    pub degree: usize,
+    pub lookups: usize,
+    pub rows_used: usize,
+    /// Estimated size in bytes of a proof for a single instance.
+    pub proof_size: usize,
+}
+
+impl CircuitStats {
+    /// Measures `circuit` at `k`.
+    pub fn measure<C: Circuit<Fr>>(name: &str, k: u32, circuit: &C) -> Result<Self, Error> {
+        let mut cs = ConstraintSystem::default();
+        super::configure(&mut cs, circuit);
+
+        let polynomials = || cs.gates().iter().flat_map(|gate| gate.polynomials());
+        let cost = CircuitCost::<G1, C>::measure(k, circuit);
+
+        Ok(Self {
+            name: name.to_string(),
+            k,
+            advice_columns: cs.num_advice_columns(),
+            fixed_columns: cs.num_fixed_columns(),
+            instance_columns: cs.num_instance_columns(),
+            selectors: cs.num_selectors(),
+            gates: cs.gates().len(),
+            constraints: polynomials().count(),
+            max_gate_degree: polynomials().map(|poly| poly.degree()).max().unwrap_or(0),
+            degree: cs.degree(),
+            lookups: cs.lookups().len(),
+            rows_used: rows_used(circuit)?,
+            proof_size: cost.proof_size(1).into(),
+        })
+    }
+
+    /// The statistics as a flat JSON object.
+    pub fn to_json(&self) -> Json {
+        json!({
+            "name": self.name,
+            "k": self.k,
+            "advice_columns": self.advice_columns,
+            "fixed_columns": self.fixed_columns,
+            "instance_columns": self.instance_columns,
+            "selectors": self.selectors,
+            "gates": self.gates,
+            "constraints": self.constraints,
+            "max_gate_degree": self.max_gate_degree,
+            "degree": self.degree,
+            "lookups": self.lookups,
+            "rows_used": self.rows_used,
+            "proof_size": self.proof_size,
+        })
+    }
+}
+
+/// Formats `stats` as a table with one row per circuit.
+pub fn table(stats: &[CircuitStats]) -> String {
+    let header = [
+        "circuit", "k", "advice", "fixed", "instance", "selectors", "gates", "constraints", "gate deg", "degree",
+        "lookups", "rows", "proof bytes",
+    ];
+    let rows: Vec<Vec<String>> = stats
+        .iter()
+        .map(|s| {
+            let counts = [
+                s.k as usize,
+                s.advice_columns,
+                s.fixed_columns,
+                s.instance_columns,
+                s.selectors,
+                s.gates,
+                s.constraints,
+                s.max_gate_degree,
+                s.degree,
+                s.lookups,
+                s.rows_used,
+                s.proof_size,
+            ];
+            [s.name.clone()].into_iter().chain(counts.iter().map(usize::to_string)).collect()
+        })
+        .collect();
+
+    format_table(&header, &rows)
+}
+
+/// Rows and cells used by the regions of a circuit sharing a name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegionStats {
+    pub name: String,
+    /// Number of regions with this name.
+    pub regions: usize,
+    /// Rows of the regions, summed.
+    pub rows: usize,
+    /// Distinct advice and fixed columns assigned by the regions.
+    pub columns: usize,
+    /// Rows times columns of each region, summed.
+    pub cells: usize,
+}
+
+/// The regions of `circuit` grouped by name, the ones using the most cells
+/// first.
+///
+/// Regions are grouped by name since a chip called in a loop lays out one
+/// region per call; give them distinct names to tell them apart.
+pub fn region_stats<C: Circuit<Fr>>(circuit: &C) -> Result<Vec<RegionStats>, Error> {
+    let mut stats: Vec<RegionStats> = vec![];
+    let mut columns: Vec<BTreeSet<String>> = vec![];
+    for region in region_usage(circuit)? {
+        let i = match stats.iter().position(|stats| stats.name == region.name) {
+            Some(i) => i,
+            None => {
+                stats.push(RegionStats {
+                    name: region.name.clone(),
+                    regions: 0,
+                    rows: 0,
+                    columns: 0,
+                    cells: 0,
+                });
+                columns.push(BTreeSet::new());
+                stats.len() - 1
+            }
+        };
+        stats[i].regions += 1;
+        stats[i].rows += region.rows.len();
+        stats[i].cells += region.cells();
+        columns[i].extend(region.columns);
+        stats[i].columns = columns[i].len();
+    }
+
+    stats.sort_by(|a, b| b.cells.cmp(&a.cells).then(b.rows.cmp(&a.rows)));
+    Ok(stats)
+}
+
+/// Formats `stats` as a table with one row per region name.
+pub fn region_table(stats: &[RegionStats]) -> String {
+    let header = ["region", "count", "rows", "columns", "cells"];
+    let rows: Vec<Vec<String>> = stats
+        .iter()
+        .map(|s| {
+            let counts = [s.regions, s.rows, s.columns, s.cells];
+            [s.name.clone()].into_iter().chain(counts.iter().map(usize::to_string)).collect()
+        })
+        .collect();
+
+    format_table(&header, &rows)
+}
+
+/// Formats `rows` under `header`, with the columns padded to their widest
+/// cell.
+pub(crate) fn format_table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let widths: Vec<usize> = header
+        .iter()
+        .enumerate()
+        .map(|(i, title)| rows.iter().map(|row| row[i].len()).fold(title.len(), usize::max))
+        .collect();
+
+    let mut out = format_row(header, &widths);
+    out += &format_row(widths.iter().map(|w| "-".repeat(*w)), &widths);
+    for row in rows {
+        out += &format_row(row, &widths);
+    }
+
+    out
+}
+
+fn format_row<S: AsRef<str>>(cells: impl IntoIterator<Item = S>, widths: &[usize]) -> String {
+    let cells: Vec<String> = cells
+        .into_iter()
+        .zip(widths)
+        .map(|(cell, &w)| format!("{:<w$}", cell.as_ref()))
+        .collect();
+    format!("| {} |\n", cells.join(" | "))
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::halo2curves::bn256::Fr as Fp;
+
+    use super::{region_stats, region_table, table, CircuitStats};
+    use crate::circuits::examples::simple::SimpleCircuit;
+
+    #[test]
+    fn simple_circuit_stats() {
+        let circuit = SimpleCircuit::new(Fp::from(2), Fp::from(3), Fp::from(4));
+        let stats = CircuitStats::measure("simple", 4, &circuit).unwrap();
+
+        assert_eq!(stats.advice_columns, 2);
+        assert_eq!(stats.fixed_columns, 1);
+        assert_eq!(stats.instance_columns, 1);
+        assert_eq!(stats.gates, 1);
+        assert_eq!(stats.lookups, 0);
+        assert_eq!(stats.rows_used, 2);
+        assert!(stats.proof_size > 0);
+
+        assert_eq!(stats.to_json()["advice_columns"], 2);
+        assert_eq!(table(&[stats]).lines().count(), 3);
+
+        let regions = region_stats(&circuit).unwrap();
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].rows, 2);
+        assert_eq!(regions[0].cells, 6);
+        assert_eq!(region_table(&regions).lines().count(), 3);
+    }
+}
@@ -0,0 +1,132 @@
+//! Pinned verifying key hashes of the registered circuits.
+//!
+//! A deployed verifier, such as a contract of the `evm` module, only accepts
+//! proofs for the verifying key it was generated from, and any change to the
+//! layout of a circuit changes the key. The hash of the verifying key of
+//! each circuit is kept in a `<name>.vk` [golden] file, and the tests
+//! declared with `pin_vk!` fail when it changes.
+//!
+//! The hash is [`VerifyingKey::transcript_repr`], the Blake2b digest halo2
+//! absorbs into every transcript, which covers the fixed and permutation
+//! commitments as well as the pinned constraint system. Keys are generated
+//! from [`pinned_params`] so that they are reproducible. After an intended
+//! change, rewrite the files with `halo2-examples vk-hash --bless`.
+
+use halo2_proofs::{
+    halo2curves::{
+        bn256::{Bn256, G1Affine},
+        group::ff::PrimeField,
+    },
+    plonk::{self, VerifyingKey},
+    poly::kzg::commitment::ParamsKZG,
+};
+use rand::{rngs::StdRng, SeedableRng};
+
+use super::golden;
+use crate::registry::{find_circuit, CircuitEntry};
+
+/// Seed of the setup of [`pinned_params`].
+const PARAMS_SEED: u64 = 0;
+
+/// Params from a setup with a fixed seed, only meant for pinning keys.
+pub fn pinned_params(k: u32) -> ParamsKZG<Bn256> {
+    ParamsKZG::setup(k, StdRng::seed_from_u64(PARAMS_SEED))
+}
+
+/// The hash of `vk`, as big-endian hex.
+pub fn vk_hash(vk: &VerifyingKey<G1Affine>) -> String {
+    let mut repr = vk.transcript_repr().to_repr();
+    repr.reverse();
+    format!("0x{}", hex::encode(repr))
+}
+
+/// The hash of the verifying key of `entry` at its [`k`](CircuitEntry::k).
+pub fn entry_vk_hash(entry: &CircuitEntry) -> Result<String, plonk::Error> {
+    let vk = (entry.without_witnesses)().keygen_vk(&pinned_params(entry.k()?))?;
+    Ok(vk_hash(&vk))
+}
+
+/// Name of the golden file of circuit `name`.
+pub fn golden_file(name: &str) -> String {
+    format!("{name}.vk")
+}
+
+/// Compares the verifying key hash of the registered circuit `name` with its
+/// golden file, see [`golden::check`].
+pub fn check_pinned(name: &str) -> Result<(), String> {
+    let entry = find_circuit(name).ok_or_else(|| format!("unknown circuit `{name}`"))?;
+    let hash = entry_vk_hash(&entry).map_err(|err| format!("keygen of `{name}` failed: {err:?}"))?;
+    golden::check(&golden_file(name), &format!("{hash}\n"))
+}
+
+/// Declares a test per registered circuit checking its verifying key hash
+/// with [`check_pinned`], and a test that all registered circuits are
+/// pinned.
+///
+/// ```ignore
+/// pin_vk! {
+///     simple_vk => "simple",
+///     super_vk => "super",
+/// }
+/// ```
+#[cfg(test)]
+macro_rules! pin_vk {
+    ($($test:ident => $circuit:literal),* $(,)?) => {
+        $(
+            #[test]
+            fn $test() {
+                if let Err(err) = $crate::dev::vk::check_pinned($circuit) {
+                    panic!("{err}");
+                }
+            }
+        )*
+
+        #[test]
+        fn registered_circuits_are_pinned() {
+            let pinned = [$($circuit),*];
+            for entry in $crate::registry::iter_circuits() {
+                assert!(pinned.contains(&entry.name), "the verifying key of `{}` is not pinned", entry.name);
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+pub(crate) use pin_vk;
+
+#[cfg(test)]
+mod tests {
+    use super::pin_vk;
+
+    pin_vk! {
+        simple_vk => "simple",
+        is_zero_vk => "is_zero",
+        range_check_vk => "range_check",
+        memory_vk => "memory",
+        poseidon_vk => "poseidon",
+        super_vk => "super",
+        aes_vk => "aes",
+        airdrop_vk => "airdrop",
+        auction_vk => "auction",
+        battleship_vk => "battleship",
+        bytecode_vk => "bytecode",
+        chacha20_vk => "chacha20",
+        coloring_vk => "coloring",
+        dfa_vk => "dfa",
+        elgamal_vk => "elgamal",
+        evm_add_sub_vk => "evm_add_sub",
+        histogram_vk => "histogram",
+        instance_commitment_vk => "instance_commitment",
+        median_vk => "median",
+        mmr_vk => "mmr",
+        reachability_vk => "reachability",
+        shuffle_vk => "shuffle",
+        statistics_vk => "statistics",
+        threshold_vk => "threshold",
+        tornado_deposit_vk => "tornado_deposit",
+        tornado_withdraw_vk => "tornado_withdraw",
+        tuple_lookup_vk => "tuple_lookup",
+        vdf_vk => "vdf",
+        vrf_vk => "vrf",
+    }
+}
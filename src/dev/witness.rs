@@ -0,0 +1,208 @@
+//! Dumps the advice and fixed values a circuit assigns, region by region.
+//!
+//! The circuit is synthesized into a recording [`Assignment`], like the mock
+//! prover does but without checking any constraint, so the witness of a
+//! circuit that fails to verify can be inspected too. Each assigned cell is
+//! recorded with its region, prefixed with the enclosing namespaces, and the
+//! annotation given to `assign_advice` or `assign_fixed`. Values are printed
+//! like the constants of [`super::expr`].
+
+use halo2_proofs::{
+    circuit::{FloorPlanner, Value},
+    plonk::{
+        Advice, Any, Assigned, Assignment, Challenge, Circuit, Column, ConstraintSystem, Error, Fixed, Instance,
+        Selector,
+    },
+};
+
+use super::expr::format_constant;
+use crate::field::Field;
+
+/// An assigned advice or fixed cell.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WitnessCell<F> {
+    pub region: String,
+    pub annotation: String,
+    /// The column, as `advice[i]` or `fixed[i]`.
+    pub column: String,
+    pub row: usize,
+    /// The value, unknown when the circuit has no witness.
+    pub value: Option<F>,
+}
+
+/// Records the cells assigned in regions.
+#[derive(Debug, Default)]
+struct WitnessRecorder<F> {
+    namespaces: Vec<String>,
+    region: Option<String>,
+    cells: Vec<WitnessCell<F>>,
+}
+
+impl<F: Field> WitnessRecorder<F> {
+    fn record<VR: Into<Assigned<F>>>(&mut self, annotation: String, column: String, row: usize, to: Value<VR>) {
+        let mut value = None;
+        to.map(|to| value = Some(to.into().evaluate()));
+        self.cells.push(WitnessCell {
+            region: self.region.clone().unwrap_or_default(),
+            annotation,
+            column,
+            row,
+            value,
+        });
+    }
+}
+
+impl<F: Field> Assignment<F> for WitnessRecorder<F> {
+    fn enter_region<NR, N>(&mut self, name: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        let mut path = self.namespaces.clone();
+        path.push(name().into());
+        self.region = Some(path.join("/"));
+    }
+
+    fn annotate_column<A, AR>(&mut self, _: A, _: Column<Any>)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+    }
+
+    fn exit_region(&mut self) {
+        self.region = None;
+    }
+
+    fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, _: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<F>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Advice>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.record(annotation().into(), format!("advice[{}]", column.index()), row, to());
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Fixed>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.record(annotation().into(), format!("fixed[{}]", column.index()), row, to());
+        Ok(())
+    }
+
+    fn copy(&mut self, _: Column<Any>, _: usize, _: Column<Any>, _: usize) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn fill_from_row(&mut self, _: Column<Fixed>, _: usize, _: Value<Assigned<F>>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn get_challenge(&self, _: Challenge) -> Value<F> {
+        Value::unknown()
+    }
+
+    fn push_namespace<NR, N>(&mut self, name: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.namespaces.push(name().into());
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {
+        self.namespaces.pop();
+    }
+}
+
+/// The cells assigned by `circuit`, in assignment order.
+pub fn dump_witness<F: Field, C: Circuit<F>>(circuit: &C) -> Result<Vec<WitnessCell<F>>, Error> {
+    let mut cs = ConstraintSystem::default();
+    let config = super::configure(&mut cs, circuit);
+
+    let mut recorder = WitnessRecorder::default();
+    C::FloorPlanner::synthesize(&mut recorder, circuit, config, cs.constants().clone())?;
+
+    Ok(recorder.cells)
+}
+
+/// Formats `cells` as CSV with a header row, leaving unknown values empty.
+pub fn witness_csv<F: Field>(cells: &[WitnessCell<F>]) -> String {
+    let mut out = String::from("region,annotation,column,row,value\n");
+    for cell in cells {
+        let value = cell.value.as_ref().map(format_constant).unwrap_or_default();
+        out += &format!(
+            "{},{},{},{},{value}\n",
+            csv_field(&cell.region),
+            csv_field(&cell.annotation),
+            cell.column,
+            cell.row
+        );
+    }
+    out
+}
+
+/// `field`, quoted when it contains a separator or a quote.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::plonk::Circuit;
+
+    use super::{dump_witness, witness_csv};
+    use crate::{circuits::examples::simple::SimpleCircuit, field::TestField as Fp};
+
+    #[test]
+    fn simple_circuit_witness() {
+        let circuit = SimpleCircuit::new(Fp::from(2), Fp::from(3), Fp::from(4));
+        let cells = dump_witness(&circuit).unwrap();
+        assert_eq!(
+            witness_csv(&cells),
+            "region,annotation,column,row,value\n\
+             witness,a,advice[0],0,2\n\
+             witness,b,advice[1],0,3\n\
+             witness,c,fixed[0],0,4\n\
+             witness,out,advice[0],1,144\n"
+        );
+
+        // Without witnesses, only the constant is known.
+        let cells = dump_witness(&circuit.without_witnesses()).unwrap();
+        assert_eq!(cells.iter().filter(|cell| cell.value.is_some()).count(), 1);
+    }
+}
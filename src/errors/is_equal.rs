@@ -1,12 +1,13 @@
 use std::marker::PhantomData;
 
-use eth_types::Field;
 use halo2_proofs::{
     circuit::Chip,
     plonk::{Advice, Column, ConstraintSystem, Fixed, Selector},
     poly::Rotation,
 };
 
+use crate::field::Field;
+
 /// Config for the IsEqual chip.
 #[derive(Clone, Debug)]
 pub struct IsEqualConfig {
@@ -83,15 +84,14 @@ impl<F: Field> Chip<F> for IsEqualChip<F> {
 mod tests {
     use std::marker::PhantomData;
 
-    use eth_types::Field;
     use halo2_proofs::{
         circuit::{Layouter, SimpleFloorPlanner, Value},
         dev::MockProver,
-        halo2curves::bn256::Fr as Fp,
         plonk::{Circuit, ConstraintSystem, Error},
     };
     
     use super::{IsEqualChip, IsEqualConfig};
+    use crate::field::{Field, TestField as Fp};
 
     #[derive(Default)]
     struct TestCircuit<F: Field> {
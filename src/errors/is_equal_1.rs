@@ -1,12 +1,13 @@
 use std::marker::PhantomData;
 
-use eth_types::Field;
 use halo2_proofs::{
     circuit::{Chip, Region, Value},
     plonk::{Advice, Column, ConstraintSystem, Error, Expression, VirtualCells, Fixed, Instance, Selector},
     poly::Rotation,
 };
 
+use crate::field::Field;
+
 /// Config for the IsEqual chip.
 #[derive(Clone, Debug)]
 pub struct IsEqualConfig {
@@ -80,15 +81,14 @@ impl<F: Field> Chip<F> for IsEqualChip<F> {
 mod tests {
     use std::marker::PhantomData;
 
-    use eth_types::Field;
     use halo2_proofs::{
         circuit::{Layouter, SimpleFloorPlanner, Value},
         dev::MockProver,
-        halo2curves::bn256::Fr as Fp,
         plonk::{Circuit, ConstraintSystem, Error, Selector},
     };
     
     use super::{IsEqualChip, IsEqualConfig};
+    use crate::field::{Field, TestField as Fp};
 
     #[derive(Clone, Debug)]
     struct TestCircuitConfig {
@@ -0,0 +1,32 @@
+//! The field bound of the chips and gadgets, and the field their tests run
+//! over.
+//!
+//! Gadgets only rely on the traits bundled in [`Field`], so they are generic
+//! over any 256-bit prime field. The tests pick their field through
+//! [`TestField`]: the bn256 scalar field by default, or the Pallas base field
+//! with the `pasta` feature (which takes precedence, so that
+//! `cargo test --features pasta` is enough to switch).
+//!
+//! CI runs the tests once per field, with `--no-default-features` and either
+//! `--features bn256` or `--features pasta`.
+
+use halo2_proofs::{
+    arithmetic,
+    halo2curves::group::ff::{FromUniformBytes, PrimeField},
+};
+
+/// Field the chips and gadgets of this crate are generic over.
+pub trait Field: arithmetic::Field + PrimeField<Repr = [u8; 32]> + FromUniformBytes<64> + Ord {}
+
+impl<F> Field for F where F: arithmetic::Field + PrimeField<Repr = [u8; 32]> + FromUniformBytes<64> + Ord {}
+
+/// Field of the gadget tests.
+#[cfg(feature = "pasta")]
+pub type TestField = halo2_proofs::halo2curves::pasta::Fp;
+
+/// Field of the gadget tests.
+#[cfg(all(feature = "bn256", not(feature = "pasta")))]
+pub type TestField = halo2_proofs::halo2curves::bn256::Fr;
+
+#[cfg(not(any(feature = "bn256", feature = "pasta")))]
+compile_error!("one of the `bn256` or `pasta` features must be enabled");
@@ -1,2 +1,9 @@
 pub mod circuits;
-pub mod errors;
\ No newline at end of file
+pub mod dev;
+pub mod errors;
+pub mod field;
+pub mod prover;
+pub mod registry;
+pub mod trace;
+#[cfg(feature = "wasm")]
+pub mod wasm;
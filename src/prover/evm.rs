@@ -0,0 +1,122 @@
+//! EVM verifier generation through snark-verifier.
+//!
+//! EVM verification uses GWC multi-opening and a Keccak-based transcript, so
+//! proofs for the generated contract must be created with [`gen_evm_proof`]
+//! rather than [`super::prove`]. Compiling the Yul source needs `solc` in
+//! `PATH`.
+
+use std::rc::Rc;
+
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fq, Fr, G1Affine},
+    plonk::{self, create_proof, Circuit, ProvingKey, VerifyingKey},
+    poly::{
+        commitment::ParamsProver,
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::ProverGWC,
+        },
+    },
+    transcript::TranscriptWriterBuffer,
+};
+use rand::rngs::OsRng;
+use snark_verifier::{
+    loader::evm::{self, EvmLoader},
+    pcs::kzg::{Gwc19, KzgAs},
+    system::halo2::{compile, transcript::evm::EvmTranscript, Config},
+    verifier::{self, SnarkVerifier},
+};
+
+type PlonkVerifier = verifier::plonk::PlonkVerifier<KzgAs<Bn256, Gwc19>>;
+
+/// Generates the Yul source of a verifier contract for `vk`, where
+/// `num_instance` holds the number of instances of each instance column.
+pub fn gen_evm_verifier_yul(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    num_instance: Vec<usize>,
+) -> String {
+    let protocol = compile(params, vk, Config::kzg().with_num_instance(num_instance.clone()));
+    let vk = (params.get_g()[0], params.g2(), params.s_g2()).into();
+
+    let loader = EvmLoader::new::<Fq, Fr>();
+    let protocol = protocol.loaded(&loader);
+    let mut transcript = EvmTranscript::<_, Rc<EvmLoader>, _, _>::new(&loader);
+
+    let instances = transcript.load_instances(num_instance);
+    let proof = PlonkVerifier::read_proof(&vk, &protocol, &instances, &mut transcript).unwrap();
+    PlonkVerifier::verify(&vk, &protocol, &instances, &proof).unwrap();
+
+    loader.yul_code()
+}
+
+/// Generates the deployment bytecode of a verifier contract for `vk`.
+pub fn gen_evm_verifier(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    num_instance: Vec<usize>,
+) -> Vec<u8> {
+    evm::compile_yul(&gen_evm_verifier_yul(params, vk, num_instance))
+}
+
+/// Creates a proof that can be checked by the contract of [`gen_evm_verifier`].
+pub fn gen_evm_proof<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: &[Vec<Fr>],
+) -> Result<Vec<u8>, plonk::Error> {
+    let instances: Vec<&[Fr]> = instances.iter().map(Vec::as_slice).collect();
+    let mut transcript = TranscriptWriterBuffer::<_, G1Affine, _>::init(Vec::new());
+    create_proof::<KZGCommitmentScheme<Bn256>, ProverGWC<_>, _, _, EvmTranscript<_, _, _, _>, _>(
+        params,
+        pk,
+        &[circuit],
+        &[&instances],
+        OsRng,
+        &mut transcript,
+    )?;
+
+    Ok(transcript.finalize())
+}
+
+/// Encodes `instances` and `proof` as the calldata expected by the verifier.
+pub fn gen_evm_calldata(instances: &[Vec<Fr>], proof: &[u8]) -> Vec<u8> {
+    evm::encode_calldata(instances, proof)
+}
+
+/// Deploys `deployment_code` in a local EVM and calls it with the encoded
+/// proof, returning the gas used on success.
+pub fn evm_verify(deployment_code: Vec<u8>, instances: &[Vec<Fr>], proof: &[u8]) -> Result<u64, String> {
+    evm::deploy_and_call(deployment_code, gen_evm_calldata(instances, proof))
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::Value,
+        halo2curves::bn256::{Bn256, Fr},
+        poly::kzg::commitment::ParamsKZG,
+    };
+    use rand::rngs::OsRng;
+
+    use super::{evm_verify, gen_evm_proof, gen_evm_verifier};
+    use crate::prover::{keygen, tests::TestCircuit};
+
+    #[test]
+    fn evm_verifier_accepts_proof() {
+        let params = ParamsKZG::<Bn256>::setup(4, OsRng);
+        let pk = keygen(&params, &TestCircuit::default()).unwrap();
+        let deployment_code = gen_evm_verifier(&params, pk.get_vk(), vec![1]);
+
+        let circuit = TestCircuit {
+            a: Value::known(Fr::from(3)),
+            b: Value::known(Fr::from(5)),
+        };
+        let instances = vec![vec![Fr::from(15)]];
+        let proof = gen_evm_proof(&params, &pk, circuit, &instances).unwrap();
+
+        assert!(evm_verify(deployment_code.clone(), &instances, &proof).is_ok());
+        assert!(evm_verify(deployment_code, &[vec![Fr::from(16)]], &proof).is_err());
+    }
+}
@@ -0,0 +1,249 @@
+//! Serialization of proving/verifying keys and an on-disk key cache.
+//!
+//! Key generation for the bigger circuits takes long enough that regenerating
+//! the keys on every test run is not an option. [`KeyCache`] stores them under
+//! `<dir>/<name>_k<k>.{pk,vk}` and only runs keygen when no file is found.
+//! The cache is not invalidated when a circuit changes: delete the directory
+//! after touching a circuit's configuration.
+
+use std::{
+    env,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{keygen_pk, keygen_vk, Circuit, ProvingKey, VerifyingKey},
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+    SerdeFormat,
+};
+
+use super::ProverError;
+use crate::trace::span;
+
+/// Environment variable overriding the default cache directory.
+pub const KEY_CACHE_DIR_ENV: &str = "HALO2_KEY_CACHE";
+
+fn create_file(path: &Path) -> io::Result<BufWriter<File>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    Ok(BufWriter::new(File::create(path)?))
+}
+
+/// Writes a proving key to `path`, creating the parent directories.
+pub fn write_pk(pk: &ProvingKey<G1Affine>, path: impl AsRef<Path>, format: SerdeFormat) -> io::Result<()> {
+    let mut writer = create_file(path.as_ref())?;
+    pk.write(&mut writer, format)?;
+    writer.flush()
+}
+
+/// Reads a proving key of circuit `C` written by [`write_pk`], configured
+/// with `params` under the `circuit-params` feature.
+pub fn read_pk<C: Circuit<Fr>>(
+    path: impl AsRef<Path>,
+    format: SerdeFormat,
+    #[cfg(feature = "circuit-params")] params: C::Params,
+) -> io::Result<ProvingKey<G1Affine>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    ProvingKey::read::<_, C>(
+        &mut reader,
+        format,
+        #[cfg(feature = "circuit-params")]
+        params,
+    )
+}
+
+/// Writes a verifying key to `path`, creating the parent directories.
+pub fn write_vk(vk: &VerifyingKey<G1Affine>, path: impl AsRef<Path>, format: SerdeFormat) -> io::Result<()> {
+    let mut writer = create_file(path.as_ref())?;
+    vk.write(&mut writer, format)?;
+    writer.flush()
+}
+
+/// Reads a verifying key of circuit `C` written by [`write_vk`], configured
+/// with `params` under the `circuit-params` feature.
+pub fn read_vk<C: Circuit<Fr>>(
+    path: impl AsRef<Path>,
+    format: SerdeFormat,
+    #[cfg(feature = "circuit-params")] params: C::Params,
+) -> io::Result<VerifyingKey<G1Affine>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    VerifyingKey::read::<_, C>(
+        &mut reader,
+        format,
+        #[cfg(feature = "circuit-params")]
+        params,
+    )
+}
+
+/// On-disk cache of keys, keyed by circuit name and `k`.
+#[derive(Clone, Debug)]
+pub struct KeyCache {
+    dir: PathBuf,
+    format: SerdeFormat,
+}
+
+impl Default for KeyCache {
+    /// Uses `$HALO2_KEY_CACHE`, or `target/halo2-keys` when it is not set.
+    fn default() -> Self {
+        let dir = env::var_os(KEY_CACHE_DIR_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("target").join("halo2-keys"));
+        Self::new(dir)
+    }
+}
+
+impl KeyCache {
+    /// Creates a cache rooted at `dir`, storing keys as `SerdeFormat::RawBytes`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            format: SerdeFormat::RawBytes,
+        }
+    }
+
+    /// Changes the serialization format of the cached keys.
+    pub fn with_format(mut self, format: SerdeFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Directory the keys are stored in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Path of the proving key of circuit `name` at `k`.
+    pub fn pk_path(&self, name: &str, k: u32) -> PathBuf {
+        self.dir.join(format!("{name}_k{k}.pk"))
+    }
+
+    /// Path of the verifying key of circuit `name` at `k`.
+    pub fn vk_path(&self, name: &str, k: u32) -> PathBuf {
+        self.dir.join(format!("{name}_k{k}.vk"))
+    }
+
+    /// Returns the cached proving key of `circuit`, generating and storing it
+    /// (along with its verifying key) on a cache miss. A key file that fails
+    /// to deserialize is treated as a miss and overwritten.
+    pub fn pk<C: Circuit<Fr>>(
+        &self,
+        name: &str,
+        params: &ParamsKZG<Bn256>,
+        circuit: &C,
+    ) -> Result<ProvingKey<G1Affine>, ProverError> {
+        let path = self.pk_path(name, params.k());
+        if let Ok(pk) = read_pk::<C>(
+            &path,
+            self.format,
+            #[cfg(feature = "circuit-params")]
+            circuit.params(),
+        ) {
+            return Ok(pk);
+        }
+
+        let vk = {
+            let _span = span!(INFO, "keygen_vk", circuit = name);
+            keygen_vk(params, circuit)?
+        };
+        write_vk(&vk, self.vk_path(name, params.k()), self.format)?;
+        let pk = {
+            let _span = span!(INFO, "keygen_pk", circuit = name);
+            keygen_pk(params, vk, circuit)?
+        };
+        write_pk(&pk, &path, self.format)?;
+
+        Ok(pk)
+    }
+
+    /// Returns the cached verifying key of `circuit`, generating and storing it
+    /// on a cache miss.
+    pub fn vk<C: Circuit<Fr>>(
+        &self,
+        name: &str,
+        params: &ParamsKZG<Bn256>,
+        circuit: &C,
+    ) -> Result<VerifyingKey<G1Affine>, ProverError> {
+        let path = self.vk_path(name, params.k());
+        if let Ok(vk) = read_vk::<C>(
+            &path,
+            self.format,
+            #[cfg(feature = "circuit-params")]
+            circuit.params(),
+        ) {
+            return Ok(vk);
+        }
+
+        let vk = {
+            let _span = span!(INFO, "keygen_vk", circuit = name);
+            keygen_vk(params, circuit)?
+        };
+        write_vk(&vk, &path, self.format)?;
+
+        Ok(vk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use halo2_proofs::{
+        circuit::Value,
+        halo2curves::bn256::{Bn256, Fr},
+        poly::kzg::commitment::ParamsKZG,
+        SerdeFormat,
+    };
+    use rand::rngs::OsRng;
+
+    use super::{read_vk, write_vk, KeyCache};
+    use crate::prover::{keygen, prove, tests::TestCircuit, verify};
+
+    fn test_dir(name: &str) -> std::path::PathBuf {
+        env::temp_dir().join(format!("halo2-key-cache-{name}-{}", std::process::id()))
+    }
+
+    #[test]
+    fn vk_roundtrip() {
+        let params = ParamsKZG::<Bn256>::setup(4, OsRng);
+        let pk = keygen(&params, &TestCircuit::default()).unwrap();
+
+        let path = test_dir("roundtrip").join("mul.vk");
+        write_vk(pk.get_vk(), &path, SerdeFormat::RawBytes).unwrap();
+        let vk = read_vk::<TestCircuit>(
+            &path,
+            SerdeFormat::RawBytes,
+            #[cfg(feature = "circuit-params")]
+            (),
+        )
+        .unwrap();
+
+        assert_eq!(
+            vk.to_bytes(SerdeFormat::RawBytes),
+            pk.get_vk().to_bytes(SerdeFormat::RawBytes)
+        );
+    }
+
+    #[test]
+    fn cached_keys_prove_and_verify() {
+        let params = ParamsKZG::<Bn256>::setup(4, OsRng);
+        let cache = KeyCache::new(test_dir("cache"));
+
+        // First call generates and stores the keys, the second one reads them back.
+        let pk = cache.pk("mul", &params, &TestCircuit::default()).unwrap();
+        assert!(cache.pk_path("mul", 4).exists());
+        assert!(cache.vk_path("mul", 4).exists());
+        let vk = cache.vk("mul", &params, &TestCircuit::default()).unwrap();
+
+        let circuit = TestCircuit {
+            a: Value::known(Fr::from(3)),
+            b: Value::known(Fr::from(5)),
+        };
+        let proof = prove(&params, &pk, circuit, &[vec![Fr::from(15)]]).unwrap();
+        assert!(verify(&params, &vk, &proof, &[vec![Fr::from(15)]]).is_ok());
+        assert!(verify(&params, &vk, &proof, &[vec![Fr::from(16)]]).is_err());
+    }
+}
@@ -0,0 +1,335 @@
+//! Key generation, proving and verification helpers for the example circuits.
+//!
+//! Everything in here is fixed to KZG over bn256 and SHPLONK multi-opening.
+//! [`prove`] and [`verify`] use a Blake2b transcript, which is the setup used
+//! by the tests in this crate, and [`prove_with`] and [`verify_with`] take the
+//! [`TranscriptHash`] as a type parameter. A proof only verifies with the
+//! transcript it was created with.
+//!
+//! [`Keccak256`] challenges are the cheap ones to recompute on the EVM. The
+//! contracts of the `evm` module have their own Keccak transcript encoding
+//! though, so their proofs come from `evm::gen_evm_proof`.
+
+use std::{fmt, io};
+
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{self, create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, ProvingKey, VerifyingKey},
+    poly::{
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::{ProverSHPLONK, VerifierSHPLONK},
+            strategy::{AccumulatorStrategy, SingleStrategy},
+        },
+        VerificationStrategy,
+    },
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, Keccak256Read, Keccak256Write, TranscriptReadBuffer,
+        TranscriptWriterBuffer,
+    },
+};
+use rand::rngs::OsRng;
+
+use crate::trace::span;
+#[cfg(feature = "trace")]
+use crate::trace::Traced;
+
+#[cfg(feature = "evm")]
+pub mod evm;
+mod keys;
+mod params;
+
+pub use keys::{read_pk, read_vk, write_pk, write_vk, KeyCache};
+pub use params::{read_ptau, read_srs, write_srs, ParamsStore};
+
+/// Errors returned by the prover helpers.
+#[derive(Debug)]
+pub enum ProverError {
+    /// Reading or writing an artifact failed.
+    Io(io::Error),
+    /// halo2 rejected the circuit, the keys or the proof.
+    Plonk(plonk::Error),
+}
+
+impl fmt::Display for ProverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProverError::Io(err) => write!(f, "io error: {err}"),
+            ProverError::Plonk(err) => write!(f, "plonk error: {err:?}"),
+        }
+    }
+}
+
+impl std::error::Error for ProverError {}
+
+impl From<io::Error> for ProverError {
+    fn from(err: io::Error) -> Self {
+        ProverError::Io(err)
+    }
+}
+
+impl From<plonk::Error> for ProverError {
+    fn from(err: plonk::Error) -> Self {
+        ProverError::Plonk(err)
+    }
+}
+
+/// The hash of the Fiat-Shamir transcript of a proof.
+pub trait TranscriptHash {
+    /// The transcript [`prove_with`] writes.
+    type Writer: TranscriptWriterBuffer<Vec<u8>, G1Affine, Challenge255<G1Affine>>;
+    /// The transcript [`verify_with`] reads.
+    type Reader<'a>: TranscriptReadBuffer<&'a [u8], G1Affine, Challenge255<G1Affine>>;
+}
+
+/// The Blake2b transcript of [`prove`] and [`verify`].
+#[derive(Clone, Copy, Debug)]
+pub struct Blake2b;
+
+impl TranscriptHash for Blake2b {
+    type Writer = Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>;
+    type Reader<'a> = Blake2bRead<&'a [u8], G1Affine, Challenge255<G1Affine>>;
+}
+
+/// A Keccak256 transcript.
+#[derive(Clone, Copy, Debug)]
+pub struct Keccak256;
+
+impl TranscriptHash for Keccak256 {
+    type Writer = Keccak256Write<Vec<u8>, G1Affine, Challenge255<G1Affine>>;
+    type Reader<'a> = Keccak256Read<&'a [u8], G1Affine, Challenge255<G1Affine>>;
+}
+
+/// Generates the verifying and proving keys of `circuit`.
+pub fn keygen<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    circuit: &C,
+) -> Result<ProvingKey<G1Affine>, plonk::Error> {
+    let vk = {
+        let _span = span!(INFO, "keygen_vk");
+        keygen_vk(params, circuit)?
+    };
+    let _span = span!(INFO, "keygen_pk");
+    keygen_pk(params, vk, circuit)
+}
+
+/// Creates a proof for `circuit`, one `Vec` of `instances` per instance column.
+pub fn prove<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: &[Vec<Fr>],
+) -> Result<Vec<u8>, plonk::Error> {
+    prove_with::<Blake2b, _>(params, pk, circuit, instances)
+}
+
+/// Verifies a proof created by [`prove`].
+pub fn verify(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proof: &[u8],
+    instances: &[Vec<Fr>],
+) -> Result<(), plonk::Error> {
+    verify_with::<Blake2b>(params, vk, proof, instances)
+}
+
+/// [`prove`] with the transcript of `T`.
+pub fn prove_with<T: TranscriptHash, C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: &[Vec<Fr>],
+) -> Result<Vec<u8>, plonk::Error> {
+    let _span = span!(INFO, "prove");
+    #[cfg(feature = "trace")]
+    let circuit = Traced(circuit);
+
+    let instances: Vec<&[Fr]> = instances.iter().map(Vec::as_slice).collect();
+    let mut transcript = T::Writer::init(vec![]);
+    create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<'_, Bn256>, Challenge255<G1Affine>, _, T::Writer, _>(
+        params,
+        pk,
+        &[circuit],
+        &[&instances],
+        OsRng,
+        &mut transcript,
+    )?;
+
+    Ok(transcript.finalize())
+}
+
+/// Verifies a proof created by [`prove_with`] with the same `T`.
+pub fn verify_with<T: TranscriptHash>(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proof: &[u8],
+    instances: &[Vec<Fr>],
+) -> Result<(), plonk::Error> {
+    let _span = span!(INFO, "verify");
+    let instances: Vec<&[Fr]> = instances.iter().map(Vec::as_slice).collect();
+    let mut transcript = T::Reader::init(proof);
+    verify_proof::<
+        KZGCommitmentScheme<Bn256>,
+        VerifierSHPLONK<'_, Bn256>,
+        Challenge255<G1Affine>,
+        T::Reader<'_>,
+        SingleStrategy<'_, Bn256>,
+    >(params, vk, SingleStrategy::new(params), &[&instances], &mut transcript)
+}
+
+/// Verifies several `(proof, instances)` pairs of the same circuit at once.
+///
+/// Instead of running one pairing check per proof, the multi-open MSMs of all
+/// proofs are folded into a single accumulator (each scaled by a fresh random
+/// factor) which is checked once at the end.
+pub fn verify_batch(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proofs: &[(Vec<u8>, Vec<Vec<Fr>>)],
+) -> Result<(), plonk::Error> {
+    let _span = span!(INFO, "verify");
+    let mut strategy = AccumulatorStrategy::new(params);
+    for (proof, instances) in proofs {
+        let instances: Vec<&[Fr]> = instances.iter().map(Vec::as_slice).collect();
+        let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof.as_slice());
+        strategy = verify_proof::<
+            KZGCommitmentScheme<Bn256>,
+            VerifierSHPLONK<'_, Bn256>,
+            Challenge255<G1Affine>,
+            Blake2bRead<&[u8], G1Affine, Challenge255<G1Affine>>,
+            AccumulatorStrategy<'_, Bn256>,
+        >(params, vk, strategy, &[&instances], &mut transcript)?;
+    }
+
+    if strategy.finalize() {
+        Ok(())
+    } else {
+        Err(plonk::Error::ConstraintSystemFailure)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        halo2curves::bn256::{Bn256, Fr},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance, Selector},
+        poly::{kzg::commitment::ParamsKZG, Rotation},
+    };
+    use rand::rngs::OsRng;
+
+    use super::{keygen, prove, prove_with, verify, verify_batch, verify_with, Blake2b, Keccak256};
+
+    #[derive(Clone, Debug)]
+    pub(crate) struct TestCircuitConfig {
+        advice: [Column<Advice>; 3],
+        instance: Column<Instance>,
+        s_mul: Selector,
+    }
+
+    /// Proves `a * b = out` with `out` exposed as the only instance.
+    #[derive(Default)]
+    pub(crate) struct TestCircuit {
+        pub(crate) a: Value<Fr>,
+        pub(crate) b: Value<Fr>,
+    }
+
+    impl Circuit<Fr> for TestCircuit {
+        type Config = TestCircuitConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let advice = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+            let instance = meta.instance_column();
+            let s_mul = meta.selector();
+
+            meta.enable_equality(advice[2]);
+            meta.enable_equality(instance);
+
+            // | a0 | a1 | a2  | s |
+            // | a  | b  | out | 1 |
+            meta.create_gate("mul", |meta| {
+                let s_mul = meta.query_selector(s_mul);
+                let a = meta.query_advice(advice[0], Rotation::cur());
+                let b = meta.query_advice(advice[1], Rotation::cur());
+                let out = meta.query_advice(advice[2], Rotation::cur());
+                vec![s_mul * (a * b - out)]
+            });
+
+            TestCircuitConfig {
+                advice,
+                instance,
+                s_mul,
+            }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fr>) -> Result<(), Error> {
+            let out = layouter.assign_region(
+                || "mul",
+                |mut region| {
+                    config.s_mul.enable(&mut region, 0)?;
+                    region.assign_advice(|| "a", config.advice[0], 0, || self.a)?;
+                    region.assign_advice(|| "b", config.advice[1], 0, || self.b)?;
+                    region.assign_advice(|| "out", config.advice[2], 0, || self.a * self.b)
+                },
+            )?;
+
+            layouter.constrain_instance(out.cell(), config.instance, 0)
+        }
+    }
+
+    #[test]
+    fn batch_verification() {
+        let params = ParamsKZG::<Bn256>::setup(4, OsRng);
+        let pk = keygen(&params, &TestCircuit::default()).unwrap();
+
+        let mut proofs: Vec<_> = [(2, 3), (4, 5), (6, 7)]
+            .into_iter()
+            .map(|(a, b)| {
+                let circuit = TestCircuit {
+                    a: Value::known(Fr::from(a)),
+                    b: Value::known(Fr::from(b)),
+                };
+                let instances = vec![vec![Fr::from(a * b)]];
+                (prove(&params, &pk, circuit, &instances).unwrap(), instances)
+            })
+            .collect();
+        assert!(verify_batch(&params, pk.get_vk(), &proofs).is_ok());
+
+        // A single bad proof makes the whole batch fail.
+        proofs[1].1 = vec![vec![Fr::from(21)]];
+        assert!(verify_batch(&params, pk.get_vk(), &proofs).is_err());
+    }
+
+    #[test]
+    fn transcripts() {
+        let params = ParamsKZG::<Bn256>::setup(4, OsRng);
+        let pk = keygen(&params, &TestCircuit::default()).unwrap();
+        let circuit = TestCircuit {
+            a: Value::known(Fr::from(3)),
+            b: Value::known(Fr::from(5)),
+        };
+        let instances = vec![vec![Fr::from(15)]];
+
+        let proof = prove_with::<Keccak256, _>(&params, &pk, circuit, &instances).unwrap();
+        assert!(verify_with::<Keccak256>(&params, pk.get_vk(), &proof, &instances).is_ok());
+        assert!(verify_with::<Keccak256>(&params, pk.get_vk(), &proof, &[vec![Fr::from(16)]]).is_err());
+
+        // The challenges of one transcript don't match the other's, in either
+        // direction: a proof must be verified with the hash it was made with.
+        assert!(verify_with::<Blake2b>(&params, pk.get_vk(), &proof, &instances).is_err());
+        assert!(verify(&params, pk.get_vk(), &proof, &instances).is_err());
+        let circuit = TestCircuit {
+            a: Value::known(Fr::from(3)),
+            b: Value::known(Fr::from(5)),
+        };
+        let proof = prove(&params, &pk, circuit, &instances).unwrap();
+        assert!(verify_with::<Keccak256>(&params, pk.get_vk(), &proof, &instances).is_err());
+    }
+}
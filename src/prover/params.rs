@@ -0,0 +1,316 @@
+//! KZG parameter (SRS) management.
+//!
+//! [`ParamsStore`] keeps one `kzg_bn254_<k>.srs` file per `k` in a directory.
+//! A missing `k` is served by downsizing the smallest larger file found, and
+//! only generated from scratch when there is none. Freshly generated params
+//! come from a local, insecure setup and must only be used for testing; real
+//! deployments should import a ceremony transcript with [`read_ptau`] or a
+//! halo2-formatted `.srs` file with [`read_srs`].
+
+use std::{
+    collections::HashMap,
+    env,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use halo2_proofs::{
+    arithmetic::best_fft,
+    halo2curves::{
+        bn256::{Bn256, Fr, G1Affine, G2Affine, G1},
+        group::{
+            ff::{Field, PrimeField},
+            Curve,
+        },
+        serde::SerdeObject,
+    },
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+};
+use rand::rngs::OsRng;
+
+/// Environment variable overriding the default params directory.
+pub const PARAMS_DIR_ENV: &str = "HALO2_PARAMS_DIR";
+
+/// Largest `k` looked up when searching for params to downsize.
+const MAX_K: u32 = 28;
+
+fn invalid_data(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+/// Reads params stored in halo2's own format.
+pub fn read_srs(path: impl AsRef<Path>) -> io::Result<ParamsKZG<Bn256>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    ParamsKZG::read(&mut reader)
+}
+
+/// Writes params in halo2's own format, creating the parent directories.
+pub fn write_srs(params: &ParamsKZG<Bn256>, path: impl AsRef<Path>) -> io::Result<()> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut writer = BufWriter::new(File::create(path)?);
+    params.write(&mut writer)?;
+    writer.flush()
+}
+
+/// Reads the first `2^k` powers of tau from a snarkjs `.ptau` file, such as
+/// the perpetual-powers-of-tau transcripts.
+///
+/// A `.ptau` file is a list of sections `(id: u32, size: u64, data)` behind a
+/// `ptau` magic header. Section 1 holds the header (`n8`, `q`, `power`),
+/// section 2 the tau powers in G1 and section 3 the tau powers in G2. Points
+/// are stored as little-endian Montgomery coordinates, which is exactly the
+/// raw encoding of halo2curves.
+pub fn read_ptau(path: impl AsRef<Path>, k: u32) -> io::Result<ParamsKZG<Bn256>> {
+    let mut reader = BufReader::new(File::open(path)?);
+
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != b"ptau" {
+        return Err(invalid_data("not a ptau file"));
+    }
+    let _version = read_u32(&mut reader)?;
+    let num_sections = read_u32(&mut reader)?;
+
+    let mut sections = HashMap::new();
+    for _ in 0..num_sections {
+        let id = read_u32(&mut reader)?;
+        let size = read_u64(&mut reader)?;
+        let offset = reader.stream_position()?;
+        sections.insert(id, (offset, size));
+        reader.seek(SeekFrom::Current(size as i64))?;
+    }
+    let section = |id: u32| sections.get(&id).copied().ok_or_else(|| invalid_data(format!("missing section {id}")));
+
+    // Header: n8 | q | power | ceremony power
+    let (offset, _) = section(1)?;
+    reader.seek(SeekFrom::Start(offset))?;
+    let n8 = read_u32(&mut reader)? as usize;
+    if n8 != 32 {
+        return Err(invalid_data(format!("unsupported field size of {n8} bytes")));
+    }
+    reader.seek(SeekFrom::Current(n8 as i64))?;
+    let power = read_u32(&mut reader)?;
+    if k > power {
+        return Err(invalid_data(format!("ptau file only supports k <= {power}")));
+    }
+
+    let n = 1usize << k;
+    let (offset, size) = section(2)?;
+    if size < (n * 2 * n8) as u64 {
+        return Err(invalid_data("tau G1 section is too short"));
+    }
+    reader.seek(SeekFrom::Start(offset))?;
+    let g = (0..n)
+        .map(|_| read_point::<G1Affine>(&mut reader, 2 * n8))
+        .collect::<io::Result<Vec<_>>>()?;
+
+    let (offset, _) = section(3)?;
+    reader.seek(SeekFrom::Start(offset))?;
+    let g2 = read_point::<G2Affine>(&mut reader, 4 * n8)?;
+    let s_g2 = read_point::<G2Affine>(&mut reader, 4 * n8)?;
+
+    Ok(params_from_powers(k, g, g2, s_g2))
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn read_point<C: SerdeObject>(reader: &mut impl Read, len: usize) -> io::Result<C> {
+    let mut buf = vec![0u8; len];
+    reader.read_exact(&mut buf)?;
+    C::from_raw_bytes(&buf).ok_or_else(|| invalid_data("invalid curve point"))
+}
+
+/// Builds params from the monomial powers `[tau^i] G1` and `[1] G2, [tau] G2`,
+/// computing the Lagrange basis with an inverse FFT over G1.
+fn params_from_powers(k: u32, g: Vec<G1Affine>, g2: G2Affine, s_g2: G2Affine) -> ParamsKZG<Bn256> {
+    let n = 1u64 << k;
+    let omega_inv = Fr::ROOT_OF_UNITY_INV.pow_vartime([1 << (Fr::S - k)]);
+    let n_inv = Fr::from(n).invert().unwrap();
+
+    let mut g_lagrange_projective: Vec<G1> = g.iter().map(|p| p.into()).collect();
+    best_fft(&mut g_lagrange_projective, omega_inv, k);
+    g_lagrange_projective.iter_mut().for_each(|p| *p *= n_inv);
+    let mut g_lagrange = vec![G1Affine::default(); n as usize];
+    G1::batch_normalize(&g_lagrange_projective, &mut g_lagrange);
+
+    // Go through halo2's raw serialization: the fields of `ParamsKZG` are private.
+    let mut bytes = k.to_le_bytes().to_vec();
+    g.iter().chain(&g_lagrange).for_each(|p| bytes.extend(p.to_raw_bytes()));
+    bytes.extend(g2.to_raw_bytes());
+    bytes.extend(s_g2.to_raw_bytes());
+
+    ParamsKZG::read(&mut bytes.as_slice()).expect("params assembled from valid points")
+}
+
+/// Directory of KZG params, one file per `k`.
+#[derive(Clone, Debug)]
+pub struct ParamsStore {
+    dir: PathBuf,
+}
+
+impl Default for ParamsStore {
+    /// Uses `$HALO2_PARAMS_DIR`, or `target/halo2-params` when it is not set.
+    fn default() -> Self {
+        let dir = env::var_os(PARAMS_DIR_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("target").join("halo2-params"));
+        Self::new(dir)
+    }
+}
+
+impl ParamsStore {
+    /// Creates a store rooted at `dir`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Directory the params are stored in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Path of the params for `k`.
+    pub fn path(&self, k: u32) -> PathBuf {
+        self.dir.join(format!("kzg_bn254_{k}.srs"))
+    }
+
+    /// Returns the params for `k`, downsizing or generating them on a miss.
+    /// Whatever is produced is written back to the store.
+    pub fn get(&self, k: u32) -> io::Result<ParamsKZG<Bn256>> {
+        let path = self.path(k);
+        if path.exists() {
+            return read_srs(path);
+        }
+
+        let params = match (k + 1..=MAX_K).map(|k| self.path(k)).find(|path| path.exists()) {
+            Some(larger) => {
+                let mut params = read_srs(larger)?;
+                params.downsize(k);
+                params
+            }
+            None => ParamsKZG::<Bn256>::setup(k, OsRng),
+        };
+        write_srs(&params, path)?;
+
+        Ok(params)
+    }
+
+    /// Imports params from a `.ptau` or halo2 `.srs` file, storing one file
+    /// per `k` in `ks`.
+    pub fn import(&self, path: impl AsRef<Path>, ks: impl IntoIterator<Item = u32>) -> io::Result<()> {
+        let path = path.as_ref();
+        let is_ptau = path.extension().map_or(false, |ext| ext == "ptau");
+        let srs = if is_ptau { None } else { Some(read_srs(path)?) };
+
+        for k in ks {
+            let params = match &srs {
+                Some(srs) => {
+                    if k > srs.k() {
+                        return Err(invalid_data(format!("srs file only supports k <= {}", srs.k())));
+                    }
+                    let mut params = srs.clone();
+                    params.downsize(k);
+                    params
+                }
+                None => read_ptau(path, k)?,
+            };
+            write_srs(&params, self.path(k))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, io::Write};
+
+    use halo2_proofs::{
+        halo2curves::{
+            bn256::{Bn256, G2Affine},
+            group::prime::PrimeCurveAffine,
+            serde::SerdeObject,
+        },
+        poly::{
+            commitment::{Params, ParamsProver},
+            kzg::commitment::ParamsKZG,
+        },
+    };
+    use rand::rngs::OsRng;
+
+    use super::{read_ptau, ParamsStore};
+
+    fn test_dir(name: &str) -> std::path::PathBuf {
+        env::temp_dir().join(format!("halo2-params-{name}-{}", std::process::id()))
+    }
+
+    fn to_bytes(params: &ParamsKZG<Bn256>) -> Vec<u8> {
+        let mut bytes = vec![];
+        params.write(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn store_downsizes_larger_params() {
+        let store = ParamsStore::new(test_dir("store"));
+
+        let large = store.get(5).unwrap();
+        assert!(store.path(5).exists());
+
+        let small = store.get(3).unwrap();
+        assert_eq!(small.k(), 3);
+        assert_eq!(small.s_g2(), large.s_g2());
+        assert_eq!(small.get_g(), &large.get_g()[..8]);
+
+        // The downsized params are now cached as well.
+        assert!(store.path(3).exists());
+        assert_eq!(to_bytes(&store.get(3).unwrap()), to_bytes(&small));
+    }
+
+    #[test]
+    fn ptau_matches_setup() {
+        let k = 3;
+        let params = ParamsKZG::<Bn256>::setup(k, OsRng);
+
+        // Minimal ptau file: header, 2^k tau powers in G1 and 2 in G2.
+        let mut header = 32u32.to_le_bytes().to_vec();
+        header.extend([0u8; 32]);
+        header.extend(k.to_le_bytes());
+        header.extend(k.to_le_bytes());
+        let tau_g1: Vec<u8> = params.get_g().iter().flat_map(|p| p.to_raw_bytes()).collect();
+        let tau_g2: Vec<u8> = [G2Affine::generator(), params.s_g2()]
+            .iter()
+            .flat_map(|p| p.to_raw_bytes())
+            .collect();
+
+        let mut bytes = b"ptau".to_vec();
+        bytes.extend(1u32.to_le_bytes());
+        bytes.extend(3u32.to_le_bytes());
+        for (id, data) in [(1u32, header), (2, tau_g1), (3, tau_g2)] {
+            bytes.extend(id.to_le_bytes());
+            bytes.extend((data.len() as u64).to_le_bytes());
+            bytes.extend(data);
+        }
+
+        let path = test_dir("ptau").join("test.ptau");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::File::create(&path).unwrap().write_all(&bytes).unwrap();
+
+        let imported = read_ptau(&path, k).unwrap();
+        assert_eq!(to_bytes(&imported), to_bytes(&params));
+    }
+}
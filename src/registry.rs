@@ -0,0 +1,1145 @@
+//! Registry of the example circuits.
+//!
+//! The CLI, the benchmarks and the dev tools all go through [`iter_circuits`]
+//! rather than naming circuits themselves, so registering a new example here
+//! is enough to make it available everywhere. Circuits are type-erased behind
+//! [`ExampleCircuit`] and fixed to the bn256 scalar field. Every module of
+//! [`crate::circuits::examples`] has an entry, except the aggregation circuit,
+//! whose keys depend on those of the proofs it aggregates, and the stacked
+//! floor planner, which is not a circuit.
+//!
+//! Inputs are a JSON object of field elements, integers, booleans and arrays
+//! of them. Field elements are given as a number, a decimal string or a
+//! `0x`-prefixed big-endian hex string, and integers as a number or, beyond
+//! `u64`, a decimal string. Instances are a JSON array with one array of field
+//! elements per instance column.
+//!
+//! Circuits taking `Circuit::Params` are registered with their default
+//! sizes, and [`CircuitEntry::with_params`] sizes them from a JSON object of
+//! integers, such as `{ "range": 32 }` or `{ "depth": 10 }`.
+
+use halo2_proofs::{
+    dev::MockProver,
+    halo2curves::{
+        bn256::{Bn256, Fr, G1Affine, G1},
+        group::{
+            ff::{Field, PrimeField},
+            Curve, Group,
+        },
+    },
+    plonk::{self, Circuit, ProvingKey, VerifyingKey},
+    poly::kzg::commitment::ParamsKZG,
+};
+use serde_json::{json, Map, Value as Json};
+
+use crate::{
+    circuits::{
+        examples::{
+            aes::AesCircuit,
+            airdrop::{AirdropCircuit, Claimer},
+            auction::{AuctionCircuit, Bid},
+            battleship::{BattleshipCircuit, CELLS},
+            bytecode::{self, BytecodeCircuit, Instruction},
+            chacha20::ChaCha20Circuit,
+            coloring::ColoringCircuit,
+            dfa::{Dfa, DfaCircuit},
+            elgamal::{ElGamalCircuit, LIMBS},
+            evm_add_sub::{self, AddSubCircuit},
+            histogram::HistogramCircuit,
+            instance_commitment::InstanceCommitmentCircuit,
+            is_zero::IsZeroCircuit,
+            median::MedianCircuit,
+            memory::{MemoryCircuit, MemoryOp},
+            mmr::{peak_depths, Mmr, MmrCircuit, MmrParams},
+            poseidon::PoseidonCircuit,
+            range_check::{RangeCheckCircuit, RangeCheckParams},
+            reachability::{CommittedGraph, ReachabilityCircuit},
+            shuffle::ShuffleCircuit,
+            simple::SimpleCircuit,
+            statistics::StatisticsCircuit,
+            super_circuit::SuperCircuit,
+            threshold::ThresholdCircuit,
+            tornado::{DepositCircuit, Note, WithdrawCircuit},
+            tuple_lookup::{ArithOp, ArithRow, TupleLookupCircuit},
+            vdf::VdfCircuit,
+            vrf::{VrfCircuit, VrfKey},
+        },
+        gadgets::{merkle::MerkleParams, poseidon::Spec},
+    },
+    dev::{
+        self,
+        expr::GateDump,
+        planner::PlannerRows,
+        stats::{CircuitStats, RegionStats},
+        witness::WitnessCell,
+    },
+    prover::{self, KeyCache, ProverError},
+};
+
+/// Object-safe view of a circuit together with its instances.
+pub trait ExampleCircuit {
+    /// Public inputs, one `Vec` per instance column.
+    fn instances(&self) -> Vec<Vec<Fr>>;
+
+    /// Runs the mock prover at `k`.
+    fn mock_prover(&self, k: u32) -> Result<MockProver<Fr>, plonk::Error>;
+
+    /// Rows used by the circuit, see [`dev::rows::rows_used`].
+    fn rows_used(&self) -> Result<usize, plonk::Error>;
+
+    /// Smallest `k` with room for `rows` rows, see [`dev::rows::min_k`].
+    fn min_k(&self, rows: usize) -> u32;
+
+    /// Column, gate and size statistics at `k`, named `name`.
+    fn stats(&self, name: &str, k: u32) -> Result<CircuitStats, plonk::Error>;
+
+    /// Rows and cells per region, see [`dev::stats::region_stats`].
+    fn region_stats(&self) -> Result<Vec<RegionStats>, plonk::Error>;
+
+    /// Rows used under each floor planner, see [`dev::planner`].
+    fn planner_rows(&self) -> Result<Vec<PlannerRows>, plonk::Error>;
+
+    /// The gates of the circuit, see [`dev::expr`].
+    fn gates(&self) -> Vec<GateDump>;
+
+    /// Fingerprint of the constraint system, see [`dev::fingerprint`].
+    fn fingerprint(&self) -> String;
+
+    /// The assigned cells, see [`dev::witness`].
+    fn witness(&self) -> Result<Vec<WitnessCell<Fr>>, plonk::Error>;
+
+    /// Renders the layout at `k` to `path`, see [`dev::layout`].
+    #[cfg(feature = "dev-graph")]
+    fn render(&self, path: &std::path::Path, title: &str, k: u32) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Generates keys with [`prover::keygen`], bypassing any cache.
+    fn keygen(&self, params: &ParamsKZG<Bn256>) -> Result<ProvingKey<G1Affine>, plonk::Error>;
+
+    /// Generates the verifying key only, bypassing any cache.
+    fn keygen_vk(&self, params: &ParamsKZG<Bn256>) -> Result<VerifyingKey<G1Affine>, plonk::Error>;
+
+    /// Proving key from `cache`, generated on a miss.
+    fn pk(&self, cache: &KeyCache, name: &str, params: &ParamsKZG<Bn256>) -> Result<ProvingKey<G1Affine>, ProverError>;
+
+    /// Verifying key from `cache`, generated on a miss.
+    fn vk(&self, cache: &KeyCache, name: &str, params: &ParamsKZG<Bn256>)
+        -> Result<VerifyingKey<G1Affine>, ProverError>;
+
+    /// Creates a proof with [`prover::prove`].
+    fn prove(&self, params: &ParamsKZG<Bn256>, pk: &ProvingKey<G1Affine>) -> Result<Vec<u8>, plonk::Error>;
+}
+
+struct Registered<C> {
+    circuit: C,
+    instances: Vec<Vec<Fr>>,
+}
+
+impl<C: Circuit<Fr> + Clone> ExampleCircuit for Registered<C> {
+    fn instances(&self) -> Vec<Vec<Fr>> {
+        self.instances.clone()
+    }
+
+    fn mock_prover(&self, k: u32) -> Result<MockProver<Fr>, plonk::Error> {
+        MockProver::run(k, &self.circuit, self.instances.clone())
+    }
+
+    fn rows_used(&self) -> Result<usize, plonk::Error> {
+        dev::rows::rows_used(&self.circuit)
+    }
+
+    fn min_k(&self, rows: usize) -> u32 {
+        dev::rows::min_k(&self.circuit, rows)
+    }
+
+    fn stats(&self, name: &str, k: u32) -> Result<CircuitStats, plonk::Error> {
+        CircuitStats::measure(name, k, &self.circuit)
+    }
+
+    fn region_stats(&self) -> Result<Vec<RegionStats>, plonk::Error> {
+        dev::stats::region_stats(&self.circuit)
+    }
+
+    fn planner_rows(&self) -> Result<Vec<PlannerRows>, plonk::Error> {
+        dev::planner::planner_rows(&self.circuit)
+    }
+
+    fn gates(&self) -> Vec<GateDump> {
+        dev::expr::dump_gates(&self.circuit)
+    }
+
+    fn fingerprint(&self) -> String {
+        dev::fingerprint::fingerprint(&self.circuit)
+    }
+
+    fn witness(&self) -> Result<Vec<WitnessCell<Fr>>, plonk::Error> {
+        dev::witness::dump_witness(&self.circuit)
+    }
+
+    #[cfg(feature = "dev-graph")]
+    fn render(&self, path: &std::path::Path, title: &str, k: u32) -> Result<(), Box<dyn std::error::Error>> {
+        dev::layout::render_layout_to(path, title, k, &self.circuit)
+    }
+
+    fn keygen(&self, params: &ParamsKZG<Bn256>) -> Result<ProvingKey<G1Affine>, plonk::Error> {
+        prover::keygen(params, &self.circuit.without_witnesses())
+    }
+
+    fn keygen_vk(&self, params: &ParamsKZG<Bn256>) -> Result<VerifyingKey<G1Affine>, plonk::Error> {
+        plonk::keygen_vk(params, &self.circuit.without_witnesses())
+    }
+
+    fn pk(&self, cache: &KeyCache, name: &str, params: &ParamsKZG<Bn256>) -> Result<ProvingKey<G1Affine>, ProverError> {
+        cache.pk(name, params, &self.circuit.without_witnesses())
+    }
+
+    fn vk(
+        &self,
+        cache: &KeyCache,
+        name: &str,
+        params: &ParamsKZG<Bn256>,
+    ) -> Result<VerifyingKey<G1Affine>, ProverError> {
+        cache.vk(name, params, &self.circuit.without_witnesses())
+    }
+
+    fn prove(&self, params: &ParamsKZG<Bn256>, pk: &ProvingKey<G1Affine>) -> Result<Vec<u8>, plonk::Error> {
+        prover::prove(params, pk, self.circuit.clone(), &self.instances)
+    }
+}
+
+fn register<C: Circuit<Fr> + Clone + 'static>(circuit: C, instances: Vec<Vec<Fr>>) -> Box<dyn ExampleCircuit> {
+    Box::new(Registered { circuit, instances })
+}
+
+/// A registered example circuit.
+pub struct CircuitEntry {
+    /// Name used on the command line and in cache file names.
+    pub name: &'static str,
+    /// One line description.
+    pub description: &'static str,
+    /// Number of instances of each instance column.
+    pub num_instance: Vec<usize>,
+    /// Valid inputs for [`Self::build`], used by the tests and benchmarks.
+    pub sample_input: fn() -> Json,
+    /// Sizes of the circuit, a JSON object, see [`Self::with_params`].
+    pub params: Json,
+    /// Builds the circuit from JSON inputs.
+    pub build: Box<dyn Fn(&Json) -> Result<Box<dyn ExampleCircuit>, String>>,
+    /// Builds the circuit without witnesses, for key generation.
+    pub without_witnesses: Box<dyn Fn() -> Box<dyn ExampleCircuit>>,
+    /// Builds the entry for other params, for the circuits taking some.
+    resize: Option<fn(&Json) -> Result<CircuitEntry, String>>,
+}
+
+impl CircuitEntry {
+    /// Smallest `k` fitting the circuit and its instances, the `k` of its
+    /// keys.
+    pub fn k(&self) -> Result<u32, plonk::Error> {
+        let circuit = (self.without_witnesses)();
+        let instances = self.num_instance.iter().copied().max().unwrap_or(0);
+        Ok(circuit.min_k(circuit.rows_used()?.max(instances)))
+    }
+
+    /// The entry sized by `params`, a JSON object such as `{ "range": 32 }`
+    /// for `range_check` or `{ "depth": 10 }` for the Merkle circuits, with
+    /// the default of each missing size.
+    ///
+    /// Only circuits taking `Circuit::Params` have sizes, which halo2 only
+    /// passes to `configure_with_params` with the `circuit-params` feature.
+    pub fn with_params(self, params: &Json) -> Result<Self, String> {
+        if params.as_object().is_some_and(Map::is_empty) {
+            return Ok(self);
+        }
+        let resize = self.resize.ok_or_else(|| format!("`{}` takes no params", self.name))?;
+        if cfg!(not(feature = "circuit-params")) {
+            return Err(format!("the params of `{}` need the `circuit-params` feature", self.name));
+        }
+        resize(params)
+    }
+
+    /// Name of the keys of the circuit in a [`KeyCache`], its name followed
+    /// by its params.
+    pub fn key_name(&self) -> String {
+        (self.params.as_object().into_iter().flatten())
+            .fold(self.name.to_string(), |name, (key, value)| format!("{name}-{key}{value}"))
+    }
+}
+
+/// The entry of a circuit taking no params, built from JSON inputs by `build`.
+fn entry<C: Circuit<Fr> + Clone + 'static>(
+    name: &'static str,
+    description: &'static str,
+    num_instance: Vec<usize>,
+    sample_input: fn() -> Json,
+    build: fn(&Json) -> Result<C, String>,
+    instances: fn(&C) -> Vec<Vec<Fr>>,
+    without_witnesses: fn() -> C,
+) -> CircuitEntry {
+    CircuitEntry {
+        name,
+        description,
+        num_instance,
+        sample_input,
+        params: json!({}),
+        build: Box::new(move |input: &Json| {
+            let circuit = build(input)?;
+            let instances = instances(&circuit);
+            Ok(register(circuit, instances))
+        }),
+        without_witnesses: Box::new(move || register(without_witnesses(), vec![])),
+        resize: None,
+    }
+}
+
+/// The `range_check` entry for `sizes`, given as the JSON `params`.
+fn range_check_entry(params: Json, sizes: RangeCheckParams) -> CircuitEntry {
+    CircuitEntry {
+        name: "range_check",
+        description: "private value in [0, range) with a degree range polynomial, range 16 by default",
+        num_instance: vec![],
+        sample_input: || json!({ "value": 15 }),
+        params,
+        build: Box::new(move |input: &Json| {
+            let circuit = RangeCheckCircuit::with_params(field(input, "value")?, sizes);
+            let instances = circuit.instances();
+            Ok(register(circuit, instances))
+        }),
+        without_witnesses: Box::new(move || {
+            let circuit = RangeCheckCircuit::with_params(Fr::ZERO, sizes);
+            register(circuit.without_witnesses(), vec![])
+        }),
+        resize: Some(|params| {
+            check_params(params, &["range"])?;
+            let sizes = RangeCheckParams {
+                range: size(params, "range", RangeCheckParams::default().range)?,
+            };
+            Ok(range_check_entry(params.clone(), sizes))
+        }),
+    }
+}
+
+/// Reads the [`MerkleParams`] of the Merkle circuits from JSON params.
+fn merkle_params(params: &Json) -> Result<MerkleParams, String> {
+    check_params(params, &["depth"])?;
+    Ok(MerkleParams {
+        depth: size(params, "depth", MerkleParams::default().depth)?,
+    })
+}
+
+/// The `airdrop` entry for `sizes`, given as the JSON `params`. Its sample
+/// input has a path of the default depth.
+fn airdrop_entry(params: Json, sizes: MerkleParams) -> CircuitEntry {
+    CircuitEntry {
+        name: "airdrop",
+        description: "claim of a private leaf of a Merkle tree of amounts, with its nullifier, depth 20 by default",
+        num_instance: AirdropCircuit::<Fr>::instance_layout().num_instance(),
+        sample_input: || json!({ "secret": 1003, "amount": 400, "recipient": "0xcafe", "path": sample_path() }),
+        params,
+        build: Box::new(move |input: &Json| {
+            let claimer = Claimer {
+                secret: field(input, "secret")?,
+                amount: int(input, "amount")?,
+            };
+            let (path, root) = merkle_path(input, claimer.leaf(), sizes)?;
+            let circuit = AirdropCircuit::<Fr>::new(claimer, path, root, field(input, "recipient")?);
+            let instances = circuit.instances();
+            Ok(register(circuit, instances))
+        }),
+        without_witnesses: Box::new(move || register(AirdropCircuit::<Fr>::with_params(sizes), vec![])),
+        resize: Some(|params| Ok(airdrop_entry(params.clone(), merkle_params(params)?))),
+    }
+}
+
+/// The `tornado_withdraw` entry for `sizes`, given as the JSON `params`. Its
+/// sample input has a path of the default depth.
+fn withdraw_entry(params: Json, sizes: MerkleParams) -> CircuitEntry {
+    CircuitEntry {
+        name: "tornado_withdraw",
+        description: "withdrawal of a private note of a Merkle tree of deposits, depth 20 by default",
+        num_instance: WithdrawCircuit::<Fr>::instance_layout().num_instance(),
+        sample_input: || json!({ "nullifier": 100, "secret": 200, "recipient": "0xcafe", "path": sample_path() }),
+        params,
+        build: Box::new(move |input: &Json| {
+            let note = Note {
+                nullifier: field(input, "nullifier")?,
+                secret: field(input, "secret")?,
+            };
+            let (path, root) = merkle_path(input, note.commitment(), sizes)?;
+            let circuit = WithdrawCircuit::<Fr>::new(note, path, root, field(input, "recipient")?);
+            let instances = circuit.instances();
+            Ok(register(circuit, instances))
+        }),
+        without_witnesses: Box::new(move || register(WithdrawCircuit::<Fr>::with_params(sizes), vec![])),
+        resize: Some(|params| Ok(withdraw_entry(params.clone(), merkle_params(params)?))),
+    }
+}
+
+/// The `mmr` entry for `sizes`, given as the JSON `params`. Its sample input
+/// has the default size.
+fn mmr_entry(params: Json, sizes: MmrParams) -> CircuitEntry {
+    CircuitEntry {
+        name: "mmr",
+        description: "membership of a leaf of a Merkle mountain range, of 11 leaves and in the first peak by default",
+        num_instance: MmrCircuit::<Fr>::instance_layout().num_instance(),
+        sample_input: || json!({ "leaves": (1..=MmrParams::default().size).collect::<Vec<_>>(), "index": 5 }),
+        params,
+        build: Box::new(move |input: &Json| {
+            let leaves = list(input, "leaves", parse_field)?;
+            if leaves.len() != sizes.size {
+                return Err(format!("expected {} leaves, got {}", sizes.size, leaves.len()));
+            }
+            let index: usize = int(input, "index")?;
+            let mmr = Mmr::new(leaves.clone());
+            // The peak of the leaf sets the number of levels hashed.
+            if index >= sizes.size || mmr.proof(index).peak != sizes.peak {
+                return Err(format!("leaf {index} is not in peak {}", sizes.peak));
+            }
+            let circuit = MmrCircuit::<Fr>::new(sizes.size, leaves[index], mmr.proof(index));
+            let instances = circuit.instances();
+            Ok(register(circuit, instances))
+        }),
+        without_witnesses: Box::new(move || register(MmrCircuit::<Fr>::with_params(sizes), vec![])),
+        resize: Some(|params| {
+            check_params(params, &["size", "peak"])?;
+            let default = MmrParams::default();
+            let sizes = MmrParams {
+                size: size(params, "size", default.size)?,
+                peak: index(params, "peak", default.peak)?,
+            };
+            let peaks = peak_depths(sizes.size).len();
+            if sizes.peak >= peaks {
+                return Err(format!("param `peak` must be less than the {peaks} peaks of {} leaves", sizes.size));
+            }
+            Ok(mmr_entry(params.clone(), sizes))
+        }),
+    }
+}
+
+/// Constant `c` of the `simple` circuit, fixed so that its keys can be cached.
+const SIMPLE_CONSTANT: u64 = 3;
+
+/// Number of accesses of the `memory` circuit.
+const MEMORY_OPS: usize = 8;
+
+/// Number of bids of the `auction` circuit.
+const AUCTION_BIDS: usize = 4;
+
+/// Number of shots of the `battleship` circuit.
+const BATTLESHIP_SHOTS: usize = 3;
+
+/// Number of steps of the trace of the `bytecode` circuit.
+const BYTECODE_ROWS: usize = 8;
+
+/// Length of the inputs of the `dfa` circuit.
+const DFA_LEN: usize = 16;
+
+/// Number of values of the `histogram` circuit.
+const HISTOGRAM_VALUES: usize = 8;
+
+/// Number of buckets of the `histogram` circuit.
+const HISTOGRAM_BUCKETS: usize = 4;
+
+/// Number of amounts of the `instance_commitment` circuit.
+const COMMITTED_AMOUNTS: usize = 8;
+
+/// Number of values of the `median` circuit.
+const MEDIAN_VALUES: usize = 7;
+
+/// Longest walk of the `reachability` circuit.
+const REACHABILITY_STEPS: usize = 4;
+
+/// Depth of the tree of edges of the `reachability` circuit.
+const REACHABILITY_DEPTH: usize = 3;
+
+/// Number of cards of the `shuffle` circuit.
+const SHUFFLE_CARDS: usize = 6;
+
+/// Number of values of the `statistics` circuit.
+const STATISTICS_VALUES: usize = 4;
+
+/// Number of values of the `threshold` circuit.
+const THRESHOLD_VALUES: usize = 8;
+
+/// Bits of the operands of the `tuple_lookup` circuit.
+const TUPLE_LOOKUP_BITS: usize = 4;
+
+/// Number of rows of the `tuple_lookup` circuit.
+const TUPLE_LOOKUP_ROWS: usize = 3;
+
+/// Program of the `bytecode` circuit, `acc = (3 + 4) ⋅ 5` jumping over a
+/// `MUL 100`. It is in the circuit's table, so it is fixed like
+/// [`SIMPLE_CONSTANT`].
+fn bytecode_program() -> Vec<Instruction> {
+    vec![
+        Instruction::new(bytecode::Opcode::Push, 3),
+        Instruction::new(bytecode::Opcode::Add, 4),
+        Instruction::new(bytecode::Opcode::Jump, 4),
+        Instruction::new(bytecode::Opcode::Mul, 100),
+        Instruction::new(bytecode::Opcode::Mul, 5),
+        Instruction::new(bytecode::Opcode::Stop, 0),
+    ]
+}
+
+/// Graph of the `coloring` circuit, the Petersen graph: an outer 5-cycle,
+/// spokes, and an inner pentagram.
+fn petersen() -> [(usize, usize); 15] {
+    std::array::from_fn(|k| {
+        let i = k % 5;
+        match k / 5 {
+            0 => (i, (i + 1) % 5),
+            1 => (i, 5 + i),
+            _ => (5 + i, 5 + (i + 2) % 5),
+        }
+    })
+}
+
+/// DFAs of the `dfa` circuit, `[a-z]+@[a-z]+\.com` with id 1 and `[0-9]+`
+/// with id 2.
+fn dfas() -> Vec<Dfa> {
+    let email = Dfa::new(1)
+        .transition(0, b'a'..=b'z', 1)
+        .transition(1, b'a'..=b'z', 1)
+        .transition(1, [b'@'], 2)
+        .transition(2, b'a'..=b'z', 3)
+        .transition(3, b'a'..=b'z', 3)
+        .transition(3, [b'.'], 4)
+        .transition(4, [b'c'], 5)
+        .transition(5, [b'o'], 6)
+        .transition(6, [b'm'], 7)
+        .accept(7);
+    let number = Dfa::new(2)
+        .transition(0, b'0'..=b'9', 1)
+        .transition(1, b'0'..=b'9', 1)
+        .accept(1);
+    vec![email, number]
+}
+
+/// Committed graph of the `reachability` circuit.
+fn reachability_graph() -> CommittedGraph<Fr> {
+    CommittedGraph::new(REACHABILITY_DEPTH, vec![(1, 2), (2, 3), (3, 4), (2, 5), (5, 4), (4, 6), (6, 1)])
+}
+
+/// A sample Merkle path of the default depth, see [`merkle_path`].
+fn sample_path() -> Json {
+    (0..MerkleParams::default().depth as u64).map(|i| json!([i + 1, i % 3 == 0])).collect()
+}
+
+/// Iterates over all the registered circuits.
+pub fn iter_circuits() -> impl Iterator<Item = CircuitEntry> {
+    [
+        entry(
+            "simple",
+            "a^2 * b^2 * c = out with private a, b",
+            vec![1],
+            || json!({ "a": 3, "b": "5" }),
+            |input| Ok(SimpleCircuit::new(field(input, "a")?, field(input, "b")?, Fr::from(SIMPLE_CONSTANT))),
+            SimpleCircuit::instances,
+            || SimpleCircuit::new(Fr::ZERO, Fr::ZERO, Fr::from(SIMPLE_CONSTANT)).without_witnesses(),
+        ),
+        entry(
+            "is_zero",
+            "exposes whether a private value is zero",
+            IsZeroCircuit::<Fr>::instance_layout().num_instance(),
+            || json!({ "value": "0x00" }),
+            |input| Ok(IsZeroCircuit::new(field(input, "value")?)),
+            IsZeroCircuit::instances,
+            IsZeroCircuit::default,
+        ),
+        range_check_entry(json!({}), RangeCheckParams::default()),
+        entry(
+            "memory",
+            "read-after-write consistency of a public trace of memory accesses",
+            MemoryCircuit::<Fr, MEMORY_OPS>::instance_layout().num_instance(),
+            || {
+                json!({
+                    "ops": [
+                        [7, 5, true], [3, 9, true], [7, 5, false], [3, 9, false],
+                        [7, 6, true], [3, 0, true], [7, 6, false], [3, 0, false],
+                    ]
+                })
+            },
+            |input| Ok(MemoryCircuit::new(array(input, "ops", parse_memory_op)?)),
+            MemoryCircuit::instances,
+            MemoryCircuit::<Fr, MEMORY_OPS>::default,
+        ),
+        entry(
+            "poseidon",
+            "Poseidon hash of two private inputs",
+            PoseidonCircuit::<Fr, 2>::instance_layout().num_instance(),
+            || json!({ "inputs": [1, 2] }),
+            |input| Ok(PoseidonCircuit::new(array(input, "inputs", parse_field)?)),
+            PoseidonCircuit::instances,
+            PoseidonCircuit::<Fr, 2>::default,
+        ),
+        entry(
+            "super",
+            "is_zero, range_check and poseidon composed on shared columns",
+            SuperCircuit::<Fr>::instance_layout().num_instance(),
+            || json!({ "value": 0, "small": 15, "inputs": [1, 2] }),
+            |input| {
+                let inputs = array(input, "inputs", parse_field)?;
+                Ok(SuperCircuit::new(field(input, "value")?, field(input, "small")?, inputs))
+            },
+            SuperCircuit::instances,
+            SuperCircuit::default,
+        ),
+        entry(
+            "aes",
+            "AES-128 encryption of a public plaintext under a private key",
+            AesCircuit::instance_layout().num_instance(),
+            || {
+                let plaintext: Vec<_> = (0..16).map(|i| 0x11 * i).collect();
+                json!({ "key": (0..16).collect::<Vec<_>>(), "plaintext": plaintext })
+            },
+            |input| Ok(AesCircuit::new(array(input, "key", parse_int)?, array(input, "plaintext", parse_int)?)),
+            AesCircuit::instances::<Fr>,
+            AesCircuit::default,
+        ),
+        airdrop_entry(json!({}), MerkleParams::default()),
+        entry(
+            "auction",
+            "highest of committed private bids, and its bidder",
+            AuctionCircuit::<Fr, AUCTION_BIDS>::instance_layout().num_instance(),
+            || json!({ "amounts": [300, 500, 200, 500], "salts": [1001, 1002, 1003, 1004] }),
+            |input| {
+                let amounts: [u64; AUCTION_BIDS] = array(input, "amounts", parse_int)?;
+                let salts = array(input, "salts", parse_field)?;
+                Ok(AuctionCircuit::new(std::array::from_fn(|i| Bid {
+                    amount: amounts[i],
+                    salt: salts[i],
+                })))
+            },
+            AuctionCircuit::instances,
+            AuctionCircuit::<Fr, AUCTION_BIDS>::default,
+        ),
+        entry(
+            "battleship",
+            "hits of public shots on a committed private board",
+            BattleshipCircuit::<Fr, BATTLESHIP_SHOTS>::instance_layout().num_instance(),
+            || {
+                let board: Vec<_> = (0..CELLS).map(|i| [0, 1, 2, 11, 15].contains(&i)).collect();
+                json!({ "board": board, "salt": 1234, "shots": [1, 5, 15] })
+            },
+            |input| {
+                let shots: [usize; BATTLESHIP_SHOTS] = array(input, "shots", parse_int)?;
+                if let Some(shot) = shots.iter().find(|shot| **shot >= CELLS) {
+                    return Err(format!("shot {shot} is off the board of {CELLS} cells"));
+                }
+                Ok(BattleshipCircuit::new(array(input, "board", parse_bool)?, field(input, "salt")?, shots))
+            },
+            BattleshipCircuit::instances,
+            BattleshipCircuit::<Fr, BATTLESHIP_SHOTS>::default,
+        ),
+        entry(
+            "bytecode",
+            "result of a fixed program, from its execution trace",
+            BytecodeCircuit::<Fr, BYTECODE_ROWS>::instance_layout().num_instance(),
+            || json!({}),
+            |_| Ok(BytecodeCircuit::new(bytecode_program())),
+            BytecodeCircuit::instances,
+            || BytecodeCircuit::<Fr, BYTECODE_ROWS>::new(bytecode_program()).without_witnesses(),
+        ),
+        entry(
+            "chacha20",
+            "ChaCha20 encryption of a private plaintext under a private key",
+            ChaCha20Circuit::instance_layout().num_instance(),
+            || {
+                json!({
+                    "key": (0..8u32).map(|i| 0x03020100 + 0x04040404 * i).collect::<Vec<_>>(),
+                    "counter": 1,
+                    "nonce": [0x09000000, 0x4a000000, 0],
+                    "plaintext": (0..16u32).map(|i| 0x01010101 * i).collect::<Vec<_>>(),
+                })
+            },
+            |input| {
+                Ok(ChaCha20Circuit::encryption(
+                    array(input, "key", parse_int)?,
+                    int(input, "counter")?,
+                    array(input, "nonce", parse_int)?,
+                    array(input, "plaintext", parse_int)?,
+                ))
+            },
+            ChaCha20Circuit::instances::<Fr>,
+            || ChaCha20Circuit::encryption([0; 8], 0, [0; 3], [0; 16]).without_witnesses(),
+        ),
+        entry(
+            "coloring",
+            "private 3-coloring of the Petersen graph",
+            vec![],
+            || json!({ "colors": [0, 1, 0, 1, 2, 1, 0, 2, 2, 1] }),
+            |input| Ok(ColoringCircuit::new(petersen(), array(input, "colors", parse_int)?)),
+            |_| vec![],
+            || ColoringCircuit::new(petersen(), [0; 10]).without_witnesses(),
+        ),
+        entry(
+            "dfa",
+            "private input accepted by a public choice of fixed DFAs",
+            DfaCircuit::<Fr, DFA_LEN>::instance_layout().num_instance(),
+            || json!({ "id": 1, "input": "alice@halo.com" }),
+            |input| {
+                let string = input.get("input").and_then(Json::as_str).ok_or("missing input string `input`")?;
+                if string.len() > DFA_LEN {
+                    return Err(format!("`input` is longer than {DFA_LEN} bytes"));
+                }
+                Ok(DfaCircuit::new(dfas(), int(input, "id")?, string.as_bytes()))
+            },
+            DfaCircuit::instances,
+            || DfaCircuit::<Fr, DFA_LEN>::new(dfas(), 0, &[]).without_witnesses(),
+        ),
+        entry(
+            "elgamal",
+            "ElGamal encryption of a private plaintext under the public key sk⋅G",
+            vec![3 * 2 * LIMBS],
+            || json!({ "sk": "0x5ec7e7", "m": 42, "r": "0xdecaf" }),
+            |input| {
+                let (m, r) = (field(input, "m")?, field(input, "r")?);
+                if m == Fr::ZERO || r == Fr::ZERO {
+                    return Err("`m` and `r` must not be zero".to_string());
+                }
+                let pk = (G1::generator() * field(input, "sk")?).to_affine();
+                Ok(ElGamalCircuit::new(pk, m, r))
+            },
+            ElGamalCircuit::instances,
+            ElGamalCircuit::default,
+        ),
+        entry(
+            "evm_add_sub",
+            "EVM ADD or SUB of two public 256-bit words, as lo and hi halves",
+            AddSubCircuit::<Fr>::instance_layout().num_instance(),
+            || json!({ "opcode": "add", "a": [5, 7], "b": [3, 2] }),
+            |input| {
+                let opcode = match input.get("opcode").and_then(Json::as_str) {
+                    Some("add") => evm_add_sub::Opcode::Add,
+                    Some("sub") => evm_add_sub::Opcode::Sub,
+                    _ => return Err("`opcode` must be \"add\" or \"sub\"".to_string()),
+                };
+                Ok(AddSubCircuit::new(opcode, array(input, "a", parse_int)?, array(input, "b", parse_int)?))
+            },
+            AddSubCircuit::instances,
+            AddSubCircuit::default,
+        ),
+        entry(
+            "histogram",
+            "histogram of committed private values over public bucket edges",
+            HistogramCircuit::<Fr, HISTOGRAM_VALUES, HISTOGRAM_BUCKETS>::instance_layout().num_instance(),
+            || json!({ "edges": [0, 18, 40, 65], "values": [34, 17, 65, 40, 22, 90, 0, 39], "salt": 5 }),
+            |input| {
+                let edges = array(input, "edges", parse_int)?;
+                Ok(HistogramCircuit::new(edges, array(input, "values", parse_int)?, field(input, "salt")?))
+            },
+            HistogramCircuit::instances,
+            || HistogramCircuit::new([0; HISTOGRAM_BUCKETS], [0; HISTOGRAM_VALUES], Fr::ZERO).without_witnesses(),
+        ),
+        entry(
+            "instance_commitment",
+            "64-bit private amounts exposed as a single commitment",
+            InstanceCommitmentCircuit::<Fr, COMMITTED_AMOUNTS>::instance_layout().num_instance(),
+            || json!({ "amounts": [100, 250, 0, u64::MAX, 42, 7, 1u64 << 40, 3] }),
+            |input| Ok(InstanceCommitmentCircuit::new(array(input, "amounts", parse_int)?)),
+            InstanceCommitmentCircuit::instances,
+            InstanceCommitmentCircuit::<Fr, COMMITTED_AMOUNTS>::default,
+        ),
+        entry(
+            "median",
+            "median of committed private values",
+            MedianCircuit::<Fr, MEDIAN_VALUES>::instance_layout().num_instance(),
+            || json!({ "values": [50, 10, 90, 30, 70, 30, 1u64 << 31], "salt": 77 }),
+            |input| Ok(MedianCircuit::new(array(input, "values", parse_int)?, field(input, "salt")?)),
+            MedianCircuit::instances,
+            MedianCircuit::<Fr, MEDIAN_VALUES>::default,
+        ),
+        mmr_entry(json!({}), MmrParams::default()),
+        entry(
+            "reachability",
+            "private walk between public vertices of a committed graph",
+            ReachabilityCircuit::<Fr, REACHABILITY_STEPS, REACHABILITY_DEPTH>::instance_layout().num_instance(),
+            || json!({ "walk": [1, 2, 5, 4, 6] }),
+            |input| {
+                let walk: Vec<u64> = list(input, "walk", parse_int)?;
+                if walk.is_empty() || walk.len() > REACHABILITY_STEPS + 1 {
+                    return Err(format!("`walk` must have 1 to {} vertices", REACHABILITY_STEPS + 1));
+                }
+                let graph = reachability_graph();
+                if let Some(edge) = walk.windows(2).find(|edge| graph.path(edge[0], edge[1]).is_none()) {
+                    return Err(format!("the graph has no edge from {} to {}", edge[0], edge[1]));
+                }
+                Ok(ReachabilityCircuit::new(&graph, &walk))
+            },
+            ReachabilityCircuit::instances,
+            ReachabilityCircuit::<Fr, REACHABILITY_STEPS, REACHABILITY_DEPTH>::default,
+        ),
+        entry(
+            "shuffle",
+            "deck shuffled from a committed private seed",
+            ShuffleCircuit::<Fr, SHUFFLE_CARDS>::instance_layout().num_instance(),
+            || json!({ "seed": "0x5eed", "salt": 1 }),
+            |input| Ok(ShuffleCircuit::new(field(input, "seed")?, field(input, "salt")?)),
+            ShuffleCircuit::instances,
+            ShuffleCircuit::<Fr, SHUFFLE_CARDS>::default,
+        ),
+        entry(
+            "statistics",
+            "rounded mean and variance of private values",
+            StatisticsCircuit::<Fr, STATISTICS_VALUES>::instance_layout().num_instance(),
+            || json!({ "values": [10, 20, 30, 45] }),
+            |input| Ok(StatisticsCircuit::new(array(input, "values", parse_int)?)),
+            StatisticsCircuit::instances,
+            StatisticsCircuit::<Fr, STATISTICS_VALUES>::default,
+        ),
+        entry(
+            "threshold",
+            "number of committed private values above a public threshold",
+            ThresholdCircuit::<Fr, THRESHOLD_VALUES>::instance_layout().num_instance(),
+            || json!({ "values": [120, 5, 100, 101, u32::MAX, 0, 100, 99], "salt": 9, "threshold": 100 }),
+            |input| {
+                let values = array(input, "values", parse_int)?;
+                Ok(ThresholdCircuit::new(values, field(input, "salt")?, int(input, "threshold")?))
+            },
+            ThresholdCircuit::instances,
+            ThresholdCircuit::<Fr, THRESHOLD_VALUES>::default,
+        ),
+        entry(
+            "tornado_deposit",
+            "commitment to a private Tornado Cash note",
+            DepositCircuit::<Fr>::instance_layout().num_instance(),
+            || json!({ "nullifier": 100, "secret": 200 }),
+            |input| {
+                Ok(DepositCircuit::new(Note {
+                    nullifier: field(input, "nullifier")?,
+                    secret: field(input, "secret")?,
+                }))
+            },
+            DepositCircuit::instances,
+            DepositCircuit::<Fr>::default,
+        ),
+        withdraw_entry(json!({}), MerkleParams::default()),
+        entry(
+            "tuple_lookup",
+            "small additions and multiplications looked up in a table of tuples",
+            TupleLookupCircuit::<Fr, TUPLE_LOOKUP_BITS, TUPLE_LOOKUP_ROWS>::instance_layout().num_instance(),
+            || json!({ "rows": [["mul", 3, 5], ["add", 15, 15], ["mul", 15, 15]] }),
+            |input| Ok(TupleLookupCircuit::new(array(input, "rows", parse_arith_row)?)),
+            TupleLookupCircuit::instances,
+            TupleLookupCircuit::<Fr, TUPLE_LOOKUP_BITS, TUPLE_LOOKUP_ROWS>::default,
+        ),
+        entry(
+            "vdf",
+            "Wesolowski proof of t squarings of a public x",
+            VdfCircuit::<Fr>::instance_layout().num_instance(),
+            || json!({ "x": 3, "t": 1000 }),
+            |input| Ok(VdfCircuit::new(field(input, "x")?, int(input, "t")?)),
+            VdfCircuit::instances,
+            VdfCircuit::default,
+        ),
+        entry(
+            "vrf",
+            "output of a VRF on a public input under a committed private key",
+            VrfCircuit::<Fr>::instance_layout().num_instance(),
+            || json!({ "sk": 31337, "input": 7 }),
+            |input| Ok(VrfCircuit::new(VrfKey { sk: field(input, "sk")? }, field(input, "input")?)),
+            VrfCircuit::instances,
+            VrfCircuit::default,
+        ),
+    ]
+    .into_iter()
+}
+
+/// Looks up a registered circuit by name.
+pub fn find_circuit(name: &str) -> Option<CircuitEntry> {
+    iter_circuits().find(|entry| entry.name == name)
+}
+
+/// Checks that JSON params are an object of the sizes `keys`.
+fn check_params(params: &Json, keys: &[&str]) -> Result<(), String> {
+    let params = params.as_object().ok_or_else(|| format!("params must be an object, got {params}"))?;
+    match params.keys().find(|key| !keys.contains(&key.as_str())) {
+        Some(key) => Err(format!("unknown param `{key}`, expected one of {keys:?}")),
+        None => Ok(()),
+    }
+}
+
+/// Reads the size `key` of JSON params, `default` when missing.
+fn size(params: &Json, key: &str, default: usize) -> Result<usize, String> {
+    match params.get(key) {
+        Some(value) => (value.as_u64().filter(|&size| size > 0))
+            .map(|size| size as usize)
+            .ok_or_else(|| format!("param `{key}` must be a positive integer, got {value}")),
+        None => Ok(default),
+    }
+}
+
+/// Reads the index `key` of JSON params, `default` when missing.
+fn index(params: &Json, key: &str, default: usize) -> Result<usize, String> {
+    match params.get(key) {
+        Some(value) => (value.as_u64().map(|index| index as usize))
+            .ok_or_else(|| format!("param `{key}` must be a non-negative integer, got {value}")),
+        None => Ok(default),
+    }
+}
+
+/// Reads the field element `key` of a JSON input object.
+pub fn field(input: &Json, key: &str) -> Result<Fr, String> {
+    input
+        .get(key)
+        .ok_or_else(|| format!("missing input `{key}`"))
+        .and_then(parse_field)
+}
+
+/// Reads the integer `key` of a JSON input object, see [`parse_int`].
+fn int<T: TryFrom<u128>>(input: &Json, key: &str) -> Result<T, String> {
+    input
+        .get(key)
+        .ok_or_else(|| format!("missing input `{key}`"))
+        .and_then(parse_int)
+}
+
+/// Reads the array `key` of a JSON input object, parsing each element with
+/// `parse`.
+fn list<T>(input: &Json, key: &str, parse: impl Fn(&Json) -> Result<T, String>) -> Result<Vec<T>, String> {
+    let values = input
+        .get(key)
+        .and_then(Json::as_array)
+        .ok_or_else(|| format!("missing input array `{key}`"))?;
+    values.iter().map(parse).collect()
+}
+
+/// Reads the array of `N` elements `key` of a JSON input object, parsing each
+/// element with `parse`.
+fn array<T, const N: usize>(
+    input: &Json,
+    key: &str,
+    parse: impl Fn(&Json) -> Result<T, String>,
+) -> Result<[T; N], String> {
+    let values = list(input, key, parse)?;
+    let len = values.len();
+    values.try_into().map_err(|_| format!("expected {N} elements in `{key}`, got {len}"))
+}
+
+/// Reads the Merkle `path` of a JSON input object, `[sibling, is_right]`
+/// arrays from the leaf up, and returns it with the root it leads to from
+/// `leaf`.
+///
+/// The path must have the depth of `sizes`, that of the keys.
+fn merkle_path(input: &Json, leaf: Fr, sizes: MerkleParams) -> Result<(Vec<(Fr, bool)>, Fr), String> {
+    let path = list(input, "path", |step| match step.as_array().map(Vec::as_slice) {
+        Some([sibling, Json::Bool(is_right)]) => Ok((parse_field(sibling)?, *is_right)),
+        _ => Err(format!("expected [sibling, is_right], got {step}")),
+    })?;
+    let depth = sizes.depth;
+    if path.len() != depth {
+        return Err(format!("expected a path of {depth} levels, got {}", path.len()));
+    }
+
+    let spec = Spec::new();
+    let root = (path.iter()).fold(leaf, |node, &(sibling, is_right)| {
+        if is_right {
+            spec.hash(&[sibling, node])
+        } else {
+            spec.hash(&[node, sibling])
+        }
+    });
+    Ok((path, root))
+}
+
+/// Parses an access of the `memory` circuit, an `[addr, value, is_write]`
+/// array.
+fn parse_memory_op(op: &Json) -> Result<MemoryOp<Fr>, String> {
+    match op.as_array().map(Vec::as_slice) {
+        Some([addr, value, Json::Bool(is_write)]) => Ok(MemoryOp {
+            addr: parse_field(addr)?,
+            value: parse_field(value)?,
+            is_write: *is_write,
+        }),
+        _ => Err(format!("expected [addr, value, is_write], got {op}")),
+    }
+}
+
+/// Parses a row of the `tuple_lookup` circuit, an `["add" | "mul", a, b]`
+/// array.
+fn parse_arith_row(row: &Json) -> Result<ArithRow, String> {
+    let (op, a, b) = match row.as_array().map(Vec::as_slice) {
+        Some([Json::String(op), a, b]) if op == "add" => (ArithOp::Add, a, b),
+        Some([Json::String(op), a, b]) if op == "mul" => (ArithOp::Mul, a, b),
+        _ => return Err(format!("expected [\"add\" | \"mul\", a, b], got {row}")),
+    };
+    Ok(ArithRow::new(op, parse_int(a)?, parse_int(b)?))
+}
+
+/// Parses an integer of type `T` from a JSON number or decimal string, the
+/// latter for integers beyond `u64`.
+fn parse_int<T: TryFrom<u128>>(value: &Json) -> Result<T, String> {
+    let int = match value {
+        Json::Number(n) => n.as_u64().map(u128::from),
+        Json::String(s) => s.parse().ok(),
+        _ => None,
+    };
+    (int.and_then(|int| T::try_from(int).ok()))
+        .ok_or_else(|| format!("{value} is not a {}", std::any::type_name::<T>()))
+}
+
+/// Parses a JSON boolean.
+fn parse_bool(value: &Json) -> Result<bool, String> {
+    value.as_bool().ok_or_else(|| format!("expected a boolean, got {value}"))
+}
+
+/// Parses a field element from a JSON number, decimal string or hex string.
+pub fn parse_field(value: &Json) -> Result<Fr, String> {
+    match value {
+        Json::Number(n) => n.as_u64().map(Fr::from).ok_or_else(|| format!("{n} is not a u64")),
+        Json::String(s) => match s.strip_prefix("0x") {
+            Some(hex) => {
+                let mut repr = [0u8; 32];
+                let bytes = hex::decode(format!("{hex:0>64}")).map_err(|err| err.to_string())?;
+                if bytes.len() != 32 {
+                    return Err(format!("{s} does not fit in 32 bytes"));
+                }
+                repr.copy_from_slice(&bytes);
+                repr.reverse();
+                Option::from(Fr::from_repr(repr)).ok_or_else(|| format!("{s} is not a field element"))
+            }
+            None => Fr::from_str_vartime(s).ok_or_else(|| format!("{s} is not a field element")),
+        },
+        _ => Err(format!("expected a number or a string, got {value}")),
+    }
+}
+
+/// Encodes instances as JSON hex strings.
+pub fn instances_to_json(instances: &[Vec<Fr>]) -> Json {
+    instances
+        .iter()
+        .map(|column| {
+            column
+                .iter()
+                .map(|value| {
+                    let mut repr = value.to_repr();
+                    repr.reverse();
+                    Json::String(format!("0x{}", hex::encode(repr)))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Decodes instances written by [`instances_to_json`].
+pub fn instances_from_json(json: &Json) -> Result<Vec<Vec<Fr>>, String> {
+    let columns = json.as_array().ok_or("instances must be an array of arrays")?;
+    columns
+        .iter()
+        .map(|column| {
+            column
+                .as_array()
+                .ok_or("instances must be an array of arrays")?
+                .iter()
+                .map(parse_field)
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use serde_json::json;
+
+    use super::{find_circuit, instances_from_json, instances_to_json, iter_circuits, parse_field};
+    use crate::circuits::examples::range_check::RangeCheckParams;
+
+    #[test]
+    fn registered_circuits_are_satisfied() {
+        for entry in iter_circuits() {
+            let circuit = (entry.build)(&(entry.sample_input)()).unwrap();
+            let instances = circuit.instances();
+            assert_eq!(instances.iter().map(Vec::len).collect::<Vec<_>>(), entry.num_instance);
+            // The witness doesn't change the shape of the circuit.
+            let rows = (entry.without_witnesses)().rows_used().unwrap();
+            assert_eq!(circuit.rows_used().unwrap(), rows, "{}", entry.name);
+            circuit.mock_prover(entry.k().unwrap()).unwrap().assert_satisfied();
+        }
+        assert!(find_circuit("simple").is_some());
+        assert!(find_circuit("missing").is_none());
+    }
+
+    #[test]
+    fn examples_are_registered() {
+        // See the module documentation.
+        let unregistered = ["aggregation", "stacked_planner"];
+        let modules = (include_str!("circuits/examples/mod.rs").lines())
+            .filter_map(|line| line.strip_prefix("pub mod ")?.strip_suffix(';'))
+            .filter(|module| !unregistered.contains(module));
+        for module in modules {
+            // Entries are named after their module, such as `tornado_deposit`,
+            // or a prefix of it, such as `super`.
+            assert!(
+                iter_circuits().any(|entry| entry.name.starts_with(module) || module.starts_with(entry.name)),
+                "the example `{module}` is not registered"
+            );
+        }
+    }
+
+    #[test]
+    fn params() {
+        let entry = find_circuit("range_check").unwrap();
+        assert_eq!(entry.key_name(), "range_check");
+        let rows = (entry.without_witnesses)().rows_used().unwrap();
+
+        let with_params = |params| find_circuit("range_check").unwrap().with_params(&params);
+        assert!(with_params(json!({ "range": 0 })).is_err());
+        assert!(with_params(json!({ "width": 8 })).is_err());
+        assert!(find_circuit("simple").unwrap().with_params(&json!({ "range": 8 })).is_err());
+
+        let default = RangeCheckParams::default().range;
+        match with_params(json!({ "range": default * 2 })) {
+            Ok(entry) => {
+                assert!(cfg!(feature = "circuit-params"));
+                assert_eq!(entry.key_name(), format!("range_check-range{}", default * 2));
+                assert_eq!((entry.without_witnesses)().rows_used().unwrap(), rows);
+                assert!((entry.without_witnesses)().gates()[0].constraints[0].degree > default * 2);
+
+                let circuit = (entry.build)(&json!({ "value": default })).unwrap();
+                circuit.mock_prover(entry.k().unwrap()).unwrap().assert_satisfied();
+            }
+            Err(_) => assert!(cfg!(not(feature = "circuit-params"))),
+        }
+    }
+
+    #[test]
+    fn merkle_params() {
+        let with_params = |name, params| find_circuit(name).unwrap().with_params(&params);
+        assert!(with_params("airdrop", json!({ "depth": 0 })).is_err());
+        assert!(with_params("mmr", json!({ "size": 11, "peak": 3 })).is_err());
+
+        // The default depth is that of the keys, so other paths are rejected.
+        let input = json!({ "nullifier": 1, "secret": 2, "recipient": 3, "path": [[4, false], [5, true], [6, false]] });
+        assert!((find_circuit("tornado_withdraw").unwrap().build)(&input).is_err());
+        match with_params("tornado_withdraw", json!({ "depth": 3 })) {
+            Ok(entry) => {
+                assert!(cfg!(feature = "circuit-params"));
+                assert_eq!(entry.key_name(), "tornado_withdraw-depth3");
+                let circuit = (entry.build)(&input).unwrap();
+                circuit.mock_prover(entry.k().unwrap()).unwrap().assert_satisfied();
+            }
+            Err(_) => assert!(cfg!(not(feature = "circuit-params"))),
+        }
+
+        // Leaves 4 and 5 of an MMR of 6 leaves are in its second peak.
+        match with_params("mmr", json!({ "size": 6, "peak": 1 })) {
+            Ok(entry) => {
+                let circuit = (entry.build)(&json!({ "leaves": [1, 2, 3, 4, 5, 6], "index": 5 })).unwrap();
+                circuit.mock_prover(entry.k().unwrap()).unwrap().assert_satisfied();
+                assert!((entry.build)(&json!({ "leaves": [1, 2, 3, 4, 5, 6], "index": 3 })).is_err());
+            }
+            Err(_) => assert!(cfg!(not(feature = "circuit-params"))),
+        }
+    }
+
+    #[test]
+    fn field_encoding() {
+        assert_eq!(parse_field(&json!(42)), Ok(Fr::from(42)));
+        assert_eq!(parse_field(&json!("42")), Ok(Fr::from(42)));
+        assert_eq!(parse_field(&json!("0x2a")), Ok(Fr::from(42)));
+        assert!(parse_field(&json!(-1)).is_err());
+        assert!(parse_field(&json!("0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff")).is_err());
+
+        let instances = vec![vec![Fr::from(1), -Fr::from(1)], vec![]];
+        assert_eq!(instances_from_json(&instances_to_json(&instances)), Ok(instances));
+    }
+}
@@ -0,0 +1,153 @@
+//! Tracing spans around synthesis and proving.
+//!
+//! With the `trace` feature, the prover helpers open [`tracing`] spans named
+//! after their phase: `keygen_vk`, `keygen_pk`, `prove` and `verify`. The
+//! circuit being proven is wrapped in [`Traced`], which adds `configure` and
+//! `synthesize` spans, and a `region` span at the debug level around each
+//! region assigned. Without the feature, the spans compile to nothing.
+//!
+//! [`init_timings`] installs a subscriber printing the time spent in each
+//! span when it closes, which the CLI does for `--timings`. Set `RUST_LOG`
+//! to `debug` to include the regions.
+
+#[cfg(feature = "trace")]
+use std::marker::PhantomData;
+
+#[cfg(feature = "trace")]
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{Cell, Layouter, Region, Table, Value},
+    plonk::{Challenge, Circuit, Column, ConstraintSystem, Error, Instance},
+};
+
+/// Enters a span at `level` with the arguments of [`tracing::span!`] until
+/// the returned guard is dropped, or does nothing without the `trace`
+/// feature.
+#[cfg(feature = "trace")]
+macro_rules! span {
+    ($level:ident, $($args:tt)*) => {
+        tracing::span!(tracing::Level::$level, $($args)*).entered()
+    };
+}
+
+#[cfg(not(feature = "trace"))]
+macro_rules! span {
+    ($($args:tt)*) => {
+        $crate::trace::NoSpan
+    };
+}
+
+pub(crate) use span;
+
+/// The guard of [`span!`] without the `trace` feature.
+#[cfg(not(feature = "trace"))]
+pub(crate) struct NoSpan;
+
+/// A circuit whose `configure` and `synthesize` run in spans, see the
+/// [module documentation](self).
+#[cfg(feature = "trace")]
+#[derive(Clone, Debug)]
+pub struct Traced<C>(pub C);
+
+#[cfg(feature = "trace")]
+impl<F: Field, C: Circuit<F>> Circuit<F> for Traced<C> {
+    type Config = C::Config;
+    type FloorPlanner = C::FloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = C::Params;
+
+    fn without_witnesses(&self) -> Self {
+        Traced(self.0.without_witnesses())
+    }
+
+    #[cfg(feature = "circuit-params")]
+    fn params(&self) -> Self::Params {
+        self.0.params()
+    }
+
+    #[cfg(feature = "circuit-params")]
+    fn configure_with_params(meta: &mut ConstraintSystem<F>, params: Self::Params) -> Self::Config {
+        let _span = span!(INFO, "configure");
+        C::configure_with_params(meta, params)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let _span = span!(INFO, "configure");
+        C::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        let _span = span!(INFO, "synthesize");
+        self.0.synthesize(config, TracedLayouter(layouter, PhantomData))
+    }
+}
+
+/// Forwards to a layouter, opening a span around each region.
+///
+/// It is its own root so that the layouters of namespaces go through it too.
+#[cfg(feature = "trace")]
+struct TracedLayouter<F, L>(L, PhantomData<F>);
+
+#[cfg(feature = "trace")]
+impl<F: Field, L: Layouter<F>> Layouter<F> for TracedLayouter<F, L> {
+    type Root = Self;
+
+    fn assign_region<A, AR, N, NR>(&mut self, name: N, assignment: A) -> Result<AR, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        let name: String = name().into();
+        let _span = span!(DEBUG, "region", name = %name);
+        self.0.assign_region(|| name.clone(), assignment)
+    }
+
+    fn assign_table<A, N, NR>(&mut self, name: N, assignment: A) -> Result<(), Error>
+    where
+        A: FnMut(Table<'_, F>) -> Result<(), Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        let name: String = name().into();
+        let _span = span!(DEBUG, "table", name = %name);
+        self.0.assign_table(|| name.clone(), assignment)
+    }
+
+    fn constrain_instance(&mut self, cell: Cell, column: Column<Instance>, row: usize) -> Result<(), Error> {
+        self.0.constrain_instance(cell, column, row)
+    }
+
+    fn get_challenge(&self, challenge: Challenge) -> Value<F> {
+        self.0.get_challenge(challenge)
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.0.push_namespace(name_fn)
+    }
+
+    fn pop_namespace(&mut self, gadget_name: Option<String>) {
+        self.0.pop_namespace(gadget_name)
+    }
+}
+
+/// Prints the time spent in each span to stderr when it closes, filtered by
+/// `RUST_LOG` and showing the phases by default.
+#[cfg(feature = "trace")]
+pub fn init_timings() {
+    use tracing_subscriber::{fmt::format::FmtSpan, EnvFilter};
+
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(std::io::stderr)
+        .init();
+}
@@ -0,0 +1,124 @@
+//! WebAssembly bindings to the circuits of the registry, built with
+//! `wasm-pack build --features wasm`.
+//!
+//! There is no file system to cache artifacts in, so params and proving keys
+//! are kept in memory for the lifetime of the module. Unless ceremony params
+//! are installed with [`load_params`], params come from a setup with a fixed
+//! seed: proofs then verify across page loads, but are worthless outside of
+//! demos since anyone can derive the toxic waste.
+
+use std::{cell::RefCell, collections::HashMap};
+
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, G1Affine},
+    plonk::ProvingKey,
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+};
+use rand::{rngs::StdRng, SeedableRng};
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    prover::verify,
+    registry::{find_circuit, instances_from_json, instances_to_json, CircuitEntry},
+};
+
+/// Seed of the fallback setup.
+const PARAMS_SEED: u64 = 0;
+
+#[derive(Default)]
+struct Cache {
+    /// Params installed by [`load_params`].
+    srs: Option<ParamsKZG<Bn256>>,
+    params: HashMap<u32, ParamsKZG<Bn256>>,
+    pks: HashMap<&'static str, ProvingKey<G1Affine>>,
+}
+
+impl Cache {
+    fn params(&mut self, k: u32) -> Result<&ParamsKZG<Bn256>, JsError> {
+        if !self.params.contains_key(&k) {
+            let params = match &self.srs {
+                Some(srs) if srs.k() < k => {
+                    return Err(JsError::new(&format!("loaded params only support k <= {}", srs.k())))
+                }
+                Some(srs) => {
+                    let mut params = srs.clone();
+                    params.downsize(k);
+                    params
+                }
+                None => ParamsKZG::setup(k, StdRng::seed_from_u64(PARAMS_SEED)),
+            };
+            self.params.insert(k, params);
+        }
+        Ok(&self.params[&k])
+    }
+
+    fn pk(&mut self, entry: &CircuitEntry) -> Result<(&ParamsKZG<Bn256>, &ProvingKey<G1Affine>), JsError> {
+        let k = entry.k()?;
+        if !self.pks.contains_key(entry.name) {
+            let pk = (entry.without_witnesses)().keygen(self.params(k)?)?;
+            self.pks.insert(entry.name, pk);
+        }
+        Ok((&self.params[&k], &self.pks[entry.name]))
+    }
+}
+
+thread_local! {
+    static CACHE: RefCell<Cache> = RefCell::new(Cache::default());
+}
+
+fn entry(name: &str) -> Result<CircuitEntry, JsError> {
+    find_circuit(name).ok_or_else(|| JsError::new(&format!("unknown circuit `{name}`")))
+}
+
+fn parse_json(json: &str) -> Result<serde_json::Value, JsError> {
+    Ok(serde_json::from_str(json)?)
+}
+
+/// Installs params in halo2's format, e.g. converted from a ceremony
+/// transcript. Drops the cached params and keys.
+#[wasm_bindgen]
+pub fn load_params(bytes: &[u8]) -> Result<(), JsError> {
+    let srs = ParamsKZG::read(&mut &bytes[..])?;
+    CACHE.with(|cache| {
+        *cache.borrow_mut() = Cache {
+            srs: Some(srs),
+            ..Cache::default()
+        }
+    });
+    Ok(())
+}
+
+/// Public inputs of circuit `name` for `inputs_json`, as a JSON array.
+#[wasm_bindgen]
+pub fn circuit_instances(name: &str, inputs_json: &str) -> Result<String, JsError> {
+    let circuit = (entry(name)?.build)(&parse_json(inputs_json)?).map_err(|err| JsError::new(&err))?;
+    Ok(instances_to_json(&circuit.instances()).to_string())
+}
+
+/// Proves circuit `name` for `inputs_json`, returning the proof as a
+/// `Uint8Array`.
+#[wasm_bindgen]
+pub fn prove_circuit(name: &str, inputs_json: &str) -> Result<Vec<u8>, JsError> {
+    let entry = entry(name)?;
+    let circuit = (entry.build)(&parse_json(inputs_json)?).map_err(|err| JsError::new(&err))?;
+
+    CACHE.with(|cache| -> Result<_, JsError> {
+        let mut cache = cache.borrow_mut();
+        let (params, pk) = cache.pk(&entry)?;
+        Ok(circuit.prove(params, pk)?)
+    })
+}
+
+/// Verifies a proof of circuit `name` against `instances_json`, as returned
+/// by [`circuit_instances`].
+#[wasm_bindgen]
+pub fn verify_circuit(name: &str, proof: &[u8], instances_json: &str) -> Result<bool, JsError> {
+    let entry = entry(name)?;
+    let instances = instances_from_json(&parse_json(instances_json)?).map_err(|err| JsError::new(&err))?;
+
+    CACHE.with(|cache| -> Result<_, JsError> {
+        let mut cache = cache.borrow_mut();
+        let (params, pk) = cache.pk(&entry)?;
+        Ok(verify(params, pk.get_vk(), proof, &instances).is_ok())
+    })
+}
@@ -0,0 +1,16 @@
+//! Run with `wasm-pack test --node -- --features wasm`.
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use halo2_circuit_examples::wasm::{circuit_instances, prove_circuit, verify_circuit};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[wasm_bindgen_test]
+fn prove_and_verify_simple() {
+    let inputs = r#"{ "a": 3, "b": 5 }"#;
+    let instances = circuit_instances("simple", inputs).unwrap();
+    let proof = prove_circuit("simple", inputs).unwrap();
+
+    assert!(verify_circuit("simple", &proof, &instances).unwrap());
+    assert!(!verify_circuit("simple", &proof, r#"[["0x01"]]"#).unwrap());
+    assert!(prove_circuit("missing", inputs).is_err());
+}